@@ -0,0 +1,8 @@
+fn main() {
+    // Do not require protoc on the build machine.
+    std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path().unwrap());
+    tonic_build::configure()
+        .build_client(false)
+        .compile(&["proto/ledger.proto"], &["proto"])
+        .expect("Could not compile the protobuf definitions.");
+}
@@ -0,0 +1,240 @@
+use clap::Parser;
+use many_client::ManyClient;
+use many_error::ManyError;
+use many_identity::{Address, AnonymousIdentity};
+use many_modules::{events, ledger};
+use many_types::SortOrder;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+use tracing_subscriber::filter::LevelFilter;
+
+pub mod proto {
+    tonic::include_proto!("many.ledger.v1");
+}
+
+use proto::ledger_server::{Ledger, LedgerServer};
+
+#[derive(clap::ArgEnum, Clone)]
+enum LogStrategy {
+    Terminal,
+    Syslog,
+}
+
+#[derive(Parser)]
+struct Opts {
+    /// Many server URL to connect to. It must implement the ledger attribute.
+    #[clap(default_value = "http://localhost:8000")]
+    server: String,
+
+    /// Port and address to bind the gRPC server to.
+    #[clap(long)]
+    addr: SocketAddr,
+
+    /// The identity of the server (an identity string), or anonymous if you don't know it.
+    server_id: Option<Address>,
+
+    /// Increase output logging verbosity to DEBUG level.
+    #[clap(short, long, parse(from_occurrences))]
+    verbose: i8,
+
+    /// Suppress all output logging. Can be used multiple times to suppress more.
+    #[clap(short, long, parse(from_occurrences))]
+    quiet: i8,
+
+    /// Use given logging strategy
+    #[clap(long, arg_enum, default_value_t = LogStrategy::Terminal)]
+    logmode: LogStrategy,
+}
+
+fn status_of(e: ManyError) -> Status {
+    warn!("{e}");
+    Status::failed_precondition(e.to_string())
+}
+
+fn address(value: &str) -> Result<Address, Status> {
+    Address::from_str(value)
+        .map_err(|_| Status::invalid_argument(format!("Invalid address '{value}'.")))
+}
+
+/// Queries are sent anonymously, only read-only endpoints are exposed.
+struct LedgerGateway {
+    client: ManyClient<AnonymousIdentity>,
+}
+
+impl LedgerGateway {
+    async fn call<R>(&self, method: &str, args: impl minicbor::Encode<()>) -> Result<R, Status>
+    where
+        R: for<'a> minicbor::Decode<'a, ()>,
+    {
+        let payload = self.client.call_(method, args).await.map_err(status_of)?;
+        minicbor::decode(&payload)
+            .map_err(ManyError::deserialization_error)
+            .map_err(status_of)
+    }
+}
+
+#[tonic::async_trait]
+impl Ledger for LedgerGateway {
+    async fn info(
+        &self,
+        _request: Request<proto::InfoRequest>,
+    ) -> Result<Response<proto::InfoReply>, Status> {
+        let info: ledger::InfoReturns = self.call("ledger.info", ()).await?;
+        let tokens = info
+            .symbols
+            .iter()
+            .map(|symbol| {
+                let summary = info.tokens.get(symbol);
+                proto::Token {
+                    symbol: symbol.to_string(),
+                    name: info.local_names.get(symbol).cloned().unwrap_or_default(),
+                    ticker: summary.map(|s| s.ticker.clone()).unwrap_or_default(),
+                    decimals: summary.map_or(0, |s| s.decimals as u32),
+                }
+            })
+            .collect();
+
+        Ok(Response::new(proto::InfoReply {
+            tokens,
+            hash: info.hash.to_vec(),
+        }))
+    }
+
+    async fn balance(
+        &self,
+        request: Request<proto::BalanceRequest>,
+    ) -> Result<Response<proto::BalanceReply>, Status> {
+        let proto::BalanceRequest { account, symbols } = request.into_inner();
+        let symbols = symbols
+            .iter()
+            .map(|symbol| address(symbol))
+            .collect::<Result<Vec<_>, _>>()?;
+        let args = ledger::BalanceArgs {
+            account: Some(address(&account)?),
+            symbols: if symbols.is_empty() {
+                None
+            } else {
+                Some(symbols.into())
+            },
+        };
+
+        let balance: ledger::BalanceReturns = self.call("ledger.balance", args).await?;
+        Ok(Response::new(proto::BalanceReply {
+            balances: balance
+                .balances
+                .into_iter()
+                .map(|(symbol, amount)| proto::Balance {
+                    symbol: symbol.to_string(),
+                    amount: amount.to_string(),
+                })
+                .collect(),
+        }))
+    }
+
+    async fn list(
+        &self,
+        request: Request<proto::ListRequest>,
+    ) -> Result<Response<proto::ListReply>, Status> {
+        let proto::ListRequest {
+            count,
+            ascending,
+            accounts,
+        } = request.into_inner();
+        let accounts = accounts
+            .iter()
+            .map(|account| address(account))
+            .collect::<Result<Vec<_>, _>>()?;
+        let args = events::ListArgs {
+            count: if count == 0 { None } else { Some(count) },
+            order: Some(if ascending {
+                SortOrder::Ascending
+            } else {
+                SortOrder::Descending
+            }),
+            filter: if accounts.is_empty() {
+                None
+            } else {
+                Some(events::EventFilter {
+                    account: Some(accounts.into()),
+                    ..events::EventFilter::default()
+                })
+            },
+        };
+
+        let list: events::ListReturns = self.call("events.list", args).await?;
+        let events = list
+            .events
+            .into_iter()
+            .map(|event| {
+                Ok(proto::Event {
+                    id: event.id.as_ref().to_vec(),
+                    time: event
+                        .time
+                        .as_system_time()?
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map_or(0, |d| d.as_secs()),
+                    kind: format!("{:?}", event.kind()),
+                    content: minicbor::to_vec(&event.content)
+                        .map_err(ManyError::serialization_error)?,
+                })
+            })
+            .collect::<Result<Vec<_>, ManyError>>()
+            .map_err(status_of)?;
+
+        Ok(Response::new(proto::ListReply {
+            nb_events: list.nb_events,
+            events,
+        }))
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let Opts {
+        server,
+        addr,
+        server_id,
+        verbose,
+        quiet,
+        logmode,
+    } = Opts::parse();
+
+    let verbose_level = 2 + verbose - quiet;
+    let log_level = match verbose_level {
+        x if x > 3 => LevelFilter::TRACE,
+        3 => LevelFilter::DEBUG,
+        2 => LevelFilter::INFO,
+        1 => LevelFilter::WARN,
+        0 => LevelFilter::ERROR,
+        x if x < 0 => LevelFilter::OFF,
+        _ => unreachable!(),
+    };
+
+    let subscriber = tracing_subscriber::fmt::Subscriber::builder().with_max_level(log_level);
+
+    match logmode {
+        LogStrategy::Terminal => {
+            let subscriber = subscriber.with_writer(std::io::stderr);
+            subscriber.init();
+        }
+        LogStrategy::Syslog => {
+            let identity = std::ffi::CStr::from_bytes_with_nul(b"grpc_gateway\0").unwrap();
+            let (options, facility) = Default::default();
+            let syslog = syslog_tracing::Syslog::new(identity, options, facility).unwrap();
+
+            let subscriber = subscriber.with_writer(syslog);
+            subscriber.init();
+        }
+    };
+
+    let client = ManyClient::new(server, server_id.unwrap_or_default(), AnonymousIdentity).unwrap();
+
+    info!("Starting gRPC server on addr {addr}");
+    tonic::transport::Server::builder()
+        .add_service(LedgerServer::new(LedgerGateway { client }))
+        .serve(addr)
+        .await
+        .unwrap();
+}
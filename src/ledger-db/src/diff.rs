@@ -0,0 +1,160 @@
+use merk::rocksdb::{IteratorMode, ReadOptions};
+use merk::tree::Tree;
+use merk::Merk;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+use std::cmp::Ordering;
+use std::iter::Peekable;
+
+#[derive(clap::ArgEnum, Clone, Copy, Debug)]
+pub enum DiffFormat {
+    /// One JSON object per line, with keys and values hex encoded.
+    Json,
+
+    /// A single CBOR map, written to stdout as binary.
+    Cbor,
+}
+
+#[derive(Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct DiffEntry {
+    #[n(0)]
+    pub key: ByteVec,
+
+    /// The value in the old store, or None if the key was added.
+    #[n(1)]
+    pub old: Option<ByteVec>,
+
+    /// The value in the new store, or None if the key was removed.
+    #[n(2)]
+    pub new: Option<ByteVec>,
+}
+
+impl DiffEntry {
+    /// The kind of state the key belongs to, e.g. `balances` or `events`.
+    pub fn kind(&self) -> &str {
+        let key = self.key.as_slice();
+        let key = key.strip_prefix(b"/").unwrap_or(key);
+        let end = key.iter().position(|c| *c == b'/').unwrap_or(key.len());
+        std::str::from_utf8(&key[..end]).unwrap_or("unknown")
+    }
+}
+
+#[derive(Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct StateDiff {
+    #[n(0)]
+    pub old_height: u64,
+
+    #[n(1)]
+    pub new_height: u64,
+
+    #[n(2)]
+    pub changes: Vec<DiffEntry>,
+}
+
+fn height(merk: &Merk) -> u64 {
+    merk.get(b"/height")
+        .expect("Could not read the height.")
+        .map_or(0u64, |x| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(x.as_slice());
+            u64::from_be_bytes(bytes)
+        })
+}
+
+fn entries(merk: &Merk) -> Peekable<impl Iterator<Item = (Vec<u8>, Vec<u8>)> + '_> {
+    merk.iter_opt(IteratorMode::Start, ReadOptions::default())
+        .map(|kv_result| {
+            let (k, v) = kv_result.expect("Could not read the store.");
+            let tree = Tree::decode(k.to_vec(), v.as_ref());
+            (k.to_vec(), tree.value().to_vec())
+        })
+        .peekable()
+}
+
+/// Compute the keys that changed between two stores, e.g. two backups of the same
+/// node taken at different heights. Both stores are walked once, in key order.
+pub fn diff(old: &Merk, new: &Merk) -> StateDiff {
+    let mut changes = Vec::new();
+    let mut old_it = entries(old);
+    let mut new_it = entries(new);
+
+    loop {
+        let ordering = match (old_it.peek(), new_it.peek()) {
+            (None, None) => break,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some((old_key, _)), Some((new_key, _))) => old_key.cmp(new_key),
+        };
+
+        match ordering {
+            Ordering::Less => {
+                let (key, value) = old_it.next().unwrap();
+                changes.push(DiffEntry {
+                    key: key.into(),
+                    old: Some(value.into()),
+                    new: None,
+                });
+            }
+            Ordering::Greater => {
+                let (key, value) = new_it.next().unwrap();
+                changes.push(DiffEntry {
+                    key: key.into(),
+                    old: None,
+                    new: Some(value.into()),
+                });
+            }
+            Ordering::Equal => {
+                let (key, old_value) = old_it.next().unwrap();
+                let (_, new_value) = new_it.next().unwrap();
+                if old_value != new_value {
+                    changes.push(DiffEntry {
+                        key: key.into(),
+                        old: Some(old_value.into()),
+                        new: Some(new_value.into()),
+                    });
+                }
+            }
+        }
+    }
+
+    StateDiff {
+        old_height: height(old),
+        new_height: height(new),
+        changes,
+    }
+}
+
+pub fn print(diff: &StateDiff, format: DiffFormat) {
+    match format {
+        DiffFormat::Json => {
+            println!(
+                "{}",
+                serde_json::json!({
+                    "old_height": diff.old_height,
+                    "new_height": diff.new_height,
+                    "changes": diff.changes.len(),
+                })
+            );
+            for entry in &diff.changes {
+                println!(
+                    "{}",
+                    serde_json::json!({
+                        "kind": entry.kind(),
+                        "key": hex::encode(entry.key.as_slice()),
+                        "old": entry.old.as_ref().map(|v| hex::encode(v.as_slice())),
+                        "new": entry.new.as_ref().map(|v| hex::encode(v.as_slice())),
+                    })
+                );
+            }
+        }
+        DiffFormat::Cbor => {
+            use std::io::Write;
+            let bytes = minicbor::to_vec(diff).expect("Could not encode the diff.");
+            std::io::stdout()
+                .write_all(&bytes)
+                .expect("Could not write the diff.");
+        }
+    }
+}
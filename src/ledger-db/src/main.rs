@@ -4,17 +4,38 @@ use merk::rocksdb::{IteratorMode, ReadOptions};
 use merk::tree::Tree;
 use std::path::PathBuf;
 
+mod diff;
+
 #[derive(Parser)]
 struct Opts {
     /// The RocksDB store to load.
     store: PathBuf,
+
+    /// Instead of dumping the store, compare it with another store (e.g. a backup
+    /// of the same node at a later height) and output the keys that changed.
+    #[clap(long)]
+    diff: Option<PathBuf>,
+
+    /// The output format of the diff.
+    #[clap(long, arg_enum, default_value_t = diff::DiffFormat::Json)]
+    format: diff::DiffFormat,
 }
 
 fn main() {
-    let Opts { store } = Opts::parse();
+    let Opts {
+        store,
+        diff,
+        format,
+    } = Opts::parse();
 
     let merk = merk::Merk::open(store).expect("Could not open the store.");
 
+    if let Some(other) = diff {
+        let other = merk::Merk::open(other).expect("Could not open the store to compare.");
+        diff::print(&diff::diff(&merk, &other), format);
+        return;
+    }
+
     let it = merk.iter_opt(IteratorMode::Start, ReadOptions::default());
 
     for kv_result in it {
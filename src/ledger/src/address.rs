@@ -0,0 +1,150 @@
+use clap::Parser;
+use many_error::ManyError;
+use many_identity::Address;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Addresses of the address book within this edit distance of an address are
+/// reported as possible typos.
+const MAXIMUM_TYPO_DISTANCE: usize = 3;
+
+#[derive(Parser)]
+pub struct VerifyAddressOpt {
+    /// The address to verify.
+    address: String,
+
+    /// A JSON file mapping names to known addresses, to detect likely typos.
+    #[clap(long)]
+    address_book: Option<PathBuf>,
+}
+
+/// The type of an address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressKind {
+    Anonymous,
+    Illegal,
+    PublicKey,
+    Subresource(u32),
+}
+
+impl Display for AddressKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AddressKind::Anonymous => f.write_str("anonymous"),
+            AddressKind::Illegal => f.write_str("illegal"),
+            AddressKind::PublicKey => f.write_str("public key"),
+            AddressKind::Subresource(id) => write!(f, "subresource {id}"),
+        }
+    }
+}
+
+impl AddressKind {
+    /// Whether the address can hold funds.
+    pub fn can_hold_funds(&self) -> bool {
+        matches!(self, AddressKind::PublicKey | AddressKind::Subresource(_))
+    }
+}
+
+pub fn classify(address: &Address) -> AddressKind {
+    if address.is_anonymous() {
+        AddressKind::Anonymous
+    } else if address.is_illegal() {
+        AddressKind::Illegal
+    } else if let Some(id) = address.subresource_id() {
+        AddressKind::Subresource(id)
+    } else {
+        AddressKind::PublicKey
+    }
+}
+
+/// Parse an address, verifying its checksum.
+pub fn parse(address: &str) -> Result<Address, ManyError> {
+    Address::from_str(address.trim())
+        .map_err(|e| ManyError::unknown(format!("Invalid address '{address}': {e}")))
+}
+
+/// Verify that `to` can receive funds, before sending any.
+pub fn verify_recipient(to: &Address) -> Result<(), ManyError> {
+    let kind = classify(to);
+    if kind.can_hold_funds() {
+        Ok(())
+    } else {
+        Err(ManyError::unknown(format!(
+            "Address {to} is {kind} and cannot receive funds."
+        )))
+    }
+}
+
+/// The Levenshtein distance between two strings.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous + usize::from(ca != *cb);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// The entries of the address book close to `address` but different from it,
+/// closest first.
+pub fn likely_typos<'a>(
+    address: &str,
+    book: &'a BTreeMap<String, Address>,
+) -> Vec<(&'a str, &'a Address, usize)> {
+    let mut typos: Vec<_> = book
+        .iter()
+        .map(|(name, known)| {
+            (
+                name.as_str(),
+                known,
+                edit_distance(address, &known.to_string()),
+            )
+        })
+        .filter(|(_, _, distance)| (1..=MAXIMUM_TYPO_DISTANCE).contains(distance))
+        .collect();
+    typos.sort_by_key(|(_, _, distance)| *distance);
+    typos
+}
+
+pub fn read_address_book(path: &Path) -> Result<BTreeMap<String, Address>, ManyError> {
+    let content = std::fs::read_to_string(path).map_err(ManyError::unknown)?;
+    let book: BTreeMap<String, String> =
+        serde_json::from_str(&content).map_err(ManyError::deserialization_error)?;
+    book.into_iter()
+        .map(|(name, address)| Ok((name, parse(&address)?)))
+        .collect()
+}
+
+pub fn verify_address(opts: VerifyAddressOpt) -> Result<(), ManyError> {
+    let VerifyAddressOpt {
+        address,
+        address_book,
+    } = opts;
+    let book = address_book
+        .as_deref()
+        .map(read_address_book)
+        .transpose()?
+        .unwrap_or_default();
+
+    let parsed = parse(&address);
+    if let Ok(parsed) = &parsed {
+        println!("{parsed}: valid checksum, {}", classify(parsed));
+        if let Some((name, _)) = book.iter().find(|(_, known)| *known == parsed) {
+            println!("Known address: {name}");
+            return Ok(());
+        }
+    }
+
+    for (name, known, distance) in likely_typos(address.trim(), &book) {
+        println!("Did you mean {name} ({known})? {distance} character(s) apart.");
+    }
+    parsed.map(|_| ())
+}
@@ -0,0 +1,268 @@
+use crate::{resolve_symbol, wait_response};
+use clap::Parser;
+use many_client::client::blocking::ManyClient;
+use many_error::ManyError;
+use many_identity::{Address, Identity};
+use many_modules::ledger;
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::Memo;
+use minicbor::{Decode, Encode};
+use num_bigint::BigUint;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::info;
+
+/// Maximum number of transfers of a `ledger.multiSend`, as enforced by the server.
+const MAX_TRANSFERS: usize = 100;
+
+#[derive(Parser)]
+pub struct SendBatchOpt {
+    /// A CSV file of `destination,amount,symbol` rows. Amounts are in the
+    /// smallest unit of the token. A header row and `#` comments are ignored.
+    file: PathBuf,
+
+    /// The from identity, if different than the one provided by the
+    /// PEM argument.
+    #[clap(long)]
+    account: Option<Address>,
+
+    /// A memo shared by every transfer.
+    #[clap(long)]
+    memo: Option<String>,
+
+    /// Send the rows one by one instead of a single atomic `ledger.multiSend`.
+    /// Required past 100 rows. Sent rows are recorded in the resume file, and
+    /// skipped when the command is run again.
+    #[clap(long)]
+    sequential: bool,
+
+    /// The resume file of `--sequential`. Defaults to the CSV file with a
+    /// `.progress` extension.
+    #[clap(long, requires = "sequential")]
+    resume_file: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+struct MultiSendTransfer {
+    #[n(0)]
+    to: Address,
+
+    #[n(1)]
+    symbol: Symbol,
+
+    #[n(2)]
+    amount: TokenAmount,
+}
+
+/// The arguments of `ledger.multiSend`.
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+struct MultiSendArgs {
+    #[n(0)]
+    from: Option<Address>,
+
+    #[n(1)]
+    transfers: Vec<MultiSendTransfer>,
+
+    #[n(2)]
+    memo: Option<Memo>,
+}
+
+/// A row of the CSV file, with its line number.
+struct Row {
+    line: usize,
+    to: Address,
+    amount: BigUint,
+    symbol: String,
+}
+
+fn parse_rows(content: &str) -> Result<Vec<Row>, ManyError> {
+    let mut rows = Vec::new();
+    for (i, text) in content.lines().enumerate() {
+        let line = i + 1;
+        let text = text.trim();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = text.split(',').map(str::trim).collect();
+        let (to, amount, symbol) = match fields.as_slice() {
+            [to, amount, symbol] => (to, amount, symbol),
+            _ => {
+                return Err(ManyError::unknown(format!(
+                    "Line {line}: expected `destination,amount,symbol`."
+                )))
+            }
+        };
+        let to = match Address::from_str(to) {
+            Ok(to) => to,
+            // The header.
+            Err(_) if rows.is_empty() && BigUint::from_str(amount).is_err() => continue,
+            Err(_) => {
+                return Err(ManyError::unknown(format!(
+                    "Line {line}: invalid destination '{to}'."
+                )))
+            }
+        };
+        crate::address::verify_recipient(&to)
+            .map_err(|e| ManyError::unknown(format!("Line {line}: {e}")))?;
+        let amount = BigUint::from_str(amount)
+            .map_err(|_| ManyError::unknown(format!("Line {line}: invalid amount '{amount}'.")))?;
+        rows.push(Row {
+            line,
+            to,
+            amount,
+            symbol: symbol.to_string(),
+        });
+    }
+    Ok(rows)
+}
+
+fn read_resume_file(path: &Path) -> Result<BTreeSet<usize>, ManyError> {
+    if !path.exists() {
+        return Ok(BTreeSet::new());
+    }
+    std::fs::read_to_string(path)
+        .map_err(ManyError::unknown)?
+        .lines()
+        .map(|line| {
+            line.trim()
+                .parse()
+                .map_err(|_| ManyError::unknown("Invalid resume file."))
+        })
+        .collect()
+}
+
+pub fn send_batch(
+    client: ManyClient<impl Identity>,
+    client_address: Address,
+    opts: SendBatchOpt,
+) -> Result<(), ManyError> {
+    let SendBatchOpt {
+        file,
+        account,
+        memo,
+        sequential,
+        resume_file,
+    } = opts;
+    let from = account.unwrap_or(client_address);
+    if from.is_anonymous() {
+        return Err(ManyError::invalid_identity());
+    }
+    let memo = memo
+        .map(|m| Memo::try_from(m.as_str()))
+        .transpose()
+        .map_err(|_| ManyError::unknown("Invalid memo."))?;
+
+    let rows = parse_rows(&std::fs::read_to_string(&file).map_err(ManyError::unknown)?)?;
+    let resume_file = resume_file.unwrap_or_else(|| file.with_extension("progress"));
+    let done = if sequential {
+        read_resume_file(&resume_file)?
+    } else {
+        BTreeSet::new()
+    };
+    let rows: Vec<Row> = rows
+        .into_iter()
+        .filter(|row| !done.contains(&row.line))
+        .collect();
+    if rows.is_empty() {
+        info!("Nothing to send.");
+        return Ok(());
+    }
+    if !sequential && rows.len() > MAX_TRANSFERS {
+        return Err(ManyError::unknown(format!(
+            "A multiSend holds at most {MAX_TRANSFERS} transfers, use --sequential."
+        )));
+    }
+
+    // Resolve the symbols once, and verify the totals against the balance.
+    let mut symbols = BTreeMap::new();
+    for row in &rows {
+        if !symbols.contains_key(&row.symbol) {
+            symbols.insert(
+                row.symbol.clone(),
+                resolve_symbol(&client, row.symbol.clone())?,
+            );
+        }
+    }
+    let mut totals: BTreeMap<Symbol, BigUint> = BTreeMap::new();
+    for row in &rows {
+        *totals.entry(symbols[&row.symbol]).or_default() += &row.amount;
+    }
+    let balance: ledger::BalanceReturns = minicbor::decode(&client.call_(
+        "ledger.balance",
+        ledger::BalanceArgs {
+            account: Some(from),
+            symbols: Some(totals.keys().copied().collect::<Vec<_>>().into()),
+        },
+    )?)
+    .map_err(ManyError::deserialization_error)?;
+    for (symbol, total) in &totals {
+        let available = balance
+            .balances
+            .get(symbol)
+            .cloned()
+            .unwrap_or_else(TokenAmount::zero);
+        if available
+            .checked_sub(&TokenAmount::from(total.clone()))
+            .is_none()
+        {
+            return Err(ManyError::unknown(format!(
+                "Insufficient funds of {symbol}: the batch sends {total}, the balance is {available}."
+            )));
+        }
+    }
+
+    if !sequential {
+        let arguments = MultiSendArgs {
+            from: Some(from),
+            transfers: rows
+                .iter()
+                .map(|row| MultiSendTransfer {
+                    to: row.to,
+                    symbol: symbols[&row.symbol],
+                    amount: TokenAmount::from(row.amount.clone()),
+                })
+                .collect(),
+            memo,
+        };
+        let response = client.call("ledger.multiSend", arguments)?;
+        wait_response(&client, response)?;
+        println!("Sent {} transfers.", rows.len());
+        return Ok(());
+    }
+
+    let mut progress = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&resume_file)
+        .map_err(ManyError::unknown)?;
+    let total = rows.len();
+    for (i, row) in rows.into_iter().enumerate() {
+        let arguments = ledger::SendArgs {
+            from: Some(from),
+            to: row.to,
+            symbol: symbols[&row.symbol],
+            amount: TokenAmount::from(row.amount.clone()),
+            memo: memo.clone(),
+        };
+        let response = client.call("ledger.send", arguments)?;
+        wait_response(&client, response).map_err(|e| {
+            ManyError::unknown(format!(
+                "Line {}: {e}. Run the command again to resume.",
+                row.line
+            ))
+        })?;
+        writeln!(progress, "{}", row.line).map_err(ManyError::unknown)?;
+        println!(
+            "[{}/{total}] Sent {} {} to {}.",
+            i + 1,
+            row.amount,
+            row.symbol,
+            row.to
+        );
+    }
+    Ok(())
+}
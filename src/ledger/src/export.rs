@@ -0,0 +1,194 @@
+use clap::Parser;
+use many_client::client::blocking::ManyClient;
+use many_error::ManyError;
+use many_identity::{Address, Identity};
+use many_modules::events::{self, EventInfo, EventLog};
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::{CborRange, SortOrder, Timestamp};
+use std::io::Write;
+use std::ops::Bound;
+
+/// The maximum number of events returned by `events.list`.
+pub(crate) const PAGE_SIZE: u64 = 100;
+
+#[derive(clap::ArgEnum, Clone, Copy, Debug)]
+pub enum ExportFormat {
+    Csv,
+    JsonLines,
+}
+
+#[derive(Parser)]
+pub struct ExportOpt {
+    /// The account to export the history of. If omitted it will use the
+    /// identity of the caller.
+    #[clap(long)]
+    account: Option<Address>,
+
+    /// The output format.
+    #[clap(long, arg_enum, default_value_t = ExportFormat::Csv)]
+    format: ExportFormat,
+
+    /// Only export the events at or after this date (RFC 3339).
+    #[clap(long)]
+    since: Option<humantime::Timestamp>,
+
+    /// Only export the events before this date (RFC 3339).
+    #[clap(long)]
+    until: Option<humantime::Timestamp>,
+}
+
+/// A transfer of tokens, as exported. Events that do not move tokens are
+/// exported with their kind only.
+pub(crate) struct Row {
+    pub id: String,
+    pub time: u64,
+    pub kind: String,
+    pub from: Option<Address>,
+    pub to: Option<Address>,
+    pub symbol: Option<Symbol>,
+    pub amount: Option<TokenAmount>,
+}
+
+pub(crate) fn rows_of(event: EventLog) -> Result<Vec<Row>, ManyError> {
+    let id = hex::encode(event.id.as_ref());
+    let time = event
+        .time
+        .as_system_time()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let kind = format!("{:?}", event.kind());
+    let row = |from, to, symbol, amount| Row {
+        id: id.clone(),
+        time,
+        kind: kind.clone(),
+        from,
+        to,
+        symbol,
+        amount,
+    };
+
+    Ok(match event.content {
+        EventInfo::Send {
+            from,
+            to,
+            symbol,
+            amount,
+            ..
+        } => vec![row(Some(from), Some(to), Some(symbol), Some(amount))],
+        EventInfo::TokenMint {
+            symbol,
+            distribution,
+            ..
+        } => distribution
+            .into_iter()
+            .map(|(to, amount)| row(None, Some(to), Some(symbol), Some(amount)))
+            .collect(),
+        EventInfo::TokenBurn {
+            symbol,
+            distribution,
+            ..
+        } => distribution
+            .into_iter()
+            .map(|(from, amount)| row(Some(from), None, Some(symbol), Some(amount)))
+            .collect(),
+        _ => vec![row(None, None, None, None)],
+    })
+}
+
+fn write_row(out: &mut impl Write, format: ExportFormat, row: &Row) -> std::io::Result<()> {
+    let text = |x: Option<String>| x.unwrap_or_default();
+    match format {
+        ExportFormat::Csv => writeln!(
+            out,
+            "{},{},{},{},{},{},{}",
+            row.id,
+            row.time,
+            row.kind,
+            text(row.from.map(|a| a.to_string())),
+            text(row.to.map(|a| a.to_string())),
+            text(row.symbol.map(|s| s.to_string())),
+            text(row.amount.as_ref().map(|a| a.to_string())),
+        ),
+        ExportFormat::JsonLines => writeln!(
+            out,
+            "{}",
+            serde_json::json!({
+                "id": row.id,
+                "time": row.time,
+                "kind": row.kind,
+                "from": row.from.map(|a| a.to_string()),
+                "to": row.to.map(|a| a.to_string()),
+                "symbol": row.symbol.map(|s| s.to_string()),
+                "amount": row.amount.as_ref().map(|a| a.to_string()),
+            })
+        ),
+    }
+}
+
+/// Write every event about the account to stdout, oldest first. Pages of
+/// `events.list` are chained by their last event ID, so the history is not
+/// limited to a single page.
+pub fn export(
+    client: ManyClient<impl Identity>,
+    client_address: Address,
+    opts: ExportOpt,
+) -> Result<(), ManyError> {
+    let ExportOpt {
+        account,
+        format,
+        since,
+        until,
+    } = opts;
+    let account = account.unwrap_or(client_address);
+    let bound = |time: Option<humantime::Timestamp>, included: bool| {
+        time.map_or(Ok(Bound::Unbounded), |t| {
+            Timestamp::from_system_time(*t).map(|t| {
+                if included {
+                    Bound::Included(t)
+                } else {
+                    Bound::Excluded(t)
+                }
+            })
+        })
+    };
+    let date_range = CborRange {
+        start: bound(since, true)?,
+        end: bound(until, false)?,
+    };
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    if let ExportFormat::Csv = format {
+        writeln!(out, "id,time,kind,from,to,symbol,amount").map_err(ManyError::unknown)?;
+    }
+
+    let mut start = Bound::Unbounded;
+    loop {
+        let args = events::ListArgs {
+            count: Some(PAGE_SIZE),
+            order: Some(SortOrder::Ascending),
+            filter: Some(events::EventFilter {
+                account: Some(vec![account].into()),
+                date_range: Some(date_range.clone()),
+                id_range: Some(CborRange {
+                    start: start.clone(),
+                    end: Bound::Unbounded,
+                }),
+                ..events::EventFilter::default()
+            }),
+        };
+        let list: events::ListReturns = minicbor::decode(&client.call_("events.list", args)?)
+            .map_err(ManyError::deserialization_error)?;
+        let last = match list.events.last() {
+            Some(event) => event.id.clone(),
+            None => return Ok(()),
+        };
+
+        for event in list.events {
+            for row in rows_of(event)? {
+                write_row(&mut out, format, &row).map_err(ManyError::unknown)?;
+            }
+        }
+        start = Bound::Excluded(last);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{resolve_symbol, wait_response};
+use clap::Parser;
+use many_client::client::blocking::ManyClient;
+use many_error::ManyError;
+use many_identity::{Address, Identity};
+use many_types::ledger::{Symbol, TokenAmount};
+use minicbor::{Decode, Encode};
+use std::collections::BTreeMap;
+
+#[derive(Parser)]
+pub struct FaucetOpt {
+    /// The symbol to claim. This can either be an identity or a local name
+    /// for a symbol. If omitted, print the faucet and when the account can
+    /// claim again.
+    symbol: Option<String>,
+
+    /// The identity to send the tokens to. If omitted it will use the
+    /// identity of the caller.
+    #[clap(long)]
+    to: Option<Address>,
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+struct FaucetInfoArgs {
+    #[n(0)]
+    account: Option<Address>,
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+struct FaucetConfig {
+    #[n(0)]
+    account: Address,
+
+    #[n(1)]
+    amounts: BTreeMap<Symbol, TokenAmount>,
+
+    #[n(2)]
+    period: u64,
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+struct FaucetInfoReturns {
+    #[n(0)]
+    config: Option<FaucetConfig>,
+
+    #[n(1)]
+    retry_after: BTreeMap<Symbol, u64>,
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+struct FaucetClaimArgs {
+    #[n(0)]
+    symbol: Symbol,
+
+    #[n(1)]
+    to: Option<Address>,
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+struct FaucetClaimReturns {
+    #[n(0)]
+    amount: TokenAmount,
+}
+
+pub fn faucet(
+    client: ManyClient<impl Identity>,
+    client_address: Address,
+    opts: FaucetOpt,
+) -> Result<(), ManyError> {
+    let FaucetOpt { symbol, to } = opts;
+    let to = to.unwrap_or(client_address);
+    if to.is_anonymous() {
+        return Err(ManyError::invalid_identity());
+    }
+
+    let symbol = match symbol {
+        Some(symbol) => resolve_symbol(&client, symbol)?,
+        None => {
+            let info: FaucetInfoReturns = minicbor::decode(
+                &client.call_("faucet.info", FaucetInfoArgs { account: Some(to) })?,
+            )
+            .map_err(ManyError::deserialization_error)?;
+            let config = match info.config {
+                Some(config) => config,
+                None => {
+                    println!("The faucet is not configured.");
+                    return Ok(());
+                }
+            };
+            println!(
+                "Faucet account {}, one claim per {} seconds.",
+                config.account, config.period
+            );
+            for (symbol, amount) in config.amounts {
+                match info.retry_after.get(&symbol) {
+                    Some(secs) => println!("{amount:>12} {symbol} (retry in {secs} seconds)"),
+                    None => println!("{amount:>12} {symbol}"),
+                }
+            }
+            return Ok(());
+        }
+    };
+
+    let response = client.call(
+        "faucet.claim",
+        FaucetClaimArgs {
+            symbol,
+            to: Some(to),
+        },
+    )?;
+    let payload = wait_response(&client, response)?;
+    let returns: FaucetClaimReturns =
+        minicbor::decode(&payload).map_err(ManyError::deserialization_error)?;
+    println!("Received {} {symbol}.", returns.amount);
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use crate::export::{rows_of, Row, PAGE_SIZE};
+use clap::Parser;
+use many_client::client::blocking::ManyClient;
+use many_error::ManyError;
+use many_identity::{Address, Identity};
+use many_modules::events::{self, EventId, EventLog};
+use many_types::{CborRange, SortOrder};
+use std::ops::Bound;
+use std::time::{Duration, UNIX_EPOCH};
+use tracing::warn;
+
+#[derive(Parser)]
+pub struct HistoryOpt {
+    /// The account to show the history of. If omitted it will use the
+    /// identity of the caller.
+    #[clap(long)]
+    account: Option<Address>,
+
+    /// The number of past events to show.
+    #[clap(long, default_value = "20")]
+    count: u64,
+
+    /// Keep polling for new events and print them as they happen, until
+    /// interrupted.
+    #[clap(long)]
+    follow: bool,
+
+    /// The number of seconds between two polls of `--follow`.
+    #[clap(long, default_value = "5", requires = "follow")]
+    interval: u64,
+}
+
+fn print_row(row: &Row) {
+    let time = humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(row.time));
+    let text = |x: Option<String>| x.unwrap_or_else(|| "-".to_string());
+    match (&row.amount, row.symbol) {
+        (Some(amount), Some(symbol)) => println!(
+            "{time} {:<12} {} -> {} {amount} {symbol}",
+            row.kind,
+            text(row.from.map(|a| a.to_string())),
+            text(row.to.map(|a| a.to_string())),
+        ),
+        _ => println!("{time} {}", row.kind),
+    }
+}
+
+fn list(
+    client: &ManyClient<impl Identity>,
+    account: Address,
+    count: u64,
+    order: SortOrder,
+    start: Bound<EventId>,
+) -> Result<Vec<EventLog>, ManyError> {
+    let args = events::ListArgs {
+        count: Some(count),
+        order: Some(order),
+        filter: Some(events::EventFilter {
+            account: Some(vec![account].into()),
+            id_range: Some(CborRange {
+                start,
+                end: Bound::Unbounded,
+            }),
+            ..events::EventFilter::default()
+        }),
+    };
+    let list: events::ListReturns = minicbor::decode(&client.call_("events.list", args)?)
+        .map_err(ManyError::deserialization_error)?;
+    Ok(list.events)
+}
+
+/// Print the last events about the account, oldest first, then with `--follow`
+/// the new ones as they are logged.
+pub fn history(
+    client: ManyClient<impl Identity>,
+    client_address: Address,
+    opts: HistoryOpt,
+) -> Result<(), ManyError> {
+    let HistoryOpt {
+        account,
+        count,
+        follow,
+        interval,
+    } = opts;
+    let account = account.unwrap_or(client_address);
+
+    let mut recent = list(
+        &client,
+        account,
+        count.min(PAGE_SIZE),
+        SortOrder::Descending,
+        Bound::Unbounded,
+    )?;
+    recent.reverse();
+    let mut last = recent.last().map(|event| event.id.clone());
+    for event in recent {
+        rows_of(event)?.iter().for_each(print_row);
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    loop {
+        std::thread::sleep(Duration::from_secs(interval));
+        let start = last.clone().map_or(Bound::Unbounded, Bound::Excluded);
+        let events = match list(&client, account, PAGE_SIZE, SortOrder::Ascending, start) {
+            Ok(events) => events,
+            // The server may be restarting, try again at the next poll.
+            Err(e) => {
+                warn!("{e}");
+                continue;
+            }
+        };
+        if let Some(event) = events.last() {
+            last = Some(event.id.clone());
+        }
+        for event in events {
+            rows_of(event)?.iter().for_each(print_row);
+        }
+    }
+}
@@ -1,11 +1,11 @@
-use clap::{ArgGroup, Parser};
+use clap::{ArgGroup, CommandFactory, Parser};
 use many_client::client::blocking::ManyClient;
-use many_error::ManyError;
+use many_error::{ManyError, ManyErrorCode};
 use many_identity::{Address, AnonymousIdentity, Identity};
 use many_identity_dsa::CoseKeyIdentity;
 use many_identity_hsm::{Hsm, HsmIdentity, HsmMechanismType, HsmSessionType, HsmUserType};
 use many_modules::r#async::{StatusArgs, StatusReturn};
-use many_modules::{ledger, r#async};
+use many_modules::{account, ledger, r#async};
 use many_protocol::ResponseMessage;
 use many_types::ledger::{Symbol, TokenAmount};
 use many_types::Memo;
@@ -13,15 +13,23 @@ use minicbor::data::Tag;
 use minicbor::encode::{Error, Write};
 use minicbor::{Decoder, Encoder};
 use num_bigint::BigUint;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{Display, Formatter};
 use std::path::PathBuf;
 use std::str::FromStr;
-use std::time::Duration;
-use tracing::{debug, error, info, trace};
+use std::time::{Duration, SystemTime};
+use tracing::{debug, error, info, trace, warn};
 use tracing_subscriber::filter::LevelFilter;
 
+mod address;
+mod batch;
+mod export;
+mod faucet;
+mod history;
+mod keys;
 mod multisig;
+mod offline;
+mod repl;
 mod tokens;
 
 #[derive(clap::ArgEnum, Clone, Debug)]
@@ -30,6 +38,44 @@ enum LogStrategy {
     Syslog,
 }
 
+/// Exit codes of the CLI. These are stable and can be relied upon by scripts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+enum ExitCode {
+    Success = 0,
+
+    /// The server returned an error not covered by other codes.
+    ApplicationError = 1,
+
+    /// The command line arguments are invalid. This is the code used by clap.
+    Usage = 2,
+
+    /// The server could not be reached.
+    Network = 3,
+
+    /// A message could not be encoded or a response could not be decoded.
+    Decode = 4,
+
+    /// The identity is invalid or not allowed to perform the operation.
+    Unauthorized = 5,
+}
+
+impl From<&ManyError> for ExitCode {
+    fn from(err: &ManyError) -> Self {
+        match err.code() {
+            ManyErrorCode::UnexpectedTransportError => ExitCode::Network,
+            ManyErrorCode::DeserializationError
+            | ManyErrorCode::SerializationError
+            | ManyErrorCode::UnexpectedEmptyResponse => ExitCode::Decode,
+            ManyErrorCode::InvalidIdentity | ManyErrorCode::InvalidFromIdentity => {
+                ExitCode::Unauthorized
+            }
+            code if code == account::errors::user_needs_role("").code() => ExitCode::Unauthorized,
+            _ => ExitCode::ApplicationError,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 #[repr(transparent)]
 struct Amount(pub BigUint);
@@ -79,6 +125,11 @@ struct Opts {
     #[clap(long)]
     pem: Option<PathBuf>,
 
+    /// The name of a key of the keystore, see `ledger keys`. Its password is
+    /// prompted for.
+    #[clap(long, conflicts_with_all(&["pem", "module"]))]
+    key: Option<String>,
+
     /// HSM PKCS#11 module path
     #[clap(long, conflicts_with("pem"))]
     module: Option<PathBuf>,
@@ -115,11 +166,51 @@ enum SubCommand {
     /// Send tokens to an account.
     Send(TargetCommandOpt),
 
+    /// Send tokens to every destination of a CSV file, in a single atomic
+    /// multiSend or one by one.
+    SendBatch(batch::SendBatchOpt),
+
+    /// Send a transaction signed with `--offline`.
+    Broadcast(offline::BroadcastOpt),
+
     /// Perform a multisig operation.
     Multisig(multisig::CommandOpt),
 
     /// Perform a token operation
     Token(tokens::CommandOpt),
+
+    /// Claim tokens from the faucet of a devnet.
+    Faucet(faucet::FaucetOpt),
+
+    /// Print the initial distribution of the ledger, as attested by the server.
+    GenesisReport,
+
+    /// Print the last events about an account, and optionally follow the new
+    /// ones.
+    History(history::HistoryOpt),
+
+    /// Export the full event history of an account as CSV or JSON lines.
+    Export(export::ExportOpt),
+
+    /// Verify the checksum and type of an address, and look for likely typos
+    /// in an address book.
+    VerifyAddress(address::VerifyAddressOpt),
+
+    /// Manage the keys of the local keystore.
+    Keys(keys::KeysOpt),
+
+    /// Start an interactive session.
+    Repl(repl::ReplOpt),
+
+    /// Generate a completion script for a shell and print it to stdout.
+    Completions(CompletionsOpt),
+}
+
+#[derive(Parser)]
+struct CompletionsOpt {
+    /// The shell to generate the completion script for.
+    #[clap(arg_enum)]
+    shell: clap_complete::Shell,
 }
 
 #[derive(Parser)]
@@ -128,6 +219,18 @@ struct BalanceOpt {
     /// identity) or an identity string. If omitted it will use the identity of the caller.
     identity: Option<String>,
 
+    /// Display the raw integer amounts, without applying the token decimals.
+    #[clap(long)]
+    raw: bool,
+
+    /// Keep polling the balance and print every change, until interrupted.
+    #[clap(long)]
+    watch: bool,
+
+    /// The number of seconds between two polls of `--watch`.
+    #[clap(long, default_value = "5", requires = "watch")]
+    interval: u64,
+
     /// The symbol to check the balance of. This can either be an identity or
     /// a local name for a symbol. If it doesn't parse to an identity an
     /// additional call will be made to retrieve local names.
@@ -156,6 +259,11 @@ pub(crate) struct TargetCommandOpt {
     /// Optional memo
     #[clap(long)]
     memo: Option<String>,
+
+    /// Sign the transaction into this file instead of sending it, e.g. on an
+    /// air-gapped machine. Send the file with `ledger broadcast`.
+    #[clap(long)]
+    offline: Option<PathBuf>,
 }
 
 pub fn resolve_symbol(
@@ -176,10 +284,31 @@ pub fn resolve_symbol(
     }
 }
 
+/// Format a raw token amount using the number of decimals of its token, e.g. `1234500`
+/// with 6 decimals is displayed as `1.2345`.
+fn format_amount(amount: &TokenAmount, decimals: u64) -> String {
+    let digits = amount.to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+
+    let digits = format!("{digits:0>width$}", width = decimals + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{integer}.{fraction}")
+    }
+}
+
 fn balance(
     client: ManyClient<impl Identity>,
     account: Option<Address>,
     symbols: Vec<String>,
+    raw: bool,
+    watch: Option<Duration>,
 ) -> Result<(), ManyError> {
     // Get info.
     let info: ledger::InfoReturns = minicbor::decode(&client.call_("ledger.info", ())?).unwrap();
@@ -189,50 +318,94 @@ fn balance(
         .map(|(x, y)| (y.clone(), *x))
         .collect();
 
-    let argument = ledger::BalanceArgs {
-        account,
-        symbols: if symbols.is_empty() {
-            None
-        } else {
-            Some(
-                symbols
-                    .iter()
-                    .map(|x| {
-                        if let Ok(i) = Address::from_str(x) {
-                            Ok(i)
-                        } else if let Some(i) = local_names.get(x.as_str()) {
-                            Ok(*i)
-                        } else {
-                            Err(ManyError::unknown(format!(
-                                "Could not resolve symbol '{x}'"
-                            )))
-                        }
-                    })
-                    .collect::<Result<Vec<_>, _>>()?
-                    .into(),
-            )
-        },
-    };
-    let payload = client.call_("ledger.balance", argument)?;
-
-    if payload.is_empty() {
-        Err(ManyError::unexpected_empty_response())
+    let symbols: Option<Vec<Symbol>> = if symbols.is_empty() {
+        None
     } else {
-        let balance: ledger::BalanceReturns = minicbor::decode(&payload).unwrap();
-        for (symbol, amount) in balance.balances {
-            if let Some(symbol_name) = info.local_names.get(&symbol) {
-                println!("{amount:>12} {symbol_name} ({symbol})");
+        Some(
+            symbols
+                .iter()
+                .map(|x| {
+                    if let Ok(i) = Address::from_str(x) {
+                        Ok(i)
+                    } else if let Some(i) = local_names.get(x.as_str()) {
+                        Ok(*i)
+                    } else {
+                        Err(ManyError::unknown(format!(
+                            "Could not resolve symbol '{x}'"
+                        )))
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        )
+    };
+    let get_balances = || -> Result<BTreeMap<Symbol, TokenAmount>, ManyError> {
+        let argument = ledger::BalanceArgs {
+            account,
+            symbols: symbols.clone().map(Into::into),
+        };
+        let payload = client.call_("ledger.balance", argument)?;
+        if payload.is_empty() {
+            return Err(ManyError::unexpected_empty_response());
+        }
+        let balance: ledger::BalanceReturns =
+            minicbor::decode(&payload).map_err(ManyError::deserialization_error)?;
+        Ok(balance.balances)
+    };
+    let display = |symbol: &Symbol, amount: &TokenAmount| match (raw, info.tokens.get(symbol)) {
+        (false, Some(summary)) => {
+            let amount = format_amount(amount, summary.decimals);
+            format!("{amount:>20} {} ({symbol})", summary.ticker)
+        }
+        _ => {
+            if let Some(symbol_name) = info.local_names.get(symbol) {
+                format!("{amount:>12} {symbol_name} ({symbol})")
             } else {
-                println!("{amount:>12} {symbol}");
+                format!("{amount:>12} {symbol}")
             }
         }
+    };
 
-        Ok(())
+    let mut balances = get_balances()?;
+    for (symbol, amount) in &balances {
+        println!("{}", display(symbol, amount));
+    }
+
+    let interval = match watch {
+        Some(interval) => interval,
+        None => return Ok(()),
+    };
+    loop {
+        std::thread::sleep(interval);
+        let current = match get_balances() {
+            Ok(current) => current,
+            // The server may be restarting, try again at the next poll.
+            Err(e) => {
+                warn!("{e}");
+                continue;
+            }
+        };
+
+        // Symbols missing from a response have a balance of zero.
+        let symbols: BTreeSet<&Symbol> = balances.keys().chain(current.keys()).collect();
+        for symbol in symbols {
+            let zero = TokenAmount::zero();
+            let before = balances.get(symbol).unwrap_or(&zero);
+            let after = current.get(symbol).unwrap_or(&zero);
+            if before != after {
+                println!(
+                    "{} {} (was {})",
+                    humantime::format_rfc3339_seconds(SystemTime::now()),
+                    display(symbol, after),
+                    display(symbol, before).trim_start()
+                );
+            }
+        }
+        balances = current;
     }
 }
 
 pub(crate) fn wait_response(
-    client: ManyClient<impl Identity>,
+    client: &ManyClient<impl Identity>,
     response: ResponseMessage,
 ) -> Result<Vec<u8>, ManyError> {
     let ResponseMessage {
@@ -296,6 +469,49 @@ pub(crate) fn wait_response(
     }
 }
 
+/// The initial distribution of the ledger, as returned by `ledger.genesisReport`.
+#[derive(minicbor::Decode)]
+#[cbor(map)]
+struct GenesisReport {
+    #[n(0)]
+    hash: minicbor::bytes::ByteVec,
+
+    #[n(1)]
+    allocations: BTreeMap<Address, BTreeMap<Symbol, TokenAmount>>,
+
+    #[n(2)]
+    totals: BTreeMap<Symbol, TokenAmount>,
+}
+
+fn genesis_report(client: ManyClient<impl Identity>) -> Result<(), ManyError> {
+    let info: ledger::InfoReturns = minicbor::decode(&client.call_("ledger.info", ())?).unwrap();
+    let name = |symbol: &Symbol| {
+        info.local_names
+            .get(symbol)
+            .map_or_else(|| symbol.to_string(), |name| format!("{name} ({symbol})"))
+    };
+
+    // The response is signed by the server, which the client already verified.
+    let response = client.call("ledger.genesisReport", ())?;
+    let signer = response.from;
+    let report: GenesisReport =
+        minicbor::decode(&response.data?).map_err(ManyError::deserialization_error)?;
+
+    println!("Genesis hash: {}", hex::encode(report.hash.as_ref()));
+    println!("Attested by:  {signer}");
+    println!();
+    for (account, balances) in report.allocations {
+        for (symbol, amount) in balances {
+            println!("{account} {amount:>20} {}", name(&symbol));
+        }
+    }
+    println!();
+    for (symbol, total) in report.totals {
+        println!("Total {total:>20} {}", name(&symbol));
+    }
+    Ok(())
+}
+
 fn send(
     client: ManyClient<impl Identity>,
     from: Address,
@@ -304,6 +520,7 @@ fn send(
     symbol: String,
     memo: Option<Memo>,
 ) -> Result<(), ManyError> {
+    address::verify_recipient(&to)?;
     let symbol = resolve_symbol(&client, symbol)?;
 
     if from.is_anonymous() {
@@ -317,15 +534,114 @@ fn send(
             memo,
         };
         let response = client.call("ledger.send", arguments)?;
-        let payload = wait_response(client, response)?;
+        let payload = wait_response(&client, response)?;
         println!("{}", minicbor::display(&payload));
         Ok(())
     }
 }
 
+/// Where the identity signing the requests comes from. The REPL builds it
+/// again for every command.
+#[derive(Clone)]
+pub(crate) enum KeySource {
+    Anonymous,
+    Pem(PathBuf),
+    Keystore(CoseKeyIdentity),
+    /// The HSM session is opened once, when parsing the command line.
+    Hsm,
+}
+
+impl KeySource {
+    pub(crate) fn identity(&self) -> Result<Box<dyn Identity>, ManyError> {
+        Ok(match self {
+            KeySource::Anonymous => Box::new(AnonymousIdentity),
+            KeySource::Pem(path) => {
+                let content = std::fs::read_to_string(path).map_err(ManyError::unknown)?;
+                Box::new(CoseKeyIdentity::from_pem(content).map_err(ManyError::unknown)?)
+            }
+            KeySource::Keystore(key) => Box::new(key.clone()),
+            KeySource::Hsm => {
+                trace!("Creating CoseKeyIdentity");
+                // Only ECDSA is supported at the moment. It should be easy to add support for new EC mechanisms
+                Box::new(
+                    HsmIdentity::new(HsmMechanismType::ECDSA)
+                        .map_err(|e| ManyError::unknown(e.to_string()))?,
+                )
+            }
+        })
+    }
+}
+
+/// The address of an identity argument, either an address or a PEM file.
+fn parse_identity(identity: &str) -> Result<Address, ManyError> {
+    Address::from_str(identity).or_else(|_| {
+        let bytes = std::fs::read_to_string(PathBuf::from(identity))
+            .map_err(|_| ManyError::unknown("Unable to decode identity command-line argument"))?;
+        Ok(CoseKeyIdentity::from_pem(bytes)
+            .map_err(ManyError::unknown)?
+            .address())
+    })
+}
+
+pub(crate) fn run(
+    client: ManyClient<impl Identity>,
+    client_address: Address,
+    subcommand: SubCommand,
+) -> Result<(), ManyError> {
+    match subcommand {
+        SubCommand::Balance(BalanceOpt {
+            identity,
+            raw,
+            watch,
+            interval,
+            symbols,
+        }) => {
+            let identity = identity.as_deref().map(parse_identity).transpose()?;
+
+            balance(
+                client,
+                identity,
+                symbols,
+                raw,
+                watch.then(|| Duration::from_secs(interval)),
+            )
+        }
+        SubCommand::Send(TargetCommandOpt {
+            account,
+            identity,
+            amount,
+            symbol,
+            memo,
+            ..
+        }) => {
+            let from = account.unwrap_or(client_address);
+            let memo = memo
+                .map(|m| Memo::try_from(m.as_str()))
+                .transpose()
+                .map_err(|e| ManyError::unknown(e.to_string()))?;
+            send(client, from, identity, amount, symbol, memo)
+        }
+        SubCommand::SendBatch(opts) => batch::send_batch(client, client_address, opts),
+        SubCommand::Multisig(opts) => multisig::multisig(client, opts),
+        SubCommand::Token(opts) => tokens::tokens(client, opts),
+        SubCommand::Faucet(opts) => faucet::faucet(client, client_address, opts),
+        SubCommand::GenesisReport => genesis_report(client),
+        SubCommand::History(opts) => history::history(client, client_address, opts),
+        SubCommand::Export(opts) => export::export(client, client_address, opts),
+        SubCommand::VerifyAddress(opts) => address::verify_address(opts),
+        SubCommand::Repl(_)
+        | SubCommand::Completions(_)
+        | SubCommand::Broadcast(_)
+        | SubCommand::Keys(_) => {
+            unreachable!("Handled before connecting to the server")
+        }
+    }
+}
+
 fn main() {
     let Opts {
         pem,
+        key,
         module,
         slot,
         keyid,
@@ -335,7 +651,25 @@ fn main() {
         verbose,
         quiet,
         logmode,
-    } = Opts::parse();
+    } = Opts::try_parse().unwrap_or_else(|e| {
+        let _ = e.print();
+        let code = if e.use_stderr() {
+            ExitCode::Usage
+        } else {
+            ExitCode::Success
+        };
+        std::process::exit(code as i32)
+    });
+
+    if let SubCommand::Completions(CompletionsOpt { shell }) = subcommand {
+        clap_complete::generate(
+            shell,
+            &mut Opts::command(),
+            "ledger",
+            &mut std::io::stdout(),
+        );
+        std::process::exit(ExitCode::Success as i32);
+    }
 
     let verbose_level = 2 + verbose - quiet;
     let log_level = match verbose_level {
@@ -364,9 +698,12 @@ fn main() {
         }
     };
 
-    let key: Box<dyn Identity> = if let (Some(module), Some(slot), Some(keyid)) =
-        (module, slot, keyid)
-    {
+    let key_source = if let Some(name) = key {
+        KeySource::Keystore(keys::load(&name).unwrap_or_else(|e| {
+            error!("{e}");
+            std::process::exit(ExitCode::Unauthorized as i32)
+        }))
+    } else if let (Some(module), Some(slot), Some(keyid)) = (module, slot, keyid) {
         trace!("Getting user PIN");
         let pin = rpassword::prompt_password("Please enter the HSM user PIN: ")
             .expect("I/O error when reading HSM PIN");
@@ -381,56 +718,29 @@ fn main() {
             hsm.open_session(slot, HsmSessionType::RO, Some(HsmUserType::User), Some(pin))
                 .expect("Failed to open HSM session");
         }
-
-        trace!("Creating CoseKeyIdentity");
-        // Only ECDSA is supported at the moment. It should be easy to add support for new EC mechanisms
-        Box::new(
-            HsmIdentity::new(HsmMechanismType::ECDSA)
-                .expect("Unable to create CoseKeyIdentity from HSM"),
-        )
+        KeySource::Hsm
     } else {
-        pem.map_or_else(
-            || Box::new(AnonymousIdentity) as Box<dyn Identity>,
-            |p| Box::new(CoseKeyIdentity::from_pem(std::fs::read_to_string(p).unwrap()).unwrap()),
-        )
+        pem.map_or(KeySource::Anonymous, KeySource::Pem)
     };
+    let key = key_source.identity().unwrap_or_else(|e| {
+        error!("{e}");
+        std::process::exit(ExitCode::Unauthorized as i32)
+    });
 
-    let client_address = key.address();
-    let client = ManyClient::new(server, server_id, key).unwrap();
     let result = match subcommand {
-        SubCommand::Balance(BalanceOpt { identity, symbols }) => {
-            let identity = identity.map(|identity| {
-                Address::from_str(&identity)
-                    .or_else(|_| {
-                        let bytes = std::fs::read_to_string(PathBuf::from(identity))?;
-
-                        Ok(CoseKeyIdentity::from_pem(bytes).unwrap().address())
-                    })
-                    .map_err(|_: std::io::Error| ())
-                    .expect("Unable to decode identity command-line argument")
-            });
-
-            balance(client, identity, symbols)
+        SubCommand::Repl(opts) => repl::repl(server, server_id, key_source, opts),
+        SubCommand::Completions(_) => unreachable!(),
+        SubCommand::Send(opts) if opts.offline.is_some() => {
+            let path = opts.offline.clone().unwrap();
+            offline::sign_send(&key, server_id, opts, &path)
         }
-        SubCommand::Send(TargetCommandOpt {
-            account,
-            identity,
-            amount,
-            symbol,
-            memo,
-        }) => {
-            let from = account.unwrap_or(client_address);
-            send(
-                client,
-                from,
-                identity,
-                amount,
-                symbol,
-                memo.map(|m| Memo::try_from(m.as_str()).unwrap()),
-            )
+        SubCommand::Broadcast(opts) => offline::broadcast(server, server_id, opts),
+        SubCommand::Keys(opts) => keys::keys(opts),
+        subcommand => {
+            let client_address = key.address();
+            let client = ManyClient::new(server, server_id, key).unwrap();
+            run(client, client_address, subcommand)
         }
-        SubCommand::Multisig(opts) => multisig::multisig(client, opts),
-        SubCommand::Token(opts) => tokens::tokens(client, opts),
     };
 
     if let Err(err) = result {
@@ -441,6 +751,6 @@ fn main() {
                 .collect::<Vec<&str>>()
                 .join("\n|  ")
         );
-        std::process::exit(1);
+        std::process::exit(ExitCode::from(&err) as i32);
     }
 }
@@ -142,7 +142,7 @@ fn submit_send(
     };
     let response = client.call("account.multisigSubmitTransaction", arguments)?;
 
-    let payload = crate::wait_response(client, response)?;
+    let payload = crate::wait_response(&client, response)?;
     let result: multisig::SubmitTransactionReturn =
         minicbor::decode(&payload).map_err(ManyError::deserialization_error)?;
 
@@ -185,7 +185,7 @@ fn submit_set_defaults(
     };
     let response = client.call("account.multisigSubmitTransaction", arguments)?;
 
-    let payload = crate::wait_response(client, response)?;
+    let payload = crate::wait_response(&client, response)?;
     let result: multisig::SubmitTransactionReturn =
         minicbor::decode(&payload).map_err(ManyError::deserialization_error)?;
 
@@ -219,7 +219,7 @@ fn approve(client: ManyClient<impl Identity>, opts: TransactionOpt) -> Result<()
     let arguments = multisig::ApproveArgs { token: opts.token };
     let response = client.call("account.multisigApprove", arguments)?;
 
-    let payload = crate::wait_response(client, response)?;
+    let payload = crate::wait_response(&client, response)?;
     let _result: multisig::ApproveReturn =
         minicbor::decode(&payload).map_err(ManyError::deserialization_error)?;
 
@@ -232,7 +232,7 @@ fn revoke(client: ManyClient<impl Identity>, opts: TransactionOpt) -> Result<(),
     let arguments = multisig::RevokeArgs { token: opts.token };
     let response = client.call("account.multisigRevoke", arguments)?;
 
-    let payload = crate::wait_response(client, response)?;
+    let payload = crate::wait_response(&client, response)?;
     let _result: multisig::RevokeReturn =
         minicbor::decode(&payload).map_err(ManyError::deserialization_error)?;
 
@@ -245,7 +245,7 @@ fn execute(client: ManyClient<impl Identity>, opts: TransactionOpt) -> Result<()
     let arguments = multisig::ExecuteArgs { token: opts.token };
     let response = client.call("account.multisigExecute", arguments)?;
 
-    let payload = crate::wait_response(client, response)?;
+    let payload = crate::wait_response(&client, response)?;
     let result: ResponseMessage =
         minicbor::decode(&payload).map_err(ManyError::deserialization_error)?;
 
@@ -258,7 +258,7 @@ fn info(client: ManyClient<impl Identity>, opts: TransactionOpt) -> Result<(), M
     let arguments = multisig::InfoArgs { token: opts.token };
     let response = client.call("account.multisigInfo", arguments)?;
 
-    let payload = crate::wait_response(client, response)?;
+    let payload = crate::wait_response(&client, response)?;
     let result: multisig::InfoReturn =
         minicbor::decode(&payload).map_err(ManyError::deserialization_error)?;
 
@@ -279,7 +279,7 @@ fn set_defaults(
     };
     let response = client.call("account.multisigSetDefaults", arguments)?;
 
-    let payload = crate::wait_response(client, response)?;
+    let payload = crate::wait_response(&client, response)?;
     let _result: multisig::SetDefaultsReturn =
         minicbor::decode(&payload).map_err(ManyError::deserialization_error)?;
 
@@ -0,0 +1,113 @@
+//! Air-gapped operations: a machine holding the key signs a command into an
+//! envelope file without contacting the server, and any online machine sends
+//! it with `ledger broadcast`.
+//!
+//! The server only accepts messages signed recently (5 minutes by default),
+//! so the envelope must be broadcast within that window.
+use crate::{address, wait_response, TargetCommandOpt};
+use clap::Parser;
+use coset::{CborSerializable, CoseSign1};
+use many_client::client::blocking::{block_on, ManyClient};
+use many_error::ManyError;
+use many_identity::verifiers::AnonymousVerifier;
+use many_identity::{Address, AnonymousIdentity, Identity};
+use many_identity_dsa::CoseKeyVerifier;
+use many_modules::ledger;
+use many_protocol::{
+    decode_response_from_cose_sign1, encode_cose_sign1_from_request, RequestMessageBuilder,
+};
+use many_types::ledger::TokenAmount;
+use many_types::{Memo, Timestamp};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::info;
+
+#[derive(Parser)]
+pub struct BroadcastOpt {
+    /// A signed envelope produced with `--offline`.
+    file: PathBuf,
+}
+
+/// Sign a message for the server and write its envelope to `path`.
+fn write_envelope(
+    key: &impl Identity,
+    server_id: Address,
+    method: &str,
+    argument: impl minicbor::Encode<()>,
+    path: &Path,
+) -> Result<(), ManyError> {
+    let message = RequestMessageBuilder::default()
+        .version(1)
+        .from(key.address())
+        .to(server_id)
+        .method(method.to_string())
+        .data(minicbor::to_vec(argument).map_err(ManyError::serialization_error)?)
+        .timestamp(Timestamp::now())
+        .build()
+        .map_err(|_| ManyError::internal_server_error())?;
+    let envelope = encode_cose_sign1_from_request(message, key)
+        .and_then(|e| e.to_vec().map_err(ManyError::serialization_error))?;
+    std::fs::write(path, envelope).map_err(ManyError::unknown)?;
+    info!("Signed {method} into {}.", path.display());
+    Ok(())
+}
+
+/// `ledger send --offline FILE`. The symbol must be an address, as local names
+/// cannot be resolved without the server.
+pub fn sign_send(
+    key: &impl Identity,
+    server_id: Address,
+    opts: TargetCommandOpt,
+    path: &Path,
+) -> Result<(), ManyError> {
+    let TargetCommandOpt {
+        account,
+        identity: to,
+        amount,
+        symbol,
+        memo,
+        ..
+    } = opts;
+    address::verify_recipient(&to)?;
+    let symbol = Address::from_str(&symbol).map_err(|_| {
+        ManyError::unknown(format!(
+            "Offline transactions need the address of the symbol, not '{symbol}'."
+        ))
+    })?;
+    let from = account.unwrap_or_else(|| key.address());
+    if from.is_anonymous() {
+        return Err(ManyError::invalid_identity());
+    }
+
+    let arguments = ledger::SendArgs {
+        from: Some(from),
+        to,
+        symbol,
+        amount: TokenAmount::from(amount),
+        memo: memo
+            .map(|m| Memo::try_from(m.as_str()))
+            .transpose()
+            .map_err(|_| ManyError::unknown("Invalid memo."))?,
+    };
+    write_envelope(key, server_id, "ledger.send", arguments, path)
+}
+
+/// `ledger broadcast FILE`: send a signed envelope and wait for its result.
+pub fn broadcast(server: String, server_id: Address, opts: BroadcastOpt) -> Result<(), ManyError> {
+    let bytes = std::fs::read(&opts.file).map_err(ManyError::unknown)?;
+    let envelope = CoseSign1::from_slice(&bytes).map_err(ManyError::deserialization_error)?;
+
+    let response = block_on(many_client::client::send_envelope(
+        server.as_str(),
+        envelope,
+    ))?;
+    let response =
+        decode_response_from_cose_sign1(&response, None, &(AnonymousVerifier, CoseKeyVerifier))?;
+
+    // Async results are polled anonymously, the envelope is already signed.
+    let client =
+        ManyClient::new(server, server_id, AnonymousIdentity).map_err(ManyError::unknown)?;
+    let payload = wait_response(&client, response)?;
+    println!("{}", minicbor::display(&payload));
+    Ok(())
+}
@@ -0,0 +1,353 @@
+use crate::{address, KeySource, SubCommand};
+use clap::{CommandFactory, Parser};
+use many_client::client::blocking::ManyClient;
+use many_error::ManyError;
+use many_identity::{Address, Identity};
+use many_modules::base;
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The commands of the REPL itself, completed along the ledger subcommands.
+const REPL_COMMANDS: &[&str] = &[
+    "identity",
+    "whoami",
+    "endpoints",
+    "call",
+    "cbor",
+    "history",
+    "help",
+    "exit",
+];
+
+const HELP: &str = "\
+Commands:
+    identity [PEM]           Use the identity in PEM for the next commands, or anonymous.
+    whoami                   Show the identity used to sign commands.
+    endpoints [PREFIX]       List the endpoints of the server, optionally filtered by prefix.
+    call ENDPOINT [HEX]      Call an endpoint with hex encoded CBOR arguments and show the response.
+    cbor HEX                 Decode and show hex encoded CBOR.
+    history                  Show the commands entered in this and previous sessions.
+    help                     Show this help.
+    exit                     Leave the REPL.
+
+Any other line is parsed as a `ledger` subcommand, e.g. `balance`, `send`, `token info`.
+Press TAB to complete commands, endpoints and the names of the address book.";
+
+#[derive(Parser)]
+pub struct ReplOpt {
+    /// A PEM file for the identity to start the session with. Defaults to the
+    /// identity of the command line, e.g. a PEM file, a key or an HSM.
+    #[clap(long)]
+    pem: Option<PathBuf>,
+
+    /// A JSON file mapping names to known addresses. The names are completed
+    /// to their address.
+    #[clap(long)]
+    address_book: Option<PathBuf>,
+}
+
+/// A ledger subcommand entered in the REPL.
+#[derive(Parser)]
+#[clap(no_binary_name = true)]
+struct ReplCommand {
+    #[clap(subcommand)]
+    subcommand: SubCommand,
+}
+
+struct Session {
+    server: String,
+    server_id: Address,
+    key: KeySource,
+    history: Vec<String>,
+}
+
+impl Session {
+    fn key(&self) -> Result<Box<dyn Identity>, ManyError> {
+        self.key.identity()
+    }
+
+    fn client(&self) -> Result<ManyClient<Box<dyn Identity>>, ManyError> {
+        ManyClient::new(self.server.clone(), self.server_id, self.key()?)
+            .map_err(ManyError::unknown)
+    }
+
+    fn execute(&mut self, line: &str) -> Result<bool, ManyError> {
+        let args = split_args(line)?;
+        let (command, rest) = match args.split_first() {
+            Some((command, rest)) => (command.as_str(), rest),
+            None => return Ok(true),
+        };
+
+        match (command, rest) {
+            ("exit" | "quit", _) => return Ok(false),
+            ("help", _) => println!("{HELP}"),
+            ("history", _) => {
+                for (i, line) in self.history.iter().enumerate() {
+                    println!("{:>5}  {line}", i + 1);
+                }
+            }
+            ("identity", []) => {
+                self.key = KeySource::Anonymous;
+                println!("{}", Address::anonymous());
+            }
+            ("identity", [pem]) => {
+                let key = KeySource::Pem(PathBuf::from(pem));
+                println!("{}", key.identity()?.address());
+                self.key = key;
+            }
+            ("whoami", _) => println!("{}", self.key()?.address()),
+            ("endpoints", prefix) => {
+                let client = self.client()?;
+                let endpoints: base::Endpoints = minicbor::decode(&client.call_("endpoints", ())?)
+                    .map_err(ManyError::deserialization_error)?;
+                let prefix = prefix.first().map(String::as_str).unwrap_or_default();
+                for endpoint in endpoints.0.iter().filter(|e| e.starts_with(prefix)) {
+                    println!("{endpoint}");
+                }
+            }
+            ("call", [endpoint, args @ ..]) => {
+                let argument = match args {
+                    [] => minicbor::to_vec(()).map_err(ManyError::serialization_error)?,
+                    [hex] => hex::decode(hex).map_err(ManyError::deserialization_error)?,
+                    _ => return Err(ManyError::unknown("Too many arguments to `call`.")),
+                };
+                let client = self.client()?;
+                let response = client.call_raw(endpoint.as_str(), &argument)?;
+                let payload = crate::wait_response(&client, response)?;
+                println!("{}", minicbor::display(&payload));
+            }
+            ("cbor", [hex]) => {
+                let bytes = hex::decode(hex).map_err(ManyError::deserialization_error)?;
+                println!("{}", minicbor::display(&bytes));
+            }
+            _ => {
+                let ReplCommand { subcommand } = ReplCommand::try_parse_from(&args)
+                    .map_err(|e| ManyError::unknown(e.to_string()))?;
+                if let SubCommand::Repl(_)
+                | SubCommand::Completions(_)
+                | SubCommand::Broadcast(_)
+                | SubCommand::Keys(_) = subcommand
+                {
+                    return Err(ManyError::unknown("Not available in a REPL session."));
+                }
+                let client = self.client()?;
+                let address = self.key()?.address();
+                crate::run(client, address, subcommand)?;
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Completes the commands, the endpoints of the server and the contacts of the
+/// address book.
+struct ReplHelper {
+    endpoints: Vec<String>,
+    contacts: BTreeMap<String, Address>,
+}
+
+impl ReplHelper {
+    /// The names which can follow the `words` already entered.
+    fn commands(&self, words: &[&str]) -> Vec<String> {
+        match words {
+            [] => {
+                let mut names: Vec<String> =
+                    REPL_COMMANDS.iter().map(ToString::to_string).collect();
+                names.extend(subcommands(&ReplCommand::command(), &[]));
+                names
+            }
+            ["call" | "endpoints"] => self.endpoints.clone(),
+            [command, rest @ ..] if !REPL_COMMANDS.contains(command) => {
+                let mut path = vec![*command];
+                path.extend(rest);
+                subcommands(&ReplCommand::command(), &path)
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// The names of the subcommands of the subcommand at `path`.
+fn subcommands(command: &clap::Command, path: &[&str]) -> Vec<String> {
+    match path.split_first() {
+        None => command
+            .get_subcommands()
+            .filter(|c| !c.is_hide_set())
+            .map(|c| c.get_name().to_string())
+            .collect(),
+        Some((name, rest)) => command
+            .find_subcommand(name)
+            .map(|c| subcommands(c, rest))
+            .unwrap_or_default(),
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let word = &line[start..];
+        let words: Vec<&str> = line[..start].split_whitespace().collect();
+
+        let mut candidates: Vec<Pair> = self
+            .commands(&words)
+            .into_iter()
+            .filter(|name| name.starts_with(word))
+            .map(|name| Pair {
+                display: name.clone(),
+                replacement: name,
+            })
+            .collect();
+        if !words.is_empty() {
+            candidates.extend(
+                self.contacts
+                    .iter()
+                    .filter(|(name, _)| name.starts_with(word))
+                    .map(|(name, address)| Pair {
+                        display: format!("{name} ({address})"),
+                        replacement: address.to_string(),
+                    }),
+            );
+        }
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+/// The file keeping the lines entered in the REPL across sessions.
+fn history_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("MANY_LEDGER_HISTORY") {
+        return Some(PathBuf::from(path));
+    }
+    std::env::var_os("HOME").map(|home| Path::new(&home).join(".many").join("ledger_history"))
+}
+
+/// Split a line into arguments, the way a shell would for simple quoting.
+fn split_args(line: &str) -> Result<Vec<String>, ManyError> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                current.get_or_insert_with(String::new);
+            }
+            (None | Some('"'), '\\') => {
+                if let Some(escaped) = chars.next() {
+                    current.get_or_insert_with(String::new).push(escaped);
+                }
+            }
+            (None, c) if c.is_whitespace() => args.extend(current.take()),
+            (_, c) => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    if quote.is_some() {
+        return Err(ManyError::unknown("Unterminated quote."));
+    }
+    args.extend(current);
+    Ok(args)
+}
+
+pub fn repl(
+    server: String,
+    server_id: Address,
+    key: KeySource,
+    opts: ReplOpt,
+) -> Result<(), ManyError> {
+    let ReplOpt { pem, address_book } = opts;
+    let contacts = address_book
+        .as_deref()
+        .map(address::read_address_book)
+        .transpose()?
+        .unwrap_or_default();
+
+    let mut session = Session {
+        server,
+        server_id,
+        key: pem.map_or(key, KeySource::Pem),
+        history: Vec::new(),
+    };
+
+    // The endpoints are only used for completion, the server might not be
+    // reachable yet.
+    let endpoints = session
+        .client()
+        .and_then(|client| client.call_("endpoints", ()))
+        .and_then(|bytes| {
+            minicbor::decode::<base::Endpoints>(&bytes).map_err(ManyError::deserialization_error)
+        })
+        .map(|endpoints| endpoints.0.into_iter().collect())
+        .unwrap_or_default();
+
+    let mut editor = Editor::<ReplHelper>::new().map_err(ManyError::unknown)?;
+    editor.set_helper(Some(ReplHelper {
+        endpoints,
+        contacts,
+    }));
+    let history = history_path();
+    if let Some(path) = &history {
+        // There is no history on the first session.
+        let _ = editor.load_history(path);
+        session.history = editor.history().iter().cloned().collect();
+    }
+
+    println!(
+        "Connected to {}. Type `help` for a list of commands.",
+        session.server
+    );
+    loop {
+        let line = match editor.readline("ledger> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(ManyError::unknown(e)),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        editor.add_history_entry(line);
+        session.history.push(line.to_string());
+
+        match session.execute(line) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+
+    if let Some(path) = &history {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(ManyError::unknown)?;
+        }
+        editor.save_history(path).map_err(ManyError::unknown)?;
+    }
+    Ok(())
+}
@@ -248,7 +248,7 @@ fn create_token(client: ManyClient<impl Identity>, opts: CreateTokenOpt) -> Resu
         memo: opts.memo,
     };
     let response = client.call("tokens.create", args)?;
-    let payload = crate::wait_response(client, response)?;
+    let payload = crate::wait_response(&client, response)?;
     let result: TokenCreateReturns =
         minicbor::decode(&payload).map_err(|e| ManyError::deserialization_error(e.to_string()))?;
 
@@ -266,7 +266,7 @@ fn update_token(client: ManyClient<impl Identity>, opts: UpdateTokenOpt) -> Resu
         memo: opts.memo,
     };
     let response = client.call("tokens.update", args)?;
-    let payload = crate::wait_response(client, response)?;
+    let payload = crate::wait_response(&client, response)?;
     let _result: TokenUpdateReturns =
         minicbor::decode(&payload).map_err(|e| ManyError::deserialization_error(e.to_string()))?;
 
@@ -282,7 +282,7 @@ fn add_ext_info(client: ManyClient<impl Identity>, opts: AddExtInfoOpt) -> Resul
         memo: opts.memo,
     };
     let response = client.call("tokens.addExtendedInfo", args)?;
-    let payload = crate::wait_response(client, response)?;
+    let payload = crate::wait_response(&client, response)?;
     let _result: TokenAddExtendedInfoReturns =
         minicbor::decode(&payload).map_err(|e| ManyError::deserialization_error(e.to_string()))?;
     Ok(())
@@ -298,7 +298,7 @@ fn remove_ext_info(
         memo: opts.memo,
     };
     let response = client.call("tokens.removeExtendedInfo", args)?;
-    let payload = crate::wait_response(client, response)?;
+    let payload = crate::wait_response(&client, response)?;
     let _result: TokenRemoveExtendedInfoReturns =
         minicbor::decode(&payload).map_err(|e| ManyError::deserialization_error(e.to_string()))?;
     Ok(())
@@ -310,7 +310,7 @@ fn info_token(client: ManyClient<impl Identity>, opts: InfoOpt) -> Result<(), Ma
         extended_info: opts.indices,
     };
     let response = client.call("tokens.info", args)?;
-    let payload = crate::wait_response(client, response)?;
+    let payload = crate::wait_response(&client, response)?;
     let result: TokenInfoReturns =
         minicbor::decode(&payload).map_err(|e| ManyError::deserialization_error(e.to_string()))?;
 
@@ -339,7 +339,7 @@ fn mint_token(client: ManyClient<impl Identity>, opts: MintOpt) -> Result<(), Ma
         memo: opts.memo,
     };
     let response = client.call("tokens.mint", args)?;
-    let payload = crate::wait_response(client, response)?;
+    let payload = crate::wait_response(&client, response)?;
     let result: TokenMintReturns =
         minicbor::decode(&payload).map_err(|e| ManyError::deserialization_error(e.to_string()))?;
 
@@ -369,7 +369,7 @@ fn burn_token(client: ManyClient<impl Identity>, opts: BurnOpt) -> Result<(), Ma
         error_on_under_burn: Some(opts.error_on_under_burn),
     };
     let response = client.call("tokens.burn", args)?;
-    let payload = crate::wait_response(client, response)?;
+    let payload = crate::wait_response(&client, response)?;
     let result: TokenBurnReturns =
         minicbor::decode(&payload).map_err(|e| ManyError::deserialization_error(e.to_string()))?;
 
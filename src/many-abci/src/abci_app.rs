@@ -1,13 +1,25 @@
+use crate::check_tx::CheckTxArgs;
+use crate::events::{tx_events, TxEventsReturns};
+use crate::priority::PriorityLane;
+use crate::snapshot::{
+    ApplySnapshotChunkArgs, ListSnapshotsReturns, LoadSnapshotChunkArgs, LoadSnapshotChunkReturns,
+    OfferSnapshotArgs,
+};
+use crate::validators::ValidatorUpdatesReturns;
 use coset::{CborSerializable, CoseSign1};
 use many_client::client::blocking::{block_on, ManyClient};
 use many_error::ManyError;
+use many_identity::verifiers::AnonymousVerifier;
 use many_identity::{Address, AnonymousIdentity};
-use many_modules::abci_backend::{AbciBlock, AbciCommitInfo, AbciInfo};
-use many_protocol::ResponseMessage;
+use many_identity_dsa::CoseKeyVerifier;
+use many_identity_webauthn::WebAuthnVerifier;
+use many_modules::abci_backend::{AbciBlock, AbciCommitInfo, AbciInfo, AbciInit, EndpointInfo};
+use many_protocol::{decode_request_from_cose_sign1, ManyUrl, ResponseMessage};
 use reqwest::{IntoUrl, Url};
+use std::collections::BTreeMap;
 use tendermint_abci::Application;
 use tendermint_proto::abci::*;
-use tracing::debug;
+use tracing::{debug, warn};
 
 lazy_static::lazy_static!(
     static ref EPOCH: many_types::Timestamp = many_types::Timestamp::new(0).unwrap();
@@ -18,6 +30,11 @@ pub struct AbciApp {
     app_name: String,
     many_client: ManyClient<AnonymousIdentity>,
     many_url: Url,
+    priority_lane: Option<PriorityLane>,
+
+    /// The endpoints of the backend, to reject transactions to unknown ones.
+    endpoints: BTreeMap<String, EndpointInfo>,
+    allow_origin: Option<Vec<ManyUrl>>,
 }
 
 impl AbciApp {
@@ -38,13 +55,87 @@ impl AbciApp {
         let many_client = ManyClient::new(many_url.clone(), server_id, AnonymousIdentity)?;
         let status = many_client.status().map_err(|x| x.to_string())?;
         let app_name = status.name;
+        let AbciInit { endpoints } = many_client
+            .call_("abci.init", ())
+            .and_then(|payload| {
+                minicbor::decode(&payload).map_err(ManyError::deserialization_error)
+            })
+            .map_err(|x| x.to_string())?;
 
         Ok(Self {
             app_name,
             many_url,
             many_client,
+            priority_lane: None,
+            endpoints,
+            allow_origin: None,
         })
     }
+
+    /// The origins accepted in the WebAuthn signatures of transactions.
+    pub fn with_allow_origin(self, allow_origin: Option<Vec<ManyUrl>>) -> Self {
+        Self {
+            allow_origin,
+            ..self
+        }
+    }
+
+    /// Verify the signature and the endpoint of a transaction, then let the
+    /// backend precheck it against its current state.
+    fn validate_tx(&self, envelope: &CoseSign1) -> Result<(), ManyError> {
+        let message = decode_request_from_cose_sign1(
+            envelope,
+            &(
+                AnonymousVerifier,
+                CoseKeyVerifier,
+                WebAuthnVerifier::new(self.allow_origin.clone()),
+            ),
+        )?;
+        match self.endpoints.get(&message.method) {
+            Some(info) if info.is_command => {}
+            _ => return Err(ManyError::invalid_method_name(message.method)),
+        }
+
+        let args = CheckTxArgs {
+            from: message.from(),
+            method: message.method.clone(),
+            data: message.data.clone().into(),
+        };
+        match self.many_client.call_("abci.checkTx", args) {
+            Ok(_) => Ok(()),
+            // The backend has no precheck.
+            Err(err) if err.code() == ManyError::invalid_method_name("").code() => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Reserve a fraction of each block for designated identities, see [`PriorityLane`].
+    pub fn with_priority_lane(self, priority_lane: Option<PriorityLane>) -> Self {
+        Self {
+            priority_lane,
+            ..self
+        }
+    }
+
+    /// The Tendermint events of the transaction just delivered, see
+    /// [`crate::events`].
+    fn tx_events(&self) -> Vec<Event> {
+        match self
+            .many_client
+            .call_("abci.txEvents", ())
+            .and_then(|payload| {
+                minicbor::decode::<TxEventsReturns>(&payload)
+                    .map_err(ManyError::deserialization_error)
+            }) {
+            Ok(returns) => tx_events(returns.events),
+            Err(err) => {
+                if err.code() != ManyError::invalid_method_name("").code() {
+                    warn!("Could not get the events of the transaction: {err}");
+                }
+                vec![]
+            }
+        }
+    }
 }
 
 impl Application for AbciApp {
@@ -128,6 +219,36 @@ impl Application for AbciApp {
         ResponseBeginBlock { events: vec![] }
     }
 
+    fn check_tx(&self, request: RequestCheckTx) -> ResponseCheckTx {
+        let cose = match CoseSign1::from_slice(&request.tx) {
+            Ok(x) => x,
+            Err(err) => {
+                return ResponseCheckTx {
+                    code: 2,
+                    log: err.to_string(),
+                    ..Default::default()
+                }
+            }
+        };
+        if let Err(err) = self.validate_tx(&cose) {
+            return ResponseCheckTx {
+                code: 1,
+                log: err.to_string(),
+                ..Default::default()
+            };
+        }
+
+        let priority = match &self.priority_lane {
+            Some(lane) => lane.priority_of(&cose, request.tx.len() as u64),
+            None => Default::default(),
+        };
+
+        ResponseCheckTx {
+            priority,
+            ..Default::default()
+        }
+    }
+
     fn deliver_tx(&self, request: RequestDeliverTx) -> ResponseDeliverTx {
         let cose = match CoseSign1::from_slice(&request.tx) {
             Ok(x) => x,
@@ -154,10 +275,19 @@ impl Application for AbciApp {
                 // The timestamp MIGHT differ between two nodes so we just force it to be 0.
                 response.timestamp = Some(*EPOCH);
 
+                // Always taken, so the events are not left for the next transaction.
+                let events = self.tx_events();
                 if let Ok(data) = response.to_bytes() {
+                    // Failed transactions did not transfer anything.
+                    let events = if response.data.is_ok() {
+                        events
+                    } else {
+                        vec![]
+                    };
                     ResponseDeliverTx {
                         code: 0,
                         data: data.into(),
+                        events,
                         ..Default::default()
                     }
                 } else {
@@ -178,7 +308,27 @@ impl Application for AbciApp {
 
     fn end_block(&self, _request: RequestEndBlock) -> ResponseEndBlock {
         let _ = self.many_client.call_("abci.endBlock", ());
-        Default::default()
+
+        let validator_updates = match self
+            .many_client
+            .call_("abci.validatorUpdates", ())
+            .and_then(|payload| {
+                minicbor::decode::<ValidatorUpdatesReturns>(&payload)
+                    .map_err(ManyError::deserialization_error)
+            }) {
+            Ok(returns) => returns.updates.into_iter().map(Into::into).collect(),
+            Err(err) => {
+                if err.code() != ManyError::invalid_method_name("").code() {
+                    warn!("Could not get the validator updates: {err}");
+                }
+                vec![]
+            }
+        };
+
+        ResponseEndBlock {
+            validator_updates,
+            ..Default::default()
+        }
     }
 
     fn flush(&self) -> ResponseFlush {
@@ -186,6 +336,10 @@ impl Application for AbciApp {
     }
 
     fn commit(&self) -> ResponseCommit {
+        if let Some(lane) = &self.priority_lane {
+            lane.reset();
+        }
+
         self.many_client.call_("abci.commit", ()).map_or_else(
             |err| ResponseCommit {
                 data: err.to_string().into_bytes().into(),
@@ -200,4 +354,98 @@ impl Application for AbciApp {
             },
         )
     }
+
+    fn list_snapshots(&self) -> ResponseListSnapshots {
+        let snapshots = self
+            .many_client
+            .call_("abci.listSnapshots", ())
+            .and_then(|payload| {
+                minicbor::decode::<ListSnapshotsReturns>(&payload)
+                    .map_err(ManyError::deserialization_error)
+            })
+            .map_or_else(
+                |err| {
+                    debug!("No snapshot listed: {err}");
+                    vec![]
+                },
+                |returns| returns.snapshots.into_iter().map(Into::into).collect(),
+            );
+
+        ResponseListSnapshots { snapshots }
+    }
+
+    fn offer_snapshot(&self, request: RequestOfferSnapshot) -> ResponseOfferSnapshot {
+        let snapshot = match request.snapshot {
+            Some(snapshot) => snapshot,
+            None => {
+                return ResponseOfferSnapshot {
+                    result: response_offer_snapshot::Result::Reject.into(),
+                }
+            }
+        };
+
+        let args = OfferSnapshotArgs {
+            snapshot: snapshot.into(),
+            app_hash: request.app_hash.to_vec().into(),
+        };
+        let result = match self.many_client.call_("abci.offerSnapshot", args) {
+            Ok(_) => response_offer_snapshot::Result::Accept,
+            Err(err) => {
+                warn!("Snapshot rejected: {err}");
+                response_offer_snapshot::Result::Reject
+            }
+        };
+        ResponseOfferSnapshot {
+            result: result.into(),
+        }
+    }
+
+    fn load_snapshot_chunk(&self, request: RequestLoadSnapshotChunk) -> ResponseLoadSnapshotChunk {
+        let args = LoadSnapshotChunkArgs {
+            height: request.height,
+            format: request.format,
+            chunk: request.chunk,
+        };
+        let chunk = self
+            .many_client
+            .call_("abci.loadSnapshotChunk", args)
+            .and_then(|payload| {
+                minicbor::decode::<LoadSnapshotChunkReturns>(&payload)
+                    .map_err(ManyError::deserialization_error)
+            })
+            .map_or_else(
+                |err| {
+                    warn!("Could not load snapshot chunk: {err}");
+                    vec![]
+                },
+                |returns| returns.chunk.to_vec(),
+            );
+
+        ResponseLoadSnapshotChunk {
+            chunk: chunk.into(),
+        }
+    }
+
+    fn apply_snapshot_chunk(
+        &self,
+        request: RequestApplySnapshotChunk,
+    ) -> ResponseApplySnapshotChunk {
+        let args = ApplySnapshotChunkArgs {
+            index: request.index,
+            chunk: request.chunk.to_vec().into(),
+        };
+        // The backend verifies the chunks as they are applied, so an invalid
+        // chunk invalidates the whole snapshot.
+        let result = match self.many_client.call_("abci.applySnapshotChunk", args) {
+            Ok(_) => response_apply_snapshot_chunk::Result::Accept,
+            Err(err) => {
+                warn!("Snapshot chunk {} rejected: {err}", request.index);
+                response_apply_snapshot_chunk::Result::RejectSnapshot
+            }
+        };
+        ResponseApplySnapshotChunk {
+            result: result.into(),
+            ..Default::default()
+        }
+    }
 }
@@ -0,0 +1,19 @@
+//! The mempool precheck call to the backend. Backends without a precheck
+//! simply do not implement `abci.checkTx`, and transactions are then only
+//! checked for their signature and endpoint.
+use many_identity::Address;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct CheckTxArgs {
+    #[n(0)]
+    pub from: Address,
+
+    #[n(1)]
+    pub method: String,
+
+    #[n(2)]
+    pub data: ByteVec,
+}
@@ -0,0 +1,85 @@
+//! The Tendermint events of delivered transactions, so that indexers and
+//! `tx_search` queries (e.g. `transfer.recipient='m...'`) work without decoding
+//! MANY messages.
+//!
+//! The events are derived from the backend events logged by the transaction,
+//! which the backend returns from `abci.txEvents`. Every command moving funds
+//! is covered, including the transfers of multisig executions. Backends which
+//! do not move funds simply do not implement `abci.txEvents`.
+use many_modules::events::EventInfo;
+use minicbor::{Decode, Encode};
+use tendermint_proto::abci::{Event, EventAttribute};
+
+/// The type of the Tendermint events of token transfers.
+pub const TRANSFER_EVENT: &str = "transfer";
+
+/// The type of the Tendermint events of minted tokens.
+pub const MINT_EVENT: &str = "mint";
+
+/// The type of the Tendermint events of burned tokens.
+pub const BURN_EVENT: &str = "burn";
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct TxEventsReturns {
+    #[n(0)]
+    pub events: Vec<EventInfo>,
+}
+
+fn attribute(key: &str, value: String) -> EventAttribute {
+    EventAttribute {
+        key: key.as_bytes().to_vec().into(),
+        value: value.into_bytes().into(),
+        index: true,
+    }
+}
+
+/// The Tendermint events of the backend events logged by a transaction.
+pub fn tx_events(events: Vec<EventInfo>) -> Vec<Event> {
+    let mut tx_events = vec![];
+    for event in events {
+        match event {
+            EventInfo::Send {
+                from,
+                to,
+                symbol,
+                amount,
+                ..
+            } => tx_events.push(Event {
+                r#type: TRANSFER_EVENT.to_string(),
+                attributes: vec![
+                    attribute("sender", from.to_string()),
+                    attribute("recipient", to.to_string()),
+                    attribute("symbol", symbol.to_string()),
+                    attribute("amount", amount.to_string()),
+                ],
+            }),
+            EventInfo::TokenMint {
+                symbol,
+                distribution,
+                ..
+            } => tx_events.extend(distribution.into_iter().map(|(recipient, amount)| Event {
+                r#type: MINT_EVENT.to_string(),
+                attributes: vec![
+                    attribute("recipient", recipient.to_string()),
+                    attribute("symbol", symbol.to_string()),
+                    attribute("amount", amount.to_string()),
+                ],
+            })),
+            EventInfo::TokenBurn {
+                symbol,
+                distribution,
+                ..
+            } => tx_events.extend(distribution.into_iter().map(|(sender, amount)| Event {
+                r#type: BURN_EVENT.to_string(),
+                attributes: vec![
+                    attribute("sender", sender.to_string()),
+                    attribute("symbol", symbol.to_string()),
+                    attribute("amount", amount.to_string()),
+                ],
+            })),
+            _ => {}
+        }
+    }
+    tx_events
+}
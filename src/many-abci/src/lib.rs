@@ -1,3 +1,8 @@
 pub mod abci_app;
+pub mod check_tx;
+pub mod events;
 pub mod many_app;
 pub mod module;
+pub mod priority;
+pub mod snapshot;
+pub mod validators;
@@ -17,12 +17,18 @@ use tracing::{debug, error, info, trace};
 use tracing_subscriber::filter::LevelFilter;
 
 mod abci_app;
+mod check_tx;
+mod events;
 mod many_app;
 mod module;
+mod priority;
+mod snapshot;
+mod validators;
 
 use abci_app::AbciApp;
-use many_app::AbciModuleMany;
+use many_app::{AbciModuleMany, DEFAULT_MAX_CONCURRENT_QUERIES};
 use module::AbciBlockchainModuleImpl;
+use priority::PriorityLane;
 
 #[derive(clap::ArgEnum, Clone, Debug)]
 enum LogStrategy {
@@ -79,6 +85,33 @@ struct Opts {
     /// Any addresses will be able to execute queries, e.g., balance, get, ...
     #[clap(long)]
     allow_addrs: Option<PathBuf>,
+
+    /// Path to a JSON file containing an array of MANY addresses whose
+    /// transactions get a higher priority in the mempool, e.g., oracle feeders.
+    /// Requires the prioritized mempool of Tendermint (`mempool.version = "v1"`).
+    #[clap(long)]
+    priority_addrs: Option<PathBuf>,
+
+    /// Fraction of each block reserved for the transactions of `--priority-addrs`.
+    #[clap(long, default_value = "0.25")]
+    priority_fraction: f64,
+
+    /// Maximum size of a block, in bytes. Must match the `block.max_bytes`
+    /// consensus parameter of Tendermint.
+    #[clap(long, default_value = "22020096")]
+    block_max_bytes: u64,
+
+    /// Send queries directly to `--many-app` instead of through the ABCI query
+    /// connection of Tendermint. Queries no longer wait behind each other on
+    /// that connection, but the MANY application may still execute them one
+    /// at a time, interleaved with the blocks.
+    #[clap(long)]
+    direct_queries: bool,
+
+    /// Maximum number of queries forwarded to the MANY application at the
+    /// same time.
+    #[clap(long, default_value_t = DEFAULT_MAX_CONCURRENT_QUERIES)]
+    max_concurrent_queries: usize,
 }
 
 #[tokio::main]
@@ -95,6 +128,11 @@ async fn main() {
         allow_origin,
         logmode,
         allow_addrs,
+        priority_addrs,
+        priority_fraction,
+        block_max_bytes,
+        direct_queries,
+        max_concurrent_queries,
     } = Opts::parse();
 
     let verbose_level = 2 + verbose - quiet;
@@ -158,8 +196,23 @@ async fn main() {
         std::thread::sleep(std::time::Duration::from_secs(1));
     };
 
+    let priority_lane = priority_addrs.map(|path| {
+        let addrs: BTreeSet<Address> =
+            json5::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        PriorityLane::new(addrs, priority_fraction, block_max_bytes)
+    });
+
+    let query_url = if direct_queries {
+        Some(reqwest::Url::parse(&many_app).expect("Invalid --many-app URL"))
+    } else {
+        None
+    };
+    let app_allow_origin = allow_origin.clone();
     let abci_app = tokio::task::spawn_blocking(move || {
-        AbciApp::create(many_app, Address::anonymous()).unwrap()
+        AbciApp::create(many_app, Address::anonymous())
+            .unwrap()
+            .with_priority_lane(priority_lane)
+            .with_allow_origin(app_allow_origin)
     })
     .await
     .unwrap();
@@ -207,7 +260,9 @@ async fn main() {
         allowed_addrs,
         allow_origin,
     )
-    .await;
+    .await
+    .with_query_url(query_url)
+    .with_max_concurrent_queries(max_concurrent_queries);
     let blockchain_impl = Arc::new(Mutex::new(AbciBlockchainModuleImpl::new(abci_client)));
 
     {
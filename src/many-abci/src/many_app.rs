@@ -15,10 +15,22 @@ use many_protocol::{
 use many_server::transport::LowLevelManyRequestHandler;
 use many_types::attributes::Attribute;
 use many_types::cbor::CborAny;
+use reqwest::Url;
+use sha2::{Digest, Sha256};
 use std::collections::{BTreeMap, BTreeSet};
 use std::default::Default;
 use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
 use tendermint_rpc::Client;
+use tokio::sync::Semaphore;
+
+/// The hash of a transaction, as computed by Tendermint.
+fn tx_hash(tx: &[u8]) -> [u8; 32] {
+    Sha256::digest(tx).into()
+}
+
+/// The default number of queries forwarded to the backend at the same time.
+pub const DEFAULT_MAX_CONCURRENT_QUERIES: usize = 16;
 
 pub struct AbciModuleMany<C: Client> {
     client: C,
@@ -27,6 +39,11 @@ pub struct AbciModuleMany<C: Client> {
     backend_endpoints: BTreeMap<String, EndpointInfo>,
     allow_addrs: Option<BTreeSet<Address>>,
     allow_origin: Option<Vec<ManyUrl>>,
+
+    /// When set, queries are sent directly to the backend instead of going
+    /// through the (sequential) ABCI query connection of Tendermint.
+    query_url: Option<Url>,
+    query_permits: Arc<Semaphore>,
 }
 
 impl<C: Client + Sync> AbciModuleMany<C> {
@@ -68,6 +85,45 @@ impl<C: Client + Sync> AbciModuleMany<C> {
             backend_endpoints: init_message.endpoints,
             allow_addrs,
             allow_origin,
+            query_url: None,
+            query_permits: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_QUERIES)),
+        }
+    }
+
+    /// Send queries directly to the backend MANY server at `url`, so a slow
+    /// query does not hold the ABCI query connection of Tendermint. The backend
+    /// may still serialize the queries with the execution of the blocks.
+    pub fn with_query_url(mut self, url: Option<Url>) -> Self {
+        self.query_url = url;
+        self
+    }
+
+    /// Bound the number of queries executed concurrently. Queries over the
+    /// limit wait for a permit before being forwarded.
+    pub fn with_max_concurrent_queries(mut self, max: usize) -> Self {
+        self.query_permits = Arc::new(Semaphore::new(max.max(1)));
+        self
+    }
+
+    async fn query(&self, envelope: CoseSign1, data: Vec<u8>) -> Result<CoseSign1, ManyError> {
+        let _permit = self
+            .query_permits
+            .acquire()
+            .await
+            .map_err(ManyError::unknown)?;
+
+        if let Some(url) = &self.query_url {
+            many_client::client::send_envelope(url.clone(), envelope)
+                .await
+                .map_err(ManyError::unexpected_transport_error)
+        } else {
+            let response = self
+                .client
+                .abci_query(None, data, None, false)
+                .await
+                .map_err(ManyError::unexpected_transport_error)?;
+
+            CoseSign1::from_slice(&response.value).map_err(ManyError::unexpected_transport_error)
         }
     }
 
@@ -94,30 +150,32 @@ impl<C: Client + Sync> AbciModuleMany<C> {
                     return Err(ManyError::invalid_from_identity());
                 }
 
-                let response = self
-                    .client
-                    .broadcast_tx_sync(tendermint_rpc::abci::Transaction::from(data))
-                    .await
-                    .map_err(ManyError::unexpected_transport_error)?;
+                // A command re-sent after it was committed (e.g. after a
+                // timeout) points to its original result instead of being
+                // broadcast again.
+                let hash = tendermint_rpc::abci::transaction::Hash::new(tx_hash(&data));
+                let hash = match self.client.tx(hash, false).await {
+                    Ok(tx) => tx.hash,
+                    Err(_) => {
+                        self.client
+                            .broadcast_tx_sync(tendermint_rpc::abci::Transaction::from(data))
+                            .await
+                            .map_err(ManyError::unexpected_transport_error)?
+                            .hash
+                    }
+                };
 
                 // A command will always return an empty payload with an ASYNC attribute.
                 let response =
                     ResponseMessage::from_request(&message, &self.identity.address(), Ok(vec![]))
                         .with_attribute(
                             many_modules::r#async::attributes::ASYNC
-                                .with_argument(CborAny::Bytes(response.hash.as_bytes().to_vec())),
+                                .with_argument(CborAny::Bytes(hash.as_bytes().to_vec())),
                         );
                 encode_cose_sign1_from_response(response, &self.identity)
                     .map_err(ManyError::unexpected_transport_error)
             } else {
-                let response = self
-                    .client
-                    .abci_query(None, data, None, false)
-                    .await
-                    .map_err(ManyError::unexpected_transport_error)?;
-
-                CoseSign1::from_slice(&response.value)
-                    .map_err(ManyError::unexpected_transport_error)
+                self.query(envelope, data).await
             }
         } else {
             Err(ManyError::invalid_method_name(message.method))
@@ -0,0 +1,68 @@
+use coset::CoseSign1;
+use many_identity::verifiers::AnonymousVerifier;
+use many_identity::Address;
+use many_identity_dsa::CoseKeyVerifier;
+use many_protocol::decode_request_from_cose_sign1;
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+
+/// Mempool priority of transactions in the priority lane.
+pub const PRIORITY_LANE: i64 = 1;
+
+/// Mempool priority of every other transaction.
+pub const DEFAULT_LANE: i64 = 0;
+
+/// Gives a higher mempool priority to transactions signed by designated
+/// identities (oracle feeders, governance executors, ...), up to a fraction of
+/// the block size. Past that fraction, their transactions compete with the
+/// user traffic until the next block is committed.
+///
+/// This only orders the mempool, so Tendermint must run the prioritized mempool
+/// (`mempool.version = "v1"`). Blocks are not validated against the lane, and
+/// nodes do not need to agree on it.
+#[derive(Clone, Debug)]
+pub struct PriorityLane {
+    addrs: BTreeSet<Address>,
+
+    /// Maximum number of bytes of priority transactions per block.
+    max_bytes: u64,
+
+    /// Number of bytes of priority transactions admitted since the last commit.
+    used_bytes: Arc<Mutex<u64>>,
+}
+
+impl PriorityLane {
+    pub fn new(addrs: BTreeSet<Address>, fraction: f64, block_max_bytes: u64) -> Self {
+        Self {
+            addrs,
+            max_bytes: (block_max_bytes as f64 * fraction.clamp(0.0, 1.0)) as u64,
+            used_bytes: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    /// The mempool priority of a transaction. Only transactions with a valid
+    /// signature from a designated identity can enter the priority lane.
+    pub fn priority_of(&self, envelope: &CoseSign1, size: u64) -> i64 {
+        let from =
+            match decode_request_from_cose_sign1(envelope, &(AnonymousVerifier, CoseKeyVerifier)) {
+                Ok(message) => message.from(),
+                Err(_) => return DEFAULT_LANE,
+            };
+        if !self.addrs.contains(&from) {
+            return DEFAULT_LANE;
+        }
+
+        let mut used_bytes = self.used_bytes.lock().unwrap();
+        if *used_bytes + size > self.max_bytes {
+            return DEFAULT_LANE;
+        }
+        *used_bytes += size;
+        PRIORITY_LANE
+    }
+
+    /// Start a new block. Transactions left in the mempool are rechecked after
+    /// a commit, so they are counted again.
+    pub fn reset(&self) {
+        *self.used_bytes.lock().unwrap() = 0;
+    }
+}
@@ -0,0 +1,92 @@
+//! The messages of the state sync calls to the backend. Backends without
+//! snapshot support simply do not implement the `abci.*Snapshot*` endpoints,
+//! and the bridge then has no snapshot to offer or restore.
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+use tendermint_proto::abci::Snapshot;
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct SnapshotInfo {
+    #[n(0)]
+    pub height: u64,
+
+    #[n(1)]
+    pub format: u32,
+
+    #[n(2)]
+    pub chunks: u32,
+
+    #[n(3)]
+    pub hash: ByteVec,
+}
+
+impl From<SnapshotInfo> for Snapshot {
+    fn from(info: SnapshotInfo) -> Self {
+        Snapshot {
+            height: info.height,
+            format: info.format,
+            chunks: info.chunks,
+            hash: info.hash.to_vec().into(),
+            metadata: Default::default(),
+        }
+    }
+}
+
+impl From<Snapshot> for SnapshotInfo {
+    fn from(snapshot: Snapshot) -> Self {
+        SnapshotInfo {
+            height: snapshot.height,
+            format: snapshot.format,
+            chunks: snapshot.chunks,
+            hash: snapshot.hash.to_vec().into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct ListSnapshotsReturns {
+    #[n(0)]
+    pub snapshots: Vec<SnapshotInfo>,
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct OfferSnapshotArgs {
+    #[n(0)]
+    pub snapshot: SnapshotInfo,
+
+    #[n(1)]
+    pub app_hash: ByteVec,
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct LoadSnapshotChunkArgs {
+    #[n(0)]
+    pub height: u64,
+
+    #[n(1)]
+    pub format: u32,
+
+    #[n(2)]
+    pub chunk: u32,
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct LoadSnapshotChunkReturns {
+    #[n(0)]
+    pub chunk: ByteVec,
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct ApplySnapshotChunkArgs {
+    #[n(0)]
+    pub index: u32,
+
+    #[n(1)]
+    pub chunk: ByteVec,
+}
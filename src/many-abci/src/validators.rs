@@ -0,0 +1,39 @@
+//! The validator updates returned to Tendermint from `EndBlock`. Backends
+//! without validator management simply do not implement
+//! `abci.validatorUpdates`, and the validator set stays the one of the
+//! Tendermint genesis.
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+use tendermint_proto::abci::ValidatorUpdate;
+use tendermint_proto::crypto::{public_key, PublicKey};
+
+/// A change of the validator set.
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct Validator {
+    /// The Ed25519 public key of the validator.
+    #[n(0)]
+    pub public_key: ByteVec,
+
+    /// The voting power. A power of 0 removes the validator.
+    #[n(1)]
+    pub power: u64,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ValidatorUpdatesReturns {
+    #[n(0)]
+    pub updates: Vec<Validator>,
+}
+
+impl From<Validator> for ValidatorUpdate {
+    fn from(validator: Validator) -> Self {
+        ValidatorUpdate {
+            pub_key: Some(PublicKey {
+                sum: Some(public_key::Sum::Ed25519(validator.public_key.to_vec())),
+            }),
+            power: validator.power as i64,
+        }
+    }
+}
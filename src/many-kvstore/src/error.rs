@@ -10,6 +10,8 @@ define_attribute_many_error!(
         5: pub fn subres_alt_unsupported() => "Subresource alternative owner unsupported.",
         6: pub fn key_not_found() => "The key was not found.",
         7: pub fn cannot_disable_empty_key() => "Unable to disable an empty key.",
+        8: pub fn value_too_large(size, max) => "Value is too large: {size} bytes > {max} bytes.",
+        9: pub fn invalid_expiry() => "The expiry of a key must be in the future.",
     }
 );
 
@@ -165,15 +165,27 @@ fn main() {
         let mut s = many.lock().unwrap();
         s.add_module(kvstore::KvStoreModule::new(module.clone()));
         let kvstore_command_module = kvstore::KvStoreCommandsModule::new(module.clone());
+        let kvstore_cas_module = cas::KvStoreCasModule::new(module.clone());
+        let kvstore_expiry_module = expiry::KvStoreExpiryModule::new(module.clone());
         if let Some(path) = allow_addrs {
             let allow_addrs: BTreeSet<Address> =
                 json5::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
             s.add_module(allow_addrs::AllowAddrsModule {
                 inner: kvstore_command_module,
+                allow_addrs: allow_addrs.clone(),
+            });
+            s.add_module(allow_addrs::AllowAddrsModule {
+                inner: kvstore_cas_module,
+                allow_addrs: allow_addrs.clone(),
+            });
+            s.add_module(allow_addrs::AllowAddrsModule {
+                inner: kvstore_expiry_module,
                 allow_addrs,
             });
         } else {
             s.add_module(kvstore_command_module);
+            s.add_module(kvstore_cas_module);
+            s.add_module(kvstore_expiry_module);
         }
         s.add_module(kvstore::KvStoreTransferModule::new(module.clone()));
         s.add_module(events::EventsModule::new(module.clone()));
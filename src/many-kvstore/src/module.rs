@@ -22,16 +22,22 @@ use tracing::info;
 
 pub mod account;
 pub mod allow_addrs;
+pub mod cas;
 mod event;
+pub mod expiry;
 
 // The initial state schema, loaded from JSON.
 #[derive(serde::Deserialize, Debug, Default)]
 pub struct InitialStateJson {
     acl: AclMap,
     identity: Address,
+    max_value_size: Option<usize>,
     hash: Option<String>,
 }
 
+/// Default maximum size of a value, in bytes.
+pub const DEFAULT_MAX_VALUE_SIZE: usize = 64 * 1024;
+
 /// A simple kv-store.
 #[derive(Debug)]
 pub struct KvStoreModuleImpl {
@@ -61,6 +67,35 @@ pub struct KvStoreMetadataWrapper(
 );
 
 impl KvStoreModuleImpl {
+    /// Verify that `sender` can write `value` at `key`, on behalf of the
+    /// alternative owner if any. Returns the owner of the written key.
+    pub(crate) fn verify_put(
+        &self,
+        sender: &Address,
+        alternative_owner: Option<Address>,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<Address, ManyError> {
+        let owner = if let Some(alternative_owner) = alternative_owner {
+            self.validate_alternative_owner(
+                sender,
+                &alternative_owner,
+                [Role::CanKvStorePut, Role::Owner],
+            )?;
+            alternative_owner
+        } else {
+            *sender
+        };
+
+        self.verify_acl(&owner, key.to_vec())?;
+
+        let max_value_size = self.storage.max_value_size()?;
+        if value.len() > max_value_size {
+            return Err(error::value_too_large(value.len(), max_value_size));
+        }
+        Ok(owner)
+    }
+
     pub fn load<P: AsRef<Path>>(
         persistent_store_path: P,
         blockchain: bool,
@@ -79,6 +114,7 @@ impl KvStoreModuleImpl {
         let storage = KvStoreStorage::new(
             initial_state.acl,
             initial_state.identity,
+            initial_state.max_value_size,
             persistence_store_path,
             blockchain,
         )
@@ -113,6 +149,9 @@ impl ManyAbciModuleBackend for KvStoreModuleImpl {
                 ("kvstore.query".to_string(), EndpointInfo { is_command: false }),
                 ("kvstore.put".to_string(), EndpointInfo { is_command: true }),
                 ("kvstore.disable".to_string(), EndpointInfo { is_command: true }),
+                ("kvstore.cas".to_string(), EndpointInfo { is_command: true }),
+                ("kvstore.putExpiring".to_string(), EndpointInfo { is_command: true }),
+                ("kvstore.expiry".to_string(), EndpointInfo { is_command: false }),
 
                 // Accounts
                 ("account.create".to_string(), EndpointInfo { is_command: true }),
@@ -168,6 +207,7 @@ impl ManyAbciModuleBackend for KvStoreModuleImpl {
     }
 
     fn commit(&mut self) -> Result<AbciCommitInfo, ManyError> {
+        self.storage.remove_expired()?;
         let result = self.storage.commit();
 
         info!(
@@ -208,24 +248,13 @@ impl KvStoreModuleBackend for KvStoreModuleImpl {
 impl KvStoreCommandsModuleBackend for KvStoreModuleImpl {
     fn put(&mut self, sender: &Address, args: PutArgs) -> Result<PutReturn, ManyError> {
         let key: Vec<u8> = args.key.into();
-        let owner = if let Some(alternative_owner) = args.alternative_owner {
-            self.validate_alternative_owner(
-                sender,
-                &alternative_owner,
-                [Role::CanKvStorePut, Role::Owner],
-            )?;
-            alternative_owner
-        } else {
-            *sender
-        };
-
-        self.verify_acl(&owner, key.clone())?;
+        let owner = self.verify_put(sender, args.alternative_owner, &key, &args.value)?;
 
         let meta = KvStoreMetadata {
             owner,
             disabled: Some(Either::Left(false)),
         };
-        self.storage.put(&meta, &key, args.value.into())?;
+        self.storage.put(&meta, &key, args.value.into(), None)?;
         Ok(PutReturn {})
     }
 
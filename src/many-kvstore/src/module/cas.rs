@@ -0,0 +1,96 @@
+use super::{KvStoreMetadata, KvStoreModuleImpl};
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_types::Either;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+use sha3::{Digest, Sha3_256};
+
+/// What the current value of a key is expected to be.
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+pub enum CasExpectation {
+    /// The key has no value.
+    #[n(0)]
+    Absent,
+
+    /// The key has this value.
+    #[n(1)]
+    Value(#[n(0)] ByteVec),
+
+    /// The key has a value with this SHA3-256 hash.
+    #[n(2)]
+    Hash(#[n(0)] ByteVec),
+}
+
+impl CasExpectation {
+    pub fn matches(&self, current: Option<&[u8]>) -> bool {
+        match (self, current) {
+            (CasExpectation::Absent, None) => true,
+            (CasExpectation::Value(expected), Some(current)) => expected.as_slice() == current,
+            (CasExpectation::Hash(expected), Some(current)) => {
+                expected.as_slice() == Sha3_256::digest(current).as_slice()
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct CasArgs {
+    #[n(0)]
+    pub key: ByteVec,
+
+    #[n(1)]
+    pub expected: CasExpectation,
+
+    #[n(2)]
+    pub value: ByteVec,
+
+    #[n(3)]
+    pub alternative_owner: Option<Address>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct CasReturns {
+    /// Whether the value was written.
+    #[n(0)]
+    pub swapped: bool,
+
+    /// The current value of the key when it did not match the expectation.
+    #[n(1)]
+    pub actual: Option<ByteVec>,
+}
+
+/// Compare-and-swap, to coordinate clients writing the same keys.
+#[many_module(name = KvStoreCasModule, namespace = kvstore, many_modules_crate = many_modules)]
+pub trait KvStoreCasModuleBackend: Send {
+    fn cas(&mut self, sender: &Address, args: CasArgs) -> Result<CasReturns, ManyError>;
+}
+
+impl KvStoreCasModuleBackend for KvStoreModuleImpl {
+    fn cas(&mut self, sender: &Address, args: CasArgs) -> Result<CasReturns, ManyError> {
+        let key: Vec<u8> = args.key.into();
+        let owner = self.verify_put(sender, args.alternative_owner, &key, &args.value)?;
+
+        let current = self.storage.get(&key)?;
+        if !args.expected.matches(current.as_deref()) {
+            return Ok(CasReturns {
+                swapped: false,
+                actual: current.map(ByteVec::from),
+            });
+        }
+
+        let meta = KvStoreMetadata {
+            owner,
+            disabled: Some(Either::Left(false)),
+        };
+        self.storage.put(&meta, &key, args.value.into(), None)?;
+        Ok(CasReturns {
+            swapped: true,
+            actual: None,
+        })
+    }
+}
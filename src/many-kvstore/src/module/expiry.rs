@@ -0,0 +1,78 @@
+use super::{KvStoreMetadata, KvStoreModuleImpl};
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::kvstore::PutReturn;
+use many_types::{Either, Timestamp};
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct PutExpiringArgs {
+    #[n(0)]
+    pub key: ByteVec,
+
+    #[n(1)]
+    pub value: ByteVec,
+
+    #[n(2)]
+    pub alternative_owner: Option<Address>,
+
+    /// When the key expires. Expired keys are hidden, then removed when the
+    /// block is committed.
+    #[n(3)]
+    pub expires: Timestamp,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ExpiryArgs {
+    #[n(0)]
+    pub key: ByteVec,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ExpiryReturns {
+    /// When the key expires, or None if it does not.
+    #[n(0)]
+    pub expires: Option<Timestamp>,
+}
+
+/// Keys expiring at a given time. Writing a key with `kvstore.put` removes its
+/// expiry.
+#[many_module(name = KvStoreExpiryModule, namespace = kvstore, many_modules_crate = many_modules)]
+pub trait KvStoreExpiryModuleBackend: Send {
+    fn put_expiring(
+        &mut self,
+        sender: &Address,
+        args: PutExpiringArgs,
+    ) -> Result<PutReturn, ManyError>;
+    fn expiry(&self, sender: &Address, args: ExpiryArgs) -> Result<ExpiryReturns, ManyError>;
+}
+
+impl KvStoreExpiryModuleBackend for KvStoreModuleImpl {
+    fn put_expiring(
+        &mut self,
+        sender: &Address,
+        args: PutExpiringArgs,
+    ) -> Result<PutReturn, ManyError> {
+        let key: Vec<u8> = args.key.into();
+        let owner = self.verify_put(sender, args.alternative_owner, &key, &args.value)?;
+
+        let meta = KvStoreMetadata {
+            owner,
+            disabled: Some(Either::Left(false)),
+        };
+        self.storage
+            .put(&meta, &key, args.value.into(), Some(args.expires))?;
+        Ok(PutReturn {})
+    }
+
+    fn expiry(&self, _sender: &Address, args: ExpiryArgs) -> Result<ExpiryReturns, ManyError> {
+        Ok(ExpiryReturns {
+            expires: self.storage.get_expiry(&args.key)?,
+        })
+    }
+}
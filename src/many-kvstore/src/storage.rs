@@ -1,4 +1,4 @@
-use crate::module::{KvStoreMetadata, KvStoreMetadataWrapper};
+use crate::module::{KvStoreMetadata, KvStoreMetadataWrapper, DEFAULT_MAX_VALUE_SIZE};
 use many_error::ManyError;
 use many_identity::Address;
 use many_modules::abci_backend::AbciCommitInfo;
@@ -11,11 +11,13 @@ use std::path::Path;
 
 mod account;
 mod event;
+mod expiry;
 
 use crate::error;
 use event::EventId;
 
 const KVSTORE_ROOT: &[u8] = b"s";
+const MAX_VALUE_SIZE_KEY: &[u8] = b"/config/max_value_size";
 const KVSTORE_ACL_ROOT: &[u8] = b"a";
 
 #[derive(Serialize, Deserialize, Debug, Eq, Ord, PartialEq, PartialOrd)]
@@ -113,6 +115,7 @@ impl KvStoreStorage {
     pub fn new<P: AsRef<Path>>(
         acl: AclMap,
         identity: Address,
+        max_value_size: Option<usize>,
         persistent_path: P,
         blockchain: bool,
     ) -> Result<Self, String> {
@@ -122,6 +125,14 @@ impl KvStoreStorage {
 
         batch.push((b"/config/identity".to_vec(), Op::Put(identity.to_vec())));
 
+        // Only written when set, so the hash of the existing initial states is unchanged.
+        if let Some(max_value_size) = max_value_size {
+            batch.push((
+                MAX_VALUE_SIZE_KEY.to_vec(),
+                Op::Put((max_value_size as u64).to_be_bytes().to_vec()),
+            ));
+        }
+
         // Initialize DB with ACL
         for (k, v) in acl.into_iter() {
             batch.push((
@@ -177,6 +188,19 @@ impl KvStoreStorage {
             })
     }
 
+    /// The maximum size of a value accepted by `kvstore.put`, from the state.
+    pub fn max_value_size(&self) -> Result<usize, ManyError> {
+        Ok(self
+            .persistent_store
+            .get(MAX_VALUE_SIZE_KEY)
+            .map_err(error::storage_get_failed)?
+            .map_or(DEFAULT_MAX_VALUE_SIZE, |x| {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(x.as_slice());
+                u64::from_be_bytes(bytes) as usize
+            }))
+    }
+
     pub fn commit(&mut self) -> AbciCommitInfo {
         let _ = self.inc_height();
         self.persistent_store
@@ -212,6 +236,9 @@ impl KvStoreStorage {
     }
 
     pub fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, ManyError> {
+        if self.is_expired(key)? {
+            return Ok(None);
+        }
         if let Some(cbor) = self._get(key, KVSTORE_ACL_ROOT)? {
             let meta: KvStoreMetadata = minicbor::decode(&cbor)
                 .map_err(|e| ManyError::deserialization_error(e.to_string()))?;
@@ -227,29 +254,36 @@ impl KvStoreStorage {
     }
 
     pub fn get_metadata(&self, key: &[u8]) -> Result<Option<Vec<u8>>, ManyError> {
+        if self.is_expired(key)? {
+            return Ok(None);
+        }
         self._get(key, KVSTORE_ACL_ROOT)
     }
 
+    /// Write a value, expiring at `expires` if any.
     pub fn put(
         &mut self,
         meta: &KvStoreMetadata,
         key: &[u8],
         value: Vec<u8>,
+        expires: Option<Timestamp>,
     ) -> Result<(), ManyError> {
+        let mut batch = self.expiry_updates(key, expires)?;
+        batch.push((
+            vec![KVSTORE_ACL_ROOT.to_vec(), key.to_vec()].concat(),
+            Op::Put(
+                minicbor::to_vec(meta)
+                    .map_err(|e| ManyError::serialization_error(e.to_string()))?,
+            ),
+        ));
+        batch.push((
+            vec![KVSTORE_ROOT.to_vec(), key.to_vec()].concat(),
+            Op::Put(value.clone()),
+        ));
+        // Keys in batch must be sorted.
+        batch.sort_by(|(a, _), (b, _)| a.cmp(b));
         self.persistent_store
-            .apply(&[
-                (
-                    vec![KVSTORE_ACL_ROOT.to_vec(), key.to_vec()].concat(),
-                    Op::Put(
-                        minicbor::to_vec(meta)
-                            .map_err(|e| ManyError::serialization_error(e.to_string()))?,
-                    ),
-                ),
-                (
-                    vec![KVSTORE_ROOT.to_vec(), key.to_vec()].concat(),
-                    Op::Put(value.clone()),
-                ),
-            ])
+            .apply(&batch)
             .map_err(|e| ManyError::unknown(e.to_string()))?;
 
         self.log_event(EventInfo::KvStorePut {
@@ -0,0 +1,133 @@
+//! Expiring keys. The expiry of a key is stored along it, and indexed by time
+//! so the expired keys are removed when the block is committed. Until then,
+//! expired keys are hidden as if they had no value.
+use super::{KvStoreStorage, KVSTORE_ACL_ROOT, KVSTORE_ROOT};
+use crate::error;
+use many_error::{ManyError, Reason};
+use many_modules::events::EventInfo;
+use many_types::Timestamp;
+use merk::rocksdb::{IteratorMode, ReadOptions};
+use merk::{BatchEntry, Op};
+use std::collections::BTreeMap;
+use std::time::UNIX_EPOCH;
+
+const EXPIRES_ROOT: &[u8] = b"/expires/";
+const EXPIRING_ROOT: &[u8] = b"/expiring/";
+
+/// The reason of the disable events logged for expired keys.
+const EXPIRED_REASON: &str = "The key expired.";
+
+fn key_for_expiry(key: &[u8]) -> Vec<u8> {
+    [EXPIRES_ROOT, key].concat()
+}
+
+fn key_for_expiring(secs: u64, key: &[u8]) -> Vec<u8> {
+    [EXPIRING_ROOT, &secs.to_be_bytes(), key].concat()
+}
+
+pub(crate) fn secs_since_epoch(time: Timestamp) -> Result<u64, ManyError> {
+    Ok(time
+        .as_system_time()?
+        .duration_since(UNIX_EPOCH)
+        .map_err(ManyError::unknown)?
+        .as_secs())
+}
+
+impl KvStoreStorage {
+    fn get_expiry_secs(&self, key: &[u8]) -> Result<Option<u64>, ManyError> {
+        Ok(self
+            .persistent_store
+            .get(&key_for_expiry(key))
+            .map_err(error::storage_get_failed)?
+            .map(|x| {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(x.as_slice());
+                u64::from_be_bytes(bytes)
+            }))
+    }
+
+    /// When the value at `key` expires, if it does.
+    pub fn get_expiry(&self, key: &[u8]) -> Result<Option<Timestamp>, ManyError> {
+        self.get_expiry_secs(key)?.map(Timestamp::new).transpose()
+    }
+
+    /// Whether the value at `key` expired, but was not removed yet.
+    pub(crate) fn is_expired(&self, key: &[u8]) -> Result<bool, ManyError> {
+        let now = secs_since_epoch(self.now())?;
+        Ok(self.get_expiry_secs(key)?.map_or(false, |secs| secs <= now))
+    }
+
+    /// The entries replacing the expiry of `key` by `expires`, to apply along
+    /// its value.
+    pub(crate) fn expiry_updates(
+        &self,
+        key: &[u8],
+        expires: Option<Timestamp>,
+    ) -> Result<Vec<BatchEntry>, ManyError> {
+        let mut batch = Vec::new();
+        if let Some(secs) = self.get_expiry_secs(key)? {
+            batch.push((key_for_expiring(secs, key), Op::Delete));
+        }
+        match expires {
+            Some(expires) => {
+                let secs = secs_since_epoch(expires)?;
+                if secs <= secs_since_epoch(self.now())? {
+                    return Err(error::invalid_expiry());
+                }
+                batch.push((key_for_expiring(secs, key), Op::Put(vec![])));
+                batch.push((key_for_expiry(key), Op::Put(secs.to_be_bytes().to_vec())));
+            }
+            None => batch.push((key_for_expiry(key), Op::Delete)),
+        }
+        Ok(batch)
+    }
+
+    /// Remove the keys expired at the current time, logging a disable event
+    /// for each.
+    pub fn remove_expired(&mut self) -> Result<(), ManyError> {
+        let now = secs_since_epoch(self.now())?;
+        let mut options = ReadOptions::default();
+        options.set_iterate_lower_bound(EXPIRING_ROOT);
+        options.set_iterate_upper_bound(key_for_expiring(now + 1, &[]));
+
+        let mut expired = Vec::new();
+        for item in self.persistent_store.iter_opt(IteratorMode::Start, options) {
+            let (k, _) = item.map_err(|e| ManyError::unknown(e.to_string()))?;
+            expired.push(k.to_vec());
+        }
+
+        for expiring in expired {
+            // The key may have been written again in this block.
+            if self
+                .persistent_store
+                .get(&expiring)
+                .map_err(error::storage_get_failed)?
+                .is_none()
+            {
+                continue;
+            }
+            let key = &expiring[EXPIRING_ROOT.len() + 8..];
+            let mut batch: Vec<BatchEntry> = vec![
+                (expiring.clone(), Op::Delete),
+                (key_for_expiry(key), Op::Delete),
+                ([KVSTORE_ACL_ROOT, key].concat(), Op::Delete),
+                ([KVSTORE_ROOT, key].concat(), Op::Delete),
+            ];
+            // Keys in batch must be sorted.
+            batch.sort_by(|(a, _), (b, _)| a.cmp(b));
+            self.persistent_store
+                .apply(&batch)
+                .map_err(error::storage_apply_failed)?;
+
+            self.log_event(EventInfo::KvStoreDisable {
+                key: key.to_vec().into(),
+                reason: Some(Reason::new(
+                    0,
+                    Some(EXPIRED_REASON.to_string()),
+                    BTreeMap::new(),
+                )),
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+pub mod common;
+
+use crate::common::{setup, Setup};
+use many_identity::testing::identity;
+use many_kvstore::error;
+use many_kvstore::module::cas::{CasArgs, CasExpectation, CasReturns, KvStoreCasModuleBackend};
+use minicbor::bytes::ByteVec;
+use sha3::{Digest, Sha3_256};
+
+fn cas(setup: &mut Setup, expected: CasExpectation, value: Vec<u8>) -> CasReturns {
+    let id = setup.id;
+    setup
+        .module_impl
+        .cas(
+            &id,
+            CasArgs {
+                key: vec![1].into(),
+                expected,
+                value: value.into(),
+                alternative_owner: None,
+            },
+        )
+        .unwrap()
+}
+
+#[test]
+fn cas_absent() {
+    let mut setup = setup();
+    let id = setup.id;
+    let r = cas(&mut setup, CasExpectation::Absent, vec![2]);
+    assert!(r.swapped);
+    assert_eq!(
+        setup.get(&id, vec![1]).unwrap().value,
+        Some(ByteVec::from(vec![2]))
+    );
+
+    // The key now has a value.
+    let r = cas(&mut setup, CasExpectation::Absent, vec![3]);
+    assert!(!r.swapped);
+    assert_eq!(r.actual, Some(ByteVec::from(vec![2])));
+}
+
+#[test]
+fn cas_value() {
+    let mut setup = setup();
+    let id = setup.id;
+    setup.put(&id, vec![1], vec![2], None).unwrap();
+
+    let r = cas(&mut setup, CasExpectation::Value(vec![3].into()), vec![4]);
+    assert!(!r.swapped);
+    assert_eq!(r.actual, Some(ByteVec::from(vec![2])));
+
+    let r = cas(&mut setup, CasExpectation::Value(vec![2].into()), vec![4]);
+    assert!(r.swapped);
+    assert_eq!(
+        setup.get(&id, vec![1]).unwrap().value,
+        Some(ByteVec::from(vec![4]))
+    );
+}
+
+#[test]
+fn cas_hash() {
+    let mut setup = Setup::new(true);
+    let id = setup.id;
+    setup.block(|setup| setup.put(&id, vec![1], vec![2], None).unwrap());
+
+    let hash = Sha3_256::digest([2u8]).to_vec();
+    let (_, r) =
+        setup.block(|setup| cas(setup, CasExpectation::Hash(hash.clone().into()), vec![3]));
+    assert!(r.swapped);
+    assert_eq!(
+        setup.get(&id, vec![1]).unwrap().value,
+        Some(ByteVec::from(vec![3]))
+    );
+
+    // The hash of the previous value no longer matches.
+    let (_, r) = setup.block(|setup| cas(setup, CasExpectation::Hash(hash.into()), vec![4]));
+    assert!(!r.swapped);
+    assert_eq!(r.actual, Some(ByteVec::from(vec![3])));
+}
+
+#[test]
+fn cas_not_owner() {
+    let mut setup = setup();
+    let id = setup.id;
+    setup.put(&id, vec![1], vec![2], None).unwrap();
+
+    let r = setup.module_impl.cas(
+        &identity(5),
+        CasArgs {
+            key: vec![1].into(),
+            expected: CasExpectation::Value(vec![2].into()),
+            value: vec![3].into(),
+            alternative_owner: None,
+        },
+    );
+    assert_eq!(r.unwrap_err().code(), error::permission_denied().code());
+}
@@ -38,11 +38,16 @@ impl Default for Setup {
 
 impl Setup {
     pub fn new(blockchain: bool) -> Self {
-        let id = generate_random_ecdsa_identity();
         let content = std::fs::read_to_string("../../staging/kvstore_state.json5")
             .or_else(|_| std::fs::read_to_string("staging/kvstore_state.json5"))
             .unwrap();
-        let state = json5::from_str(&content).unwrap();
+        Self::new_with_state(&content, blockchain)
+    }
+
+    /// Create the module from the given JSON5 initial state.
+    pub fn new_with_state(content: &str, blockchain: bool) -> Self {
+        let id = generate_random_ecdsa_identity();
+        let state = json5::from_str(content).unwrap();
         Self {
             module_impl: KvStoreModuleImpl::new(state, tempfile::tempdir().unwrap(), blockchain)
                 .unwrap(),
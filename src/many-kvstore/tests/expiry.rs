@@ -0,0 +1,95 @@
+pub mod common;
+
+use crate::common::Setup;
+use many_error::ManyError;
+use many_kvstore::error;
+use many_kvstore::module::expiry::{ExpiryArgs, KvStoreExpiryModuleBackend, PutExpiringArgs};
+use many_modules::events;
+use many_modules::events::{EventInfo, EventsModuleBackend};
+use many_modules::kvstore::PutReturn;
+use many_types::Timestamp;
+use minicbor::bytes::ByteVec;
+
+fn put_expiring(setup: &mut Setup, key: Vec<u8>, expires: u64) -> Result<PutReturn, ManyError> {
+    let id = setup.id;
+    setup.module_impl.put_expiring(
+        &id,
+        PutExpiringArgs {
+            key: key.into(),
+            value: vec![2].into(),
+            alternative_owner: None,
+            expires: Timestamp::new(expires).unwrap(),
+        },
+    )
+}
+
+fn expiry(setup: &Setup, key: Vec<u8>) -> Option<Timestamp> {
+    setup
+        .module_impl
+        .expiry(&setup.id, ExpiryArgs { key: key.into() })
+        .unwrap()
+        .expires
+}
+
+#[test]
+fn expired_keys_are_removed() {
+    let mut setup = Setup::new(true);
+    let id = setup.id;
+    // Blocks start at 1_000_001.
+    setup.block(|setup| put_expiring(setup, vec![1], 1_000_003).unwrap());
+    assert_eq!(
+        expiry(&setup, vec![1]),
+        Some(Timestamp::new(1_000_003).unwrap())
+    );
+
+    setup.block(|setup| {
+        assert_eq!(
+            setup.get(&id, vec![1]).unwrap().value,
+            Some(ByteVec::from(vec![2]))
+        )
+    });
+
+    // Hidden as soon as it expires, then removed on commit.
+    setup.block(|setup| {
+        assert_eq!(setup.get(&id, vec![1]).unwrap().value, None);
+        assert!(setup.query(&id, vec![1]).is_err());
+    });
+    assert_eq!(setup.get(&id, vec![1]).unwrap().value, None);
+    assert_eq!(expiry(&setup, vec![1]), None);
+
+    let events = setup
+        .module_impl
+        .list(events::ListArgs {
+            count: None,
+            order: None,
+            filter: None,
+        })
+        .unwrap();
+    assert_eq!(events.nb_events, 2);
+    assert!(events.events.iter().any(
+        |e| matches!(&e.content, EventInfo::KvStoreDisable { key, .. } if key.as_slice() == [1])
+    ));
+}
+
+#[test]
+fn put_removes_expiry() {
+    let mut setup = Setup::new(true);
+    let id = setup.id;
+    setup.block(|setup| put_expiring(setup, vec![1], 1_000_003).unwrap());
+    setup.block(|setup| setup.put(&id, vec![1], vec![3], None).unwrap());
+    assert_eq!(expiry(&setup, vec![1]), None);
+
+    setup.block(|_| {});
+    setup.block(|_| {});
+    assert_eq!(
+        setup.get(&id, vec![1]).unwrap().value,
+        Some(ByteVec::from(vec![3]))
+    );
+}
+
+#[test]
+fn expiry_in_the_past() {
+    let mut setup = Setup::new(true);
+    let (_, r) = setup.block(|setup| put_expiring(setup, vec![1], 1_000_001));
+    assert_eq!(r.unwrap_err().code(), error::invalid_expiry().code());
+}
@@ -55,6 +55,24 @@ fn put_get_disable_block() {
     assert_eq!(get_value.unwrap_err().code(), error::key_disabled().code());
 }
 
+#[test]
+fn put_value_too_large() {
+    let mut setup = Setup::new_with_state(
+        r#"{
+            identity: "mahukzwuwgt3porn6q4vq4xu3mwy5gyskhouryzbscq7wb2iow",
+            acl: {},
+            max_value_size: 4,
+        }"#,
+        false,
+    );
+    let id = setup.id;
+
+    assert!(setup.put(&id, vec![1], vec![2; 4], None).is_ok());
+    let put = setup.put(&id, vec![2], vec![2; 5], None);
+    assert_eq!(put.unwrap_err().code(), error::value_too_large(0, 0).code());
+    assert!(setup.get(&id, vec![2]).unwrap().value.is_none());
+}
+
 #[test]
 fn put_put() {
     let mut setup = setup();
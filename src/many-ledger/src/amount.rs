@@ -0,0 +1,40 @@
+//! Checked arithmetic on token amounts.
+//!
+//! The operators of [`TokenAmount`] clamp to zero on underflow, which can hide
+//! accounting bugs. Code moving funds should use these methods instead.
+use crate::error;
+use many_error::ManyError;
+use many_types::ledger::TokenAmount;
+use num_bigint::BigUint;
+
+pub trait CheckedTokenAmount: Sized {
+    /// None if `other` is greater than `self`.
+    fn checked_sub(&self, other: &Self) -> Option<Self>;
+
+    fn try_to_u64(&self) -> Result<u64, ManyError>;
+
+    fn try_to_u128(&self) -> Result<u128, ManyError>;
+}
+
+fn to_biguint(amount: &TokenAmount) -> BigUint {
+    BigUint::from_bytes_be(&amount.to_vec())
+}
+
+impl CheckedTokenAmount for TokenAmount {
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        let (a, b) = (to_biguint(self), to_biguint(other));
+        if b > a {
+            None
+        } else {
+            Some(TokenAmount::from(a - b))
+        }
+    }
+
+    fn try_to_u64(&self) -> Result<u64, ManyError> {
+        u64::try_from(to_biguint(self)).map_err(|_| error::amount_out_of_range(self, u64::MAX))
+    }
+
+    fn try_to_u128(&self) -> Result<u128, ManyError> {
+        u128::try_from(to_biguint(self)).map_err(|_| error::amount_out_of_range(self, u128::MAX))
+    }
+}
@@ -0,0 +1,115 @@
+//! Audit of the application hash chain.
+//!
+//! The application hash of every committed height is kept in the store (see
+//! [`crate::storage::app_hash`]). The audit compares each with the `app_hash` of
+//! the Tendermint header of the next height, which commits to the state after
+//! the block, and produces a report signed by the node. Auditors verify the
+//! signature and query the headers themselves to confirm the application never
+//! forked from the consensus.
+use crate::error;
+use crate::replica::http_request;
+use crate::storage::app_hash::read_app_hash;
+use crate::storage::{read_height, InnerStorage};
+use coset::{CborSerializable, CoseSign1Builder};
+use many_error::ManyError;
+use many_identity::Identity;
+use many_identity_dsa::CoseKeyIdentity;
+use many_protocol::ManyUrl;
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct AppHashEntry {
+    pub height: u64,
+
+    /// The application hash recorded by the store, in hexadecimal.
+    pub app_hash: Option<String>,
+
+    /// The application hash in the header of the next height, in hexadecimal.
+    pub consensus_app_hash: Option<String>,
+
+    pub matches: bool,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct AppHashReport {
+    /// The address of the node signing the report.
+    pub signer: String,
+    pub from: u64,
+    pub to: u64,
+    pub entries: Vec<AppHashEntry>,
+
+    /// The heights whose hashes differ or are missing.
+    pub mismatches: Vec<u64>,
+}
+
+impl AppHashReport {
+    pub fn verified(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// The JSON report as the payload of a COSE_Sign1 envelope signed by `key`.
+    pub fn sign(&self, key: &CoseKeyIdentity) -> Result<Vec<u8>, ManyError> {
+        let payload = serde_json::to_vec(self).map_err(ManyError::serialization_error)?;
+        key.sign_1(CoseSign1Builder::new().payload(payload).build())?
+            .to_vec()
+            .map_err(ManyError::serialization_error)
+    }
+}
+
+pub struct AppHashAudit {
+    /// The Tendermint RPC server.
+    rpc: ManyUrl,
+}
+
+impl AppHashAudit {
+    pub fn new(rpc: ManyUrl) -> Self {
+        Self { rpc }
+    }
+
+    /// The application hash in the header of `height`, or None if the chain has
+    /// no such height.
+    fn consensus_app_hash(&self, height: u64) -> Result<Option<String>, ManyError> {
+        let mut url = self.rpc.join("commit").map_err(error::audit_failed)?;
+        url.set_query(Some(&format!("height={height}")));
+        let body = http_request("GET", &url, &[]).map_err(error::audit_failed)?;
+        let response: serde_json::Value =
+            serde_json::from_slice(&body).map_err(ManyError::deserialization_error)?;
+        Ok(response
+            .pointer("/result/signed_header/header/app_hash")
+            .and_then(|hash| hash.as_str())
+            .map(str::to_lowercase))
+    }
+
+    /// Audit the heights from `from` to the height of `store`, signing the
+    /// report as `signer`.
+    pub fn run(
+        &self,
+        store: &InnerStorage,
+        from: u64,
+        signer: &CoseKeyIdentity,
+    ) -> Result<AppHashReport, ManyError> {
+        let to = read_height(store)?;
+        let mut entries = Vec::new();
+        let mut mismatches = Vec::new();
+        for height in from.max(1)..=to {
+            let app_hash = read_app_hash(store, height)?.map(hex::encode);
+            let consensus_app_hash = self.consensus_app_hash(height + 1)?;
+            let matches = app_hash.is_some() && app_hash == consensus_app_hash;
+            if !matches {
+                mismatches.push(height);
+            }
+            entries.push(AppHashEntry {
+                height,
+                app_hash,
+                consensus_app_hash,
+                matches,
+            });
+        }
+        Ok(AppHashReport {
+            signer: signer.address().to_string(),
+            from,
+            to,
+            entries,
+            mismatches,
+        })
+    }
+}
@@ -0,0 +1,132 @@
+//! Fast catch-up from a trusted peer.
+//!
+//! A node far behind the chain fetches the state diffs of the missing heights
+//! from a trusted peer keeping them (see [`crate::storage::diff`]) instead of
+//! replaying the blocks through Tendermint. Every response must be signed by the
+//! peer, and every diff must reproduce the application hash the peer recorded at
+//! its height. When the peer no longer has the first diff needed, its latest
+//! snapshot is restored first. Tendermint replays the blocks after the last diff
+//! as usual.
+use crate::error;
+use crate::module::snapshot::{
+    SnapshotChunkArgs, SnapshotChunkReturns, SnapshotInfoReturns, StateDiffArgs, StateDiffReturns,
+};
+use crate::replica::call;
+use crate::storage::diff::StateDiff;
+use crate::storage::snapshot::{remove_dir_if_exists, restore_snapshot};
+use crate::storage::{read_height, InnerStorage};
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::EmptyArg;
+use many_protocol::ManyUrl;
+use minicbor::{Decode, Encode};
+use std::path::Path;
+use tracing::info;
+
+pub struct CatchUp {
+    peer: ManyUrl,
+
+    /// The address responses must be signed by.
+    peer_id: Address,
+}
+
+impl CatchUp {
+    pub fn new(peer: ManyUrl, peer_id: Address) -> Self {
+        Self { peer, peer_id }
+    }
+
+    fn call<T: for<'a> Decode<'a, ()>>(
+        &self,
+        method: &str,
+        data: impl Encode<()>,
+    ) -> Result<T, ManyError> {
+        call(
+            &self.peer,
+            Some(&self.peer_id),
+            method,
+            data,
+            error::catch_up_failed,
+        )
+    }
+
+    /// The diff of `height`, if the peer has it.
+    fn diff(&self, height: u64) -> Result<Option<StateDiff>, ManyError> {
+        match self.call::<StateDiffReturns>("ledger.stateDiff", StateDiffArgs { height }) {
+            Ok(returns) => Ok(Some(returns.diff)),
+            Err(e) if e.code() == error::state_diff_not_found("").code() => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Replace the store at `path` with the latest snapshot of the peer if it is
+    /// above `height`, returning the height of the snapshot. The snapshot is
+    /// restored next to the store first.
+    fn restore_latest(&self, path: &Path, height: u64) -> Result<Option<u64>, ManyError> {
+        let latest = self
+            .call::<SnapshotInfoReturns>("ledger.snapshotInfo", EmptyArg)?
+            .latest
+            .ok_or_else(|| error::catch_up_failed("the peer has no snapshot"))?;
+        if latest.height <= height {
+            return Ok(None);
+        }
+
+        info!(
+            "Restoring the snapshot of the peer at height {}",
+            latest.height
+        );
+        let tmp_path = path.with_extension("catch-up");
+        drop(restore_snapshot(&tmp_path, &latest, |chunk| {
+            self.call::<SnapshotChunkReturns>(
+                "ledger.snapshotChunk",
+                SnapshotChunkArgs {
+                    height: latest.height,
+                    chunk,
+                },
+            )
+            .map(|returns| returns.chunk.to_vec())
+        })?);
+        remove_dir_if_exists(path)?;
+        std::fs::rename(&tmp_path, path).map_err(error::catch_up_failed)?;
+        Ok(Some(latest.height))
+    }
+
+    /// Bring the store at `path`, created if missing, to the latest height the
+    /// peer has a diff of. Returns the height reached.
+    pub fn run(&self, path: &Path) -> Result<u64, ManyError> {
+        let mut height = if path.exists() {
+            read_height(&InnerStorage::open(path).map_err(error::storage_open_failed)?)?
+        } else {
+            0
+        };
+
+        // A missing store has no genesis to apply the diffs on.
+        let mut next = self.diff(height + 1)?;
+        if next.is_none() || !path.exists() {
+            if let Some(restored) = self.restore_latest(path, height)? {
+                height = restored;
+                next = self.diff(height + 1)?;
+            }
+        }
+        if !path.exists() {
+            return Err(error::catch_up_failed(
+                "the peer has no snapshot to start from",
+            ));
+        }
+
+        let mut store = InnerStorage::open(path).map_err(error::storage_open_failed)?;
+        while let Some(diff) = next {
+            if diff.height != height + 1 {
+                return Err(error::invalid_state_diff(
+                    diff.height,
+                    format!("expected height {}", height + 1),
+                ));
+            }
+            diff.apply(&mut store)?;
+            height = diff.height;
+            next = self.diff(height + 1)?;
+        }
+
+        info!("Caught up with the peer at height {height}");
+        Ok(height)
+    }
+}
@@ -0,0 +1,70 @@
+//! Developer mode, without Tendermint.
+//!
+//! Every command is executed in a block of its own, committed as soon as the
+//! command returns, so the endpoints behave as on a network (heights, block
+//! times, events, scheduled tasks, ...) with instant feedback. Block times are
+//! the current time, and at least one second after the previous block.
+use crate::module::LedgerModuleImpl;
+use async_trait::async_trait;
+use coset::CoseSign1;
+use many_modules::abci_backend::{AbciBlock, ManyAbciModuleBackend};
+use many_protocol::RequestMessage;
+use many_server::transport::LowLevelManyRequestHandler;
+use std::collections::BTreeSet;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Wraps every command in a block when `module_impl` is set.
+pub struct DevHandler<H> {
+    pub inner: H,
+    pub module_impl: Option<Arc<Mutex<LedgerModuleImpl>>>,
+    pub commands: BTreeSet<String>,
+
+    /// The time of the last block. Also serializes the blocks.
+    pub last_time: tokio::sync::Mutex<u64>,
+}
+
+impl<H> Debug for DevHandler<H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DevHandler")
+    }
+}
+
+#[async_trait]
+impl<H: LowLevelManyRequestHandler> LowLevelManyRequestHandler for DevHandler<H> {
+    async fn execute(&self, envelope: CoseSign1) -> Result<CoseSign1, String> {
+        let module_impl = match &self.module_impl {
+            Some(module_impl) => module_impl,
+            None => return self.inner.execute(envelope).await,
+        };
+        let is_command = envelope
+            .payload
+            .as_deref()
+            .and_then(|payload| RequestMessage::from_bytes(payload).ok())
+            .map_or(false, |message| self.commands.contains(&message.method));
+        if !is_command {
+            return self.inner.execute(envelope).await;
+        }
+
+        let mut last_time = self.last_time.lock().await;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        *last_time = now.max(*last_time + 1);
+        module_impl
+            .lock()
+            .unwrap()
+            .begin_block(AbciBlock {
+                time: Some(*last_time),
+            })
+            .map_err(|e| e.to_string())?;
+
+        let result = self.inner.execute(envelope).await;
+
+        let mut module_impl = module_impl.lock().unwrap();
+        module_impl.end_block().map_err(|e| e.to_string())?;
+        module_impl.commit().map_err(|e| e.to_string())?;
+        result
+    }
+}
@@ -1,10 +1,25 @@
 use many_error::{define_application_many_error, define_attribute_many_error};
 
+pub mod registry;
+
+/// Errors as they were returned before a migration changed them. The result of
+/// a command is part of its block, so these are kept for the blocks before the
+/// activation height.
+pub mod legacy {
+    use many_error::define_attribute_many_error;
+
+    define_attribute_many_error!(
+        attribute 2 => {
+            3: pub fn insufficient_funds() => "Insufficient funds.",
+        }
+    );
+}
+
 define_attribute_many_error!(
     attribute 2 => {
         1: pub fn unknown_symbol(symbol) => "Symbol not supported by this ledger: {symbol}.",
         2: pub fn unauthorized() => "Unauthorized to do this operation.",
-        3: pub fn insufficient_funds() => "Insufficient funds.",
+        3: pub fn insufficient_funds(amount, balance) => "Insufficient funds: {amount} needed, {balance} available.",
         4: pub fn anonymous_cannot_hold_funds() => "Anonymous is not a valid account identity.",
         5: pub fn invalid_initial_state(expected, actual)
             => "Invalid initial state hash. Expected '{expected}', was '{actual}'.",
@@ -17,6 +32,46 @@ define_attribute_many_error!(
         9: pub fn amount_is_zero()
             => "Unable to send zero (0) token.",
         10: pub fn storage_key_not_found(key) => "Key not found in storage: {key:?}.",
+        11: pub fn fee_collector_missing() => "Transfer fees are configured without a fee collector.",
+        12: pub fn memo_too_large(size, max) => "Memo is too large: {size} bytes > {max} bytes.",
+        13: pub fn credential_too_large(size, max) => "Credential is too large: {size} bytes > {max} bytes.",
+        14: pub fn invalid_transfer_count(count, max) => "Invalid number of transfers: {count}, must be between 1 and {max}.",
+        15: pub fn account_frozen(account) => "Account {account} is frozen.",
+        16: pub fn invalid_genesis(reason) => "Invalid initial state: {reason}.",
+        17: pub fn allowance_exceeded(amount, allowance) => "Amount exceeds the allowance: {amount} > {allowance}.",
+        18: pub fn quota_exceeded(used, window, quota) => "Download quota exceeded: {used} bytes served in the last {window} seconds, the quota is {quota} bytes.",
+        19: pub fn read_only_replica(method) => "This node is a read replica, {method} must be sent to the primary.",
+        20: pub fn replica_stale(age, max) => "The replica state is stale: {age} seconds old, the bound is {max} seconds.",
+        21: pub fn amount_out_of_range(amount, max) => "Amount out of range: {amount} > {max}.",
+        22: pub fn pending_send_not_found(id) => "Pending transfer {id} not found.",
+        23: pub fn invalid_pending_timeout(timeout, max) => "Invalid pending transfer timeout: {timeout} seconds, must be between 1 and {max}.",
+        24: pub fn invalid_sub_account_name(name) => "Invalid sub-account name: '{name}'.",
+        25: pub fn sub_account_exists(name) => "Sub-account '{name}' already exists.",
+        26: pub fn sub_account_not_found(name) => "Sub-account '{name}' not found.",
+        27: pub fn invalid_sub_account_parent(parent) => "Only public key identities can have sub-accounts, not {parent}.",
+        28: pub fn rate_limited(retry_after) => "Too many calls, retry in {retry_after} seconds.",
+        29: pub fn duplicate_idempotency_key(key) => "The idempotency key {key} was already used.",
+        30: pub fn invalid_idempotency_key(max) => "Idempotency keys cannot be longer than {max} bytes.",
+        31: pub fn invalid_idempotency_window(max) => "Idempotency windows cannot be longer than {max} seconds.",
+        32: pub fn invalid_notice(reason) => "Invalid notice: {reason}.",
+        33: pub fn notice_not_found(id) => "Notice {id} not found.",
+        34: pub fn invalid_scheduled_time(max) => "Scheduled transfers must execute in the future, within {max} seconds.",
+        35: pub fn scheduled_send_not_found(id) => "Scheduled transfer {id} not found.",
+        36: pub fn invalid_recurring_send(reason) => "Invalid recurring transfer: {reason}.",
+        37: pub fn recurring_send_not_found(id) => "Recurring transfer {id} not found.",
+        38: pub fn escrow_not_found(id) => "Escrow {id} not found.",
+        39: pub fn invalid_escrow_timeout(timeout, max) => "Invalid escrow timeout: {timeout} seconds, must be between 1 and {max}.",
+        40: pub fn invalid_escrow_arbiter(arbiter) => "Invalid escrow arbiter: {arbiter}.",
+        41: pub fn holders_index_inactive() => "The holders index is not active on this ledger.",
+        42: pub fn invalid_holders_cursor() => "Invalid holders cursor.",
+        43: pub fn invalid_validator(reason) => "Invalid validator: {reason}.",
+        44: pub fn faucet_disabled() => "The faucet is not configured on this ledger.",
+        45: pub fn faucet_claim_too_soon(retry_after) => "Already claimed from the faucet, retry in {retry_after} seconds.",
+        46: pub fn height_not_retained(height) => "The state at height {height} is not retained.",
+        47: pub fn not_queryable_at_height(method) => "{method} cannot be queried at a height.",
+        48: pub fn anonymous_download_too_large(size, max) => "Anonymous responses are limited to {max} bytes, this one is {size} bytes. Sign the request to use the download quota.",
+        49: pub fn invalid_transfer_fee(symbol, basis_points, max) => "Invalid transfer fee for {symbol}: {basis_points} basis points, the maximum is {max}.",
+        50: pub fn state_not_committed() => "The state has changes which are not committed yet, retry after the block.",
     }
 );
 
@@ -47,5 +102,16 @@ define_application_many_error!(
         3: pub fn storage_commit_failed(desc) => "Unable to commit data to persistent storage: {desc}.",
         4: pub fn storage_open_failed(desc) => "Unable to open persistent storage: {desc}.",
         5: pub fn unable_to_load_migrations(desc) => "Unable to load migrations: {desc}.",
+        6: pub fn snapshot_failed(desc) => "Unable to read or write snapshots: {desc}.",
+        7: pub fn snapshot_not_found(height) => "No snapshot at height {height}.",
+        8: pub fn invalid_snapshot(reason) => "Invalid snapshot: {reason}.",
+        9: pub fn replica_sync_failed(desc) => "Unable to sync from the primary: {desc}.",
+        10: pub fn state_diff_not_found(height) => "No state diff at height {height}.",
+        11: pub fn invalid_state_diff(height, reason) => "Invalid state diff at height {height}: {reason}.",
+        12: pub fn catch_up_failed(desc) => "Unable to catch up from the peer: {desc}.",
+        13: pub fn storage_prove_failed(desc) => "Unable to prove data of persistent storage: {desc}.",
+        14: pub fn audit_failed(desc) => "Unable to audit the application hashes: {desc}.",
+        15: pub fn invariant_violated(report) => "Ledger invariants violated: {report}.",
+        16: pub fn migration_height_mismatch(name, stored, configured) => "The migration {name} activated at height {stored} in the state, but is configured at {configured}.",
     }
 );
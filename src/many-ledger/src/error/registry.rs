@@ -0,0 +1,166 @@
+//! Every error the ledger can return, with its stable numeric code.
+//!
+//! Clients should branch on the code and read the fields by name instead of
+//! parsing messages, which are free to change. A code is never reused for a
+//! different error, so entries are only ever added here.
+use crate::error;
+use many_error::ManyError;
+use many_modules::account::{self, features::multisig};
+use many_modules::idstore;
+use minicbor::{Decode, Encode};
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ErrorInfo {
+    #[n(0)]
+    pub code: i64,
+
+    #[n(1)]
+    pub name: String,
+
+    /// The message template, with the fields between braces.
+    #[n(2)]
+    pub message: String,
+
+    #[n(3)]
+    pub fields: Vec<String>,
+}
+
+impl ErrorInfo {
+    fn new(name: &str, e: ManyError) -> Self {
+        Self {
+            code: i64::from(e.code()),
+            name: name.to_string(),
+            message: e.message().unwrap_or_default().to_string(),
+            fields: e.arguments().keys().cloned().collect(),
+        }
+    }
+}
+
+/// Build the entry of each error by calling its constructor with the name of
+/// every field as the field value.
+macro_rules! registry {
+    ($( $($path: ident)::+ ( $($field: ident),* ) ),* $(,)?) => {
+        vec![
+            $(
+                ErrorInfo::new(
+                    stringify!($($path)::+).rsplit("::").next().unwrap_or_default().trim(),
+                    $($path)::+ ( $( stringify!($field) ),* ),
+                )
+            ),*
+        ]
+    };
+}
+
+/// The registry, sorted by code.
+pub fn error_codes() -> Vec<ErrorInfo> {
+    let mut errors = registry![
+        // Generic errors.
+        ManyError::unknown(message),
+        ManyError::invalid_method_name(method),
+        ManyError::invalid_identity(),
+        ManyError::invalid_from_identity(),
+        ManyError::serialization_error(details),
+        ManyError::deserialization_error(details),
+        ManyError::non_webauthn_request_denied(method),
+        // Ledger.
+        error::unknown_symbol(symbol),
+        error::unauthorized(),
+        error::insufficient_funds(amount, balance),
+        error::anonymous_cannot_hold_funds(),
+        error::invalid_initial_state(expected, actual),
+        error::unexpected_subresource_id(expected, actual),
+        error::unexpected_account_id(expected, actual),
+        error::destination_is_source(),
+        error::amount_is_zero(),
+        error::storage_key_not_found(key),
+        error::fee_collector_missing(),
+        error::memo_too_large(size, max),
+        error::credential_too_large(size, max),
+        error::invalid_transfer_count(count, max),
+        error::account_frozen(account),
+        error::invalid_genesis(reason),
+        error::allowance_exceeded(amount, allowance),
+        error::quota_exceeded(used, window, quota),
+        error::read_only_replica(method),
+        error::replica_stale(age, max),
+        error::amount_out_of_range(amount, max),
+        error::pending_send_not_found(id),
+        error::invalid_pending_timeout(timeout, max),
+        error::invalid_sub_account_name(name),
+        error::sub_account_exists(name),
+        error::sub_account_not_found(name),
+        error::invalid_sub_account_parent(parent),
+        error::rate_limited(retry_after),
+        error::duplicate_idempotency_key(key),
+        error::invalid_idempotency_key(max),
+        error::invalid_idempotency_window(max),
+        error::invalid_notice(reason),
+        error::notice_not_found(id),
+        error::invalid_scheduled_time(max),
+        error::scheduled_send_not_found(id),
+        error::invalid_recurring_send(reason),
+        error::recurring_send_not_found(id),
+        error::escrow_not_found(id),
+        error::invalid_escrow_timeout(timeout, max),
+        error::invalid_escrow_arbiter(arbiter),
+        error::holders_index_inactive(),
+        error::invalid_holders_cursor(),
+        error::invalid_validator(reason),
+        error::faucet_disabled(),
+        error::faucet_claim_too_soon(retry_after),
+        error::height_not_retained(height),
+        error::not_queryable_at_height(method),
+        error::anonymous_download_too_large(size, max),
+        error::invalid_transfer_fee(symbol, basis_points, max),
+        error::state_not_committed(),
+        // Tokens.
+        error::token_info_not_found(symbol),
+        error::ext_info_not_found(symbol),
+        error::invalid_sender(),
+        error::ticker_exists(ticker),
+        error::subresource_exhausted(key),
+        // Mint and burn.
+        error::symbol_not_found(symbol),
+        error::over_maximum_supply(symbol, amount, max),
+        error::missing_funds(symbol, amount, balance),
+        error::unable_to_distribute_zero(symbol),
+        error::partial_burn_disabled(),
+        // Application.
+        error::storage_apply_failed(desc),
+        error::storage_get_failed(desc),
+        error::storage_commit_failed(desc),
+        error::storage_open_failed(desc),
+        error::unable_to_load_migrations(desc),
+        error::snapshot_failed(desc),
+        error::snapshot_not_found(height),
+        error::invalid_snapshot(reason),
+        error::replica_sync_failed(desc),
+        error::state_diff_not_found(height),
+        error::invalid_state_diff(height, reason),
+        error::catch_up_failed(desc),
+        error::storage_prove_failed(desc),
+        error::audit_failed(desc),
+        error::invariant_violated(report),
+        error::migration_height_mismatch(name, stored, configured),
+        // IdStore.
+        idstore::existing_entry(),
+        idstore::entry_not_found(entry),
+        idstore::invalid_address(address),
+        idstore::invalid_credential_id(credential_id),
+        idstore::recall_phrase_generation_failed(),
+        // Accounts.
+        account::errors::unknown_account(account),
+        account::errors::unknown_role(role),
+        account::errors::user_needs_role(role),
+        account::errors::empty_feature(),
+        account::errors::account_must_own_itself(),
+        multisig::errors::transaction_cannot_be_found(),
+        multisig::errors::user_cannot_approve_transaction(),
+        multisig::errors::transaction_type_unsupported(),
+        multisig::errors::transaction_expired_or_withdrawn(),
+        multisig::errors::cannot_execute_transaction(),
+    ];
+    errors.sort_by_key(|e| e.code);
+    errors
+}
@@ -0,0 +1,103 @@
+//! Builds the initial state JSON of a network from a list of balances, and
+//! embeds the hash of the resulting state, verified by a dry run of the
+//! ledger.
+//!
+//! The spec is an initial state without `initial` and `hash`, with a list of
+//! balances instead:
+//!
+//! ```json5
+//! {
+//!   identity: "maffbahksdwaqeenayy2gxke32hgb7aq4ao4wt745lsfs6wijp",
+//!   symbols: { "mqbfbahksdwaqeenayy2gxke32hgb7aq4ao4wt745lsfs6wiaaaaqnz": "MFX" },
+//!   balances: [
+//!     { identity: "maffbahksdwaqeenayy2gxke32hgb7aq4ao4wt745lsfs6wijp", symbol: "MFX", amount: 1000 },
+//!   ],
+//! }
+//! ```
+//!
+//! Symbols of balances are either tickers or symbol addresses. Every other
+//! field is copied to the initial state as is.
+use crate::error;
+use crate::json::InitialStateJson;
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use serde_json::{Map, Value};
+
+#[derive(serde::Deserialize)]
+struct BalanceSpec {
+    identity: String,
+    symbol: String,
+    amount: Value,
+}
+
+fn invalid(reason: impl ToString) -> ManyError {
+    error::invalid_genesis(reason.to_string())
+}
+
+/// The hash of the state built from `state`, in a temporary directory. The
+/// state is parsed as the ledger parses its `--state` file.
+fn dry_run(state: &Value) -> Result<String, ManyError> {
+    let state: InitialStateJson = json5::from_str(&state.to_string()).map_err(invalid)?;
+    let dir = tempfile::tempdir().map_err(ManyError::unknown)?;
+    let module_impl = LedgerModuleImpl::new(state, None, dir.path(), false)?;
+    Ok(hex::encode(module_impl.hash()))
+}
+
+/// Build the initial state of `spec`, a JSON5 document, and return it as JSON.
+pub fn build(spec: &str) -> Result<String, ManyError> {
+    let mut state: Map<String, Value> = json5::from_str(spec).map_err(invalid)?;
+    if state.contains_key("initial") {
+        return Err(invalid("the spec lists `balances`, not `initial`"));
+    }
+    state.remove("hash");
+
+    let balances: Vec<BalanceSpec> = state
+        .remove("balances")
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(invalid)?
+        .unwrap_or_default();
+    let tickers = state
+        .get("symbols")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+
+    let mut initial: Map<String, Value> = Map::new();
+    for BalanceSpec {
+        identity,
+        symbol,
+        amount,
+    } in balances
+    {
+        let ticker = match tickers.get(&symbol) {
+            Some(Value::String(ticker)) => ticker.clone(),
+            _ if tickers
+                .values()
+                .any(|t| t.as_str() == Some(symbol.as_str())) =>
+            {
+                symbol
+            }
+            _ => return Err(invalid(format!("unknown symbol '{symbol}'"))),
+        };
+        let account = initial
+            .entry(identity.clone())
+            .or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()
+            .unwrap();
+        if account.insert(ticker.clone(), amount).is_some() {
+            return Err(invalid(format!(
+                "duplicate balance of {ticker} for {identity}"
+            )));
+        }
+    }
+    state.insert("initial".to_string(), Value::Object(initial));
+
+    let mut state = Value::Object(state);
+    let hash = dry_run(&state)?;
+    state["hash"] = Value::String(hash);
+
+    // Loading the state verifies the hash.
+    dry_run(&state)?;
+    serde_json::to_string_pretty(&state).map_err(ManyError::serialization_error)
+}
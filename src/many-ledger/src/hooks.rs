@@ -0,0 +1,186 @@
+//! Post-commit hooks, to trigger downstream pipelines from the node.
+//!
+//! A hook is either a program, executed with the height, the hash and the event
+//! count as arguments, or an http:// URL receiving them as a JSON POST. Every hook
+//! runs on its own thread after the block is committed and is abandoned after a
+//! timeout, so a slow or failing hook never delays the commit or other hooks.
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Timeout of a hook when none is specified, in seconds.
+pub const DEFAULT_HOOK_TIMEOUT: u64 = 10;
+
+/// Interval between two checks of a running program.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CommitHook {
+    /// Execute a program with the height, hash and event count as arguments.
+    Exec(PathBuf),
+
+    /// POST the height, hash and event count as JSON to an http:// URL.
+    Http {
+        host: String,
+        port: u16,
+        path: String,
+    },
+}
+
+impl FromStr for CommitHook {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = match s.strip_prefix("http://") {
+            Some(rest) => rest,
+            None if s.contains("://") => {
+                return Err(format!(
+                    "Unsupported hook URL: {s}, only http:// is supported."
+                ))
+            }
+            None => return Ok(CommitHook::Exec(PathBuf::from(s))),
+        };
+
+        let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse()
+                    .map_err(|_| format!("Invalid port in hook URL: {s}."))?,
+            ),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(format!("Missing host in hook URL: {s}."));
+        }
+        Ok(CommitHook::Http {
+            host: host.to_string(),
+            port,
+            path: if path.is_empty() { "/" } else { path }.to_string(),
+        })
+    }
+}
+
+/// What hooks are told about a commit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitInfo {
+    pub height: u64,
+    pub hash: Vec<u8>,
+
+    /// The total number of events of the ledger.
+    pub event_count: u64,
+}
+
+impl CommitInfo {
+    fn to_json(&self) -> String {
+        format!(
+            r#"{{"height":{},"hash":"{}","event_count":{}}}"#,
+            self.height,
+            hex::encode(&self.hash),
+            self.event_count
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CommitHooks {
+    hooks: Vec<CommitHook>,
+    timeout: Duration,
+}
+
+impl CommitHooks {
+    pub fn new(hooks: Vec<CommitHook>, timeout: Duration) -> Self {
+        Self { hooks, timeout }
+    }
+
+    /// Run every hook in the background. Failures are logged and ignored.
+    pub fn notify(&self, info: &CommitInfo) -> Vec<std::thread::JoinHandle<()>> {
+        self.hooks
+            .iter()
+            .cloned()
+            .map(|hook| {
+                let (info, timeout) = (info.clone(), self.timeout);
+                std::thread::spawn(move || {
+                    if let Err(e) = run(&hook, &info, timeout) {
+                        warn!("Commit hook {hook:?} failed at height {}: {e}", info.height);
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+fn run(hook: &CommitHook, info: &CommitInfo, timeout: Duration) -> Result<(), String> {
+    match hook {
+        CommitHook::Exec(program) => exec(program, info, timeout),
+        CommitHook::Http { host, port, path } => post(host, *port, path, info, timeout),
+    }
+}
+
+fn exec(program: &Path, info: &CommitInfo, timeout: Duration) -> Result<(), String> {
+    let mut child = Command::new(program)
+        .arg(info.height.to_string())
+        .arg(hex::encode(&info.hash))
+        .arg(info.event_count.to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            return if status.success() {
+                Ok(())
+            } else {
+                Err(format!("exited with {status}"))
+            };
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!("timed out after {}s", timeout.as_secs_f64()));
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn post(
+    host: &str,
+    port: u16,
+    path: &str,
+    info: &CommitInfo,
+    timeout: Duration,
+) -> Result<(), String> {
+    let addr = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| e.to_string())?
+        .next()
+        .ok_or_else(|| format!("could not resolve {host}"))?;
+    let mut stream = TcpStream::connect_timeout(&addr, timeout).map_err(|e| e.to_string())?;
+    stream
+        .set_read_timeout(Some(timeout))
+        .and_then(|_| stream.set_write_timeout(Some(timeout)))
+        .map_err(|e| e.to_string())?;
+
+    let body = info.to_json();
+    write!(
+        stream,
+        "POST {path} HTTP/1.1\r\nHost: {host}:{port}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut status = String::new();
+    BufReader::new(stream)
+        .read_line(&mut status)
+        .map_err(|e| e.to_string())?;
+    match status.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(()),
+        _ => Err(format!("unexpected response: {}", status.trim())),
+    }
+}
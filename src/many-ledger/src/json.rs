@@ -1,5 +1,11 @@
+use crate::error;
 use crate::storage::account::AccountMeta;
+use crate::storage::faucet::FaucetConfig;
+use crate::storage::fees::{TransferFee, MAX_BASIS_POINTS};
 use crate::storage::ledger_tokens::SymbolMeta;
+use crate::storage::limits::PayloadLimits;
+use crate::storage::multisig::MultisigDefaults;
+use crate::storage::token_metadata::TokenMetadata;
 use many_error::ManyError;
 use many_identity::Address;
 use many_modules::account;
@@ -9,6 +15,11 @@ use many_types::ledger::{Symbol, TokenAmount};
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
+use std::str::FromStr;
+
+/// The latest version of the initial state schema. Files without a version
+/// are version 1, and still load.
+pub const INITIAL_STATE_VERSION: u32 = 2;
 
 #[derive(serde::Deserialize, Clone, Debug, Default)]
 pub struct MultisigFeatureArgJson {
@@ -128,19 +139,172 @@ impl From<SymbolMetaJson> for SymbolMeta {
     }
 }
 
+#[derive(serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferFeeJson {
+    Flat(TokenAmount),
+    BasisPoints(u32),
+}
+
+/// Converts the JSON transfer fee to our internal representation
+impl From<TransferFeeJson> for TransferFee {
+    fn from(value: TransferFeeJson) -> Self {
+        match value {
+            TransferFeeJson::Flat(amount) => TransferFee::Flat(amount),
+            TransferFeeJson::BasisPoints(bps) => TransferFee::BasisPoints(bps),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, Clone, Debug, Default)]
+pub struct MultisigDefaultsJson {
+    pub timeout_in_secs: Option<u64>,
+    pub execute_automatically: Option<bool>,
+}
+
+/// Converts the JSON multisig defaults to our internal representation
+impl From<MultisigDefaultsJson> for MultisigDefaults {
+    fn from(value: MultisigDefaultsJson) -> Self {
+        Self {
+            timeout_in_secs: value.timeout_in_secs,
+            execute_automatically: value.execute_automatically,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct FaucetJson {
+    pub account: Address,
+    pub amounts: BTreeMap<Symbol, TokenAmount>,
+    /// In seconds.
+    pub period: u64,
+}
+
+/// Converts the JSON faucet to our internal representation
+impl From<FaucetJson> for FaucetConfig {
+    fn from(value: FaucetJson) -> Self {
+        Self {
+            account: value.account,
+            amounts: value.amounts,
+            period: value.period,
+        }
+    }
+}
+
+/// The payload limits, the defaults apply to the missing ones.
+#[derive(serde::Deserialize, Clone, Debug, Default)]
+pub struct PayloadLimitsJson {
+    pub max_memo_size: Option<usize>,
+    pub max_credential_size: Option<usize>,
+}
+
+impl From<PayloadLimitsJson> for PayloadLimits {
+    fn from(value: PayloadLimitsJson) -> Self {
+        let defaults = PayloadLimits::default();
+        Self {
+            max_memo_size: value.max_memo_size.unwrap_or(defaults.max_memo_size),
+            max_credential_size: value
+                .max_credential_size
+                .unwrap_or(defaults.max_credential_size),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct TokenMetadataJson {
+    pub decimals: u64,
+    pub ticker: String,
+    pub description: Option<String>,
+    /// Hex encoded.
+    pub logo_hash: Option<String>,
+}
+
+/// Converts the JSON token metadata to our internal representation
+impl TryFrom<TokenMetadataJson> for TokenMetadata {
+    type Error = ManyError;
+
+    fn try_from(value: TokenMetadataJson) -> Result<Self, Self::Error> {
+        Ok(Self {
+            decimals: value.decimals,
+            ticker: value.ticker,
+            description: value.description,
+            logo_hash: value
+                .logo_hash
+                .map(|h| hex::decode(h).map(Into::into))
+                .transpose()
+                .map_err(ManyError::deserialization_error)?,
+        })
+    }
+}
+
+/// Deserialize a map, failing on duplicate keys instead of keeping the last value.
+fn deserialize_unique_map<'de, D, K, V>(deserializer: D) -> Result<BTreeMap<K, V>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    K: serde::Deserialize<'de> + Ord + std::fmt::Display,
+    V: serde::Deserialize<'de>,
+{
+    struct UniqueMapVisitor<K, V>(std::marker::PhantomData<(K, V)>);
+
+    impl<'de, K, V> serde::de::Visitor<'de> for UniqueMapVisitor<K, V>
+    where
+        K: serde::Deserialize<'de> + Ord + std::fmt::Display,
+        V: serde::Deserialize<'de>,
+    {
+        type Value = BTreeMap<K, V>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a map without duplicate keys")
+        }
+
+        fn visit_map<A: serde::de::MapAccess<'de>>(
+            self,
+            mut access: A,
+        ) -> Result<Self::Value, A::Error> {
+            let mut map = BTreeMap::new();
+            while let Some((k, v)) = access.next_entry::<K, V>()? {
+                if map.contains_key(&k) {
+                    return Err(serde::de::Error::custom(format!("duplicate key '{k}'")));
+                }
+                map.insert(k, v);
+            }
+            Ok(map)
+        }
+    }
+
+    deserializer.deserialize_map(UniqueMapVisitor(std::marker::PhantomData))
+}
+
 /// The initial state schema, loaded from JSON.
 #[derive(serde::Deserialize, Clone, Debug, Default)]
 pub struct InitialStateJson {
+    /// The version of the schema, 1 if absent. Version 2 adds
+    /// `multisig_defaults`, and validates the accounts.
+    pub version: Option<u32>,
     pub identity: Address,
+    #[serde(deserialize_with = "deserialize_unique_map")]
     pub initial: BTreeMap<Address, BTreeMap<String, TokenAmount>>,
     pub token_identity: Option<Address>,
     pub account_identity: Option<Address>,
     pub token_next_subresource: Option<u32>,
+    #[serde(deserialize_with = "deserialize_unique_map")]
     pub symbols: BTreeMap<Address, String>,
     pub symbols_meta: Option<BTreeMap<Address, SymbolMetaJson>>,
     pub accounts: Option<Vec<AccountJson>>,
+    pub multisig_defaults: Option<MultisigDefaultsJson>,
     pub id_store_seed: Option<u64>,
     pub id_store_keys: Option<BTreeMap<String, String>>,
+    pub fee_collector: Option<Address>,
+    pub fees: Option<BTreeMap<Symbol, TransferFeeJson>>,
+    pub token_metadata: Option<BTreeMap<Symbol, TokenMetadataJson>>,
+    pub compliance_identity: Option<Address>,
+    pub notice_identity: Option<Address>,
+    pub validator_identity: Option<Address>,
+    pub faucet: Option<FaucetJson>,
+    pub payload_limits: Option<PayloadLimitsJson>,
+
+    /// Maximum age of the events, in seconds, see [`crate::storage::event_pruning`].
+    pub events_max_age: Option<u64>,
     pub hash: Option<String>,
 }
 
@@ -157,9 +321,173 @@ impl InitialStateJson {
                 )));
             }
         }
+        s.validate().map_err(Box::new)?;
         Ok(s)
     }
 
+    /// Verify the consistency of the initial state, so a mistake fails loudly
+    /// instead of producing a different initial hash.
+    pub fn validate(&self) -> Result<(), ManyError> {
+        let version = self.version.unwrap_or(1);
+        if version == 0 || version > INITIAL_STATE_VERSION {
+            return Err(error::invalid_genesis(format!(
+                "unsupported version {version}, the latest is {INITIAL_STATE_VERSION}"
+            )));
+        }
+        if version < 2 && self.multisig_defaults.is_some() {
+            return Err(error::invalid_genesis(
+                "multisig_defaults requires version 2".to_string(),
+            ));
+        }
+        if version >= 2 {
+            self.validate_accounts()?;
+        }
+
+        let identities = [
+            ("identity", Some(self.identity)),
+            ("token_identity", self.token_identity),
+            ("account_identity", self.account_identity),
+            ("fee_collector", self.fee_collector),
+            ("compliance_identity", self.compliance_identity),
+            ("notice_identity", self.notice_identity),
+            ("validator_identity", self.validator_identity),
+            ("faucet.account", self.faucet.as_ref().map(|f| f.account)),
+        ];
+        for (name, identity) in identities {
+            if let Some(identity) = identity {
+                if identity.is_anonymous() || identity.is_illegal() {
+                    return Err(error::invalid_genesis(format!(
+                        "{name} must be a public key or subresource identity, was {identity}"
+                    )));
+                }
+            }
+        }
+
+        let mut names = BTreeSet::new();
+        for (symbol, name) in &self.symbols {
+            if symbol.is_anonymous() || symbol.is_illegal() {
+                return Err(error::invalid_genesis(format!(
+                    "invalid symbol identity {symbol}"
+                )));
+            }
+            if !names.insert(name) {
+                return Err(error::invalid_genesis(format!(
+                    "duplicate symbol name '{name}'"
+                )));
+            }
+            if self.symbols.keys().any(|s| s.to_string() == *name) {
+                return Err(error::invalid_genesis(format!(
+                    "symbol name '{name}' is the identity of another symbol"
+                )));
+            }
+        }
+
+        let undefined = |symbol: &Symbol, field: &str| {
+            error::invalid_genesis(format!("{field} references undefined symbol {symbol}"))
+        };
+        let metas = self.symbols_meta.iter().flat_map(|m| m.keys());
+        let fees = self.fees.iter().flat_map(|f| f.keys());
+        let metadata = self.token_metadata.iter().flat_map(|m| m.keys());
+        for (field, symbol) in metas
+            .map(|s| ("symbols_meta", s))
+            .chain(fees.map(|s| ("fees", s)))
+            .chain(metadata.map(|s| ("token_metadata", s)))
+        {
+            if !self.symbols.contains_key(symbol) {
+                return Err(undefined(symbol, field));
+            }
+        }
+
+        for (symbol, fee) in self.fees.iter().flatten() {
+            if let TransferFeeJson::BasisPoints(bps) = fee {
+                if *bps > MAX_BASIS_POINTS {
+                    return Err(error::invalid_transfer_fee(symbol, bps, MAX_BASIS_POINTS));
+                }
+            }
+        }
+
+        let mut totals: BTreeMap<Symbol, TokenAmount> = BTreeMap::new();
+        for (id, balances) in &self.initial {
+            if id.is_anonymous() || id.is_illegal() {
+                return Err(error::invalid_genesis(format!(
+                    "initial balances of invalid identity {id}"
+                )));
+            }
+            let mut seen = BTreeSet::new();
+            for (token_name, amount) in balances {
+                let symbol = self.resolve_symbol(token_name).ok_or_else(|| {
+                    error::invalid_genesis(format!(
+                        "initial balance of {id} references undefined symbol '{token_name}'"
+                    ))
+                })?;
+                if !seen.insert(symbol) {
+                    return Err(error::invalid_genesis(format!(
+                        "initial balance of {id} lists symbol {symbol} more than once"
+                    )));
+                }
+                *totals.entry(symbol).or_default() += amount.clone();
+            }
+        }
+
+        for (symbol, meta) in self.symbols_meta.iter().flatten() {
+            if let Some(maximum) = &meta.maximum {
+                let total = totals.get(symbol).cloned().unwrap_or_default();
+                if total > *maximum {
+                    return Err(error::invalid_genesis(format!(
+                        "initial supply of {symbol} ({total}) exceeds its maximum ({maximum})"
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Verify the roles and features of the accounts, which would otherwise
+    /// panic when converted.
+    fn validate_accounts(&self) -> Result<(), ManyError> {
+        for (i, account) in self.accounts.iter().flatten().enumerate() {
+            let name = account
+                .id
+                .map_or_else(|| format!("account #{i}"), |id| format!("account {id}"));
+            for (id, roles) in &account.roles {
+                for role in roles {
+                    if account::Role::from_str(role).is_err() {
+                        return Err(error::invalid_genesis(format!(
+                            "{name} gives the unknown role '{role}' to {id}"
+                        )));
+                    }
+                }
+            }
+            for feature in &account.features {
+                if feature.id == features::multisig::MultisigAccountFeature::ID {
+                    let arg = feature.arg.clone().unwrap_or_default();
+                    if serde_json::from_value::<MultisigFeatureArgJson>(arg).is_err() {
+                        return Err(error::invalid_genesis(format!(
+                            "{name} has an invalid multisig feature argument"
+                        )));
+                    }
+                } else if feature.try_into_feature().is_none() {
+                    return Err(error::invalid_genesis(format!(
+                        "{name} has the unsupported feature {}",
+                        feature.id
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve_symbol(&self, token_name: &str) -> Option<Symbol> {
+        self.symbols.iter().find_map(|(s, n)| {
+            if *s == token_name || n == token_name {
+                Some(*s)
+            } else {
+                None
+            }
+        })
+    }
+
     pub fn symbols(&self) -> BTreeMap<Address, String> {
         self.symbols.clone()
     }
@@ -170,19 +498,9 @@ impl InitialStateJson {
             .map(|(id, b)| {
                 let mut balances = BTreeMap::new();
                 for (token_name, amount) in b {
-                    let symbol = self
-                        .symbols
-                        .iter()
-                        .find_map(|(s, n)| {
-                            if *s == token_name.as_str() || n == token_name {
-                                Some(*s)
-                            } else {
-                                None
-                            }
-                        })
-                        .ok_or_else(|| {
-                            ManyError::unknown(format!("Could not resolve symbol '{token_name}'"))
-                        })?;
+                    let symbol = self.resolve_symbol(token_name).ok_or_else(|| {
+                        ManyError::unknown(format!("Could not resolve symbol '{token_name}'"))
+                    })?;
                     balances.insert(symbol, amount.clone());
                 }
                 Ok((*id, balances))
@@ -2,8 +2,17 @@
 
 extern crate core;
 
+pub mod amount;
 pub mod error;
+pub mod genesis_file;
+pub mod hooks;
 pub mod json;
+pub mod metrics;
 pub mod migration;
 pub mod module;
+pub mod response_metadata;
+pub mod schema;
 pub mod storage;
+pub mod subscriptions;
+pub mod unsigned_response;
+pub mod view;
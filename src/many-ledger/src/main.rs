@@ -6,6 +6,7 @@ use many_identity::{Address, Identity};
 use many_identity_dsa::{CoseKeyIdentity, CoseKeyVerifier};
 use many_identity_webauthn::WebAuthnVerifier;
 use many_migration::MigrationConfig;
+use many_modules::abci_backend::ManyAbciModuleBackend;
 use many_modules::account::features::Feature;
 use many_modules::{abci_backend, account, data, events, idstore, ledger};
 use many_protocol::ManyUrl;
@@ -27,11 +28,24 @@ use crate::migration::MIGRATIONS;
 use crate::module::account::AccountFeatureModule;
 use module::*;
 
+mod amount;
+mod audit;
+mod catch_up;
+mod dev;
 mod error;
+mod genesis_file;
+mod hooks;
 mod json;
+mod metrics;
 mod migration;
 mod module;
+mod replica;
+mod response_metadata;
+mod schema;
 mod storage;
+mod subscriptions;
+mod unsigned_response;
+mod view;
 
 #[derive(clap::ArgEnum, Clone, Debug)]
 enum LogStrategy {
@@ -39,6 +53,12 @@ enum LogStrategy {
     Syslog,
 }
 
+#[derive(clap::ArgEnum, Clone, Debug)]
+enum SchemaFormat {
+    Cddl,
+    Openapi,
+}
+
 #[derive(Parser, Debug)]
 #[clap(args_override_self(true))]
 struct Opts {
@@ -63,6 +83,11 @@ struct Opts {
     #[clap(long)]
     abci: bool,
 
+    /// Run without Tendermint, for local development. Every command is
+    /// executed and committed in a block of its own.
+    #[clap(long, conflicts_with_all = &["abci", "replica-of"])]
+    dev: bool,
+
     /// Path of a state file (that will be used for the initial setup).
     #[clap(long)]
     state: Option<PathBuf>,
@@ -106,19 +131,215 @@ struct Opts {
 
     /// Path to a JSON file containing the configurations for the
     /// migrations. Migrations are DISABLED unless this configuration file
-    /// is given.
+    /// is given. The activation heights are recorded in the state, and
+    /// cannot change once a migration activated.
     #[clap(long, short)]
     migrations_config: Option<PathBuf>,
 
+    /// Directory where the state is backed up before activating migrations.
+    /// Defaults to the persistent storage path with a `.backups` extension.
+    #[clap(long)]
+    migration_backups: Option<PathBuf>,
+
+    /// Do not back up the state before activating migrations.
+    #[clap(long, conflicts_with = "migration-backups")]
+    no_migration_backups: bool,
+
+    /// Activate the pending migrations on a copy of the persistent store,
+    /// print the resulting hash at each activation height and exit. The
+    /// store is not modified.
+    #[clap(long, requires = "migrations-config", conflicts_with = "clean")]
+    dry_run_migrations: bool,
+
+    /// Verify the integrity of the persistent store, e.g. after a crash:
+    /// recompute its tree hashes, compare its root with the application
+    /// hash, and check the ledger invariants. Print a report and exit, with
+    /// status 1 if any divergence is found.
+    #[clap(long, conflicts_with_all = &["clean", "dry-run-migrations"])]
+    verify_state: bool,
+
     /// List built-in migrations supported by this binary
     #[clap(long, exclusive = true)]
     list_migrations: bool,
 
+    /// Print the schema of the endpoints and exit: CDDL of the MANY messages,
+    /// or OpenAPI of the routes of the REST gateway.
+    #[clap(long, arg_enum, exclusive = true)]
+    dump_schema: Option<SchemaFormat>,
+
+    /// Build an initial state from a spec file listing the balances, with
+    /// the hash of the state embedded, print it and exit. See
+    /// [`genesis_file`] for the format of the spec.
+    #[clap(long, exclusive = true)]
+    genesis: Option<PathBuf>,
+
     /// Path to a JSON file containing an array of MANY addresses
     /// Only addresses from this array will be able to execute commands, e.g., send, put, ...
     /// Any addresses will be able to execute queries, e.g., balance, get, ...
     #[clap(long)]
     allow_addrs: Option<PathBuf>,
+
+    /// Path to a rocksdb database where events older than --cold-after heights
+    /// are copied. The events pruned from the persistent store are still served
+    /// from it.
+    #[clap(long, requires = "cold-after")]
+    cold_store: Option<PathBuf>,
+
+    /// Number of heights before events are copied to the cold store.
+    #[clap(long, requires = "cold-store")]
+    cold_after: Option<u64>,
+
+    /// Number of blocks Tendermint keeps, older blocks are pruned. Every block
+    /// is kept if left empty.
+    #[clap(long)]
+    retain_blocks: Option<u64>,
+
+    /// Maximum number of bytes served to a single identity by the event
+    /// listing endpoints over --quota-window seconds. Unlimited if left empty.
+    /// Anonymous requests are not counted, their responses are limited to
+    /// 64 KiB instead.
+    #[clap(long)]
+    download_quota: Option<u64>,
+
+    /// The duration of the sliding window of --download-quota, in seconds.
+    #[clap(long, default_value_t = 3600)]
+    quota_window: u64,
+
+    /// Number of recall phrase lookups an identity can make at once.
+    #[clap(long, default_value_t = 10)]
+    recall_phrase_burst: u32,
+
+    /// Number of recall phrase lookups an identity regains per hour. Lookups
+    /// are not limited if 0.
+    #[clap(long, default_value_t = 60)]
+    recall_phrase_rate: u32,
+
+    /// Path to a directory where snapshot archives are kept. Snapshots are
+    /// also served to new nodes joining the network through the ABCI bridge.
+    #[clap(long, requires = "snapshot-interval")]
+    snapshots: Option<PathBuf>,
+
+    /// Number of heights between two snapshots.
+    #[clap(long, requires = "snapshots")]
+    snapshot_interval: Option<u64>,
+
+    /// Number of snapshots kept in --snapshots.
+    #[clap(long, default_value_t = storage::snapshot::DEFAULT_SNAPSHOTS_KEPT)]
+    snapshot_keep: usize,
+
+    /// Remove the snapshots older than this number of seconds, in block time.
+    /// The latest snapshot is always kept.
+    #[clap(long)]
+    snapshot_max_age: Option<u64>,
+
+    /// Restore the persistent store from this snapshot archive before
+    /// starting. The ledger resumes from the height of the snapshot. The
+    /// existing persistent store, if any, is replaced.
+    #[clap(long, conflicts_with_all = &["clean", "replica-of"])]
+    restore_snapshot: Option<PathBuf>,
+
+    /// The application hash recorded by the chain at the height of
+    /// --restore-snapshot, in hexadecimal. The snapshot is refused if its
+    /// state hash does not match.
+    #[clap(long, requires = "restore-snapshot")]
+    restore_app_hash: Option<String>,
+
+    /// Keep the state diff of this number of heights, for the nodes catching up
+    /// from this node with --catch-up-from.
+    #[clap(long, requires = "abci")]
+    keep_state_diffs: Option<u64>,
+
+    /// Before starting, catch up from the MANY server of a trusted peer at this
+    /// http:// URL by applying its state diffs instead of replaying the blocks.
+    /// The peer must keep state diffs and take snapshots.
+    #[clap(
+        long,
+        requires = "catch-up-peer",
+        conflicts_with_all = &["clean", "restore-snapshot", "replica-of"]
+    )]
+    catch_up_from: Option<ManyUrl>,
+
+    /// The address of the peer of --catch-up-from, which must sign every
+    /// response.
+    #[clap(long, requires = "catch-up-from")]
+    catch_up_peer: Option<Address>,
+
+    /// Compare the application hash of every height of the persistent store
+    /// with the headers of the Tendermint RPC server at --audit-rpc, write a
+    /// report signed with --pem to this path, and exit. The exit status is 1
+    /// if any hash differs.
+    #[clap(long, requires = "audit-rpc")]
+    audit_app_hashes: Option<PathBuf>,
+
+    /// The http:// URL of the Tendermint RPC server of --audit-app-hashes.
+    #[clap(long, requires = "audit-app-hashes")]
+    audit_rpc: Option<ManyUrl>,
+
+    /// The first height audited by --audit-app-hashes.
+    #[clap(long, default_value_t = 1)]
+    audit_from: u64,
+
+    /// Run as a read replica of the MANY server at this http:// URL. The
+    /// replica restores the snapshots of the primary, which must take
+    /// snapshots, and refuses commands.
+    #[clap(long, conflicts_with = "abci")]
+    replica_of: Option<ManyUrl>,
+
+    /// Number of seconds between two checks for a new snapshot of the primary.
+    #[clap(long, default_value_t = 10)]
+    replica_poll: u64,
+
+    /// Maximum age of the state served by a replica, in seconds. Queries
+    /// fail while the replica is staler.
+    #[clap(long, default_value_t = 600)]
+    max_staleness: u64,
+
+    /// The address and port to bind to for the Prometheus metrics listener,
+    /// serving `GET /metrics`. Metrics are disabled if left empty. The growth
+    /// of the events and of the store is only counted when metrics are enabled.
+    #[clap(long)]
+    metrics: Option<SocketAddr>,
+
+    /// The address and port to bind to for the WebSocket listener serving
+    /// event subscriptions. Subscriptions are disabled if left empty.
+    #[clap(long)]
+    subscriptions: Option<SocketAddr>,
+
+    /// A program to execute, or an http:// URL to POST to, after every commit.
+    /// Programs receive the height, the hash and the event count as arguments,
+    /// URLs as a JSON object. Can be repeated.
+    #[clap(long, multiple_occurrences = true)]
+    commit_hook: Vec<hooks::CommitHook>,
+
+    /// Number of seconds after which a commit hook is abandoned.
+    #[clap(long, default_value_t = hooks::DEFAULT_HOOK_TIMEOUT)]
+    commit_hook_timeout: u64,
+
+    /// Recompute the ledger invariants (token supplies, event log and index)
+    /// after every commit, and stop the node with a report if any is violated.
+    /// This reads every balance, so is meant for networks of moderate size.
+    #[clap(long)]
+    check_invariants: bool,
+
+    /// Number of responses to anonymous queries cached until the next block,
+    /// see [`unsigned_response`]. 0 disables the cache.
+    #[clap(long, default_value_t = unsigned_response::DEFAULT_RESPONSE_CACHE_SIZE)]
+    response_cache_size: usize,
+
+    /// Number of balances cached, see [`storage::balance_cache`]. 0 disables
+    /// the cache.
+    #[clap(long, default_value_t = storage::balance_cache::DEFAULT_BALANCE_CACHE_SIZE)]
+    balance_cache_size: usize,
+
+    /// Answer the balance and events queries from a view of the committed
+    /// state, added after every commit, so they don't wait for the block
+    /// being executed. See [`view`].
+    #[clap(long, conflicts_with_all = &["cold-store", "replica-of"])]
+    read_view: bool,
+
+    /// Number of heights whose views are kept, for queries at a height.
+    #[clap(long, requires = "read-view", default_value_t = view::DEFAULT_VIEW_HEIGHTS)]
+    read_view_heights: usize,
 }
 
 fn main() {
@@ -128,14 +349,52 @@ fn main() {
         pem,
         addr,
         abci,
+        dev,
         mut state,
         persistent,
         clean,
         logmode,
         migrations_config,
+        migration_backups,
+        no_migration_backups,
+        dry_run_migrations,
+        verify_state,
         allow_origin,
         allow_addrs,
         list_migrations,
+        dump_schema,
+        genesis,
+        cold_store,
+        cold_after,
+        retain_blocks,
+        download_quota,
+        quota_window,
+        recall_phrase_burst,
+        recall_phrase_rate,
+        snapshots,
+        snapshot_interval,
+        snapshot_keep,
+        snapshot_max_age,
+        restore_snapshot,
+        restore_app_hash,
+        keep_state_diffs,
+        catch_up_from,
+        catch_up_peer,
+        audit_app_hashes,
+        audit_rpc,
+        audit_from,
+        replica_of,
+        replica_poll,
+        max_staleness,
+        metrics,
+        subscriptions,
+        commit_hook,
+        commit_hook_timeout,
+        check_invariants,
+        response_cache_size,
+        balance_cache_size,
+        read_view,
+        read_view_heights,
         ..
     } = Opts::parse();
 
@@ -180,19 +439,60 @@ fn main() {
         return;
     }
 
+    if let Some(format) = dump_schema {
+        match format {
+            SchemaFormat::Cddl => print!("{}", schema::cddl()),
+            SchemaFormat::Openapi => println!("{}", schema::openapi()),
+        }
+        return;
+    }
+
+    if let Some(spec) = genesis {
+        let spec = std::fs::read_to_string(spec).expect("Could not read the genesis spec");
+        match genesis_file::build(&spec) {
+            Ok(state) => println!("{state}"),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     // Safe unwrap.
     // At this point the Options should contain a value.
     let pem = pem.unwrap();
     let persistent = persistent.unwrap();
 
+    if let Some(archive) = restore_snapshot {
+        let app_hash =
+            restore_app_hash.map(|h| hex::decode(h).expect("Invalid --restore-app-hash."));
+        let info =
+            storage::snapshot::restore_snapshot_archive(&archive, &persistent, app_hash.as_deref())
+                .expect("Could not restore the snapshot.");
+        info!(
+            "Restored the snapshot {} at height {}",
+            archive.display(),
+            info.height
+        );
+    }
+
+    if let Some((url, peer)) = catch_up_from.zip(catch_up_peer) {
+        catch_up::CatchUp::new(url, peer)
+            .run(&persistent)
+            .expect("Could not catch up from the peer.");
+    }
+
     if clean {
-        // Delete the persistent storage.
+        // Delete the persistent storage, and the events moved out of it.
         // Ignore NotFound errors.
-        match std::fs::remove_dir_all(persistent.as_path()) {
-            Ok(_) => {}
-            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
-            Err(e) => {
-                panic!("Error: {e}")
+        for path in std::iter::once(&persistent).chain(cold_store.iter()) {
+            match std::fs::remove_dir_all(path.as_path()) {
+                Ok(_) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    panic!("Error: {e}")
+                }
             }
         }
     } else if persistent.exists() {
@@ -204,9 +504,31 @@ fn main() {
     let key = CoseKeyIdentity::from_pem(pem).expect("Could not generate identity from PEM file.");
     info!(address = key.address().to_string().as_str());
 
+    if let Some((output, rpc)) = audit_app_hashes.zip(audit_rpc) {
+        let store = storage::InnerStorage::open(&persistent).expect("Could not open the store.");
+        let report = audit::AppHashAudit::new(rpc)
+            .run(&store, audit_from, &key)
+            .expect("Could not audit the application hashes.");
+        let signed = report.sign(&key).expect("Could not sign the report.");
+        std::fs::write(&output, signed).expect("Could not write the report.");
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        if !report.verified() {
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let state: Option<InitialStateJson> =
         state.map(|p| InitialStateJson::read(p).expect("Could not read state file."));
 
+    // Backups are only taken when migrations activate.
+    let migration_backups = match (&migrations_config, no_migration_backups) {
+        (Some(_), false) => {
+            Some(migration_backups.unwrap_or_else(|| persistent.with_extension("backups")))
+        }
+        _ => None,
+    };
+
     info!("Loading migrations from {migrations_config:?}");
     let maybe_migrations = migrations_config.map(|file| {
         let content = std::fs::read_to_string(file)
@@ -215,6 +537,56 @@ fn main() {
         config.strict()
     });
 
+    if dry_run_migrations {
+        let storage = storage::LedgerStorage::load(&persistent, true, maybe_migrations)
+            .expect("Could not load the persistent store.");
+        let height = storage.get_height().expect("Could not read the height.");
+        println!("Height: {height}");
+        println!("Hash: {}", hex::encode(storage.hash()));
+        for run in storage
+            .dry_run_migrations()
+            .expect("Could not simulate the migrations.")
+        {
+            println!(
+                "Height {}: {} => {}",
+                run.height,
+                run.migrations.join(", "),
+                hex::encode(run.hash)
+            );
+        }
+        return;
+    }
+
+    if verify_state {
+        let storage = storage::LedgerStorage::load(&persistent, true, maybe_migrations)
+            .expect("Could not load the persistent store.");
+        let report = storage
+            .verify_state()
+            .expect("Could not verify the persistent store.");
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        if !report.verified() {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let replica = replica_of.map(|url| Arc::new(replica::Replica::new(url, max_staleness)));
+    let replica_path = persistent.with_extension("replica");
+    let read_view_path = persistent.with_extension("view");
+    let read_view_migrations = maybe_migrations.clone();
+    if let Some(replica) = &replica {
+        if !persistent.exists() {
+            replica
+                .bootstrap(&persistent)
+                .expect("Could not restore the latest snapshot of the primary.");
+        }
+    }
+
+    // Kept to measure the storage size, the paths are moved into the storage.
+    let storage_paths: Vec<PathBuf> = std::iter::once(persistent.clone())
+        .chain(cold_store.clone())
+        .collect();
+
     let module_impl = if persistent.exists() {
         if state.is_some() {
             warn!(
@@ -237,12 +609,12 @@ fn main() {
             }
         }
 
-        LedgerModuleImpl::load(maybe_migrations, persistent, abci).unwrap()
+        LedgerModuleImpl::load(maybe_migrations, persistent, abci || dev).unwrap()
     } else if let Some(state) = state {
         #[cfg(feature = "balance_testing")]
         {
             let mut module_impl =
-                LedgerModuleImpl::new(state, maybe_migrations, persistent, abci).unwrap();
+                LedgerModuleImpl::new(state, maybe_migrations, persistent, abci || dev).unwrap();
 
             use std::str::FromStr;
 
@@ -271,15 +643,124 @@ fn main() {
         }
 
         #[cfg(not(feature = "balance_testing"))]
-        LedgerModuleImpl::new(state, maybe_migrations, persistent, abci).unwrap()
+        LedgerModuleImpl::new(state, maybe_migrations, persistent, abci || dev).unwrap()
     } else {
         panic!("Persistent store or staging file not found.")
     };
+    let module_impl =
+        module_impl.with_cold_store(cold_store.zip(cold_after).map(|(path, after)| {
+            storage::cold::ColdStore::open(path, after).expect("Could not open the cold store.")
+        }));
+    let module_impl = module_impl.with_retain_blocks(retain_blocks);
+    let module_impl =
+        module_impl.with_snapshots(snapshots.zip(snapshot_interval).map(|(path, interval)| {
+            storage::snapshot::Snapshots::new(path, interval)
+                .expect("Could not open the snapshots directory.")
+                .with_retention(snapshot_keep, snapshot_max_age)
+        }));
+    let module_impl = module_impl
+        .with_migration_backups(migration_backups)
+        .expect("Could not open the migration backups directory.");
+    let module_impl = module_impl.with_commit_hooks((!commit_hook.is_empty()).then(|| {
+        hooks::CommitHooks::new(
+            commit_hook,
+            std::time::Duration::from_secs(commit_hook_timeout),
+        )
+    }));
+    let module_impl = module_impl
+        .with_state_diffs(keep_state_diffs)
+        .with_growth_metrics(metrics.is_some())
+        .with_invariant_checks(check_invariants)
+        .with_balance_cache(balance_cache_size)
+        .with_tx_events(abci);
+    let module_impl = module_impl.with_subscriptions(subscriptions.map(|addr| {
+        let subscriptions = Arc::new(subscriptions::Subscriptions::default());
+        subscriptions::serve(addr, subscriptions.clone())
+            .expect("Could not bind the subscriptions listener.");
+        info!("Serving event subscriptions on {addr}");
+        subscriptions
+    }));
+    let read_views = read_view.then(|| {
+        let key = key.clone();
+        let allow_origin = allow_origin.clone();
+        let server: view::ViewServer = Box::new(move |module_impl| {
+            let server = ManyServer::simple(
+                "many-ledger",
+                key.clone(),
+                (
+                    AnonymousVerifier,
+                    CoseKeyVerifier,
+                    WebAuthnVerifier::new(allow_origin.clone()),
+                ),
+                Some(env!("CARGO_PKG_VERSION").to_string()),
+            );
+            {
+                let mut s = server.lock().unwrap();
+                s.add_module(ledger::LedgerModule::new(module_impl.clone()));
+                s.add_module(proof::LedgerProofModule::new(module_impl.clone()));
+                s.add_module(events::EventsModule::new(module_impl));
+            }
+            server
+        });
+        Arc::new(
+            view::ReadViews::open(
+                read_view_path,
+                read_view_migrations,
+                read_view_heights,
+                server,
+                &module_impl,
+            )
+            .expect("Could not open the read views."),
+        )
+    });
+    let module_impl = module_impl.with_read_views(read_views.clone());
     let module_impl = Arc::new(Mutex::new(module_impl));
 
+    let metrics = metrics.map(|metrics_addr| {
+        let metrics = Arc::new(metrics::Metrics::default());
+        let module_impl = module_impl.clone();
+        metrics::serve(metrics_addr, metrics.clone(), move || {
+            let mut state = module_impl
+                .lock()
+                .unwrap()
+                .state_metrics()
+                .unwrap_or_else(|e| {
+                    warn!("Could not read the state metrics: {e}");
+                    Default::default()
+                });
+            state.storage_bytes = storage_paths.iter().map(metrics::directory_size).sum();
+            state
+        })
+        .expect("Could not bind the metrics listener.");
+        info!("Serving metrics on {metrics_addr}");
+        metrics
+    });
+
+    if let Some(replica) = &replica {
+        if let Err(e) = replica.sync(&module_impl, &replica_path) {
+            warn!("Could not sync with the primary: {e}");
+        }
+        replica.clone().follow(
+            module_impl.clone(),
+            replica_path,
+            std::time::Duration::from_secs(replica_poll),
+        );
+    }
+    let commands = ManyAbciModuleBackend::init(&mut *module_impl.lock().unwrap())
+        .expect("Could not list the endpoints.")
+        .endpoints
+        .into_iter()
+        .filter(|(_, info)| info.is_command)
+        .map(|(endpoint, _)| endpoint)
+        .collect();
+    let sequence_module_impl = module_impl.clone();
+    let delivered_module_impl = module_impl.clone();
+    let unsigned_module_impl = module_impl.clone();
+    let dev_module_impl = dev.then(|| module_impl.clone());
+
     let many = ManyServer::simple(
         "many-ledger",
-        key,
+        key.clone(),
         (
             AnonymousVerifier,
             CoseKeyVerifier,
@@ -291,22 +772,114 @@ fn main() {
     {
         let mut s = many.lock().unwrap();
         s.add_module(ledger::LedgerModule::new(module_impl.clone()));
+        s.add_module(simulate::LedgerSimulateModule::new(module_impl.clone()));
+        s.add_module(fees::LedgerFeesModule::new(module_impl.clone()));
+        s.add_module(genesis::LedgerGenesisModule::new(module_impl.clone()));
+        s.add_module(snapshot::LedgerSnapshotModule::new(module_impl.clone()));
+        s.add_module(growth::LedgerGrowthModule::new(module_impl.clone()));
+        s.add_module(error_codes::LedgerErrorCodesModule::new(
+            module_impl.clone(),
+        ));
+        s.add_module(migrations::LedgerMigrationsModule::new(module_impl.clone()));
+        s.add_module(token_metadata::LedgerTokenMetadataModule::new(
+            module_impl.clone(),
+        ));
+        s.add_module(supply::LedgerSupplyModule::new(module_impl.clone()));
         let ledger_command_module = ledger::LedgerCommandsModule::new(module_impl.clone());
+        let multi_send_module = multi_send::LedgerMultiSendModule::new(module_impl.clone());
+        let allowance_module = allowance::LedgerAllowanceModule::new(module_impl.clone());
+        let pending_send_module = pending_send::LedgerPendingSendModule::new(module_impl.clone());
+        let idempotency_module = idempotency::LedgerIdempotencyModule::new(module_impl.clone());
+        let scheduled_send_module =
+            scheduled_send::LedgerScheduledSendModule::new(module_impl.clone());
+        let recurring_send_module =
+            recurring_send::LedgerRecurringSendModule::new(module_impl.clone());
+        let escrow_module = escrow::EscrowModule::new(module_impl.clone());
+        let faucet_module = faucet::FaucetModule::new(module_impl.clone());
         if let Some(path) = allow_addrs {
             let allow_addrs: BTreeSet<Address> =
                 json5::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
             s.add_module(AllowAddrsModule {
                 inner: ledger_command_module,
+                allow_addrs: allow_addrs.clone(),
+            });
+            s.add_module(AllowAddrsModule {
+                inner: multi_send_module,
+                allow_addrs: allow_addrs.clone(),
+            });
+            s.add_module(AllowAddrsModule {
+                inner: allowance_module,
+                allow_addrs: allow_addrs.clone(),
+            });
+            s.add_module(AllowAddrsModule {
+                inner: pending_send_module,
+                allow_addrs: allow_addrs.clone(),
+            });
+            s.add_module(AllowAddrsModule {
+                inner: idempotency_module,
+                allow_addrs: allow_addrs.clone(),
+            });
+            s.add_module(AllowAddrsModule {
+                inner: scheduled_send_module,
+                allow_addrs: allow_addrs.clone(),
+            });
+            s.add_module(AllowAddrsModule {
+                inner: recurring_send_module,
+                allow_addrs: allow_addrs.clone(),
+            });
+            s.add_module(AllowAddrsModule {
+                inner: escrow_module,
+                allow_addrs: allow_addrs.clone(),
+            });
+            s.add_module(AllowAddrsModule {
+                inner: faucet_module,
                 allow_addrs,
             });
         } else {
             s.add_module(ledger_command_module);
+            s.add_module(multi_send_module);
+            s.add_module(allowance_module);
+            s.add_module(pending_send_module);
+            s.add_module(idempotency_module);
+            s.add_module(scheduled_send_module);
+            s.add_module(recurring_send_module);
+            s.add_module(escrow_module);
+            s.add_module(faucet_module);
+        }
+        s.add_module(freeze::LedgerFreezeModule::new(module_impl.clone()));
+        s.add_module(notice::NoticeModule::new(module_impl.clone()));
+        s.add_module(validators::ValidatorsModule::new(module_impl.clone()));
+        s.add_module(sequence::LedgerSequenceModule::new(module_impl.clone()));
+        s.add_module(proof::LedgerProofModule::new(module_impl.clone()));
+        let events_module = events::EventsModule::new(module_impl.clone());
+        let events_page_module = events_page::EventsPageModule::new(module_impl.clone());
+        if let Some(max_bytes) = download_quota {
+            let quota = Arc::new(quota::BandwidthQuota::new(
+                max_bytes,
+                std::time::Duration::from_secs(quota_window),
+            ));
+            s.add_module(quota::QuotaModule {
+                inner: events_module,
+                quota: quota.clone(),
+            });
+            s.add_module(quota::QuotaModule {
+                inner: events_page_module,
+                quota,
+            });
+        } else {
+            s.add_module(events_module);
+            s.add_module(events_page_module);
         }
-        s.add_module(events::EventsModule::new(module_impl.clone()));
         s.add_module(ledger::LedgerTokensModule::new(module_impl.clone()));
         s.add_module(ledger::LedgerMintBurnModule::new(module_impl.clone()));
 
         let idstore_module = idstore::IdStoreModule::new(module_impl.clone());
+        let recall_phrase_limiter = (recall_phrase_rate > 0).then(|| {
+            Arc::new(rate_limit::RateLimiter::new(
+                recall_phrase_burst,
+                recall_phrase_rate,
+            ))
+        });
         #[cfg(feature = "webauthn_testing")]
         {
             let Opts {
@@ -315,16 +888,35 @@ fn main() {
             } = Opts::parse();
 
             if disable_webauthn_only_for_testing {
-                s.add_module(IdStoreWebAuthnModule {
-                    inner: idstore_module,
-                    check_webauthn: false,
+                s.add_module(rate_limit::RateLimitModule {
+                    inner: IdStoreWebAuthnModule {
+                        inner: idstore_module,
+                        check_webauthn: false,
+                    },
+                    method: "idstore.getFromRecallPhrase",
+                    limiter: recall_phrase_limiter,
                 });
             } else {
-                s.add_module(idstore_module);
+                s.add_module(rate_limit::RateLimitModule {
+                    inner: idstore_module,
+                    method: "idstore.getFromRecallPhrase",
+                    limiter: recall_phrase_limiter,
+                });
             }
         }
         #[cfg(not(feature = "webauthn_testing"))]
-        s.add_module(idstore_module);
+        s.add_module(rate_limit::RateLimitModule {
+            inner: idstore_module,
+            method: "idstore.getFromRecallPhrase",
+            limiter: recall_phrase_limiter,
+        });
+        s.add_module(idstore_update::IdStoreUpdateModule::new(
+            module_impl.clone(),
+        ));
+        s.add_module(idstore_credentials::IdStoreCredentialsModule::new(
+            module_impl.clone(),
+        ));
+        s.add_module(idstore_alias::IdStoreAliasModule::new(module_impl.clone()));
 
         s.add_module(AccountFeatureModule::new(
             account::AccountModule::new(module_impl.clone()),
@@ -333,14 +925,55 @@ fn main() {
         s.add_module(account::features::multisig::AccountMultisigModule::new(
             module_impl.clone(),
         ));
+        s.add_module(sub_account::AccountSubAccountsModule::new(
+            module_impl.clone(),
+        ));
         s.add_module(data::DataModule::new(module_impl.clone()));
         if abci {
             s.set_timeout(u64::MAX);
+            s.add_module(snapshot::AbciSnapshotModule::new(module_impl.clone()));
+            s.add_module(check_tx::AbciCheckTxModule::new(module_impl.clone()));
+            s.add_module(validators::AbciValidatorsModule::new(module_impl.clone()));
+            s.add_module(tx_events::AbciTxEventsModule::new(module_impl.clone()));
             s.add_module(abci_backend::AbciModule::new(module_impl));
         }
     }
 
-    let mut many_server = HttpServer::new(many);
+    let mut many_server = HttpServer::new(replica::ReplicaHandler {
+        inner: metrics::MetricsHandler {
+            inner: unsigned_response::UnsignedResponseHandler {
+                inner: response_metadata::ResponseMetadataHandler {
+                    inner: dev::DevHandler {
+                        inner: delivered::DeliveredHandler {
+                            inner: sequence::SequenceHandler {
+                                inner: view::ReadViewHandler {
+                                    inner: many,
+                                    views: read_views,
+                                    key: key.clone(),
+                                },
+                                module_impl: sequence_module_impl,
+                                commands: commands.clone(),
+                            },
+                            module_impl: delivered_module_impl,
+                            commands: commands.clone(),
+                            key: key.clone(),
+                        },
+                        module_impl: dev_module_impl,
+                        commands: commands.clone(),
+                        last_time: Default::default(),
+                    },
+                    key: key.clone(),
+                },
+                module_impl: unsigned_module_impl,
+                commands: commands.clone(),
+                cache: Mutex::new(unsigned_response::ResponseCache::new(response_cache_size)),
+            },
+            metrics,
+        },
+        replica,
+        commands,
+        key,
+    });
 
     signal_hook::flag::register(signal_hook::consts::SIGTERM, many_server.term_signal())
         .expect("Could not register signal handler");
@@ -0,0 +1,255 @@
+//! Prometheus metrics of the ledger server.
+//!
+//! Metrics are served in the Prometheus text exposition format by a small HTTP
+//! listener on its own address, so scrapers never go through the MANY server.
+use async_trait::async_trait;
+use coset::CoseSign1;
+use many_protocol::RequestMessage;
+use many_server::transport::LowLevelManyRequestHandler;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Maximum number of distinct method names counted. Calls to any other method
+/// are counted under `other`, so clients cannot grow the metrics without bound
+/// by sending made up method names.
+const MAX_METHODS: usize = 256;
+
+const OTHER_METHOD: &str = "other";
+
+/// Values read from the ledger state when the metrics are scraped.
+#[derive(Clone, Debug, Default)]
+pub struct StateMetrics {
+    pub height: u64,
+    pub events: u64,
+    pub storage_bytes: u64,
+    pub growth: GrowthRates,
+}
+
+/// Average growth per day, see [`crate::storage::growth`].
+#[derive(Clone, Debug, Default)]
+pub struct GrowthRates {
+    /// Events per day, per module.
+    pub events: BTreeMap<String, f64>,
+
+    /// Bytes per day, per key root.
+    pub bytes: BTreeMap<String, f64>,
+
+    pub symbol_events: BTreeMap<String, f64>,
+    pub symbol_bytes: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Default)]
+struct CommitMetrics {
+    count: u64,
+    seconds: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct Metrics {
+    calls: Mutex<BTreeMap<String, u64>>,
+    commits: Mutex<CommitMetrics>,
+}
+
+impl Metrics {
+    pub fn record_call(&self, method: &str) {
+        let mut calls = self.calls.lock().unwrap();
+        if let Some(count) = calls.get_mut(method) {
+            *count += 1;
+        } else if calls.len() < MAX_METHODS {
+            calls.insert(method.to_string(), 1);
+        } else {
+            *calls.entry(OTHER_METHOD.to_string()).or_default() += 1;
+        }
+    }
+
+    pub fn record_commit(&self, duration: Duration) {
+        let mut commits = self.commits.lock().unwrap();
+        commits.count += 1;
+        commits.seconds += duration.as_secs_f64();
+    }
+
+    /// Render every metric in the Prometheus text exposition format.
+    pub fn render(&self, state: &StateMetrics) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP many_ledger_calls_total Number of calls of each endpoint.\n");
+        out.push_str("# TYPE many_ledger_calls_total counter\n");
+        for (method, count) in self.calls.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "many_ledger_calls_total{{method=\"{}\"}} {count}",
+                escape_label(method)
+            );
+        }
+
+        let commits = self.commits.lock().unwrap();
+        out.push_str("# HELP many_ledger_commit_seconds Time spent committing blocks.\n");
+        out.push_str("# TYPE many_ledger_commit_seconds summary\n");
+        let _ = writeln!(out, "many_ledger_commit_seconds_sum {}", commits.seconds);
+        let _ = writeln!(out, "many_ledger_commit_seconds_count {}", commits.count);
+
+        let gauges = [
+            (
+                "many_ledger_height",
+                "Height of the last committed block.",
+                state.height,
+            ),
+            (
+                "many_ledger_events",
+                "Number of events in the event log.",
+                state.events,
+            ),
+            (
+                "many_ledger_storage_bytes",
+                "Size of the storage on disk, in bytes.",
+                state.storage_bytes,
+            ),
+        ];
+        for (name, help, value) in gauges {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} gauge");
+            let _ = writeln!(out, "{name} {value}");
+        }
+
+        let growth = [
+            (
+                "many_ledger_events_per_day",
+                "Events logged per day, per module.",
+                "module",
+                &state.growth.events,
+            ),
+            (
+                "many_ledger_storage_bytes_per_day",
+                "Bytes added to the store per day, per key root.",
+                "root",
+                &state.growth.bytes,
+            ),
+            (
+                "many_ledger_symbol_events_per_day",
+                "Events logged per day, per symbol.",
+                "symbol",
+                &state.growth.symbol_events,
+            ),
+            (
+                "many_ledger_symbol_bytes_per_day",
+                "Bytes of events and balances added per day, per symbol.",
+                "symbol",
+                &state.growth.symbol_bytes,
+            ),
+        ];
+        for (name, help, label, values) in growth {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} gauge");
+            for (key, value) in values {
+                let _ = writeln!(out, "{name}{{{label}=\"{}\"}} {value}", escape_label(key));
+            }
+        }
+
+        out
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Total size of the files under `path`. Missing files are ignored, since the
+/// storage can change while it is walked.
+pub fn directory_size<P: AsRef<Path>>(path: P) -> u64 {
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(m) if m.is_dir() => directory_size(entry.path()),
+            Ok(m) => m.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// A MANY request handler counting the calls made to the inner handler, if
+/// metrics are enabled.
+#[derive(Debug)]
+pub struct MetricsHandler<H> {
+    pub inner: H,
+    pub metrics: Option<Arc<Metrics>>,
+}
+
+#[async_trait]
+impl<H: LowLevelManyRequestHandler> LowLevelManyRequestHandler for MetricsHandler<H> {
+    async fn execute(&self, envelope: CoseSign1) -> Result<CoseSign1, String> {
+        let metrics = match &self.metrics {
+            Some(metrics) => metrics,
+            None => return self.inner.execute(envelope).await,
+        };
+        let method = envelope
+            .payload
+            .as_deref()
+            .and_then(|payload| RequestMessage::from_bytes(payload).ok())
+            .map(|message| message.method);
+
+        let start = Instant::now();
+        let result = self.inner.execute(envelope).await;
+
+        if let Some(method) = method {
+            if method == "abci.commit" {
+                metrics.record_commit(start.elapsed());
+            }
+            metrics.record_call(&method);
+        }
+        result
+    }
+}
+
+fn respond(
+    stream: TcpStream,
+    metrics: &Metrics,
+    state: &impl Fn() -> StateMetrics,
+) -> std::io::Result<()> {
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+
+    let mut path = request_line.split_whitespace().skip(1);
+    let (status, body) = match (request_line.starts_with("GET "), path.next()) {
+        (true, Some("/metrics")) => ("200 OK", metrics.render(&state())),
+        _ => ("404 Not Found", String::new()),
+    };
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+/// Serve `GET /metrics` on `addr` from a background thread. `state` is called
+/// on every scrape.
+pub fn serve(
+    addr: SocketAddr,
+    metrics: Arc<Metrics>,
+    state: impl Fn() -> StateMetrics + Send + 'static,
+) -> std::io::Result<std::thread::JoinHandle<()>> {
+    let listener = TcpListener::bind(addr)?;
+    Ok(std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let result = stream.and_then(|stream| respond(stream, &metrics, &state));
+            if let Err(e) = result {
+                warn!("Could not serve metrics: {e}");
+            }
+        }
+    }))
+}
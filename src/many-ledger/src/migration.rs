@@ -4,8 +4,17 @@ use many_error::ManyError;
 use many_migration::{InnerMigration, MigrationSet};
 
 pub mod block_9400;
+pub mod chunked;
 pub mod data;
+pub mod delivered;
+pub mod event_id;
+pub mod event_index;
+pub mod insufficient_funds;
 pub mod memo;
+pub mod migration_heights;
+pub mod patch;
+pub mod sequence;
+pub mod supply;
 pub mod tokens;
 
 #[cfg(feature = "migration_testing")]
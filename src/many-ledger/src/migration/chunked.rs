@@ -0,0 +1,84 @@
+//! Long-running data migrations.
+//!
+//! A chunked migration transforms the store in bounded chunks, one chunk per
+//! block, so transformations too large for a single commit (e.g. re-indexing
+//! millions of events) run across many blocks. It is activated like any other
+//! migration, through the migration it is attached to, whose initialization
+//! can be a no-op (see [`start`]). From then on a chunk runs at every commit
+//! until the transformation is done.
+//!
+//! The progress is stored in the state, so every node runs the same chunks,
+//! and it is served by `ledger.migrationProgress`.
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_migration::InnerMigration;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+use serde_json::Value;
+use std::collections::HashMap;
+
+pub const MIGRATION_PROGRESS_ROOT: &str = "/migrations/progress/";
+
+pub fn key_for_migration_progress(name: &str) -> Vec<u8> {
+    format!("{MIGRATION_PROGRESS_ROOT}{name}").into_bytes()
+}
+
+/// The outcome of a chunk.
+#[derive(Clone, Debug, Default)]
+pub struct Chunk {
+    /// Where the next chunk starts, or None if the transformation is done.
+    pub cursor: Option<Vec<u8>>,
+
+    /// Number of items processed by the chunk.
+    pub processed: u64,
+}
+
+/// Process at most `limit` items, starting at `cursor` or at the beginning if
+/// None.
+pub type ChunkFn = fn(&mut InnerStorage, Option<&[u8]>, usize) -> Result<Chunk, ManyError>;
+
+pub struct ChunkedMigration {
+    /// The migration activating this one.
+    pub migration: &'static InnerMigration<InnerStorage, ManyError>,
+
+    /// Maximum number of items processed in a block.
+    pub chunk_size: usize,
+
+    pub run: ChunkFn,
+}
+
+impl ChunkedMigration {
+    pub fn name(&self) -> &str {
+        self.migration.name()
+    }
+}
+
+#[derive(Clone, Debug, Default, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct MigrationProgress {
+    /// Number of items processed so far.
+    #[n(0)]
+    pub processed: u64,
+
+    #[n(1)]
+    pub done: bool,
+
+    /// Where the next chunk starts.
+    #[n(2)]
+    pub cursor: Option<ByteVec>,
+
+    /// Height of the last chunk.
+    #[n(3)]
+    pub height: u64,
+}
+
+// The registry of chunked migrations.
+#[distributed_slice]
+pub static CHUNKED_MIGRATIONS: [ChunkedMigration] = [..];
+
+/// An initialization which does nothing, for migrations only activating a
+/// chunked migration.
+pub fn start(_: &mut InnerStorage, _: &HashMap<String, Value>) -> Result<(), ManyError> {
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use crate::migration::MIGRATIONS;
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_migration::InnerMigration;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Nothing to convert, the commands delivered after the activation height are
+/// recorded, see [`crate::storage::delivered`].
+fn initialize(_: &mut InnerStorage, _: &HashMap<String, Value>) -> Result<(), ManyError> {
+    Ok(())
+}
+
+#[distributed_slice(MIGRATIONS)]
+pub static DELIVERED_REQUEST_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_initialize(
+        initialize,
+        "Delivered Request Migration",
+        "Remember the results of the commands delivered, so a command sent again returns its original result instead of being executed twice.",
+    );
@@ -0,0 +1,21 @@
+use crate::migration::MIGRATIONS;
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_migration::InnerMigration;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Events already in the store keep their IDs, the events of the blocks after
+/// the activation height are numbered from their own height.
+fn initialize(_: &mut InnerStorage, _: &HashMap<String, Value>) -> Result<(), ManyError> {
+    Ok(())
+}
+
+#[distributed_slice(MIGRATIONS)]
+pub static EVENT_ID_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_initialize(
+        initialize,
+        "Block Height Event ID Migration",
+        "Number the events of a block from the block height, so event IDs give the height and index of the event.",
+    );
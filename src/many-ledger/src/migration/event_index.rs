@@ -0,0 +1,69 @@
+use crate::error;
+use crate::migration::MIGRATIONS;
+use crate::storage::event_index::{index_keys_for_event, symbol_index_key_for_event};
+use crate::storage::iterator::LedgerIterator;
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_migration::InnerMigration;
+use many_modules::events::EventLog;
+use merk::Op;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Index every event already in the store. Events logged after the migration is
+/// active are indexed as they are logged.
+fn initialize(storage: &mut InnerStorage, _: &HashMap<String, Value>) -> Result<(), ManyError> {
+    let mut batch = Vec::new();
+    for item in LedgerIterator::all_events(storage) {
+        let (_, v) = item.map_err(ManyError::unknown)?;
+        let log =
+            minicbor::decode::<EventLog>(v.as_slice()).map_err(ManyError::deserialization_error)?;
+        for key in index_keys_for_event(&log)? {
+            batch.push((key, Op::Put(vec![])));
+        }
+    }
+
+    // Keys in batch must be sorted.
+    batch.sort_by(|(a, _), (b, _)| a.cmp(b));
+    storage.apply(&batch).map_err(error::storage_apply_failed)?;
+    Ok(())
+}
+
+#[distributed_slice(MIGRATIONS)]
+pub static EVENT_INDEX_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_initialize(
+        initialize,
+        "Event Index Migration",
+        "Index events by account and by kind, so listing them does not scan the whole event log.",
+    );
+
+/// Index every event about a single symbol already in the store by kind and
+/// symbol.
+fn initialize_symbol_index(
+    storage: &mut InnerStorage,
+    _: &HashMap<String, Value>,
+) -> Result<(), ManyError> {
+    let mut batch = Vec::new();
+    for item in LedgerIterator::all_events(storage) {
+        let (_, v) = item.map_err(ManyError::unknown)?;
+        let log =
+            minicbor::decode::<EventLog>(v.as_slice()).map_err(ManyError::deserialization_error)?;
+        if let Some(key) = symbol_index_key_for_event(&log)? {
+            batch.push((key, Op::Put(vec![])));
+        }
+    }
+
+    // Keys in batch must be sorted.
+    batch.sort_by(|(a, _), (b, _)| a.cmp(b));
+    storage.apply(&batch).map_err(error::storage_apply_failed)?;
+    Ok(())
+}
+
+#[distributed_slice(MIGRATIONS)]
+pub static SYMBOL_EVENT_INDEX_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_initialize(
+        initialize_symbol_index,
+        "Symbol Event Index Migration",
+        "Index the events about a symbol by kind and symbol, e.g. to audit the mints and burns of a token.",
+    );
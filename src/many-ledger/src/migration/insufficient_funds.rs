@@ -0,0 +1,21 @@
+use crate::migration::MIGRATIONS;
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_migration::InnerMigration;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Nothing to convert, the transfers failing after the activation height return
+/// the amount and balance, see [`crate::error::insufficient_funds`].
+fn initialize(_: &mut InnerStorage, _: &HashMap<String, Value>) -> Result<(), ManyError> {
+    Ok(())
+}
+
+#[distributed_slice(MIGRATIONS)]
+pub static INSUFFICIENT_FUNDS_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_initialize(
+        initialize,
+        "Insufficient Funds Arguments Migration",
+        "Return the amount needed and the balance available with the insufficient funds error.",
+    );
@@ -0,0 +1,21 @@
+use crate::migration::MIGRATIONS;
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_migration::InnerMigration;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The heights are recorded on the next commit, see
+/// [`crate::storage::migrations::MIGRATION_HEIGHTS_KEY`].
+fn initialize(_: &mut InnerStorage, _: &HashMap<String, Value>) -> Result<(), ManyError> {
+    Ok(())
+}
+
+#[distributed_slice(MIGRATIONS)]
+pub static MIGRATION_HEIGHTS_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_initialize(
+        initialize,
+        "Migration Heights Migration",
+        "Record the activation heights of the enabled migrations in the state.",
+    );
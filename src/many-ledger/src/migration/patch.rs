@@ -0,0 +1,68 @@
+//! One-shot data migrations.
+//!
+//! A patch migration rewrites specific keys of the store exactly once, at its
+//! activation height, e.g. to correct a known bad balance. Like a chunked
+//! migration, it is activated through the migration it is attached to, whose
+//! initialization can be [`crate::migration::chunked::start`].
+//!
+//! Patches go through the same path as transactions, so the indexes of the
+//! balances are kept up to date. What changed, with the previous values, is
+//! recorded in the state and served by `ledger.migrationStatus`.
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_migration::InnerMigration;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+
+pub const MIGRATION_PATCH_ROOT: &str = "/migrations/patches/";
+
+pub fn key_for_migration_patch(name: &str) -> Vec<u8> {
+    format!("{MIGRATION_PATCH_ROOT}{name}").into_bytes()
+}
+
+/// The new values of the keys to patch, None deleting a key.
+pub type PatchFn = fn(&InnerStorage) -> Result<Vec<(Vec<u8>, Option<Vec<u8>>)>, ManyError>;
+
+pub struct PatchMigration {
+    /// The migration activating this one.
+    pub migration: &'static InnerMigration<InnerStorage, ManyError>,
+
+    pub patch: PatchFn,
+}
+
+impl PatchMigration {
+    pub fn name(&self) -> &str {
+        self.migration.name()
+    }
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct PatchChange {
+    #[n(0)]
+    pub key: ByteVec,
+
+    /// The value before the patch, None if the key did not exist.
+    #[n(1)]
+    pub before: Option<ByteVec>,
+
+    /// The value after the patch, None if the key was deleted.
+    #[n(2)]
+    pub after: Option<ByteVec>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct PatchRecord {
+    /// Height at which the patch was applied.
+    #[n(0)]
+    pub height: u64,
+
+    #[n(1)]
+    pub changes: Vec<PatchChange>,
+}
+
+// The registry of patch migrations.
+#[distributed_slice]
+pub static PATCH_MIGRATIONS: [PatchMigration] = [..];
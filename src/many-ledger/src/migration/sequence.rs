@@ -0,0 +1,21 @@
+use crate::migration::MIGRATIONS;
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_migration::InnerMigration;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Nothing to convert, sequences start at 0 and the commands executed after the
+/// activation height are counted, see [`crate::module::sequence`].
+fn initialize(_: &mut InnerStorage, _: &HashMap<String, Value>) -> Result<(), ManyError> {
+    Ok(())
+}
+
+#[distributed_slice(MIGRATIONS)]
+pub static SEQUENCE_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_initialize(
+        initialize,
+        "Sequence Number Migration",
+        "Count the commands executed successfully by each identity in the state.",
+    );
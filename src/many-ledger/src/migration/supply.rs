@@ -0,0 +1,45 @@
+use crate::error;
+use crate::migration::MIGRATIONS;
+use crate::storage::holders::index_holders;
+use crate::storage::supply::count_holders;
+use crate::storage::InnerStorage;
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_migration::InnerMigration;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Count the holders of every symbol already in the store. Balances written
+/// after the migration is active update the counts as they are written.
+fn initialize(storage: &mut InnerStorage, _: &HashMap<String, Value>) -> Result<(), ManyError> {
+    let batch = count_holders(storage)?;
+    storage.apply(&batch).map_err(error::storage_apply_failed)?;
+    Ok(())
+}
+
+#[distributed_slice(MIGRATIONS)]
+pub static HOLDER_COUNT_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_initialize(
+        initialize,
+        "Holder Count Migration",
+        "Count the accounts holding each symbol, for the supply query.",
+    );
+
+/// Index every non-zero balance already in the store. Balances written after
+/// the migration is active update the index as they are written.
+fn initialize_index(
+    storage: &mut InnerStorage,
+    _: &HashMap<String, Value>,
+) -> Result<(), ManyError> {
+    let batch = index_holders(storage)?;
+    storage.apply(&batch).map_err(error::storage_apply_failed)?;
+    Ok(())
+}
+
+#[distributed_slice(MIGRATIONS)]
+pub static HOLDERS_INDEX_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_initialize(
+        initialize_index,
+        "Holders Index Migration",
+        "Index the accounts holding each symbol by balance, for the holders query.",
+    );
@@ -1,29 +1,77 @@
 use crate::error;
+use crate::hooks::CommitHooks;
 use crate::json::InitialStateJson;
-use crate::storage::LedgerStorage;
+use crate::metrics::StateMetrics;
+use crate::storage::clock::Clock;
+use crate::storage::cold::ColdStore;
+use crate::storage::limits::PayloadLimits;
+use crate::storage::scheduler::{TaskHandle, TaskHandler, Trigger};
+use crate::storage::snapshot::Snapshots;
+use crate::storage::{InnerStorage, LedgerStorage};
+use crate::subscriptions::Subscriptions;
+use crate::view::ReadViews;
 use many_error::ManyError;
 use many_migration::MigrationConfig;
 use std::fmt::Debug;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tracing::info;
 
 mod abci;
 pub mod account;
 pub mod allow_addrs;
+pub mod allowance;
+pub mod check_tx;
 mod data;
-mod event;
+pub mod delivered;
+pub mod error_codes;
+pub mod escrow;
+pub mod event;
+pub mod events_page;
+pub mod faucet;
+pub mod fees;
+pub mod freeze;
+pub mod genesis;
+pub mod growth;
+pub mod idempotency;
 mod idstore;
+pub mod idstore_alias;
+pub mod idstore_credentials;
+pub mod idstore_update;
 pub mod idstore_webauthn;
 mod ledger;
 mod ledger_commands;
 mod ledger_mintburn;
 mod ledger_tokens;
+pub mod migrations;
+pub mod multi_send;
 mod multisig;
+pub mod notice;
+pub mod pending_send;
+pub mod proof;
+pub mod quota;
+pub mod rate_limit;
+pub mod recurring_send;
+pub mod scheduled_send;
+pub mod sequence;
+pub mod simulate;
+pub mod snapshot;
+pub mod sub_account;
+pub mod supply;
+pub mod token_metadata;
+pub mod tx_events;
+pub mod validators;
+
+pub use abci::endpoints;
 
 /// A simple ledger that keeps transactions in memory.
 #[derive(Debug)]
 pub struct LedgerModuleImpl {
     storage: LedgerStorage,
+    commit_hooks: Option<CommitHooks>,
+    subscriptions: Option<Arc<Subscriptions>>,
+    invariant_checks: bool,
+    read_views: Option<Arc<ReadViews>>,
 }
 
 impl LedgerModuleImpl {
@@ -33,14 +81,27 @@ impl LedgerModuleImpl {
         persistence_store_path: P,
         blockchain: bool,
     ) -> Result<Self, ManyError> {
+        state.validate()?;
         let symbols = state.symbols();
         let balances = state.balances()?;
+        let allocations = balances.clone();
         let symbols_meta = state
             .symbols_meta
             .map(|b| b.into_iter().map(|(k, v)| (k, v.into())).collect());
         let accounts = state
             .accounts
             .map(|a| a.into_iter().map(|v| v.into()).collect());
+        let fees = state
+            .fees
+            .map(|f| f.into_iter().map(|(k, v)| (k, v.into())).collect());
+        let token_metadata = state
+            .token_metadata
+            .map(|m| {
+                m.into_iter()
+                    .map(|(k, v)| Ok((k, v.try_into()?)))
+                    .collect::<Result<_, ManyError>>()
+            })
+            .transpose()?;
 
         let storage =
             LedgerStorage::new(&symbols, persistence_store_path, state.identity, blockchain)?
@@ -54,8 +115,18 @@ impl LedgerModuleImpl {
                     state.token_next_subresource,
                     balances,
                 )?
+                .with_multisig_defaults(state.multisig_defaults.map(Into::into))?
                 .with_account(state.account_identity, accounts)?
-                .build()?;
+                .with_fees(state.fee_collector, fees)?
+                .with_token_metadata(token_metadata)?
+                .with_compliance_identity(state.compliance_identity)?
+                .with_notice_identity(state.notice_identity)?
+                .with_validator_identity(state.validator_identity)?
+                .with_faucet(state.faucet.map(Into::into))?
+                .with_payload_limits(state.payload_limits.map(Into::into))?
+                .with_events_max_age(state.events_max_age)?
+                .build()?
+                .with_genesis_report(allocations)?;
 
         if let Some(h) = state.hash {
             // Verify the hash.
@@ -72,7 +143,13 @@ impl LedgerModuleImpl {
 
         tracing::debug!("Final migrations: {:?}", storage.migrations());
 
-        Ok(Self { storage })
+        Ok(Self {
+            storage,
+            commit_hooks: None,
+            subscriptions: None,
+            invariant_checks: false,
+            read_views: None,
+        })
     }
 
     pub fn load<P: AsRef<Path>>(
@@ -84,7 +161,191 @@ impl LedgerModuleImpl {
 
         tracing::debug!("Final migrations: {:?}", storage.migrations());
 
-        Ok(Self { storage })
+        Ok(Self {
+            storage,
+            commit_hooks: None,
+            subscriptions: None,
+            invariant_checks: false,
+            read_views: None,
+        })
+    }
+
+    /// Replace the clock used by the ledger, e.g. with a simulated one for testing.
+    pub fn with_clock(self, clock: impl Clock + 'static) -> Self {
+        Self {
+            storage: self.storage.with_clock(clock),
+            ..self
+        }
+    }
+
+    /// Copy old events to a cold store, see [`ColdStore`].
+    pub fn with_cold_store(self, cold: Option<ColdStore>) -> Self {
+        Self {
+            storage: self.storage.with_cold_store(cold),
+            ..self
+        }
+    }
+
+    /// Cache the most queried balances, see [`crate::storage::balance_cache`].
+    pub fn with_balance_cache(self, capacity: usize) -> Self {
+        Self {
+            storage: self.storage.with_balance_cache(capacity),
+            ..self
+        }
+    }
+
+    /// Retain a number of blocks, see [`crate::storage::retention`].
+    pub fn with_retain_blocks(self, retain_blocks: Option<u64>) -> Self {
+        Self {
+            storage: self.storage.with_retain_blocks(retain_blocks),
+            ..self
+        }
+    }
+
+    /// Take state sync snapshots, see [`crate::storage::snapshot`].
+    pub fn with_snapshots(self, snapshots: Option<Snapshots>) -> Self {
+        Self {
+            storage: self.storage.with_snapshots(snapshots),
+            ..self
+        }
+    }
+
+    /// Keep the state diff of the last `keep` heights, see [`crate::storage::diff`].
+    pub fn with_state_diffs(self, keep: Option<u64>) -> Self {
+        Self {
+            storage: self.storage.with_state_diffs(keep),
+            ..self
+        }
+    }
+
+    /// Count the growth of the event log and of the store, see
+    /// [`crate::storage::growth`].
+    pub fn with_growth_metrics(self, enabled: bool) -> Self {
+        Self {
+            storage: self.storage.with_growth_metrics(enabled),
+            ..self
+        }
+    }
+
+    /// Back up the state before activating migrations.
+    pub fn with_migration_backups(self, path: Option<PathBuf>) -> Result<Self, ManyError> {
+        Ok(Self {
+            storage: self.storage.with_migration_backups(path)?,
+            ..self
+        })
+    }
+
+    /// Replace the whole state with a restored snapshot, see
+    /// [`crate::storage::snapshot::restore_snapshot`].
+    pub fn replace_store(
+        &mut self,
+        store: InnerStorage,
+        restored_path: &Path,
+    ) -> Result<(), ManyError> {
+        self.storage.swap_store(store, restored_path)
+    }
+
+    /// A checkpoint of the committed store at `path`, sharing its files.
+    pub fn checkpoint(&self, path: &Path) -> Result<InnerStorage, ManyError> {
+        self.storage.checkpoint(path)
+    }
+
+    /// Add a read view after every commit, see [`crate::view`].
+    pub fn with_read_views(self, read_views: Option<Arc<ReadViews>>) -> Self {
+        Self { read_views, ..self }
+    }
+
+    /// Register the handler of a kind of scheduled task, see [`crate::storage::scheduler`].
+    pub fn with_task_handler(self, kind: &'static str, handler: TaskHandler) -> Self {
+        Self {
+            storage: self.storage.with_task_handler(kind, handler),
+            ..self
+        }
+    }
+
+    pub fn schedule_task(
+        &mut self,
+        trigger: Trigger,
+        kind: &str,
+        payload: Vec<u8>,
+    ) -> Result<TaskHandle, ManyError> {
+        self.storage.schedule_task(trigger, kind, payload)
+    }
+
+    pub fn cancel_task(&mut self, handle: &TaskHandle) -> Result<bool, ManyError> {
+        self.storage.cancel_task(handle)
+    }
+
+    /// Run hooks after every commit, see [`crate::hooks`].
+    pub fn with_commit_hooks(self, commit_hooks: Option<CommitHooks>) -> Self {
+        Self {
+            commit_hooks,
+            ..self
+        }
+    }
+
+    /// Check the invariants of the state after every commit, and stop the node
+    /// if any is violated, see [`crate::storage::invariants`].
+    pub fn with_invariant_checks(self, invariant_checks: bool) -> Self {
+        Self {
+            invariant_checks,
+            ..self
+        }
+    }
+
+    /// Recompute the invariants of the state.
+    pub fn check_invariants(&self) -> Result<(), ManyError> {
+        let violations = self.storage.check_invariants()?;
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(error::invariant_violated(violations.join("; ")))
+        }
+    }
+
+    /// Send the events of every committed block to the subscribers, see
+    /// [`crate::subscriptions`].
+    pub fn with_subscriptions(self, subscriptions: Option<Arc<Subscriptions>>) -> Self {
+        Self {
+            storage: self.storage.with_block_events(subscriptions.is_some()),
+            subscriptions,
+            ..self
+        }
+    }
+
+    /// Keep the events logged by every transaction for the ABCI bridge, see
+    /// [`tx_events`].
+    pub fn with_tx_events(self, enabled: bool) -> Self {
+        Self {
+            storage: self.storage.with_tx_events(enabled),
+            ..self
+        }
+    }
+
+    /// The maximum sizes of payloads accepted by the ledger, from the state.
+    pub(crate) fn limits(&self) -> Result<PayloadLimits, ManyError> {
+        self.storage.payload_limits()
+    }
+
+    /// The metrics read from the state, except for the storage size which is
+    /// measured on disk by the caller.
+    pub fn state_metrics(&self) -> Result<StateMetrics, ManyError> {
+        Ok(StateMetrics {
+            height: self.storage.get_height()?,
+            events: self.storage.nb_events()?,
+            growth: self.storage.growth_rates()?,
+            ..Default::default()
+        })
+    }
+
+    /// The hash of the current state.
+    pub fn hash(&self) -> Vec<u8> {
+        self.storage.hash()
+    }
+
+    /// The application hash of the block committed at `height`, if recorded.
+    pub fn app_hash(&self, height: u64) -> Result<Option<Vec<u8>>, ManyError> {
+        self.storage.get_app_hash(height)
     }
 
     #[cfg(feature = "balance_testing")]
@@ -1,3 +1,4 @@
+use crate::hooks::CommitInfo;
 use crate::module::LedgerModuleImpl;
 use many_error::ManyError;
 use many_modules::abci_backend::{
@@ -6,62 +7,135 @@ use many_modules::abci_backend::{
 };
 use many_types::Timestamp;
 use std::collections::BTreeMap;
-use tracing::info;
+use tracing::{error, info};
+
+/// The endpoints of the ledger, and whether they are commands to go through
+/// the blockchain.
+#[rustfmt::skip]
+pub fn endpoints() -> BTreeMap<String, EndpointInfo> {
+    BTreeMap::from([
+        ("ledger.info".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.balance".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.send".to_string(), EndpointInfo { is_command: true }),
+        ("ledger.sendOnce".to_string(), EndpointInfo { is_command: true }),
+        ("ledger.idempotencyKeyUsed".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.multiSend".to_string(), EndpointInfo { is_command: true }),
+        ("ledger.sendSymbols".to_string(), EndpointInfo { is_command: true }),
+        ("ledger.freeze".to_string(), EndpointInfo { is_command: true }),
+        ("ledger.unfreeze".to_string(), EndpointInfo { is_command: true }),
+        ("ledger.approve".to_string(), EndpointInfo { is_command: true }),
+        ("ledger.transferFrom".to_string(), EndpointInfo { is_command: true }),
+        ("ledger.allowance".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.allowanceHistory".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.sendPending".to_string(), EndpointInfo { is_command: true }),
+        ("ledger.acceptPending".to_string(), EndpointInfo { is_command: true }),
+        ("ledger.pendingInfo".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.sendScheduled".to_string(), EndpointInfo { is_command: true }),
+        ("ledger.cancelScheduled".to_string(), EndpointInfo { is_command: true }),
+        ("ledger.scheduledInfo".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.subscribe".to_string(), EndpointInfo { is_command: true }),
+        ("ledger.unsubscribe".to_string(), EndpointInfo { is_command: true }),
+        ("ledger.listSubscriptions".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.simulate".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.feeInfo".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.tokenInfo".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.supply".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.holders".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.genesisReport".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.snapshotInfo".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.snapshotChunk".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.stateDiff".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.growth".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.sequence".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.balanceProof".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.commitment".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.errorCodes".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.migrationProgress".to_string(), EndpointInfo { is_command: false }),
+        ("ledger.migrationStatus".to_string(), EndpointInfo { is_command: false }),
+
+        // Escrows
+        ("escrow.create".to_string(), EndpointInfo { is_command: true }),
+        ("escrow.release".to_string(), EndpointInfo { is_command: true }),
+        ("escrow.refund".to_string(), EndpointInfo { is_command: true }),
+        ("escrow.info".to_string(), EndpointInfo { is_command: false }),
+
+        // Events
+        ("events.info".to_string(), EndpointInfo { is_command: false }),
+        ("events.list".to_string(), EndpointInfo { is_command: false }),
+        ("events.listPage".to_string(), EndpointInfo { is_command: false }),
+        ("events.listCount".to_string(), EndpointInfo { is_command: false }),
+
+        // IdStore
+        ("idstore.store".to_string(), EndpointInfo { is_command: true }),
+        ("idstore.getFromRecallPhrase".to_string(), EndpointInfo { is_command: false }),
+        ("idstore.getFromAddress".to_string(), EndpointInfo { is_command: false }),
+        ("idstore.update".to_string(), EndpointInfo { is_command: true }),
+        ("idstore.revoke".to_string(), EndpointInfo { is_command: true }),
+        ("idstore.listFromAddress".to_string(), EndpointInfo { is_command: false }),
+        ("idstore.removeCredential".to_string(), EndpointInfo { is_command: true }),
+        ("idstore.addAlias".to_string(), EndpointInfo { is_command: true }),
+        ("idstore.removeAlias".to_string(), EndpointInfo { is_command: true }),
+        ("idstore.listAliases".to_string(), EndpointInfo { is_command: false }),
+        ("idstore.getAliasOwner".to_string(), EndpointInfo { is_command: false }),
+
+        // Notices
+        ("notice.publish".to_string(), EndpointInfo { is_command: true }),
+        ("notice.retract".to_string(), EndpointInfo { is_command: true }),
+        ("notice.list".to_string(), EndpointInfo { is_command: false }),
+
+        // Validators
+        ("validators.update".to_string(), EndpointInfo { is_command: true }),
+        ("validators.list".to_string(), EndpointInfo { is_command: false }),
+
+        // Faucet
+        ("faucet.info".to_string(), EndpointInfo { is_command: false }),
+        ("faucet.claim".to_string(), EndpointInfo { is_command: true }),
+
+        // Accounts
+        ("account.create".to_string(), EndpointInfo { is_command: true }),
+        ("account.setDescription".to_string(), EndpointInfo { is_command: true }),
+        ("account.listRoles".to_string(), EndpointInfo { is_command: false }),
+        ("account.getRoles".to_string(), EndpointInfo { is_command: false }),
+        ("account.addRoles".to_string(), EndpointInfo { is_command: true }),
+        ("account.removeRoles".to_string(), EndpointInfo { is_command: true }),
+        ("account.info".to_string(), EndpointInfo { is_command: false }),
+        ("account.disable".to_string(), EndpointInfo { is_command: true }),
+        ("account.addFeatures".to_string(), EndpointInfo { is_command: true }),
+        ("account.createSubAccount".to_string(), EndpointInfo { is_command: true }),
+        ("account.listSubAccounts".to_string(), EndpointInfo { is_command: false }),
+        ("account.disableSubAccount".to_string(), EndpointInfo { is_command: true }),
+
+        // Account Features - Multisig
+        ("account.multisigSetDefaults".to_string(), EndpointInfo { is_command: true }),
+        ("account.multisigSubmitTransaction".to_string(), EndpointInfo { is_command: true }),
+        ("account.multisigInfo".to_string(), EndpointInfo { is_command: false }),
+        ("account.multisigApprove".to_string(), EndpointInfo { is_command: true }),
+        ("account.multisigRevoke".to_string(), EndpointInfo { is_command: true }),
+        ("account.multisigExecute".to_string(), EndpointInfo { is_command: true }),
+        ("account.multisigWithdraw".to_string(), EndpointInfo { is_command: true }),
+
+        // Data Attributes
+        ("data.info".to_string(), EndpointInfo { is_command: false }),
+        ("data.getInfo".to_string(), EndpointInfo { is_command: false }),
+        ("data.query".to_string(), EndpointInfo { is_command: false }),
+
+        // Token attribute
+        ("tokens.create".to_string(), EndpointInfo { is_command : true }),
+        ("tokens.update".to_string(), EndpointInfo { is_command : true }),
+        ("tokens.info".to_string(), EndpointInfo { is_command : false }),
+        ("tokens.addExtendedInfo".to_string(), EndpointInfo { is_command : true }),
+        ("tokens.removeExtendedInfo".to_string(), EndpointInfo { is_command : true }),
+        ("tokens.mint".to_string(), EndpointInfo { is_command : true }),
+        ("tokens.burn".to_string(), EndpointInfo { is_command : true }),
+    ])
+}
 
 // This module is always supported, but will only be added when created using an ABCI
 // flag.
 impl ManyAbciModuleBackend for LedgerModuleImpl {
-    #[rustfmt::skip]
     fn init(&mut self) -> Result<AbciInit, ManyError> {
         Ok(AbciInit {
-            endpoints: BTreeMap::from([
-                ("ledger.info".to_string(), EndpointInfo { is_command: false }),
-                ("ledger.balance".to_string(), EndpointInfo { is_command: false }),
-                ("ledger.send".to_string(), EndpointInfo { is_command: true }),
-
-                // Events
-                ("events.info".to_string(), EndpointInfo { is_command: false }),
-                ("events.list".to_string(), EndpointInfo { is_command: false }),
-
-                // IdStore
-                ("idstore.store".to_string(), EndpointInfo { is_command: true }),
-                ("idstore.getFromRecallPhrase".to_string(), EndpointInfo { is_command: false }),
-                ("idstore.getFromAddress".to_string(), EndpointInfo { is_command: false }),
-
-                // Accounts
-                ("account.create".to_string(), EndpointInfo { is_command: true }),
-                ("account.setDescription".to_string(), EndpointInfo { is_command: true }),
-                ("account.listRoles".to_string(), EndpointInfo { is_command: false }),
-                ("account.getRoles".to_string(), EndpointInfo { is_command: false }),
-                ("account.addRoles".to_string(), EndpointInfo { is_command: true }),
-                ("account.removeRoles".to_string(), EndpointInfo { is_command: true }),
-                ("account.info".to_string(), EndpointInfo { is_command: false }),
-                ("account.disable".to_string(), EndpointInfo { is_command: true }),
-                ("account.addFeatures".to_string(), EndpointInfo { is_command: true }),
-
-                // Account Features - Multisig
-                ("account.multisigSetDefaults".to_string(), EndpointInfo { is_command: true }),
-                ("account.multisigSubmitTransaction".to_string(), EndpointInfo { is_command: true }),
-                ("account.multisigInfo".to_string(), EndpointInfo { is_command: false }),
-                ("account.multisigApprove".to_string(), EndpointInfo { is_command: true }),
-                ("account.multisigRevoke".to_string(), EndpointInfo { is_command: true }),
-                ("account.multisigExecute".to_string(), EndpointInfo { is_command: true }),
-                ("account.multisigWithdraw".to_string(), EndpointInfo { is_command: true }),
-
-                // Data Attributes
-                ("data.info".to_string(), EndpointInfo { is_command: false }),
-                ("data.getInfo".to_string(), EndpointInfo { is_command: false }),
-                ("data.query".to_string(), EndpointInfo { is_command: false }),
-
-                // Token attribute
-                ("tokens.create".to_string(), EndpointInfo { is_command : true }),
-                ("tokens.update".to_string(), EndpointInfo { is_command : true }),
-                ("tokens.info".to_string(), EndpointInfo { is_command : false }),
-                ("tokens.addExtendedInfo".to_string(), EndpointInfo { is_command : true }),
-                ("tokens.removeExtendedInfo".to_string(), EndpointInfo { is_command : true }),
-                ("tokens.mint".to_string(), EndpointInfo { is_command : true }),
-                ("tokens.burn".to_string(), EndpointInfo { is_command : true }),
-            ]),
+            endpoints: endpoints(),
         })
     }
 
@@ -82,6 +156,9 @@ impl ManyAbciModuleBackend for LedgerModuleImpl {
             let time = Timestamp::new(time)?;
             self.storage.set_time(time);
         }
+        // The events logged outside of a transaction, e.g. by the scheduled
+        // tasks of the last commit, belong to no transaction.
+        self.storage.take_tx_events();
 
         Ok(BeginBlockReturn {})
     }
@@ -109,6 +186,31 @@ impl ManyAbciModuleBackend for LedgerModuleImpl {
             result.retain_height,
             hex::encode(result.hash.as_slice()).as_str()
         );
+
+        if self.invariant_checks {
+            if let Err(e) = self.check_invariants() {
+                // Never answer with the hash of a corrupt state.
+                error!(
+                    "abci.commit(): halting at height {}: {e}",
+                    self.storage.get_height()?
+                );
+                std::process::exit(1);
+            }
+        }
+
+        if let Some(hooks) = &self.commit_hooks {
+            hooks.notify(&CommitInfo {
+                height: self.storage.get_height()?,
+                hash: result.hash.as_slice().to_vec(),
+                event_count: self.storage.nb_events()?,
+            });
+        }
+        if let Some(subscriptions) = &self.subscriptions {
+            subscriptions.notify(self.storage.take_block_events());
+        }
+        if let Some(read_views) = &self.read_views {
+            read_views.refresh(self);
+        }
         Ok(result)
     }
 }
@@ -1,24 +1,25 @@
 use coset::CoseSign1;
 use many_error::ManyError;
 use many_identity::Address;
-use many_modules::{ledger, ManyModule, ManyModuleInfo};
+use many_modules::{ManyModule, ManyModuleInfo};
 use many_protocol::{RequestMessage, ResponseMessage};
 use std::collections::BTreeSet;
 use std::fmt::{Debug, Formatter};
 
-pub struct AllowAddrsModule<T: ledger::LedgerCommandsModuleBackend> {
-    pub inner: ledger::LedgerCommandsModule<T>,
+/// Restricts a module to a set of senders, e.g. the ledger commands.
+pub struct AllowAddrsModule<M: ManyModule> {
+    pub inner: M,
     pub allow_addrs: BTreeSet<Address>,
 }
 
-impl<T: ledger::LedgerCommandsModuleBackend> Debug for AllowAddrsModule<T> {
+impl<M: ManyModule> Debug for AllowAddrsModule<M> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.write_str("AllowAddrsModule")
     }
 }
 
 #[async_trait::async_trait]
-impl<T: ledger::LedgerCommandsModuleBackend> ManyModule for AllowAddrsModule<T> {
+impl<M: ManyModule> ManyModule for AllowAddrsModule<M> {
     fn info(&self) -> &ManyModuleInfo {
         self.inner.info()
     }
@@ -0,0 +1,187 @@
+use crate::module::LedgerModuleImpl;
+use crate::storage::allowance::AllowanceEventLog;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::EmptyReturn;
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::{Memo, SortOrder};
+use minicbor::{Decode, Encode};
+
+/// Default maximum number of allowance events returned by `ledger.allowanceHistory`.
+const DEFAULT_HISTORY_COUNT: usize = 100;
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ApproveArgs {
+    /// The owner of the funds. Defaults to the sender.
+    #[n(0)]
+    pub from: Option<Address>,
+
+    #[n(1)]
+    pub spender: Address,
+
+    #[n(2)]
+    pub symbol: Symbol,
+
+    /// The new allowance, replacing the previous one. Zero revokes it.
+    #[n(3)]
+    pub amount: TokenAmount,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct TransferFromArgs {
+    /// The owner of the funds. The sender is the spender.
+    #[n(0)]
+    pub from: Address,
+
+    #[n(1)]
+    pub to: Address,
+
+    #[n(2)]
+    pub symbol: Symbol,
+
+    #[n(3)]
+    pub amount: TokenAmount,
+
+    #[n(4)]
+    pub memo: Option<Memo>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct AllowanceArgs {
+    #[n(0)]
+    pub owner: Address,
+
+    #[n(1)]
+    pub spender: Address,
+
+    #[n(2)]
+    pub symbol: Symbol,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct AllowanceReturns {
+    #[n(0)]
+    pub amount: TokenAmount,
+}
+
+#[derive(Clone, Debug, Default, Encode, Decode)]
+#[cbor(map)]
+pub struct AllowanceHistoryArgs {
+    /// Only return the events involving this account.
+    #[n(0)]
+    pub account: Option<Address>,
+
+    #[n(1)]
+    pub count: Option<u64>,
+
+    #[n(2)]
+    pub order: Option<SortOrder>,
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct AllowanceHistoryReturns {
+    #[n(0)]
+    pub events: Vec<AllowanceEventLog>,
+}
+
+#[many_module(name = LedgerAllowanceModule, namespace = ledger, many_modules_crate = many_modules)]
+pub trait LedgerAllowanceModuleBackend: Send {
+    fn approve(&mut self, sender: &Address, args: ApproveArgs) -> Result<EmptyReturn, ManyError>;
+    fn transfer_from(
+        &mut self,
+        sender: &Address,
+        args: TransferFromArgs,
+    ) -> Result<EmptyReturn, ManyError>;
+    fn allowance(
+        &self,
+        sender: &Address,
+        args: AllowanceArgs,
+    ) -> Result<AllowanceReturns, ManyError>;
+    fn allowance_history(
+        &self,
+        sender: &Address,
+        args: AllowanceHistoryArgs,
+    ) -> Result<AllowanceHistoryReturns, ManyError>;
+}
+
+impl LedgerAllowanceModuleBackend for LedgerModuleImpl {
+    fn approve(&mut self, sender: &Address, args: ApproveArgs) -> Result<EmptyReturn, ManyError> {
+        let ApproveArgs {
+            from,
+            spender,
+            symbol,
+            amount,
+        } = args;
+
+        let owner = from.as_ref().unwrap_or(sender);
+        self.verify_send_sender(sender, owner)?;
+
+        self.storage.approve(owner, &spender, &symbol, amount)?;
+        Ok(EmptyReturn)
+    }
+
+    fn transfer_from(
+        &mut self,
+        sender: &Address,
+        args: TransferFromArgs,
+    ) -> Result<EmptyReturn, ManyError> {
+        let TransferFromArgs {
+            from,
+            to,
+            symbol,
+            amount,
+            memo,
+        } = args;
+
+        self.limits()?.check_memo(memo.as_ref())?;
+        self.storage
+            .transfer_from(sender, &from, &to, &symbol, amount, memo)?;
+        Ok(EmptyReturn)
+    }
+
+    fn allowance(
+        &self,
+        _sender: &Address,
+        args: AllowanceArgs,
+    ) -> Result<AllowanceReturns, ManyError> {
+        Ok(AllowanceReturns {
+            amount: self
+                .storage
+                .get_allowance(&args.owner, &args.spender, &args.symbol)?,
+        })
+    }
+
+    fn allowance_history(
+        &self,
+        _sender: &Address,
+        args: AllowanceHistoryArgs,
+    ) -> Result<AllowanceHistoryReturns, ManyError> {
+        let count = args
+            .count
+            .map_or(DEFAULT_HISTORY_COUNT, |c| c as usize)
+            .min(DEFAULT_HISTORY_COUNT);
+
+        let mut events = Vec::new();
+        for item in self
+            .storage
+            .iter_allowance_events(args.order.unwrap_or_default())
+        {
+            if events.len() >= count {
+                break;
+            }
+            let (_, v) = item.map_err(ManyError::unknown)?;
+            let event: AllowanceEventLog =
+                minicbor::decode(&v).map_err(ManyError::deserialization_error)?;
+            if args.account.map_or(true, |a| event.content.is_about(&a)) {
+                events.push(event);
+            }
+        }
+        Ok(AllowanceHistoryReturns { events })
+    }
+}
@@ -0,0 +1,49 @@
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::{ledger, EmptyReturn};
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct CheckTxArgs {
+    /// The verified sender of the transaction.
+    #[n(0)]
+    pub from: Address,
+
+    #[n(1)]
+    pub method: String,
+
+    /// The CBOR encoded arguments of the transaction.
+    #[n(2)]
+    pub data: ByteVec,
+}
+
+/// The mempool precheck of the ABCI bridge, rejecting transactions that would
+/// obviously fail against the current state. Only added behind the bridge.
+#[many_module(name = AbciCheckTxModule, namespace = abci, many_modules_crate = many_modules)]
+pub trait AbciCheckTxModuleBackend: Send {
+    fn check_tx(&self, args: CheckTxArgs) -> Result<EmptyReturn, ManyError>;
+}
+
+impl AbciCheckTxModuleBackend for LedgerModuleImpl {
+    fn check_tx(&self, args: CheckTxArgs) -> Result<EmptyReturn, ManyError> {
+        let CheckTxArgs { from, method, data } = args;
+        // Other commands are only validated when they are executed.
+        if method == "ledger.send" {
+            let ledger::SendArgs {
+                from: source,
+                to,
+                symbol,
+                amount,
+                ..
+            } = minicbor::decode(&data).map_err(ManyError::deserialization_error)?;
+            let source = source.unwrap_or(from);
+            self.verify_send_sender(&from, &source)?;
+            self.storage.prepare_send(&source, &to, &symbol, &amount)?;
+        }
+        Ok(EmptyReturn)
+    }
+}
@@ -0,0 +1,87 @@
+//! Exactly-once commands.
+//!
+//! The result of every command executed successfully is kept in the state,
+//! keyed by the hash of its request, see [`crate::storage::delivered`]. The same
+//! request delivered again, e.g. in a later block after a client retried it,
+//! returns the original result without being executed.
+use crate::module::LedgerModuleImpl;
+use crate::storage::delivered::request_hash;
+use async_trait::async_trait;
+use coset::CoseSign1;
+use many_error::ManyError;
+use many_identity::Identity;
+use many_identity_dsa::CoseKeyIdentity;
+use many_protocol::{encode_cose_sign1_from_response, RequestMessage, ResponseMessage};
+use many_server::transport::LowLevelManyRequestHandler;
+use std::collections::BTreeSet;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+impl LedgerModuleImpl {
+    /// The original result of a request already delivered, if any.
+    pub fn delivered_response(&self, hash: &[u8]) -> Result<Option<Vec<u8>>, ManyError> {
+        self.storage.delivered_response(hash)
+    }
+
+    /// Remember the result of a command executed successfully.
+    pub fn command_delivered(&mut self, hash: &[u8], response: Vec<u8>) -> Result<(), ManyError> {
+        self.storage.record_delivered_request(hash, response)
+    }
+}
+
+/// Returns the original result of the commands already delivered, and records
+/// the result of the others. The original results are signed again with `key`.
+pub struct DeliveredHandler<H> {
+    pub inner: H,
+    pub module_impl: Arc<Mutex<LedgerModuleImpl>>,
+    pub commands: BTreeSet<String>,
+    pub key: CoseKeyIdentity,
+}
+
+impl<H> Debug for DeliveredHandler<H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DeliveredHandler")
+    }
+}
+
+#[async_trait]
+impl<H: LowLevelManyRequestHandler> LowLevelManyRequestHandler for DeliveredHandler<H> {
+    async fn execute(&self, envelope: CoseSign1) -> Result<CoseSign1, String> {
+        let request = envelope.payload.as_deref().and_then(|payload| {
+            RequestMessage::from_bytes(payload)
+                .ok()
+                .filter(|message| self.commands.contains(&message.method))
+                .map(|message| (message, request_hash(payload)))
+        });
+        let (request, hash) = match request {
+            Some(request) => request,
+            None => return self.inner.execute(envelope).await,
+        };
+
+        let delivered = self
+            .module_impl
+            .lock()
+            .unwrap()
+            .delivered_response(&hash)
+            .map_err(|e| e.to_string())?;
+        if let Some(data) = delivered {
+            let response = ResponseMessage::from_request(&request, &self.key.address(), Ok(data));
+            return encode_cose_sign1_from_response(response, &self.key).map_err(|e| e.to_string());
+        }
+
+        let result = self.inner.execute(envelope).await?;
+        let data = result
+            .payload
+            .as_deref()
+            .and_then(|payload| ResponseMessage::from_bytes(payload).ok())
+            .and_then(|response| response.data.ok());
+        if let Some(data) = data {
+            let mut module_impl = self.module_impl.lock().unwrap();
+            if let Err(e) = module_impl.command_delivered(&hash, data) {
+                warn!("Could not record the request {}: {e}", hex::encode(&hash));
+            }
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,32 @@
+use crate::error::registry::{error_codes, ErrorInfo};
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::EmptyArg;
+use minicbor::{Decode, Encode};
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ErrorCodesReturns {
+    #[n(0)]
+    pub errors: Vec<ErrorInfo>,
+}
+
+#[many_module(name = LedgerErrorCodesModule, namespace = ledger, many_modules_crate = many_modules)]
+pub trait LedgerErrorCodesModuleBackend: Send {
+    fn error_codes(&self, sender: &Address, args: EmptyArg)
+        -> Result<ErrorCodesReturns, ManyError>;
+}
+
+impl LedgerErrorCodesModuleBackend for LedgerModuleImpl {
+    fn error_codes(
+        &self,
+        _sender: &Address,
+        _args: EmptyArg,
+    ) -> Result<ErrorCodesReturns, ManyError> {
+        Ok(ErrorCodesReturns {
+            errors: error_codes(),
+        })
+    }
+}
@@ -0,0 +1,133 @@
+use crate::error;
+use crate::module::LedgerModuleImpl;
+use crate::storage::escrow::{Escrow, EscrowCondition};
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::EmptyReturn;
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::Memo;
+use minicbor::{Decode, Encode};
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct CreateArgs {
+    /// The source of the funds. Defaults to the sender.
+    #[n(0)]
+    pub from: Option<Address>,
+
+    #[n(1)]
+    pub to: Address,
+
+    #[n(2)]
+    pub amount: TokenAmount,
+
+    #[n(3)]
+    pub symbol: Symbol,
+
+    #[n(4)]
+    pub memo: Option<Memo>,
+
+    /// The identity deciding on the release of the funds. Without an arbiter,
+    /// the funds are released on timeout.
+    #[n(5)]
+    pub arbiter: Option<Address>,
+
+    /// Seconds before the escrow is released, or refunded if it has an
+    /// arbiter.
+    #[n(6)]
+    pub timeout_in_secs: u64,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct CreateReturns {
+    #[n(0)]
+    pub id: u64,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ReleaseArgs {
+    #[n(0)]
+    pub id: u64,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct RefundArgs {
+    #[n(0)]
+    pub id: u64,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct InfoArgs {
+    #[n(0)]
+    pub id: u64,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct InfoReturns {
+    #[n(0)]
+    pub escrow: Escrow,
+}
+
+/// Funds locked to a recipient until a condition releases them.
+#[many_module(name = EscrowModule, namespace = escrow, many_modules_crate = many_modules)]
+pub trait EscrowModuleBackend: Send {
+    fn create(&mut self, sender: &Address, args: CreateArgs) -> Result<CreateReturns, ManyError>;
+    fn release(&mut self, sender: &Address, args: ReleaseArgs) -> Result<EmptyReturn, ManyError>;
+    fn refund(&mut self, sender: &Address, args: RefundArgs) -> Result<EmptyReturn, ManyError>;
+    fn info(&self, sender: &Address, args: InfoArgs) -> Result<InfoReturns, ManyError>;
+}
+
+impl EscrowModuleBackend for LedgerModuleImpl {
+    fn create(&mut self, sender: &Address, args: CreateArgs) -> Result<CreateReturns, ManyError> {
+        let CreateArgs {
+            from,
+            to,
+            amount,
+            symbol,
+            memo,
+            arbiter,
+            timeout_in_secs,
+        } = args;
+
+        let from = from.as_ref().unwrap_or(sender);
+        self.verify_send_sender(sender, from)?;
+        self.limits()?.check_memo(memo.as_ref())?;
+
+        let condition = arbiter.map_or(EscrowCondition::Timeout, EscrowCondition::Arbiter);
+        let id = self.storage.create_escrow(
+            from,
+            &to,
+            &symbol,
+            amount,
+            memo,
+            condition,
+            timeout_in_secs,
+        )?;
+        Ok(CreateReturns { id })
+    }
+
+    fn release(&mut self, sender: &Address, args: ReleaseArgs) -> Result<EmptyReturn, ManyError> {
+        self.storage.release_escrow(sender, args.id)?;
+        Ok(EmptyReturn)
+    }
+
+    fn refund(&mut self, sender: &Address, args: RefundArgs) -> Result<EmptyReturn, ManyError> {
+        self.storage.refund_escrow(sender, args.id)?;
+        Ok(EmptyReturn)
+    }
+
+    fn info(&self, _sender: &Address, args: InfoArgs) -> Result<InfoReturns, ManyError> {
+        Ok(InfoReturns {
+            escrow: self
+                .storage
+                .get_escrow(args.id)?
+                .ok_or_else(|| error::escrow_not_found(args.id))?,
+        })
+    }
+}
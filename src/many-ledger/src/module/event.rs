@@ -1,15 +1,22 @@
 use crate::module::LedgerModuleImpl;
+use crate::storage::event::range_after_cursor;
+use crate::storage::event_index::{
+    prefix_for_account_events, prefix_for_kind_events, prefix_for_kind_symbol_events,
+};
+use crate::storage::growth::symbol_of;
+use crate::storage::LedgerStorage;
 use many_error::ManyError;
 use many_identity::Address;
 use many_modules::account::features::multisig::MultisigTransactionState;
 use many_modules::events;
 use many_modules::events::{
-    EventFilterAttributeSpecific, EventFilterAttributeSpecificIndex, EventInfo, EventLog,
+    EventFilterAttributeSpecific, EventFilterAttributeSpecificIndex, EventId, EventInfo, EventLog,
 };
-use many_types::{CborRange, Timestamp, VecOrSingle};
+use many_types::ledger::Symbol;
+use many_types::{CborRange, SortOrder, Timestamp, VecOrSingle};
 use std::collections::BTreeMap;
 
-const MAXIMUM_EVENT_COUNT: usize = 100;
+pub const MAXIMUM_EVENT_COUNT: usize = 100;
 
 type EventLogResult = Result<events::EventLog, ManyError>;
 
@@ -29,6 +36,21 @@ fn filter_account<'a>(
     }
 }
 
+fn filter_symbol<'a>(
+    it: Box<dyn Iterator<Item = EventLogResult> + 'a>,
+    symbol: Option<Symbol>,
+) -> Box<dyn Iterator<Item = EventLogResult> + 'a> {
+    if let Some(symbol) = symbol {
+        Box::new(it.filter(move |t| match t {
+            // Propagate the errors.
+            Err(_) => true,
+            Ok(t) => symbol_of(&t.content) == Some(symbol),
+        }))
+    } else {
+        it
+    }
+}
+
 fn filter_event_kind<'a>(
     it: Box<dyn Iterator<Item = EventLogResult> + 'a>,
     event_kind: Option<VecOrSingle<events::EventKind>>,
@@ -98,6 +120,57 @@ fn filter_attribute_specific<'a>(
     it
 }
 
+/// The prefix of the event index that can serve `filter`, if any. Only filters
+/// on a single kind and a symbol, on a single account or on a single kind can
+/// use an index.
+fn index_prefix(
+    storage: &LedgerStorage,
+    filter: &events::EventFilter,
+    symbol: Option<&Symbol>,
+) -> Result<Option<Vec<u8>>, ManyError> {
+    if let (Some(VecOrSingle(kinds)), Some(symbol)) = (&filter.kind, symbol) {
+        if let [kind] = kinds.as_slice() {
+            if storage.is_symbol_event_index_active() {
+                return Ok(Some(prefix_for_kind_symbol_events(kind, symbol)?));
+            }
+        }
+    }
+    if !storage.is_event_index_active() {
+        return Ok(None);
+    }
+    if let Some(VecOrSingle(accounts)) = &filter.account {
+        if let [account] = accounts.as_slice() {
+            return Ok(Some(prefix_for_account_events(account)));
+        }
+    }
+    if let Some(VecOrSingle(kinds)) = &filter.kind {
+        if let [kind] = kinds.as_slice() {
+            return Ok(Some(prefix_for_kind_events(kind)?));
+        }
+    }
+    Ok(None)
+}
+
+/// Whether the keys of the ID range, or of the index serving `filter`, answer
+/// the whole filter so no event needs to be read.
+fn keys_only(
+    storage: &LedgerStorage,
+    filter: &events::EventFilter,
+    symbol: Option<&Symbol>,
+) -> bool {
+    fn len<T>(x: &Option<VecOrSingle<T>>) -> usize {
+        x.as_ref().map_or(0, |VecOrSingle(v)| v.len())
+    }
+    filter.date_range.is_none()
+        && filter.events_filter_attribute_specific.is_empty()
+        && match (len(&filter.account), len(&filter.kind), symbol) {
+            (0, 0, None) => true,
+            (0, 1, Some(_)) => storage.is_symbol_event_index_active(),
+            (1, 0, None) | (0, 1, None) => storage.is_event_index_active(),
+            _ => false,
+        }
+}
+
 impl events::EventsModuleBackend for LedgerModuleImpl {
     fn info(&self, _args: events::InfoArgs) -> Result<events::InfoReturn, ManyError> {
         use strum::IntoEnumIterator;
@@ -113,32 +186,119 @@ impl events::EventsModuleBackend for LedgerModuleImpl {
             order,
             filter,
         } = args;
+        let (nb_events, events, _) = self.list_events(count, order, filter, None, None, None)?;
+
+        Ok(events::ListReturns { nb_events, events })
+    }
+}
+
+impl LedgerModuleImpl {
+    /// The events matching `filter` and `symbol` with an ID in `range`, in
+    /// `order`.
+    fn filtered_events<'a>(
+        &'a self,
+        filter: &'a events::EventFilter,
+        range: CborRange<EventId>,
+        order: SortOrder,
+        symbol: Option<Symbol>,
+        offset: usize,
+    ) -> Result<Box<dyn Iterator<Item = EventLogResult> + 'a>, ManyError> {
+        let storage = &self.storage;
+
+        // When the keys answer the whole filter, skip them before reading any
+        // event, e.g. for the most recent events of an account past the first
+        // pages.
+        let (storage_offset, offset) = if keys_only(storage, filter, symbol.as_ref()) {
+            (offset, 0)
+        } else {
+            (0, offset)
+        };
+        let iter: Box<dyn Iterator<Item = EventLogResult>> =
+            match index_prefix(storage, filter, symbol.as_ref())? {
+                // The remaining filters still apply below, including the indexed one
+                // which is then a no-op.
+                Some(prefix) => {
+                    Box::new(storage.iter_indexed_events(&prefix, range, order, storage_offset))
+                }
+                None => Box::new(storage.iter_events(range, order).skip(storage_offset).map(
+                    |item| {
+                        let (_k, v) = item.map_err(ManyError::unknown)?;
+                        minicbor::decode::<events::EventLog>(v.as_slice())
+                            .map_err(ManyError::deserialization_error)
+                    },
+                )),
+            };
+
+        let iter = filter_account(iter, filter.account.clone());
+        let iter = filter_symbol(iter, symbol);
+        let iter = filter_event_kind(iter, filter.kind.clone());
+        let iter = filter_date(iter, filter.date_range.clone().unwrap_or_default());
+        Ok(Box::new(
+            filter_attribute_specific(iter, &filter.events_filter_attribute_specific).skip(offset),
+        ))
+    }
+
+    /// List the events matching `filter`, starting right after `cursor` if any
+    /// and skipping the first `offset` of them. Returns the total number of
+    /// events, the events of the page and, if more events match, the cursor of
+    /// the next page.
+    pub(crate) fn list_events(
+        &self,
+        count: Option<u64>,
+        order: Option<SortOrder>,
+        filter: Option<events::EventFilter>,
+        cursor: Option<EventId>,
+        symbol: Option<Symbol>,
+        offset: Option<u64>,
+    ) -> Result<(u64, Vec<events::EventLog>, Option<EventId>), ManyError> {
         let filter = filter.unwrap_or_default();
+        let order = order.unwrap_or_default();
 
         let count = count.map_or(MAXIMUM_EVENT_COUNT, |c| {
             std::cmp::min(c as usize, MAXIMUM_EVENT_COUNT)
         });
 
-        let storage = &self.storage;
-        let nb_events = storage.nb_events()?;
-        let iter = storage.iter_events(
-            filter.id_range.unwrap_or_default(),
-            order.unwrap_or_default(),
-        );
+        let range = filter.id_range.clone().unwrap_or_default();
+        let range = match cursor {
+            Some(cursor) => range_after_cursor(range, cursor, &order),
+            None => range,
+        };
 
-        let iter = Box::new(iter.map(|item| {
-            let (_k, v) = item.map_err(ManyError::unknown)?;
-            minicbor::decode::<events::EventLog>(v.as_slice())
-                .map_err(ManyError::deserialization_error)
-        }));
+        let nb_events = self.storage.nb_events()?;
+        let offset = offset.map_or(0, |o| o as usize);
+        let iter = self.filtered_events(&filter, range, order, symbol, offset)?;
 
-        let iter = filter_account(iter, filter.account);
-        let iter = filter_event_kind(iter, filter.kind);
-        let iter = filter_date(iter, filter.date_range.unwrap_or_default());
-        let iter = filter_attribute_specific(iter, &filter.events_filter_attribute_specific);
+        // Read one more event than requested to know if there is a next page.
+        let mut events: Vec<events::EventLog> = iter.take(count + 1).collect::<Result<_, _>>()?;
+        let next = if events.len() > count {
+            events.truncate(count);
+            events.last().map(|e| e.id.clone())
+        } else {
+            None
+        };
 
-        let events: Vec<events::EventLog> = iter.take(count).collect::<Result<_, _>>()?;
+        Ok((nb_events, events, next))
+    }
 
-        Ok(events::ListReturns { nb_events, events })
+    /// The number of events matching `filter` and `symbol`. When the keys
+    /// answer the whole filter, only keys are counted and no event is read.
+    pub(crate) fn count_events(
+        &self,
+        filter: Option<events::EventFilter>,
+        symbol: Option<Symbol>,
+    ) -> Result<u64, ManyError> {
+        let filter = filter.unwrap_or_default();
+        let range = filter.id_range.clone().unwrap_or_default();
+        let storage = &self.storage;
+
+        if keys_only(storage, &filter, symbol.as_ref()) {
+            return match index_prefix(storage, &filter, symbol.as_ref())? {
+                Some(prefix) => storage.count_indexed_events(&prefix, range),
+                None => storage.count_events(range),
+            };
+        }
+
+        self.filtered_events(&filter, range, SortOrder::Indeterminate, symbol, 0)?
+            .try_fold(0, |count, event| event.map(|_| count + 1))
     }
 }
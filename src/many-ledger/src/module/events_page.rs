@@ -0,0 +1,207 @@
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::events::{EventFilter, EventId, EventLog};
+use many_types::ledger::Symbol;
+use many_types::SortOrder;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+
+#[derive(Clone, Debug, Default, Encode, Decode)]
+#[cbor(map)]
+pub struct ListPageArgs {
+    #[n(0)]
+    pub count: Option<u64>,
+
+    #[n(1)]
+    pub order: Option<SortOrder>,
+
+    #[n(2)]
+    pub filter: Option<EventFilter>,
+
+    /// The cursor returned with the previous page. The first page is returned
+    /// if omitted.
+    #[n(3)]
+    pub cursor: Option<EventId>,
+
+    /// Account names, as typed by users, added to the account filter. Names are
+    /// IdStore recall phrases, with words separated by spaces.
+    #[n(4)]
+    pub account_names: Option<Vec<String>>,
+
+    /// Return a Merkle proof of each event against the application hash.
+    #[n(5)]
+    pub proof: Option<bool>,
+
+    /// Only the events about this symbol, e.g. with a kind filter, the mints of
+    /// a token.
+    #[n(6)]
+    pub symbol: Option<Symbol>,
+
+    /// The number of matching events to skip, after the cursor if any.
+    #[n(7)]
+    pub offset: Option<u64>,
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct ListPageReturns {
+    #[n(0)]
+    pub nb_events: u64,
+
+    #[n(1)]
+    pub events: Vec<EventLog>,
+
+    /// An opaque cursor to pass back to get the next page, or None if this page
+    /// is the last one.
+    #[n(2)]
+    pub cursor: Option<EventId>,
+
+    /// A merk proof of each event, in order, if asked for. Events in the cold
+    /// store have no proof.
+    #[n(3)]
+    pub proofs: Option<Vec<Option<ByteVec>>>,
+
+    /// The height of the block whose application hash the proofs verify
+    /// against.
+    #[n(4)]
+    pub height: Option<u64>,
+
+    /// The height of the block of each event, in order.
+    #[n(5)]
+    pub heights: Option<Vec<Option<u64>>>,
+}
+
+#[derive(Clone, Debug, Default, Encode, Decode)]
+#[cbor(map)]
+pub struct ListCountArgs {
+    #[n(0)]
+    pub filter: Option<EventFilter>,
+
+    /// Account names, as in [`ListPageArgs`].
+    #[n(1)]
+    pub account_names: Option<Vec<String>>,
+
+    #[n(2)]
+    pub symbol: Option<Symbol>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ListCountReturns {
+    /// The number of events matching the filter, unlike the total number of
+    /// events returned by `events.list`.
+    #[n(0)]
+    pub count: u64,
+}
+
+#[many_module(name = EventsPageModule, namespace = events, many_modules_crate = many_modules)]
+pub trait EventsPageModuleBackend: Send {
+    fn list_page(&self, sender: &Address, args: ListPageArgs)
+        -> Result<ListPageReturns, ManyError>;
+    fn list_count(
+        &self,
+        sender: &Address,
+        args: ListCountArgs,
+    ) -> Result<ListCountReturns, ManyError>;
+}
+
+impl LedgerModuleImpl {
+    fn filter_with_named_accounts(
+        &self,
+        filter: Option<EventFilter>,
+        account_names: Option<Vec<String>>,
+    ) -> Result<Option<EventFilter>, ManyError> {
+        Ok(match account_names.filter(|names| !names.is_empty()) {
+            Some(names) => Some(self.filter_named_accounts(filter.unwrap_or_default(), names)?),
+            None => filter,
+        })
+    }
+
+    /// Resolve account names and add them to the account filter.
+    fn filter_named_accounts(
+        &self,
+        mut filter: EventFilter,
+        names: Vec<String>,
+    ) -> Result<EventFilter, ManyError> {
+        let mut accounts: Vec<Address> = filter.account.map(Into::into).unwrap_or_default();
+        for name in names {
+            let recall_phrase = name.split_whitespace().map(str::to_string).collect();
+            accounts.push(
+                self.storage
+                    .get_address_from_recall_phrase(&recall_phrase)?,
+            );
+        }
+        filter.account = Some(accounts.into());
+        Ok(filter)
+    }
+}
+
+impl EventsPageModuleBackend for LedgerModuleImpl {
+    fn list_page(
+        &self,
+        _sender: &Address,
+        args: ListPageArgs,
+    ) -> Result<ListPageReturns, ManyError> {
+        let ListPageArgs {
+            count,
+            order,
+            filter,
+            cursor,
+            account_names,
+            proof,
+            symbol,
+            offset,
+        } = args;
+
+        let filter = self.filter_with_named_accounts(filter, account_names)?;
+
+        let (nb_events, events, cursor) =
+            self.list_events(count, order, filter, cursor, symbol, offset)?;
+
+        let (proofs, height) = if proof.unwrap_or(false) {
+            let proofs = events
+                .iter()
+                .map(|event| {
+                    self.storage
+                        .prove_event(event.id.clone())
+                        .map(|proof| proof.map(Into::into))
+                })
+                .collect::<Result<_, ManyError>>()?;
+            (Some(proofs), Some(self.storage.get_height()?))
+        } else {
+            (None, None)
+        };
+
+        let heights = events
+            .iter()
+            .map(|event| self.storage.event_block_height(&event.id))
+            .collect();
+
+        Ok(ListPageReturns {
+            nb_events,
+            events,
+            cursor,
+            proofs,
+            height,
+            heights: Some(heights),
+        })
+    }
+
+    fn list_count(
+        &self,
+        _sender: &Address,
+        args: ListCountArgs,
+    ) -> Result<ListCountReturns, ManyError> {
+        let ListCountArgs {
+            filter,
+            account_names,
+            symbol,
+        } = args;
+        let filter = self.filter_with_named_accounts(filter, account_names)?;
+        Ok(ListCountReturns {
+            count: self.count_events(filter, symbol)?,
+        })
+    }
+}
@@ -0,0 +1,95 @@
+use crate::module::LedgerModuleImpl;
+use crate::storage::faucet::FaucetConfig;
+use crate::storage::scheduler::secs_since_epoch;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_types::ledger::{Symbol, TokenAmount};
+use minicbor::{Decode, Encode};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, Default, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct FaucetInfoArgs {
+    /// The identity to return the next claims of. Defaults to the sender.
+    #[n(0)]
+    pub account: Option<Address>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct FaucetInfoReturns {
+    /// The faucet, or None if it is not configured.
+    #[n(0)]
+    pub config: Option<FaucetConfig>,
+
+    /// The number of seconds before the account can claim each symbol again.
+    /// Symbols it can claim now are absent.
+    #[n(1)]
+    pub retry_after: BTreeMap<Symbol, u64>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct FaucetClaimArgs {
+    #[n(0)]
+    pub symbol: Symbol,
+
+    /// The identity to send the tokens to. Defaults to the sender.
+    #[n(1)]
+    pub to: Option<Address>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct FaucetClaimReturns {
+    /// The amount sent.
+    #[n(0)]
+    pub amount: TokenAmount,
+}
+
+/// A faucet for devnets. Always served, claims fail unless the faucet is
+/// configured in the initial state.
+#[many_module(name = FaucetModule, namespace = faucet, many_modules_crate = many_modules)]
+pub trait FaucetModuleBackend: Send {
+    fn info(&self, sender: &Address, args: FaucetInfoArgs) -> Result<FaucetInfoReturns, ManyError>;
+    fn claim(
+        &mut self,
+        sender: &Address,
+        args: FaucetClaimArgs,
+    ) -> Result<FaucetClaimReturns, ManyError>;
+}
+
+impl FaucetModuleBackend for LedgerModuleImpl {
+    fn info(&self, sender: &Address, args: FaucetInfoArgs) -> Result<FaucetInfoReturns, ManyError> {
+        let account = args.account.unwrap_or(*sender);
+        let config = self.storage.get_faucet()?;
+        let now = secs_since_epoch(self.storage.now())?;
+
+        let mut retry_after = BTreeMap::new();
+        if let Some(config) = &config {
+            for symbol in config.amounts.keys() {
+                if let Some(last) = self.storage.get_faucet_claim(&account, symbol)? {
+                    let next = last.saturating_add(config.period);
+                    if now < next {
+                        retry_after.insert(*symbol, next - now);
+                    }
+                }
+            }
+        }
+        Ok(FaucetInfoReturns {
+            config,
+            retry_after,
+        })
+    }
+
+    fn claim(
+        &mut self,
+        sender: &Address,
+        args: FaucetClaimArgs,
+    ) -> Result<FaucetClaimReturns, ManyError> {
+        let to = args.to.unwrap_or(*sender);
+        let amount = self.storage.faucet_claim(&to, &args.symbol)?;
+        Ok(FaucetClaimReturns { amount })
+    }
+}
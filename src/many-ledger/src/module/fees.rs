@@ -0,0 +1,47 @@
+use crate::module::LedgerModuleImpl;
+use crate::storage::fees::TransferFee;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_types::ledger::Symbol;
+use minicbor::{Decode, Encode};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Clone, Debug, Default, Encode, Decode)]
+#[cbor(map)]
+pub struct FeeInfoArgs {
+    /// Only return the fees of these symbols. All fees are returned if empty.
+    #[n(0)]
+    pub symbols: Option<BTreeSet<Symbol>>,
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct FeeInfoReturns {
+    /// The identity transfer fees are paid to.
+    #[n(0)]
+    pub collector: Option<Address>,
+
+    /// The fee charged on each transfer, per symbol. Symbols without fees are absent.
+    #[n(1)]
+    pub fees: BTreeMap<Symbol, TransferFee>,
+}
+
+#[many_module(name = LedgerFeesModule, namespace = ledger, many_modules_crate = many_modules)]
+pub trait LedgerFeesModuleBackend: Send {
+    fn fee_info(&self, sender: &Address, args: FeeInfoArgs) -> Result<FeeInfoReturns, ManyError>;
+}
+
+impl LedgerFeesModuleBackend for LedgerModuleImpl {
+    fn fee_info(&self, _sender: &Address, args: FeeInfoArgs) -> Result<FeeInfoReturns, ManyError> {
+        let mut fees = self.storage.get_transfer_fees()?;
+        if let Some(symbols) = args.symbols.filter(|s| !s.is_empty()) {
+            fees.retain(|symbol, _| symbols.contains(symbol));
+        }
+
+        Ok(FeeInfoReturns {
+            collector: self.storage.get_fee_collector()?,
+            fees,
+        })
+    }
+}
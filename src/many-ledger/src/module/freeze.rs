@@ -0,0 +1,51 @@
+use crate::error;
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::EmptyReturn;
+use minicbor::{Decode, Encode};
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct FreezeArgs {
+    #[n(0)]
+    pub account: Address,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct UnfreezeArgs {
+    #[n(0)]
+    pub account: Address,
+}
+
+/// Regulatory holds. A frozen account can neither send nor receive funds.
+#[many_module(name = LedgerFreezeModule, namespace = ledger, many_modules_crate = many_modules)]
+pub trait LedgerFreezeModuleBackend: Send {
+    fn freeze(&mut self, sender: &Address, args: FreezeArgs) -> Result<EmptyReturn, ManyError>;
+    fn unfreeze(&mut self, sender: &Address, args: UnfreezeArgs) -> Result<EmptyReturn, ManyError>;
+}
+
+impl LedgerModuleImpl {
+    fn verify_compliance_sender(&self, sender: &Address) -> Result<(), ManyError> {
+        if *sender != self.storage.get_compliance_identity()? {
+            return Err(error::unauthorized());
+        }
+        Ok(())
+    }
+}
+
+impl LedgerFreezeModuleBackend for LedgerModuleImpl {
+    fn freeze(&mut self, sender: &Address, args: FreezeArgs) -> Result<EmptyReturn, ManyError> {
+        self.verify_compliance_sender(sender)?;
+        self.storage.set_frozen(&args.account, true)?;
+        Ok(EmptyReturn)
+    }
+
+    fn unfreeze(&mut self, sender: &Address, args: UnfreezeArgs) -> Result<EmptyReturn, ManyError> {
+        self.verify_compliance_sender(sender)?;
+        self.storage.set_frozen(&args.account, false)?;
+        Ok(EmptyReturn)
+    }
+}
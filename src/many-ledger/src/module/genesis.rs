@@ -0,0 +1,26 @@
+use crate::error;
+use crate::module::LedgerModuleImpl;
+use crate::storage::genesis::{GenesisReport, GENESIS_REPORT_KEY};
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::EmptyArg;
+
+/// The report of the initial distribution. As every response, it is signed by
+/// the node, so it can be kept as an attestation.
+#[many_module(name = LedgerGenesisModule, namespace = ledger, many_modules_crate = many_modules)]
+pub trait LedgerGenesisModuleBackend: Send {
+    fn genesis_report(&self, sender: &Address, args: EmptyArg) -> Result<GenesisReport, ManyError>;
+}
+
+impl LedgerGenesisModuleBackend for LedgerModuleImpl {
+    fn genesis_report(
+        &self,
+        _sender: &Address,
+        _args: EmptyArg,
+    ) -> Result<GenesisReport, ManyError> {
+        self.storage.get_genesis_report()?.ok_or_else(|| {
+            error::storage_key_not_found(String::from_utf8_lossy(GENESIS_REPORT_KEY))
+        })
+    }
+}
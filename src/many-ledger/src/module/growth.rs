@@ -0,0 +1,47 @@
+use crate::module::LedgerModuleImpl;
+use crate::storage::growth::Growth;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use minicbor::{Decode, Encode};
+use std::collections::BTreeMap;
+
+/// Number of days returned when none is specified.
+pub const DEFAULT_GROWTH_DAYS: u64 = 30;
+
+/// Maximum number of days returned.
+pub const MAX_GROWTH_DAYS: u64 = 366;
+
+#[derive(Clone, Debug, Default, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct GrowthArgs {
+    /// Number of days to return, today included.
+    #[n(0)]
+    pub days: Option<u64>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct GrowthReturns {
+    /// The growth of each day with growth, by number of days since the Unix
+    /// epoch, in block time.
+    #[n(0)]
+    pub days: BTreeMap<u64, Growth>,
+}
+
+#[many_module(name = LedgerGrowthModule, namespace = ledger, many_modules_crate = many_modules)]
+pub trait LedgerGrowthModuleBackend: Send {
+    fn growth(&self, sender: &Address, args: GrowthArgs) -> Result<GrowthReturns, ManyError>;
+}
+
+impl LedgerGrowthModuleBackend for LedgerModuleImpl {
+    fn growth(&self, _sender: &Address, args: GrowthArgs) -> Result<GrowthReturns, ManyError> {
+        let days = args
+            .days
+            .unwrap_or(DEFAULT_GROWTH_DAYS)
+            .min(MAX_GROWTH_DAYS);
+        Ok(GrowthReturns {
+            days: self.storage.growth_per_day(days)?,
+        })
+    }
+}
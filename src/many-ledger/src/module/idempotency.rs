@@ -0,0 +1,110 @@
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::EmptyReturn;
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::Memo;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct SendOnceArgs {
+    /// The source of the funds. Defaults to the sender.
+    #[n(0)]
+    pub from: Option<Address>,
+
+    #[n(1)]
+    pub to: Address,
+
+    #[n(2)]
+    pub amount: TokenAmount,
+
+    #[n(3)]
+    pub symbol: Symbol,
+
+    #[n(4)]
+    pub memo: Option<Memo>,
+
+    /// A key chosen by the client, e.g. a random nonce, to pass again when
+    /// retrying the same transfer. A key already used by the sender within its
+    /// window is rejected.
+    #[n(5)]
+    pub idempotency_key: ByteVec,
+
+    /// Seconds the key is remembered for. Defaults to a day.
+    #[n(6)]
+    pub window_in_secs: Option<u64>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct IdempotencyKeyUsedArgs {
+    /// The sender of the command. Defaults to the sender of the query.
+    #[n(0)]
+    pub sender: Option<Address>,
+
+    #[n(1)]
+    pub idempotency_key: ByteVec,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct IdempotencyKeyUsedReturns {
+    #[n(0)]
+    pub used: bool,
+}
+
+#[many_module(name = LedgerIdempotencyModule, namespace = ledger, many_modules_crate = many_modules)]
+pub trait LedgerIdempotencyModuleBackend: Send {
+    fn send_once(&mut self, sender: &Address, args: SendOnceArgs)
+        -> Result<EmptyReturn, ManyError>;
+    fn idempotency_key_used(
+        &self,
+        sender: &Address,
+        args: IdempotencyKeyUsedArgs,
+    ) -> Result<IdempotencyKeyUsedReturns, ManyError>;
+}
+
+impl LedgerIdempotencyModuleBackend for LedgerModuleImpl {
+    fn send_once(
+        &mut self,
+        sender: &Address,
+        args: SendOnceArgs,
+    ) -> Result<EmptyReturn, ManyError> {
+        let SendOnceArgs {
+            from,
+            to,
+            amount,
+            symbol,
+            memo,
+            idempotency_key,
+            window_in_secs,
+        } = args;
+
+        let from = from.as_ref().unwrap_or(sender);
+        self.verify_send_sender(sender, from)?;
+        self.limits()?.check_memo(memo.as_ref())?;
+        self.storage
+            .check_idempotency_key(sender, &idempotency_key, window_in_secs)?;
+
+        self.storage.send(from, &to, &symbol, amount, memo)?;
+        self.storage
+            .record_idempotency_key(sender, &idempotency_key, window_in_secs)?;
+        Ok(EmptyReturn)
+    }
+
+    fn idempotency_key_used(
+        &self,
+        sender: &Address,
+        args: IdempotencyKeyUsedArgs,
+    ) -> Result<IdempotencyKeyUsedReturns, ManyError> {
+        let sender = args.sender.as_ref().unwrap_or(sender);
+        Ok(IdempotencyKeyUsedReturns {
+            used: self
+                .storage
+                .is_idempotency_key_used(sender, &args.idempotency_key)?,
+        })
+    }
+}
@@ -31,6 +31,13 @@ pub fn generate_recall_phrase<const W: usize, const FB: usize, const CS: usize>(
     Ok(recall_phrase)
 }
 
+pub(crate) fn validate_credential_id(cred_id: &idstore::CredentialId) -> Result<(), ManyError> {
+    if !(16..=1023).contains(&cred_id.0.len()) {
+        return Err(idstore::invalid_credential_id(hex::encode(&*cred_id.0)));
+    }
+    Ok(())
+}
+
 impl idstore::IdStoreModuleBackend for LedgerModuleImpl {
     fn store(
         &mut self,
@@ -49,10 +56,8 @@ impl idstore::IdStoreModuleBackend for LedgerModuleImpl {
             return Err(idstore::invalid_address(address.to_string()));
         }
 
-        if !(16..=1023).contains(&cred_id.0.len()) {
-            return Err(idstore::invalid_credential_id(hex::encode(&*cred_id.0)));
-        }
-
+        validate_credential_id(&cred_id)?;
+        self.limits()?.check_credential(&public_key.0)?;
         let _: CoseKey =
             CoseKey::from_slice(&public_key.0).map_err(ManyError::deserialization_error)?;
 
@@ -78,7 +83,7 @@ impl idstore::IdStoreModuleBackend for LedgerModuleImpl {
                 _ => unimplemented!(),
             }?;
 
-            if self.storage.get_from_recall_phrase(&recall_phrase).is_ok() {
+            if self.storage.is_recall_phrase_taken(&recall_phrase)? {
                 current_try += 1;
                 tracing::debug!("Recall phrase generation failed, retrying...")
             } else {
@@ -254,4 +259,44 @@ mod tests {
         let rp = result.unwrap().0;
         assert_eq!(rp.len(), 5);
     }
+
+    #[test]
+    /// Revoked recall phrases are never generated again
+    fn idstore_revoked_recall_phrase_not_reused() {
+        let cose_key_id = generate_random_ed25519_identity();
+        let public_key: idstore::PublicKey =
+            idstore::PublicKey(cose_key_id.public_key().to_vec().unwrap().into());
+        let mut module_impl = LedgerModuleImpl::new(
+            InitialStateJson::read("../../staging/ledger_state.json5")
+                .or_else(|_| InitialStateJson::read("staging/ledger_state.json5"))
+                .expect("Could not read initial state."),
+            None,
+            tempfile::tempdir().unwrap(),
+            false,
+        )
+        .unwrap();
+        let id = cose_key_id.address();
+        let args = idstore::StoreArgs {
+            address: id,
+            cred_id: idstore::CredentialId(vec![1; 16].into()),
+            public_key,
+        };
+
+        module_impl
+            .storage
+            .set_idstore_seed(0)
+            .expect("Unable to set idstore seed.");
+        let rp = module_impl.store(&id, args.clone()).unwrap().0;
+        module_impl
+            .storage
+            .revoke_recall_phrase(&rp, &id)
+            .expect("Unable to revoke the recall phrase.");
+
+        module_impl
+            .storage
+            .set_idstore_seed(0)
+            .expect("Unable to set idstore seed.");
+        let rp2 = module_impl.store(&id, args).unwrap().0;
+        assert_ne!(rp, rp2);
+    }
 }
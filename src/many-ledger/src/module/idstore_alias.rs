@@ -0,0 +1,121 @@
+use crate::error;
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::{idstore, EmptyReturn};
+use minicbor::{Decode, Encode};
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct AddAliasArgs {
+    /// A subresource of the sender to bind to it.
+    #[n(0)]
+    pub alias: Address,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct RemoveAliasArgs {
+    #[n(0)]
+    pub alias: Address,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ListAliasesArgs {
+    #[n(0)]
+    pub address: Address,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ListAliasesReturns {
+    /// Every alias bound to the address, in the order they were added.
+    #[n(0)]
+    pub aliases: Vec<Address>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct GetAliasOwnerArgs {
+    #[n(0)]
+    pub alias: Address,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct GetAliasOwnerReturns {
+    #[n(0)]
+    pub address: Address,
+}
+
+#[many_module(name = IdStoreAliasModule, namespace = idstore, many_modules_crate = many_modules)]
+pub trait IdStoreAliasModuleBackend: Send {
+    fn add_alias(&mut self, sender: &Address, args: AddAliasArgs)
+        -> Result<EmptyReturn, ManyError>;
+    fn remove_alias(
+        &mut self,
+        sender: &Address,
+        args: RemoveAliasArgs,
+    ) -> Result<EmptyReturn, ManyError>;
+    fn list_aliases(&self, args: ListAliasesArgs) -> Result<ListAliasesReturns, ManyError>;
+    fn get_alias_owner(&self, args: GetAliasOwnerArgs) -> Result<GetAliasOwnerReturns, ManyError>;
+}
+
+impl IdStoreAliasModuleBackend for LedgerModuleImpl {
+    fn add_alias(
+        &mut self,
+        sender: &Address,
+        args: AddAliasArgs,
+    ) -> Result<EmptyReturn, ManyError> {
+        let AddAliasArgs { alias } = args;
+        if !sender.is_public_key() {
+            return Err(ManyError::invalid_identity());
+        }
+
+        // Only subresources of the sender are provably controlled by it.
+        let is_subresource = match alias.subresource_id() {
+            Some(id) => sender.with_subresource_id(id)? == alias,
+            None => false,
+        };
+        if !is_subresource {
+            return Err(idstore::invalid_address(alias.to_string()));
+        }
+
+        self.storage.add_alias(sender, &alias)?;
+        Ok(EmptyReturn)
+    }
+
+    fn remove_alias(
+        &mut self,
+        sender: &Address,
+        args: RemoveAliasArgs,
+    ) -> Result<EmptyReturn, ManyError> {
+        let RemoveAliasArgs { alias } = args;
+        if sender.is_anonymous() {
+            return Err(ManyError::invalid_identity());
+        }
+        match self.storage.get_alias_owner(&alias)? {
+            Some(owner) if &owner == sender => {}
+            Some(_) => return Err(error::unauthorized()),
+            None => return Err(idstore::entry_not_found(alias.to_string())),
+        }
+
+        self.storage.remove_alias(sender, &alias)?;
+        Ok(EmptyReturn)
+    }
+
+    fn list_aliases(&self, args: ListAliasesArgs) -> Result<ListAliasesReturns, ManyError> {
+        Ok(ListAliasesReturns {
+            aliases: self.storage.get_aliases(&args.address)?,
+        })
+    }
+
+    fn get_alias_owner(&self, args: GetAliasOwnerArgs) -> Result<GetAliasOwnerReturns, ManyError> {
+        self.storage
+            .get_alias_owner(&args.alias)?
+            .map(|address| GetAliasOwnerReturns { address })
+            .ok_or_else(|| idstore::entry_not_found(args.alias.to_string()))
+    }
+}
@@ -0,0 +1,82 @@
+use crate::error;
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::{idstore, EmptyReturn};
+use minicbor::{Decode, Encode};
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ListFromAddressArgs {
+    #[n(0)]
+    pub address: Address,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq)]
+#[cbor(map)]
+pub struct ListFromAddressReturns {
+    /// Every credential of the address, oldest first.
+    #[n(0)]
+    pub credentials: Vec<idstore::GetReturns>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct RemoveCredentialArgs {
+    /// The address to remove a credential from. Only the address itself can
+    /// remove its credentials.
+    #[n(0)]
+    pub address: Address,
+
+    #[n(1)]
+    pub cred_id: idstore::CredentialId,
+}
+
+#[many_module(name = IdStoreCredentialsModule, namespace = idstore, many_modules_crate = many_modules)]
+pub trait IdStoreCredentialsModuleBackend: Send {
+    fn list_from_address(
+        &self,
+        args: ListFromAddressArgs,
+    ) -> Result<ListFromAddressReturns, ManyError>;
+    fn remove_credential(
+        &mut self,
+        sender: &Address,
+        args: RemoveCredentialArgs,
+    ) -> Result<EmptyReturn, ManyError>;
+}
+
+impl IdStoreCredentialsModuleBackend for LedgerModuleImpl {
+    fn list_from_address(
+        &self,
+        args: ListFromAddressArgs,
+    ) -> Result<ListFromAddressReturns, ManyError> {
+        let credentials = self
+            .storage
+            .get_all_from_address(&args.address)?
+            .into_iter()
+            .map(|(cred_id, public_key)| idstore::GetReturns {
+                cred_id,
+                public_key,
+            })
+            .collect();
+        Ok(ListFromAddressReturns { credentials })
+    }
+
+    fn remove_credential(
+        &mut self,
+        sender: &Address,
+        args: RemoveCredentialArgs,
+    ) -> Result<EmptyReturn, ManyError> {
+        let RemoveCredentialArgs { address, cred_id } = args;
+        if sender.is_anonymous() {
+            return Err(ManyError::invalid_identity());
+        }
+        if sender != &address {
+            return Err(error::unauthorized());
+        }
+
+        self.storage.remove_credential(&address, &cred_id)?;
+        Ok(EmptyReturn)
+    }
+}
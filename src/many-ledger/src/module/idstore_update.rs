@@ -0,0 +1,66 @@
+use crate::error;
+use crate::module::idstore::validate_credential_id;
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::{idstore, EmptyReturn};
+use minicbor::{Decode, Encode};
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct UpdateArgs {
+    /// The address to update. Only the address itself can update its entry.
+    #[n(0)]
+    pub address: Address,
+
+    #[n(1)]
+    pub cred_id: idstore::CredentialId,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct RevokeArgs {
+    /// The recall phrase to revoke. Only the address it was stored with can
+    /// revoke it.
+    #[n(0)]
+    pub recall_phrase: idstore::RecallPhrase,
+}
+
+#[many_module(name = IdStoreUpdateModule, namespace = idstore, many_modules_crate = many_modules)]
+pub trait IdStoreUpdateModuleBackend: Send {
+    fn update(&mut self, sender: &Address, args: UpdateArgs) -> Result<EmptyReturn, ManyError>;
+    fn revoke(&mut self, sender: &Address, args: RevokeArgs) -> Result<EmptyReturn, ManyError>;
+}
+
+impl IdStoreUpdateModuleBackend for LedgerModuleImpl {
+    fn update(&mut self, sender: &Address, args: UpdateArgs) -> Result<EmptyReturn, ManyError> {
+        let UpdateArgs { address, cred_id } = args;
+        if sender.is_anonymous() {
+            return Err(ManyError::invalid_identity());
+        }
+        if sender != &address {
+            return Err(error::unauthorized());
+        }
+        validate_credential_id(&cred_id)?;
+
+        self.storage.update_credential(&address, cred_id)?;
+        Ok(EmptyReturn)
+    }
+
+    fn revoke(&mut self, sender: &Address, args: RevokeArgs) -> Result<EmptyReturn, ManyError> {
+        if sender.is_anonymous() {
+            return Err(ManyError::invalid_identity());
+        }
+        let address = self
+            .storage
+            .get_address_from_recall_phrase(&args.recall_phrase)?;
+        if sender != &address {
+            return Err(error::unauthorized());
+        }
+
+        self.storage
+            .revoke_recall_phrase(&args.recall_phrase, &address)?;
+        Ok(EmptyReturn)
+    }
+}
@@ -18,6 +18,21 @@ impl ledger::LedgerCommandsModuleBackend for LedgerModuleImpl {
         } = args;
 
         let from = from.as_ref().unwrap_or(sender);
+        self.verify_send_sender(sender, from)?;
+        self.limits()?.check_memo(memo.as_ref())?;
+
+        self.storage.send(from, &to, &symbol, amount, memo)?;
+        Ok(EmptyReturn)
+    }
+}
+
+impl LedgerModuleImpl {
+    /// Verify that `sender` is allowed to transfer funds out of `from`.
+    pub(crate) fn verify_send_sender(
+        &self,
+        sender: &Address,
+        from: &Address,
+    ) -> Result<(), ManyError> {
         // We check here to make sure there isn't a code path that might ends up here without
         // proper validation (e.g. multisig or delayed execution). This should normally
         // not be a problem unless you have an instance of the module directly.
@@ -36,8 +51,6 @@ impl ledger::LedgerCommandsModuleBackend for LedgerModuleImpl {
                 return Err(error::unauthorized());
             }
         }
-
-        self.storage.send(from, &to, &symbol, amount, memo)?;
-        Ok(EmptyReturn)
+        Ok(())
     }
 }
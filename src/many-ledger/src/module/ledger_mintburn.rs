@@ -42,6 +42,7 @@ impl ledger::LedgerMintBurnModuleBackend for LedgerModuleImpl {
         )?;
 
         check_symbol_exists(&symbol, self.storage.get_symbols()?)?;
+        self.limits()?.check_memo(memo.as_ref())?;
 
         // Mint into storage
         self.storage.mint_token(symbol, &distribution)?;
@@ -80,6 +81,7 @@ impl ledger::LedgerMintBurnModuleBackend for LedgerModuleImpl {
         )?;
 
         check_symbol_exists(&symbol, self.storage.get_symbols()?)?;
+        self.limits()?.check_memo(memo.as_ref())?;
 
         // Disable partial burn, for now
         if let Some(error) = error_on_under_burn {
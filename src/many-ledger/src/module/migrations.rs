@@ -0,0 +1,105 @@
+use crate::migration::chunked::MigrationProgress;
+use crate::migration::patch::PatchRecord;
+use crate::migration::MIGRATIONS;
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::EmptyArg;
+use minicbor::{Decode, Encode};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct MigrationProgressReturns {
+    /// The progress of the chunked migrations which started, by name.
+    #[n(0)]
+    pub migrations: BTreeMap<String, MigrationProgress>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct MigrationStatus {
+    #[n(0)]
+    pub name: String,
+
+    #[n(1)]
+    pub description: String,
+
+    /// Whether the migration is enabled in the migrations config.
+    #[n(2)]
+    pub enabled: bool,
+
+    /// The activation height of the migration, if it is configured.
+    #[n(3)]
+    pub block_height: Option<u64>,
+
+    /// Whether the migration is active. An enabled migration which is not
+    /// active is pending.
+    #[n(4)]
+    pub active: bool,
+
+    /// The changes of a patch migration, once applied.
+    #[n(5)]
+    pub patch: Option<PatchRecord>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct MigrationStatusReturns {
+    /// Every migration known to the server, configured or not.
+    #[n(0)]
+    pub migrations: Vec<MigrationStatus>,
+}
+
+#[many_module(name = LedgerMigrationsModule, namespace = ledger, many_modules_crate = many_modules)]
+pub trait LedgerMigrationsModuleBackend: Send {
+    fn migration_progress(
+        &self,
+        sender: &Address,
+        args: EmptyArg,
+    ) -> Result<MigrationProgressReturns, ManyError>;
+
+    fn migration_status(
+        &self,
+        sender: &Address,
+        args: EmptyArg,
+    ) -> Result<MigrationStatusReturns, ManyError>;
+}
+
+impl LedgerMigrationsModuleBackend for LedgerModuleImpl {
+    fn migration_progress(
+        &self,
+        _sender: &Address,
+        _args: EmptyArg,
+    ) -> Result<MigrationProgressReturns, ManyError> {
+        Ok(MigrationProgressReturns {
+            migrations: self.storage.migrations_progress()?,
+        })
+    }
+
+    fn migration_status(
+        &self,
+        _sender: &Address,
+        _args: EmptyArg,
+    ) -> Result<MigrationStatusReturns, ManyError> {
+        let migrations = self.storage.migrations();
+        Ok(MigrationStatusReturns {
+            migrations: MIGRATIONS
+                .iter()
+                .map(|migration| {
+                    let patch = self.storage.migration_patch(migration.name())?;
+                    let configured = migrations.values().find(|m| m.name() == migration.name());
+                    Ok(MigrationStatus {
+                        name: migration.name().to_string(),
+                        description: migration.description().to_string(),
+                        enabled: configured.map_or(false, |m| m.is_enabled()),
+                        block_height: configured.map(|m| m.metadata().block_height),
+                        active: migrations.is_active(migration),
+                        patch,
+                    })
+                })
+                .collect::<Result<_, ManyError>>()?,
+        })
+    }
+}
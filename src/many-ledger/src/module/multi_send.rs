@@ -0,0 +1,130 @@
+use crate::error;
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::EmptyReturn;
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::Memo;
+use minicbor::{Decode, Encode};
+use std::collections::BTreeMap;
+
+/// Maximum number of transfers in a single `ledger.multiSend` call.
+pub const MAX_TRANSFERS: usize = 100;
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct MultiSendTransfer {
+    #[n(0)]
+    pub to: Address,
+
+    #[n(1)]
+    pub symbol: Symbol,
+
+    #[n(2)]
+    pub amount: TokenAmount,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct MultiSendArgs {
+    #[n(0)]
+    pub from: Option<Address>,
+
+    #[n(1)]
+    pub transfers: Vec<MultiSendTransfer>,
+
+    /// A memo shared by every transfer.
+    #[n(2)]
+    pub memo: Option<Memo>,
+}
+
+/// Several symbols sent to the same destination, e.g. a principal and a fee token.
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct SendSymbolsArgs {
+    #[n(0)]
+    pub from: Option<Address>,
+
+    #[n(1)]
+    pub to: Address,
+
+    #[n(2)]
+    pub amounts: BTreeMap<Symbol, TokenAmount>,
+
+    #[n(3)]
+    pub memo: Option<Memo>,
+}
+
+#[many_module(name = LedgerMultiSendModule, namespace = ledger, many_modules_crate = many_modules)]
+pub trait LedgerMultiSendModuleBackend: Send {
+    fn multi_send(
+        &mut self,
+        sender: &Address,
+        args: MultiSendArgs,
+    ) -> Result<EmptyReturn, ManyError>;
+
+    /// Send several symbols between the same two parties atomically. The event
+    /// log holds one Send event per symbol, all sharing the memo.
+    fn send_symbols(
+        &mut self,
+        sender: &Address,
+        args: SendSymbolsArgs,
+    ) -> Result<EmptyReturn, ManyError>;
+}
+
+impl LedgerMultiSendModuleBackend for LedgerModuleImpl {
+    fn multi_send(
+        &mut self,
+        sender: &Address,
+        args: MultiSendArgs,
+    ) -> Result<EmptyReturn, ManyError> {
+        let MultiSendArgs {
+            from,
+            transfers,
+            memo,
+        } = args;
+
+        let from = from.as_ref().unwrap_or(sender);
+        self.verify_send_sender(sender, from)?;
+        self.limits()?.check_memo(memo.as_ref())?;
+        if transfers.is_empty() || transfers.len() > MAX_TRANSFERS {
+            return Err(error::invalid_transfer_count(
+                transfers.len(),
+                MAX_TRANSFERS,
+            ));
+        }
+
+        let transfers: Vec<_> = transfers
+            .into_iter()
+            .map(|t| (t.to, t.symbol, t.amount))
+            .collect();
+        self.storage.multi_send(from, &transfers, memo)?;
+        Ok(EmptyReturn)
+    }
+
+    fn send_symbols(
+        &mut self,
+        sender: &Address,
+        args: SendSymbolsArgs,
+    ) -> Result<EmptyReturn, ManyError> {
+        let SendSymbolsArgs {
+            from,
+            to,
+            amounts,
+            memo,
+        } = args;
+
+        self.multi_send(
+            sender,
+            MultiSendArgs {
+                from,
+                transfers: amounts
+                    .into_iter()
+                    .map(|(symbol, amount)| MultiSendTransfer { to, symbol, amount })
+                    .collect(),
+                memo,
+            },
+        )
+    }
+}
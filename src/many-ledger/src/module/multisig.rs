@@ -2,7 +2,7 @@ use crate::module::LedgerModuleImpl;
 use many_error::ManyError;
 use many_identity::Address;
 use many_modules::account::features::multisig;
-use many_modules::EmptyReturn;
+use many_modules::{events, EmptyReturn};
 use many_protocol::ResponseMessage;
 use minicbor::bytes::ByteVec;
 
@@ -12,6 +12,11 @@ impl multisig::AccountMultisigModuleBackend for LedgerModuleImpl {
         sender: &Address,
         arg: multisig::SubmitTransactionArgs,
     ) -> Result<multisig::SubmitTransactionReturn, ManyError> {
+        self.limits()?.check_memo(arg.memo.as_ref())?;
+        if let events::AccountMultisigTransaction::Send(send) = arg.transaction.as_ref() {
+            self.limits()?.check_memo(send.memo.as_ref())?;
+        }
+
         let token = self.storage.create_multisig_transaction(sender, arg)?;
         Ok(multisig::SubmitTransactionReturn {
             token: ByteVec::from(token),
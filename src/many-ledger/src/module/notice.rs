@@ -0,0 +1,104 @@
+use crate::error;
+use crate::module::LedgerModuleImpl;
+use crate::storage::notice::Notice;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::EmptyReturn;
+use many_types::Timestamp;
+use minicbor::{Decode, Encode};
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct PublishArgs {
+    #[n(0)]
+    pub title: String,
+
+    #[n(1)]
+    pub body: String,
+
+    /// When the announced operation starts, e.g. an upgrade.
+    #[n(2)]
+    pub starts: Option<Timestamp>,
+
+    /// When the notice is removed. Notices without expiry stay until they are
+    /// retracted.
+    #[n(3)]
+    pub expires: Option<Timestamp>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct PublishReturns {
+    #[n(0)]
+    pub id: u64,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct RetractArgs {
+    #[n(0)]
+    pub id: u64,
+}
+
+#[derive(Clone, Debug, Default, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ListArgs {}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ListReturns {
+    /// The notices that did not expire, oldest first.
+    #[n(0)]
+    pub notices: Vec<Notice>,
+}
+
+/// Operational notices of the network, published by the notice identity.
+#[many_module(name = NoticeModule, namespace = notice, many_modules_crate = many_modules)]
+pub trait NoticeModuleBackend: Send {
+    fn publish(&mut self, sender: &Address, args: PublishArgs)
+        -> Result<PublishReturns, ManyError>;
+    fn retract(&mut self, sender: &Address, args: RetractArgs) -> Result<EmptyReturn, ManyError>;
+    fn list(&self, sender: &Address, args: ListArgs) -> Result<ListReturns, ManyError>;
+}
+
+impl LedgerModuleImpl {
+    fn verify_notice_sender(&self, sender: &Address) -> Result<(), ManyError> {
+        if *sender != self.storage.get_notice_identity()? {
+            return Err(error::unauthorized());
+        }
+        Ok(())
+    }
+}
+
+impl NoticeModuleBackend for LedgerModuleImpl {
+    fn publish(
+        &mut self,
+        sender: &Address,
+        args: PublishArgs,
+    ) -> Result<PublishReturns, ManyError> {
+        self.verify_notice_sender(sender)?;
+        let PublishArgs {
+            title,
+            body,
+            starts,
+            expires,
+        } = args;
+        let id = self
+            .storage
+            .publish_notice(sender, title, body, starts, expires)?;
+        Ok(PublishReturns { id })
+    }
+
+    fn retract(&mut self, sender: &Address, args: RetractArgs) -> Result<EmptyReturn, ManyError> {
+        self.verify_notice_sender(sender)?;
+        self.storage.retract_notice(args.id)?;
+        Ok(EmptyReturn)
+    }
+
+    fn list(&self, _sender: &Address, _args: ListArgs) -> Result<ListReturns, ManyError> {
+        Ok(ListReturns {
+            notices: self.storage.list_notices()?,
+        })
+    }
+}
@@ -0,0 +1,137 @@
+use crate::error;
+use crate::module::LedgerModuleImpl;
+use crate::storage::pending_send::PendingSend;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::EmptyReturn;
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::Memo;
+use minicbor::{Decode, Encode};
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct SendPendingArgs {
+    /// The source of the funds. Defaults to the sender.
+    #[n(0)]
+    pub from: Option<Address>,
+
+    #[n(1)]
+    pub to: Address,
+
+    #[n(2)]
+    pub amount: TokenAmount,
+
+    #[n(3)]
+    pub symbol: Symbol,
+
+    #[n(4)]
+    pub memo: Option<Memo>,
+
+    /// Seconds the recipient has to accept the transfer before it is refunded.
+    #[n(5)]
+    pub timeout_in_secs: Option<u64>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct SendPendingReturns {
+    #[n(0)]
+    pub id: u64,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct AcceptPendingArgs {
+    #[n(0)]
+    pub id: u64,
+
+    /// The recipient of the transfer. Defaults to the sender.
+    #[n(1)]
+    pub to: Option<Address>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct PendingInfoArgs {
+    #[n(0)]
+    pub id: u64,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct PendingInfoReturns {
+    #[n(0)]
+    pub pending: PendingSend,
+}
+
+#[many_module(name = LedgerPendingSendModule, namespace = ledger, many_modules_crate = many_modules)]
+pub trait LedgerPendingSendModuleBackend: Send {
+    fn send_pending(
+        &mut self,
+        sender: &Address,
+        args: SendPendingArgs,
+    ) -> Result<SendPendingReturns, ManyError>;
+    fn accept_pending(
+        &mut self,
+        sender: &Address,
+        args: AcceptPendingArgs,
+    ) -> Result<EmptyReturn, ManyError>;
+    fn pending_info(
+        &self,
+        sender: &Address,
+        args: PendingInfoArgs,
+    ) -> Result<PendingInfoReturns, ManyError>;
+}
+
+impl LedgerPendingSendModuleBackend for LedgerModuleImpl {
+    fn send_pending(
+        &mut self,
+        sender: &Address,
+        args: SendPendingArgs,
+    ) -> Result<SendPendingReturns, ManyError> {
+        let SendPendingArgs {
+            from,
+            to,
+            amount,
+            symbol,
+            memo,
+            timeout_in_secs,
+        } = args;
+
+        let from = from.as_ref().unwrap_or(sender);
+        self.verify_send_sender(sender, from)?;
+        self.limits()?.check_memo(memo.as_ref())?;
+
+        let id = self
+            .storage
+            .send_pending(from, &to, &symbol, amount, memo, timeout_in_secs)?;
+        Ok(SendPendingReturns { id })
+    }
+
+    fn accept_pending(
+        &mut self,
+        sender: &Address,
+        args: AcceptPendingArgs,
+    ) -> Result<EmptyReturn, ManyError> {
+        let to = args.to.as_ref().unwrap_or(sender);
+        // Accepting moves funds into `to`, it takes the same role as a send out of it.
+        self.verify_send_sender(sender, to)?;
+
+        self.storage.accept_pending_send(to, args.id)?;
+        Ok(EmptyReturn)
+    }
+
+    fn pending_info(
+        &self,
+        _sender: &Address,
+        args: PendingInfoArgs,
+    ) -> Result<PendingInfoReturns, ManyError> {
+        Ok(PendingInfoReturns {
+            pending: self
+                .storage
+                .get_pending_send(args.id)?
+                .ok_or_else(|| error::pending_send_not_found(args.id))?,
+        })
+    }
+}
@@ -0,0 +1,92 @@
+use crate::error;
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::VecOrSingle;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Clone, Debug, Default, Encode, Decode)]
+#[cbor(map)]
+pub struct BalanceProofArgs {
+    /// Defaults to the sender.
+    #[n(0)]
+    pub account: Option<Address>,
+
+    /// Defaults to all the symbols of the ledger.
+    #[n(1)]
+    pub symbols: Option<VecOrSingle<Symbol>>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct BalanceProofReturns {
+    #[n(0)]
+    pub balances: BTreeMap<Symbol, TokenAmount>,
+
+    /// A merk proof of the balance keys of the account, present or absent.
+    #[n(1)]
+    pub proof: ByteVec,
+
+    /// The height of the block whose application hash the proof verifies
+    /// against.
+    #[n(2)]
+    pub height: u64,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct CommitmentReturns {
+    #[n(0)]
+    pub height: u64,
+
+    /// The application hash of the state at `height`, which the proofs and the
+    /// unsigned responses of the node verify against.
+    #[n(1)]
+    pub hash: ByteVec,
+}
+
+/// Balances verifiable against the application hash, so they can be read from
+/// untrusted nodes, and the application hash itself.
+#[many_module(name = LedgerProofModule, namespace = ledger, many_modules_crate = many_modules)]
+pub trait LedgerProofModuleBackend: Send {
+    fn balance_proof(
+        &self,
+        sender: &Address,
+        args: BalanceProofArgs,
+    ) -> Result<BalanceProofReturns, ManyError>;
+    fn commitment(&self) -> Result<CommitmentReturns, ManyError>;
+}
+
+impl LedgerProofModuleBackend for LedgerModuleImpl {
+    fn balance_proof(
+        &self,
+        sender: &Address,
+        args: BalanceProofArgs,
+    ) -> Result<BalanceProofReturns, ManyError> {
+        // The balances, the proof and the height must all be those of the last
+        // committed block, which the read views answer while blocks execute.
+        if self.storage.has_uncommitted_changes() {
+            return Err(error::state_not_committed());
+        }
+
+        let identity = args.account.as_ref().unwrap_or(sender);
+        let symbols: BTreeSet<Symbol> = args.symbols.unwrap_or_default().0.into_iter().collect();
+
+        Ok(BalanceProofReturns {
+            balances: self.storage.get_multiple_balances(identity, &symbols)?,
+            proof: self.storage.prove_balances(identity, &symbols)?.into(),
+            height: self.storage.get_height()?,
+        })
+    }
+
+    fn commitment(&self) -> Result<CommitmentReturns, ManyError> {
+        Ok(CommitmentReturns {
+            height: self.storage.get_height()?,
+            hash: self.storage.hash().into(),
+        })
+    }
+}
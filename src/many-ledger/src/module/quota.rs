@@ -0,0 +1,153 @@
+use crate::error;
+use coset::CoseSign1;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::{ManyModule, ManyModuleInfo};
+use many_protocol::{RequestMessage, ResponseMessage};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Number of identities above which the ones with nothing served within the
+/// window are forgotten, so senders cannot grow the quota without bound by
+/// using new identities.
+const MAX_IDENTITIES: usize = 100_000;
+
+/// Largest response served to anonymous senders. They all share the same
+/// address, so they cannot have a quota of their own.
+pub const MAX_ANONYMOUS_BYTES: u64 = 64 * 1024;
+
+/// Bytes served to each identity over a sliding window. This is local to the
+/// node and not part of the state, so every node can use its own quota.
+///
+/// Anonymous senders are not tracked, their responses are limited in size
+/// instead, see [`BandwidthQuota::check_anonymous`].
+#[derive(Debug)]
+pub struct BandwidthQuota {
+    max_bytes: u64,
+    window: Duration,
+    served: Mutex<BTreeMap<Address, VecDeque<(Instant, u64)>>>,
+}
+
+impl BandwidthQuota {
+    pub fn new(max_bytes: u64, window: Duration) -> Self {
+        Self {
+            max_bytes,
+            window,
+            served: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Forget everything served to an identity before the window ending at
+    /// `now`.
+    fn expire(&self, entries: &mut VecDeque<(Instant, u64)>, now: Instant) {
+        while let Some((t, _)) = entries.front() {
+            if now.saturating_duration_since(*t) < self.window {
+                break;
+            }
+            entries.pop_front();
+        }
+    }
+
+    /// Bytes served to `from` within the window ending at `now`.
+    fn used(
+        &self,
+        served: &mut BTreeMap<Address, VecDeque<(Instant, u64)>>,
+        from: &Address,
+        now: Instant,
+    ) -> u64 {
+        let entries = match served.get_mut(from) {
+            Some(entries) => entries,
+            None => return 0,
+        };
+        self.expire(entries, now);
+        if entries.is_empty() {
+            served.remove(from);
+            return 0;
+        }
+        entries.iter().map(|(_, bytes)| bytes).sum()
+    }
+
+    /// Fail if `from` already used its quota.
+    pub fn check(&self, from: &Address, now: Instant) -> Result<(), ManyError> {
+        let mut served = self.served.lock().unwrap();
+        let used = self.used(&mut served, from, now);
+        if used >= self.max_bytes {
+            return Err(error::quota_exceeded(
+                used,
+                self.window.as_secs(),
+                self.max_bytes,
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn record(&self, from: &Address, bytes: u64, now: Instant) {
+        let mut served = self.served.lock().unwrap();
+        if served.len() >= MAX_IDENTITIES && !served.contains_key(from) {
+            served.retain(|_, entries| {
+                self.expire(entries, now);
+                !entries.is_empty()
+            });
+        }
+        served.entry(*from).or_default().push_back((now, bytes));
+    }
+
+    /// Number of identities with bytes served within the window.
+    pub fn identities(&self) -> usize {
+        self.served.lock().unwrap().len()
+    }
+
+    /// Fail if a response of `bytes` is too large for an anonymous sender.
+    pub fn check_anonymous(&self, bytes: u64) -> Result<(), ManyError> {
+        let max = self.max_bytes.min(MAX_ANONYMOUS_BYTES);
+        if bytes > max {
+            return Err(error::anonymous_download_too_large(bytes, max));
+        }
+        Ok(())
+    }
+}
+
+/// Enforces a download quota on a module, e.g. the event listing. The response
+/// which crosses the quota is still served, the next requests are refused.
+/// Large responses to anonymous senders are refused outright.
+pub struct QuotaModule<M: ManyModule> {
+    pub inner: M,
+    pub quota: Arc<BandwidthQuota>,
+}
+
+impl<M: ManyModule> Debug for QuotaModule<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("QuotaModule")
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: ManyModule> ManyModule for QuotaModule<M> {
+    fn info(&self) -> &ManyModuleInfo {
+        self.inner.info()
+    }
+
+    fn validate(&self, message: &RequestMessage, envelope: &CoseSign1) -> Result<(), ManyError> {
+        self.inner.validate(message, envelope)
+    }
+
+    async fn execute(&self, message: RequestMessage) -> Result<ResponseMessage, ManyError> {
+        let from = message.from();
+        if from.is_anonymous() {
+            let response = self.inner.execute(message).await?;
+            if let Ok(data) = &response.data {
+                self.quota.check_anonymous(data.len() as u64)?;
+            }
+            return Ok(response);
+        }
+        self.quota.check(&from, Instant::now())?;
+
+        let response = self.inner.execute(message).await?;
+        if let Ok(data) = &response.data {
+            self.quota.record(&from, data.len() as u64, Instant::now());
+        }
+        Ok(response)
+    }
+}
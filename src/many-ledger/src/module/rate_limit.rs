@@ -0,0 +1,109 @@
+use crate::error;
+use coset::CoseSign1;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::{ManyModule, ManyModuleInfo};
+use many_protocol::{RequestMessage, ResponseMessage};
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Number of buckets above which the full buckets are forgotten, so senders
+/// cannot grow the limiter without bound by using new identities.
+const MAX_BUCKETS: usize = 100_000;
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// A token bucket per identity. This is local to the node and not part of the
+/// state, so every node can use its own limits.
+#[derive(Debug)]
+pub struct RateLimiter {
+    burst: f64,
+    per_second: f64,
+    buckets: Mutex<BTreeMap<Address, Bucket>>,
+}
+
+impl RateLimiter {
+    /// Allow `burst` calls at once, refilled at `per_hour` calls per hour.
+    pub fn new(burst: u32, per_hour: u32) -> Self {
+        Self {
+            burst: f64::from(burst.max(1)),
+            per_second: f64::from(per_hour) / 3600.,
+            buckets: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn refilled(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        (bucket.tokens + elapsed * self.per_second).min(self.burst)
+    }
+
+    /// Take a token from the bucket of `from`, or fail with the number of
+    /// seconds until one is available.
+    pub fn check(&self, from: &Address, now: Instant) -> Result<(), ManyError> {
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_BUCKETS {
+            buckets.retain(|_, bucket| self.refilled(bucket, now) < self.burst);
+        }
+
+        let tokens = buckets
+            .get(from)
+            .map_or(self.burst, |bucket| self.refilled(bucket, now));
+        if tokens < 1. {
+            let retry_after = match self.per_second {
+                r if r > 0. => ((1. - tokens) / r).ceil().to_string(),
+                _ => "never".to_string(),
+            };
+            return Err(error::rate_limited(retry_after));
+        }
+        buckets.insert(
+            *from,
+            Bucket {
+                tokens: tokens - 1.,
+                updated: now,
+            },
+        );
+        Ok(())
+    }
+}
+
+/// Rate limits a method of a module per sender, e.g. the recall phrase lookups
+/// of the IdStore, which could otherwise be brute-forced.
+pub struct RateLimitModule<M: ManyModule> {
+    pub inner: M,
+    pub method: &'static str,
+
+    /// The limiter, or None if the method is not limited.
+    pub limiter: Option<Arc<RateLimiter>>,
+}
+
+impl<M: ManyModule> Debug for RateLimitModule<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("RateLimitModule")
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: ManyModule> ManyModule for RateLimitModule<M> {
+    fn info(&self) -> &ManyModuleInfo {
+        self.inner.info()
+    }
+
+    fn validate(&self, message: &RequestMessage, envelope: &CoseSign1) -> Result<(), ManyError> {
+        self.inner.validate(message, envelope)
+    }
+
+    async fn execute(&self, message: RequestMessage) -> Result<ResponseMessage, ManyError> {
+        if let Some(limiter) = &self.limiter {
+            if message.method == self.method {
+                limiter.check(&message.from(), Instant::now())?;
+            }
+        }
+        self.inner.execute(message).await
+    }
+}
@@ -0,0 +1,154 @@
+use crate::error;
+use crate::module::LedgerModuleImpl;
+use crate::storage::recurring_send::{recurring_send_memo, RecurringSend};
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::EmptyReturn;
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::{Memo, Timestamp};
+use minicbor::{Decode, Encode};
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct SubscribeArgs {
+    /// The source of the funds. Defaults to the sender.
+    #[n(0)]
+    pub from: Option<Address>,
+
+    #[n(1)]
+    pub to: Address,
+
+    #[n(2)]
+    pub amount: TokenAmount,
+
+    #[n(3)]
+    pub symbol: Symbol,
+
+    #[n(4)]
+    pub memo: Option<Memo>,
+
+    #[n(5)]
+    pub interval_in_secs: u64,
+
+    /// When the first transfer executes. Defaults to the current block.
+    #[n(6)]
+    pub start: Option<Timestamp>,
+
+    /// No transfer executes after this time. Runs until cancelled if omitted.
+    #[n(7)]
+    pub end: Option<Timestamp>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct SubscribeReturns {
+    #[n(0)]
+    pub id: u64,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct UnsubscribeArgs {
+    #[n(0)]
+    pub id: u64,
+}
+
+#[derive(Clone, Debug, Default, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ListSubscriptionsArgs {
+    /// The source of the transfers. Defaults to the sender.
+    #[n(0)]
+    pub from: Option<Address>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ListSubscriptionsReturns {
+    #[n(0)]
+    pub subscriptions: Vec<RecurringSend>,
+}
+
+/// Recurring transfers, executed every interval until their end.
+#[many_module(name = LedgerRecurringSendModule, namespace = ledger, many_modules_crate = many_modules)]
+pub trait LedgerRecurringSendModuleBackend: Send {
+    fn subscribe(
+        &mut self,
+        sender: &Address,
+        args: SubscribeArgs,
+    ) -> Result<SubscribeReturns, ManyError>;
+    fn unsubscribe(
+        &mut self,
+        sender: &Address,
+        args: UnsubscribeArgs,
+    ) -> Result<EmptyReturn, ManyError>;
+    fn list_subscriptions(
+        &self,
+        sender: &Address,
+        args: ListSubscriptionsArgs,
+    ) -> Result<ListSubscriptionsReturns, ManyError>;
+}
+
+impl LedgerRecurringSendModuleBackend for LedgerModuleImpl {
+    fn subscribe(
+        &mut self,
+        sender: &Address,
+        args: SubscribeArgs,
+    ) -> Result<SubscribeReturns, ManyError> {
+        let SubscribeArgs {
+            from,
+            to,
+            amount,
+            symbol,
+            memo,
+            interval_in_secs,
+            start,
+            end,
+        } = args;
+
+        let from = from.as_ref().unwrap_or(sender);
+        self.verify_send_sender(sender, from)?;
+        // The marker counts towards the memo limits.
+        let memo = recurring_send_memo(memo)?;
+        self.limits()?.check_memo(Some(&memo))?;
+
+        let id = self.storage.subscribe(
+            from,
+            &to,
+            &symbol,
+            amount,
+            memo,
+            interval_in_secs,
+            start,
+            end,
+        )?;
+        Ok(SubscribeReturns { id })
+    }
+
+    fn unsubscribe(
+        &mut self,
+        sender: &Address,
+        args: UnsubscribeArgs,
+    ) -> Result<EmptyReturn, ManyError> {
+        let order = self
+            .storage
+            .get_recurring_send(args.id)?
+            .ok_or_else(|| error::recurring_send_not_found(args.id))?;
+        // Cancelling takes the same role as sending out of the source.
+        self.verify_send_sender(sender, &order.from)?;
+
+        self.storage.unsubscribe(args.id)?;
+        Ok(EmptyReturn)
+    }
+
+    fn list_subscriptions(
+        &self,
+        sender: &Address,
+        args: ListSubscriptionsArgs,
+    ) -> Result<ListSubscriptionsReturns, ManyError> {
+        let from = args.from.as_ref().unwrap_or(sender);
+        Ok(ListSubscriptionsReturns {
+            subscriptions: self.storage.list_recurring_sends(from)?,
+        })
+    }
+}
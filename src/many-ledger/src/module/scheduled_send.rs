@@ -0,0 +1,139 @@
+use crate::error;
+use crate::module::LedgerModuleImpl;
+use crate::storage::scheduled_send::{scheduled_send_memo, ScheduledSend};
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::EmptyReturn;
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::{Memo, Timestamp};
+use minicbor::{Decode, Encode};
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct SendScheduledArgs {
+    /// The source of the funds. Defaults to the sender.
+    #[n(0)]
+    pub from: Option<Address>,
+
+    #[n(1)]
+    pub to: Address,
+
+    #[n(2)]
+    pub amount: TokenAmount,
+
+    #[n(3)]
+    pub symbol: Symbol,
+
+    #[n(4)]
+    pub memo: Option<Memo>,
+
+    /// When to execute the transfer. It executes on the first block at or
+    /// after this time.
+    #[n(5)]
+    pub execute_at: Timestamp,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct SendScheduledReturns {
+    #[n(0)]
+    pub id: u64,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct CancelScheduledArgs {
+    #[n(0)]
+    pub id: u64,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ScheduledInfoArgs {
+    #[n(0)]
+    pub id: u64,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ScheduledInfoReturns {
+    #[n(0)]
+    pub scheduled: ScheduledSend,
+}
+
+#[many_module(name = LedgerScheduledSendModule, namespace = ledger, many_modules_crate = many_modules)]
+pub trait LedgerScheduledSendModuleBackend: Send {
+    fn send_scheduled(
+        &mut self,
+        sender: &Address,
+        args: SendScheduledArgs,
+    ) -> Result<SendScheduledReturns, ManyError>;
+    fn cancel_scheduled(
+        &mut self,
+        sender: &Address,
+        args: CancelScheduledArgs,
+    ) -> Result<EmptyReturn, ManyError>;
+    fn scheduled_info(
+        &self,
+        sender: &Address,
+        args: ScheduledInfoArgs,
+    ) -> Result<ScheduledInfoReturns, ManyError>;
+}
+
+impl LedgerScheduledSendModuleBackend for LedgerModuleImpl {
+    fn send_scheduled(
+        &mut self,
+        sender: &Address,
+        args: SendScheduledArgs,
+    ) -> Result<SendScheduledReturns, ManyError> {
+        let SendScheduledArgs {
+            from,
+            to,
+            amount,
+            symbol,
+            memo,
+            execute_at,
+        } = args;
+
+        let from = from.as_ref().unwrap_or(sender);
+        self.verify_send_sender(sender, from)?;
+        // The marker counts towards the memo limits.
+        let memo = scheduled_send_memo(memo)?;
+        self.limits()?.check_memo(Some(&memo))?;
+
+        let id = self
+            .storage
+            .send_scheduled(from, &to, &symbol, amount, memo, execute_at)?;
+        Ok(SendScheduledReturns { id })
+    }
+
+    fn cancel_scheduled(
+        &mut self,
+        sender: &Address,
+        args: CancelScheduledArgs,
+    ) -> Result<EmptyReturn, ManyError> {
+        let scheduled = self
+            .storage
+            .get_scheduled_send(args.id)?
+            .ok_or_else(|| error::scheduled_send_not_found(args.id))?;
+        // Cancelling takes the same role as sending out of the source.
+        self.verify_send_sender(sender, &scheduled.from)?;
+
+        self.storage.cancel_scheduled_send(args.id)?;
+        Ok(EmptyReturn)
+    }
+
+    fn scheduled_info(
+        &self,
+        _sender: &Address,
+        args: ScheduledInfoArgs,
+    ) -> Result<ScheduledInfoReturns, ManyError> {
+        Ok(ScheduledInfoReturns {
+            scheduled: self
+                .storage
+                .get_scheduled_send(args.id)?
+                .ok_or_else(|| error::scheduled_send_not_found(args.id))?,
+        })
+    }
+}
@@ -0,0 +1,109 @@
+//! Per-identity sequence numbers.
+//!
+//! Every command executed successfully increments the sequence number of its
+//! sender, in the state. Clients read it with `ledger.sequence` to order their
+//! commands strictly, and to find out whether a command they retry was already
+//! executed.
+//!
+//! Commands are only counted once the [`SEQUENCE_MIGRATION`] is active.
+use crate::migration::sequence::SEQUENCE_MIGRATION;
+use crate::module::LedgerModuleImpl;
+use async_trait::async_trait;
+use coset::CoseSign1;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_protocol::{RequestMessage, ResponseMessage};
+use many_server::transport::LowLevelManyRequestHandler;
+use minicbor::{Decode, Encode};
+use std::collections::BTreeSet;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct SequenceArgs {
+    /// Defaults to the sender.
+    #[n(0)]
+    pub address: Option<Address>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct SequenceReturns {
+    /// The number of commands the address executed successfully.
+    #[n(0)]
+    pub sequence: u64,
+}
+
+#[many_module(name = LedgerSequenceModule, namespace = ledger, many_modules_crate = many_modules)]
+pub trait LedgerSequenceModuleBackend: Send {
+    fn sequence(&self, sender: &Address, args: SequenceArgs) -> Result<SequenceReturns, ManyError>;
+}
+
+impl LedgerSequenceModuleBackend for LedgerModuleImpl {
+    fn sequence(&self, sender: &Address, args: SequenceArgs) -> Result<SequenceReturns, ManyError> {
+        let address = args.address.as_ref().unwrap_or(sender);
+        Ok(SequenceReturns {
+            sequence: self.storage.get_sequence(address)?,
+        })
+    }
+}
+
+impl LedgerModuleImpl {
+    /// Count a command executed successfully by `sender`. Anonymous commands,
+    /// and commands executed before the migration is active, are not counted.
+    pub fn command_executed(&mut self, sender: &Address) -> Result<u64, ManyError> {
+        if sender.is_anonymous() || !self.storage.migrations().is_active(&SEQUENCE_MIGRATION) {
+            return Ok(0);
+        }
+        self.storage.increment_sequence(sender)
+    }
+}
+
+/// Increments the sequence number of the sender of every command executed
+/// successfully.
+pub struct SequenceHandler<H> {
+    pub inner: H,
+    pub module_impl: Arc<Mutex<LedgerModuleImpl>>,
+    pub commands: BTreeSet<String>,
+}
+
+impl<H> Debug for SequenceHandler<H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SequenceHandler")
+    }
+}
+
+#[async_trait]
+impl<H: LowLevelManyRequestHandler> LowLevelManyRequestHandler for SequenceHandler<H> {
+    async fn execute(&self, envelope: CoseSign1) -> Result<CoseSign1, String> {
+        let request = envelope
+            .payload
+            .as_deref()
+            .and_then(|payload| RequestMessage::from_bytes(payload).ok())
+            .filter(|message| self.commands.contains(&message.method));
+        let request = match request {
+            Some(request) => request,
+            None => return self.inner.execute(envelope).await,
+        };
+
+        let result = self.inner.execute(envelope).await?;
+        let executed = result
+            .payload
+            .as_deref()
+            .and_then(|payload| ResponseMessage::from_bytes(payload).ok())
+            .map_or(false, |response| response.data.is_ok());
+        if executed {
+            let mut module_impl = self.module_impl.lock().unwrap();
+            if let Err(e) = module_impl.command_executed(&request.from()) {
+                warn!(
+                    "Could not increment the sequence of {}: {e}",
+                    request.from()
+                );
+            }
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,90 @@
+use crate::module::LedgerModuleImpl;
+use crate::storage::fees::transfer_fee_memo;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::events::EventInfo;
+use many_modules::ledger;
+use many_types::ledger::{Symbol, TokenAmount};
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct SimulateArgs {
+    /// The endpoint of the command to simulate, e.g. `ledger.send`.
+    #[n(0)]
+    pub method: String,
+
+    /// The CBOR encoded arguments of the command.
+    #[n(1)]
+    pub data: ByteVec,
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct SimulateReturns {
+    /// The events the command would log.
+    #[n(0)]
+    pub events: Vec<EventInfo>,
+
+    /// The balances of the accounts touched by the command, as they would be
+    /// after its execution.
+    #[n(1)]
+    pub balances: BTreeMap<Address, BTreeMap<Symbol, TokenAmount>>,
+}
+
+#[many_module(name = LedgerSimulateModule, namespace = ledger, many_modules_crate = many_modules)]
+pub trait LedgerSimulateModuleBackend: Send {
+    fn simulate(&self, sender: &Address, args: SimulateArgs) -> Result<SimulateReturns, ManyError>;
+}
+
+impl LedgerSimulateModuleBackend for LedgerModuleImpl {
+    fn simulate(&self, sender: &Address, args: SimulateArgs) -> Result<SimulateReturns, ManyError> {
+        let SimulateArgs { method, data } = args;
+
+        match method.as_str() {
+            "ledger.send" => {
+                let ledger::SendArgs {
+                    from,
+                    to,
+                    symbol,
+                    amount,
+                    memo,
+                } = minicbor::decode(&data).map_err(ManyError::deserialization_error)?;
+
+                let from = from.as_ref().unwrap_or(sender);
+                self.verify_send_sender(sender, from)?;
+                let outcome = self.storage.prepare_send(from, &to, &symbol, &amount)?;
+
+                let mut events = vec![EventInfo::Send {
+                    from: *from,
+                    to,
+                    symbol,
+                    amount,
+                    memo,
+                }];
+                if let Some((collector, fee)) = outcome.fee {
+                    events.push(EventInfo::Send {
+                        from: *from,
+                        to: collector,
+                        symbol,
+                        amount: fee,
+                        memo: Some(transfer_fee_memo()?),
+                    });
+                }
+
+                Ok(SimulateReturns {
+                    events,
+                    balances: outcome
+                        .balances
+                        .into_iter()
+                        .map(|(id, balance)| (id, BTreeMap::from([(symbol, balance)])))
+                        .collect(),
+                })
+            }
+            _ => Err(ManyError::invalid_method_name(method)),
+        }
+    }
+}
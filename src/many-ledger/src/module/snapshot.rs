@@ -0,0 +1,210 @@
+use crate::module::LedgerModuleImpl;
+use crate::storage::diff::StateDiff;
+use crate::storage::snapshot::{SnapshotInfo, SNAPSHOT_FORMAT};
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::{EmptyArg, EmptyReturn};
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ListSnapshotsReturns {
+    #[n(0)]
+    pub snapshots: Vec<SnapshotInfo>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct OfferSnapshotArgs {
+    #[n(0)]
+    pub snapshot: SnapshotInfo,
+
+    /// The trusted application hash at the snapshot height.
+    #[n(1)]
+    pub app_hash: ByteVec,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct LoadSnapshotChunkArgs {
+    #[n(0)]
+    pub height: u64,
+
+    #[n(1)]
+    pub format: u32,
+
+    #[n(2)]
+    pub chunk: u32,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct LoadSnapshotChunkReturns {
+    #[n(0)]
+    pub chunk: ByteVec,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ApplySnapshotChunkArgs {
+    #[n(0)]
+    pub index: u32,
+
+    #[n(1)]
+    pub chunk: ByteVec,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct SnapshotInfoReturns {
+    /// The most recent snapshot, if snapshots are enabled on this node.
+    #[n(0)]
+    pub latest: Option<SnapshotInfo>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct SnapshotChunkArgs {
+    #[n(0)]
+    pub height: u64,
+
+    #[n(1)]
+    pub chunk: u32,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct SnapshotChunkReturns {
+    #[n(0)]
+    pub chunk: ByteVec,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct StateDiffArgs {
+    #[n(0)]
+    pub height: u64,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct StateDiffReturns {
+    #[n(0)]
+    pub diff: StateDiff,
+}
+
+#[many_module(name = LedgerSnapshotModule, namespace = ledger, many_modules_crate = many_modules)]
+pub trait LedgerSnapshotModuleBackend: Send {
+    fn snapshot_info(
+        &self,
+        sender: &Address,
+        args: EmptyArg,
+    ) -> Result<SnapshotInfoReturns, ManyError>;
+
+    /// A chunk of a snapshot, e.g. for a read replica following this node.
+    fn snapshot_chunk(
+        &self,
+        sender: &Address,
+        args: SnapshotChunkArgs,
+    ) -> Result<SnapshotChunkReturns, ManyError>;
+
+    /// The state diff of a height, e.g. for a node catching up from this node.
+    fn state_diff(
+        &self,
+        sender: &Address,
+        args: StateDiffArgs,
+    ) -> Result<StateDiffReturns, ManyError>;
+}
+
+impl LedgerSnapshotModuleBackend for LedgerModuleImpl {
+    fn snapshot_info(
+        &self,
+        _sender: &Address,
+        _args: EmptyArg,
+    ) -> Result<SnapshotInfoReturns, ManyError> {
+        Ok(SnapshotInfoReturns {
+            latest: self.storage.latest_snapshot()?,
+        })
+    }
+
+    fn snapshot_chunk(
+        &self,
+        _sender: &Address,
+        args: SnapshotChunkArgs,
+    ) -> Result<SnapshotChunkReturns, ManyError> {
+        Ok(SnapshotChunkReturns {
+            chunk: self
+                .storage
+                .load_snapshot_chunk(args.height, SNAPSHOT_FORMAT, args.chunk)?
+                .into(),
+        })
+    }
+
+    fn state_diff(
+        &self,
+        _sender: &Address,
+        args: StateDiffArgs,
+    ) -> Result<StateDiffReturns, ManyError> {
+        Ok(StateDiffReturns {
+            diff: self.storage.get_state_diff(args.height)?,
+        })
+    }
+}
+
+/// The state sync calls of the ABCI bridge. Like the rest of the `abci`
+/// namespace, this module is only added when the ledger runs behind the bridge.
+#[many_module(name = AbciSnapshotModule, namespace = abci, many_modules_crate = many_modules)]
+pub trait AbciSnapshotModuleBackend: Send {
+    fn list_snapshots(&self) -> Result<ListSnapshotsReturns, ManyError>;
+    fn load_snapshot_chunk(
+        &self,
+        args: LoadSnapshotChunkArgs,
+    ) -> Result<LoadSnapshotChunkReturns, ManyError>;
+    fn offer_snapshot(&mut self, args: OfferSnapshotArgs) -> Result<EmptyReturn, ManyError>;
+    fn apply_snapshot_chunk(
+        &mut self,
+        args: ApplySnapshotChunkArgs,
+    ) -> Result<EmptyReturn, ManyError>;
+}
+
+impl AbciSnapshotModuleBackend for LedgerModuleImpl {
+    fn list_snapshots(&self) -> Result<ListSnapshotsReturns, ManyError> {
+        Ok(ListSnapshotsReturns {
+            snapshots: self.storage.list_snapshots()?,
+        })
+    }
+
+    fn load_snapshot_chunk(
+        &self,
+        args: LoadSnapshotChunkArgs,
+    ) -> Result<LoadSnapshotChunkReturns, ManyError> {
+        let LoadSnapshotChunkArgs {
+            height,
+            format,
+            chunk,
+        } = args;
+        Ok(LoadSnapshotChunkReturns {
+            chunk: self
+                .storage
+                .load_snapshot_chunk(height, format, chunk)?
+                .into(),
+        })
+    }
+
+    fn offer_snapshot(&mut self, args: OfferSnapshotArgs) -> Result<EmptyReturn, ManyError> {
+        self.storage
+            .offer_snapshot(args.snapshot, args.app_hash.as_slice())?;
+        Ok(EmptyReturn)
+    }
+
+    fn apply_snapshot_chunk(
+        &mut self,
+        args: ApplySnapshotChunkArgs,
+    ) -> Result<EmptyReturn, ManyError> {
+        self.storage
+            .apply_snapshot_chunk(args.index, args.chunk.as_slice())?;
+        Ok(EmptyReturn)
+    }
+}
@@ -0,0 +1,126 @@
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::account::features::{FeatureInfo, FeatureSet};
+use many_modules::{account, EmptyReturn};
+use many_types::Either;
+use minicbor::{Decode, Encode};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct CreateSubAccountArgs {
+    #[n(0)]
+    pub name: String,
+
+    /// The features of the sub-account. Defaults to the ledger feature.
+    #[n(1)]
+    pub features: Option<FeatureSet>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct CreateSubAccountReturns {
+    #[n(0)]
+    pub id: Address,
+}
+
+#[derive(Clone, Debug, Default, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ListSubAccountsArgs {
+    /// The parent of the sub-accounts. Defaults to the sender.
+    #[n(0)]
+    pub parent: Option<Address>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct SubAccountInfo {
+    #[n(0)]
+    pub id: Address,
+
+    #[n(1)]
+    pub disabled: bool,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ListSubAccountsReturns {
+    #[n(0)]
+    pub accounts: BTreeMap<String, SubAccountInfo>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct DisableSubAccountArgs {
+    #[n(0)]
+    pub name: String,
+}
+
+#[many_module(name = AccountSubAccountsModule, namespace = account, many_modules_crate = many_modules)]
+pub trait AccountSubAccountsModuleBackend: Send {
+    fn create_sub_account(
+        &mut self,
+        sender: &Address,
+        args: CreateSubAccountArgs,
+    ) -> Result<CreateSubAccountReturns, ManyError>;
+    fn list_sub_accounts(
+        &self,
+        sender: &Address,
+        args: ListSubAccountsArgs,
+    ) -> Result<ListSubAccountsReturns, ManyError>;
+    fn disable_sub_account(
+        &mut self,
+        sender: &Address,
+        args: DisableSubAccountArgs,
+    ) -> Result<EmptyReturn, ManyError>;
+}
+
+impl AccountSubAccountsModuleBackend for LedgerModuleImpl {
+    fn create_sub_account(
+        &mut self,
+        sender: &Address,
+        args: CreateSubAccountArgs,
+    ) -> Result<CreateSubAccountReturns, ManyError> {
+        let features = args.features.unwrap_or_else(|| {
+            FeatureSet::from_iter([account::features::ledger::AccountLedger.as_feature()])
+        });
+        if features.is_empty() {
+            return Err(account::errors::empty_feature());
+        }
+
+        let id = self
+            .storage
+            .create_sub_account(sender, args.name, features)?;
+        Ok(CreateSubAccountReturns { id })
+    }
+
+    fn list_sub_accounts(
+        &self,
+        sender: &Address,
+        args: ListSubAccountsArgs,
+    ) -> Result<ListSubAccountsReturns, ManyError> {
+        let parent = args.parent.unwrap_or(*sender);
+        let mut accounts = BTreeMap::new();
+        for (name, id) in self.storage.get_sub_accounts(&parent)?.accounts {
+            let disabled = self
+                .storage
+                .get_account_even_disabled(&id)?
+                .map_or(true, |account| {
+                    account.disabled.is_some() && account.disabled != Some(Either::Left(false))
+                });
+            accounts.insert(name, SubAccountInfo { id, disabled });
+        }
+        Ok(ListSubAccountsReturns { accounts })
+    }
+
+    fn disable_sub_account(
+        &mut self,
+        sender: &Address,
+        args: DisableSubAccountArgs,
+    ) -> Result<EmptyReturn, ManyError> {
+        self.storage.disable_sub_account(sender, &args.name)?;
+        Ok(EmptyReturn)
+    }
+}
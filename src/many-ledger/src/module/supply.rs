@@ -0,0 +1,101 @@
+use crate::error;
+use crate::module::LedgerModuleImpl;
+use crate::storage::holders::Holder;
+use crate::storage::supply::SymbolSupply;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_types::ledger::Symbol;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Clone, Debug, Default, Encode, Decode)]
+#[cbor(map)]
+pub struct SupplyArgs {
+    /// Only return the supply of these symbols. All symbols are returned if empty.
+    #[n(0)]
+    pub symbols: Option<BTreeSet<Symbol>>,
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct SupplyReturns {
+    #[n(0)]
+    pub supply: BTreeMap<Symbol, SymbolSupply>,
+}
+
+/// Default number of holders returned by `ledger.holders`.
+pub const DEFAULT_HOLDERS_COUNT: u64 = 20;
+
+/// Maximum number of holders returned by `ledger.holders`.
+pub const MAXIMUM_HOLDERS_COUNT: u64 = 1000;
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct HoldersArgs {
+    #[n(0)]
+    pub symbol: Symbol,
+
+    /// Number of holders to return, capped to [`MAXIMUM_HOLDERS_COUNT`].
+    #[n(1)]
+    pub count: Option<u64>,
+
+    /// The cursor returned with the previous page. The first page is returned
+    /// if omitted.
+    #[n(2)]
+    pub cursor: Option<ByteVec>,
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct HoldersReturns {
+    /// The holders, largest balance first.
+    #[n(0)]
+    pub holders: Vec<Holder>,
+
+    /// An opaque cursor to pass back to get the next page, or None if this page
+    /// is the last one.
+    #[n(1)]
+    pub cursor: Option<ByteVec>,
+}
+
+#[many_module(name = LedgerSupplyModule, namespace = ledger, many_modules_crate = many_modules)]
+pub trait LedgerSupplyModuleBackend: Send {
+    fn supply(&self, sender: &Address, args: SupplyArgs) -> Result<SupplyReturns, ManyError>;
+    fn holders(&self, sender: &Address, args: HoldersArgs) -> Result<HoldersReturns, ManyError>;
+}
+
+impl LedgerSupplyModuleBackend for LedgerModuleImpl {
+    fn supply(&self, _sender: &Address, args: SupplyArgs) -> Result<SupplyReturns, ManyError> {
+        let mut symbols = self.storage.get_symbols()?;
+        if let Some(requested) = args.symbols.filter(|s| !s.is_empty()) {
+            symbols.retain(|symbol| requested.contains(symbol));
+        }
+
+        let supply = symbols
+            .into_iter()
+            .map(|symbol| Ok((symbol, self.storage.get_supply(&symbol)?)))
+            .collect::<Result<_, ManyError>>()?;
+        Ok(SupplyReturns { supply })
+    }
+
+    fn holders(&self, _sender: &Address, args: HoldersArgs) -> Result<HoldersReturns, ManyError> {
+        if !self.storage.get_symbols()?.contains(&args.symbol) {
+            return Err(error::unknown_symbol(args.symbol));
+        }
+        let count = args
+            .count
+            .unwrap_or(DEFAULT_HOLDERS_COUNT)
+            .min(MAXIMUM_HOLDERS_COUNT) as usize;
+        let (holders, cursor) = self.storage.get_holders(
+            &args.symbol,
+            count,
+            args.cursor.as_ref().map(|c| c.as_slice()),
+        )?;
+        Ok(HoldersReturns {
+            holders,
+            cursor: cursor.map(ByteVec::from),
+        })
+    }
+}
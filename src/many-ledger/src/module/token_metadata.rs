@@ -0,0 +1,54 @@
+use crate::module::LedgerModuleImpl;
+use crate::storage::token_metadata::TokenMetadata;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_types::ledger::Symbol;
+use minicbor::{Decode, Encode};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Clone, Debug, Default, Encode, Decode)]
+#[cbor(map)]
+pub struct TokenMetadataArgs {
+    /// Only return the metadata of these symbols. All symbols are returned if empty.
+    #[n(0)]
+    pub symbols: Option<BTreeSet<Symbol>>,
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct TokenMetadataReturns {
+    /// Symbols without metadata are absent.
+    #[n(0)]
+    pub metadata: BTreeMap<Symbol, TokenMetadata>,
+}
+
+#[many_module(name = LedgerTokenMetadataModule, namespace = ledger, many_modules_crate = many_modules)]
+pub trait LedgerTokenMetadataModuleBackend: Send {
+    fn token_info(
+        &self,
+        sender: &Address,
+        args: TokenMetadataArgs,
+    ) -> Result<TokenMetadataReturns, ManyError>;
+}
+
+impl LedgerTokenMetadataModuleBackend for LedgerModuleImpl {
+    fn token_info(
+        &self,
+        _sender: &Address,
+        args: TokenMetadataArgs,
+    ) -> Result<TokenMetadataReturns, ManyError> {
+        let mut symbols = self.storage.get_symbols()?;
+        if let Some(requested) = args.symbols.filter(|s| !s.is_empty()) {
+            symbols.retain(|symbol| requested.contains(symbol));
+        }
+
+        let mut metadata = BTreeMap::new();
+        for symbol in symbols {
+            if let Some(meta) = self.storage.get_token_metadata(&symbol)? {
+                metadata.insert(symbol, meta);
+            }
+        }
+        Ok(TokenMetadataReturns { metadata })
+    }
+}
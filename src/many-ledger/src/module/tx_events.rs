@@ -0,0 +1,28 @@
+//! The events logged by the transaction just delivered, returned to the ABCI
+//! bridge which turns the transfers, mints and burns into Tendermint events.
+use crate::module::LedgerModuleImpl;
+use many_error::ManyError;
+use many_macros::many_module;
+use many_modules::events::EventInfo;
+use minicbor::{Decode, Encode};
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+pub struct TxEventsReturns {
+    #[n(0)]
+    pub events: Vec<EventInfo>,
+}
+
+/// Only added behind the bridge.
+#[many_module(name = AbciTxEventsModule, namespace = abci, many_modules_crate = many_modules)]
+pub trait AbciTxEventsModuleBackend: Send {
+    fn tx_events(&mut self) -> Result<TxEventsReturns, ManyError>;
+}
+
+impl AbciTxEventsModuleBackend for LedgerModuleImpl {
+    fn tx_events(&mut self) -> Result<TxEventsReturns, ManyError> {
+        Ok(TxEventsReturns {
+            events: self.storage.take_tx_events(),
+        })
+    }
+}
@@ -0,0 +1,86 @@
+use crate::error;
+use crate::module::LedgerModuleImpl;
+use crate::storage::validators::Validator;
+use many_error::ManyError;
+use many_identity::Address;
+use many_macros::many_module;
+use many_modules::EmptyReturn;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct UpdateArgs {
+    /// The Ed25519 public key of the validator.
+    #[n(0)]
+    pub public_key: ByteVec,
+
+    /// The new voting power. A power of 0 removes the validator.
+    #[n(1)]
+    pub power: u64,
+}
+
+#[derive(Clone, Debug, Default, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ListArgs {}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ListReturns {
+    #[n(0)]
+    pub validators: Vec<Validator>,
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ValidatorUpdatesReturns {
+    #[n(0)]
+    pub updates: Vec<Validator>,
+}
+
+/// Validator set management. The validator identity can be a multisig account,
+/// so changes go through an on-chain vote of its owners.
+#[many_module(name = ValidatorsModule, namespace = validators, many_modules_crate = many_modules)]
+pub trait ValidatorsModuleBackend: Send {
+    fn update(&mut self, sender: &Address, args: UpdateArgs) -> Result<EmptyReturn, ManyError>;
+    fn list(&self, sender: &Address, args: ListArgs) -> Result<ListReturns, ManyError>;
+}
+
+/// The validator updates returned to Tendermint from `EndBlock`. Only added
+/// behind the bridge.
+#[many_module(name = AbciValidatorsModule, namespace = abci, many_modules_crate = many_modules)]
+pub trait AbciValidatorsModuleBackend: Send {
+    fn validator_updates(&mut self) -> Result<ValidatorUpdatesReturns, ManyError>;
+}
+
+impl LedgerModuleImpl {
+    fn verify_validator_sender(&self, sender: &Address) -> Result<(), ManyError> {
+        if *sender != self.storage.get_validator_identity()? {
+            return Err(error::unauthorized());
+        }
+        Ok(())
+    }
+}
+
+impl ValidatorsModuleBackend for LedgerModuleImpl {
+    fn update(&mut self, sender: &Address, args: UpdateArgs) -> Result<EmptyReturn, ManyError> {
+        self.verify_validator_sender(sender)?;
+        self.storage
+            .update_validator(&args.public_key, args.power)?;
+        Ok(EmptyReturn)
+    }
+
+    fn list(&self, _sender: &Address, _args: ListArgs) -> Result<ListReturns, ManyError> {
+        Ok(ListReturns {
+            validators: self.storage.list_validators()?,
+        })
+    }
+}
+
+impl AbciValidatorsModuleBackend for LedgerModuleImpl {
+    fn validator_updates(&mut self) -> Result<ValidatorUpdatesReturns, ManyError> {
+        Ok(ValidatorUpdatesReturns {
+            updates: self.storage.take_validator_updates()?,
+        })
+    }
+}
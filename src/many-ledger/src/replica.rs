@@ -0,0 +1,299 @@
+//! Read replicas.
+//!
+//! A replica has no consensus role. It follows a primary node by restoring every
+//! new snapshot the primary publishes (see [`crate::storage::snapshot`]) and
+//! serves queries from the restored state. Commands are refused, and so are
+//! queries once the state is older than the staleness bound, so clients never
+//! silently read outdated data.
+use crate::error;
+use crate::module::snapshot::{SnapshotChunkArgs, SnapshotChunkReturns, SnapshotInfoReturns};
+use crate::module::LedgerModuleImpl;
+use crate::storage::scheduler::secs_since_epoch;
+use crate::storage::snapshot::{restore_snapshot, SnapshotInfo};
+use async_trait::async_trait;
+use coset::{CborSerializable, CoseSign1};
+use many_error::ManyError;
+use many_identity::verifiers::AnonymousVerifier;
+use many_identity::{Address, AnonymousIdentity, Identity};
+use many_identity_dsa::{CoseKeyIdentity, CoseKeyVerifier};
+use many_modules::EmptyArg;
+use many_protocol::{
+    decode_response_from_cose_sign1, encode_cose_sign1_from_request,
+    encode_cose_sign1_from_response, ManyUrl, RequestMessage, RequestMessageBuilder,
+    ResponseMessage,
+};
+use many_server::transport::LowLevelManyRequestHandler;
+use many_types::Timestamp;
+use minicbor::{Decode, Encode};
+use std::collections::BTreeSet;
+use std::fmt::{Debug, Formatter};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{info, warn};
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Send a request to an HTTP server over plain HTTP, e.g. a MANY server or a
+/// Tendermint RPC server, and return the body of the response.
+pub(crate) fn http_request(method: &str, url: &ManyUrl, body: &[u8]) -> std::io::Result<Vec<u8>> {
+    let invalid = |msg: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, msg);
+    if url.scheme() != "http" {
+        return Err(invalid("only http:// URLs are supported"));
+    }
+    let host = url.host_str().ok_or_else(|| invalid("missing host"))?;
+    let port = url.port_or_known_default().unwrap_or(80);
+
+    let mut stream = TcpStream::connect((host, port))?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let path = match url.query() {
+        Some(query) => format!("{}?{query}", url.path()),
+        None => url.path().to_string(),
+    };
+    write!(
+        stream,
+        "{method} {path} HTTP/1.1\r\nHost: {host}\r\nContent-Type: application/cbor\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(body)?;
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response)?;
+    let end_of_head = response
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or_else(|| invalid("invalid HTTP response"))?;
+    let status = String::from_utf8_lossy(&response[..end_of_head]);
+    if status.split_whitespace().nth(1) != Some("200") {
+        return Err(invalid(&format!(
+            "unexpected HTTP status: {}",
+            status.lines().next().unwrap_or_default()
+        )));
+    }
+    Ok(response.split_off(end_of_head + 4))
+}
+
+/// Call `method` on the MANY server at `url` anonymously. When `peer` is given,
+/// the response must be signed by it. Transport errors are mapped with
+/// `transport_error`.
+pub(crate) fn call<T: for<'a> Decode<'a, ()>>(
+    url: &ManyUrl,
+    peer: Option<&Address>,
+    method: &str,
+    data: impl Encode<()>,
+    transport_error: impl FnOnce(std::io::Error) -> ManyError,
+) -> Result<T, ManyError> {
+    let message = RequestMessageBuilder::default()
+        .from(Address::anonymous())
+        .method(method.to_string())
+        .data(minicbor::to_vec(data).map_err(ManyError::serialization_error)?)
+        .build()
+        .map_err(ManyError::unknown)?;
+    let envelope = encode_cose_sign1_from_request(message, &AnonymousIdentity)?
+        .to_vec()
+        .map_err(ManyError::serialization_error)?;
+
+    let response = http_request("POST", url, &envelope).map_err(transport_error)?;
+    let envelope = CoseSign1::from_slice(&response).map_err(ManyError::deserialization_error)?;
+    let response = match peer {
+        Some(peer) => {
+            let response = decode_response_from_cose_sign1(&envelope, None, &CoseKeyVerifier)
+                .map_err(ManyError::unknown)?;
+            if &response.from != peer {
+                return Err(ManyError::invalid_from_identity());
+            }
+            response
+        }
+        None => {
+            decode_response_from_cose_sign1(&envelope, None, &(AnonymousVerifier, CoseKeyVerifier))
+                .map_err(ManyError::unknown)?
+        }
+    };
+    minicbor::decode(&response.data?).map_err(ManyError::deserialization_error)
+}
+
+pub struct Replica {
+    primary: ManyUrl,
+
+    /// Maximum age of the state served, in seconds.
+    max_staleness: u64,
+
+    /// The snapshot the state was restored from, if known.
+    synced: Mutex<Option<SnapshotInfo>>,
+}
+
+impl Debug for Replica {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Replica")
+            .field("primary", &self.primary.as_str())
+            .field("max_staleness", &self.max_staleness)
+            .finish()
+    }
+}
+
+impl Replica {
+    pub fn new(primary: ManyUrl, max_staleness: u64) -> Self {
+        Self {
+            primary,
+            max_staleness,
+            synced: Mutex::new(None),
+        }
+    }
+
+    fn call<T: for<'a> Decode<'a, ()>>(
+        &self,
+        method: &str,
+        data: impl Encode<()>,
+    ) -> Result<T, ManyError> {
+        call(
+            &self.primary,
+            None,
+            method,
+            data,
+            error::replica_sync_failed,
+        )
+    }
+
+    /// The latest snapshot of the primary.
+    fn latest(&self) -> Result<SnapshotInfo, ManyError> {
+        self.call::<SnapshotInfoReturns>("ledger.snapshotInfo", EmptyArg)?
+            .latest
+            .ok_or_else(|| error::replica_sync_failed("the primary has no snapshot"))
+    }
+
+    fn restore(
+        &self,
+        info: &SnapshotInfo,
+        path: &Path,
+    ) -> Result<crate::storage::InnerStorage, ManyError> {
+        info!(
+            "Restoring the snapshot of the primary at height {}",
+            info.height
+        );
+        restore_snapshot(path, info, |chunk| {
+            self.call::<SnapshotChunkReturns>(
+                "ledger.snapshotChunk",
+                SnapshotChunkArgs {
+                    height: info.height,
+                    chunk,
+                },
+            )
+            .map(|returns| returns.chunk.to_vec())
+        })
+    }
+
+    /// Create the store of a new replica from the latest snapshot of the primary.
+    pub fn bootstrap(&self, path: &Path) -> Result<(), ManyError> {
+        let latest = self.latest()?;
+        drop(self.restore(&latest, path)?);
+        *self.synced.lock().unwrap() = Some(latest);
+        Ok(())
+    }
+
+    /// Restore the latest snapshot of the primary if it is newer than the state.
+    /// The snapshot is restored at `restore_path` first, so queries are only
+    /// blocked while the stores are swapped.
+    pub fn sync(
+        &self,
+        module_impl: &Mutex<LedgerModuleImpl>,
+        restore_path: &Path,
+    ) -> Result<(), ManyError> {
+        let latest = self.latest()?;
+        let height = module_impl.lock().unwrap().state_metrics()?.height;
+        if latest.height > height {
+            let store = self.restore(&latest, restore_path)?;
+            module_impl
+                .lock()
+                .unwrap()
+                .replace_store(store, restore_path)?;
+        } else if latest.height < height {
+            return Err(error::replica_sync_failed(format!(
+                "the replica is ahead of the primary: {height} > {}",
+                latest.height
+            )));
+        }
+        *self.synced.lock().unwrap() = Some(latest);
+        Ok(())
+    }
+
+    /// Sync with the primary every `poll` in a background thread.
+    pub fn follow(
+        self: Arc<Self>,
+        module_impl: Arc<Mutex<LedgerModuleImpl>>,
+        restore_path: PathBuf,
+        poll: Duration,
+    ) -> std::thread::JoinHandle<()> {
+        std::thread::spawn(move || loop {
+            std::thread::sleep(poll);
+            if let Err(e) = self.sync(&module_impl, &restore_path) {
+                warn!("Could not sync with the primary: {e}");
+            }
+        })
+    }
+
+    /// Fail if the state is older than the staleness bound at `now`.
+    pub fn check_staleness(&self, now: Timestamp) -> Result<(), ManyError> {
+        let time = self.synced.lock().unwrap().as_ref().and_then(|s| s.time);
+        let age = match time {
+            Some(time) => secs_since_epoch(now)?.saturating_sub(secs_since_epoch(time)?),
+            None => {
+                return Err(error::replica_stale(
+                    "an unknown number of",
+                    self.max_staleness,
+                ))
+            }
+        };
+        if age > self.max_staleness {
+            return Err(error::replica_stale(age, self.max_staleness));
+        }
+        Ok(())
+    }
+}
+
+/// Refuses commands, and queries when the replica is stale. The `base`
+/// endpoints (status, heartbeat, ...) are always served.
+#[derive(Debug)]
+pub struct ReplicaHandler<H> {
+    pub inner: H,
+    pub replica: Option<Arc<Replica>>,
+    pub commands: BTreeSet<String>,
+    pub key: CoseKeyIdentity,
+}
+
+impl<H> ReplicaHandler<H> {
+    fn check(&self, message: &RequestMessage) -> Result<(), ManyError> {
+        let replica = match &self.replica {
+            Some(replica) => replica,
+            None => return Ok(()),
+        };
+        if self.commands.contains(&message.method) {
+            return Err(error::read_only_replica(&message.method));
+        }
+        if !message.method.starts_with("base.") {
+            replica.check_staleness(Timestamp::now())?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<H: LowLevelManyRequestHandler> LowLevelManyRequestHandler for ReplicaHandler<H> {
+    async fn execute(&self, envelope: CoseSign1) -> Result<CoseSign1, String> {
+        let message = envelope
+            .payload
+            .as_deref()
+            .and_then(|payload| RequestMessage::from_bytes(payload).ok());
+
+        if let Some(message) = message {
+            if let Err(e) = self.check(&message) {
+                let response = ResponseMessage::error(self.key.address(), message.id, e);
+                return encode_cose_sign1_from_response(response, &self.key)
+                    .map_err(|e| e.to_string());
+            }
+        }
+        self.inner.execute(envelope).await
+    }
+}
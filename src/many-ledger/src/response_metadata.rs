@@ -0,0 +1,122 @@
+//! Timing and truncation metadata in responses.
+//!
+//! Clients opt in by adding the [`RESPONSE_METADATA`] attribute, without
+//! arguments, to a request. The response then carries the same attribute with
+//! the arguments
+//!
+//! ```text
+//! [ elapsed: uint, request-size: uint, response-size: uint, ? truncated: bool ]
+//! ```
+//!
+//! `elapsed` is the time spent by the server on the request, in microseconds,
+//! and the sizes are in bytes. `truncated` is only set by listing endpoints and
+//! is true when more items match than were returned.
+//!
+//! Only queries get the metadata. The responses of commands are part of the
+//! results delivered to Tendermint, so every validator must return the same
+//! bytes, without node-local timings.
+use crate::module::endpoints;
+use crate::module::event::MAXIMUM_EVENT_COUNT;
+use crate::module::events_page::ListPageReturns;
+use async_trait::async_trait;
+use coset::CoseSign1;
+use many_identity_dsa::CoseKeyIdentity;
+use many_modules::events;
+use many_protocol::{encode_cose_sign1_from_response, RequestMessage, ResponseMessage};
+use many_server::transport::LowLevelManyRequestHandler;
+use many_types::attributes::{Attribute, AttributeId};
+use many_types::cbor::CborAny;
+use std::time::{Duration, Instant};
+
+pub const RESPONSE_METADATA: AttributeId = 3_000;
+
+/// Whether the response of a listing endpoint left out matching items. None
+/// for the other endpoints, or if the messages cannot be decoded.
+pub fn truncated(method: &str, request: &[u8], response: &[u8]) -> Option<bool> {
+    match method {
+        "events.list" => {
+            let args: events::ListArgs = minicbor::decode(request).ok()?;
+            let returns: events::ListReturns = minicbor::decode(response).ok()?;
+            // The server caps the number of events, a client asking for more
+            // events than the cap may not get them all.
+            let capped = args.count.map_or(true, |c| c > MAXIMUM_EVENT_COUNT as u64);
+            Some(capped && returns.events.len() == MAXIMUM_EVENT_COUNT)
+        }
+        "events.listPage" => {
+            let returns: ListPageReturns = minicbor::decode(response).ok()?;
+            Some(returns.cursor.is_some())
+        }
+        _ => None,
+    }
+}
+
+/// Whether the response to `method` may carry the metadata, i.e. `method` is a
+/// known query.
+pub fn is_query(method: &str) -> bool {
+    endpoints()
+        .get(method)
+        .map_or(false, |info| !info.is_command)
+}
+
+/// The metadata attribute of a response.
+pub fn metadata(
+    elapsed: Duration,
+    request: &RequestMessage,
+    response: &ResponseMessage,
+) -> Attribute {
+    let response_data = response.data.as_deref().unwrap_or_default();
+    let mut arguments = vec![
+        CborAny::Int(elapsed.as_micros() as i64),
+        CborAny::Int(request.data.len() as i64),
+        CborAny::Int(response_data.len() as i64),
+    ];
+    if let Some(truncated) = truncated(&request.method, &request.data, response_data) {
+        arguments.push(CborAny::Bool(truncated));
+    }
+    Attribute::new(RESPONSE_METADATA, arguments)
+}
+
+/// Adds the metadata attribute to the responses of the queries asking for it.
+/// These responses are signed again with `key`.
+#[derive(Debug)]
+pub struct ResponseMetadataHandler<H> {
+    pub inner: H,
+    pub key: CoseKeyIdentity,
+}
+
+#[async_trait]
+impl<H: LowLevelManyRequestHandler> LowLevelManyRequestHandler for ResponseMetadataHandler<H> {
+    async fn execute(&self, envelope: CoseSign1) -> Result<CoseSign1, String> {
+        let request = envelope
+            .payload
+            .as_deref()
+            .and_then(|payload| RequestMessage::from_bytes(payload).ok())
+            .filter(|message| {
+                message
+                    .attributes
+                    .get_attribute(RESPONSE_METADATA)
+                    .is_some()
+                    && is_query(&message.method)
+            });
+        let request = match request {
+            Some(request) => request,
+            None => return self.inner.execute(envelope).await,
+        };
+
+        let start = Instant::now();
+        let result = self.inner.execute(envelope).await?;
+        let elapsed = start.elapsed();
+
+        let response = match result
+            .payload
+            .as_deref()
+            .and_then(|payload| ResponseMessage::from_bytes(payload).ok())
+        {
+            Some(response) => response,
+            None => return Ok(result),
+        };
+        let attribute = metadata(elapsed, &request, &response);
+        encode_cose_sign1_from_response(response.with_attribute(attribute), &self.key)
+            .map_err(|e| e.to_string())
+    }
+}
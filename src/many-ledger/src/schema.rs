@@ -0,0 +1,218 @@
+//! Machine-readable descriptions of the endpoints of the ledger, printed by
+//! `--dump-schema`, so client SDKs can be generated.
+//!
+//! The endpoints come from the list the ledger registers with the ABCI bridge.
+//! Their request and response shapes are described here, as CDDL; endpoints
+//! without a description are typed `any` until one is added to [`SHAPES`].
+use crate::module::endpoints;
+use serde_json::json;
+use std::fmt::Write;
+
+/// The CDDL of the types shared by the endpoints.
+const PRELUDE: &str = r#"address = #6.10000(bytes)
+symbol = address
+ledger-amount = uint / biguint
+timestamp = #6.1(uint)
+memo = [* (tstr / bytes)]
+event-id = bytes / uint
+"#;
+
+/// The arguments and returns of the described endpoints.
+#[rustfmt::skip]
+const SHAPES: &[(&str, &str, &str)] = &[
+    ("ledger.info",
+        "nil / {}",
+        "{ 0 => [* symbol], 1 => bytes, 2 => { * symbol => tstr }, * int => any }"),
+    ("ledger.balance",
+        "{ ? 0 => address, ? 1 => symbol / [* symbol] }",
+        "{ 0 => { * symbol => ledger-amount } }"),
+    ("ledger.send",
+        "{ ? 0 => address, 1 => address, 2 => ledger-amount, 3 => symbol, ? 4 => memo }",
+        "nil"),
+    ("ledger.holders",
+        "{ 0 => symbol, ? 1 => uint, ? 2 => bytes }",
+        "{ 0 => [* { 0 => address, 1 => ledger-amount }], ? 1 => bytes }"),
+    ("ledger.migrationStatus",
+        "nil / {}",
+        "{ 0 => [* { 0 => tstr, 1 => tstr, 2 => bool, ? 3 => uint, 4 => bool, \
+         ? 5 => { 0 => uint, 1 => [* { 0 => bytes, ? 1 => bytes, ? 2 => bytes }] } }] }"),
+    ("events.list",
+        "{ ? 0 => uint, ? 1 => int, ? 2 => { * int => any } }",
+        "{ 0 => uint, 1 => [* { 0 => event-id, 1 => timestamp, 2 => any }] }"),
+    ("notice.publish",
+        "{ 0 => tstr, 1 => tstr, ? 2 => timestamp, ? 3 => timestamp }",
+        "{ 0 => uint }"),
+    ("notice.retract",
+        "{ 0 => uint }",
+        "nil"),
+    ("notice.list",
+        "{}",
+        "{ 0 => [* { 0 => uint, 1 => address, 2 => timestamp, 3 => tstr, 4 => tstr, \
+         ? 5 => timestamp, ? 6 => timestamp, ? 7 => uint }] }"),
+    ("validators.update",
+        "{ 0 => bytes .size 32, 1 => uint }",
+        "nil"),
+    ("validators.list",
+        "{}",
+        "{ 0 => [* { 0 => bytes, 1 => uint }] }"),
+    ("faucet.info",
+        "{ ? 0 => address }",
+        "{ ? 0 => { 0 => address, 1 => { * symbol => ledger-amount }, 2 => uint }, \
+         1 => { * symbol => uint } }"),
+    ("faucet.claim",
+        "{ 0 => symbol, ? 1 => address }",
+        "{ 0 => ledger-amount }"),
+];
+
+fn shape_of(method: &str) -> (&'static str, &'static str) {
+    SHAPES
+        .iter()
+        .find(|(m, _, _)| *m == method)
+        .map_or(("any", "any"), |(_, args, returns)| (args, returns))
+}
+
+/// The CDDL description of every endpoint. Commands are marked as such, as
+/// they are only accepted through the blockchain.
+pub fn cddl() -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "; The endpoints of many-ledger {}.",
+        env!("CARGO_PKG_VERSION")
+    );
+    out.push_str(PRELUDE);
+
+    for (method, info) in endpoints() {
+        let (args, returns) = shape_of(&method);
+        let kind = if info.is_command { "command" } else { "query" };
+        let _ = writeln!(out, "\n; {method} ({kind})");
+        let _ = writeln!(out, "{method}-args = {args}");
+        let _ = writeln!(out, "{method}-returns = {returns}");
+    }
+    out
+}
+
+/// The OpenAPI description of the routes of the REST gateway.
+pub fn openapi() -> String {
+    let error = json!({
+        "description": "Error",
+        "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } }
+    });
+    let spec = json!({
+        "openapi": "3.0.3",
+        "info": { "title": "MANY ledger REST gateway", "version": env!("CARGO_PKG_VERSION") },
+        "paths": {
+            "/info": {
+                "get": {
+                    "summary": "ledger.info",
+                    "responses": {
+                        "200": {
+                            "description": "The symbols of the ledger.",
+                            "content": { "application/json": { "schema": {
+                                "type": "object",
+                                "properties": { "symbols": {
+                                    "type": "object",
+                                    "additionalProperties": {
+                                        "type": "object",
+                                        "properties": {
+                                            "name": { "type": "string", "nullable": true },
+                                            "ticker": { "type": "string" },
+                                            "decimals": { "type": "integer" }
+                                        }
+                                    }
+                                } }
+                            } } }
+                        },
+                        "default": error
+                    }
+                }
+            },
+            "/balances/{identity}": {
+                "get": {
+                    "summary": "ledger.balance",
+                    "parameters": [
+                        { "name": "identity", "in": "path", "required": true,
+                          "schema": { "type": "string" } },
+                        { "name": "symbol", "in": "query", "required": false,
+                          "schema": { "type": "array", "items": { "type": "string" } },
+                          "style": "form", "explode": true }
+                    ],
+                    "responses": {
+                        "200": {
+                            "description": "The balances, in the smallest unit of each token.",
+                            "content": { "application/json": { "schema": {
+                                "type": "object",
+                                "properties": { "balances": {
+                                    "type": "object",
+                                    "additionalProperties": { "type": "string" }
+                                } }
+                            } } }
+                        },
+                        "default": error
+                    }
+                }
+            },
+            "/send": {
+                "post": {
+                    "summary": "ledger.send",
+                    "requestBody": {
+                        "description": "A ledger.send COSE_Sign1 envelope signed by the sender.",
+                        "required": true,
+                        "content": { "application/cbor": { "schema": {
+                            "type": "string",
+                            "format": "binary"
+                        } } }
+                    },
+                    "responses": {
+                        "200": { "description": "Executed." },
+                        "202": {
+                            "description": "Accepted by the blockchain.",
+                            "content": { "application/json": { "schema": {
+                                "type": "object",
+                                "properties": { "token": { "type": "string" } }
+                            } } }
+                        },
+                        "default": error
+                    }
+                }
+            },
+            "/async/{token}": {
+                "get": {
+                    "summary": "async.status",
+                    "parameters": [
+                        { "name": "token", "in": "path", "required": true,
+                          "schema": { "type": "string" } }
+                    ],
+                    "responses": {
+                        "200": {
+                            "description": "The status of the command.",
+                            "content": { "application/json": { "schema": {
+                                "type": "object",
+                                "properties": {
+                                    "status": { "type": "string",
+                                                "enum": ["pending", "done", "expired"] },
+                                    "error": { "$ref": "#/components/schemas/ErrorBody" }
+                                }
+                            } } }
+                        },
+                        "default": error
+                    }
+                }
+            }
+        },
+        "components": { "schemas": {
+            "ErrorBody": {
+                "type": "object",
+                "properties": {
+                    "code": { "type": "integer" },
+                    "message": { "type": "string" }
+                }
+            },
+            "Error": {
+                "type": "object",
+                "properties": { "error": { "$ref": "#/components/schemas/ErrorBody" } }
+            }
+        } }
+    });
+    serde_json::to_string_pretty(&spec).unwrap()
+}
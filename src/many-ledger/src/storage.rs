@@ -2,29 +2,69 @@ use crate::error;
 use crate::migration::tokens::TOKEN_MIGRATION;
 use crate::migration::{LedgerMigrations, MIGRATIONS};
 use crate::storage::account::ACCOUNT_SUBRESOURCE_ID_ROOT;
-use crate::storage::event::HEIGHT_EVENTID_SHIFT;
+use crate::storage::balance_cache::BalanceCache;
+use crate::storage::clock::{BlockClock, Clock, SystemClock};
+use crate::storage::cold::ColdStore;
+use crate::storage::diff::Diffs;
+use crate::storage::growth::Growth;
+use crate::storage::scheduler::TaskHandler;
+use crate::storage::snapshot::{Restore, Snapshots};
 use many_error::ManyError;
 use many_identity::{Address, MAX_SUBRESOURCE_ID};
 use many_migration::{MigrationConfig, MigrationSet};
-use many_modules::events::EventId;
+use many_modules::events::{EventId, EventInfo, EventLog};
 use many_types::ledger::Symbol;
 use many_types::Timestamp;
-use merk::Op;
+use merk::{BatchEntry, Op};
 use std::collections::{BTreeMap, BTreeSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 mod abci;
 pub mod account;
+pub mod allowance;
+pub mod app_hash;
+pub mod balance_cache;
+pub mod clock;
+pub mod cold;
 pub mod data;
+pub mod delivered;
+pub mod diff;
+pub mod escrow;
 pub mod event;
+pub mod event_index;
+pub mod event_pruning;
+pub mod faucet;
+pub mod fees;
+pub mod freeze;
+pub mod genesis;
+pub mod growth;
+pub mod holders;
+pub mod idempotency;
 mod idstore;
+pub mod invariants;
 pub mod iterator;
 mod ledger;
 mod ledger_commands;
 pub mod ledger_mintburn;
 pub mod ledger_tokens;
-mod migrations;
+pub mod limits;
+pub mod migrations;
 pub mod multisig;
+pub mod notice;
+pub mod pending_send;
+pub mod proof;
+pub mod recurring_send;
+pub mod retention;
+pub mod scheduled_send;
+pub mod scheduler;
+pub mod sequence;
+pub mod snapshot;
+pub mod sub_account;
+pub mod supply;
+pub mod token_metadata;
+pub mod validators;
+pub mod verify;
 
 pub const SYMBOLS_ROOT: &str = "/config/symbols";
 pub const IDENTITY_ROOT: &str = "/config/identity";
@@ -45,8 +85,21 @@ pub(super) fn key_for_subresource_counter(id: &Address, token_migration_active:
 
 pub type InnerStorage = merk::Merk;
 
+/// The height of the last block committed to `store`.
+pub(crate) fn read_height(store: &InnerStorage) -> Result<u64, ManyError> {
+    Ok(store
+        .get(HEIGHT_ROOT.as_bytes())
+        .map_err(error::storage_get_failed)?
+        .map_or(0u64, |x| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(x.as_slice());
+            u64::from_be_bytes(bytes)
+        }))
+}
+
 pub struct LedgerStorage {
     persistent_store: InnerStorage,
+    persistent_path: PathBuf,
 
     /// When this is true, we do not commit every transactions as they come,
     /// but wait for a `commit` call before committing the batch to the
@@ -55,10 +108,36 @@ pub struct LedgerStorage {
 
     latest_tid: EventId,
 
-    current_time: Option<Timestamp>,
+    clock: Box<dyn Clock>,
     current_hash: Option<Vec<u8>>,
 
     migrations: LedgerMigrations,
+    migration_config: Option<MigrationConfig>,
+
+    /// Where the state is backed up before activating migrations, if anywhere.
+    migration_backups: Option<PathBuf>,
+
+    cold: Option<ColdStore>,
+
+    /// Number of blocks to retain, see [`retention`].
+    retain_blocks: Option<u64>,
+
+    task_handlers: BTreeMap<&'static str, TaskHandler>,
+
+    snapshots: Option<Snapshots>,
+    restore: Option<Restore>,
+
+    diffs: Option<Diffs>,
+    growth: Option<Growth>,
+
+    /// The events logged in the block, if kept for the subscribers.
+    block_events: Option<Vec<EventLog>>,
+
+    /// The events logged by the transaction being delivered, if kept for the
+    /// ABCI bridge.
+    tx_events: Option<Vec<EventInfo>>,
+
+    balance_cache: Option<Mutex<BalanceCache>>,
 }
 
 impl LedgerStorage {
@@ -76,9 +155,7 @@ impl LedgerStorage {
         let key = key_for_account_balance(&account, &symbol);
         let amount = many_types::ledger::TokenAmount::from(amount);
 
-        self.persistent_store
-            .apply(&[(key, Op::Put(amount.to_vec()))])
-            .map_err(error::storage_apply_failed)?;
+        self.apply_to_store(&[(key, Op::Put(amount.to_vec()))])?;
 
         // Always commit to the store. In blockchain mode this will fail.
         self.persistent_store
@@ -99,17 +176,112 @@ impl std::fmt::Debug for LedgerStorage {
 impl LedgerStorage {
     #[inline]
     pub fn set_time(&mut self, time: Timestamp) {
-        self.current_time = Some(time);
+        self.clock.set_time(time);
     }
     #[inline]
     pub fn now(&self) -> Timestamp {
-        self.current_time.unwrap_or_else(Timestamp::now)
+        self.clock.now()
+    }
+
+    /// Replace the clock of the storage, e.g. with a simulated one for testing.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    fn default_clock(blockchain: bool) -> Box<dyn Clock> {
+        if blockchain {
+            Box::new(BlockClock::default())
+        } else {
+            Box::new(SystemClock::default())
+        }
+    }
+
+    /// The handlers of the tasks scheduled by the ledger itself.
+    fn default_task_handlers() -> BTreeMap<&'static str, TaskHandler> {
+        BTreeMap::from([
+            (
+                pending_send::PENDING_SEND_REFUND_TASK,
+                pending_send::refund_pending_send as TaskHandler,
+            ),
+            (
+                delivered::DELIVERED_EXPIRY_TASK,
+                delivered::expire_delivered_request as TaskHandler,
+            ),
+            (
+                escrow::ESCROW_TIMEOUT_TASK,
+                escrow::escrow_timeout as TaskHandler,
+            ),
+            (
+                idempotency::IDEMPOTENCY_EXPIRY_TASK,
+                idempotency::expire_idempotency_key as TaskHandler,
+            ),
+            (
+                notice::NOTICE_EXPIRY_TASK,
+                notice::expire_notice as TaskHandler,
+            ),
+            (
+                recurring_send::RECURRING_SEND_TASK,
+                recurring_send::execute_recurring_send as TaskHandler,
+            ),
+            (
+                scheduled_send::SCHEDULED_SEND_TASK,
+                scheduled_send::execute_scheduled_send as TaskHandler,
+            ),
+        ])
     }
 
     pub fn migrations(&self) -> &LedgerMigrations {
         &self.migrations
     }
 
+    /// Apply a batch to the persistent store, recording it in the state diff of
+    /// the block and in the growth of the store if they are kept. The holder
+    /// counts and the holders index of the balances written are updated along.
+    fn apply_to_store(&mut self, batch: &[BatchEntry]) -> Result<(), ManyError> {
+        let mut holder_updates = Vec::new();
+        if self.is_holder_count_active() {
+            holder_updates.extend(self.holder_count_updates(batch)?);
+        }
+        if self.is_holders_index_active() {
+            holder_updates.extend(self.holders_index_updates(batch)?);
+        }
+        let mut merged;
+        let batch = if holder_updates.is_empty() {
+            batch
+        } else {
+            // Keys in batch must be sorted.
+            merged = batch
+                .iter()
+                .map(|(key, op)| {
+                    let op = match op {
+                        Op::Put(value) => Op::Put(value.clone()),
+                        Op::Delete => Op::Delete,
+                    };
+                    (key.clone(), op)
+                })
+                .chain(holder_updates)
+                .collect::<Vec<BatchEntry>>();
+            merged.sort_by(|(a, _), (b, _)| a.cmp(b));
+            merged.as_slice()
+        };
+
+        if let Some(diffs) = &mut self.diffs {
+            diffs.record(batch);
+        }
+        if let Some(growth) = &mut self.growth {
+            growth.record_batch(&self.persistent_store, batch)?;
+        }
+        if let Some(mut cache) = self.balance_cache() {
+            for (key, _) in batch {
+                cache.invalidate_key(key);
+            }
+        }
+        self.persistent_store
+            .apply(batch)
+            .map_err(error::storage_apply_failed)
+    }
+
     #[inline]
     fn maybe_commit(&mut self) -> Result<(), ManyError> {
         if !self.blockchain {
@@ -120,8 +292,15 @@ impl LedgerStorage {
 
     #[inline]
     fn commit_storage(&mut self) -> Result<(), ManyError> {
+        self.commit_storage_with_aux(vec![])
+    }
+
+    /// Commit the applied batches and the auxiliary entries `aux` in a single
+    /// write, along with the growth of the store.
+    fn commit_storage_with_aux(&mut self, mut aux: Vec<BatchEntry>) -> Result<(), ManyError> {
+        aux.extend(self.take_growth()?);
         self.persistent_store
-            .commit(&[])
+            .commit(&aux)
             .map_err(error::storage_commit_failed)?;
         Ok(())
     }
@@ -131,41 +310,53 @@ impl LedgerStorage {
         blockchain: bool,
         migration_config: Option<MigrationConfig>,
     ) -> Result<Self, ManyError> {
+        let persistent_path = persistent_path.as_ref().to_path_buf();
         let persistent_store =
-            InnerStorage::open(persistent_path).map_err(error::storage_open_failed)?;
+            InnerStorage::open(&persistent_path).map_err(error::storage_open_failed)?;
 
-        let height = persistent_store
-            .get(HEIGHT_ROOT.as_bytes())
-            .map_err(error::storage_get_failed)?
-            .map_or(0u64, |x| {
-                let mut bytes = [0u8; 8];
-                bytes.copy_from_slice(x.as_slice());
-                u64::from_be_bytes(bytes)
-            });
+        let height = read_height(&persistent_store)?;
 
-        // The call to `saturating_sub()` is required to fix
-        // https://github.com/liftedinit/many-framework/issues/289
-        //
-        // The `commit()` function computes the `latest_tid` using the previous height while
-        // the following line computes the `latest_tid` using the current height.
-        //
-        // The discrepancy will lead to an application hash mismatch if the block following the `load()` contains
-        // a transaction.
-        let latest_tid = EventId::from(height.saturating_sub(1) << HEIGHT_EVENTID_SHIFT);
         let migrations = migration_config
+            .clone()
             .map_or_else(MigrationSet::empty, |config| {
                 LedgerMigrations::load(&MIGRATIONS, config, height)
             })
             .map_err(error::unable_to_load_migrations)?;
 
-        Ok(Self {
+        let mut storage = Self {
             persistent_store,
+            persistent_path,
             blockchain,
-            latest_tid,
-            current_time: None,
+            latest_tid: EventId::from(vec![0]),
+            clock: Self::default_clock(blockchain),
             current_hash: None,
             migrations,
-        })
+            migration_config,
+            migration_backups: None,
+            cold: None,
+            retain_blocks: None,
+            task_handlers: Self::default_task_handlers(),
+            snapshots: None,
+            restore: None,
+            diffs: None,
+            growth: None,
+            block_events: None,
+            tx_events: None,
+            balance_cache: None,
+        };
+
+        // The events of the next block must be numbered as `commit()` numbers
+        // them, or the application hash of a block following the `load()`
+        // would mismatch, see
+        // https://github.com/liftedinit/many-framework/issues/289
+        storage.latest_tid = storage.event_id_base(height + 1);
+
+        // A node must not run with activation heights other validators have
+        // already passed.
+        if blockchain {
+            storage.check_migration_heights()?;
+        }
+        Ok(storage)
     }
 
     pub fn new<P: AsRef<Path>>(
@@ -174,8 +365,9 @@ impl LedgerStorage {
         identity: Address,
         blockchain: bool,
     ) -> Result<Self, ManyError> {
+        let persistent_path = persistent_path.as_ref().to_path_buf();
         let mut persistent_store =
-            InnerStorage::open(persistent_path).map_err(ManyError::unknown)?; // TODO: Custom error
+            InnerStorage::open(&persistent_path).map_err(ManyError::unknown)?; // TODO: Custom error
 
         persistent_store
             .apply(&[
@@ -197,11 +389,24 @@ impl LedgerStorage {
 
         Ok(Self {
             persistent_store,
+            persistent_path,
             blockchain,
             latest_tid: EventId::from(vec![0]),
-            current_time: None,
+            clock: Self::default_clock(blockchain),
             current_hash: None,
             migrations: MigrationSet::empty().map_err(ManyError::unknown)?, // TODO: Custom error
+            migration_config: None,
+            migration_backups: None,
+            cold: None,
+            retain_blocks: None,
+            task_handlers: Self::default_task_handlers(),
+            snapshots: None,
+            restore: None,
+            diffs: None,
+            growth: None,
+            block_events: None,
+            tx_events: None,
+            balance_cache: None,
         })
     }
 
@@ -237,27 +442,17 @@ impl LedgerStorage {
 
     fn inc_height(&mut self) -> Result<u64, ManyError> {
         let current_height = self.get_height()?;
-        self.persistent_store
-            .apply(&[(
-                HEIGHT_ROOT.as_bytes().to_vec(),
-                Op::Put((current_height + 1).to_be_bytes().to_vec()),
-            )])
-            .map_err(error::storage_apply_failed)?;
+        self.apply_to_store(&[(
+            HEIGHT_ROOT.as_bytes().to_vec(),
+            Op::Put((current_height + 1).to_be_bytes().to_vec()),
+        )])?;
         Ok(current_height)
     }
 
     /// Return the current height of the blockchain.
     /// The current height correspond to finished, committed blocks.
     pub fn get_height(&self) -> Result<u64, ManyError> {
-        Ok(self
-            .persistent_store
-            .get(HEIGHT_ROOT.as_bytes())
-            .map_err(error::storage_get_failed)?
-            .map_or(0u64, |x| {
-                let mut bytes = [0u8; 8];
-                bytes.copy_from_slice(x.as_slice());
-                u64::from_be_bytes(bytes)
-            }))
+        read_height(&self.persistent_store)
     }
 
     pub fn hash(&self) -> Vec<u8> {
@@ -308,15 +503,13 @@ impl LedgerStorage {
             next_subresource = subresource_identity.with_subresource_id(current_id)?;
         }
 
-        self.persistent_store
-            .apply(&[(
-                key_for_subresource_counter(
-                    &subresource_identity,
-                    self.migrations.is_active(&TOKEN_MIGRATION),
-                ),
-                Op::Put((current_id + 1).to_be_bytes().to_vec()),
-            )])
-            .map_err(error::storage_apply_failed)?;
+        self.apply_to_store(&[(
+            key_for_subresource_counter(
+                &subresource_identity,
+                self.migrations.is_active(&TOKEN_MIGRATION),
+            ),
+            Op::Put((current_id + 1).to_be_bytes().to_vec()),
+        )])?;
 
         self.persistent_store
             .get(identity_root.as_bytes())
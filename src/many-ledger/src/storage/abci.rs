@@ -1,7 +1,6 @@
-use crate::storage::event::HEIGHT_EVENTID_SHIFT;
 use crate::storage::LedgerStorage;
 use many_modules::abci_backend::AbciCommitInfo;
-use many_modules::events::EventId;
+use tracing::warn;
 
 impl LedgerStorage {
     pub fn commit(&mut self) -> AbciCommitInfo {
@@ -10,24 +9,65 @@ impl LedgerStorage {
         let _ = self.check_timed_out_multisig_transactions();
 
         let height = self.inc_height().expect("Unable to increment height.");
-        let retain_height = 0;
+        let retain_height = self.retain_height(height + 1);
 
-        // Committing before the migration so that the migration has
-        // the actual state of the database when setting its
-        // attributes.
-        self.commit_storage().expect("Unable to commit to storage.");
+        self.run_scheduled_tasks(height + 1)
+            .expect("Unable to run scheduled tasks.");
+
+        self.copy_events_to_cold_store(height)
+            .expect("Unable to copy events to the cold store.");
+        self.prune_old_events()
+            .expect("Unable to prune the old events.");
+        self.store_migration_heights()
+            .expect("Unable to store the migration heights.");
+
+        // Migrations read the committed state of the database, e.g. with
+        // iterators, which don't see the applied batches. Blocks without
+        // migrations to run are committed once.
+        let migrations_run = self
+            .migrations_run_at(height + 1)
+            .expect("Unable to check the migrations.");
+        if migrations_run {
+            self.commit_storage().expect("Unable to commit to storage.");
+        }
+
+        // A failed upgrade can only be rolled back with a backup.
+        self.backup_before_migrations(height + 1)
+            .expect("Unable to back up the state, not activating migrations.");
 
         // Initialize/update migrations at current height, if any
         self.migrations
             .update_at_height(&mut self.persistent_store, height + 1)
             .expect("Unable to run migrations");
+        self.run_chunked_migrations(height + 1)
+            .expect("Unable to run chunked migrations");
+        self.run_patch_migrations(height + 1)
+            .expect("Unable to run patch migrations");
+        if migrations_run {
+            // Migrations write to the store directly.
+            self.clear_balance_cache();
+        }
 
-        self.commit_storage().expect("Unable to commit to storage.");
-
+        // The root hash covers the applied batches, so the hash, the state
+        // diff and the block are committed together.
         let hash = self.persistent_store.root_hash().to_vec();
+        let mut aux = vec![self.app_hash_aux(height + 1, &hash)];
+        aux.extend(
+            self.take_state_diff(height + 1, &hash)
+                .expect("Unable to store the state diff."),
+        );
+        self.commit_storage_with_aux(aux)
+            .expect("Unable to commit to storage.");
         self.current_hash = Some(hash.clone());
 
-        self.latest_tid = EventId::from(height << HEIGHT_EVENTID_SHIFT);
+        // The migrations of this block are active, the next block is numbered
+        // accordingly.
+        self.latest_tid = self.event_id_base(height + 2);
+
+        // A failed snapshot must not stop the chain.
+        if let Err(e) = self.maybe_take_snapshot(height + 1) {
+            warn!("Unable to take a snapshot: {e}");
+        }
 
         AbciCommitInfo {
             retain_height,
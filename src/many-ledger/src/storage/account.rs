@@ -58,12 +58,10 @@ impl LedgerStorage {
     ) -> Result<Self, ManyError> {
         if self.migrations.is_active(&TOKEN_MIGRATION) {
             let identity = identity.unwrap_or(self.get_identity(IDENTITY_ROOT)?);
-            self.persistent_store
-                .apply(&[(
-                    ACCOUNT_IDENTITY_ROOT.as_bytes().to_vec(),
-                    Op::Put(identity.to_vec()),
-                )])
-                .map_err(error::storage_apply_failed)?;
+            self.apply_to_store(&[(
+                ACCOUNT_IDENTITY_ROOT.as_bytes().to_vec(),
+                Op::Put(identity.to_vec()),
+            )])?;
         }
 
         if let Some(accounts) = accounts {
@@ -108,6 +106,7 @@ impl LedgerStorage {
         account.add_role(&id, account::Role::Owner);
 
         // Set the multisig threshold properly.
+        let defaults = self.get_multisig_defaults()?;
         if let Ok(mut multisig) = account
             .features
             .get::<account::features::multisig::MultisigAccountFeature>()
@@ -128,18 +127,19 @@ impl LedgerStorage {
                 ),
             );
             multisig.arg.timeout_in_secs = Some(
-                multisig
-                    .arg
-                    .timeout_in_secs
-                    .map_or(MULTISIG_DEFAULT_TIMEOUT_IN_SECS, |v| {
-                        MULTISIG_MAXIMUM_TIMEOUT_IN_SECS.min(v)
-                    }),
+                multisig.arg.timeout_in_secs.map_or(
+                    defaults
+                        .timeout_in_secs
+                        .unwrap_or(MULTISIG_DEFAULT_TIMEOUT_IN_SECS),
+                    |v| MULTISIG_MAXIMUM_TIMEOUT_IN_SECS.min(v),
+                ),
             );
             multisig.arg.execute_automatically = Some(
-                multisig
-                    .arg
-                    .execute_automatically
-                    .unwrap_or(MULTISIG_DEFAULT_EXECUTE_AUTOMATICALLY),
+                multisig.arg.execute_automatically.unwrap_or(
+                    defaults
+                        .execute_automatically
+                        .unwrap_or(MULTISIG_DEFAULT_EXECUTE_AUTOMATICALLY),
+                ),
             );
 
             account.features.insert(multisig.as_feature());
@@ -311,12 +311,10 @@ impl LedgerStorage {
     ) -> Result<(), ManyError> {
         tracing::debug!("commit({:?})", account);
 
-        self.persistent_store
-            .apply(&[(
-                key_for_account(id),
-                Op::Put(minicbor::to_vec(account).map_err(ManyError::serialization_error)?),
-            )])
-            .map_err(|e| ManyError::unknown(e.to_string()))?;
+        self.apply_to_store(&[(
+            key_for_account(id),
+            Op::Put(minicbor::to_vec(account).map_err(ManyError::serialization_error)?),
+        )])?;
 
         self.maybe_commit()?;
 
@@ -0,0 +1,201 @@
+//! Delegated spending. An owner approves a spender to transfer up to an amount of
+//! a symbol on its behalf. Every approval and delegated transfer is kept in an
+//! allowance log, since the event kinds of the events module cannot represent them.
+use crate::error;
+use crate::storage::iterator::LedgerIterator;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_identity::Address;
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::{Memo, SortOrder, Timestamp};
+use merk::Op;
+use minicbor::{Decode, Encode};
+
+pub const ALLOWANCE_EVENTS_ROOT: &[u8] = b"/allowance_events/";
+pub const ALLOWANCE_EVENT_COUNT_ROOT: &[u8] = b"/config/allowance_event_count";
+
+pub fn key_for_allowance(owner: &Address, spender: &Address, symbol: &Symbol) -> Vec<u8> {
+    format!("/allowances/{owner}/{spender}/{symbol}").into_bytes()
+}
+
+pub fn key_for_allowance_event(id: u64) -> Vec<u8> {
+    [ALLOWANCE_EVENTS_ROOT, format!("{id:020}").as_bytes()].concat()
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+pub enum AllowanceEvent {
+    #[n(0)]
+    Approve {
+        #[n(0)]
+        owner: Address,
+        #[n(1)]
+        spender: Address,
+        #[n(2)]
+        symbol: Symbol,
+        #[n(3)]
+        amount: TokenAmount,
+    },
+    #[n(1)]
+    TransferFrom {
+        #[n(0)]
+        spender: Address,
+        #[n(1)]
+        owner: Address,
+        #[n(2)]
+        to: Address,
+        #[n(3)]
+        symbol: Symbol,
+        #[n(4)]
+        amount: TokenAmount,
+    },
+}
+
+impl AllowanceEvent {
+    pub fn is_about(&self, account: &Address) -> bool {
+        match self {
+            AllowanceEvent::Approve { owner, spender, .. } => {
+                owner == account || spender == account
+            }
+            AllowanceEvent::TransferFrom {
+                spender, owner, to, ..
+            } => spender == account || owner == account || to == account,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct AllowanceEventLog {
+    #[n(0)]
+    pub id: u64,
+
+    #[n(1)]
+    pub time: Timestamp,
+
+    #[n(2)]
+    pub content: AllowanceEvent,
+}
+
+impl LedgerStorage {
+    pub fn get_allowance(
+        &self,
+        owner: &Address,
+        spender: &Address,
+        symbol: &Symbol,
+    ) -> Result<TokenAmount, ManyError> {
+        Ok(self
+            .persistent_store
+            .get(&key_for_allowance(owner, spender, symbol))
+            .map_err(error::storage_get_failed)?
+            .map_or_else(TokenAmount::zero, TokenAmount::from))
+    }
+
+    fn set_allowance(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        symbol: &Symbol,
+        amount: &TokenAmount,
+    ) -> Result<(), ManyError> {
+        let op = if amount.is_zero() {
+            Op::Delete
+        } else {
+            Op::Put(amount.to_vec())
+        };
+        self.apply_to_store(&[(key_for_allowance(owner, spender, symbol), op)])
+    }
+
+    fn log_allowance_event(&mut self, content: AllowanceEvent) -> Result<(), ManyError> {
+        let id = self
+            .persistent_store
+            .get(ALLOWANCE_EVENT_COUNT_ROOT)
+            .map_err(error::storage_get_failed)?
+            .map_or(0, |x| {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(x.as_slice());
+                u64::from_be_bytes(bytes)
+            });
+        let event = AllowanceEventLog {
+            id,
+            time: self.now(),
+            content,
+        };
+
+        // Keys in batch must be sorted.
+        self.apply_to_store(&[
+            (
+                key_for_allowance_event(id),
+                Op::Put(minicbor::to_vec(&event).map_err(ManyError::serialization_error)?),
+            ),
+            (
+                ALLOWANCE_EVENT_COUNT_ROOT.to_vec(),
+                Op::Put((id + 1).to_be_bytes().to_vec()),
+            ),
+        ])
+    }
+
+    /// Set the amount of `symbol` that `spender` can transfer out of `owner`,
+    /// replacing any previous allowance.
+    pub fn approve(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        symbol: &Symbol,
+        amount: TokenAmount,
+    ) -> Result<(), ManyError> {
+        if owner == spender {
+            return Err(error::destination_is_source());
+        }
+        if owner.is_anonymous() || spender.is_anonymous() {
+            return Err(error::anonymous_cannot_hold_funds());
+        }
+        if !self.get_symbols()?.contains(symbol) {
+            return Err(error::unknown_symbol(symbol));
+        }
+
+        self.set_allowance(owner, spender, symbol, &amount)?;
+        self.log_allowance_event(AllowanceEvent::Approve {
+            owner: *owner,
+            spender: *spender,
+            symbol: *symbol,
+            amount,
+        })?;
+        self.maybe_commit()
+    }
+
+    /// Transfer funds out of `owner` on its behalf, consuming the allowance of
+    /// `spender`. Transfer fees are paid by the owner and do not consume the
+    /// allowance.
+    pub fn transfer_from(
+        &mut self,
+        spender: &Address,
+        owner: &Address,
+        to: &Address,
+        symbol: &Symbol,
+        amount: TokenAmount,
+        memo: Option<Memo>,
+    ) -> Result<(), ManyError> {
+        let mut allowance = self.get_allowance(owner, spender, symbol)?;
+        if amount > allowance {
+            return Err(error::allowance_exceeded(&amount, &allowance));
+        }
+
+        // Send first, it validates the transfer before changing anything.
+        self.send(owner, to, symbol, amount.clone(), memo)?;
+
+        allowance -= amount.clone();
+        self.set_allowance(owner, spender, symbol, &allowance)?;
+        self.log_allowance_event(AllowanceEvent::TransferFrom {
+            spender: *spender,
+            owner: *owner,
+            to: *to,
+            symbol: *symbol,
+            amount,
+        })?;
+        self.maybe_commit()
+    }
+
+    pub fn iter_allowance_events(&self, order: SortOrder) -> LedgerIterator {
+        LedgerIterator::all_allowance_events(&self.persistent_store, order)
+    }
+}
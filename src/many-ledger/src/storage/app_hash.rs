@@ -0,0 +1,32 @@
+//! The application hash of every committed height, kept as merk auxiliary data
+//! so the chain of hashes can be audited against the consensus (see
+//! [`crate::audit`]).
+use crate::error;
+use crate::storage::{InnerStorage, LedgerStorage};
+use many_error::ManyError;
+use merk::{BatchEntry, Op};
+
+pub(crate) const APP_HASH_ROOT: &[u8] = b"/app_hashes/";
+
+pub(crate) fn key_for_app_hash(height: u64) -> Vec<u8> {
+    [APP_HASH_ROOT, &height.to_be_bytes()].concat()
+}
+
+/// The application hash of the block committed at `height`, if recorded.
+pub fn read_app_hash(store: &InnerStorage, height: u64) -> Result<Option<Vec<u8>>, ManyError> {
+    store
+        .get_aux(&key_for_app_hash(height))
+        .map_err(error::storage_get_failed)
+}
+
+impl LedgerStorage {
+    /// The auxiliary entry recording the application hash of `height`, to
+    /// commit with the block.
+    pub(crate) fn app_hash_aux(&self, height: u64, hash: &[u8]) -> BatchEntry {
+        (key_for_app_hash(height), Op::Put(hash.to_vec()))
+    }
+
+    pub fn get_app_hash(&self, height: u64) -> Result<Option<Vec<u8>>, ManyError> {
+        read_app_hash(&self.persistent_store, height)
+    }
+}
@@ -0,0 +1,141 @@
+//! A cache of the decoded balances of the most queried accounts, so repeated
+//! `ledger.balance` queries for popular accounts don't read and decode the
+//! store every time.
+//!
+//! The balances written by a batch are invalidated as the batch is applied.
+//! Writes bypassing the batches, i.e. migrations and snapshot restores, clear
+//! the whole cache.
+use crate::storage::LedgerStorage;
+use many_identity::Address;
+use many_types::ledger::{Symbol, TokenAmount};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// Number of balances cached when none is specified.
+pub const DEFAULT_BALANCE_CACHE_SIZE: usize = 10_000;
+
+/// The identity and symbol of a balance key.
+fn balance_of_key(key: &[u8]) -> Option<(Address, Symbol)> {
+    let key = std::str::from_utf8(key).ok()?.strip_prefix("/balances/")?;
+    let (identity, symbol) = key.rsplit_once('/')?;
+    Some((
+        Address::from_str(identity).ok()?,
+        Symbol::from_str(symbol).ok()?,
+    ))
+}
+
+/// The least recently used balances are evicted first. A balance which does
+/// not exist is cached as None.
+#[derive(Default)]
+pub struct BalanceCache {
+    capacity: usize,
+    entries: BTreeMap<(Address, Symbol), (Option<TokenAmount>, u64)>,
+    recency: BTreeMap<u64, (Address, Symbol)>,
+    tick: u64,
+}
+
+impl BalanceCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ..Default::default()
+        }
+    }
+
+    pub fn get(&mut self, identity: &Address, symbol: &Symbol) -> Option<Option<TokenAmount>> {
+        let key = (*identity, *symbol);
+        let (amount, last_used) = self.entries.get_mut(&key)?;
+        self.tick += 1;
+        self.recency.remove(last_used);
+        self.recency.insert(self.tick, key);
+        *last_used = self.tick;
+        Some(amount.clone())
+    }
+
+    pub fn insert(&mut self, identity: &Address, symbol: &Symbol, amount: Option<TokenAmount>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let key = (*identity, *symbol);
+        self.remove(&key);
+        if self.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = self.recency.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        self.recency.insert(self.tick, key);
+        self.entries.insert(key, (amount, self.tick));
+    }
+
+    fn remove(&mut self, key: &(Address, Symbol)) {
+        if let Some((_, last_used)) = self.entries.remove(key) {
+            self.recency.remove(&last_used);
+        }
+    }
+
+    /// Invalidate the balance of a key written to the store, if it is one.
+    pub fn invalidate_key(&mut self, key: &[u8]) {
+        if let Some(balance) = balance_of_key(key) {
+            self.remove(&balance);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+}
+
+impl LedgerStorage {
+    /// Cache up to `capacity` balances. 0 disables the cache.
+    pub fn with_balance_cache(mut self, capacity: usize) -> Self {
+        self.balance_cache = (capacity > 0).then(|| Mutex::new(BalanceCache::new(capacity)));
+        self
+    }
+
+    pub(crate) fn balance_cache(&self) -> Option<std::sync::MutexGuard<'_, BalanceCache>> {
+        self.balance_cache
+            .as_ref()
+            .map(|cache| cache.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Clear the cache after writes bypassing the batches.
+    pub(crate) fn clear_balance_cache(&self) {
+        if let Some(mut cache) = self.balance_cache() {
+            cache.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use many_identity::testing::identity;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = BalanceCache::new(2);
+        let symbol = identity(1000);
+        cache.insert(&identity(1), &symbol, Some(1u64.into()));
+        cache.insert(&identity(2), &symbol, None);
+        assert_eq!(cache.get(&identity(1), &symbol), Some(Some(1u64.into())));
+
+        cache.insert(&identity(3), &symbol, Some(3u64.into()));
+        assert_eq!(cache.get(&identity(2), &symbol), None);
+        assert_eq!(cache.get(&identity(1), &symbol), Some(Some(1u64.into())));
+        assert_eq!(cache.get(&identity(3), &symbol), Some(Some(3u64.into())));
+    }
+
+    #[test]
+    fn invalidates_balance_keys() {
+        let mut cache = BalanceCache::new(2);
+        let symbol = identity(1000);
+        cache.insert(&identity(1), &symbol, Some(1u64.into()));
+        cache.invalidate_key(format!("/balances/{}/{symbol}", identity(2)).as_bytes());
+        assert!(cache.get(&identity(1), &symbol).is_some());
+        cache.invalidate_key(format!("/balances/{}/{symbol}", identity(1)).as_bytes());
+        assert_eq!(cache.get(&identity(1), &symbol), None);
+    }
+}
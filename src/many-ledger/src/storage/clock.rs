@@ -0,0 +1,91 @@
+//! The source of time of the ledger.
+//!
+//! Everything time dependent (event timestamps, multisig timeouts, ...) must read
+//! the time through the storage clock. In blockchain mode the clock only moves
+//! when a block begins, so every validator sees the exact same time when
+//! executing a transaction.
+use many_types::Timestamp;
+use std::sync::{Arc, Mutex};
+
+pub trait Clock: Send {
+    /// The current time, as seen by the ledger.
+    fn now(&self) -> Timestamp;
+
+    /// Called with the time of every new block.
+    fn set_time(&mut self, time: Timestamp);
+}
+
+/// The time of the latest block. Before the first block, this is the epoch.
+#[derive(Clone, Debug)]
+pub struct BlockClock {
+    time: Timestamp,
+}
+
+impl Default for BlockClock {
+    fn default() -> Self {
+        Self {
+            time: Timestamp::new(0).unwrap(),
+        }
+    }
+}
+
+impl Clock for BlockClock {
+    fn now(&self) -> Timestamp {
+        self.time
+    }
+
+    fn set_time(&mut self, time: Timestamp) {
+        self.time = time;
+    }
+}
+
+/// The wall clock, unless a time was explicitly set. Only used outside of
+/// blockchain mode, where determinism is not required.
+#[derive(Clone, Debug, Default)]
+pub struct SystemClock {
+    time: Option<Timestamp>,
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Timestamp {
+        self.time.unwrap_or_else(Timestamp::now)
+    }
+
+    fn set_time(&mut self, time: Timestamp) {
+        self.time = Some(time);
+    }
+}
+
+/// A clock fully controlled by its owner, e.g. to test timeouts. Clones share
+/// the same time, so a test can keep a handle after giving the clock away.
+#[derive(Clone, Debug)]
+pub struct SimulatedClock {
+    time: Arc<Mutex<Timestamp>>,
+}
+
+impl SimulatedClock {
+    pub fn new(time: Timestamp) -> Self {
+        Self {
+            time: Arc::new(Mutex::new(time)),
+        }
+    }
+
+    /// Move the time forward by `secs` seconds.
+    pub fn advance(&self, secs: u64) {
+        let mut time = self.time.lock().unwrap();
+        *time = Timestamp::from_system_time(
+            time.as_system_time().unwrap() + std::time::Duration::from_secs(secs),
+        )
+        .unwrap();
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> Timestamp {
+        *self.time.lock().unwrap()
+    }
+
+    fn set_time(&mut self, time: Timestamp) {
+        *self.time.lock().unwrap() = time;
+    }
+}
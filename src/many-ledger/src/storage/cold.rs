@@ -0,0 +1,203 @@
+//! Cold storage tier for old events.
+//!
+//! Events older than a number of heights are copied out of merk into a plain,
+//! compressed rocksdb database, which is cheaper to keep around (no merkle
+//! tree, can live on slower disks). The events stay in merk, so the cold store
+//! does not change the application hash and each node can configure its own.
+//! Events pruned from merk by [`super::event_pruning`] are still served from
+//! the cold store: listing events merges both tiers, merk taking precedence.
+use crate::error;
+use crate::storage::event::{key_for_event, EVENTS_ROOT, HEIGHT_EVENTID_SHIFT};
+use crate::storage::iterator::{events_iterator_mode, events_read_options, LedgerIterator};
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_modules::events::EventId;
+use many_types::{CborRange, SortOrder};
+use merk::rocksdb::{DBCompressionType, Options, WriteBatch, DB};
+use merk::Op;
+use std::ops::Bound;
+use std::path::Path;
+
+/// Key of the cold store holding the ID below which the events were copied.
+const COPIED_UNTIL_KEY: &[u8] = b"/copied_until";
+
+pub type EventIterator<'a> =
+    Box<dyn Iterator<Item = Result<(Box<[u8]>, Vec<u8>), merk::rocksdb::Error>> + 'a>;
+
+pub struct ColdStore {
+    db: DB,
+
+    /// Number of heights before events are copied.
+    after: u64,
+}
+
+impl ColdStore {
+    pub fn open<P: AsRef<Path>>(path: P, after: u64) -> Result<Self, ManyError> {
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        options.set_compression_type(DBCompressionType::Zlib);
+        let db = DB::open(&options, path).map_err(error::storage_open_failed)?;
+        Ok(Self { db, after })
+    }
+
+    fn iter(&self, range: CborRange<EventId>, order: SortOrder) -> EventIterator<'_> {
+        Box::new(
+            self.db
+                .iterator_opt(
+                    events_iterator_mode(order),
+                    events_read_options(EVENTS_ROOT, range),
+                )
+                .map(|item| item.map(|(k, v)| (k, v.to_vec()))),
+        )
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, ManyError> {
+        self.db.get(key).map_err(error::storage_get_failed)
+    }
+
+    fn copied_until(&self) -> Result<Option<EventId>, ManyError> {
+        Ok(self.get(COPIED_UNTIL_KEY)?.map(EventId::from))
+    }
+}
+
+/// Restrict `range` to the IDs below `id`.
+fn range_below(range: CborRange<EventId>, id: EventId) -> CborRange<EventId> {
+    let below = match &range.end {
+        Bound::Included(end) | Bound::Excluded(end) => {
+            key_for_event(end.clone()) < key_for_event(id.clone())
+        }
+        Bound::Unbounded => false,
+    };
+    let end = if below {
+        range.end
+    } else {
+        Bound::Excluded(id)
+    };
+    CborRange {
+        start: range.start,
+        end,
+    }
+}
+
+impl LedgerStorage {
+    pub fn with_cold_store(mut self, cold: Option<ColdStore>) -> Self {
+        self.cold = cold;
+        self
+    }
+
+    /// Iterate over the events of both tiers. The cold store only serves the
+    /// events older than the oldest event still in merk, which were pruned, so
+    /// the tiers are simply chained.
+    pub fn iter_events(&self, range: CborRange<EventId>, order: SortOrder) -> EventIterator<'_> {
+        let hot = |range, order| {
+            LedgerIterator::events_scoped_by_id(&self.persistent_store, range, order)
+        };
+        let cold = match &self.cold {
+            Some(cold) => cold,
+            None => return Box::new(hot(range, order)),
+        };
+        let pruned = match self.oldest_hot_event() {
+            Ok(Some(oldest)) => range_below(range.clone(), oldest),
+            Ok(None) => range.clone(),
+            Err(e) => return Box::new(std::iter::once(Err(e))),
+        };
+        match order {
+            SortOrder::Descending => Box::new(
+                hot(range, SortOrder::Descending).chain(cold.iter(pruned, SortOrder::Descending)),
+            ),
+            _ => Box::new(
+                cold.iter(pruned, SortOrder::Ascending)
+                    .chain(hot(range, SortOrder::Ascending)),
+            ),
+        }
+    }
+
+    /// The ID of the oldest event in merk, if any.
+    fn oldest_hot_event(&self) -> Result<Option<EventId>, merk::rocksdb::Error> {
+        LedgerIterator::events_scoped_by_id(
+            &self.persistent_store,
+            CborRange::default(),
+            SortOrder::Ascending,
+        )
+        .next()
+        .transpose()
+        .map(|item| item.map(|(k, _)| EventId::from(k[EVENTS_ROOT.len()..].to_vec())))
+    }
+
+    /// The number of events with an ID in `range`, in both stores, without
+    /// decoding them.
+    pub fn count_events(&self, range: CborRange<EventId>) -> Result<u64, ManyError> {
+        self.iter_events(range, SortOrder::Indeterminate)
+            .try_fold(0, |count, item| item.map(|_| count + 1))
+            .map_err(ManyError::unknown)
+    }
+
+    /// Get an event by its key, from whichever tier holds it.
+    pub(crate) fn get_event_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>, ManyError> {
+        match self
+            .persistent_store
+            .get(key)
+            .map_err(error::storage_get_failed)?
+        {
+            Some(bytes) => Ok(Some(bytes)),
+            None => match &self.cold {
+                Some(cold) => cold.get(key),
+                None => Ok(None),
+            },
+        }
+    }
+
+    /// Copy the events older than the configured number of heights to the cold
+    /// store, from where the previous copy stopped. Called on commit, and only
+    /// writes to the cold store.
+    pub(crate) fn copy_events_to_cold_store(&self, height: u64) -> Result<(), ManyError> {
+        let cold = match &self.cold {
+            Some(cold) if height > cold.after => cold,
+            _ => return Ok(()),
+        };
+
+        let boundary = EventId::from((height - cold.after) << HEIGHT_EVENTID_SHIFT);
+        let range = CborRange {
+            start: cold
+                .copied_until()?
+                .map_or(Bound::Unbounded, Bound::Included),
+            end: Bound::Excluded(boundary.clone()),
+        };
+
+        let mut batch = WriteBatch::default();
+        for item in
+            LedgerIterator::events_scoped_by_id(&self.persistent_store, range, SortOrder::Ascending)
+        {
+            let (k, v) = item.map_err(ManyError::unknown)?;
+            batch.put(&k, &v);
+        }
+        let boundary: &[u8] = boundary.as_ref();
+        batch.put(COPIED_UNTIL_KEY, boundary);
+        cold.db.write(batch).map_err(error::storage_apply_failed)
+    }
+
+    /// Remove events from merk, copying them to the cold store if there is one.
+    pub(crate) fn remove_events(
+        &mut self,
+        events: Vec<(Box<[u8]>, Vec<u8>)>,
+    ) -> Result<(), ManyError> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let mut cold_batch = WriteBatch::default();
+        let mut batch = Vec::new();
+        for (k, v) in events {
+            cold_batch.put(&k, &v);
+            batch.push((k.to_vec(), Op::Delete));
+        }
+
+        // Write to the cold store first, so a crash in between never loses events.
+        if let Some(cold) = &self.cold {
+            cold.db
+                .write(cold_batch)
+                .map_err(error::storage_apply_failed)?;
+        }
+        self.apply_to_store(&batch)
+    }
+}
@@ -1,8 +1,7 @@
 use crate::error;
 use crate::migration::data::{ACCOUNT_TOTAL_COUNT_INDEX, NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX};
-use crate::storage::{key_for_account_balance, LedgerStorage};
+use crate::storage::LedgerStorage;
 use many_error::ManyError;
-use many_identity::Address;
 use many_modules::data::{DataIndex, DataInfo, DataValue};
 use many_types::ledger::TokenAmount;
 use merk::Op;
@@ -30,61 +29,42 @@ impl LedgerStorage {
             .map(|x| minicbor::decode(&x).unwrap()))
     }
 
-    pub(crate) fn update_account_count(
+    /// Update the account counts for balances changing from `old` to `new`, where
+    /// `old` is None if the balance key did not exist.
+    pub(crate) fn update_account_counts<'a>(
         &mut self,
-        from: &Address,
-        to: &Address,
-        amount: TokenAmount,
-        symbol: &Address,
+        changes: impl IntoIterator<Item = (Option<&'a TokenAmount>, &'a TokenAmount)>,
     ) -> Result<(), ManyError> {
         if let Some(mut attributes) = self.data_attributes()? {
-            let destination_key = key_for_account_balance(to, symbol);
-            let destination_is_empty = self
-                .persistent_store
-                .get(&destination_key)
-                .map_err(error::storage_get_failed)?
-                .is_none();
-            let destination_is_zero = self.get_balance(to, symbol)?.is_zero();
+            let (mut new_accounts, mut non_zero_delta) = (0u64, 0i64);
+            for (old, new) in changes {
+                if old.is_none() {
+                    new_accounts += 1;
+                }
+                let was_zero = old.map_or(true, TokenAmount::is_zero);
+                match (was_zero, new.is_zero()) {
+                    (true, false) => non_zero_delta += 1,
+                    (false, true) => non_zero_delta -= 1,
+                    _ => {}
+                }
+            }
 
-            // If the destination account does not exist, increase
-            // account total count
-            if destination_is_empty {
-                attributes.entry(ACCOUNT_TOTAL_COUNT_INDEX).and_modify(|x| {
+            attributes.entry(ACCOUNT_TOTAL_COUNT_INDEX).and_modify(|x| {
+                if let DataValue::Counter(count) = x {
+                    *count += new_accounts;
+                }
+            });
+            attributes
+                .entry(NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX)
+                .and_modify(|x| {
                     if let DataValue::Counter(count) = x {
-                        *count += 1;
+                        *count = count.saturating_add_signed(non_zero_delta);
                     }
                 });
-            }
-            // If the destination account either is empty or is zero,
-            // the amount of non zero accounts increases
-            if destination_is_zero || destination_is_empty {
-                attributes
-                    .entry(NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX)
-                    .and_modify(|x| {
-                        if let DataValue::Counter(count) = x {
-                            *count += 1;
-                        }
-                    });
-            }
-            // If the amount from the origin account is equal to the
-            // amount being sent, the account will become zero, hence
-            // the non zero account total count decreases
-            let origin_balance = self.get_balance(from, symbol)?;
-            if origin_balance == amount {
-                attributes
-                    .entry(NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX)
-                    .and_modify(|x| {
-                        if let DataValue::Counter(count) = x {
-                            *count -= 1;
-                        }
-                    });
-            }
-            self.persistent_store
-                .apply(&[(
-                    DATA_ATTRIBUTES_KEY.to_vec(),
-                    Op::Put(minicbor::to_vec(attributes).unwrap()),
-                )])
-                .map_err(error::storage_apply_failed)?
+            self.apply_to_store(&[(
+                DATA_ATTRIBUTES_KEY.to_vec(),
+                Op::Put(minicbor::to_vec(attributes).unwrap()),
+            )])?
         }
         Ok(())
     }
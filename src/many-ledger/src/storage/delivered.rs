@@ -0,0 +1,113 @@
+//! Commands delivered recently. A command sent again after it was executed,
+//! e.g. by a client retrying after a timeout, gets its original result back
+//! instead of being executed a second time.
+//!
+//! The results are keyed by the hash of their request and kept in the state for
+//! [`DELIVERED_REQUEST_BLOCKS`] blocks, after which the scheduler forgets them.
+//! Requests are only recorded once the [`DELIVERED_REQUEST_MIGRATION`] is
+//! active.
+use crate::error;
+use crate::migration::delivered::DELIVERED_REQUEST_MIGRATION;
+use crate::storage::scheduler::Trigger;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use merk::Op;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+use sha3::{Digest, Sha3_256};
+
+pub const DELIVERED_ROOT: &str = "/delivered/";
+
+/// The kind of the scheduled task forgetting a delivered request.
+pub const DELIVERED_EXPIRY_TASK: &str = "delivered_expiry";
+
+/// Number of blocks the result of a command is remembered for.
+pub const DELIVERED_REQUEST_BLOCKS: u64 = 100_000;
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct DeliveredRequest {
+    /// The height of the first block the request is forgotten at.
+    #[n(0)]
+    pub expiry: u64,
+
+    /// The result of the command.
+    #[n(1)]
+    pub response: ByteVec,
+}
+
+/// The hash of a request message, as signed by its sender.
+pub fn request_hash(payload: &[u8]) -> Vec<u8> {
+    Sha3_256::digest(payload).to_vec()
+}
+
+pub fn key_for_delivered_request(hash: &[u8]) -> Vec<u8> {
+    format!("{DELIVERED_ROOT}{}", hex::encode(hash)).into_bytes()
+}
+
+/// Forget a delivered request once its expiry height is reached. The payload is
+/// the storage key.
+pub fn expire_delivered_request(
+    storage: &mut LedgerStorage,
+    payload: &[u8],
+) -> Result<(), ManyError> {
+    if let Some(delivered) = storage.get_delivered_request(payload)? {
+        if delivered.expiry <= storage.get_height()? {
+            storage.apply_to_store(&[(payload.to_vec(), Op::Delete)])?;
+        }
+    }
+    Ok(())
+}
+
+impl LedgerStorage {
+    fn get_delivered_request(&self, key: &[u8]) -> Result<Option<DeliveredRequest>, ManyError> {
+        self.persistent_store
+            .get(key)
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+
+    /// The result of the request with this hash, if it was delivered and is not
+    /// expired yet.
+    pub fn delivered_response(&self, hash: &[u8]) -> Result<Option<Vec<u8>>, ManyError> {
+        if !self.migrations.is_active(&DELIVERED_REQUEST_MIGRATION) {
+            return Ok(None);
+        }
+        let height = self.get_height()? + 1;
+        Ok(self
+            .get_delivered_request(&key_for_delivered_request(hash))?
+            .filter(|delivered| delivered.expiry > height)
+            .map(|delivered| delivered.response.to_vec()))
+    }
+
+    /// Remember the result of the request with this hash, for
+    /// [`DELIVERED_REQUEST_BLOCKS`] blocks. Nothing is recorded before the
+    /// migration is active.
+    pub fn record_delivered_request(
+        &mut self,
+        hash: &[u8],
+        response: Vec<u8>,
+    ) -> Result<(), ManyError> {
+        if !self.migrations.is_active(&DELIVERED_REQUEST_MIGRATION) {
+            return Ok(());
+        }
+        let expiry = self.get_height()? + 1 + DELIVERED_REQUEST_BLOCKS;
+        let delivered = DeliveredRequest {
+            expiry,
+            response: response.into(),
+        };
+
+        let storage_key = key_for_delivered_request(hash);
+        self.schedule_task(
+            Trigger::Height(expiry),
+            DELIVERED_EXPIRY_TASK,
+            storage_key.clone(),
+        )?;
+        self.apply_to_store(&[(
+            storage_key,
+            Op::Put(minicbor::to_vec(delivered).map_err(ManyError::serialization_error)?),
+        )])?;
+        self.maybe_commit()
+    }
+}
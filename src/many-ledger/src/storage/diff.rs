@@ -0,0 +1,147 @@
+//! Per-height state diffs, for fast catch-up between trusted nodes.
+//!
+//! When enabled, every batch applied to the persistent store during a block is
+//! recorded, in order, and kept with the block as merk auxiliary data. The shape
+//! of the merk tree, and so its hash, depends on the order of the batches, so they
+//! are kept as applied instead of merged. Replaying the batches of a height on the
+//! state of the previous height reproduces the state and its hash exactly.
+//!
+//! Auxiliary data is part of neither the application hash nor snapshots. Writes
+//! made by migrations through the store directly are not recorded: the diff of a
+//! height activating such a migration does not verify, and a node catching up
+//! stops there and replays the following blocks through Tendermint.
+use crate::error;
+use crate::storage::{InnerStorage, LedgerStorage};
+use many_error::ManyError;
+use merk::{BatchEntry, Op};
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+
+pub(crate) const DIFF_ROOT: &[u8] = b"/diffs/";
+
+pub(crate) fn key_for_diff(height: u64) -> Vec<u8> {
+    [DIFF_ROOT, &height.to_be_bytes()].concat()
+}
+
+/// A change to a key, `None` deleting it.
+pub type DiffEntry = (ByteVec, Option<ByteVec>);
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct StateDiff {
+    #[n(0)]
+    pub height: u64,
+
+    /// The batches applied during the block, in order.
+    #[n(1)]
+    pub batches: Vec<Vec<DiffEntry>>,
+
+    /// The application hash once the batches are applied.
+    #[n(2)]
+    pub hash: ByteVec,
+}
+
+impl StateDiff {
+    /// Apply the diff on `store`, which must be at the previous height, and
+    /// commit it. Nothing is committed if the resulting hash is not the hash of
+    /// the diff.
+    pub fn apply(&self, store: &mut InnerStorage) -> Result<(), ManyError> {
+        for batch in &self.batches {
+            let batch: Vec<BatchEntry> = batch
+                .iter()
+                .map(|(key, value)| {
+                    let op = match value {
+                        Some(value) => Op::Put(value.to_vec()),
+                        None => Op::Delete,
+                    };
+                    (key.to_vec(), op)
+                })
+                .collect();
+            store.apply(&batch).map_err(error::storage_apply_failed)?;
+        }
+
+        // Uncommitted changes are discarded when the store is closed.
+        if store.root_hash().as_slice() != self.hash.as_slice() {
+            return Err(error::invalid_state_diff(
+                self.height,
+                "the hash does not match",
+            ));
+        }
+        store.commit(&[]).map_err(error::storage_commit_failed)
+    }
+}
+
+/// The batches of the block being executed, and how many diffs are kept.
+#[derive(Default)]
+pub(crate) struct Diffs {
+    keep: u64,
+    batches: Vec<Vec<DiffEntry>>,
+}
+
+impl Diffs {
+    pub(crate) fn record(&mut self, batch: &[BatchEntry]) {
+        self.batches.push(
+            batch
+                .iter()
+                .map(|(key, op)| {
+                    let value = match op {
+                        Op::Put(value) => Some(value.clone().into()),
+                        Op::Delete => None,
+                    };
+                    (key.clone().into(), value)
+                })
+                .collect(),
+        );
+    }
+}
+
+impl LedgerStorage {
+    /// Keep the diff of the last `keep` heights. Only the writes made after this
+    /// call are recorded, so it must come after the store is initialized.
+    pub fn with_state_diffs(mut self, keep: Option<u64>) -> Self {
+        self.diffs = keep.map(|keep| Diffs {
+            keep: keep.max(1),
+            ..Diffs::default()
+        });
+        self
+    }
+
+    /// Store the diff of the block committed at `height`, whose application hash
+    /// is `hash`, and prune the diffs out of the kept range.
+    /// The auxiliary entries recording the diff of the block at `height`, and
+    /// deleting the expired one, to commit with the block.
+    pub(crate) fn take_state_diff(
+        &mut self,
+        height: u64,
+        hash: &[u8],
+    ) -> Result<Vec<BatchEntry>, ManyError> {
+        let diffs = match &mut self.diffs {
+            Some(diffs) => diffs,
+            None => return Ok(vec![]),
+        };
+
+        let diff = StateDiff {
+            height,
+            batches: std::mem::take(&mut diffs.batches),
+            hash: hash.to_vec().into(),
+        };
+        let mut aux = Vec::new();
+        if let Some(expired) = height.checked_sub(diffs.keep) {
+            aux.push((key_for_diff(expired), Op::Delete));
+        }
+        aux.push((
+            key_for_diff(height),
+            Op::Put(minicbor::to_vec(diff).map_err(ManyError::serialization_error)?),
+        ));
+        Ok(aux)
+    }
+
+    /// The diff of the block committed at `height`.
+    pub fn get_state_diff(&self, height: u64) -> Result<StateDiff, ManyError> {
+        self.persistent_store
+            .get_aux(&key_for_diff(height))
+            .map_err(error::storage_get_failed)?
+            .ok_or_else(|| error::state_diff_not_found(height))
+            .and_then(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+    }
+}
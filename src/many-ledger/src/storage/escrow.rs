@@ -0,0 +1,322 @@
+//! Escrows. The funds (and the transfer fee) leave the sender when the escrow
+//! is created and are held until it is released to the recipient or refunded
+//! to the sender, depending on its condition:
+//!
+//! - [`EscrowCondition::Timeout`]: released when the timeout is reached. The
+//!   sender can release it earlier, and the recipient can decline it, which
+//!   refunds the sender.
+//! - [`EscrowCondition::Arbiter`]: released or refunded by the arbiter. Escrows
+//!   the arbiter did not decide on before the timeout are refunded.
+//!
+//! Like pending transfers, the Send events are only logged on release.
+use crate::error;
+use crate::storage::fees::transfer_fee_memo;
+use crate::storage::scheduler::{TaskHandle, Trigger};
+use crate::storage::{key_for_account_balance, LedgerStorage};
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::events::EventInfo;
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::{Memo, Timestamp};
+use merk::Op;
+use minicbor::{Decode, Encode};
+use tracing::info;
+
+pub const ESCROWS_ROOT: &[u8] = b"/escrows/";
+pub const ESCROW_NEXT_ID_KEY: &[u8] = b"/config/escrow_next_id";
+
+/// The kind of the scheduled task resolving an escrow on its timeout.
+pub const ESCROW_TIMEOUT_TASK: &str = "escrow_timeout";
+
+/// Maximum timeout of an escrow, in seconds.
+pub const MAXIMUM_ESCROW_TIMEOUT: u64 = 365 * 24 * 60 * 60;
+
+pub fn key_for_escrow(id: u64) -> Vec<u8> {
+    [ESCROWS_ROOT, format!("{id:020}").as_bytes()].concat()
+}
+
+/// What releases the funds of an escrow.
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+pub enum EscrowCondition {
+    /// The funds are released when the timeout is reached.
+    #[n(0)]
+    Timeout,
+
+    /// The funds are released or refunded by this identity.
+    #[n(1)]
+    Arbiter(#[n(0)] Address),
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct Escrow {
+    #[n(0)]
+    pub id: u64,
+
+    #[n(1)]
+    pub from: Address,
+
+    #[n(2)]
+    pub to: Address,
+
+    #[n(3)]
+    pub symbol: Symbol,
+
+    #[n(4)]
+    pub amount: TokenAmount,
+
+    /// The transfer fee held along the amount, and who it is paid to.
+    #[n(5)]
+    pub fee: Option<(Address, TokenAmount)>,
+
+    #[n(6)]
+    pub memo: Option<Memo>,
+
+    #[n(7)]
+    pub condition: EscrowCondition,
+
+    /// When the escrow is released or refunded, depending on its condition.
+    #[n(8)]
+    pub timeout: Timestamp,
+
+    /// The scheduled timeout task.
+    #[n(9)]
+    pub task: u64,
+}
+
+impl Escrow {
+    /// The total amount held, i.e. the amount and the fee.
+    pub fn debit(&self) -> TokenAmount {
+        let mut debit = self.amount.clone();
+        if let Some((_, fee)) = &self.fee {
+            debit += fee.clone();
+        }
+        debit
+    }
+
+    /// Whether `sender` can release the funds to the recipient.
+    pub fn can_release(&self, sender: &Address) -> bool {
+        match &self.condition {
+            EscrowCondition::Timeout => sender == &self.from,
+            EscrowCondition::Arbiter(arbiter) => sender == arbiter,
+        }
+    }
+
+    /// Whether `sender` can refund the funds to the sender.
+    pub fn can_refund(&self, sender: &Address) -> bool {
+        match &self.condition {
+            EscrowCondition::Timeout => sender == &self.to,
+            EscrowCondition::Arbiter(arbiter) => sender == arbiter,
+        }
+    }
+
+    fn timeout_task(&self) -> TaskHandle {
+        TaskHandle {
+            trigger: Trigger::Time(self.timeout),
+            id: self.task,
+        }
+    }
+}
+
+/// Resolve an escrow on its timeout. The payload is the ID of the escrow.
+pub fn escrow_timeout(storage: &mut LedgerStorage, payload: &[u8]) -> Result<(), ManyError> {
+    let id = u64::from_be_bytes(
+        payload
+            .try_into()
+            .map_err(|_| ManyError::unknown("Invalid escrow ID.".to_string()))?,
+    );
+    // A resolved escrow is already gone.
+    if let Some(escrow) = storage.get_escrow(id)? {
+        info!("escrow_timeout({id})");
+        // A recipient frozen in the meantime cannot receive the funds.
+        match escrow.condition {
+            EscrowCondition::Timeout if !storage.is_frozen(&escrow.to)? => {
+                storage.apply_escrow_release(escrow)
+            }
+            _ => storage.apply_escrow_refund(escrow),
+        }?;
+    }
+    Ok(())
+}
+
+impl LedgerStorage {
+    pub fn get_escrow(&self, id: u64) -> Result<Option<Escrow>, ManyError> {
+        self.persistent_store
+            .get(&key_for_escrow(id))
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+
+    fn next_escrow_id(&mut self) -> Result<u64, ManyError> {
+        let id = self
+            .persistent_store
+            .get(ESCROW_NEXT_ID_KEY)
+            .map_err(error::storage_get_failed)?
+            .map_or(0, |x| {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(x.as_slice());
+                u64::from_be_bytes(bytes)
+            });
+        self.apply_to_store(&[(
+            ESCROW_NEXT_ID_KEY.to_vec(),
+            Op::Put((id + 1).to_be_bytes().to_vec()),
+        )])?;
+        Ok(id)
+    }
+
+    /// Lock funds from `from` to `to` until the escrow is resolved, at the
+    /// latest after `timeout` seconds. The amount and the transfer fee are
+    /// debited from `from` right away. Returns the ID of the escrow.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_escrow(
+        &mut self,
+        from: &Address,
+        to: &Address,
+        symbol: &Symbol,
+        amount: TokenAmount,
+        memo: Option<Memo>,
+        condition: EscrowCondition,
+        timeout: u64,
+    ) -> Result<u64, ManyError> {
+        if timeout == 0 || timeout > MAXIMUM_ESCROW_TIMEOUT {
+            return Err(error::invalid_escrow_timeout(
+                timeout,
+                MAXIMUM_ESCROW_TIMEOUT,
+            ));
+        }
+        if let EscrowCondition::Arbiter(arbiter) = &condition {
+            if arbiter.is_anonymous() || arbiter.is_illegal() {
+                return Err(error::invalid_escrow_arbiter(arbiter));
+            }
+        }
+
+        // Validates the transfer the same way a send does.
+        let outcome = self.prepare_send(from, to, symbol, &amount)?;
+        let balance = outcome
+            .balances
+            .get(from)
+            .cloned()
+            .ok_or_else(|| ManyError::unknown("Missing sender balance.".to_string()))?;
+
+        let timeout_at = Timestamp::from_system_time(
+            self.now()
+                .as_system_time()?
+                .checked_add(std::time::Duration::from_secs(timeout))
+                .ok_or_else(|| ManyError::unknown("Invalid time.".to_string()))?,
+        )?;
+
+        let id = self.next_escrow_id()?;
+        let task = self.schedule_task(
+            Trigger::Time(timeout_at),
+            ESCROW_TIMEOUT_TASK,
+            id.to_be_bytes().to_vec(),
+        )?;
+        let escrow = Escrow {
+            id,
+            from: *from,
+            to: *to,
+            symbol: *symbol,
+            amount,
+            fee: outcome.fee,
+            memo,
+            condition,
+            timeout: timeout_at,
+            task: task.id,
+        };
+
+        info!(
+            "create_escrow({} => {}, {} {}, id {id})",
+            from, to, &escrow.amount, symbol
+        );
+
+        let old = self.get_balance(from, symbol)?;
+        self.update_account_counts([(Some(&old), &balance)])?;
+        self.apply_to_store(&[
+            (
+                key_for_account_balance(from, symbol),
+                Op::Put(balance.to_vec()),
+            ),
+            (
+                key_for_escrow(id),
+                Op::Put(minicbor::to_vec(&escrow).map_err(ManyError::serialization_error)?),
+            ),
+        ])?;
+
+        self.maybe_commit()?;
+        Ok(id)
+    }
+
+    /// Release an escrow to its recipient, on behalf of `sender`.
+    pub fn release_escrow(&mut self, sender: &Address, id: u64) -> Result<(), ManyError> {
+        let escrow = self
+            .get_escrow(id)?
+            .ok_or_else(|| error::escrow_not_found(id))?;
+        if !escrow.can_release(sender) {
+            return Err(error::unauthorized());
+        }
+        self.verify_not_frozen([&escrow.to])?;
+        self.cancel_task(&escrow.timeout_task())?;
+        self.apply_escrow_release(escrow)?;
+        self.maybe_commit()
+    }
+
+    /// Refund an escrow to its sender, on behalf of `sender`.
+    pub fn refund_escrow(&mut self, sender: &Address, id: u64) -> Result<(), ManyError> {
+        let escrow = self
+            .get_escrow(id)?
+            .ok_or_else(|| error::escrow_not_found(id))?;
+        if !escrow.can_refund(sender) {
+            return Err(error::unauthorized());
+        }
+        self.cancel_task(&escrow.timeout_task())?;
+        self.apply_escrow_refund(escrow)?;
+        self.maybe_commit()
+    }
+
+    /// Credit the held funds to the recipient and the fee collector, and log
+    /// the transfer.
+    fn apply_escrow_release(&mut self, escrow: Escrow) -> Result<(), ManyError> {
+        info!("release_escrow({} => {})", escrow.id, escrow.to);
+        let Escrow {
+            id,
+            from,
+            to,
+            symbol,
+            amount,
+            fee,
+            memo,
+            ..
+        } = escrow;
+
+        let credits = std::iter::once((&to, amount.clone()))
+            .chain(fee.iter().map(|(collector, fee)| (collector, fee.clone())));
+        self.apply_pending_balances(&symbol, credits)?;
+        self.apply_to_store(&[(key_for_escrow(id), Op::Delete)])?;
+
+        self.log_event(EventInfo::Send {
+            from,
+            to,
+            symbol,
+            amount,
+            memo,
+        })?;
+        if let Some((collector, fee)) = fee {
+            self.log_event(EventInfo::Send {
+                from,
+                to: collector,
+                symbol,
+                amount: fee,
+                memo: Some(transfer_fee_memo()?),
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Credit the held funds, fee included, back to the sender.
+    fn apply_escrow_refund(&mut self, escrow: Escrow) -> Result<(), ManyError> {
+        info!("refund_escrow({} => {})", escrow.id, escrow.from);
+        self.apply_pending_balances(&escrow.symbol, [(&escrow.from, escrow.debit())])?;
+        self.apply_to_store(&[(key_for_escrow(escrow.id), Op::Delete)])
+    }
+}
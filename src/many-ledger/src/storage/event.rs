@@ -1,4 +1,6 @@
 use crate::error;
+use crate::migration::event_id::EVENT_ID_MIGRATION;
+use crate::storage::event_index::{index_keys_for_event, symbol_index_key_for_event};
 use crate::storage::iterator::LedgerIterator;
 use crate::storage::LedgerStorage;
 use many_error::ManyError;
@@ -6,6 +8,7 @@ use many_modules::events;
 use many_modules::events::EventId;
 use many_types::{CborRange, SortOrder};
 use merk::Op;
+use std::ops::Bound;
 
 pub(crate) const EVENTS_ROOT: &[u8] = b"/events/";
 pub(crate) const EVENT_COUNT_ROOT: &[u8] = b"/events_count";
@@ -13,13 +16,45 @@ pub(crate) const EVENT_COUNT_ROOT: &[u8] = b"/events_count";
 // Left-shift the height by this amount of bits
 pub(crate) const HEIGHT_EVENTID_SHIFT: u64 = 32;
 
+/// The bits of an event ID holding the index of the event in its block.
+const EVENTID_INDEX_MASK: u64 = (1 << HEIGHT_EVENTID_SHIFT) - 1;
+
 /// Number of bytes in an event ID when serialized. Keys smaller than this
 /// will have `\0` prepended, and keys larger will be cut to this number of
 /// bytes.
 pub(crate) const EVENT_ID_KEY_SIZE_IN_BYTES: usize = 32;
 
+/// The ID of the `index`-th event, from 1, numbered from `height`.
+pub fn event_id(height: u64, index: u32) -> events::EventId {
+    events::EventId::from(height << HEIGHT_EVENTID_SHIFT | index as u64)
+}
+
+/// The numeric value of an event ID, or None if it does not fit in 64 bits.
+fn event_id_value(id: &events::EventId) -> Option<u64> {
+    let bytes = id.as_ref();
+    (bytes.len() <= 8).then(|| bytes.iter().fold(0, |acc, b| acc << 8 | *b as u64))
+}
+
+/// The height an event ID is numbered from. See
+/// [`LedgerStorage::event_block_height`] for the height of the block of the
+/// event.
+pub fn event_id_height(id: &events::EventId) -> Option<u64> {
+    event_id_value(id).map(|id| id >> HEIGHT_EVENTID_SHIFT)
+}
+
+/// The index of an event in its block, from 1.
+pub fn event_id_index(id: &events::EventId) -> Option<u32> {
+    event_id_value(id).map(|id| (id & EVENTID_INDEX_MASK) as u32)
+}
+
 /// Returns the storage key for an event in the kv-store.
 pub(super) fn key_for_event(id: events::EventId) -> Vec<u8> {
+    key_for_event_with_prefix(EVENTS_ROOT, id)
+}
+
+/// Returns the key of an event ID under `prefix`, e.g. the root of the events or
+/// of one of their indexes.
+pub(super) fn key_for_event_with_prefix(prefix: &[u8], id: events::EventId) -> Vec<u8> {
     let id = id.as_ref();
     let id = if id.len() > EVENT_ID_KEY_SIZE_IN_BYTES {
         &id[0..EVENT_ID_KEY_SIZE_IN_BYTES]
@@ -29,10 +64,75 @@ pub(super) fn key_for_event(id: events::EventId) -> Vec<u8> {
 
     let mut exp_id = [0u8; EVENT_ID_KEY_SIZE_IN_BYTES];
     exp_id[(EVENT_ID_KEY_SIZE_IN_BYTES - id.len())..].copy_from_slice(id);
-    vec![EVENTS_ROOT.to_vec(), exp_id.to_vec()].concat()
+    vec![prefix.to_vec(), exp_id.to_vec()].concat()
+}
+
+/// Restrict `range` to the events that come strictly after `cursor` when iterating
+/// in `order`. Bounds already tighter than the cursor are kept as is.
+pub fn range_after_cursor(
+    range: CborRange<EventId>,
+    cursor: EventId,
+    order: &SortOrder,
+) -> CborRange<EventId> {
+    let CborRange { start, end } = range;
+    match order {
+        SortOrder::Indeterminate | SortOrder::Ascending => {
+            let lower = match &start {
+                Bound::Included(x) => Some(key_for_event(x.clone())),
+                Bound::Excluded(x) => Some(key_for_event(x.clone() + 1)),
+                Bound::Unbounded => None,
+            };
+            let start = match lower {
+                Some(lower) if lower >= key_for_event(cursor.clone() + 1) => start,
+                _ => Bound::Excluded(cursor),
+            };
+            CborRange { start, end }
+        }
+        SortOrder::Descending => {
+            let upper = match &end {
+                Bound::Included(x) => Some(key_for_event(x.clone() + 1)),
+                Bound::Excluded(x) => Some(key_for_event(x.clone())),
+                Bound::Unbounded => None,
+            };
+            let end = match upper {
+                Some(upper) if upper <= key_for_event(cursor.clone()) => end,
+                _ => Bound::Excluded(cursor),
+            };
+            CborRange { start, end }
+        }
+    }
 }
 
 impl LedgerStorage {
+    /// The activation height of the event ID migration, if enabled.
+    fn event_id_migration_height(&self) -> Option<u64> {
+        self.migrations
+            .values()
+            .find(|m| m.is_enabled() && m.name() == EVENT_ID_MIGRATION.name())
+            .map(|m| m.metadata().block_height)
+    }
+
+    /// The ID preceding the events of the block at `height`. Before the event
+    /// ID migration, the events of a block are numbered from two heights
+    /// below it, so the events of blocks 1 and 2 share the same IDs.
+    pub(crate) fn event_id_base(&self, height: u64) -> events::EventId {
+        match self.event_id_migration_height() {
+            Some(activation) if height > activation => event_id(height, 0),
+            _ => event_id(height.saturating_sub(2), 0),
+        }
+    }
+
+    /// The height of the block an event was logged in, or None if the ID is
+    /// not numbered from a height. Events numbered from 0 before the event ID
+    /// migration are reported in block 2.
+    pub fn event_block_height(&self, id: &events::EventId) -> Option<u64> {
+        let height = event_id_height(id)?;
+        Some(match self.event_id_migration_height() {
+            Some(activation) if height > activation => height,
+            _ => height + 2,
+        })
+    }
+
     pub(crate) fn new_event_id(&mut self) -> events::EventId {
         self.latest_tid += 1;
         self.latest_tid.clone()
@@ -57,29 +157,74 @@ impl LedgerStorage {
             content,
         };
 
-        self.persistent_store
-            .apply(&[
-                (
-                    key_for_event(event.id.clone()),
-                    Op::Put(minicbor::to_vec(&event).map_err(ManyError::serialization_error)?),
-                ),
-                (
-                    EVENT_COUNT_ROOT.to_vec(),
-                    Op::Put((current_nb_events + 1).to_be_bytes().to_vec()),
-                ),
-            ])
-            .map_err(error::storage_apply_failed)?;
+        let value = minicbor::to_vec(&event).map_err(ManyError::serialization_error)?;
+        if let Some(growth) = &mut self.growth {
+            growth.record_event(&event.content, value.len());
+        }
+
+        let mut batch = vec![
+            (key_for_event(event.id.clone()), Op::Put(value)),
+            (
+                EVENT_COUNT_ROOT.to_vec(),
+                Op::Put((current_nb_events + 1).to_be_bytes().to_vec()),
+            ),
+        ];
+        if self.is_event_index_active() {
+            for key in index_keys_for_event(&event)? {
+                batch.push((key, Op::Put(vec![])));
+            }
+        }
+        if self.is_symbol_event_index_active() {
+            if let Some(key) = symbol_index_key_for_event(&event)? {
+                batch.push((key, Op::Put(vec![])));
+            }
+        }
+        // Keys in batch must be sorted.
+        batch.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        self.apply_to_store(&batch)?;
+        if let Some(tx_events) = &mut self.tx_events {
+            tx_events.push(event.content.clone());
+        }
+        if let Some(block_events) = &mut self.block_events {
+            block_events.push(event);
+        }
 
         self.maybe_commit()?;
         Ok(())
     }
 
-    pub fn iter_multisig(&self, order: SortOrder) -> LedgerIterator {
-        LedgerIterator::all_multisig(&self.persistent_store, order)
+    /// Keep the events logged in every block, see [`Self::take_block_events`].
+    pub fn with_block_events(mut self, enabled: bool) -> Self {
+        self.block_events = enabled.then(Vec::new);
+        self
+    }
+
+    /// The events logged since the last call, if kept.
+    pub(crate) fn take_block_events(&mut self) -> Vec<events::EventLog> {
+        self.block_events
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Keep the events logged by every transaction, see
+    /// [`Self::take_tx_events`].
+    pub fn with_tx_events(mut self, enabled: bool) -> Self {
+        self.tx_events = enabled.then(Vec::new);
+        self
     }
 
-    pub fn iter_events(&self, range: CborRange<EventId>, order: SortOrder) -> LedgerIterator {
-        LedgerIterator::events_scoped_by_id(&self.persistent_store, range, order)
+    /// The events logged since the last call, if kept.
+    pub(crate) fn take_tx_events(&mut self) -> Vec<events::EventInfo> {
+        self.tx_events
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    pub fn iter_multisig(&self, order: SortOrder) -> LedgerIterator {
+        LedgerIterator::all_multisig(&self.persistent_store, order)
     }
 }
 
@@ -88,6 +233,20 @@ pub mod tests {
     use super::*;
     use many_modules::events::EventId;
 
+    #[test]
+    fn event_id_parts() {
+        let id = event_id(1234, 5);
+        assert_eq!(id, EventId::from(1234 << HEIGHT_EVENTID_SHIFT | 5));
+        assert_eq!(event_id_height(&id), Some(1234));
+        assert_eq!(event_id_index(&id), Some(5));
+        assert_eq!(event_id_height(&(event_id(7, 0) + 1)), Some(7));
+        assert_eq!(event_id_index(&(event_id(7, 0) + 1)), Some(1));
+
+        let long = EventId::from(b"012345678901234567890123456789".to_vec());
+        assert_eq!(event_id_height(&long), None);
+        assert_eq!(event_id_index(&long), None);
+    }
+
     #[test]
     fn event_key_size() {
         let golden_size = key_for_event(events::EventId::from(0)).len();
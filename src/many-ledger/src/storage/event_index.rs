@@ -0,0 +1,162 @@
+//! Secondary indexes of the event log, so listing the events of an account or
+//! of a kind does not require decoding every event in the store.
+//!
+//! Index entries are empty values whose keys end with the same padded event ID
+//! as the event key itself, e.g. `/events_by_account/{address}/{event_id}`.
+//!
+//! The events about a single symbol (transfers, mints, burns, ...) are also
+//! indexed by kind and symbol once the symbol index migration is active, for
+//! supply audits.
+use crate::error;
+use crate::migration::event_index::{EVENT_INDEX_MIGRATION, SYMBOL_EVENT_INDEX_MIGRATION};
+use crate::storage::event::{key_for_event, EVENTS_ROOT, EVENT_ID_KEY_SIZE_IN_BYTES};
+use crate::storage::growth::symbol_of;
+use crate::storage::iterator::LedgerIterator;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::events::{EventId, EventKind, EventLog};
+use many_types::ledger::Symbol;
+use many_types::{CborRange, SortOrder};
+use minicbor::data::{Tag, Type};
+use minicbor::Decoder;
+use std::collections::BTreeSet;
+
+pub(crate) const EVENTS_BY_ACCOUNT_ROOT: &str = "/events_by_account";
+pub(crate) const EVENTS_BY_KIND_ROOT: &str = "/events_by_kind";
+pub(crate) const EVENTS_BY_KIND_SYMBOL_ROOT: &str = "/events_by_kind_symbol";
+
+/// The CBOR tag of a MANY address.
+const ADDRESS_TAG: u64 = 10000;
+
+pub(crate) fn prefix_for_account_events(account: &Address) -> Vec<u8> {
+    format!("{EVENTS_BY_ACCOUNT_ROOT}/{account}/").into_bytes()
+}
+
+pub(crate) fn prefix_for_kind_events(kind: &EventKind) -> Result<Vec<u8>, ManyError> {
+    let kind = minicbor::to_vec(kind).map_err(ManyError::serialization_error)?;
+    Ok(format!("{EVENTS_BY_KIND_ROOT}/{}/", hex::encode(kind)).into_bytes())
+}
+
+pub(crate) fn prefix_for_kind_symbol_events(
+    kind: &EventKind,
+    symbol: &Symbol,
+) -> Result<Vec<u8>, ManyError> {
+    let kind = minicbor::to_vec(kind).map_err(ManyError::serialization_error)?;
+    Ok(format!(
+        "{EVENTS_BY_KIND_SYMBOL_ROOT}/{}/{symbol}/",
+        hex::encode(kind)
+    )
+    .into_bytes())
+}
+
+/// Every address appearing in an encoded event.
+fn addresses_in(bytes: &[u8]) -> Result<BTreeSet<Address>, minicbor::decode::Error> {
+    let mut addresses = BTreeSet::new();
+    let mut d = Decoder::new(bytes);
+    while d.position() < d.input().len() {
+        match d.datatype()? {
+            Type::Tag => {
+                if d.tag()? == Tag::Unassigned(ADDRESS_TAG) && d.datatype()? == Type::Bytes {
+                    if let Ok(address) = Address::from_bytes(d.bytes()?) {
+                        addresses.insert(address);
+                    }
+                }
+            }
+            // Walk into containers instead of skipping them.
+            Type::Array | Type::ArrayIndef => {
+                d.array()?;
+            }
+            Type::Map | Type::MapIndef => {
+                d.map()?;
+            }
+            Type::Break => d.set_position(d.position() + 1),
+            _ => d.skip()?,
+        }
+    }
+    Ok(addresses)
+}
+
+/// The index keys of an event.
+pub(crate) fn index_keys_for_event(event: &EventLog) -> Result<Vec<Vec<u8>>, ManyError> {
+    let event_key = key_for_event(event.id.clone());
+    let suffix = &event_key[EVENTS_ROOT.len()..];
+
+    let bytes = minicbor::to_vec(event).map_err(ManyError::serialization_error)?;
+    let addresses = addresses_in(&bytes).map_err(ManyError::deserialization_error)?;
+
+    let mut keys: Vec<Vec<u8>> = addresses
+        .into_iter()
+        .filter(|address| event.is_about(*address))
+        .map(|address| [prefix_for_account_events(&address), suffix.to_vec()].concat())
+        .collect();
+    keys.push([prefix_for_kind_events(&event.kind())?, suffix.to_vec()].concat());
+    Ok(keys)
+}
+
+/// The kind and symbol index key of an event, if it is about a single symbol.
+pub(crate) fn symbol_index_key_for_event(event: &EventLog) -> Result<Option<Vec<u8>>, ManyError> {
+    let symbol = match symbol_of(&event.content) {
+        Some(symbol) => symbol,
+        None => return Ok(None),
+    };
+    let event_key = key_for_event(event.id.clone());
+    let suffix = &event_key[EVENTS_ROOT.len()..];
+    Ok(Some(
+        [
+            prefix_for_kind_symbol_events(&event.kind(), &symbol)?,
+            suffix.to_vec(),
+        ]
+        .concat(),
+    ))
+}
+
+impl LedgerStorage {
+    pub fn is_event_index_active(&self) -> bool {
+        self.migrations.is_active(&EVENT_INDEX_MIGRATION)
+    }
+
+    pub fn is_symbol_event_index_active(&self) -> bool {
+        self.migrations.is_active(&SYMBOL_EVENT_INDEX_MIGRATION)
+    }
+
+    /// The number of entries of an index with an event ID in `range`, without
+    /// reading the events.
+    pub fn count_indexed_events(
+        &self,
+        prefix: &[u8],
+        range: CborRange<EventId>,
+    ) -> Result<u64, ManyError> {
+        LedgerIterator::events_scoped_by_prefix(
+            &self.persistent_store,
+            prefix,
+            range,
+            SortOrder::Indeterminate,
+        )
+        .try_fold(0, |count, item| item.map(|_| count + 1))
+        .map_err(ManyError::unknown)
+    }
+
+    /// Iterate over the events of an index, in `order`. The event IDs in `range`
+    /// apply to the events themselves. The first `offset` entries are skipped
+    /// without reading their events.
+    pub fn iter_indexed_events(
+        &self,
+        prefix: &[u8],
+        range: CborRange<EventId>,
+        order: SortOrder,
+        offset: usize,
+    ) -> impl Iterator<Item = Result<EventLog, ManyError>> + '_ {
+        LedgerIterator::events_scoped_by_prefix(&self.persistent_store, prefix, range, order)
+            .skip(offset)
+            .map(move |item| {
+                let (k, _) = item.map_err(ManyError::unknown)?;
+                let suffix = &k[k.len() - EVENT_ID_KEY_SIZE_IN_BYTES..];
+                let event_key = [EVENTS_ROOT, suffix].concat();
+                let bytes = self
+                    .get_event_bytes(&event_key)?
+                    .ok_or_else(|| error::storage_key_not_found(hex::encode(&event_key)))?;
+                minicbor::decode::<EventLog>(&bytes).map_err(ManyError::deserialization_error)
+            })
+    }
+}
@@ -0,0 +1,72 @@
+//! Age-based pruning of the event log. Events older than a maximum age are
+//! removed from merk on commit: archived in the cold store if there is one,
+//! deleted otherwise. The age is measured against the block time and set by
+//! the initial state, so every node prunes the same events.
+//!
+//! The events count keeps counting the pruned events.
+use crate::error;
+use crate::storage::iterator::LedgerIterator;
+use crate::storage::scheduler::secs_since_epoch;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_modules::events::EventLog;
+use many_types::{CborRange, SortOrder};
+use merk::Op;
+
+pub const EVENTS_MAX_AGE_KEY: &[u8] = b"/config/events_max_age";
+
+impl LedgerStorage {
+    /// Store the maximum age of the events, in seconds, from the initial state.
+    /// Nothing is written if None, so the initial hash is unchanged.
+    pub fn with_events_max_age(mut self, max_age: Option<u64>) -> Result<Self, ManyError> {
+        if let Some(max_age) = max_age {
+            self.apply_to_store(&[(
+                EVENTS_MAX_AGE_KEY.to_vec(),
+                Op::Put(max_age.to_be_bytes().to_vec()),
+            )])?;
+        }
+        Ok(self)
+    }
+
+    /// The maximum age of the events, in seconds, or None to keep them forever.
+    pub fn events_max_age(&self) -> Result<Option<u64>, ManyError> {
+        Ok(self
+            .persistent_store
+            .get(EVENTS_MAX_AGE_KEY)
+            .map_err(error::storage_get_failed)?
+            .map(|x| {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(x.as_slice());
+                u64::from_be_bytes(bytes)
+            }))
+    }
+
+    /// Remove the events older than the maximum age. Called on commit, before
+    /// the storage is committed.
+    pub(crate) fn prune_old_events(&mut self) -> Result<(), ManyError> {
+        let max_age = match self.events_max_age()? {
+            Some(max_age) => max_age,
+            None => return Ok(()),
+        };
+        let cutoff = match secs_since_epoch(self.now())?.checked_sub(max_age) {
+            Some(cutoff) => cutoff,
+            None => return Ok(()),
+        };
+
+        // Events are ordered by height, hence by time.
+        let mut events = Vec::new();
+        for item in LedgerIterator::events_scoped_by_id(
+            &self.persistent_store,
+            CborRange::default(),
+            SortOrder::Ascending,
+        ) {
+            let (k, v) = item.map_err(ManyError::unknown)?;
+            let event: EventLog = minicbor::decode(&v).map_err(ManyError::deserialization_error)?;
+            if secs_since_epoch(event.time)? >= cutoff {
+                break;
+            }
+            events.push((k, v));
+        }
+        self.remove_events(events)
+    }
+}
@@ -0,0 +1,132 @@
+//! A faucet for devnets, dispensing a capped amount of tokens to each identity
+//! per period.
+//!
+//! The tokens are sent from the faucet account, which has to be funded like
+//! any other account. The time of the last claim of each recipient and symbol
+//! is kept in the state, so every node enforces the same periods.
+use crate::error;
+use crate::storage::scheduler::secs_since_epoch;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_identity::Address;
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::Memo;
+use merk::Op;
+use minicbor::{Decode, Encode};
+use std::collections::BTreeMap;
+
+pub const FAUCET_CONFIG_KEY: &[u8] = b"/config/faucet";
+pub const FAUCET_CLAIMS_ROOT: &str = "/faucet/";
+
+pub fn key_for_faucet_claim(to: &Address, symbol: &Symbol) -> Vec<u8> {
+    format!("{FAUCET_CLAIMS_ROOT}{to}/{symbol}").into_bytes()
+}
+
+pub fn faucet_memo() -> Result<Memo, ManyError> {
+    Memo::try_from("Faucet").map_err(ManyError::unknown)
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct FaucetConfig {
+    /// The account the tokens are sent from.
+    #[n(0)]
+    pub account: Address,
+
+    /// The amount sent by a claim, per symbol. Other symbols cannot be claimed.
+    #[n(1)]
+    pub amounts: BTreeMap<Symbol, TokenAmount>,
+
+    /// The number of seconds between two claims of a symbol by an identity.
+    #[n(2)]
+    pub period: u64,
+}
+
+impl LedgerStorage {
+    pub fn with_faucet(mut self, faucet: Option<FaucetConfig>) -> Result<Self, ManyError> {
+        let faucet = match faucet {
+            Some(faucet) => faucet,
+            None => return Ok(self),
+        };
+
+        let symbols = self.get_symbols()?;
+        for (symbol, amount) in &faucet.amounts {
+            if !symbols.contains(symbol) {
+                return Err(error::unknown_symbol(symbol));
+            }
+            if amount.is_zero() {
+                return Err(error::invalid_genesis(format!(
+                    "the faucet amount of {symbol} is zero"
+                )));
+            }
+        }
+
+        self.apply_to_store(&[(
+            FAUCET_CONFIG_KEY.to_vec(),
+            Op::Put(minicbor::to_vec(&faucet).map_err(ManyError::serialization_error)?),
+        )])?;
+        Ok(self)
+    }
+
+    pub fn get_faucet(&self) -> Result<Option<FaucetConfig>, ManyError> {
+        self.persistent_store
+            .get(FAUCET_CONFIG_KEY)
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+
+    /// The time `to` last claimed `symbol`, in seconds since the epoch.
+    pub fn get_faucet_claim(
+        &self,
+        to: &Address,
+        symbol: &Symbol,
+    ) -> Result<Option<u64>, ManyError> {
+        self.persistent_store
+            .get(&key_for_faucet_claim(to, symbol))
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| {
+                bytes
+                    .try_into()
+                    .map(u64::from_be_bytes)
+                    .map_err(|_| ManyError::unknown("Invalid faucet claim."))
+            })
+            .transpose()
+    }
+
+    /// Send the faucet amount of `symbol` to `to`, once per period.
+    pub fn faucet_claim(
+        &mut self,
+        to: &Address,
+        symbol: &Symbol,
+    ) -> Result<TokenAmount, ManyError> {
+        let faucet = self.get_faucet()?.ok_or_else(error::faucet_disabled)?;
+        let amount = faucet
+            .amounts
+            .get(symbol)
+            .cloned()
+            .ok_or_else(|| error::unknown_symbol(symbol))?;
+
+        let now = secs_since_epoch(self.now())?;
+        if let Some(last) = self.get_faucet_claim(to, symbol)? {
+            let next = last.saturating_add(faucet.period);
+            if now < next {
+                return Err(error::faucet_claim_too_soon(next - now));
+            }
+        }
+
+        self.send(
+            &faucet.account,
+            to,
+            symbol,
+            amount.clone(),
+            Some(faucet_memo()?),
+        )?;
+        self.apply_to_store(&[(
+            key_for_faucet_claim(to, symbol),
+            Op::Put(now.to_be_bytes().to_vec()),
+        )])?;
+        self.maybe_commit()?;
+        Ok(amount)
+    }
+}
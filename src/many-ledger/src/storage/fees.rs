@@ -0,0 +1,128 @@
+use crate::error;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_identity::Address;
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::Memo;
+use merk::{BatchEntry, Op};
+use minicbor::{Decode, Encode};
+use num_bigint::BigUint;
+use std::collections::BTreeMap;
+
+pub const FEE_COLLECTOR_ROOT: &str = "/config/fee_collector";
+
+/// The largest percentage fee, i.e. the whole amount sent.
+pub const MAX_BASIS_POINTS: u32 = 10_000;
+
+pub fn transfer_fee_memo() -> Result<Memo, ManyError> {
+    Memo::try_from("Transfer fee").map_err(ManyError::unknown)
+}
+
+pub fn key_for_transfer_fee(symbol: &Symbol) -> Vec<u8> {
+    format!("/config/fees/{symbol}").into_bytes()
+}
+
+/// The fee charged to the sender of a transfer, on top of the amount sent.
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+pub enum TransferFee {
+    /// A flat amount of the transferred symbol.
+    #[n(0)]
+    Flat(#[n(0)] TokenAmount),
+
+    /// A percentage of the amount sent, in basis points (1/100th of a percent).
+    #[n(1)]
+    BasisPoints(#[n(0)] u32),
+}
+
+impl TransferFee {
+    pub fn amount_for(&self, amount: &TokenAmount) -> TokenAmount {
+        match self {
+            TransferFee::Flat(fee) => fee.clone(),
+            TransferFee::BasisPoints(bps) => {
+                let amount = BigUint::from_bytes_be(&amount.to_vec());
+                TokenAmount::from(amount * *bps / MAX_BASIS_POINTS)
+            }
+        }
+    }
+}
+
+impl LedgerStorage {
+    pub fn with_fees(
+        mut self,
+        collector: Option<Address>,
+        fees: Option<BTreeMap<Symbol, TransferFee>>,
+    ) -> Result<Self, ManyError> {
+        let fees = fees.unwrap_or_default();
+        let collector = match collector {
+            Some(collector) => collector,
+            None if fees.is_empty() => return Ok(self),
+            None => return Err(error::fee_collector_missing()),
+        };
+
+        let symbols = self.get_symbols()?;
+        let mut batch: Vec<BatchEntry> = Vec::new();
+        for (symbol, fee) in fees {
+            if !symbols.contains(&symbol) {
+                return Err(error::unknown_symbol(symbol));
+            }
+            batch.push((
+                key_for_transfer_fee(&symbol),
+                Op::Put(minicbor::to_vec(fee).map_err(ManyError::serialization_error)?),
+            ));
+        }
+        batch.push((
+            FEE_COLLECTOR_ROOT.as_bytes().to_vec(),
+            Op::Put(collector.to_vec()),
+        ));
+        batch.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        self.apply_to_store(&batch)?;
+
+        Ok(self)
+    }
+
+    pub fn get_fee_collector(&self) -> Result<Option<Address>, ManyError> {
+        self.persistent_store
+            .get(FEE_COLLECTOR_ROOT.as_bytes())
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| Address::from_bytes(&bytes))
+            .transpose()
+    }
+
+    pub fn get_transfer_fee(&self, symbol: &Symbol) -> Result<Option<TransferFee>, ManyError> {
+        self.persistent_store
+            .get(&key_for_transfer_fee(symbol))
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+
+    pub fn get_transfer_fees(&self) -> Result<BTreeMap<Symbol, TransferFee>, ManyError> {
+        let mut fees = BTreeMap::new();
+        for symbol in self.get_symbols()? {
+            if let Some(fee) = self.get_transfer_fee(&symbol)? {
+                fees.insert(symbol, fee);
+            }
+        }
+        Ok(fees)
+    }
+
+    /// The fee `from` has to pay to transfer `amount` of `symbol`, and the identity
+    /// it is paid to. The fee collector itself never pays fees.
+    pub fn transfer_fee(
+        &self,
+        from: &Address,
+        symbol: &Symbol,
+        amount: &TokenAmount,
+    ) -> Result<Option<(Address, TokenAmount)>, ManyError> {
+        let collector = match self.get_fee_collector()? {
+            Some(collector) if collector != *from => collector,
+            _ => return Ok(None),
+        };
+        Ok(self
+            .get_transfer_fee(symbol)?
+            .map(|fee| fee.amount_for(amount))
+            .filter(|fee| !fee.is_zero())
+            .map(|fee| (collector, fee)))
+    }
+}
@@ -0,0 +1,60 @@
+use crate::error;
+use crate::storage::{LedgerStorage, IDENTITY_ROOT};
+use many_error::ManyError;
+use many_identity::Address;
+use merk::Op;
+
+pub const COMPLIANCE_IDENTITY_ROOT: &str = "/config/compliance_identity";
+pub const FROZEN_ROOT: &str = "/frozen/";
+
+pub fn key_for_frozen_account(account: &Address) -> Vec<u8> {
+    format!("{FROZEN_ROOT}{account}").into_bytes()
+}
+
+impl LedgerStorage {
+    pub fn with_compliance_identity(
+        mut self,
+        compliance_identity: Option<Address>,
+    ) -> Result<Self, ManyError> {
+        if let Some(identity) = compliance_identity {
+            self.apply_to_store(&[(
+                COMPLIANCE_IDENTITY_ROOT.as_bytes().to_vec(),
+                Op::Put(identity.to_vec()),
+            )])?;
+        }
+        Ok(self)
+    }
+
+    /// The identity allowed to freeze accounts. Defaults to the ledger identity.
+    pub fn get_compliance_identity(&self) -> Result<Address, ManyError> {
+        self.get_identity(COMPLIANCE_IDENTITY_ROOT)
+            .or_else(|_| self.get_identity(IDENTITY_ROOT))
+    }
+
+    pub fn is_frozen(&self, account: &Address) -> Result<bool, ManyError> {
+        Ok(self
+            .persistent_store
+            .get(&key_for_frozen_account(account))
+            .map_err(error::storage_get_failed)?
+            .is_some())
+    }
+
+    /// Fail if any of `accounts` is frozen.
+    pub(crate) fn verify_not_frozen<'a>(
+        &self,
+        accounts: impl IntoIterator<Item = &'a Address>,
+    ) -> Result<(), ManyError> {
+        for account in accounts {
+            if self.is_frozen(account)? {
+                return Err(error::account_frozen(account));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn set_frozen(&mut self, account: &Address, frozen: bool) -> Result<(), ManyError> {
+        let op = if frozen { Op::Put(vec![1]) } else { Op::Delete };
+        self.apply_to_store(&[(key_for_frozen_account(account), op)])?;
+        self.maybe_commit()
+    }
+}
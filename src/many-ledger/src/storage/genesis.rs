@@ -0,0 +1,67 @@
+//! The initial distribution of the ledger, kept so auditors can verify the launch
+//! state without access to the initial state JSON.
+//!
+//! The report is stored in the auxiliary data of merk, which is not part of the
+//! application hash, since it describes the hash itself.
+use crate::error;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_identity::Address;
+use many_types::ledger::{Symbol, TokenAmount};
+use merk::Op;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+use std::collections::BTreeMap;
+
+pub const GENESIS_REPORT_KEY: &[u8] = b"/genesis_report";
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct GenesisReport {
+    /// The application hash right after genesis.
+    #[n(0)]
+    pub hash: ByteVec,
+
+    /// Every initial allocation.
+    #[n(1)]
+    pub allocations: BTreeMap<Address, BTreeMap<Symbol, TokenAmount>>,
+
+    /// The total allocated, per symbol.
+    #[n(2)]
+    pub totals: BTreeMap<Symbol, TokenAmount>,
+}
+
+impl LedgerStorage {
+    /// Record the genesis report. Must be called once the genesis is built.
+    pub fn with_genesis_report(
+        mut self,
+        allocations: BTreeMap<Address, BTreeMap<Symbol, TokenAmount>>,
+    ) -> Result<Self, ManyError> {
+        let mut totals: BTreeMap<Symbol, TokenAmount> = BTreeMap::new();
+        for (symbol, amount) in allocations.values().flatten() {
+            *totals.entry(*symbol).or_default() += amount.clone();
+        }
+        let report = GenesisReport {
+            hash: self.hash().into(),
+            allocations,
+            totals,
+        };
+
+        self.persistent_store
+            .commit(&[(
+                GENESIS_REPORT_KEY.to_vec(),
+                Op::Put(minicbor::to_vec(report).map_err(ManyError::serialization_error)?),
+            )])
+            .map_err(error::storage_commit_failed)?;
+        Ok(self)
+    }
+
+    /// The genesis report, if this store was created with one.
+    pub fn get_genesis_report(&self) -> Result<Option<GenesisReport>, ManyError> {
+        self.persistent_store
+            .get_aux(GENESIS_REPORT_KEY)
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+}
@@ -0,0 +1,255 @@
+//! Growth of the event log and of the store, for capacity planning.
+//!
+//! When enabled, the node counts the events it logs per module and per symbol,
+//! and the net bytes written to the store per key root (e.g. `balances`) and per
+//! symbol. The counters are added up per day of block time and kept as merk
+//! auxiliary data when the store commits, so they survive restarts without being
+//! part of the application hash.
+use crate::error;
+use crate::metrics::GrowthRates;
+use crate::storage::scheduler::secs_since_epoch;
+use crate::storage::{InnerStorage, LedgerStorage};
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::events::EventInfo;
+use many_types::ledger::Symbol;
+use merk::{BatchEntry, Op};
+use minicbor::{Decode, Encode};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+pub(crate) const GROWTH_ROOT: &[u8] = b"/growth/";
+
+/// Number of complete days the growth rates are averaged over.
+pub const GROWTH_RATE_DAYS: u64 = 7;
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+pub(crate) fn key_for_growth(day: u64) -> Vec<u8> {
+    [GROWTH_ROOT, &day.to_be_bytes()].concat()
+}
+
+/// The module of the endpoints logging an event.
+fn module_of(event: &EventInfo) -> &'static str {
+    match event {
+        EventInfo::Send { .. } => "ledger",
+        EventInfo::TokenCreate { .. }
+        | EventInfo::TokenUpdate { .. }
+        | EventInfo::TokenAddExtendedInfo { .. }
+        | EventInfo::TokenRemoveExtendedInfo { .. }
+        | EventInfo::TokenMint { .. }
+        | EventInfo::TokenBurn { .. } => "tokens",
+        EventInfo::AccountMultisigSubmit { .. }
+        | EventInfo::AccountMultisigApprove { .. }
+        | EventInfo::AccountMultisigRevoke { .. }
+        | EventInfo::AccountMultisigExecute { .. }
+        | EventInfo::AccountMultisigWithdraw { .. }
+        | EventInfo::AccountMultisigSetDefaults { .. }
+        | EventInfo::AccountMultisigExpired { .. } => "account.multisig",
+        EventInfo::AccountCreate { .. }
+        | EventInfo::AccountSetDescription { .. }
+        | EventInfo::AccountAddRoles { .. }
+        | EventInfo::AccountRemoveRoles { .. }
+        | EventInfo::AccountDisable { .. }
+        | EventInfo::AccountAddFeatures { .. } => "account",
+        _ => "other",
+    }
+}
+
+/// The symbol of an event about a single symbol.
+pub(crate) fn symbol_of(event: &EventInfo) -> Option<Symbol> {
+    match event {
+        EventInfo::Send { symbol, .. }
+        | EventInfo::TokenCreate { symbol, .. }
+        | EventInfo::TokenUpdate { symbol, .. }
+        | EventInfo::TokenAddExtendedInfo { symbol, .. }
+        | EventInfo::TokenRemoveExtendedInfo { symbol, .. }
+        | EventInfo::TokenMint { symbol, .. }
+        | EventInfo::TokenBurn { symbol, .. } => Some(*symbol),
+        _ => None,
+    }
+}
+
+/// The first segment of a key, e.g. `balances` for `/balances/{id}/{symbol}`.
+fn root_of(key: &[u8]) -> String {
+    let key = String::from_utf8_lossy(key);
+    match key.strip_prefix('/') {
+        Some(rest) => rest.split('/').next().unwrap_or_default().to_string(),
+        None => "other".to_string(),
+    }
+}
+
+/// The symbol of a balance key.
+pub(crate) fn balance_symbol(key: &[u8]) -> Option<Symbol> {
+    let key = std::str::from_utf8(key).ok()?.strip_prefix("/balances/")?;
+    Address::from_str(key.rsplit('/').next()?).ok()
+}
+
+fn add<K: Ord, V: std::ops::AddAssign + Default>(map: &mut BTreeMap<K, V>, key: K, value: V) {
+    *map.entry(key).or_default() += value;
+}
+
+#[derive(Clone, Debug, Default, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct Growth {
+    /// Number of events logged, per module.
+    #[n(0)]
+    pub events: BTreeMap<String, u64>,
+
+    /// Net number of bytes written to the store, keys included, per key root.
+    #[n(1)]
+    pub bytes: BTreeMap<String, i64>,
+
+    /// Number of events logged about a symbol.
+    #[n(2)]
+    pub symbol_events: BTreeMap<Symbol, u64>,
+
+    /// Net number of bytes of the events and balances of a symbol.
+    #[n(3)]
+    pub symbol_bytes: BTreeMap<Symbol, i64>,
+}
+
+impl Growth {
+    pub(crate) fn record_event(&mut self, event: &EventInfo, size: usize) {
+        add(&mut self.events, module_of(event).to_string(), 1);
+        if let Some(symbol) = symbol_of(event) {
+            add(&mut self.symbol_events, symbol, 1);
+            add(&mut self.symbol_bytes, symbol, size as i64);
+        }
+    }
+
+    /// Count the bytes a batch adds to `store`, before it is applied.
+    pub(crate) fn record_batch(
+        &mut self,
+        store: &InnerStorage,
+        batch: &[BatchEntry],
+    ) -> Result<(), ManyError> {
+        for (key, op) in batch {
+            let old = store
+                .get(key)
+                .map_err(error::storage_get_failed)?
+                .map_or(0, |value| key.len() + value.len());
+            let new = match op {
+                Op::Put(value) => key.len() + value.len(),
+                Op::Delete => 0,
+            };
+            let delta = new as i64 - old as i64;
+            if delta == 0 {
+                continue;
+            }
+            add(&mut self.bytes, root_of(key), delta);
+            if let Some(symbol) = balance_symbol(key) {
+                add(&mut self.symbol_bytes, symbol, delta);
+            }
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, other: Growth) {
+        for (k, v) in other.events {
+            add(&mut self.events, k, v);
+        }
+        for (k, v) in other.bytes {
+            add(&mut self.bytes, k, v);
+        }
+        for (k, v) in other.symbol_events {
+            add(&mut self.symbol_events, k, v);
+        }
+        for (k, v) in other.symbol_bytes {
+            add(&mut self.symbol_bytes, k, v);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.events.is_empty()
+            && self.bytes.is_empty()
+            && self.symbol_events.is_empty()
+            && self.symbol_bytes.is_empty()
+    }
+}
+
+impl LedgerStorage {
+    /// Count the growth of the event log and of the store from now on.
+    pub fn with_growth_metrics(mut self, enabled: bool) -> Self {
+        self.growth = enabled.then(Growth::default);
+        self
+    }
+
+    fn today(&self) -> Result<u64, ManyError> {
+        Ok(secs_since_epoch(self.now())? / SECS_PER_DAY)
+    }
+
+    /// The auxiliary entry adding the growth counted since the last commit to
+    /// the growth of the day.
+    pub(crate) fn take_growth(&mut self) -> Result<Vec<BatchEntry>, ManyError> {
+        let growth = match &mut self.growth {
+            Some(growth) if !growth.is_empty() => std::mem::take(growth),
+            _ => return Ok(vec![]),
+        };
+
+        let day = self.today()?;
+        let mut total = self.get_growth(day)?.unwrap_or_default();
+        total.merge(growth);
+        Ok(vec![(
+            key_for_growth(day),
+            Op::Put(minicbor::to_vec(total).map_err(ManyError::serialization_error)?),
+        )])
+    }
+
+    fn get_growth(&self, day: u64) -> Result<Option<Growth>, ManyError> {
+        self.persistent_store
+            .get_aux(&key_for_growth(day))
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+
+    /// The growth of each of the last `days` days, today included, by number of
+    /// days since the Unix epoch. Days without growth are omitted.
+    pub fn growth_per_day(&self, days: u64) -> Result<BTreeMap<u64, Growth>, ManyError> {
+        let today = self.today()?;
+        let mut growth = BTreeMap::new();
+        for day in today.saturating_sub(days.saturating_sub(1))..=today {
+            if let Some(g) = self.get_growth(day)? {
+                growth.insert(day, g);
+            }
+        }
+        Ok(growth)
+    }
+
+    /// The average growth per day over the last [`GROWTH_RATE_DAYS`] complete
+    /// days, counting only the days with growth.
+    pub fn growth_rates(&self) -> Result<GrowthRates, ManyError> {
+        let mut days = self.growth_per_day(GROWTH_RATE_DAYS + 1)?;
+        days.remove(&self.today()?);
+
+        let mut total = Growth::default();
+        let count = days.len().max(1) as f64;
+        for growth in days.into_values() {
+            total.merge(growth);
+        }
+        let average = |v: f64| v / count;
+        Ok(GrowthRates {
+            events: total
+                .events
+                .into_iter()
+                .map(|(k, v)| (k, average(v as f64)))
+                .collect(),
+            bytes: total
+                .bytes
+                .into_iter()
+                .map(|(k, v)| (k, average(v as f64)))
+                .collect(),
+            symbol_events: total
+                .symbol_events
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), average(v as f64)))
+                .collect(),
+            symbol_bytes: total
+                .symbol_bytes
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), average(v as f64)))
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,180 @@
+//! The holders of each symbol, ordered by balance. Once the holders index
+//! migration is active, every non-zero balance has an entry in the index,
+//! written along the balance, whose key sorts the largest balances first:
+//!
+//! `/supply/rank/{symbol}/{inverted length}{inverted amount}{address}`
+//!
+//! where the amount is big-endian, and each byte of the amount and its length
+//! is inverted. Equal balances are ordered by address.
+use crate::error;
+use crate::migration::supply::HOLDERS_INDEX_MIGRATION;
+use crate::storage::growth::balance_symbol;
+use crate::storage::{InnerStorage, LedgerStorage};
+use many_error::ManyError;
+use many_identity::Address;
+use many_types::ledger::{Symbol, TokenAmount};
+use merk::rocksdb::{self, Direction, IteratorMode, ReadOptions};
+use merk::tree::Tree;
+use merk::{BatchEntry, Op};
+use minicbor::{Decode, Encode};
+use std::str::FromStr;
+
+pub const HOLDERS_INDEX_ROOT: &str = "/supply/rank/";
+
+const BALANCES_ROOT: &[u8] = b"/balances/";
+
+pub fn key_for_holders_index(symbol: &Symbol) -> Vec<u8> {
+    format!("{HOLDERS_INDEX_ROOT}{symbol}/").into_bytes()
+}
+
+/// The index key of a balance, or None if it cannot be indexed.
+fn key_for_holder(symbol: &Symbol, account: &Address, amount: &TokenAmount) -> Option<Vec<u8>> {
+    let amount = amount.to_vec();
+    let len = u8::try_from(amount.len()).ok()?;
+    let mut key = key_for_holders_index(symbol);
+    key.push(!len);
+    key.extend(amount.iter().map(|b| !b));
+    key.extend(account.to_string().into_bytes());
+    Some(key)
+}
+
+/// Decode the account and amount of an index key, without its symbol prefix.
+fn decode_holder(suffix: &[u8]) -> Result<Holder, ManyError> {
+    let invalid = || ManyError::unknown("Invalid holders index key.".to_string());
+    let (len, rest) = suffix.split_first().ok_or_else(invalid)?;
+    let len = !len as usize;
+    if rest.len() < len {
+        return Err(invalid());
+    }
+    let (amount, account) = rest.split_at(len);
+    Ok(Holder {
+        account: Address::from_str(std::str::from_utf8(account).map_err(ManyError::unknown)?)?,
+        amount: TokenAmount::from(amount.iter().map(|b| !b).collect::<Vec<u8>>()),
+    })
+}
+
+/// The account and symbol of a balance key.
+fn balance_account(key: &[u8]) -> Option<(Address, Symbol)> {
+    let symbol = balance_symbol(key)?;
+    let key = std::str::from_utf8(key).ok()?.strip_prefix("/balances/")?;
+    let (account, _) = key.rsplit_once('/')?;
+    Some((Address::from_str(account).ok()?, symbol))
+}
+
+/// The index key of a stored balance value, if it is non-zero.
+fn holder_key(account: &Address, symbol: &Symbol, value: Option<&[u8]>) -> Option<Vec<u8>> {
+    let amount = TokenAmount::from(value?.to_vec());
+    if amount.is_zero() {
+        None
+    } else {
+        key_for_holder(symbol, account, &amount)
+    }
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct Holder {
+    #[n(0)]
+    pub account: Address,
+
+    #[n(1)]
+    pub amount: TokenAmount,
+}
+
+impl LedgerStorage {
+    pub fn is_holders_index_active(&self) -> bool {
+        self.migrations.is_active(&HOLDERS_INDEX_MIGRATION)
+    }
+
+    /// The index entries to update when applying `batch`, to apply along it.
+    pub(crate) fn holders_index_updates(
+        &self,
+        batch: &[BatchEntry],
+    ) -> Result<Vec<BatchEntry>, ManyError> {
+        let mut updates = Vec::new();
+        for (key, op) in batch {
+            let (account, symbol) = match balance_account(key) {
+                Some(x) => x,
+                None => continue,
+            };
+            let old = self
+                .persistent_store
+                .get(key)
+                .map_err(error::storage_get_failed)?;
+            let new = match op {
+                Op::Put(value) => Some(value.as_slice()),
+                Op::Delete => None,
+            };
+            let old = holder_key(&account, &symbol, old.as_deref());
+            let new = holder_key(&account, &symbol, new);
+            if old == new {
+                continue;
+            }
+            if let Some(old) = old {
+                updates.push((old, Op::Delete));
+            }
+            if let Some(new) = new {
+                updates.push((new, Op::Put(account.to_vec())));
+            }
+        }
+        Ok(updates)
+    }
+
+    /// Up to `count` holders of `symbol`, largest balance first, starting at
+    /// `cursor` if any. Also returns the cursor of the next page, if there are
+    /// more holders.
+    pub fn get_holders(
+        &self,
+        symbol: &Symbol,
+        count: usize,
+        cursor: Option<&[u8]>,
+    ) -> Result<(Vec<Holder>, Option<Vec<u8>>), ManyError> {
+        if !self.is_holders_index_active() {
+            return Err(error::holders_index_inactive());
+        }
+        let prefix = key_for_holders_index(symbol);
+        let start = match cursor {
+            Some(cursor) if cursor.starts_with(&prefix) => cursor.to_vec(),
+            Some(_) => return Err(error::invalid_holders_cursor()),
+            None => prefix.clone(),
+        };
+
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(prefix.as_slice()));
+        let mut it = self
+            .persistent_store
+            .iter_opt(IteratorMode::From(&start, Direction::Forward), options);
+
+        let mut holders = Vec::new();
+        for item in it.by_ref().take(count) {
+            let (k, _) = item.map_err(ManyError::unknown)?;
+            holders.push(decode_holder(&k[prefix.len()..])?);
+        }
+        let next = it
+            .next()
+            .transpose()
+            .map_err(ManyError::unknown)?
+            .map(|(k, _)| k.to_vec());
+        Ok((holders, next))
+    }
+}
+
+/// Index every non-zero balance already in the store.
+pub(crate) fn index_holders(store: &InnerStorage) -> Result<Vec<BatchEntry>, ManyError> {
+    let mut options = ReadOptions::default();
+    options.set_iterate_range(rocksdb::PrefixRange(BALANCES_ROOT));
+    let mut batch = Vec::new();
+    for item in store.iter_opt(IteratorMode::Start, options) {
+        let (k, v) = item.map_err(ManyError::unknown)?;
+        let value = Tree::decode(k.to_vec(), v.as_ref());
+        if let Some((account, symbol)) = balance_account(&k) {
+            if let Some(key) = holder_key(&account, &symbol, Some(value.value())) {
+                batch.push((key, Op::Put(account.to_vec())));
+            }
+        }
+    }
+
+    // Keys in batch must be sorted.
+    batch.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(batch)
+}
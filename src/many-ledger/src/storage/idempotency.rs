@@ -0,0 +1,112 @@
+//! Idempotency keys of commands. A client sending a command again after losing
+//! its response passes the same key, and the command is rejected instead of
+//! being executed twice.
+//!
+//! Keys are scoped to their sender and remembered for a window chosen by the
+//! client, after which the scheduler forgets them.
+use crate::error;
+use crate::storage::scheduler::{secs_since_epoch, Trigger};
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_identity::Address;
+use many_types::Timestamp;
+use merk::Op;
+
+pub const IDEMPOTENCY_ROOT: &str = "/idempotency/";
+
+/// The kind of the scheduled task forgetting an idempotency key.
+pub const IDEMPOTENCY_EXPIRY_TASK: &str = "idempotency_expiry";
+
+/// Seconds an idempotency key is remembered when none is specified.
+pub const DEFAULT_IDEMPOTENCY_WINDOW: u64 = 24 * 60 * 60;
+
+/// Maximum number of seconds an idempotency key is remembered.
+pub const MAXIMUM_IDEMPOTENCY_WINDOW: u64 = 7 * 24 * 60 * 60;
+
+/// Maximum size of an idempotency key, in bytes.
+pub const MAXIMUM_IDEMPOTENCY_KEY_SIZE: usize = 64;
+
+pub fn key_for_idempotency_key(sender: &Address, key: &[u8]) -> Vec<u8> {
+    format!("{IDEMPOTENCY_ROOT}{sender}/{}", hex::encode(key)).into_bytes()
+}
+
+fn decode_expiry(bytes: &[u8]) -> Result<u64, ManyError> {
+    Ok(u64::from_be_bytes(bytes.try_into().map_err(|_| {
+        ManyError::unknown("Invalid idempotency key expiry.".to_string())
+    })?))
+}
+
+/// Forget an idempotency key once its window is over. The payload is the
+/// storage key. A key used again since keeps its new window.
+pub fn expire_idempotency_key(
+    storage: &mut LedgerStorage,
+    payload: &[u8],
+) -> Result<(), ManyError> {
+    if let Some(expiry) = storage
+        .persistent_store
+        .get(payload)
+        .map_err(error::storage_get_failed)?
+    {
+        if decode_expiry(&expiry)? <= secs_since_epoch(storage.now())? {
+            storage.apply_to_store(&[(payload.to_vec(), Op::Delete)])?;
+        }
+    }
+    Ok(())
+}
+
+impl LedgerStorage {
+    /// Whether `sender` used `key` within its window.
+    pub fn is_idempotency_key_used(&self, sender: &Address, key: &[u8]) -> Result<bool, ManyError> {
+        match self
+            .persistent_store
+            .get(&key_for_idempotency_key(sender, key))
+            .map_err(error::storage_get_failed)?
+        {
+            Some(expiry) => Ok(decode_expiry(&expiry)? > secs_since_epoch(self.now())?),
+            None => Ok(false),
+        }
+    }
+
+    /// Check that `key` and `window` are valid, and that `sender` did not use
+    /// `key` within its window.
+    pub fn check_idempotency_key(
+        &self,
+        sender: &Address,
+        key: &[u8],
+        window: Option<u64>,
+    ) -> Result<(), ManyError> {
+        if window.unwrap_or_default() > MAXIMUM_IDEMPOTENCY_WINDOW {
+            return Err(error::invalid_idempotency_window(
+                MAXIMUM_IDEMPOTENCY_WINDOW,
+            ));
+        }
+        if key.len() > MAXIMUM_IDEMPOTENCY_KEY_SIZE {
+            return Err(error::invalid_idempotency_key(MAXIMUM_IDEMPOTENCY_KEY_SIZE));
+        }
+        if self.is_idempotency_key_used(sender, key)? {
+            return Err(error::duplicate_idempotency_key(hex::encode(key)));
+        }
+        Ok(())
+    }
+
+    /// Remember that `sender` used `key`, for `window` seconds. The key and
+    /// the window are checked by [`LedgerStorage::check_idempotency_key`].
+    pub fn record_idempotency_key(
+        &mut self,
+        sender: &Address,
+        key: &[u8],
+        window: Option<u64>,
+    ) -> Result<(), ManyError> {
+        let window = window.unwrap_or(DEFAULT_IDEMPOTENCY_WINDOW);
+        let expiry = secs_since_epoch(self.now())? + window;
+
+        let storage_key = key_for_idempotency_key(sender, key);
+        self.schedule_task(
+            Trigger::Time(Timestamp::new(expiry)?),
+            IDEMPOTENCY_EXPIRY_TASK,
+            storage_key.clone(),
+        )?;
+        self.apply_to_store(&[(storage_key, Op::Put(expiry.to_be_bytes().to_vec()))])?;
+        self.maybe_commit()
+    }
+}
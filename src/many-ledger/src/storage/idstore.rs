@@ -3,13 +3,15 @@ use crate::storage::LedgerStorage;
 use many_error::ManyError;
 use many_identity::Address;
 use many_modules::idstore;
+use merk::rocksdb::{self, IteratorMode, ReadOptions};
+use merk::tree::Tree;
 use merk::{BatchEntry, Op};
 use std::collections::BTreeMap;
 
 pub(crate) const IDSTORE_ROOT: &[u8] = b"/idstore/";
 pub(crate) const IDSTORE_SEED_ROOT: &[u8] = b"/config/idstore_seed";
 
-#[derive(Clone, minicbor::Encode, minicbor::Decode)]
+#[derive(Clone, PartialEq, minicbor::Encode, minicbor::Decode)]
 #[cbor(map)]
 struct CredentialStorage {
     #[n(0)]
@@ -22,6 +24,14 @@ struct CredentialStorage {
 enum IdStoreRootSeparator {
     RecallPhrase,
     Address,
+    /// Revoked recall phrases, which cannot be stored again.
+    Tombstone,
+    /// Every credential of an address. The address entry holds the latest one.
+    Credentials,
+    /// The addresses bound to a public-key address.
+    Aliases,
+    /// The public-key address an alias is bound to.
+    AliasOwner,
 }
 
 impl IdStoreRootSeparator {
@@ -29,8 +39,16 @@ impl IdStoreRootSeparator {
         match *self {
             IdStoreRootSeparator::RecallPhrase => b"00",
             IdStoreRootSeparator::Address => b"01",
+            IdStoreRootSeparator::Tombstone => b"02",
+            IdStoreRootSeparator::Credentials => b"03",
+            IdStoreRootSeparator::Aliases => b"04",
+            IdStoreRootSeparator::AliasOwner => b"05",
         }
     }
+
+    fn key(&self, key: &[u8]) -> Vec<u8> {
+        [IDSTORE_ROOT, self.value(), key].concat()
+    }
 }
 
 impl LedgerStorage {
@@ -63,9 +81,7 @@ impl LedgerStorage {
             }
         }
 
-        self.persistent_store
-            .apply(batch.as_slice())
-            .map_err(error::storage_apply_failed)?;
+        self.apply_to_store(batch.as_slice())?;
 
         Ok(self)
     }
@@ -81,12 +97,10 @@ impl LedgerStorage {
                 u64::from_be_bytes(bytes)
             });
 
-        self.persistent_store
-            .apply(&[(
-                IDSTORE_SEED_ROOT.to_vec(),
-                Op::Put((idstore_seed + 1).to_be_bytes().to_vec()),
-            )])
-            .map_err(error::storage_apply_failed)?;
+        self.apply_to_store(&[(
+            IDSTORE_SEED_ROOT.to_vec(),
+            Op::Put((idstore_seed + 1).to_be_bytes().to_vec()),
+        )])?;
 
         self.maybe_commit()?;
 
@@ -100,53 +114,80 @@ impl LedgerStorage {
         cred_id: idstore::CredentialId,
         public_key: idstore::PublicKey,
     ) -> Result<(), ManyError> {
-        let recall_phrase_cbor =
-            minicbor::to_vec(recall_phrase).map_err(ManyError::serialization_error)?;
-        if self
-            .persistent_store
-            .get(&recall_phrase_cbor)
-            .map_err(error::storage_get_failed)?
-            .is_some()
-        {
+        if self.is_recall_phrase_taken(recall_phrase)? {
             return Err(idstore::existing_entry());
         }
+        let recall_phrase_cbor =
+            minicbor::to_vec(recall_phrase).map_err(ManyError::serialization_error)?;
 
-        let value = minicbor::to_vec(CredentialStorage {
+        let credential = CredentialStorage {
             cred_id,
             public_key,
-        })
-        .map_err(ManyError::serialization_error)?;
+        };
+        let value = minicbor::to_vec(&credential).map_err(ManyError::serialization_error)?;
 
-        let batch = vec![
-            (
-                vec![
-                    IDSTORE_ROOT,
-                    IdStoreRootSeparator::RecallPhrase.value(),
-                    &recall_phrase_cbor,
-                ]
-                .concat(),
-                Op::Put(value.clone()),
-            ),
-            (
-                vec![
-                    IDSTORE_ROOT,
-                    IdStoreRootSeparator::Address.value(),
-                    &address.to_vec(),
-                ]
-                .concat(),
-                Op::Put(value),
-            ),
-        ];
+        // Storing a credential ID again replaces it.
+        let mut credentials = self.get_credentials(address)?;
+        credentials.retain(|c| c.cred_id != credential.cred_id);
+        credentials.push(credential);
 
-        self.persistent_store
-            .apply(&batch)
-            .map_err(error::storage_apply_failed)?;
+        // Keys in batch must be sorted.
+        let mut batch = self.credentials_batch(address, &credentials)?;
+        batch.push((
+            IdStoreRootSeparator::RecallPhrase.key(&recall_phrase_cbor),
+            Op::Put(value),
+        ));
+        batch.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        self.apply_to_store(&batch)?;
 
         self.maybe_commit()?;
 
         Ok(())
     }
 
+    /// Every credential of `address`, oldest first. Addresses stored before
+    /// multiple credentials were supported only have their address entry.
+    fn get_credentials(&self, address: &Address) -> Result<Vec<CredentialStorage>, ManyError> {
+        if let Some(value) =
+            self.get_from_storage(&address.to_vec(), IdStoreRootSeparator::Credentials)?
+        {
+            minicbor::decode(&value).map_err(ManyError::deserialization_error)
+        } else if let Some(value) =
+            self.get_from_storage(&address.to_vec(), IdStoreRootSeparator::Address)?
+        {
+            Ok(vec![
+                minicbor::decode(&value).map_err(ManyError::deserialization_error)?
+            ])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    /// The batch entries replacing the credentials of `address`, keeping the
+    /// address entry on the latest one.
+    fn credentials_batch(
+        &self,
+        address: &Address,
+        credentials: &[CredentialStorage],
+    ) -> Result<Vec<BatchEntry>, ManyError> {
+        let address_key = IdStoreRootSeparator::Address.key(&address.to_vec());
+        let credentials_key = IdStoreRootSeparator::Credentials.key(&address.to_vec());
+        Ok(match credentials.last() {
+            Some(latest) => vec![
+                (
+                    address_key,
+                    Op::Put(minicbor::to_vec(latest).map_err(ManyError::serialization_error)?),
+                ),
+                (
+                    credentials_key,
+                    Op::Put(minicbor::to_vec(credentials).map_err(ManyError::serialization_error)?),
+                ),
+            ],
+            None => vec![(address_key, Op::Delete), (credentials_key, Op::Delete)],
+        })
+    }
+
     fn get_from_storage(
         &self,
         key: &Vec<u8>,
@@ -174,6 +215,62 @@ impl LedgerStorage {
         }
     }
 
+    /// Resolve a recall phrase to the address it was stored with. Entries are only
+    /// indexed by recall phrase and by address, so this scans the address entries
+    /// for the same credential.
+    pub fn get_address_from_recall_phrase(
+        &self,
+        recall_phrase: &idstore::RecallPhrase,
+    ) -> Result<Address, ManyError> {
+        let recall_phrase_cbor =
+            minicbor::to_vec(recall_phrase).map_err(ManyError::serialization_error)?;
+        let value = self
+            .get_from_storage(&recall_phrase_cbor, IdStoreRootSeparator::RecallPhrase)?
+            .ok_or_else(|| idstore::entry_not_found(recall_phrase.join(" ")))?;
+
+        let prefix = [IDSTORE_ROOT, IdStoreRootSeparator::Address.value()].concat();
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(prefix.as_slice()));
+        for item in self.persistent_store.iter_opt(IteratorMode::Start, options) {
+            let (k, v) = item.map_err(ManyError::unknown)?;
+            if Tree::decode(k.to_vec(), v.as_ref()).value() == value.as_slice() {
+                return Address::from_bytes(&k[prefix.len()..]);
+            }
+        }
+
+        // The credential may not be the latest of its address.
+        let credential: CredentialStorage =
+            minicbor::decode(&value).map_err(ManyError::deserialization_error)?;
+        let prefix = IdStoreRootSeparator::Credentials.key(&[]);
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(prefix.as_slice()));
+        for item in self.persistent_store.iter_opt(IteratorMode::Start, options) {
+            let (k, v) = item.map_err(ManyError::unknown)?;
+            let credentials: Vec<CredentialStorage> =
+                minicbor::decode(Tree::decode(k.to_vec(), v.as_ref()).value())
+                    .map_err(ManyError::deserialization_error)?;
+            if credentials.contains(&credential) {
+                return Address::from_bytes(&k[prefix.len()..]);
+            }
+        }
+        Err(idstore::entry_not_found(recall_phrase.join(" ")))
+    }
+
+    /// Whether a recall phrase is stored or was revoked.
+    pub fn is_recall_phrase_taken(
+        &self,
+        recall_phrase: &idstore::RecallPhrase,
+    ) -> Result<bool, ManyError> {
+        let recall_phrase_cbor =
+            minicbor::to_vec(recall_phrase).map_err(ManyError::serialization_error)?;
+        Ok(self
+            .get_from_storage(&recall_phrase_cbor, IdStoreRootSeparator::RecallPhrase)?
+            .is_some()
+            || self
+                .get_from_storage(&recall_phrase_cbor, IdStoreRootSeparator::Tombstone)?
+                .is_some())
+    }
+
     pub fn get_from_address(
         &self,
         address: &Address,
@@ -188,6 +285,209 @@ impl LedgerStorage {
             Err(idstore::entry_not_found(address.to_string()))
         }
     }
+
+    /// Every credential of `address`, oldest first.
+    pub fn get_all_from_address(
+        &self,
+        address: &Address,
+    ) -> Result<Vec<(idstore::CredentialId, idstore::PublicKey)>, ManyError> {
+        let credentials = self.get_credentials(address)?;
+        if credentials.is_empty() {
+            return Err(idstore::entry_not_found(address.to_string()));
+        }
+        Ok(credentials
+            .into_iter()
+            .map(|c| (c.cred_id, c.public_key))
+            .collect())
+    }
+
+    /// Remove a credential of `address`. The recall phrases of the credential
+    /// are revoked.
+    pub fn remove_credential(
+        &mut self,
+        address: &Address,
+        cred_id: &idstore::CredentialId,
+    ) -> Result<(), ManyError> {
+        let mut credentials = self.get_credentials(address)?;
+        let (removed, kept): (Vec<_>, Vec<_>) =
+            credentials.drain(..).partition(|c| &c.cred_id == cred_id);
+        if removed.is_empty() {
+            return Err(idstore::entry_not_found(hex::encode(&*cred_id.0)));
+        }
+
+        // Keys in batch must be sorted.
+        let mut batch = self.credentials_batch(address, &kept)?;
+        for credential in removed {
+            let value = minicbor::to_vec(credential).map_err(ManyError::serialization_error)?;
+            for key in self.recall_phrase_keys_with_value(&value)? {
+                let recall_phrase_cbor =
+                    key[IdStoreRootSeparator::RecallPhrase.key(&[]).len()..].to_vec();
+                batch.push((key, Op::Delete));
+                batch.push((
+                    IdStoreRootSeparator::Tombstone.key(&recall_phrase_cbor),
+                    Op::Put(address.to_vec()),
+                ));
+            }
+        }
+        batch.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        self.apply_to_store(&batch)?;
+
+        self.maybe_commit()
+    }
+
+    /// The keys of the recall phrase entries holding `value`.
+    fn recall_phrase_keys_with_value(&self, value: &[u8]) -> Result<Vec<Vec<u8>>, ManyError> {
+        let prefix = IdStoreRootSeparator::RecallPhrase.key(&[]);
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(prefix.as_slice()));
+        let mut keys = Vec::new();
+        for item in self.persistent_store.iter_opt(IteratorMode::Start, options) {
+            let (k, v) = item.map_err(ManyError::unknown)?;
+            if Tree::decode(k.to_vec(), v.as_ref()).value() == value {
+                keys.push(k.to_vec());
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Replace the credential ID of the latest credential of `address`, and of
+    /// the recall phrases stored with it.
+    pub fn update_credential(
+        &mut self,
+        address: &Address,
+        cred_id: idstore::CredentialId,
+    ) -> Result<(), ManyError> {
+        let mut credentials = self.get_credentials(address)?;
+        let latest = credentials
+            .last_mut()
+            .ok_or_else(|| idstore::entry_not_found(address.to_string()))?;
+        let old = minicbor::to_vec(&*latest).map_err(ManyError::serialization_error)?;
+        latest.cred_id = cred_id;
+        let value = minicbor::to_vec(&*latest).map_err(ManyError::serialization_error)?;
+
+        // Keys in batch must be sorted.
+        let mut batch = self.credentials_batch(address, &credentials)?;
+        batch.extend(
+            self.recall_phrase_keys_with_value(&old)?
+                .into_iter()
+                .map(|k| (k, Op::Put(value.clone()))),
+        );
+        batch.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        self.apply_to_store(&batch)?;
+
+        self.maybe_commit()
+    }
+
+    /// Revoke a recall phrase. A tombstone is kept so the phrase is never
+    /// stored again. The credential of the phrase is removed from the address.
+    pub fn revoke_recall_phrase(
+        &mut self,
+        recall_phrase: &idstore::RecallPhrase,
+        address: &Address,
+    ) -> Result<(), ManyError> {
+        let recall_phrase_cbor =
+            minicbor::to_vec(recall_phrase).map_err(ManyError::serialization_error)?;
+        let value = self
+            .get_from_storage(&recall_phrase_cbor, IdStoreRootSeparator::RecallPhrase)?
+            .ok_or_else(|| idstore::entry_not_found(recall_phrase.join(" ")))?;
+
+        // Keys in batch must be sorted.
+        let mut batch: Vec<BatchEntry> = vec![
+            (
+                IdStoreRootSeparator::RecallPhrase.key(&recall_phrase_cbor),
+                Op::Delete,
+            ),
+            (
+                IdStoreRootSeparator::Tombstone.key(&recall_phrase_cbor),
+                Op::Put(address.to_vec()),
+            ),
+        ];
+        let credential: CredentialStorage =
+            minicbor::decode(&value).map_err(ManyError::deserialization_error)?;
+        let mut credentials = self.get_credentials(address)?;
+        if credentials.contains(&credential) {
+            credentials.retain(|c| c != &credential);
+            batch.extend(self.credentials_batch(address, &credentials)?);
+        }
+        batch.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        self.apply_to_store(&batch)?;
+
+        self.maybe_commit()
+    }
+
+    /// The aliases bound to `address`, in the order they were added.
+    pub fn get_aliases(&self, address: &Address) -> Result<Vec<Address>, ManyError> {
+        self.get_from_storage(&address.to_vec(), IdStoreRootSeparator::Aliases)?
+            .map_or(Ok(vec![]), |value| {
+                minicbor::decode(&value).map_err(ManyError::deserialization_error)
+            })
+    }
+
+    /// The address `alias` is bound to, if any.
+    pub fn get_alias_owner(&self, alias: &Address) -> Result<Option<Address>, ManyError> {
+        self.get_from_storage(&alias.to_vec(), IdStoreRootSeparator::AliasOwner)?
+            .map(|value| Address::from_bytes(&value))
+            .transpose()
+    }
+
+    /// Bind `alias` to `address`. An alias can only be bound to one address.
+    pub fn add_alias(&mut self, address: &Address, alias: &Address) -> Result<(), ManyError> {
+        if self.get_alias_owner(alias)?.is_some() {
+            return Err(idstore::existing_entry());
+        }
+        let mut aliases = self.get_aliases(address)?;
+        aliases.push(*alias);
+
+        // Keys in batch must be sorted.
+        let mut batch: Vec<BatchEntry> = vec![
+            (
+                IdStoreRootSeparator::Aliases.key(&address.to_vec()),
+                Op::Put(minicbor::to_vec(&aliases).map_err(ManyError::serialization_error)?),
+            ),
+            (
+                IdStoreRootSeparator::AliasOwner.key(&alias.to_vec()),
+                Op::Put(address.to_vec()),
+            ),
+        ];
+        batch.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        self.apply_to_store(&batch)?;
+
+        self.maybe_commit()
+    }
+
+    /// Unbind `alias` from `address`.
+    pub fn remove_alias(&mut self, address: &Address, alias: &Address) -> Result<(), ManyError> {
+        if self.get_alias_owner(alias)?.as_ref() != Some(address) {
+            return Err(idstore::entry_not_found(alias.to_string()));
+        }
+        let mut aliases = self.get_aliases(address)?;
+        aliases.retain(|a| a != alias);
+
+        let aliases_key = IdStoreRootSeparator::Aliases.key(&address.to_vec());
+        let mut batch: Vec<BatchEntry> = vec![
+            (
+                aliases_key,
+                if aliases.is_empty() {
+                    Op::Delete
+                } else {
+                    Op::Put(minicbor::to_vec(&aliases).map_err(ManyError::serialization_error)?)
+                },
+            ),
+            (
+                IdStoreRootSeparator::AliasOwner.key(&alias.to_vec()),
+                Op::Delete,
+            ),
+        ];
+        batch.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        self.apply_to_store(&batch)?;
+
+        self.maybe_commit()
+    }
 }
 
 #[cfg(test)]
@@ -196,12 +496,10 @@ pub mod tests {
 
     impl LedgerStorage {
         pub fn set_idstore_seed(&mut self, seed: u64) -> Result<(), ManyError> {
-            self.persistent_store
-                .apply(&[(
-                    IDSTORE_SEED_ROOT.to_vec(),
-                    Op::Put(seed.to_be_bytes().to_vec()),
-                )])
-                .map_err(error::storage_apply_failed)?;
+            self.apply_to_store(&[(
+                IDSTORE_SEED_ROOT.to_vec(),
+                Op::Put(seed.to_be_bytes().to_vec()),
+            )])?;
 
             self.persistent_store
                 .commit(&[])
@@ -0,0 +1,115 @@
+//! Invariants of the ledger state, recomputed after every commit when enabled.
+//!
+//! - The circulating supply of every token is the sum of its balances and of
+//!   the funds held by pending transfers and escrows. Balances are unsigned,
+//!   so an amount going below zero shows up as a supply mismatch.
+//! - Every event is in the event log, in either store, and the kind index has
+//!   one entry per event.
+//!
+//! A node finding a violation stops before the state spreads any further.
+use crate::error;
+use crate::storage::escrow::{Escrow, ESCROWS_ROOT};
+use crate::storage::event_index::prefix_for_kind_events;
+use crate::storage::growth::balance_symbol;
+use crate::storage::ledger_tokens::key_for_symbol;
+use crate::storage::pending_send::{PendingSend, PENDING_SENDS_ROOT};
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_modules::events::EventKind;
+use many_types::ledger::{Symbol, TokenAmount, TokenInfo};
+use many_types::CborRange;
+use merk::rocksdb::{self, IteratorMode, ReadOptions};
+use merk::tree::Tree;
+use std::collections::BTreeMap;
+
+const BALANCES_ROOT: &[u8] = b"/balances/";
+
+impl LedgerStorage {
+    /// The sum of the balances and of the held funds of every symbol.
+    fn held_supply(&self) -> Result<BTreeMap<Symbol, TokenAmount>, ManyError> {
+        let mut supply: BTreeMap<Symbol, TokenAmount> = BTreeMap::new();
+
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(BALANCES_ROOT));
+        for item in self.persistent_store.iter_opt(IteratorMode::Start, options) {
+            let (k, v) = item.map_err(ManyError::unknown)?;
+            if let Some(symbol) = balance_symbol(&k) {
+                let amount =
+                    TokenAmount::from(Tree::decode(k.to_vec(), v.as_ref()).value().to_vec());
+                *supply.entry(symbol).or_default() += amount;
+            }
+        }
+
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(PENDING_SENDS_ROOT));
+        for item in self.persistent_store.iter_opt(IteratorMode::Start, options) {
+            let (k, v) = item.map_err(ManyError::unknown)?;
+            let pending: PendingSend =
+                minicbor::decode(Tree::decode(k.to_vec(), v.as_ref()).value())
+                    .map_err(ManyError::deserialization_error)?;
+            *supply.entry(pending.symbol).or_default() += pending.debit();
+        }
+
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(ESCROWS_ROOT));
+        for item in self.persistent_store.iter_opt(IteratorMode::Start, options) {
+            let (k, v) = item.map_err(ManyError::unknown)?;
+            let escrow: Escrow = minicbor::decode(Tree::decode(k.to_vec(), v.as_ref()).value())
+                .map_err(ManyError::deserialization_error)?;
+            *supply.entry(escrow.symbol).or_default() += escrow.debit();
+        }
+
+        Ok(supply)
+    }
+
+    /// Recompute the invariants of the state and describe every violation.
+    /// Symbols without token information are not checked.
+    pub fn check_invariants(&self) -> Result<Vec<String>, ManyError> {
+        let mut violations = Vec::new();
+
+        let held = self.held_supply()?;
+        for symbol in self._get_symbols()? {
+            let info: TokenInfo = match self
+                .persistent_store
+                .get(key_for_symbol(&symbol).as_bytes())
+                .map_err(error::storage_get_failed)?
+            {
+                Some(bytes) => {
+                    minicbor::decode(&bytes).map_err(ManyError::deserialization_error)?
+                }
+                None => continue,
+            };
+            let circulating = info.supply.circulating;
+            let actual = held.get(&symbol).cloned().unwrap_or_default();
+            if actual != circulating {
+                violations.push(format!(
+                    "{symbol} has a circulating supply of {circulating} but {actual} in balances"
+                ));
+            }
+        }
+
+        let nb_events = self.nb_events()?;
+        let stored = self.count_events(CborRange::default())?;
+        if stored != nb_events {
+            violations.push(format!(
+                "{nb_events} events were logged but {stored} are stored"
+            ));
+        }
+
+        if self.is_event_index_active() {
+            use strum::IntoEnumIterator;
+            let mut indexed = 0;
+            for kind in EventKind::iter() {
+                indexed += self
+                    .count_indexed_events(&prefix_for_kind_events(&kind)?, CborRange::default())?;
+            }
+            if indexed != stored {
+                violations.push(format!(
+                    "{stored} events are stored but {indexed} are indexed by kind"
+                ));
+            }
+        }
+
+        Ok(violations)
+    }
+}
@@ -1,4 +1,4 @@
-use crate::storage::event::{key_for_event, EVENTS_ROOT};
+use crate::storage::event::{key_for_event_with_prefix, EVENTS_ROOT};
 use crate::storage::InnerStorage;
 use many_modules::events::EventId;
 use many_types::{CborRange, SortOrder};
@@ -47,6 +47,22 @@ impl<'a> LedgerIterator<'a> {
         Self { inner }
     }
 
+    pub fn all_allowance_events(merk: &'a InnerStorage, order: SortOrder) -> Self {
+        use crate::storage::allowance::ALLOWANCE_EVENTS_ROOT;
+
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(ALLOWANCE_EVENTS_ROOT));
+
+        let it_mode = match order {
+            SortOrder::Indeterminate | SortOrder::Ascending => IteratorMode::Start,
+            SortOrder::Descending => IteratorMode::End,
+        };
+
+        let inner = merk.iter_opt(it_mode, options);
+
+        Self { inner }
+    }
+
     pub fn all_events(merk: &'a InnerStorage) -> Self {
         Self::events_scoped_by_id(merk, CborRange::default(), SortOrder::Indeterminate)
     }
@@ -56,32 +72,54 @@ impl<'a> LedgerIterator<'a> {
         range: CborRange<EventId>,
         order: SortOrder,
     ) -> Self {
-        let mut opts = ReadOptions::default();
+        Self::events_scoped_by_prefix(merk, EVENTS_ROOT, range, order)
+    }
 
-        match range.start_bound() {
-            Bound::Included(x) => opts.set_iterate_lower_bound(key_for_event(x.clone())),
-            Bound::Excluded(x) => opts.set_iterate_lower_bound(key_for_event(x.clone() + 1)),
-            Bound::Unbounded => opts.set_iterate_lower_bound(EVENTS_ROOT),
-        }
-        match range.end_bound() {
-            Bound::Included(x) => opts.set_iterate_upper_bound(key_for_event(x.clone() + 1)),
-            Bound::Excluded(x) => opts.set_iterate_upper_bound(key_for_event(x.clone())),
-            Bound::Unbounded => {
-                let mut bound = EVENTS_ROOT.to_vec();
-                bound[EVENTS_ROOT.len() - 1] += 1;
-                opts.set_iterate_upper_bound(bound);
-            }
+    /// Iterate over the keys under `prefix` that end with an event ID in `range`,
+    /// e.g. the events themselves or the entries of an event index.
+    pub fn events_scoped_by_prefix(
+        merk: &'a InnerStorage,
+        prefix: &[u8],
+        range: CborRange<EventId>,
+        order: SortOrder,
+    ) -> Self {
+        Self {
+            inner: merk.iter_opt(
+                events_iterator_mode(order),
+                events_read_options(prefix, range),
+            ),
         }
+    }
+}
 
-        let mode = match order {
-            SortOrder::Indeterminate | SortOrder::Ascending => IteratorMode::Start,
-            SortOrder::Descending => IteratorMode::End,
-        };
+/// Read options bounding an iterator to the keys under `prefix` that end with an
+/// event ID in `range`.
+pub(crate) fn events_read_options(prefix: &[u8], range: CborRange<EventId>) -> ReadOptions {
+    let mut opts = ReadOptions::default();
+    let key = |id: EventId| key_for_event_with_prefix(prefix, id);
 
-        Self {
-            inner: merk.iter_opt(mode, opts),
+    match range.start_bound() {
+        Bound::Included(x) => opts.set_iterate_lower_bound(key(x.clone())),
+        Bound::Excluded(x) => opts.set_iterate_lower_bound(key(x.clone() + 1)),
+        Bound::Unbounded => opts.set_iterate_lower_bound(prefix),
+    }
+    match range.end_bound() {
+        Bound::Included(x) => opts.set_iterate_upper_bound(key(x.clone() + 1)),
+        Bound::Excluded(x) => opts.set_iterate_upper_bound(key(x.clone())),
+        Bound::Unbounded => {
+            let mut bound = prefix.to_vec();
+            bound[prefix.len() - 1] += 1;
+            opts.set_iterate_upper_bound(bound);
         }
     }
+    opts
+}
+
+pub(crate) fn events_iterator_mode(order: SortOrder) -> IteratorMode<'static> {
+    match order {
+        SortOrder::Indeterminate | SortOrder::Ascending => IteratorMode::Start,
+        SortOrder::Descending => IteratorMode::End,
+    }
 }
 
 impl<'a> Iterator for LedgerIterator<'a> {
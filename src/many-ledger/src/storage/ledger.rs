@@ -26,9 +26,7 @@ impl LedgerStorage {
             }
         }
 
-        self.persistent_store
-            .apply(batch.as_slice())
-            .map_err(error::storage_apply_failed)?;
+        self.apply_to_store(batch.as_slice())?;
 
         Ok(self)
     }
@@ -42,16 +40,25 @@ impl LedgerStorage {
             Ok(BTreeMap::new())
         } else {
             let mut result = BTreeMap::new();
+            let mut cache = self.balance_cache();
             for symbol in self.get_symbols()? {
-                match self
-                    .persistent_store
-                    .get(&key_for_account_balance(identity, &symbol))
-                    .map_err(error::storage_get_failed)?
-                {
-                    None => {}
-                    Some(value) => {
-                        result.insert(symbol, TokenAmount::from(value));
+                let cached = cache.as_mut().and_then(|c| c.get(identity, &symbol));
+                let amount = match cached {
+                    Some(amount) => amount,
+                    None => {
+                        let amount = self
+                            .persistent_store
+                            .get(&key_for_account_balance(identity, &symbol))
+                            .map_err(error::storage_get_failed)?
+                            .map(TokenAmount::from);
+                        if let Some(cache) = cache.as_mut() {
+                            cache.insert(identity, &symbol, amount.clone());
+                        }
+                        amount
                     }
+                };
+                if let Some(amount) = amount {
+                    result.insert(symbol, amount);
                 }
             }
 
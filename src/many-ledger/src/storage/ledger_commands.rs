@@ -1,4 +1,7 @@
+use crate::amount::CheckedTokenAmount;
 use crate::error;
+use crate::migration::insufficient_funds::INSUFFICIENT_FUNDS_MIGRATION;
+use crate::storage::fees::transfer_fee_memo;
 use crate::storage::{key_for_account_balance, LedgerStorage};
 use many_error::ManyError;
 use many_identity::Address;
@@ -6,9 +9,20 @@ use many_modules::events::EventInfo;
 use many_types::ledger::{Symbol, TokenAmount};
 use many_types::Memo;
 use merk::{BatchEntry, Op};
-use std::cmp::Ordering;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
 use tracing::info;
 
+/// The outcome of a transfer, as computed by [`LedgerStorage::prepare_send`].
+#[derive(Clone, Debug)]
+pub struct SendOutcome {
+    /// The fee paid by the sender on top of the amount, and who it is paid to.
+    pub fee: Option<(Address, TokenAmount)>,
+
+    /// The balances of every identity touched by the transfer, after it is applied.
+    pub balances: BTreeMap<Address, TokenAmount>,
+}
+
 impl LedgerStorage {
     pub fn get_balance(
         &self,
@@ -32,14 +46,14 @@ impl LedgerStorage {
         }
     }
 
-    pub fn send(
-        &mut self,
+    /// Validate a transfer and compute its outcome, without modifying the storage.
+    pub fn prepare_send(
+        &self,
         from: &Address,
         to: &Address,
         symbol: &Symbol,
-        amount: TokenAmount,
-        memo: Option<Memo>,
-    ) -> Result<(), ManyError> {
+        amount: &TokenAmount,
+    ) -> Result<SendOutcome, ManyError> {
         if from == to {
             return Err(error::destination_is_source());
         }
@@ -51,38 +65,70 @@ impl LedgerStorage {
         if to.is_anonymous() || from.is_anonymous() {
             return Err(error::anonymous_cannot_hold_funds());
         }
+        self.verify_not_frozen([from, to])?;
 
-        let mut amount_from = self.get_balance(from, symbol)?;
-        if amount > amount_from {
-            return Err(error::insufficient_funds());
+        let fee = self.transfer_fee(from, symbol, amount)?;
+        let mut debit = amount.clone();
+        if let Some((_, fee)) = &fee {
+            debit += fee.clone();
         }
 
-        info!("send({} => {}, {} {})", from, to, &amount, symbol);
+        let balance = self.get_balance(from, symbol)?;
+        let amount_from = balance
+            .checked_sub(&debit)
+            .ok_or_else(|| self.insufficient_funds(&debit, &balance))?;
+
+        let mut balances = BTreeMap::from([(*from, amount_from)]);
+        let credits = std::iter::once((to, amount)).chain(fee.iter().map(|(id, fee)| (id, fee)));
+        for (id, credit) in credits {
+            let mut balance = match balances.remove(id) {
+                Some(balance) => balance,
+                None => self.get_balance(id, symbol)?,
+            };
+            balance += credit.clone();
+            balances.insert(*id, balance);
+        }
+
+        Ok(SendOutcome { fee, balances })
+    }
+
+    pub fn send(
+        &mut self,
+        from: &Address,
+        to: &Address,
+        symbol: &Symbol,
+        amount: TokenAmount,
+        memo: Option<Memo>,
+    ) -> Result<(), ManyError> {
+        let SendOutcome { fee, balances } = self.prepare_send(from, to, symbol, &amount)?;
 
-        let mut amount_to = self.get_balance(to, symbol)?;
-        amount_to += amount.clone();
-        amount_from -= amount.clone();
+        info!("send({} => {}, {} {})", from, to, &amount, symbol);
 
         // Keys in batch must be sorted.
-        let key_from = key_for_account_balance(from, symbol);
-        let key_to = key_for_account_balance(to, symbol);
-
-        let batch: Vec<BatchEntry> = match key_from.cmp(&key_to) {
-            Ordering::Less | Ordering::Equal => vec![
-                (key_from, Op::Put(amount_from.to_vec())),
-                (key_to, Op::Put(amount_to.to_vec())),
-            ],
-            _ => vec![
-                (key_to, Op::Put(amount_to.to_vec())),
-                (key_from, Op::Put(amount_from.to_vec())),
-            ],
-        };
+        let mut batch: Vec<BatchEntry> = balances
+            .iter()
+            .map(|(id, balance)| {
+                (
+                    key_for_account_balance(id, symbol),
+                    Op::Put(balance.to_vec()),
+                )
+            })
+            .collect();
+        batch.sort_by(|(a, _), (b, _)| a.cmp(b));
 
-        self.update_account_count(from, to, amount.clone(), symbol)?;
+        // Count every balance the transfer changes, including the fee collector's.
+        let mut changes = Vec::new();
+        for (id, balance) in &balances {
+            let old = self
+                .persistent_store
+                .get(&key_for_account_balance(id, symbol))
+                .map_err(error::storage_get_failed)?
+                .map(TokenAmount::from);
+            changes.push((old, balance));
+        }
+        self.update_account_counts(changes.iter().map(|(old, new)| (old.as_ref(), *new)))?;
 
-        self.persistent_store
-            .apply(&batch)
-            .map_err(error::storage_apply_failed)?;
+        self.apply_to_store(&batch)?;
 
         self.log_event(EventInfo::Send {
             from: *from,
@@ -92,6 +138,140 @@ impl LedgerStorage {
             memo,
         })?;
 
+        if let Some((collector, fee)) = fee {
+            self.log_event(EventInfo::Send {
+                from: *from,
+                to: collector,
+                symbol: *symbol,
+                amount: fee,
+                memo: Some(transfer_fee_memo()?),
+            })?;
+        }
+
+        self.maybe_commit()?;
+
+        Ok(())
+    }
+
+    /// The balance of `id` in a set of pending balances, loading it from the
+    /// storage the first time. The stored balance is kept along, or None if the
+    /// balance key does not exist.
+    /// The error of a transfer exceeding the balance. The amount and balance
+    /// are only returned once the migration is active.
+    fn insufficient_funds(&self, amount: &TokenAmount, balance: &TokenAmount) -> ManyError {
+        if self.migrations.is_active(&INSUFFICIENT_FUNDS_MIGRATION) {
+            error::insufficient_funds(amount, balance)
+        } else {
+            error::legacy::insufficient_funds()
+        }
+    }
+
+    pub(crate) fn pending_balance<'a>(
+        &self,
+        balances: &'a mut BTreeMap<(Address, Symbol), (Option<TokenAmount>, TokenAmount)>,
+        id: &Address,
+        symbol: &Symbol,
+    ) -> Result<&'a mut TokenAmount, ManyError> {
+        let entry = match balances.entry((*id, *symbol)) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let old = self
+                    .persistent_store
+                    .get(&key_for_account_balance(id, symbol))
+                    .map_err(error::storage_get_failed)?
+                    .map(TokenAmount::from);
+                let current = old.clone().unwrap_or_else(TokenAmount::zero);
+                entry.insert((old, current))
+            }
+        };
+        Ok(&mut entry.1)
+    }
+
+    /// Apply several transfers from the same source atomically: either all of them
+    /// are applied, or none is.
+    pub fn multi_send(
+        &mut self,
+        from: &Address,
+        transfers: &[(Address, Symbol, TokenAmount)],
+        memo: Option<Memo>,
+    ) -> Result<(), ManyError> {
+        if from.is_anonymous() {
+            return Err(error::anonymous_cannot_hold_funds());
+        }
+        self.verify_not_frozen([from])?;
+
+        // Validate every transfer and compute the balances in memory first, keeping
+        // the original balances to update the account counts.
+        let mut balances: BTreeMap<(Address, Symbol), (Option<TokenAmount>, TokenAmount)> =
+            BTreeMap::new();
+        let mut events = Vec::new();
+        for (to, symbol, amount) in transfers {
+            if from == to {
+                return Err(error::destination_is_source());
+            }
+            if amount.is_zero() {
+                return Err(error::amount_is_zero());
+            }
+            if to.is_anonymous() {
+                return Err(error::anonymous_cannot_hold_funds());
+            }
+            self.verify_not_frozen([to])?;
+
+            let fee = self.transfer_fee(from, symbol, amount)?;
+            let mut debit = amount.clone();
+            if let Some((_, fee)) = &fee {
+                debit += fee.clone();
+            }
+
+            let balance = self.pending_balance(&mut balances, from, symbol)?;
+            *balance = balance
+                .checked_sub(&debit)
+                .ok_or_else(|| self.insufficient_funds(&debit, &*balance))?;
+            *self.pending_balance(&mut balances, to, symbol)? += amount.clone();
+            if let Some((collector, fee)) = &fee {
+                *self.pending_balance(&mut balances, collector, symbol)? += fee.clone();
+            }
+
+            events.push(EventInfo::Send {
+                from: *from,
+                to: *to,
+                symbol: *symbol,
+                amount: amount.clone(),
+                memo: memo.clone(),
+            });
+            if let Some((collector, fee)) = fee {
+                events.push(EventInfo::Send {
+                    from: *from,
+                    to: collector,
+                    symbol: *symbol,
+                    amount: fee,
+                    memo: Some(transfer_fee_memo()?),
+                });
+            }
+        }
+
+        info!("multi_send({} => {} transfers)", from, transfers.len());
+
+        self.update_account_counts(balances.values().map(|(old, new)| (old.as_ref(), new)))?;
+
+        // Keys in batch must be sorted.
+        let mut batch: Vec<BatchEntry> = balances
+            .iter()
+            .map(|((id, symbol), (_, balance))| {
+                (
+                    key_for_account_balance(id, symbol),
+                    Op::Put(balance.to_vec()),
+                )
+            })
+            .collect();
+        batch.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        self.apply_to_store(&batch)?;
+
+        for event in events {
+            self.log_event(event)?;
+        }
+
         self.maybe_commit()?;
 
         Ok(())
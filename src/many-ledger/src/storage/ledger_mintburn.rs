@@ -66,9 +66,7 @@ impl LedgerStorage {
             Op::Put(minicbor::to_vec(&info).map_err(ManyError::serialization_error)?),
         ));
 
-        self.persistent_store
-            .apply(batch.as_slice())
-            .map_err(error::storage_apply_failed)?;
+        self.apply_to_store(batch.as_slice())?;
 
         self.maybe_commit()?;
 
@@ -120,9 +118,7 @@ impl LedgerStorage {
             Op::Put(minicbor::to_vec(&info).map_err(ManyError::serialization_error)?),
         ));
 
-        self.persistent_store
-            .apply(batch.as_slice())
-            .map_err(error::storage_apply_failed)?;
+        self.apply_to_store(batch.as_slice())?;
 
         self.maybe_commit()?;
 
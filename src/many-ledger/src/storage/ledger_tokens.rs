@@ -130,9 +130,7 @@ impl LedgerStorage {
                     Op::Put(minicbor::to_vec(info).map_err(ManyError::serialization_error)?),
                 ));
             }
-            self.persistent_store
-                .apply(batch.as_slice())
-                .map_err(error::storage_apply_failed)?;
+            self.apply_to_store(batch.as_slice())?;
 
             let token_identity = token_identity.unwrap_or(self.get_identity(IDENTITY_ROOT)?);
             let batch: Vec<BatchEntry> = vec![
@@ -148,9 +146,7 @@ impl LedgerStorage {
                     Op::Put(token_identity.to_vec()),
                 ),
             ];
-            self.persistent_store
-                .apply(batch.as_slice())
-                .map_err(error::storage_apply_failed)?;
+            self.apply_to_store(batch.as_slice())?;
 
             self.commit_storage()?;
         }
@@ -212,12 +208,10 @@ impl LedgerStorage {
         let mut symbols = self.get_symbols_and_tickers()?;
         symbols.insert(symbol, ticker);
 
-        self.persistent_store
-            .apply(&[(
-                b"/config/symbols".to_vec(),
-                Op::Put(minicbor::to_vec(&symbols).map_err(ManyError::serialization_error)?),
-            )])
-            .map_err(error::storage_apply_failed)?;
+        self.apply_to_store(&[(
+            b"/config/symbols".to_vec(),
+            Op::Put(minicbor::to_vec(&symbols).map_err(ManyError::serialization_error)?),
+        )])?;
 
         Ok(())
     }
@@ -292,9 +286,7 @@ impl LedgerStorage {
         })?;
 
         batch.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
-        self.persistent_store
-            .apply(batch.as_slice())
-            .map_err(error::storage_apply_failed)?;
+        self.apply_to_store(batch.as_slice())?;
 
         self.maybe_commit()?;
 
@@ -382,12 +374,10 @@ impl LedgerStorage {
                 },
             };
 
-            self.persistent_store
-                .apply(&[(
-                    key_for_symbol(&symbol).into(),
-                    Op::Put(minicbor::to_vec(&info).map_err(ManyError::serialization_error)?),
-                )])
-                .map_err(error::storage_apply_failed)?;
+            self.apply_to_store(&[(
+                key_for_symbol(&symbol).into(),
+                Op::Put(minicbor::to_vec(&info).map_err(ManyError::serialization_error)?),
+            )])?;
 
             self.log_event(EventInfo::TokenUpdate {
                 symbol,
@@ -438,12 +428,10 @@ impl LedgerStorage {
             indices.push(AttributeRelatedIndex::from(ExtendedInfoKey::VisualLogo));
         }
 
-        self.persistent_store
-            .apply(&[(
-                key_for_ext_info(&symbol),
-                Op::Put(minicbor::to_vec(&ext_info).map_err(ManyError::serialization_error)?),
-            )])
-            .map_err(error::storage_apply_failed)?;
+        self.apply_to_store(&[(
+            key_for_ext_info(&symbol),
+            Op::Put(minicbor::to_vec(&ext_info).map_err(ManyError::serialization_error)?),
+        )])?;
 
         self.log_event(EventInfo::TokenAddExtendedInfo {
             symbol,
@@ -482,12 +470,10 @@ impl LedgerStorage {
             }
         }
 
-        self.persistent_store
-            .apply(&[(
-                key_for_ext_info(&symbol),
-                Op::Put(minicbor::to_vec(&ext_info).map_err(ManyError::serialization_error)?),
-            )])
-            .map_err(error::storage_apply_failed)?;
+        self.apply_to_store(&[(
+            key_for_ext_info(&symbol),
+            Op::Put(minicbor::to_vec(&ext_info).map_err(ManyError::serialization_error)?),
+        )])?;
 
         self.log_event(EventInfo::TokenRemoveExtendedInfo {
             symbol,
@@ -0,0 +1,86 @@
+//! Maximum sizes of the user provided payloads that end up in the state.
+//!
+//! Commands are accepted or refused according to them, so they are consensus
+//! rules. They are set by the initial state and read from the state, so every
+//! validator applies the same limits.
+use crate::error;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_types::Memo;
+use merk::Op;
+use minicbor::{Decode, Encode};
+
+pub const PAYLOAD_LIMITS_KEY: &[u8] = b"/config/payload_limits";
+
+/// Default maximum size of a memo, in bytes once encoded.
+pub const DEFAULT_MAX_MEMO_SIZE: usize = 4_000;
+
+/// Default maximum size of a credential public key stored in the IdStore, in bytes.
+pub const DEFAULT_MAX_CREDENTIAL_SIZE: usize = 2_048;
+
+/// Maximum sizes of the user provided payloads that end up in the state.
+#[derive(Clone, Copy, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct PayloadLimits {
+    #[n(0)]
+    pub max_memo_size: usize,
+
+    #[n(1)]
+    pub max_credential_size: usize,
+}
+
+impl Default for PayloadLimits {
+    fn default() -> Self {
+        Self {
+            max_memo_size: DEFAULT_MAX_MEMO_SIZE,
+            max_credential_size: DEFAULT_MAX_CREDENTIAL_SIZE,
+        }
+    }
+}
+
+impl PayloadLimits {
+    pub fn check_memo(&self, memo: Option<&Memo>) -> Result<(), ManyError> {
+        if let Some(memo) = memo {
+            let size = minicbor::to_vec(memo)
+                .map_err(ManyError::serialization_error)?
+                .len();
+            if size > self.max_memo_size {
+                return Err(error::memo_too_large(size, self.max_memo_size));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn check_credential(&self, credential: &[u8]) -> Result<(), ManyError> {
+        if credential.len() > self.max_credential_size {
+            return Err(error::credential_too_large(
+                credential.len(),
+                self.max_credential_size,
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl LedgerStorage {
+    /// Store the limits of the initial state. The defaults apply if None, and
+    /// nothing is written so the initial hash is unchanged.
+    pub fn with_payload_limits(mut self, limits: Option<PayloadLimits>) -> Result<Self, ManyError> {
+        if let Some(limits) = limits {
+            self.apply_to_store(&[(
+                PAYLOAD_LIMITS_KEY.to_vec(),
+                Op::Put(minicbor::to_vec(limits).map_err(ManyError::serialization_error)?),
+            )])?;
+        }
+        Ok(self)
+    }
+
+    pub fn payload_limits(&self) -> Result<PayloadLimits, ManyError> {
+        self.persistent_store
+            .get(PAYLOAD_LIMITS_KEY)
+            .map_err(error::storage_get_failed)?
+            .map_or(Ok(PayloadLimits::default()), |bytes| {
+                minicbor::decode(&bytes).map_err(ManyError::deserialization_error)
+            })
+    }
+}
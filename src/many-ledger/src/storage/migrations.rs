@@ -1,7 +1,43 @@
+use crate::error;
+use crate::migration::chunked::{
+    key_for_migration_progress, MigrationProgress, CHUNKED_MIGRATIONS,
+};
+use crate::migration::migration_heights::MIGRATION_HEIGHTS_MIGRATION;
+use crate::migration::patch::{
+    key_for_migration_patch, PatchChange, PatchRecord, PATCH_MIGRATIONS,
+};
 use crate::migration::{LedgerMigrations, MIGRATIONS};
+use crate::storage::snapshot::{
+    read_snapshot_chunk, remove_dir_if_exists, restore_snapshot, write_snapshot, SnapshotInfo,
+};
 use crate::storage::LedgerStorage;
 use many_error::ManyError;
 use many_migration::{MigrationConfig, MigrationSet};
+use merk::Op;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::path::PathBuf;
+use tracing::info;
+
+/// The log of the backups taken before activating migrations, in the backups
+/// directory. Each line is `<height> <snapshot hash> <migration names>`.
+pub const MIGRATION_LOG: &str = "migrations.log";
+
+/// The activation heights of the enabled migrations, by name. They are kept
+/// in the state so that validators running with different migration configs
+/// diverge on the next block, instead of at an activation height.
+/// They are only recorded once the [`MIGRATION_HEIGHTS_MIGRATION`] is active,
+/// so replaying the blocks before it gives the same app hashes.
+pub const MIGRATION_HEIGHTS_KEY: &[u8] = b"/config/migration_heights";
+
+/// The state after activating the migrations of a block height, as simulated
+/// by `LedgerStorage::dry_run_migrations`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MigrationDryRun {
+    pub height: u64,
+    pub migrations: Vec<String>,
+    pub hash: Vec<u8>,
+}
 
 impl LedgerStorage {
     pub fn with_migrations(
@@ -10,6 +46,7 @@ impl LedgerStorage {
     ) -> Result<Self, ManyError> {
         // NOTE: Migrations are only applied in blockchain mode when loading an existing DB
         //       It is currently NOT possible to run new code in non-blockchain mode when loading an existing DB
+        self.migration_config = migration_config.clone();
         self.migrations = migration_config
             .map_or_else(MigrationSet::empty, |config| {
                 LedgerMigrations::load(&MIGRATIONS, config, 0)
@@ -18,4 +55,300 @@ impl LedgerStorage {
 
         Ok(self)
     }
+
+    /// Back up the state in `path` before activating migrations.
+    pub fn with_migration_backups(mut self, path: Option<PathBuf>) -> Result<Self, ManyError> {
+        if let Some(path) = &path {
+            std::fs::create_dir_all(path).map_err(error::snapshot_failed)?;
+        }
+        self.migration_backups = path;
+        Ok(self)
+    }
+
+    /// Names of the enabled migrations activating at `height`.
+    fn migrations_activating_at(&self, height: u64) -> Vec<String> {
+        self.migrations
+            .values()
+            .filter(|m| m.is_enabled() && m.metadata().block_height == height)
+            .map(|m| m.name().to_string())
+            .collect()
+    }
+
+    /// The activation heights of the enabled migrations of the config.
+    pub fn migration_heights(&self) -> BTreeMap<String, u64> {
+        self.migrations
+            .values()
+            .filter(|m| m.is_enabled())
+            .map(|m| (m.name().to_string(), m.metadata().block_height))
+            .collect()
+    }
+
+    /// The activation heights recorded in the state, if any.
+    pub fn get_stored_migration_heights(&self) -> Result<Option<BTreeMap<String, u64>>, ManyError> {
+        self.persistent_store
+            .get(MIGRATION_HEIGHTS_KEY)
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+
+    /// Verify that the config agrees with the state on the migrations which
+    /// activated already. Pending migrations can be rescheduled.
+    pub(crate) fn check_migration_heights(&self) -> Result<(), ManyError> {
+        let stored = match self.get_stored_migration_heights()? {
+            Some(stored) => stored,
+            None => return Ok(()),
+        };
+        let height = self.get_height()?;
+        let configured = self.migration_heights();
+        let show = |h: Option<&u64>| h.map_or_else(|| "none".to_string(), u64::to_string);
+
+        let names = stored
+            .keys()
+            .chain(configured.keys())
+            .collect::<BTreeSet<_>>();
+        for name in names {
+            let (s, c) = (stored.get(name), configured.get(name));
+            let activated = |h: Option<&u64>| h.map_or(false, |h| *h <= height);
+            if s != c && (activated(s) || activated(c)) {
+                return Err(error::migration_height_mismatch(name, show(s), show(c)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Record the activation heights of the config in the state, if they
+    /// changed. Nothing is written before the migration recording them is
+    /// active.
+    pub(crate) fn store_migration_heights(&mut self) -> Result<(), ManyError> {
+        if !self.migrations.is_active(&MIGRATION_HEIGHTS_MIGRATION) {
+            return Ok(());
+        }
+        let heights = self.migration_heights();
+        let stored = self.get_stored_migration_heights()?;
+        if stored
+            .as_ref()
+            .map_or(heights.is_empty(), |s| *s == heights)
+        {
+            return Ok(());
+        }
+        self.apply_to_store(&[(
+            MIGRATION_HEIGHTS_KEY.to_vec(),
+            Op::Put(minicbor::to_vec(&heights).map_err(ManyError::serialization_error)?),
+        )])
+    }
+
+    /// Whether migrations change the store at `height`: migrations activate,
+    /// or chunked migrations are not done.
+    pub(crate) fn migrations_run_at(&self, height: u64) -> Result<bool, ManyError> {
+        if !self.migrations_activating_at(height).is_empty() {
+            return Ok(true);
+        }
+        for chunked in CHUNKED_MIGRATIONS {
+            if self.migrations.is_active(chunked.migration)
+                && !self
+                    .migration_progress(chunked.name())?
+                    .map_or(false, |p| p.done)
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Take a snapshot of the committed store if migrations activate at
+    /// `height`, and verify it by restoring it. The migrations must not be
+    /// activated if this fails, as the upgrade could not be rolled back.
+    pub(crate) fn backup_before_migrations(
+        &self,
+        height: u64,
+    ) -> Result<Option<SnapshotInfo>, ManyError> {
+        let dir = match &self.migration_backups {
+            Some(dir) => dir,
+            None => return Ok(None),
+        };
+        let names = self.migrations_activating_at(height);
+        if names.is_empty() {
+            return Ok(None);
+        }
+
+        let path = dir.join(format!("{height:020}.snapshot"));
+        let info = write_snapshot(&self.persistent_store, &path, height, Some(self.now()))?;
+
+        let verify_path = dir.join("verify");
+        drop(restore_snapshot(&verify_path, &info, |index| {
+            read_snapshot_chunk(&path, index)
+        })?);
+        remove_dir_if_exists(&verify_path)?;
+
+        let hash = hex::encode(info.hash.as_slice());
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(MIGRATION_LOG))
+            .and_then(|mut log| writeln!(log, "{height} {hash} {}", names.join(",")))
+            .map_err(error::snapshot_failed)?;
+
+        info!(
+            "Backed up the state at height {height} before activating {}: {}",
+            names.join(", "),
+            path.display()
+        );
+        Ok(Some(info))
+    }
+
+    /// Activate the pending migrations on a copy of the committed store, and
+    /// return the root hash after each activation height. The store itself is
+    /// not modified.
+    ///
+    /// Only the migrations are simulated: the blocks between the activation
+    /// heights are not, and chunked migrations run their first chunk.
+    pub fn dry_run_migrations(&self) -> Result<Vec<MigrationDryRun>, ManyError> {
+        let height = self.get_height()?;
+        let heights = self
+            .migrations
+            .values()
+            .filter(|m| m.is_enabled() && m.metadata().block_height > height)
+            .map(|m| m.metadata().block_height)
+            .collect::<BTreeSet<u64>>();
+        if heights.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let dir = tempfile::tempdir().map_err(error::snapshot_failed)?;
+        let snapshot = dir.path().join("dry-run.snapshot");
+        let store = dir.path().join("store");
+        let info = write_snapshot(&self.persistent_store, &snapshot, height, None)?;
+        drop(restore_snapshot(&store, &info, |index| {
+            read_snapshot_chunk(&snapshot, index)
+        })?);
+
+        let mut copy = LedgerStorage::load(&store, true, self.migration_config.clone())?;
+        let mut results = Vec::new();
+        for height in heights {
+            let migrations = copy.migrations_activating_at(height);
+            copy.migrations
+                .update_at_height(&mut copy.persistent_store, height)?;
+            copy.run_chunked_migrations(height)?;
+            copy.run_patch_migrations(height)?;
+            copy.commit_storage()?;
+            results.push(MigrationDryRun {
+                height,
+                migrations,
+                hash: copy.persistent_store.root_hash().to_vec(),
+            });
+        }
+        Ok(results)
+    }
+
+    fn migration_progress(&self, name: &str) -> Result<Option<MigrationProgress>, ManyError> {
+        self.persistent_store
+            .get(&key_for_migration_progress(name))
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+
+    /// The progress of every chunked migration which started.
+    pub fn migrations_progress(&self) -> Result<BTreeMap<String, MigrationProgress>, ManyError> {
+        let mut progress = BTreeMap::new();
+        for chunked in CHUNKED_MIGRATIONS {
+            if let Some(p) = self.migration_progress(chunked.name())? {
+                progress.insert(chunked.name().to_string(), p);
+            }
+        }
+        Ok(progress)
+    }
+
+    /// Run a chunk of every active chunked migration which is not done.
+    pub(crate) fn run_chunked_migrations(&mut self, height: u64) -> Result<(), ManyError> {
+        for chunked in CHUNKED_MIGRATIONS {
+            if !self.migrations.is_active(chunked.migration) {
+                continue;
+            }
+            let mut progress = self.migration_progress(chunked.name())?.unwrap_or_default();
+            if progress.done {
+                continue;
+            }
+
+            let chunk = (chunked.run)(
+                &mut self.persistent_store,
+                progress.cursor.as_deref().map(|c| c.as_slice()),
+                chunked.chunk_size,
+            )?;
+            progress.processed += chunk.processed;
+            progress.done = chunk.cursor.is_none();
+            progress.cursor = chunk.cursor.map(Into::into);
+            progress.height = height;
+            if progress.done {
+                info!(
+                    "Migration {} done, {} items processed",
+                    chunked.name(),
+                    progress.processed
+                );
+            }
+
+            self.apply_to_store(&[(
+                key_for_migration_progress(chunked.name()),
+                Op::Put(minicbor::to_vec(&progress).map_err(ManyError::serialization_error)?),
+            )])?;
+        }
+        Ok(())
+    }
+
+    /// The record of a patch migration, if it was applied.
+    pub fn migration_patch(&self, name: &str) -> Result<Option<PatchRecord>, ManyError> {
+        self.persistent_store
+            .get(&key_for_migration_patch(name))
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+
+    /// Apply the patch migrations activating at `height`, and record their
+    /// changes. A patch is never applied twice.
+    pub(crate) fn run_patch_migrations(&mut self, height: u64) -> Result<(), ManyError> {
+        let activating = self.migrations_activating_at(height);
+        for patch in PATCH_MIGRATIONS {
+            if !activating.iter().any(|name| name == patch.name())
+                || !self.migrations.is_active(patch.migration)
+                || self.migration_patch(patch.name())?.is_some()
+            {
+                continue;
+            }
+
+            let mut values = (patch.patch)(&self.persistent_store)?;
+            // Keys in batch must be sorted.
+            values.sort_by(|(a, _), (b, _)| a.cmp(b));
+            values.dedup_by(|(a, _), (b, _)| a == b);
+
+            let mut changes = Vec::with_capacity(values.len());
+            let mut batch = Vec::with_capacity(values.len());
+            for (key, after) in values {
+                let before = self
+                    .persistent_store
+                    .get(&key)
+                    .map_err(error::storage_get_failed)?;
+                changes.push(PatchChange {
+                    key: key.clone().into(),
+                    before: before.map(Into::into),
+                    after: after.clone().map(Into::into),
+                });
+                batch.push((key, after.map_or(Op::Delete, Op::Put)));
+            }
+            self.apply_to_store(&batch)?;
+
+            info!(
+                "Migration {} patched {} keys at height {height}",
+                patch.name(),
+                changes.len()
+            );
+            let record = PatchRecord { height, changes };
+            self.apply_to_store(&[(
+                key_for_migration_patch(patch.name()),
+                Op::Put(minicbor::to_vec(&record).map_err(ManyError::serialization_error)?),
+            )])?;
+        }
+        Ok(())
+    }
 }
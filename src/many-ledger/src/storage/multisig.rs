@@ -11,6 +11,7 @@ use many_modules::{account, events, EmptyReturn};
 use many_protocol::ResponseMessage;
 use many_types::{SortOrder, Timestamp};
 use merk::Op;
+use minicbor::{Decode, Encode};
 use std::collections::BTreeMap;
 use tracing::debug;
 
@@ -188,8 +189,49 @@ pub const MULTISIG_DEFAULT_THRESHOLD: u64 = 1;
 pub const MULTISIG_DEFAULT_TIMEOUT_IN_SECS: u64 = 60 * 60 * 24; // A day.
 pub const MULTISIG_DEFAULT_EXECUTE_AUTOMATICALLY: bool = false;
 pub const MULTISIG_MAXIMUM_TIMEOUT_IN_SECS: u64 = 185 * 60 * 60 * 24; // ~6 months.
+pub const MULTISIG_DEFAULTS_KEY: &[u8] = b"/config/multisig_defaults";
+
+/// The defaults of the network for multisig accounts created without their own
+/// values, instead of [`MULTISIG_DEFAULT_TIMEOUT_IN_SECS`] and
+/// [`MULTISIG_DEFAULT_EXECUTE_AUTOMATICALLY`].
+#[derive(Clone, Debug, Default, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct MultisigDefaults {
+    #[n(0)]
+    pub timeout_in_secs: Option<u64>,
+
+    #[n(1)]
+    pub execute_automatically: Option<bool>,
+}
 
 impl LedgerStorage {
+    pub fn with_multisig_defaults(
+        mut self,
+        defaults: Option<MultisigDefaults>,
+    ) -> Result<Self, ManyError> {
+        if let Some(defaults) = defaults {
+            if defaults.timeout_in_secs > Some(MULTISIG_MAXIMUM_TIMEOUT_IN_SECS) {
+                return Err(error::invalid_genesis(format!(
+                    "the multisig timeout cannot be more than {MULTISIG_MAXIMUM_TIMEOUT_IN_SECS} seconds"
+                )));
+            }
+            self.apply_to_store(&[(
+                MULTISIG_DEFAULTS_KEY.to_vec(),
+                Op::Put(minicbor::to_vec(defaults).map_err(ManyError::serialization_error)?),
+            )])?;
+        }
+        Ok(self)
+    }
+
+    pub fn get_multisig_defaults(&self) -> Result<MultisigDefaults, ManyError> {
+        self.persistent_store
+            .get(MULTISIG_DEFAULTS_KEY)
+            .map_err(error::storage_get_failed)?
+            .map_or(Ok(MultisigDefaults::default()), |bytes| {
+                minicbor::decode(&bytes).map_err(ManyError::deserialization_error)
+            })
+    }
+
     pub fn check_timed_out_multisig_transactions(&mut self) -> Result<(), ManyError> {
         let it = self.iter_multisig(SortOrder::Descending);
         let mut batch = vec![];
@@ -221,9 +263,7 @@ impl LedgerStorage {
         if !batch.is_empty() {
             // Reverse the batch so keys are in sorted order.
             batch.reverse();
-            self.persistent_store
-                .apply(&batch)
-                .map_err(error::storage_apply_failed)?;
+            self.apply_to_store(&batch)?;
         }
 
         self.maybe_commit()?;
@@ -243,34 +283,32 @@ impl LedgerStorage {
 
         account.needs_role(sender, [account::Role::Owner])?;
 
-        // Set the multisig threshold properly.
-        if let Ok(mut multisig) = account
+        // Accounts without the multisig feature have no defaults to set.
+        let mut multisig = account
             .features
-            .get::<account::features::multisig::MultisigAccountFeature>()
-        {
-            if let Some(threshold) = args.threshold {
-                multisig.arg.threshold = Some(threshold);
-            }
-            let timeout_in_secs = args
-                .timeout_in_secs
-                .map(|t| t.min(MULTISIG_MAXIMUM_TIMEOUT_IN_SECS));
-            if let Some(timeout_in_secs) = timeout_in_secs {
-                multisig.arg.timeout_in_secs = Some(timeout_in_secs);
-            }
-            if let Some(execute_automatically) = args.execute_automatically {
-                multisig.arg.execute_automatically = Some(execute_automatically);
-            }
-
-            account.features.insert(multisig.as_feature());
-            self.log_event(events::EventInfo::AccountMultisigSetDefaults {
-                submitter: *sender,
-                account: args.account,
-                threshold: args.threshold,
-                timeout_in_secs,
-                execute_automatically: args.execute_automatically,
-            })?;
-            self.commit_account(&args.account, account)?;
+            .get::<account::features::multisig::MultisigAccountFeature>()?;
+        if let Some(threshold) = args.threshold {
+            multisig.arg.threshold = Some(threshold);
         }
+        let timeout_in_secs = args
+            .timeout_in_secs
+            .map(|t| t.min(MULTISIG_MAXIMUM_TIMEOUT_IN_SECS));
+        if let Some(timeout_in_secs) = timeout_in_secs {
+            multisig.arg.timeout_in_secs = Some(timeout_in_secs);
+        }
+        if let Some(execute_automatically) = args.execute_automatically {
+            multisig.arg.execute_automatically = Some(execute_automatically);
+        }
+
+        account.features.insert(multisig.as_feature());
+        self.log_event(events::EventInfo::AccountMultisigSetDefaults {
+            submitter: *sender,
+            account: args.account,
+            threshold: args.threshold,
+            timeout_in_secs,
+            execute_automatically: args.execute_automatically,
+        })?;
+        self.commit_account(&args.account, account)?;
         Ok(())
     }
 
@@ -280,12 +318,10 @@ impl LedgerStorage {
         tx: &MultisigTransactionStorage,
     ) -> Result<(), ManyError> {
         debug!("{:?}", tx);
-        self.persistent_store
-            .apply(&[(
-                key_for_multisig_transaction(tx_id),
-                Op::Put(minicbor::to_vec(tx).map_err(ManyError::serialization_error)?),
-            )])
-            .map_err(error::storage_apply_failed)?;
+        self.apply_to_store(&[(
+            key_for_multisig_transaction(tx_id),
+            Op::Put(minicbor::to_vec(tx).map_err(ManyError::serialization_error)?),
+        )])?;
 
         self.maybe_commit()?;
         Ok(())
@@ -555,9 +591,7 @@ impl LedgerStorage {
         let v =
             minicbor::to_vec(storage).map_err(|e| ManyError::serialization_error(e.to_string()))?;
 
-        self.persistent_store
-            .apply(&[(key_for_multisig_transaction(tx_id), Op::Put(v))])
-            .map_err(error::storage_apply_failed)?;
+        self.apply_to_store(&[(key_for_multisig_transaction(tx_id), Op::Put(v))])?;
 
         self.maybe_commit()?;
         Ok(())
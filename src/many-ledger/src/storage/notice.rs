@@ -0,0 +1,219 @@
+//! Operational notices (upgrade schedules, maintenance windows, ...) published
+//! on-chain by the notice identity, so wallets and operators get the same
+//! messages through the protocol itself.
+//!
+//! A notice is retracted by its publisher, or forgotten by the scheduler when
+//! it expires.
+use crate::error;
+use crate::storage::scheduler::{TaskHandle, Trigger};
+use crate::storage::{LedgerStorage, IDENTITY_ROOT};
+use many_error::ManyError;
+use many_identity::Address;
+use many_types::Timestamp;
+use merk::rocksdb::{self, IteratorMode, ReadOptions};
+use merk::tree::Tree;
+use merk::Op;
+use minicbor::{Decode, Encode};
+
+pub const NOTICE_IDENTITY_ROOT: &str = "/config/notice_identity";
+pub const NOTICES_ROOT: &[u8] = b"/notices/";
+pub const NOTICE_NEXT_ID_KEY: &[u8] = b"/config/notice_next_id";
+
+/// The kind of the scheduled task forgetting an expired notice.
+pub const NOTICE_EXPIRY_TASK: &str = "notice_expiry";
+
+/// Maximum size of the title of a notice, in bytes.
+pub const MAXIMUM_NOTICE_TITLE_SIZE: usize = 256;
+
+/// Maximum size of the body of a notice, in bytes.
+pub const MAXIMUM_NOTICE_BODY_SIZE: usize = 4096;
+
+pub fn key_for_notice(id: u64) -> Vec<u8> {
+    [NOTICES_ROOT, format!("{id:020}").as_bytes()].concat()
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct Notice {
+    #[n(0)]
+    pub id: u64,
+
+    /// The identity that signed the publication.
+    #[n(1)]
+    pub author: Address,
+
+    /// When the notice was published.
+    #[n(2)]
+    pub time: Timestamp,
+
+    #[n(3)]
+    pub title: String,
+
+    #[n(4)]
+    pub body: String,
+
+    /// When the announced operation starts, e.g. an upgrade.
+    #[n(5)]
+    pub starts: Option<Timestamp>,
+
+    /// When the notice is no longer relevant.
+    #[n(6)]
+    pub expires: Option<Timestamp>,
+
+    /// The scheduled expiry task, if the notice expires.
+    #[n(7)]
+    pub task: Option<u64>,
+}
+
+impl Notice {
+    fn expiry_task(&self) -> Option<TaskHandle> {
+        self.expires.zip(self.task).map(|(expires, id)| TaskHandle {
+            trigger: Trigger::Time(expires),
+            id,
+        })
+    }
+}
+
+/// Forget an expired notice. The payload is the ID of the notice.
+pub fn expire_notice(storage: &mut LedgerStorage, payload: &[u8]) -> Result<(), ManyError> {
+    let id = u64::from_be_bytes(
+        payload
+            .try_into()
+            .map_err(|_| ManyError::unknown("Invalid notice ID.".to_string()))?,
+    );
+    // A retracted notice is already gone.
+    if storage.get_notice(id)?.is_some() {
+        storage.apply_to_store(&[(key_for_notice(id), Op::Delete)])?;
+    }
+    Ok(())
+}
+
+impl LedgerStorage {
+    pub fn with_notice_identity(
+        mut self,
+        notice_identity: Option<Address>,
+    ) -> Result<Self, ManyError> {
+        if let Some(identity) = notice_identity {
+            self.apply_to_store(&[(
+                NOTICE_IDENTITY_ROOT.as_bytes().to_vec(),
+                Op::Put(identity.to_vec()),
+            )])?;
+        }
+        Ok(self)
+    }
+
+    /// The identity allowed to publish notices. Defaults to the ledger identity.
+    pub fn get_notice_identity(&self) -> Result<Address, ManyError> {
+        self.get_identity(NOTICE_IDENTITY_ROOT)
+            .or_else(|_| self.get_identity(IDENTITY_ROOT))
+    }
+
+    pub fn get_notice(&self, id: u64) -> Result<Option<Notice>, ManyError> {
+        self.persistent_store
+            .get(&key_for_notice(id))
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+
+    /// The notices that did not expire yet, oldest first.
+    pub fn list_notices(&self) -> Result<Vec<Notice>, ManyError> {
+        let now = self.now();
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(NOTICES_ROOT));
+
+        let mut notices = Vec::new();
+        for item in self.persistent_store.iter_opt(IteratorMode::Start, options) {
+            let (k, v) = item.map_err(ManyError::unknown)?;
+            let notice: Notice = minicbor::decode(Tree::decode(k.to_vec(), v.as_ref()).value())
+                .map_err(ManyError::deserialization_error)?;
+            // Expired notices are only removed when the next block begins.
+            if notice.expires.map_or(true, |expires| expires > now) {
+                notices.push(notice);
+            }
+        }
+        Ok(notices)
+    }
+
+    fn next_notice_id(&mut self) -> Result<u64, ManyError> {
+        let id = self
+            .persistent_store
+            .get(NOTICE_NEXT_ID_KEY)
+            .map_err(error::storage_get_failed)?
+            .map_or(0, |x| {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(x.as_slice());
+                u64::from_be_bytes(bytes)
+            });
+        self.apply_to_store(&[(
+            NOTICE_NEXT_ID_KEY.to_vec(),
+            Op::Put((id + 1).to_be_bytes().to_vec()),
+        )])?;
+        Ok(id)
+    }
+
+    /// Publish a notice. Returns its ID.
+    pub fn publish_notice(
+        &mut self,
+        author: &Address,
+        title: String,
+        body: String,
+        starts: Option<Timestamp>,
+        expires: Option<Timestamp>,
+    ) -> Result<u64, ManyError> {
+        if title.is_empty() || title.len() > MAXIMUM_NOTICE_TITLE_SIZE {
+            return Err(error::invalid_notice(format!(
+                "the title must be between 1 and {MAXIMUM_NOTICE_TITLE_SIZE} bytes"
+            )));
+        }
+        if body.len() > MAXIMUM_NOTICE_BODY_SIZE {
+            return Err(error::invalid_notice(format!(
+                "the body cannot be longer than {MAXIMUM_NOTICE_BODY_SIZE} bytes"
+            )));
+        }
+        let time = self.now();
+        if expires.map_or(false, |expires| expires <= time) {
+            return Err(error::invalid_notice("the notice is already expired"));
+        }
+
+        let id = self.next_notice_id()?;
+        let task = expires
+            .map(|expires| {
+                self.schedule_task(
+                    Trigger::Time(expires),
+                    NOTICE_EXPIRY_TASK,
+                    id.to_be_bytes().to_vec(),
+                )
+            })
+            .transpose()?;
+        let notice = Notice {
+            id,
+            author: *author,
+            time,
+            title,
+            body,
+            starts,
+            expires,
+            task: task.map(|task| task.id),
+        };
+
+        self.apply_to_store(&[(
+            key_for_notice(id),
+            Op::Put(minicbor::to_vec(&notice).map_err(ManyError::serialization_error)?),
+        )])?;
+        self.maybe_commit()?;
+        Ok(id)
+    }
+
+    /// Retract a notice before it expires.
+    pub fn retract_notice(&mut self, id: u64) -> Result<(), ManyError> {
+        let notice = self
+            .get_notice(id)?
+            .ok_or_else(|| error::notice_not_found(id))?;
+        if let Some(task) = notice.expiry_task() {
+            self.cancel_task(&task)?;
+        }
+        self.apply_to_store(&[(key_for_notice(id), Op::Delete)])?;
+        self.maybe_commit()
+    }
+}
@@ -0,0 +1,287 @@
+//! Two-phase transfers. The funds (and the transfer fee) leave the sender right
+//! away and are held until the recipient accepts the transfer. If it is not
+//! accepted before its timeout, the scheduler refunds the sender. This makes a
+//! transfer to a mistyped address recoverable.
+//!
+//! The Send events are only logged when the transfer is accepted; a refunded
+//! transfer leaves no event, as the balances end up unchanged.
+use crate::error;
+use crate::storage::fees::transfer_fee_memo;
+use crate::storage::scheduler::{TaskHandle, Trigger};
+use crate::storage::{key_for_account_balance, LedgerStorage};
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::events::EventInfo;
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::{Memo, Timestamp};
+use merk::{BatchEntry, Op};
+use minicbor::{Decode, Encode};
+use std::collections::BTreeMap;
+use tracing::info;
+
+pub const PENDING_SENDS_ROOT: &[u8] = b"/pending_sends/";
+pub const PENDING_SEND_NEXT_ID_KEY: &[u8] = b"/config/pending_send_next_id";
+
+/// The kind of the scheduled task refunding a pending transfer.
+pub const PENDING_SEND_REFUND_TASK: &str = "pending_send_refund";
+
+/// Timeout of a pending transfer when none is specified, in seconds.
+pub const DEFAULT_PENDING_SEND_TIMEOUT: u64 = 24 * 60 * 60;
+
+/// Maximum timeout of a pending transfer, in seconds.
+pub const MAXIMUM_PENDING_SEND_TIMEOUT: u64 = 30 * 24 * 60 * 60;
+
+pub fn key_for_pending_send(id: u64) -> Vec<u8> {
+    [PENDING_SENDS_ROOT, format!("{id:020}").as_bytes()].concat()
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct PendingSend {
+    #[n(0)]
+    pub id: u64,
+
+    #[n(1)]
+    pub from: Address,
+
+    #[n(2)]
+    pub to: Address,
+
+    #[n(3)]
+    pub symbol: Symbol,
+
+    #[n(4)]
+    pub amount: TokenAmount,
+
+    /// The transfer fee held along the amount, and who it is paid to.
+    #[n(5)]
+    pub fee: Option<(Address, TokenAmount)>,
+
+    #[n(6)]
+    pub memo: Option<Memo>,
+
+    /// When the transfer is refunded if it was not accepted.
+    #[n(7)]
+    pub refund_at: Timestamp,
+
+    /// The scheduled refund task.
+    #[n(8)]
+    pub task: u64,
+}
+
+impl PendingSend {
+    /// The total amount held, i.e. the amount and the fee.
+    pub fn debit(&self) -> TokenAmount {
+        let mut debit = self.amount.clone();
+        if let Some((_, fee)) = &self.fee {
+            debit += fee.clone();
+        }
+        debit
+    }
+
+    fn refund_task(&self) -> TaskHandle {
+        TaskHandle {
+            trigger: Trigger::Time(self.refund_at),
+            id: self.task,
+        }
+    }
+}
+
+/// Refund a pending transfer that was not accepted in time. The payload is the
+/// ID of the transfer.
+pub fn refund_pending_send(storage: &mut LedgerStorage, payload: &[u8]) -> Result<(), ManyError> {
+    let id = u64::from_be_bytes(
+        payload
+            .try_into()
+            .map_err(|_| ManyError::unknown("Invalid pending transfer ID.".to_string()))?,
+    );
+    // An accepted transfer is already gone.
+    if let Some(pending) = storage.get_pending_send(id)? {
+        info!("refund_pending_send({id} => {})", pending.from);
+        storage.apply_pending_balances(&pending.symbol, [(&pending.from, pending.debit())])?;
+        storage.remove_pending_send(id)?;
+    }
+    Ok(())
+}
+
+impl LedgerStorage {
+    pub fn get_pending_send(&self, id: u64) -> Result<Option<PendingSend>, ManyError> {
+        self.persistent_store
+            .get(&key_for_pending_send(id))
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+
+    fn remove_pending_send(&mut self, id: u64) -> Result<(), ManyError> {
+        self.apply_to_store(&[(key_for_pending_send(id), Op::Delete)])
+    }
+
+    fn next_pending_send_id(&mut self) -> Result<u64, ManyError> {
+        let id = self
+            .persistent_store
+            .get(PENDING_SEND_NEXT_ID_KEY)
+            .map_err(error::storage_get_failed)?
+            .map_or(0, |x| {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(x.as_slice());
+                u64::from_be_bytes(bytes)
+            });
+        self.apply_to_store(&[(
+            PENDING_SEND_NEXT_ID_KEY.to_vec(),
+            Op::Put((id + 1).to_be_bytes().to_vec()),
+        )])?;
+        Ok(id)
+    }
+
+    /// Credit the held funds to `credits`, updating the account counts.
+    pub(crate) fn apply_pending_balances<'a>(
+        &mut self,
+        symbol: &Symbol,
+        credits: impl IntoIterator<Item = (&'a Address, TokenAmount)>,
+    ) -> Result<(), ManyError> {
+        let mut balances = BTreeMap::new();
+        for (id, credit) in credits {
+            *self.pending_balance(&mut balances, id, symbol)? += credit;
+        }
+        self.update_account_counts(balances.values().map(|(old, new)| (old.as_ref(), new)))?;
+
+        // Keys in batch must be sorted.
+        let mut batch: Vec<BatchEntry> = balances
+            .iter()
+            .map(|((id, symbol), (_, balance))| {
+                (
+                    key_for_account_balance(id, symbol),
+                    Op::Put(balance.to_vec()),
+                )
+            })
+            .collect();
+        batch.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        self.apply_to_store(&batch)
+    }
+
+    /// Start a transfer that `to` has to accept within `timeout` seconds. The
+    /// amount and the transfer fee are debited from `from` right away. Returns
+    /// the ID of the pending transfer.
+    pub fn send_pending(
+        &mut self,
+        from: &Address,
+        to: &Address,
+        symbol: &Symbol,
+        amount: TokenAmount,
+        memo: Option<Memo>,
+        timeout: Option<u64>,
+    ) -> Result<u64, ManyError> {
+        let timeout = timeout.unwrap_or(DEFAULT_PENDING_SEND_TIMEOUT);
+        if timeout == 0 || timeout > MAXIMUM_PENDING_SEND_TIMEOUT {
+            return Err(error::invalid_pending_timeout(
+                timeout,
+                MAXIMUM_PENDING_SEND_TIMEOUT,
+            ));
+        }
+
+        // Validates the transfer the same way a send does.
+        let outcome = self.prepare_send(from, to, symbol, &amount)?;
+        let balance = outcome
+            .balances
+            .get(from)
+            .cloned()
+            .ok_or_else(|| ManyError::unknown("Missing sender balance.".to_string()))?;
+
+        let refund_at = Timestamp::from_system_time(
+            self.now()
+                .as_system_time()?
+                .checked_add(std::time::Duration::from_secs(timeout))
+                .ok_or_else(|| ManyError::unknown("Invalid time.".to_string()))?,
+        )?;
+
+        let id = self.next_pending_send_id()?;
+        let task = self.schedule_task(
+            Trigger::Time(refund_at),
+            PENDING_SEND_REFUND_TASK,
+            id.to_be_bytes().to_vec(),
+        )?;
+        let pending = PendingSend {
+            id,
+            from: *from,
+            to: *to,
+            symbol: *symbol,
+            amount,
+            fee: outcome.fee,
+            memo,
+            refund_at,
+            task: task.id,
+        };
+
+        info!(
+            "send_pending({} => {}, {} {}, id {id})",
+            from, to, &pending.amount, symbol
+        );
+
+        let old = self.get_balance(from, symbol)?;
+        self.update_account_counts([(Some(&old), &balance)])?;
+        self.apply_to_store(&[
+            (
+                key_for_account_balance(from, symbol),
+                Op::Put(balance.to_vec()),
+            ),
+            (
+                key_for_pending_send(id),
+                Op::Put(minicbor::to_vec(&pending).map_err(ManyError::serialization_error)?),
+            ),
+        ])?;
+
+        self.maybe_commit()?;
+        Ok(id)
+    }
+
+    /// Accept a pending transfer to `to`, crediting the funds and logging the
+    /// transfer.
+    pub fn accept_pending_send(&mut self, to: &Address, id: u64) -> Result<(), ManyError> {
+        let pending = self
+            .get_pending_send(id)?
+            .ok_or_else(|| error::pending_send_not_found(id))?;
+        if &pending.to != to {
+            return Err(error::unauthorized());
+        }
+        self.verify_not_frozen([&pending.to])?;
+
+        info!("accept_pending_send({id} => {to})");
+
+        let PendingSend {
+            from,
+            to,
+            symbol,
+            amount,
+            fee,
+            memo,
+            ..
+        } = pending.clone();
+
+        let credits = std::iter::once((&to, amount.clone()))
+            .chain(fee.iter().map(|(collector, fee)| (collector, fee.clone())));
+        self.apply_pending_balances(&symbol, credits)?;
+        self.remove_pending_send(id)?;
+        self.cancel_task(&pending.refund_task())?;
+
+        self.log_event(EventInfo::Send {
+            from,
+            to,
+            symbol,
+            amount,
+            memo,
+        })?;
+        if let Some((collector, fee)) = fee {
+            self.log_event(EventInfo::Send {
+                from,
+                to: collector,
+                symbol,
+                amount: fee,
+                memo: Some(transfer_fee_memo()?),
+            })?;
+        }
+
+        self.maybe_commit()
+    }
+}
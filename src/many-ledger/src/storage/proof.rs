@@ -0,0 +1,67 @@
+//! Merkle proofs of the state against the application hash, for light clients.
+//!
+//! A proof is a merk proof of the values of some keys, or of their absence. It
+//! verifies against the root hash of the store, which is the application hash of
+//! the last committed block. While a block executes, the root hash covers its
+//! changes, so proofs are only made from a committed state, e.g. a read view.
+use crate::error;
+use crate::storage::event::key_for_event;
+use crate::storage::{key_for_account_balance, LedgerStorage};
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::events::EventId;
+use many_types::ledger::Symbol;
+use merk::proofs::Query;
+use std::collections::BTreeSet;
+
+impl LedgerStorage {
+    /// Whether the store has changes of a block which is not committed yet.
+    pub fn has_uncommitted_changes(&self) -> bool {
+        self.persistent_store.root_hash().as_slice() != self.hash().as_slice()
+    }
+
+    /// A proof of the values of `keys` against the current root hash.
+    pub fn prove(&self, keys: impl IntoIterator<Item = Vec<u8>>) -> Result<Vec<u8>, ManyError> {
+        let mut query = Query::new();
+        for key in keys {
+            query.insert_key(key);
+        }
+        self.persistent_store
+            .prove(query)
+            .map_err(error::storage_prove_failed)
+    }
+
+    /// A proof of the event `id`, or None if the event was moved to the cold
+    /// store, which is not part of the application hash.
+    pub fn prove_event(&self, id: EventId) -> Result<Option<Vec<u8>>, ManyError> {
+        let key = key_for_event(id);
+        if self
+            .persistent_store
+            .get(&key)
+            .map_err(error::storage_get_failed)?
+            .is_none()
+        {
+            return Ok(None);
+        }
+        self.prove([key]).map(Some)
+    }
+
+    /// A proof of the balances of `identity` in `symbols`, or in all the symbols
+    /// of the ledger if empty. Zero balances are proven absent.
+    pub fn prove_balances(
+        &self,
+        identity: &Address,
+        symbols: &BTreeSet<Symbol>,
+    ) -> Result<Vec<u8>, ManyError> {
+        let symbols = if symbols.is_empty() {
+            self.get_symbols()?
+        } else {
+            symbols.clone()
+        };
+        self.prove(
+            symbols
+                .iter()
+                .map(|symbol| key_for_account_balance(identity, symbol)),
+        )
+    }
+}
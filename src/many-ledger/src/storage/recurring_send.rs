@@ -0,0 +1,284 @@
+//! Recurring transfers (standing orders). Each order executes a transfer every
+//! interval, from its start until its end, on the commit of the first block at
+//! or after each due time.
+//!
+//! Like scheduled transfers, funds are not held: every execution is validated
+//! on its own. An execution failing, e.g. for insufficient funds, is counted in
+//! the order and the order keeps running. The event log has no kind for these
+//! transfers nor their failures, so executions log a Send event marked by
+//! [`recurring_send_memo`], and failures are only recorded in the order.
+//!
+//! A chain halted for several intervals catches up one execution per block.
+use crate::error;
+use crate::storage::scheduler::{secs_since_epoch, TaskHandle, Trigger};
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_identity::Address;
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::{Memo, Timestamp};
+use merk::rocksdb::{self, IteratorMode, ReadOptions};
+use merk::tree::Tree;
+use merk::Op;
+use minicbor::{Decode, Encode};
+use tracing::{info, warn};
+
+pub const RECURRING_SENDS_ROOT: &[u8] = b"/recurring_sends/";
+pub const RECURRING_SEND_NEXT_ID_KEY: &[u8] = b"/config/recurring_send_next_id";
+
+/// The kind of the scheduled task executing a recurring transfer.
+pub const RECURRING_SEND_TASK: &str = "recurring_send";
+
+/// Minimum interval between two executions of a recurring transfer, in seconds.
+pub const MINIMUM_RECURRING_INTERVAL: u64 = 60 * 60;
+
+/// Maximum delay before the first execution of a recurring transfer, in seconds.
+pub const MAXIMUM_RECURRING_START_DELAY: u64 = 365 * 24 * 60 * 60;
+
+const RECURRING_SEND_MARKER: &str = "Recurring transfer";
+
+/// The memo of the Send events of a recurring transfer: the memo of the
+/// transfer, followed by a marker.
+pub fn recurring_send_memo(memo: Option<Memo>) -> Result<Memo, ManyError> {
+    match memo {
+        Some(mut memo) => {
+            memo.push_str(RECURRING_SEND_MARKER.to_string())?;
+            Ok(memo)
+        }
+        None => Memo::try_from(RECURRING_SEND_MARKER).map_err(ManyError::unknown),
+    }
+}
+
+pub fn key_for_recurring_send(id: u64) -> Vec<u8> {
+    [RECURRING_SENDS_ROOT, format!("{id:020}").as_bytes()].concat()
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct RecurringSend {
+    #[n(0)]
+    pub id: u64,
+
+    #[n(1)]
+    pub from: Address,
+
+    #[n(2)]
+    pub to: Address,
+
+    #[n(3)]
+    pub symbol: Symbol,
+
+    #[n(4)]
+    pub amount: TokenAmount,
+
+    /// The memo of the Send events, see [`recurring_send_memo`].
+    #[n(5)]
+    pub memo: Memo,
+
+    #[n(6)]
+    pub interval_in_secs: u64,
+
+    /// When the next execution is due.
+    #[n(7)]
+    pub next: Timestamp,
+
+    /// No execution is due after this time.
+    #[n(8)]
+    pub end: Option<Timestamp>,
+
+    /// The scheduled task of the next execution.
+    #[n(9)]
+    pub task: u64,
+
+    #[n(10)]
+    pub executions: u64,
+
+    #[n(11)]
+    pub failures: u64,
+
+    #[n(12)]
+    pub last_failure: Option<Timestamp>,
+}
+
+impl RecurringSend {
+    fn next_task(&self) -> TaskHandle {
+        TaskHandle {
+            trigger: Trigger::Time(self.next),
+            id: self.task,
+        }
+    }
+}
+
+/// Execute a recurring transfer and schedule its next execution, if any. The
+/// payload is the ID of the order.
+pub fn execute_recurring_send(
+    storage: &mut LedgerStorage,
+    payload: &[u8],
+) -> Result<(), ManyError> {
+    let id = u64::from_be_bytes(
+        payload
+            .try_into()
+            .map_err(|_| ManyError::unknown("Invalid recurring transfer ID.".to_string()))?,
+    );
+    // A cancelled order is already gone.
+    let mut order = match storage.get_recurring_send(id)? {
+        Some(order) => order,
+        None => return Ok(()),
+    };
+
+    info!("execute_recurring_send({id})");
+    match storage.send(
+        &order.from,
+        &order.to,
+        &order.symbol,
+        order.amount.clone(),
+        Some(order.memo.clone()),
+    ) {
+        Ok(()) => order.executions += 1,
+        Err(e) => {
+            warn!("Recurring transfer {id} failed: {e}");
+            order.failures += 1;
+            order.last_failure = Some(storage.now());
+        }
+    }
+
+    let next = Timestamp::new(secs_since_epoch(order.next)? + order.interval_in_secs)?;
+    if order.end.map_or(false, |end| next > end) {
+        return storage.apply_to_store(&[(key_for_recurring_send(id), Op::Delete)]);
+    }
+    let task = storage.schedule_task(
+        Trigger::Time(next),
+        RECURRING_SEND_TASK,
+        id.to_be_bytes().to_vec(),
+    )?;
+    order.next = next;
+    order.task = task.id;
+    storage.put_recurring_send(&order)
+}
+
+impl LedgerStorage {
+    pub fn get_recurring_send(&self, id: u64) -> Result<Option<RecurringSend>, ManyError> {
+        self.persistent_store
+            .get(&key_for_recurring_send(id))
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+
+    fn put_recurring_send(&mut self, order: &RecurringSend) -> Result<(), ManyError> {
+        self.apply_to_store(&[(
+            key_for_recurring_send(order.id),
+            Op::Put(minicbor::to_vec(order).map_err(ManyError::serialization_error)?),
+        )])
+    }
+
+    /// The recurring transfers out of `from`, by ID.
+    pub fn list_recurring_sends(&self, from: &Address) -> Result<Vec<RecurringSend>, ManyError> {
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(RECURRING_SENDS_ROOT));
+
+        let mut orders = Vec::new();
+        for item in self.persistent_store.iter_opt(IteratorMode::Start, options) {
+            let (k, v) = item.map_err(ManyError::unknown)?;
+            let order: RecurringSend =
+                minicbor::decode(Tree::decode(k.to_vec(), v.as_ref()).value())
+                    .map_err(ManyError::deserialization_error)?;
+            if &order.from == from {
+                orders.push(order);
+            }
+        }
+        Ok(orders)
+    }
+
+    fn next_recurring_send_id(&mut self) -> Result<u64, ManyError> {
+        let id = self
+            .persistent_store
+            .get(RECURRING_SEND_NEXT_ID_KEY)
+            .map_err(error::storage_get_failed)?
+            .map_or(0, |x| {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(x.as_slice());
+                u64::from_be_bytes(bytes)
+            });
+        self.apply_to_store(&[(
+            RECURRING_SEND_NEXT_ID_KEY.to_vec(),
+            Op::Put((id + 1).to_be_bytes().to_vec()),
+        )])?;
+        Ok(id)
+    }
+
+    /// Create a recurring transfer, first executing at `start` (defaults to the
+    /// current time, i.e. the commit of this block) then every interval until
+    /// `end`. Returns the ID of the order.
+    #[allow(clippy::too_many_arguments)]
+    pub fn subscribe(
+        &mut self,
+        from: &Address,
+        to: &Address,
+        symbol: &Symbol,
+        amount: TokenAmount,
+        memo: Memo,
+        interval_in_secs: u64,
+        start: Option<Timestamp>,
+        end: Option<Timestamp>,
+    ) -> Result<u64, ManyError> {
+        if interval_in_secs < MINIMUM_RECURRING_INTERVAL {
+            return Err(error::invalid_recurring_send(format!(
+                "the interval must be at least {MINIMUM_RECURRING_INTERVAL} seconds"
+            )));
+        }
+        let now = self.now();
+        let start = start.unwrap_or(now);
+        let delay = secs_since_epoch(start)?.checked_sub(secs_since_epoch(now)?);
+        if delay.map_or(true, |delay| delay > MAXIMUM_RECURRING_START_DELAY) {
+            return Err(error::invalid_recurring_send(format!(
+                "the start cannot be in the past nor more than {MAXIMUM_RECURRING_START_DELAY} seconds away"
+            )));
+        }
+        if end.map_or(false, |end| end < start) {
+            return Err(error::invalid_recurring_send("the end is before the start"));
+        }
+
+        self.verify_future_send(from, to, symbol, &amount)?;
+
+        let id = self.next_recurring_send_id()?;
+        let task = self.schedule_task(
+            Trigger::Time(start),
+            RECURRING_SEND_TASK,
+            id.to_be_bytes().to_vec(),
+        )?;
+        let order = RecurringSend {
+            id,
+            from: *from,
+            to: *to,
+            symbol: *symbol,
+            amount,
+            memo,
+            interval_in_secs,
+            next: start,
+            end,
+            task: task.id,
+            executions: 0,
+            failures: 0,
+            last_failure: None,
+        };
+
+        info!(
+            "subscribe({} => {}, {} {} every {interval_in_secs} seconds, id {id})",
+            from, to, &order.amount, symbol
+        );
+
+        self.put_recurring_send(&order)?;
+        self.maybe_commit()?;
+        Ok(id)
+    }
+
+    /// Cancel a recurring transfer. Past executions are not affected.
+    pub fn unsubscribe(&mut self, id: u64) -> Result<(), ManyError> {
+        let order = self
+            .get_recurring_send(id)?
+            .ok_or_else(|| error::recurring_send_not_found(id))?;
+        self.cancel_task(&order.next_task())?;
+        self.apply_to_store(&[(key_for_recurring_send(id), Op::Delete)])?;
+        self.maybe_commit()
+    }
+}
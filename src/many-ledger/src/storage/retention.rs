@@ -0,0 +1,20 @@
+//! Block retention. With a number of blocks to retain, the commit returns a
+//! rolling retain height so Tendermint prunes the older blocks. The state is
+//! left untouched, so each node can retain a different number of blocks.
+use crate::storage::LedgerStorage;
+
+impl LedgerStorage {
+    pub fn with_retain_blocks(mut self, retain_blocks: Option<u64>) -> Self {
+        self.retain_blocks = retain_blocks;
+        self
+    }
+
+    /// The lowest height to retain once block `height` is committed, or 0 to
+    /// retain every block.
+    pub fn retain_height(&self, height: u64) -> u64 {
+        match self.retain_blocks {
+            Some(blocks) if blocks > 0 && height > blocks => height - blocks + 1,
+            _ => 0,
+        }
+    }
+}
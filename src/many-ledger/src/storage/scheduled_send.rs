@@ -0,0 +1,220 @@
+//! Future-dated transfers. The transfer is recorded with its execution time and
+//! applied by the scheduler on the commit of the first block at or after it.
+//!
+//! Funds are not held in the meantime: the transfer is validated again when it
+//! executes, and dropped if it fails then, e.g. for insufficient funds. The
+//! event log has no kind for scheduled transfers, so the Send event of an
+//! executed transfer is marked by [`scheduled_send_memo`] instead.
+use crate::error;
+use crate::storage::scheduler::{secs_since_epoch, TaskHandle, Trigger};
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_identity::Address;
+use many_types::ledger::{Symbol, TokenAmount};
+use many_types::{Memo, Timestamp};
+use merk::Op;
+use minicbor::{Decode, Encode};
+use tracing::info;
+
+pub const SCHEDULED_SENDS_ROOT: &[u8] = b"/scheduled_sends/";
+pub const SCHEDULED_SEND_NEXT_ID_KEY: &[u8] = b"/config/scheduled_send_next_id";
+
+/// The kind of the scheduled task executing a scheduled transfer.
+pub const SCHEDULED_SEND_TASK: &str = "scheduled_send";
+
+/// Maximum delay before a scheduled transfer executes, in seconds.
+pub const MAXIMUM_SCHEDULED_SEND_DELAY: u64 = 365 * 24 * 60 * 60;
+
+const SCHEDULED_SEND_MARKER: &str = "Scheduled transfer";
+
+/// The memo of the Send event of a scheduled transfer: the memo of the
+/// transfer, followed by a marker.
+pub fn scheduled_send_memo(memo: Option<Memo>) -> Result<Memo, ManyError> {
+    match memo {
+        Some(mut memo) => {
+            memo.push_str(SCHEDULED_SEND_MARKER.to_string())?;
+            Ok(memo)
+        }
+        None => Memo::try_from(SCHEDULED_SEND_MARKER).map_err(ManyError::unknown),
+    }
+}
+
+pub fn key_for_scheduled_send(id: u64) -> Vec<u8> {
+    [SCHEDULED_SENDS_ROOT, format!("{id:020}").as_bytes()].concat()
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct ScheduledSend {
+    #[n(0)]
+    pub id: u64,
+
+    #[n(1)]
+    pub from: Address,
+
+    #[n(2)]
+    pub to: Address,
+
+    #[n(3)]
+    pub symbol: Symbol,
+
+    #[n(4)]
+    pub amount: TokenAmount,
+
+    /// The memo of the Send event, see [`scheduled_send_memo`].
+    #[n(5)]
+    pub memo: Memo,
+
+    #[n(6)]
+    pub execute_at: Timestamp,
+
+    /// The scheduled execution task.
+    #[n(7)]
+    pub task: u64,
+}
+
+impl ScheduledSend {
+    fn execution_task(&self) -> TaskHandle {
+        TaskHandle {
+            trigger: Trigger::Time(self.execute_at),
+            id: self.task,
+        }
+    }
+}
+
+/// Execute a scheduled transfer. The payload is the ID of the transfer.
+pub fn execute_scheduled_send(
+    storage: &mut LedgerStorage,
+    payload: &[u8],
+) -> Result<(), ManyError> {
+    let id = u64::from_be_bytes(
+        payload
+            .try_into()
+            .map_err(|_| ManyError::unknown("Invalid scheduled transfer ID.".to_string()))?,
+    );
+    // A cancelled transfer is already gone.
+    if let Some(scheduled) = storage.get_scheduled_send(id)? {
+        info!("execute_scheduled_send({id})");
+        storage.apply_to_store(&[(key_for_scheduled_send(id), Op::Delete)])?;
+        let ScheduledSend {
+            from,
+            to,
+            symbol,
+            amount,
+            memo,
+            ..
+        } = scheduled;
+        storage.send(&from, &to, &symbol, amount, Some(memo))?;
+    }
+    Ok(())
+}
+
+impl LedgerStorage {
+    pub fn get_scheduled_send(&self, id: u64) -> Result<Option<ScheduledSend>, ManyError> {
+        self.persistent_store
+            .get(&key_for_scheduled_send(id))
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+
+    fn next_scheduled_send_id(&mut self) -> Result<u64, ManyError> {
+        let id = self
+            .persistent_store
+            .get(SCHEDULED_SEND_NEXT_ID_KEY)
+            .map_err(error::storage_get_failed)?
+            .map_or(0, |x| {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(x.as_slice());
+                u64::from_be_bytes(bytes)
+            });
+        self.apply_to_store(&[(
+            SCHEDULED_SEND_NEXT_ID_KEY.to_vec(),
+            Op::Put((id + 1).to_be_bytes().to_vec()),
+        )])?;
+        Ok(id)
+    }
+
+    /// Validate a transfer executing later, except for the balance which is only
+    /// checked when it executes.
+    pub(crate) fn verify_future_send(
+        &self,
+        from: &Address,
+        to: &Address,
+        symbol: &Symbol,
+        amount: &TokenAmount,
+    ) -> Result<(), ManyError> {
+        if from == to {
+            return Err(error::destination_is_source());
+        }
+        if amount.is_zero() {
+            return Err(error::amount_is_zero());
+        }
+        if to.is_anonymous() || from.is_anonymous() {
+            return Err(error::anonymous_cannot_hold_funds());
+        }
+        if !self.get_symbols()?.contains(symbol) {
+            return Err(error::unknown_symbol(symbol));
+        }
+        self.verify_not_frozen([from, to])
+    }
+
+    /// Record a transfer to execute at `execute_at`, with the memo of its Send
+    /// event. Returns the ID of the scheduled transfer.
+    pub fn send_scheduled(
+        &mut self,
+        from: &Address,
+        to: &Address,
+        symbol: &Symbol,
+        amount: TokenAmount,
+        memo: Memo,
+        execute_at: Timestamp,
+    ) -> Result<u64, ManyError> {
+        let now = secs_since_epoch(self.now())?;
+        let at = secs_since_epoch(execute_at)?;
+        if at <= now || at - now > MAXIMUM_SCHEDULED_SEND_DELAY {
+            return Err(error::invalid_scheduled_time(MAXIMUM_SCHEDULED_SEND_DELAY));
+        }
+
+        self.verify_future_send(from, to, symbol, &amount)?;
+
+        let id = self.next_scheduled_send_id()?;
+        let task = self.schedule_task(
+            Trigger::Time(execute_at),
+            SCHEDULED_SEND_TASK,
+            id.to_be_bytes().to_vec(),
+        )?;
+        let scheduled = ScheduledSend {
+            id,
+            from: *from,
+            to: *to,
+            symbol: *symbol,
+            amount,
+            memo,
+            execute_at,
+            task: task.id,
+        };
+
+        info!(
+            "send_scheduled({} => {}, {} {}, id {id})",
+            from, to, &scheduled.amount, symbol
+        );
+
+        self.apply_to_store(&[(
+            key_for_scheduled_send(id),
+            Op::Put(minicbor::to_vec(&scheduled).map_err(ManyError::serialization_error)?),
+        )])?;
+        self.maybe_commit()?;
+        Ok(id)
+    }
+
+    /// Cancel a scheduled transfer before it executes.
+    pub fn cancel_scheduled_send(&mut self, id: u64) -> Result<(), ManyError> {
+        let scheduled = self
+            .get_scheduled_send(id)?
+            .ok_or_else(|| error::scheduled_send_not_found(id))?;
+        self.cancel_task(&scheduled.execution_task())?;
+        self.apply_to_store(&[(key_for_scheduled_send(id), Op::Delete)])?;
+        self.maybe_commit()
+    }
+}
@@ -0,0 +1,209 @@
+//! Deterministic scheduling of work at a future height or time.
+//!
+//! Features like vesting, scheduled sends or expiries register a handler for a
+//! kind of task, then schedule tasks of that kind with an opaque payload. Tasks
+//! are kept in merk, so they survive restarts and are part of the application
+//! hash, and run on commit in key order (by trigger, then by scheduling order).
+//!
+//! Handlers are not persisted and must be registered every time the storage is
+//! opened. A task whose handler fails, or has no handler, is dropped.
+use crate::error;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_types::Timestamp;
+use merk::rocksdb::{self, ReadOptions};
+use merk::tree::Tree;
+use merk::Op;
+use minicbor::{Decode, Encode};
+use rocksdb::IteratorMode;
+use std::time::UNIX_EPOCH;
+use tracing::warn;
+
+pub const SCHEDULER_ROOT: &str = "/scheduler/";
+pub const SCHEDULER_NEXT_ID_KEY: &[u8] = b"/config/scheduler_next_id";
+
+/// Run a scheduled task, with its payload.
+pub type TaskHandler = fn(&mut LedgerStorage, &[u8]) -> Result<(), ManyError>;
+
+/// When a task should run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Trigger {
+    /// On the commit of the block at this height, or the first one after.
+    Height(u64),
+
+    /// On the commit of the first block whose time is at or after this time.
+    Time(Timestamp),
+}
+
+/// A task as returned when scheduling it, to cancel it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TaskHandle {
+    pub trigger: Trigger,
+    pub id: u64,
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+#[cbor(map)]
+struct ScheduledTask {
+    #[n(0)]
+    kind: String,
+
+    #[cbor(n(1), with = "minicbor::bytes")]
+    payload: Vec<u8>,
+}
+
+pub(crate) fn secs_since_epoch(time: Timestamp) -> Result<u64, ManyError> {
+    Ok(time
+        .as_system_time()?
+        .duration_since(UNIX_EPOCH)
+        .map_err(ManyError::unknown)?
+        .as_secs())
+}
+
+fn prefix_for_trigger(trigger: &Trigger) -> Result<String, ManyError> {
+    Ok(match trigger {
+        Trigger::Height(height) => format!("{SCHEDULER_ROOT}height/{height:020}/"),
+        Trigger::Time(time) => format!("{SCHEDULER_ROOT}time/{:020}/", secs_since_epoch(*time)?),
+    })
+}
+
+fn key_for_task(handle: &TaskHandle) -> Result<Vec<u8>, ManyError> {
+    Ok(format!("{}{:020}", prefix_for_trigger(&handle.trigger)?, handle.id).into_bytes())
+}
+
+impl LedgerStorage {
+    /// Register the handler of a kind of task, replacing any previous one.
+    pub fn register_task_handler(&mut self, kind: &'static str, handler: TaskHandler) {
+        self.task_handlers.insert(kind, handler);
+    }
+
+    pub fn with_task_handler(mut self, kind: &'static str, handler: TaskHandler) -> Self {
+        self.register_task_handler(kind, handler);
+        self
+    }
+
+    fn next_task_id(&mut self) -> Result<u64, ManyError> {
+        let id = self
+            .persistent_store
+            .get(SCHEDULER_NEXT_ID_KEY)
+            .map_err(error::storage_get_failed)?
+            .map_or(0, |x| {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(x.as_slice());
+                u64::from_be_bytes(bytes)
+            });
+        self.apply_to_store(&[(
+            SCHEDULER_NEXT_ID_KEY.to_vec(),
+            Op::Put((id + 1).to_be_bytes().to_vec()),
+        )])?;
+        Ok(id)
+    }
+
+    /// Schedule a task of `kind` to run once `trigger` is reached. A trigger
+    /// already reached runs on the next commit.
+    pub fn schedule_task(
+        &mut self,
+        trigger: Trigger,
+        kind: &str,
+        payload: Vec<u8>,
+    ) -> Result<TaskHandle, ManyError> {
+        let handle = TaskHandle {
+            trigger,
+            id: self.next_task_id()?,
+        };
+        let task = ScheduledTask {
+            kind: kind.to_string(),
+            payload,
+        };
+        self.apply_to_store(&[(
+            key_for_task(&handle)?,
+            Op::Put(minicbor::to_vec(task).map_err(ManyError::serialization_error)?),
+        )])?;
+        self.maybe_commit()?;
+        Ok(handle)
+    }
+
+    /// Cancel a task. Returns false if the task does not exist (anymore).
+    pub fn cancel_task(&mut self, handle: &TaskHandle) -> Result<bool, ManyError> {
+        let key = key_for_task(handle)?;
+        if self
+            .persistent_store
+            .get(&key)
+            .map_err(error::storage_get_failed)?
+            .is_none()
+        {
+            return Ok(false);
+        }
+        self.apply_to_store(&[(key, Op::Delete)])?;
+        self.maybe_commit()?;
+        Ok(true)
+    }
+
+    /// The keys and tasks under `prefix` whose trigger is reached, i.e. whose key
+    /// is below `end`.
+    fn due_tasks(
+        &self,
+        prefix: &str,
+        end: Vec<u8>,
+    ) -> Result<Vec<(Vec<u8>, ScheduledTask)>, ManyError> {
+        let mut options = ReadOptions::default();
+        options.set_iterate_lower_bound(prefix.as_bytes());
+        options.set_iterate_upper_bound(end);
+
+        self.persistent_store
+            .iter_opt(IteratorMode::Start, options)
+            .map(|item| {
+                let (k, v) = item.map_err(ManyError::unknown)?;
+                let tree = Tree::decode(k.to_vec(), v.as_ref());
+                let task =
+                    minicbor::decode(tree.value()).map_err(ManyError::deserialization_error)?;
+                Ok((k.to_vec(), task))
+            })
+            .collect()
+    }
+
+    /// Run every task whose trigger is reached at `height` and the current time,
+    /// height triggers first. Called on commit.
+    pub(crate) fn run_scheduled_tasks(&mut self, height: u64) -> Result<(), ManyError> {
+        let height_end = format!("{SCHEDULER_ROOT}height/{:020}", height + 1);
+        let time_end = format!(
+            "{SCHEDULER_ROOT}time/{:020}",
+            secs_since_epoch(self.now())? + 1
+        );
+        let mut tasks = self.due_tasks(&format!("{SCHEDULER_ROOT}height/"), height_end.into())?;
+        tasks.extend(self.due_tasks(&format!("{SCHEDULER_ROOT}time/"), time_end.into())?);
+
+        for (key, task) in tasks {
+            // The iterator only sees committed tasks, skip the ones cancelled since.
+            if self
+                .persistent_store
+                .get(&key)
+                .map_err(error::storage_get_failed)?
+                .is_none()
+            {
+                continue;
+            }
+
+            // Remove the task first, so a handler can reschedule the same work.
+            self.apply_to_store(&[(key.clone(), Op::Delete)])?;
+
+            match self.task_handlers.get(task.kind.as_str()).copied() {
+                Some(handler) => {
+                    if let Err(e) = handler(self, &task.payload) {
+                        warn!(
+                            "Scheduled task {} ({}) failed: {e}",
+                            String::from_utf8_lossy(&key),
+                            task.kind
+                        );
+                    }
+                }
+                None => warn!(
+                    "No handler for scheduled task {} ({})",
+                    String::from_utf8_lossy(&key),
+                    task.kind
+                ),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+use crate::error;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_identity::Address;
+use merk::Op;
+
+pub fn key_for_sequence(id: &Address) -> Vec<u8> {
+    format!("/sequences/{id}").into_bytes()
+}
+
+impl LedgerStorage {
+    /// The number of commands `id` executed successfully.
+    pub fn get_sequence(&self, id: &Address) -> Result<u64, ManyError> {
+        Ok(self
+            .persistent_store
+            .get(&key_for_sequence(id))
+            .map_err(error::storage_get_failed)?
+            .map_or(0, |bytes| {
+                let mut be = [0u8; 8];
+                be.copy_from_slice(&bytes[..8]);
+                u64::from_be_bytes(be)
+            }))
+    }
+
+    /// Count a command executed by `id`, returning its new sequence number.
+    pub fn increment_sequence(&mut self, id: &Address) -> Result<u64, ManyError> {
+        let sequence = self.get_sequence(id)? + 1;
+        self.apply_to_store(&[(
+            key_for_sequence(id),
+            Op::Put(sequence.to_be_bytes().to_vec()),
+        )])?;
+        self.maybe_commit()?;
+        Ok(sequence)
+    }
+}
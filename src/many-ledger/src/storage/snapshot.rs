@@ -0,0 +1,487 @@
+//! Ledger snapshots.
+//!
+//! Every `interval` heights, right after the commit, the whole merk tree is
+//! exported as chunks into a single archive file. Archives are portable: they
+//! can be copied to another machine and restored, and they are served to new
+//! nodes through ABCI state sync. A node restores the chunks in a separate
+//! store, which merk verifies against the application hash of the snapshot,
+//! and then swaps it in place of its own store.
+//!
+//! An archive is a CBOR array of the [`SnapshotInfo`] and of the array of
+//! chunks. Old archives are pruned by count and by age, measured in block time.
+//!
+//! Only the merk tree is part of a snapshot. The auxiliary data of merk (e.g. the
+//! genesis report) and the events moved to a cold store are not.
+use crate::error;
+use crate::migration::{LedgerMigrations, MIGRATIONS};
+use crate::storage::scheduler::secs_since_epoch;
+use crate::storage::{InnerStorage, LedgerStorage};
+use many_error::ManyError;
+use many_migration::MigrationSet;
+use many_types::Timestamp;
+use merk::restore::Restorer;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Decoder, Encode, Encoder};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// The only snapshot format, merk chunks.
+pub const SNAPSHOT_FORMAT: u32 = 1;
+
+/// Default number of snapshots kept on disk.
+pub const DEFAULT_SNAPSHOTS_KEPT: usize = 2;
+
+const SNAPSHOT_EXTENSION: &str = "snapshot";
+
+/// The archive header is small, only its beginning is read when listing.
+const MAX_HEADER_SIZE: u64 = 1024;
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct SnapshotInfo {
+    #[n(0)]
+    pub height: u64,
+
+    #[n(1)]
+    pub format: u32,
+
+    /// Number of chunks of the snapshot.
+    #[n(2)]
+    pub chunks: u32,
+
+    /// The application hash at the snapshot height.
+    #[n(3)]
+    pub hash: ByteVec,
+
+    /// The block time when the snapshot was taken.
+    #[n(4)]
+    pub time: Option<Timestamp>,
+}
+
+/// Where and how often snapshots are taken, and how long they are kept.
+pub struct Snapshots {
+    dir: PathBuf,
+    interval: u64,
+    keep: usize,
+    max_age: Option<u64>,
+}
+
+impl Snapshots {
+    pub fn new<P: AsRef<Path>>(dir: P, interval: u64) -> Result<Self, ManyError> {
+        if interval == 0 {
+            return Err(error::invalid_snapshot("the interval must be above 0"));
+        }
+        std::fs::create_dir_all(dir.as_ref()).map_err(error::snapshot_failed)?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+            interval,
+            keep: DEFAULT_SNAPSHOTS_KEPT,
+            max_age: None,
+        })
+    }
+
+    /// Keep at most `keep` snapshots, and remove the snapshots older than
+    /// `max_age` seconds. The latest snapshot is never removed.
+    pub fn with_retention(self, keep: usize, max_age: Option<u64>) -> Self {
+        Self {
+            keep: keep.max(1),
+            max_age,
+            ..self
+        }
+    }
+
+    fn path(&self, height: u64) -> PathBuf {
+        self.dir
+            .join(format!("{height:020}"))
+            .with_extension(SNAPSHOT_EXTENSION)
+    }
+
+    /// The heights of the snapshots on disk, in ascending order.
+    fn heights(&self) -> Result<Vec<u64>, ManyError> {
+        let mut heights = std::fs::read_dir(&self.dir)
+            .map_err(error::snapshot_failed)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().and_then(|e| e.to_str()) == Some(SNAPSHOT_EXTENSION))
+            .filter_map(|path| path.file_stem()?.to_str()?.parse::<u64>().ok())
+            .collect::<Vec<_>>();
+        heights.sort_unstable();
+        Ok(heights)
+    }
+
+    fn info(&self, height: u64) -> Result<SnapshotInfo, ManyError> {
+        read_snapshot_info(self.path(height))
+    }
+
+    /// The snapshots to remove, given the current time in seconds.
+    fn expired(&self, now: u64) -> Result<Vec<u64>, ManyError> {
+        let heights = self.heights()?;
+        let older = match heights.split_last() {
+            Some((_latest, older)) => older,
+            None => return Ok(vec![]),
+        };
+
+        let by_count = older.len().saturating_sub(self.keep - 1);
+        let mut expired = older[..by_count].to_vec();
+        if let Some(max_age) = self.max_age {
+            for height in &older[by_count..] {
+                let time = match self.info(*height)?.time {
+                    Some(time) => secs_since_epoch(time)?,
+                    None => continue,
+                };
+                if now.saturating_sub(time) > max_age {
+                    expired.push(*height);
+                }
+            }
+        }
+        Ok(expired)
+    }
+}
+
+/// Read the header of a snapshot archive.
+pub fn read_snapshot_info<P: AsRef<Path>>(path: P) -> Result<SnapshotInfo, ManyError> {
+    let mut header = Vec::new();
+    std::fs::File::open(path.as_ref())
+        .map_err(|_| error::snapshot_not_found(path.as_ref().display()))?
+        .take(MAX_HEADER_SIZE)
+        .read_to_end(&mut header)
+        .map_err(error::snapshot_failed)?;
+
+    let mut d = Decoder::new(&header);
+    d.array().map_err(error::invalid_snapshot)?;
+    d.decode().map_err(error::invalid_snapshot)
+}
+
+/// Read a chunk of a snapshot archive.
+pub fn read_snapshot_chunk<P: AsRef<Path>>(path: P, index: u32) -> Result<Vec<u8>, ManyError> {
+    let bytes = std::fs::read(path.as_ref())
+        .map_err(|_| error::snapshot_not_found(path.as_ref().display()))?;
+
+    let mut d = Decoder::new(&bytes);
+    d.array().map_err(error::invalid_snapshot)?;
+    d.skip().map_err(error::invalid_snapshot)?;
+    let len = d
+        .array()
+        .map_err(error::invalid_snapshot)?
+        .unwrap_or_default();
+    if u64::from(index) >= len {
+        return Err(error::invalid_snapshot(format!("unknown chunk {index}")));
+    }
+    for _ in 0..index {
+        d.skip().map_err(error::invalid_snapshot)?;
+    }
+    Ok(d.bytes().map_err(error::invalid_snapshot)?.to_vec())
+}
+
+/// Export every chunk of `store` into an archive. The archive is written to a
+/// temporary file first, so a partial archive is never listed.
+pub(crate) fn write_snapshot(
+    store: &InnerStorage,
+    path: &Path,
+    height: u64,
+    time: Option<Timestamp>,
+) -> Result<SnapshotInfo, ManyError> {
+    let mut chunks = store.chunks().map_err(error::snapshot_failed)?;
+    let len = chunks.len();
+    let info = SnapshotInfo {
+        height,
+        format: SNAPSHOT_FORMAT,
+        chunks: len as u32,
+        hash: store.root_hash().to_vec().into(),
+        time,
+    };
+
+    let mut e = Encoder::new(Vec::new());
+    e.array(2)
+        .and_then(|e| e.encode(&info))
+        .and_then(|e| e.array(len as u64))
+        .map_err(ManyError::serialization_error)?;
+    for index in 0..len {
+        let chunk = chunks.chunk(index).map_err(error::snapshot_failed)?;
+        e.bytes(&chunk).map_err(ManyError::serialization_error)?;
+    }
+
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, e.into_writer()).map_err(error::snapshot_failed)?;
+    std::fs::rename(&tmp, path).map_err(error::snapshot_failed)?;
+    Ok(info)
+}
+
+/// Restore a snapshot into a new store at `path`, reading its chunks from
+/// `chunk`. Merk verifies every chunk against the snapshot hash.
+pub fn restore_snapshot<P: AsRef<Path>>(
+    path: P,
+    info: &SnapshotInfo,
+    mut chunk: impl FnMut(u32) -> Result<Vec<u8>, ManyError>,
+) -> Result<InnerStorage, ManyError> {
+    if info.format != SNAPSHOT_FORMAT {
+        return Err(error::invalid_snapshot(format!(
+            "unknown format {}",
+            info.format
+        )));
+    }
+    let hash: merk::Hash = info
+        .hash
+        .as_slice()
+        .try_into()
+        .map_err(|_| error::invalid_snapshot("invalid hash"))?;
+
+    remove_dir_if_exists(path.as_ref())?;
+    let mut restorer = InnerStorage::restore(path.as_ref(), hash, info.chunks as usize)
+        .map_err(error::snapshot_failed)?;
+    for index in 0..info.chunks {
+        restorer
+            .process_chunk(&chunk(index)?)
+            .map_err(error::invalid_snapshot)?;
+    }
+    restorer.finalize().map_err(error::invalid_snapshot)
+}
+
+/// Restore the snapshot archive at `archive` into a new store at `path`,
+/// replacing the store there. When `app_hash` is given, e.g. the application
+/// hash the chain recorded at the snapshot height, the snapshot must match it.
+/// The existing store is only removed once the snapshot restored successfully.
+pub fn restore_snapshot_archive<A: AsRef<Path>, P: AsRef<Path>>(
+    archive: A,
+    path: P,
+    app_hash: Option<&[u8]>,
+) -> Result<SnapshotInfo, ManyError> {
+    let info = read_snapshot_info(archive.as_ref())?;
+    if let Some(app_hash) = app_hash {
+        if info.hash.as_slice() != app_hash {
+            return Err(error::invalid_snapshot(
+                "the snapshot hash is not the application hash",
+            ));
+        }
+    }
+
+    let tmp_path = path.as_ref().with_extension("restore");
+    drop(restore_snapshot(&tmp_path, &info, |index| {
+        read_snapshot_chunk(archive.as_ref(), index)
+    })?);
+    remove_dir_if_exists(path.as_ref())?;
+    std::fs::rename(&tmp_path, path.as_ref()).map_err(error::snapshot_failed)?;
+    Ok(info)
+}
+
+pub(crate) fn remove_dir_if_exists(path: &Path) -> Result<(), ManyError> {
+    match std::fs::remove_dir_all(path) {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(error::snapshot_failed(e)),
+    }
+}
+
+/// A snapshot being restored.
+pub(crate) struct Restore {
+    restorer: Restorer,
+    path: PathBuf,
+    height: u64,
+    next_chunk: u32,
+}
+
+impl LedgerStorage {
+    pub fn with_snapshots(mut self, snapshots: Option<Snapshots>) -> Self {
+        self.snapshots = snapshots;
+        self
+    }
+
+    /// Take a snapshot of the committed store if `height` is on the interval,
+    /// and prune the expired snapshots.
+    pub(crate) fn maybe_take_snapshot(&self, height: u64) -> Result<(), ManyError> {
+        let snapshots = match &self.snapshots {
+            Some(snapshots) if height % snapshots.interval == 0 => snapshots,
+            _ => return Ok(()),
+        };
+
+        let path = snapshots.path(height);
+        if !path.exists() {
+            let info = write_snapshot(&self.persistent_store, &path, height, Some(self.now()))?;
+            info!(
+                "Took snapshot at height {height}: {} chunks, hash={}",
+                info.chunks,
+                hex::encode(info.hash.as_slice())
+            );
+        }
+
+        for old in snapshots.expired(secs_since_epoch(self.now())?)? {
+            std::fs::remove_file(snapshots.path(old)).map_err(error::snapshot_failed)?;
+        }
+        Ok(())
+    }
+
+    /// The snapshots available, in ascending height.
+    pub fn list_snapshots(&self) -> Result<Vec<SnapshotInfo>, ManyError> {
+        let snapshots = match &self.snapshots {
+            Some(snapshots) => snapshots,
+            None => return Ok(vec![]),
+        };
+
+        snapshots
+            .heights()?
+            .into_iter()
+            .map(|height| snapshots.info(height))
+            .collect()
+    }
+
+    /// The most recent snapshot, if any.
+    pub fn latest_snapshot(&self) -> Result<Option<SnapshotInfo>, ManyError> {
+        match &self.snapshots {
+            Some(snapshots) => snapshots
+                .heights()?
+                .last()
+                .map(|height| snapshots.info(*height))
+                .transpose(),
+            None => Ok(None),
+        }
+    }
+
+    pub fn load_snapshot_chunk(
+        &self,
+        height: u64,
+        format: u32,
+        chunk: u32,
+    ) -> Result<Vec<u8>, ManyError> {
+        if format != SNAPSHOT_FORMAT {
+            return Err(error::invalid_snapshot(format!("unknown format {format}")));
+        }
+        let snapshots = self
+            .snapshots
+            .as_ref()
+            .ok_or_else(|| error::snapshot_not_found(height))?;
+
+        let path = snapshots.path(height);
+        if !path.exists() {
+            return Err(error::snapshot_not_found(height));
+        }
+        read_snapshot_chunk(path, chunk)
+    }
+
+    /// Start restoring a snapshot. Only a store which never committed a block can
+    /// be restored.
+    pub fn offer_snapshot(
+        &mut self,
+        snapshot: SnapshotInfo,
+        app_hash: &[u8],
+    ) -> Result<(), ManyError> {
+        if snapshot.format != SNAPSHOT_FORMAT {
+            return Err(error::invalid_snapshot(format!(
+                "unknown format {}",
+                snapshot.format
+            )));
+        }
+        if self.get_height()? != 0 {
+            return Err(error::invalid_snapshot("the store already has blocks"));
+        }
+        if snapshot.hash.as_slice() != app_hash {
+            return Err(error::invalid_snapshot(
+                "the snapshot hash is not the application hash",
+            ));
+        }
+        let hash: merk::Hash = snapshot
+            .hash
+            .as_slice()
+            .try_into()
+            .map_err(|_| error::invalid_snapshot("invalid hash"))?;
+
+        let path = self.restore_path();
+        remove_dir_if_exists(&path)?;
+        let restorer = InnerStorage::restore(&path, hash, snapshot.chunks as usize)
+            .map_err(error::snapshot_failed)?;
+
+        self.restore = Some(Restore {
+            restorer,
+            path,
+            height: snapshot.height,
+            next_chunk: 0,
+        });
+        Ok(())
+    }
+
+    /// Restore a chunk of the offered snapshot. Chunks must be applied in order.
+    /// Once the last chunk is applied, the restored store replaces this store.
+    pub fn apply_snapshot_chunk(&mut self, index: u32, chunk: &[u8]) -> Result<(), ManyError> {
+        let restore = self
+            .restore
+            .as_mut()
+            .ok_or_else(|| error::invalid_snapshot("no snapshot was offered"))?;
+        if index != restore.next_chunk {
+            return Err(error::invalid_snapshot(format!(
+                "expected chunk {}, got {index}",
+                restore.next_chunk
+            )));
+        }
+
+        let remaining = match restore.restorer.process_chunk(chunk) {
+            Ok(remaining) => remaining,
+            Err(e) => {
+                // The snapshot is rejected as a whole.
+                self.restore = None;
+                return Err(error::invalid_snapshot(e));
+            }
+        };
+        restore.next_chunk += 1;
+        if remaining > 0 {
+            return Ok(());
+        }
+
+        let Restore {
+            restorer,
+            path,
+            height,
+            ..
+        } = self.restore.take().unwrap();
+        let restored = restorer.finalize().map_err(error::invalid_snapshot)?;
+        self.swap_store(restored, &path)?;
+        info!("Restored snapshot at height {height}");
+        Ok(())
+    }
+
+    fn restore_path(&self) -> PathBuf {
+        let mut path = self.persistent_path.clone().into_os_string();
+        path.push(".restore");
+        PathBuf::from(path)
+    }
+
+    /// A checkpoint of the committed store at `path`, sharing its files.
+    pub(crate) fn checkpoint(&self, path: &Path) -> Result<InnerStorage, ManyError> {
+        self.persistent_store
+            .checkpoint(path)
+            .map_err(error::snapshot_failed)
+    }
+
+    /// Replace the persistent store with a restored store, moving it to the
+    /// persistent path. A checkpoint is used since an open store cannot be moved.
+    pub(crate) fn swap_store(
+        &mut self,
+        restored: InnerStorage,
+        restored_path: &Path,
+    ) -> Result<(), ManyError> {
+        drop(std::mem::replace(&mut self.persistent_store, restored));
+        remove_dir_if_exists(&self.persistent_path)?;
+
+        let store = self
+            .persistent_store
+            .checkpoint(&self.persistent_path)
+            .map_err(error::snapshot_failed)?;
+        drop(std::mem::replace(&mut self.persistent_store, store));
+        self.clear_balance_cache();
+        if let Err(e) = std::fs::remove_dir_all(restored_path) {
+            warn!("Could not remove the restored store: {e}");
+        }
+
+        // Reset everything derived from the height, as `load` does.
+        let height = self.get_height()?;
+        self.current_hash = Some(self.persistent_store.root_hash().to_vec());
+        self.migrations = self
+            .migration_config
+            .clone()
+            .map_or_else(MigrationSet::empty, |config| {
+                LedgerMigrations::load(&MIGRATIONS, config, height)
+            })
+            .map_err(error::unable_to_load_migrations)?;
+        self.latest_tid = self.event_id_base(height + 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+//! Named sub-accounts of an identity, e.g. for departments. The address of a
+//! sub-account is a subresource of its parent's address, and the parent always
+//! owns it. Sub-accounts are regular accounts otherwise, and their lifecycle is
+//! logged with the account events.
+use crate::error;
+use crate::module::account::validate_account;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_identity::Address;
+use many_modules::account::features::FeatureSet;
+use many_modules::{account, events};
+use merk::Op;
+use minicbor::{Decode, Encode};
+use std::collections::BTreeMap;
+
+/// Maximum length of a sub-account name, in bytes.
+pub const MAXIMUM_SUB_ACCOUNT_NAME_LENGTH: usize = 64;
+
+pub fn key_for_sub_accounts(parent: &Address) -> Vec<u8> {
+    format!("/sub_accounts/{parent}").into_bytes()
+}
+
+/// The sub-accounts of a parent, by name.
+#[derive(Clone, Debug, Default, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct SubAccounts {
+    /// The subresource ID of the next sub-account.
+    #[n(0)]
+    pub next_id: u32,
+
+    #[n(1)]
+    pub accounts: BTreeMap<String, Address>,
+}
+
+fn validate_name(name: &str) -> Result<(), ManyError> {
+    if name.is_empty()
+        || name.len() > MAXIMUM_SUB_ACCOUNT_NAME_LENGTH
+        || name.chars().any(|c| c.is_control() || c == '/')
+    {
+        return Err(error::invalid_sub_account_name(name));
+    }
+    Ok(())
+}
+
+impl LedgerStorage {
+    pub fn get_sub_accounts(&self, parent: &Address) -> Result<SubAccounts, ManyError> {
+        self.persistent_store
+            .get(&key_for_sub_accounts(parent))
+            .map_err(error::storage_get_failed)?
+            .map_or_else(
+                || Ok(SubAccounts::default()),
+                |bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error),
+            )
+    }
+
+    /// Create a sub-account of `parent` named `name`. Returns its address.
+    pub fn create_sub_account(
+        &mut self,
+        parent: &Address,
+        name: String,
+        features: FeatureSet,
+    ) -> Result<Address, ManyError> {
+        if !parent.is_public_key() {
+            return Err(error::invalid_sub_account_parent(parent));
+        }
+        validate_name(&name)?;
+
+        let mut sub_accounts = self.get_sub_accounts(parent)?;
+        if sub_accounts.accounts.contains_key(&name) {
+            return Err(error::sub_account_exists(name));
+        }
+
+        let id = parent.with_subresource_id(sub_accounts.next_id)?;
+        sub_accounts.next_id += 1;
+        sub_accounts.accounts.insert(name.clone(), id);
+
+        // The parent owns the account, and the account owns itself.
+        let mut account = account::Account::create(
+            parent,
+            account::CreateArgs {
+                description: Some(name),
+                roles: None,
+                features,
+            },
+        );
+        account.add_role(&id, account::Role::Owner);
+        validate_account(&account)?;
+
+        self.apply_to_store(&[(
+            key_for_sub_accounts(parent),
+            Op::Put(minicbor::to_vec(&sub_accounts).map_err(ManyError::serialization_error)?),
+        )])?;
+
+        self.log_event(events::EventInfo::AccountCreate {
+            account: id,
+            description: account.description.clone(),
+            roles: account.roles.clone(),
+            features: account.features.clone(),
+        })?;
+        self.commit_account(&id, account)?;
+        Ok(id)
+    }
+
+    /// Disable the sub-account of `parent` named `name`.
+    pub fn disable_sub_account(&mut self, parent: &Address, name: &str) -> Result<(), ManyError> {
+        let id = self
+            .get_sub_accounts(parent)?
+            .accounts
+            .get(name)
+            .copied()
+            .ok_or_else(|| error::sub_account_not_found(name))?;
+        self.disable_account(&id)
+    }
+}
@@ -0,0 +1,185 @@
+//! Supply figures of every symbol. The total and circulating supplies are kept
+//! in the token information by mints and burns; the number of holders, i.e.
+//! of non-zero balances, is counted as balances are written once the holder
+//! count migration is active.
+use crate::amount::CheckedTokenAmount;
+use crate::error;
+use crate::migration::supply::HOLDER_COUNT_MIGRATION;
+use crate::storage::freeze::FROZEN_ROOT;
+use crate::storage::growth::balance_symbol;
+use crate::storage::ledger_tokens::key_for_symbol;
+use crate::storage::{InnerStorage, LedgerStorage};
+use many_error::ManyError;
+use many_identity::Address;
+use many_types::ledger::{Symbol, TokenAmount, TokenInfo};
+use merk::rocksdb::{self, IteratorMode, ReadOptions};
+use merk::{BatchEntry, Op};
+use minicbor::{Decode, Encode};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+pub const HOLDERS_ROOT: &str = "/supply/holders/";
+
+const BALANCES_ROOT: &[u8] = b"/balances/";
+
+pub fn key_for_holder_count(symbol: &Symbol) -> Vec<u8> {
+    format!("{HOLDERS_ROOT}{symbol}").into_bytes()
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct SymbolSupply {
+    /// Every token minted and not burnt.
+    #[n(0)]
+    pub total: TokenAmount,
+
+    /// The circulating supply, less the balances of frozen accounts.
+    #[n(1)]
+    pub circulating: TokenAmount,
+
+    /// Number of accounts with a non-zero balance, if the holder count
+    /// migration is active.
+    #[n(2)]
+    pub holders: Option<u64>,
+}
+
+fn is_holding(amount: Option<&[u8]>) -> bool {
+    amount.map_or(false, |amount| {
+        !TokenAmount::from(amount.to_vec()).is_zero()
+    })
+}
+
+/// The change in the number of holders of each symbol when applying `batch`.
+pub(crate) fn holder_deltas(
+    store: &InnerStorage,
+    batch: &[BatchEntry],
+) -> Result<BTreeMap<Symbol, i64>, ManyError> {
+    let mut deltas = BTreeMap::new();
+    for (key, op) in batch {
+        let symbol = match balance_symbol(key) {
+            Some(symbol) => symbol,
+            None => continue,
+        };
+        let old = store.get(key).map_err(error::storage_get_failed)?;
+        let new = match op {
+            Op::Put(value) => Some(value.as_slice()),
+            Op::Delete => None,
+        };
+        match (is_holding(old.as_deref()), is_holding(new)) {
+            (false, true) => *deltas.entry(symbol).or_default() += 1,
+            (true, false) => *deltas.entry(symbol).or_default() -= 1,
+            _ => {}
+        }
+    }
+    Ok(deltas)
+}
+
+impl LedgerStorage {
+    pub fn is_holder_count_active(&self) -> bool {
+        self.migrations.is_active(&HOLDER_COUNT_MIGRATION)
+    }
+
+    pub fn get_holder_count(&self, symbol: &Symbol) -> Result<u64, ManyError> {
+        Ok(self
+            .persistent_store
+            .get(&key_for_holder_count(symbol))
+            .map_err(error::storage_get_failed)?
+            .map_or(0, |x| {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(x.as_slice());
+                u64::from_be_bytes(bytes)
+            }))
+    }
+
+    /// The updated holder counts after applying `batch`, to apply along it.
+    pub(crate) fn holder_count_updates(
+        &self,
+        batch: &[BatchEntry],
+    ) -> Result<Vec<BatchEntry>, ManyError> {
+        holder_deltas(&self.persistent_store, batch)?
+            .into_iter()
+            .filter(|(_, delta)| *delta != 0)
+            .map(|(symbol, delta)| {
+                let count = self.get_holder_count(&symbol)?.saturating_add_signed(delta);
+                Ok((
+                    key_for_holder_count(&symbol),
+                    Op::Put(count.to_be_bytes().to_vec()),
+                ))
+            })
+            .collect()
+    }
+
+    /// The accounts currently frozen.
+    fn frozen_accounts(&self) -> Result<Vec<Address>, ManyError> {
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(FROZEN_ROOT.as_bytes()));
+        self.persistent_store
+            .iter_opt(IteratorMode::Start, options)
+            .map(|item| {
+                let (k, _) = item.map_err(ManyError::unknown)?;
+                let key = std::str::from_utf8(&k).map_err(ManyError::unknown)?;
+                Address::from_str(&key[FROZEN_ROOT.len()..])
+            })
+            .collect()
+    }
+
+    /// The supply figures of `symbol`. Requires the token information of the
+    /// symbol.
+    pub fn get_supply(&self, symbol: &Symbol) -> Result<SymbolSupply, ManyError> {
+        let info: TokenInfo = self
+            .persistent_store
+            .get(key_for_symbol(symbol).as_bytes())
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()?
+            .ok_or_else(|| error::token_info_not_found(symbol))?;
+
+        let mut frozen = TokenAmount::zero();
+        for account in self.frozen_accounts()? {
+            frozen += self.get_balance(&account, symbol)?;
+        }
+        let circulating = info
+            .supply
+            .circulating
+            .checked_sub(&frozen)
+            .unwrap_or_else(TokenAmount::zero);
+
+        Ok(SymbolSupply {
+            total: info.supply.total,
+            circulating,
+            holders: self
+                .is_holder_count_active()
+                .then(|| self.get_holder_count(symbol))
+                .transpose()?,
+        })
+    }
+}
+
+/// Count the holders of every symbol already in the store.
+pub(crate) fn count_holders(store: &InnerStorage) -> Result<Vec<BatchEntry>, ManyError> {
+    let mut counts: BTreeMap<Symbol, u64> = BTreeMap::new();
+    let mut options = ReadOptions::default();
+    options.set_iterate_range(rocksdb::PrefixRange(BALANCES_ROOT));
+    for item in store.iter_opt(IteratorMode::Start, options) {
+        let (k, v) = item.map_err(ManyError::unknown)?;
+        let value = merk::tree::Tree::decode(k.to_vec(), v.as_ref());
+        if let Some(symbol) = balance_symbol(&k) {
+            if is_holding(Some(value.value())) {
+                *counts.entry(symbol).or_default() += 1;
+            }
+        }
+    }
+
+    // Keys in batch must be sorted.
+    let mut batch: Vec<BatchEntry> = counts
+        .into_iter()
+        .map(|(symbol, count)| {
+            (
+                key_for_holder_count(&symbol),
+                Op::Put(count.to_be_bytes().to_vec()),
+            )
+        })
+        .collect();
+    batch.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(batch)
+}
@@ -0,0 +1,68 @@
+use crate::error;
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+use many_types::ledger::Symbol;
+use merk::{BatchEntry, Op};
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+use std::collections::BTreeMap;
+
+pub fn key_for_token_metadata(symbol: &Symbol) -> Vec<u8> {
+    format!("/config/token_metadata/{symbol}").into_bytes()
+}
+
+/// Display information of a symbol, for wallets and explorers.
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct TokenMetadata {
+    /// Number of decimals of an amount, e.g. 9 means 1 token is 10^9 units.
+    #[n(0)]
+    pub decimals: u64,
+
+    #[n(1)]
+    pub ticker: String,
+
+    #[n(2)]
+    pub description: Option<String>,
+
+    /// Hash of the logo, so clients can verify a logo fetched from elsewhere.
+    #[n(3)]
+    pub logo_hash: Option<ByteVec>,
+}
+
+impl LedgerStorage {
+    pub fn with_token_metadata(
+        mut self,
+        metadata: Option<BTreeMap<Symbol, TokenMetadata>>,
+    ) -> Result<Self, ManyError> {
+        let metadata = match metadata {
+            Some(metadata) if !metadata.is_empty() => metadata,
+            _ => return Ok(self),
+        };
+
+        let symbols = self.get_symbols()?;
+        let mut batch: Vec<BatchEntry> = Vec::new();
+        for (symbol, meta) in metadata {
+            if !symbols.contains(&symbol) {
+                return Err(error::unknown_symbol(symbol));
+            }
+            batch.push((
+                key_for_token_metadata(&symbol),
+                Op::Put(minicbor::to_vec(meta).map_err(ManyError::serialization_error)?),
+            ));
+        }
+        batch.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        self.apply_to_store(&batch)?;
+
+        Ok(self)
+    }
+
+    pub fn get_token_metadata(&self, symbol: &Symbol) -> Result<Option<TokenMetadata>, ManyError> {
+        self.persistent_store
+            .get(&key_for_token_metadata(symbol))
+            .map_err(error::storage_get_failed)?
+            .map(|bytes| minicbor::decode(&bytes).map_err(ManyError::deserialization_error))
+            .transpose()
+    }
+}
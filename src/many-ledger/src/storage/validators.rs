@@ -0,0 +1,146 @@
+//! The validator set of the network, managed by the validator identity.
+//!
+//! Changes are kept as pending updates until the end of the block, when the
+//! ABCI bridge takes them and returns them to Tendermint from `EndBlock`. The
+//! pending updates live under a single key, as iterators only see committed
+//! state.
+use crate::error;
+use crate::storage::{LedgerStorage, IDENTITY_ROOT};
+use many_error::ManyError;
+use many_identity::Address;
+use merk::rocksdb::{self, IteratorMode, ReadOptions};
+use merk::tree::Tree;
+use merk::Op;
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+
+pub const VALIDATOR_IDENTITY_ROOT: &str = "/config/validator_identity";
+pub const VALIDATORS_ROOT: &[u8] = b"/validators/";
+pub const VALIDATOR_UPDATES_KEY: &[u8] = b"/config/validator_updates";
+
+/// Size of an Ed25519 public key, the only key type Tendermint validators use
+/// by default.
+pub const VALIDATOR_PUBLIC_KEY_SIZE: usize = 32;
+
+/// Maximum voting power of a validator, as bounded by Tendermint.
+pub const MAXIMUM_VALIDATOR_POWER: u64 = (i64::MAX / 8) as u64;
+
+pub fn key_for_validator(public_key: &[u8]) -> Vec<u8> {
+    [VALIDATORS_ROOT, public_key].concat()
+}
+
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+#[cbor(map)]
+pub struct Validator {
+    /// The Ed25519 public key of the validator.
+    #[n(0)]
+    pub public_key: ByteVec,
+
+    /// The voting power. An update with a power of 0 removes the validator.
+    #[n(1)]
+    pub power: u64,
+}
+
+impl LedgerStorage {
+    pub fn with_validator_identity(
+        mut self,
+        validator_identity: Option<Address>,
+    ) -> Result<Self, ManyError> {
+        if let Some(identity) = validator_identity {
+            self.apply_to_store(&[(
+                VALIDATOR_IDENTITY_ROOT.as_bytes().to_vec(),
+                Op::Put(identity.to_vec()),
+            )])?;
+        }
+        Ok(self)
+    }
+
+    /// The identity allowed to change the validator set. Defaults to the
+    /// ledger identity.
+    pub fn get_validator_identity(&self) -> Result<Address, ManyError> {
+        self.get_identity(VALIDATOR_IDENTITY_ROOT)
+            .or_else(|_| self.get_identity(IDENTITY_ROOT))
+    }
+
+    /// The validators added through this ledger as of the last commit, ordered
+    /// by public key. Validators of the Tendermint genesis are only listed once
+    /// updated.
+    pub fn list_validators(&self) -> Result<Vec<Validator>, ManyError> {
+        let mut options = ReadOptions::default();
+        options.set_iterate_range(rocksdb::PrefixRange(VALIDATORS_ROOT));
+
+        let mut validators = Vec::new();
+        for item in self.persistent_store.iter_opt(IteratorMode::Start, options) {
+            let (k, v) = item.map_err(ManyError::unknown)?;
+            let value = Tree::decode(k.to_vec(), v.as_ref()).value().to_vec();
+            validators.push(Validator {
+                public_key: k[VALIDATORS_ROOT.len()..].to_vec().into(),
+                power: u64::from_be_bytes(
+                    value
+                        .try_into()
+                        .map_err(|_| ManyError::unknown("Invalid validator power."))?,
+                ),
+            });
+        }
+        Ok(validators)
+    }
+
+    fn get_validator_updates(&self) -> Result<Vec<Validator>, ManyError> {
+        self.persistent_store
+            .get(VALIDATOR_UPDATES_KEY)
+            .map_err(error::storage_get_failed)?
+            .map_or(Ok(vec![]), |bytes| {
+                minicbor::decode(&bytes).map_err(ManyError::deserialization_error)
+            })
+    }
+
+    /// Add a validator, change its voting power, or remove it with a power of 0.
+    pub fn update_validator(&mut self, public_key: &[u8], power: u64) -> Result<(), ManyError> {
+        if public_key.len() != VALIDATOR_PUBLIC_KEY_SIZE {
+            return Err(error::invalid_validator(format!(
+                "the public key must be {VALIDATOR_PUBLIC_KEY_SIZE} bytes"
+            )));
+        }
+        if power > MAXIMUM_VALIDATOR_POWER {
+            return Err(error::invalid_validator(format!(
+                "the power cannot be more than {MAXIMUM_VALIDATOR_POWER}"
+            )));
+        }
+
+        // Only the last update of a validator in a block counts.
+        let mut updates = self.get_validator_updates()?;
+        updates.retain(|update| update.public_key.as_slice() != public_key);
+        updates.push(Validator {
+            public_key: public_key.to_vec().into(),
+            power,
+        });
+
+        let mut batch = vec![(
+            VALIDATOR_UPDATES_KEY.to_vec(),
+            Op::Put(minicbor::to_vec(&updates).map_err(ManyError::serialization_error)?),
+        )];
+        let key = key_for_validator(public_key);
+        if power > 0 {
+            batch.push((key, Op::Put(power.to_be_bytes().to_vec())));
+        } else if self
+            .persistent_store
+            .get(&key)
+            .map_err(error::storage_get_failed)?
+            .is_some()
+        {
+            // Validators of the Tendermint genesis are not stored.
+            batch.push((key, Op::Delete));
+        }
+        self.apply_to_store(&batch)?;
+        self.maybe_commit()
+    }
+
+    /// The validator updates of the current block, cleared once taken.
+    pub fn take_validator_updates(&mut self) -> Result<Vec<Validator>, ManyError> {
+        let updates = self.get_validator_updates()?;
+        if !updates.is_empty() {
+            self.apply_to_store(&[(VALIDATOR_UPDATES_KEY.to_vec(), Op::Delete)])?;
+        }
+        Ok(updates)
+    }
+}
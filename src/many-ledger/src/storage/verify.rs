@@ -0,0 +1,76 @@
+//! Verification of the integrity of a store, e.g. after a crash or a disk
+//! issue, printed by `--verify-state`.
+//!
+//! - Every node of the merk tree is walked and its hash recomputed up to the
+//!   root hash, by restoring the chunks of the store into a temporary copy.
+//! - The root hash is compared with the application hash recorded at the
+//!   committed height, if any. Stores older than the records have none.
+//! - The invariants of [`crate::storage::invariants`] are recomputed: token
+//!   supplies against the sum of balances, and the event log.
+use crate::error;
+use crate::storage::snapshot::{read_snapshot_chunk, restore_snapshot, write_snapshot};
+use crate::storage::LedgerStorage;
+use many_error::ManyError;
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct StateReport {
+    pub height: u64,
+
+    /// The root hash of the committed store, in hexadecimal.
+    pub hash: String,
+
+    /// The application hash recorded at `height`, in hexadecimal.
+    pub app_hash: Option<String>,
+
+    /// Every divergence found.
+    pub violations: Vec<String>,
+}
+
+impl StateReport {
+    pub fn verified(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl LedgerStorage {
+    /// Verify the committed store. This needs the disk space of a copy of
+    /// the store.
+    pub fn verify_state(&self) -> Result<StateReport, ManyError> {
+        let height = self.get_height()?;
+        let hash = self.persistent_store.root_hash().to_vec();
+        let mut violations = Vec::new();
+
+        let dir = tempfile::tempdir().map_err(error::snapshot_failed)?;
+        let snapshot = dir.path().join("verify.snapshot");
+        let info = write_snapshot(&self.persistent_store, &snapshot, height, None)?;
+        match restore_snapshot(dir.path().join("store"), &info, |index| {
+            read_snapshot_chunk(&snapshot, index)
+        }) {
+            Ok(copy) if copy.root_hash().as_slice() != hash.as_slice() => violations.push(format!(
+                "The tree hashes to {} instead of its root hash",
+                hex::encode(copy.root_hash())
+            )),
+            Ok(_) => {}
+            Err(e) => violations.push(format!("The tree does not match its root hash: {e}")),
+        }
+
+        let app_hash = self.get_app_hash(height)?;
+        if app_hash
+            .as_ref()
+            .map_or(false, |app_hash| *app_hash != hash)
+        {
+            violations.push(format!(
+                "The root hash differs from the application hash of height {height}"
+            ));
+        }
+
+        violations.extend(self.check_invariants()?);
+
+        Ok(StateReport {
+            height,
+            hash: hex::encode(&hash),
+            app_hash: app_hash.map(hex::encode),
+            violations,
+        })
+    }
+}
@@ -0,0 +1,300 @@
+//! Event subscriptions over WebSocket.
+//!
+//! A client opens a WebSocket and sends a binary message with its filter, a CBOR
+//! encoded [`SubscribeArgs`]. The server then sends every event matching the
+//! filter in a binary message of its own, a CBOR encoded `EventLog`, as soon as
+//! the block logging it is committed. Clients reading too slowly are dropped.
+//!
+//! Only the subset of RFC 6455 needed for this is implemented: unfragmented
+//! messages, and no extensions.
+use crate::storage::growth::symbol_of;
+use many_identity::Address;
+use many_modules::events::{EventKind, EventLog};
+use many_types::ledger::Symbol;
+use many_types::VecOrSingle;
+use minicbor::{Decode, Encode};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Number of events queued for a subscriber before it is dropped.
+pub const SUBSCRIBER_QUEUE_SIZE: usize = 1024;
+
+/// Maximum size of a message from a client, in bytes.
+const MAX_CLIENT_MESSAGE_SIZE: u64 = 64 * 1024;
+
+/// Time a client has to send its filter.
+const SUBSCRIBE_TIMEOUT: Duration = Duration::from_secs(30);
+
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+
+#[derive(Clone, Debug, Default, Encode, Decode)]
+#[cbor(map)]
+pub struct SubscribeArgs {
+    /// Only the events about this account.
+    #[n(0)]
+    pub account: Option<Address>,
+
+    /// Only the events about this symbol.
+    #[n(1)]
+    pub symbol: Option<Symbol>,
+
+    /// Only the events of these kinds.
+    #[n(2)]
+    pub kind: Option<VecOrSingle<EventKind>>,
+}
+
+impl SubscribeArgs {
+    pub fn matches(&self, event: &EventLog) -> bool {
+        self.account.map_or(true, |account| event.is_about(account))
+            && self
+                .symbol
+                .map_or(true, |symbol| symbol_of(&event.content) == Some(symbol))
+            && self
+                .kind
+                .as_ref()
+                .map_or(true, |kinds| kinds.0.contains(&event.kind()))
+    }
+}
+
+struct Subscriber {
+    filter: SubscribeArgs,
+    sender: SyncSender<Arc<EventLog>>,
+}
+
+/// The subscribers, notified of the events of every committed block.
+#[derive(Default)]
+pub struct Subscriptions {
+    subscribers: Mutex<Vec<Subscriber>>,
+}
+
+impl std::fmt::Debug for Subscriptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Subscriptions")
+            .field("subscribers", &self.subscribers.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl Subscriptions {
+    pub fn subscribe(&self, filter: SubscribeArgs) -> Receiver<Arc<EventLog>> {
+        let (sender, receiver) = sync_channel(SUBSCRIBER_QUEUE_SIZE);
+        self.subscribers
+            .lock()
+            .unwrap()
+            .push(Subscriber { filter, sender });
+        receiver
+    }
+
+    /// Send `events` to the subscribers whose filter they match. Subscribers
+    /// which are gone or too slow are dropped.
+    pub fn notify(&self, events: Vec<EventLog>) {
+        let events: Vec<Arc<EventLog>> = events.into_iter().map(Arc::new).collect();
+        self.subscribers.lock().unwrap().retain(|subscriber| {
+            events
+                .iter()
+                .filter(|event| subscriber.filter.matches(event))
+                .all(|event| match subscriber.sender.try_send(event.clone()) {
+                    Ok(()) => true,
+                    Err(TrySendError::Full(_)) => {
+                        debug!("Dropping a subscriber too slow to read the events");
+                        false
+                    }
+                    Err(TrySendError::Disconnected(_)) => false,
+                })
+        });
+    }
+}
+
+/// Minimal SHA-1, only used for the `Sec-WebSocket-Accept` header.
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for chunk in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in chunk.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, wi) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*wi);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (h, v) in h.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(v);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (bytes, word) in digest.chunks_mut(4).zip(h) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+/// The `Sec-WebSocket-Accept` header answering the `Sec-WebSocket-Key` of a
+/// client.
+pub fn accept_key(key: &str) -> String {
+    base64::encode(sha1(format!("{}{WEBSOCKET_GUID}", key.trim()).as_bytes()))
+}
+
+fn invalid(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+}
+
+/// Read the upgrade request and answer it.
+fn handshake(reader: &mut impl BufRead, stream: &mut TcpStream) -> std::io::Result<()> {
+    let mut key = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(invalid("connection closed during the handshake"));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("sec-websocket-key") {
+                key = Some(value.trim().to_string());
+            }
+        }
+    }
+    let key = key.ok_or_else(|| invalid("not a WebSocket upgrade request"))?;
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(&key)
+    )
+}
+
+/// Read a message from a client, returning its opcode and payload.
+fn read_frame(reader: &mut impl Read) -> std::io::Result<(u8, Vec<u8>)> {
+    let mut head = [0u8; 2];
+    reader.read_exact(&mut head)?;
+    let opcode = head[0] & 0x0F;
+    if head[0] & 0x80 == 0 {
+        return Err(invalid("fragmented messages are not supported"));
+    }
+    let len = match head[1] & 0x7F {
+        126 => {
+            let mut len = [0u8; 2];
+            reader.read_exact(&mut len)?;
+            u16::from_be_bytes(len) as u64
+        }
+        127 => {
+            let mut len = [0u8; 8];
+            reader.read_exact(&mut len)?;
+            u64::from_be_bytes(len)
+        }
+        len => len as u64,
+    };
+    if len > MAX_CLIENT_MESSAGE_SIZE {
+        return Err(invalid("message too large"));
+    }
+    // Clients must mask their messages.
+    if head[1] & 0x80 == 0 {
+        return Err(invalid("unmasked client message"));
+    }
+    let mut mask = [0u8; 4];
+    reader.read_exact(&mut mask)?;
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload)?;
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+    Ok((opcode, payload))
+}
+
+/// Write an unmasked message to a client.
+fn write_frame(stream: &mut impl Write, opcode: u8, payload: &[u8]) -> std::io::Result<()> {
+    let mut head = vec![0x80 | opcode];
+    match payload.len() {
+        len if len < 126 => head.push(len as u8),
+        len if len <= u16::MAX as usize => {
+            head.push(126);
+            head.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            head.push(127);
+            head.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    stream.write_all(&head)?;
+    stream.write_all(payload)
+}
+
+fn serve_client(mut stream: TcpStream, subscriptions: &Subscriptions) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(SUBSCRIBE_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    handshake(&mut reader, &mut stream)?;
+
+    let filter = match read_frame(&mut reader)? {
+        (OPCODE_BINARY, payload) => minicbor::decode::<SubscribeArgs>(&payload)
+            .map_err(|e| invalid(&format!("invalid filter: {e}")))?,
+        (OPCODE_CLOSE, _) => return Ok(()),
+        _ => return Err(invalid("the filter must be a binary message")),
+    };
+
+    for event in subscriptions.subscribe(filter) {
+        let bytes = minicbor::to_vec(event.as_ref())
+            .map_err(|e| invalid(&format!("could not encode an event: {e}")))?;
+        write_frame(&mut stream, OPCODE_BINARY, &bytes)?;
+    }
+    // The subscriber was dropped.
+    write_frame(&mut stream, OPCODE_CLOSE, &[])
+}
+
+/// Accept WebSocket clients on `addr`, each on a thread of its own.
+pub fn serve(
+    addr: SocketAddr,
+    subscriptions: Arc<Subscriptions>,
+) -> std::io::Result<std::thread::JoinHandle<()>> {
+    let listener = TcpListener::bind(addr)?;
+    Ok(std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let subscriptions = subscriptions.clone();
+            match stream {
+                Ok(stream) => {
+                    std::thread::spawn(move || {
+                        if let Err(e) = serve_client(stream, &subscriptions) {
+                            debug!("Subscription closed: {e}");
+                        }
+                    });
+                }
+                Err(e) => warn!("Could not accept a subscription: {e}"),
+            }
+        }
+    }))
+}
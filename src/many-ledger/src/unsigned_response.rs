@@ -0,0 +1,167 @@
+//! Unsigned, cached responses to anonymous queries.
+//!
+//! Signing every response costs public nodes serving explorers most of their
+//! CPU. Clients that verify data against the application hash instead, e.g.
+//! with `ledger.balanceProof`, opt out of signatures by adding the
+//! [`UNSIGNED_RESPONSE`] attribute, without arguments, to an anonymous query.
+//! The response then carries the same attribute with the arguments
+//!
+//! ```text
+//! [ height: uint ]
+//! ```
+//!
+//! and an empty signature. `height` is the height of the state the response
+//! was computed from, whose hash `ledger.commitment` returns signed.
+//!
+//! Responses to these queries, and to `ledger.commitment`, are cached until the
+//! next block, so each is computed and signed once per block at most.
+use crate::module::LedgerModuleImpl;
+use async_trait::async_trait;
+use coset::CoseSign1;
+use many_modules::abci_backend::ManyAbciModuleBackend;
+use many_protocol::{RequestMessage, ResponseMessage};
+use many_server::transport::LowLevelManyRequestHandler;
+use many_types::attributes::{Attribute, AttributeId};
+use many_types::cbor::CborAny;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+
+pub const UNSIGNED_RESPONSE: AttributeId = 3_001;
+
+/// The signed commitment to the state, cached like unsigned responses.
+const COMMITMENT_METHOD: &str = "ledger.commitment";
+
+/// Number of responses cached when none is specified.
+pub const DEFAULT_RESPONSE_CACHE_SIZE: usize = 10_000;
+
+/// The responses of a single height, by method and arguments.
+#[derive(Debug, Default)]
+pub struct ResponseCache {
+    height: u64,
+    responses: BTreeMap<(String, Vec<u8>), CoseSign1>,
+    capacity: usize,
+}
+
+impl ResponseCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ..Default::default()
+        }
+    }
+
+    pub fn get(&mut self, height: u64, method: &str, data: &[u8]) -> Option<CoseSign1> {
+        if height != self.height {
+            self.height = height;
+            self.responses.clear();
+        }
+        self.responses
+            .get(&(method.to_string(), data.to_vec()))
+            .cloned()
+    }
+
+    pub fn insert(&mut self, height: u64, method: &str, data: &[u8], response: CoseSign1) {
+        if height != self.height || self.capacity == 0 {
+            return;
+        }
+        // Simpler than evicting entries one by one, and the cache is cleared
+        // every block anyway.
+        if self.responses.len() >= self.capacity {
+            self.responses.clear();
+        }
+        self.responses
+            .insert((method.to_string(), data.to_vec()), response);
+    }
+}
+
+/// Remove the signature of a response, and add the height it was computed at.
+pub fn unsign(envelope: CoseSign1, height: u64) -> Result<CoseSign1, String> {
+    let payload = envelope
+        .payload
+        .as_deref()
+        .ok_or_else(|| "Empty response.".to_string())?;
+    let response = ResponseMessage::from_bytes(payload)
+        .map_err(|e| e.to_string())?
+        .with_attribute(Attribute::new(
+            UNSIGNED_RESPONSE,
+            vec![CborAny::Int(height as i64)],
+        ));
+    Ok(CoseSign1 {
+        payload: Some(response.to_bytes().map_err(|e| e.to_string())?),
+        signature: vec![],
+        ..envelope
+    })
+}
+
+/// Answers the anonymous queries asking for it with unsigned responses, and
+/// caches them with the state commitment until the next block.
+pub struct UnsignedResponseHandler<H> {
+    pub inner: H,
+    pub module_impl: Arc<Mutex<LedgerModuleImpl>>,
+    pub commands: BTreeSet<String>,
+    pub cache: Mutex<ResponseCache>,
+}
+
+impl<H> Debug for UnsignedResponseHandler<H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("UnsignedResponseHandler")
+    }
+}
+
+#[async_trait]
+impl<H: LowLevelManyRequestHandler> LowLevelManyRequestHandler for UnsignedResponseHandler<H> {
+    async fn execute(&self, envelope: CoseSign1) -> Result<CoseSign1, String> {
+        let request = envelope
+            .payload
+            .as_deref()
+            .and_then(|payload| RequestMessage::from_bytes(payload).ok())
+            .filter(|message| !self.commands.contains(&message.method));
+        let (request, unsigned) = match request {
+            Some(request) if request.method == COMMITMENT_METHOD => (request, false),
+            Some(request)
+                if request.from().is_anonymous()
+                    && request
+                        .attributes
+                        .get_attribute(UNSIGNED_RESPONSE)
+                        .is_some() =>
+            {
+                (request, true)
+            }
+            _ => return self.inner.execute(envelope).await,
+        };
+
+        let height = {
+            let module_impl = self.module_impl.lock().unwrap();
+            ManyAbciModuleBackend::info(&*module_impl)
+                .map_err(|e| e.to_string())?
+                .height
+        };
+        // Unsigned responses are cached apart from signed ones.
+        let method = if unsigned {
+            format!("{}?unsigned", request.method)
+        } else {
+            request.method.clone()
+        };
+        if let Some(response) = self
+            .cache
+            .lock()
+            .unwrap()
+            .get(height, &method, &request.data)
+        {
+            return Ok(response);
+        }
+
+        let response = self.inner.execute(envelope).await?;
+        let response = if unsigned {
+            unsign(response, height)?
+        } else {
+            response
+        };
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(height, &method, &request.data, response.clone());
+        Ok(response)
+    }
+}
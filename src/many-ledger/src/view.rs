@@ -0,0 +1,248 @@
+//! Read views of the committed state, so queries don't wait for the block
+//! being executed, and can be answered at a past height.
+//!
+//! A view is a checkpoint of the store, made of hard links, opened as a
+//! separate ledger. A view is added after every commit, and the views of the
+//! last heights are kept. The queries of [`VIEW_METHODS`] are answered from
+//! the latest view while the next block executes on the store. When they
+//! carry the [`AT_HEIGHT`] attribute, with the arguments
+//!
+//! ```text
+//! [ height: uint ]
+//! ```
+//!
+//! they are answered from the view of that height, e.g. so explorers show
+//! consistent views of a block. While the view of the latest height could not
+//! be added, queries without a height are answered from the store instead.
+use crate::error;
+use crate::module::LedgerModuleImpl;
+use crate::storage::snapshot::remove_dir_if_exists;
+use async_trait::async_trait;
+use coset::CoseSign1;
+use many_error::ManyError;
+use many_identity::Identity;
+use many_identity_dsa::CoseKeyIdentity;
+use many_migration::MigrationConfig;
+use many_modules::abci_backend::ManyAbciModuleBackend;
+use many_protocol::{encode_cose_sign1_from_response, RequestMessage, ResponseMessage};
+use many_server::transport::LowLevelManyRequestHandler;
+use many_server::ManyServer;
+use many_types::attributes::AttributeId;
+use many_types::cbor::CborAny;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use tracing::warn;
+
+pub const AT_HEIGHT: AttributeId = 3_002;
+
+/// The queries answered by the views.
+pub const VIEW_METHODS: &[&str] = &[
+    "ledger.info",
+    "ledger.balance",
+    "ledger.balanceProof",
+    "ledger.commitment",
+    "events.info",
+    "events.list",
+];
+
+/// Number of heights kept when none is specified.
+pub const DEFAULT_VIEW_HEIGHTS: usize = 1;
+
+/// The server of the modules of a view.
+pub type ViewServer =
+    Box<dyn Fn(Arc<Mutex<LedgerModuleImpl>>) -> Arc<Mutex<ManyServer>> + Send + Sync>;
+
+struct View {
+    module_impl: Arc<Mutex<LedgerModuleImpl>>,
+    server: Arc<Mutex<ManyServer>>,
+}
+
+pub struct ReadViews {
+    dir: PathBuf,
+    migrations: Option<MigrationConfig>,
+    keep: usize,
+    server: ViewServer,
+    views: RwLock<BTreeMap<u64, View>>,
+    outdated: AtomicBool,
+}
+
+impl Debug for ReadViews {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReadViews")
+            .field("dir", &self.dir)
+            .field("keep", &self.keep)
+            .finish()
+    }
+}
+
+impl ReadViews {
+    /// Keep the views of the last `keep` heights in `dir`, served by `server`,
+    /// starting with the committed state of `primary`. Previous views in `dir`
+    /// are removed.
+    pub fn open<P: AsRef<Path>>(
+        dir: P,
+        migrations: Option<MigrationConfig>,
+        keep: usize,
+        server: ViewServer,
+        primary: &LedgerModuleImpl,
+    ) -> Result<Self, ManyError> {
+        let dir = dir.as_ref().to_path_buf();
+        remove_dir_if_exists(&dir)?;
+        std::fs::create_dir_all(&dir).map_err(error::snapshot_failed)?;
+        let views = Self {
+            dir,
+            migrations,
+            keep: keep.max(1),
+            server,
+            views: RwLock::new(BTreeMap::new()),
+            outdated: AtomicBool::new(false),
+        };
+        views.add(primary)?;
+        Ok(views)
+    }
+
+    fn path(&self, height: u64) -> PathBuf {
+        self.dir.join(format!("{height:020}"))
+    }
+
+    fn add(&self, primary: &LedgerModuleImpl) -> Result<(), ManyError> {
+        let height = ManyAbciModuleBackend::info(primary)?.height;
+        let path = self.path(height);
+        remove_dir_if_exists(&path)?;
+        drop(primary.checkpoint(&path)?);
+        let module_impl = Arc::new(Mutex::new(LedgerModuleImpl::load(
+            self.migrations.clone(),
+            &path,
+            true,
+        )?));
+        let view = View {
+            server: (self.server)(module_impl.clone()),
+            module_impl,
+        };
+
+        let mut views = self.views.write().unwrap();
+        views.insert(height, view);
+        while views.len() > self.keep {
+            if let Some((expired, view)) = views.pop_first() {
+                // Queries still running on the view keep its files open.
+                drop(view);
+                remove_dir_if_exists(&self.path(expired))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Add the view of the committed state of `primary`.
+    pub fn refresh(&self, primary: &LedgerModuleImpl) {
+        let outdated = match self.add(primary) {
+            Ok(()) => false,
+            Err(e) => {
+                warn!("Could not add a read view, queries use the store: {e}");
+                true
+            }
+        };
+        self.outdated.store(outdated, Ordering::Release);
+    }
+
+    pub fn is_outdated(&self) -> bool {
+        self.outdated.load(Ordering::Acquire)
+    }
+
+    /// The heights of the views.
+    pub fn heights(&self) -> Vec<u64> {
+        self.views.read().unwrap().keys().copied().collect()
+    }
+
+    /// The ledger of the view at `height`, or of the latest view.
+    pub fn module_impl(&self, height: Option<u64>) -> Option<Arc<Mutex<LedgerModuleImpl>>> {
+        self.view(height, |view| view.module_impl.clone())
+    }
+
+    fn server(&self, height: Option<u64>) -> Option<Arc<Mutex<ManyServer>>> {
+        self.view(height, |view| view.server.clone())
+    }
+
+    fn view<T>(&self, height: Option<u64>, f: impl FnOnce(&View) -> T) -> Option<T> {
+        let views = self.views.read().unwrap();
+        match height {
+            Some(height) => views.get(&height),
+            None => views.values().next_back(),
+        }
+        .map(f)
+    }
+}
+
+/// The height of the [`AT_HEIGHT`] attribute of a request, if any.
+fn requested_height(message: &RequestMessage) -> Result<Option<u64>, ManyError> {
+    let attribute = match message.attributes.get_attribute(AT_HEIGHT) {
+        Some(attribute) => attribute,
+        None => return Ok(None),
+    };
+    match attribute.arguments.first() {
+        Some(CborAny::Int(height)) if *height >= 0 => Ok(Some(*height as u64)),
+        _ => Err(ManyError::unknown("Invalid height attribute.")),
+    }
+}
+
+/// Sends the queries of [`VIEW_METHODS`] to the servers of the views.
+pub struct ReadViewHandler<H> {
+    pub inner: H,
+    pub views: Option<Arc<ReadViews>>,
+    pub key: CoseKeyIdentity,
+}
+
+impl<H> Debug for ReadViewHandler<H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ReadViewHandler")
+    }
+}
+
+impl<H> ReadViewHandler<H> {
+    /// The server answering `message`, if not the store's.
+    fn route(&self, message: &RequestMessage) -> Result<Option<Arc<Mutex<ManyServer>>>, ManyError> {
+        let height = requested_height(message)?;
+        let in_view = VIEW_METHODS.contains(&message.method.as_str());
+        let views = match (&self.views, height) {
+            (_, Some(_)) if !in_view => {
+                return Err(error::not_queryable_at_height(&message.method))
+            }
+            (None, Some(height)) => return Err(error::height_not_retained(height)),
+            (None, None) => return Ok(None),
+            (Some(views), _) => views,
+        };
+        match height {
+            Some(height) => views
+                .server(Some(height))
+                .map(Some)
+                .ok_or_else(|| error::height_not_retained(height)),
+            None if in_view && !views.is_outdated() => Ok(views.server(None)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[async_trait]
+impl<H: LowLevelManyRequestHandler> LowLevelManyRequestHandler for ReadViewHandler<H> {
+    async fn execute(&self, envelope: CoseSign1) -> Result<CoseSign1, String> {
+        let message = envelope
+            .payload
+            .as_deref()
+            .and_then(|payload| RequestMessage::from_bytes(payload).ok());
+
+        if let Some(message) = message {
+            match self.route(&message) {
+                Ok(Some(server)) => return server.execute(envelope).await,
+                Ok(None) => {}
+                Err(e) => {
+                    let response = ResponseMessage::error(self.key.address(), message.id, e);
+                    return encode_cose_sign1_from_response(response, &self.key)
+                        .map_err(|e| e.to_string());
+                }
+            }
+        }
+        self.inner.execute(envelope).await
+    }
+}
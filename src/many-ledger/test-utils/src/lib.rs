@@ -1,4 +1,5 @@
 pub mod cucumber;
+pub mod replay;
 
 use coset::CborSerializable;
 use itertools::Itertools;
@@ -186,6 +187,7 @@ impl Setup {
         blockchain: bool,
         migration_config: Option<MigrationConfig>,
         skip_hash_check: bool, // If true, skip the staging file hash check
+        edit_state: impl FnOnce(&mut InitialStateJson),
     ) -> Self {
         let id = generate_random_ed25519_identity();
         let public_key = PublicKey(id.public_key().to_vec().unwrap().into());
@@ -199,6 +201,7 @@ impl Setup {
         if skip_hash_check {
             state.hash = None;
         }
+        edit_state(&mut state);
 
         Self {
             module_impl: LedgerModuleImpl::new(state, migration_config, store_path, blockchain)
@@ -211,7 +214,16 @@ impl Setup {
     }
 
     pub fn new(blockchain: bool) -> Self {
-        Setup::_new(blockchain, None, false)
+        Setup::_new(blockchain, None, false, |_| {})
+    }
+
+    /// Create a setup from the staging initial state edited by `edit_state`.
+    /// The hash of the staging file is not checked.
+    pub fn new_with_state(
+        blockchain: bool,
+        edit_state: impl FnOnce(&mut InitialStateJson),
+    ) -> Self {
+        Setup::_new(blockchain, None, true, edit_state)
     }
 
     pub fn new_with_migrations(
@@ -231,6 +243,7 @@ impl Setup {
             blockchain,
             Some(serde_json::from_str(&migrations).unwrap()),
             skip_hash_check,
+            |_| {},
         )
     }
 
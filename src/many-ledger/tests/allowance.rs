@@ -0,0 +1,137 @@
+use many_identity::testing::identity;
+use many_ledger::error;
+use many_ledger::module::allowance::{
+    AllowanceArgs, AllowanceHistoryArgs, ApproveArgs, LedgerAllowanceModuleBackend,
+    TransferFromArgs,
+};
+use many_ledger::module::LedgerModuleImpl;
+use many_ledger::storage::allowance::AllowanceEvent;
+use many_ledger_test_utils::*;
+use many_types::ledger::TokenAmount;
+
+fn allowance(module_impl: &LedgerModuleImpl) -> TokenAmount {
+    module_impl
+        .allowance(
+            &identity(1),
+            AllowanceArgs {
+                owner: identity(1),
+                spender: identity(2),
+                symbol: *MFX_SYMBOL,
+            },
+        )
+        .unwrap()
+        .amount
+}
+
+fn transfer_from(
+    module_impl: &mut LedgerModuleImpl,
+    amount: u64,
+) -> Result<(), many_error::ManyError> {
+    module_impl
+        .transfer_from(
+            &identity(2),
+            TransferFromArgs {
+                from: identity(1),
+                to: identity(3),
+                symbol: *MFX_SYMBOL,
+                amount: amount.into(),
+                memo: None,
+            },
+        )
+        .map(|_| ())
+}
+
+fn setup_with_allowance(amount: u64) -> LedgerModuleImpl {
+    let Setup {
+        mut module_impl, ..
+    } = setup();
+    module_impl
+        .set_balance_only_for_testing(identity(1), 1000, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+    module_impl
+        .approve(
+            &identity(1),
+            ApproveArgs {
+                from: None,
+                spender: identity(2),
+                symbol: *MFX_SYMBOL,
+                amount: amount.into(),
+            },
+        )
+        .unwrap();
+    module_impl
+}
+
+#[test]
+fn transfer_from_consumes_allowance() {
+    let mut module_impl = setup_with_allowance(300);
+    assert_eq!(allowance(&module_impl), 300u64.into());
+
+    transfer_from(&mut module_impl, 200).unwrap();
+    assert_eq!(allowance(&module_impl), 100u64.into());
+    verify_balance(&module_impl, identity(1), *MFX_SYMBOL, 800u64.into());
+    verify_balance(&module_impl, identity(3), *MFX_SYMBOL, 200u64.into());
+
+    let result = transfer_from(&mut module_impl, 101);
+    assert_eq!(
+        result.unwrap_err().code(),
+        error::allowance_exceeded("", "").code()
+    );
+    verify_balance(&module_impl, identity(1), *MFX_SYMBOL, 800u64.into());
+}
+
+#[test]
+fn transfer_from_without_allowance() {
+    let Setup {
+        mut module_impl, ..
+    } = setup();
+    module_impl
+        .set_balance_only_for_testing(identity(1), 1000, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+
+    let result = transfer_from(&mut module_impl, 1);
+    assert_eq!(
+        result.unwrap_err().code(),
+        error::allowance_exceeded("", "").code()
+    );
+}
+
+#[test]
+fn failed_transfer_keeps_allowance() {
+    let mut module_impl = setup_with_allowance(5000);
+    let result = transfer_from(&mut module_impl, 2000);
+    assert_eq!(
+        result.unwrap_err().code(),
+        error::insufficient_funds("", "").code()
+    );
+    assert_eq!(allowance(&module_impl), 5000u64.into());
+}
+
+#[test]
+fn allowance_history() {
+    let mut module_impl = setup_with_allowance(300);
+    transfer_from(&mut module_impl, 200).unwrap();
+
+    let events = module_impl
+        .allowance_history(
+            &identity(1),
+            AllowanceHistoryArgs {
+                account: Some(identity(3)),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .events;
+    assert_eq!(events.len(), 1);
+    assert!(matches!(
+        events[0].content,
+        AllowanceEvent::TransferFrom { .. }
+    ));
+
+    let events = module_impl
+        .allowance_history(&identity(1), AllowanceHistoryArgs::default())
+        .unwrap()
+        .events;
+    assert_eq!(events.len(), 2);
+    assert!(matches!(events[0].content, AllowanceEvent::Approve { .. }));
+}
@@ -0,0 +1,55 @@
+use many_identity::testing::identity;
+use many_ledger::amount::CheckedTokenAmount;
+use many_ledger::error;
+use many_ledger::migration::insufficient_funds::INSUFFICIENT_FUNDS_MIGRATION;
+use many_ledger_test_utils::*;
+use many_types::ledger::TokenAmount;
+use num_bigint::BigUint;
+
+#[test]
+fn checked_arithmetic() {
+    let a = TokenAmount::from(100u64);
+    let b = TokenAmount::from(40u64);
+    assert_eq!(a.checked_sub(&b), Some(TokenAmount::from(60u64)));
+    assert_eq!(a.checked_sub(&a), Some(TokenAmount::zero()));
+    assert_eq!(b.checked_sub(&a), None);
+}
+
+#[test]
+fn conversions() {
+    let max = TokenAmount::from(BigUint::from(u128::MAX));
+    assert_eq!(TokenAmount::from(42u64).try_to_u64().unwrap(), 42);
+    assert_eq!(max.try_to_u128().unwrap(), u128::MAX);
+    assert_eq!(
+        max.try_to_u64().unwrap_err().code(),
+        error::amount_out_of_range("", "").code()
+    );
+}
+
+#[test]
+fn insufficient_funds_arguments() {
+    let mut harness = Setup::new_with_migrations(true, [(1, &INSUFFICIENT_FUNDS_MIGRATION)], false);
+    let id = harness.id;
+
+    // Before the migration, the error has no arguments.
+    let (_, legacy) = harness.block(|h| {
+        h.send(id, identity(1), 1_000_000_000u64, *MFX_SYMBOL)
+            .unwrap_err()
+    });
+    assert_eq!(legacy.code(), error::insufficient_funds("", "").code());
+    assert_eq!(
+        legacy.message(),
+        error::legacy::insufficient_funds().message()
+    );
+    assert!(legacy.arguments().is_empty());
+
+    let (_, e) = harness.block(|h| {
+        h.send(id, identity(1), 1_000_000_000u64, *MFX_SYMBOL)
+            .unwrap_err()
+    });
+    assert_eq!(e.code(), legacy.code());
+    assert_eq!(
+        e.arguments().get("amount").map(String::as_str),
+        Some("1000000000")
+    );
+}
@@ -56,3 +56,23 @@ fn query_in_flight() {
 
     assert_eq!(harness.balance_(harness.id), 996u32);
 }
+
+/// Test that the application hash of every height is kept for audits.
+#[test]
+fn app_hash_chain() {
+    use many_modules::abci_backend::ManyAbciModuleBackend;
+
+    let mut harness = Setup::new(true);
+    harness.set_balance(harness.id, 1_000_000, *MFX_SYMBOL);
+    let mut hashes = vec![];
+    for i in 0..3u32 {
+        harness.block(|harness| harness.send_(harness.id, identity(2), 100 + i));
+        let info = harness.module_impl.info().unwrap();
+        hashes.push((info.height, info.hash.to_vec()));
+    }
+
+    assert_eq!(harness.module_impl.app_hash(0).unwrap(), None);
+    for (height, hash) in hashes {
+        assert_eq!(harness.module_impl.app_hash(height).unwrap(), Some(hash));
+    }
+}
@@ -0,0 +1,61 @@
+use many_identity::testing::identity;
+use many_identity::Address;
+use many_ledger::error;
+use many_ledger::module::check_tx::{AbciCheckTxModuleBackend, CheckTxArgs};
+use many_ledger_test_utils::*;
+use many_modules::ledger::SendArgs;
+use many_types::ledger::TokenAmount;
+
+fn send_args(from: Option<Address>, amount: u64) -> Vec<u8> {
+    minicbor::to_vec(SendArgs {
+        from,
+        to: identity(2),
+        symbol: *MFX_SYMBOL,
+        amount: TokenAmount::from(amount),
+        memo: None,
+    })
+    .unwrap()
+}
+
+#[test]
+fn send_is_prechecked() {
+    let mut h = Setup::new(false);
+    h.set_balance(identity(1), 1_000, *MFX_SYMBOL);
+
+    let check = |h: &Setup, from, data| {
+        h.module_impl.check_tx(CheckTxArgs {
+            from,
+            method: "ledger.send".to_string(),
+            data: data.into(),
+        })
+    };
+
+    assert!(check(&h, identity(1), send_args(None, 1_000)).is_ok());
+    assert_eq!(
+        check(&h, identity(1), send_args(None, 1_001))
+            .unwrap_err()
+            .code(),
+        error::insufficient_funds("", "").code()
+    );
+
+    // Sending on behalf of another account needs a permission.
+    assert_eq!(
+        check(&h, identity(3), send_args(Some(identity(1)), 1))
+            .unwrap_err()
+            .code(),
+        error::unauthorized().code()
+    );
+}
+
+#[test]
+fn other_commands_are_admitted() {
+    let h = Setup::new(false);
+    assert!(h
+        .module_impl
+        .check_tx(CheckTxArgs {
+            from: identity(1),
+            method: "ledger.burn".to_string(),
+            data: vec![].into(),
+        })
+        .is_ok());
+}
@@ -0,0 +1,91 @@
+use many_identity::testing::identity;
+use many_ledger::module::LedgerModuleImpl;
+use many_ledger::storage::clock::SimulatedClock;
+use many_ledger_test_utils::*;
+use many_modules::abci_backend::{AbciBlock, ManyAbciModuleBackend};
+use many_modules::events::{self, EventsModuleBackend};
+use many_modules::ledger;
+use many_modules::ledger::LedgerCommandsModuleBackend;
+use many_types::Timestamp;
+
+fn send(module_impl: &mut LedgerModuleImpl) {
+    module_impl
+        .send(
+            &identity(1),
+            ledger::SendArgs {
+                from: Some(identity(1)),
+                to: identity(2),
+                amount: 10u16.into(),
+                symbol: *MFX_SYMBOL,
+                memo: None,
+            },
+        )
+        .unwrap();
+}
+
+fn event_times(module_impl: &LedgerModuleImpl) -> Vec<Timestamp> {
+    module_impl
+        .list(events::ListArgs {
+            count: None,
+            order: None,
+            filter: None,
+        })
+        .unwrap()
+        .events
+        .into_iter()
+        .map(|e| e.time)
+        .collect()
+}
+
+#[test]
+fn simulated_clock() {
+    let clock = SimulatedClock::new(Timestamp::new(1_000).unwrap());
+    let Setup { module_impl, .. } = setup();
+    let mut module_impl = module_impl.with_clock(clock.clone());
+    module_impl
+        .set_balance_only_for_testing(identity(1), 1000, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+
+    send(&mut module_impl);
+    clock.advance(500);
+    send(&mut module_impl);
+
+    assert_eq!(
+        event_times(&module_impl),
+        vec![
+            Timestamp::new(1_000).unwrap(),
+            Timestamp::new(1_500).unwrap()
+        ]
+    );
+}
+
+#[test]
+fn blockchain_uses_block_time() {
+    let Setup {
+        mut module_impl, ..
+    } = Setup::new(true);
+    module_impl
+        .set_balance_only_for_testing(identity(1), 1000, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+
+    for time in [1_000, 2_000] {
+        module_impl
+            .begin_block(AbciBlock { time: Some(time) })
+            .unwrap();
+        send(&mut module_impl);
+        send(&mut module_impl);
+        module_impl.end_block().unwrap();
+        module_impl.commit().unwrap();
+    }
+
+    // Every transaction of a block shares the block time, whatever the wall clock.
+    assert_eq!(
+        event_times(&module_impl),
+        vec![
+            Timestamp::new(1_000).unwrap(),
+            Timestamp::new(1_000).unwrap(),
+            Timestamp::new(2_000).unwrap(),
+            Timestamp::new(2_000).unwrap(),
+        ]
+    );
+}
@@ -0,0 +1,68 @@
+use many_identity::testing::identity;
+use many_ledger::storage::cold::ColdStore;
+use many_ledger_test_utils::*;
+use many_modules::abci_backend::ManyAbciModuleBackend;
+use many_modules::events::{self, EventsModuleBackend};
+use many_types::SortOrder;
+
+fn list_ids(harness: &Setup, order: SortOrder) -> Vec<events::EventId> {
+    harness
+        .module_impl
+        .list(events::ListArgs {
+            count: None,
+            order: Some(order),
+            filter: None,
+        })
+        .unwrap()
+        .events
+        .into_iter()
+        .map(|e| e.id)
+        .collect()
+}
+
+#[test]
+fn events_are_merged_across_tiers() {
+    let cold_path = tempfile::tempdir().unwrap();
+    let mut harness = Setup::new(true);
+    harness.module_impl = harness
+        .module_impl
+        .with_cold_store(Some(ColdStore::open(cold_path.path(), 2).unwrap()));
+    harness.set_balance(harness.id, 1_000_000, *MFX_SYMBOL);
+
+    for i in 1..=6 {
+        harness.block(|h| h.send_(h.id, identity(i), 10u32));
+    }
+
+    // Some events were moved, none were lost.
+    let ascending = list_ids(&harness, SortOrder::Ascending);
+    assert_eq!(ascending.len(), 6);
+
+    let mut descending = list_ids(&harness, SortOrder::Descending);
+    descending.reverse();
+    assert_eq!(ascending, descending);
+
+    let info = EventsModuleBackend::info(&harness.module_impl, events::InfoArgs {}).unwrap();
+    assert_eq!(info.total, 6);
+}
+
+#[test]
+fn cold_store_keeps_the_app_hash() {
+    let send_in_blocks = |harness: &mut Setup| {
+        harness.set_balance(identity(100), 1_000_000, *MFX_SYMBOL);
+        for i in 1..=6 {
+            harness.block(|h| h.send_(identity(100), identity(i), 10u32));
+        }
+        ManyAbciModuleBackend::info(&harness.module_impl)
+            .unwrap()
+            .hash
+    };
+
+    let cold_path = tempfile::tempdir().unwrap();
+    let mut cold = Setup::new(true);
+    cold.module_impl = cold
+        .module_impl
+        .with_cold_store(Some(ColdStore::open(cold_path.path(), 2).unwrap()));
+    let mut hot = Setup::new(true);
+
+    assert_eq!(send_in_blocks(&mut cold), send_in_blocks(&mut hot));
+}
@@ -0,0 +1,37 @@
+use many_ledger::migration::delivered::DELIVERED_REQUEST_MIGRATION;
+use many_ledger::storage::delivered::request_hash;
+use many_ledger_test_utils::*;
+
+#[test]
+fn delivered_requests_return_their_response() {
+    let mut harness = Setup::new_with_migrations(true, [(2, &DELIVERED_REQUEST_MIGRATION)], false);
+    let hash = request_hash(b"request");
+
+    // Nothing is recorded before the migration is active, i.e. in the blocks
+    // up to its height.
+    for _ in 0..2 {
+        harness.block(|h| {
+            h.module_impl
+                .command_delivered(&hash, vec![1, 2, 3])
+                .unwrap()
+        });
+    }
+    assert_eq!(harness.module_impl.delivered_response(&hash).unwrap(), None);
+
+    harness.block(|h| {
+        h.module_impl
+            .command_delivered(&hash, vec![1, 2, 3])
+            .unwrap()
+    });
+    assert_eq!(
+        harness.module_impl.delivered_response(&hash).unwrap(),
+        Some(vec![1, 2, 3])
+    );
+    assert_eq!(
+        harness
+            .module_impl
+            .delivered_response(&request_hash(b"other"))
+            .unwrap(),
+        None
+    );
+}
@@ -0,0 +1,19 @@
+use many_ledger::error;
+use many_ledger::error::registry::error_codes;
+use std::collections::BTreeSet;
+
+#[test]
+fn codes_are_unique() {
+    let errors = error_codes();
+    let codes = errors.iter().map(|e| e.code).collect::<BTreeSet<_>>();
+    assert_eq!(codes.len(), errors.len());
+}
+
+#[test]
+fn entry() {
+    let errors = error_codes();
+    let entry = errors.iter().find(|e| e.name == "quota_exceeded").unwrap();
+    assert_eq!(entry.code, i64::from(error::quota_exceeded(0, 0, 0).code()));
+    assert_eq!(entry.fields, vec!["quota", "used", "window"]);
+    assert!(entry.message.contains("{used}"));
+}
@@ -0,0 +1,156 @@
+use many_error::ManyError;
+use many_identity::testing::identity;
+use many_identity::Address;
+use many_ledger::error;
+use many_ledger::module::escrow::{
+    CreateArgs, EscrowModuleBackend, InfoArgs, RefundArgs, ReleaseArgs,
+};
+use many_ledger::storage::escrow::MAXIMUM_ESCROW_TIMEOUT;
+use many_ledger_test_utils::*;
+use many_types::ledger::TokenAmount;
+
+fn arbiter() -> Address {
+    identity(10)
+}
+
+fn create(h: &mut Setup, arbiter: Option<Address>, timeout_in_secs: u64) -> Result<u64, ManyError> {
+    h.module_impl
+        .create(
+            &identity(1),
+            CreateArgs {
+                from: None,
+                to: identity(2),
+                amount: 100u64.into(),
+                symbol: *MFX_SYMBOL,
+                memo: None,
+                arbiter,
+                timeout_in_secs,
+            },
+        )
+        .map(|r| r.id)
+}
+
+fn release(h: &mut Setup, sender: Address, id: u64) -> Result<(), ManyError> {
+    h.module_impl
+        .release(&sender, ReleaseArgs { id })
+        .map(|_| ())
+}
+
+fn refund(h: &mut Setup, sender: Address, id: u64) -> Result<(), ManyError> {
+    h.module_impl.refund(&sender, RefundArgs { id }).map(|_| ())
+}
+
+fn escrow_exists(h: &Setup, id: u64) -> bool {
+    h.module_impl.info(&identity(1), InfoArgs { id }).is_ok()
+}
+
+fn setup_escrow(arbiter: Option<Address>) -> (Setup, u64) {
+    let mut h = Setup::new(true);
+    let (_, id) = h.block(|h| {
+        h.set_balance(identity(1), 1000, *MFX_SYMBOL);
+        create(h, arbiter, 10).unwrap()
+    });
+
+    // The funds are held until the escrow is resolved.
+    assert_eq!(h.balance_(identity(1)), TokenAmount::from(900u64));
+    assert_eq!(h.balance_(identity(2)), TokenAmount::zero());
+    assert!(escrow_exists(&h, id));
+    (h, id)
+}
+
+fn assert_balances(h: &Setup, from: u64, to: u64) {
+    assert_eq!(h.balance_(identity(1)), TokenAmount::from(from));
+    assert_eq!(h.balance_(identity(2)), TokenAmount::from(to));
+}
+
+#[test]
+fn released_on_timeout() {
+    let (mut h, id) = setup_escrow(None);
+
+    h.block(|_| ());
+    assert!(escrow_exists(&h, id));
+
+    h.inc_time(20);
+    h.block(|_| ());
+    assert_balances(&h, 900, 100);
+    assert!(!escrow_exists(&h, id));
+}
+
+#[test]
+fn released_early_by_sender() {
+    let (mut h, id) = setup_escrow(None);
+
+    let (_, r) = h.block(|h| release(h, identity(2), id));
+    assert_eq!(r.unwrap_err().code(), error::unauthorized().code());
+
+    let (_, r) = h.block(|h| release(h, identity(1), id));
+    assert!(r.is_ok());
+    assert_balances(&h, 900, 100);
+
+    // The timeout does not apply once released.
+    h.inc_time(20);
+    h.block(|_| ());
+    assert_balances(&h, 900, 100);
+}
+
+#[test]
+fn declined_by_recipient() {
+    let (mut h, id) = setup_escrow(None);
+
+    let (_, r) = h.block(|h| refund(h, identity(1), id));
+    assert_eq!(r.unwrap_err().code(), error::unauthorized().code());
+
+    let (_, r) = h.block(|h| refund(h, identity(2), id));
+    assert!(r.is_ok());
+    assert_balances(&h, 1000, 0);
+    assert!(!escrow_exists(&h, id));
+}
+
+#[test]
+fn arbiter_decides() {
+    let (mut h, id) = setup_escrow(Some(arbiter()));
+
+    for sender in [identity(1), identity(2)] {
+        let (_, r) = h.block(|h| release(h, sender, id));
+        assert_eq!(r.unwrap_err().code(), error::unauthorized().code());
+        let (_, r) = h.block(|h| refund(h, sender, id));
+        assert_eq!(r.unwrap_err().code(), error::unauthorized().code());
+    }
+
+    let (_, r) = h.block(|h| release(h, arbiter(), id));
+    assert!(r.is_ok());
+    assert_balances(&h, 900, 100);
+
+    let (_, r) = h.block(|h| refund(h, arbiter(), id));
+    assert_eq!(r.unwrap_err().code(), error::escrow_not_found("").code());
+}
+
+#[test]
+fn refunded_on_timeout_without_decision() {
+    let (mut h, id) = setup_escrow(Some(arbiter()));
+
+    h.inc_time(20);
+    h.block(|_| ());
+    assert_balances(&h, 1000, 0);
+    assert!(!escrow_exists(&h, id));
+}
+
+#[test]
+fn invalid_escrow() {
+    let mut h = Setup::new(true);
+    h.set_balance(identity(1), 1000, *MFX_SYMBOL);
+    for timeout in [0, MAXIMUM_ESCROW_TIMEOUT + 1] {
+        let (_, r) = h.block(|h| create(h, None, timeout));
+        assert_eq!(
+            r.unwrap_err().code(),
+            error::invalid_escrow_timeout("", "").code()
+        );
+    }
+
+    let (_, r) = h.block(|h| create(h, Some(Address::anonymous()), 10));
+    assert_eq!(
+        r.unwrap_err().code(),
+        error::invalid_escrow_arbiter("").code()
+    );
+    assert_eq!(h.balance_(identity(1)), TokenAmount::from(1000u64));
+}
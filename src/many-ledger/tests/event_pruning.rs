@@ -0,0 +1,50 @@
+use many_identity::testing::identity;
+use many_ledger::storage::cold::ColdStore;
+use many_ledger_test_utils::*;
+use many_modules::events::{self, EventsModuleBackend};
+
+fn nb_listed(harness: &Setup) -> usize {
+    harness
+        .module_impl
+        .list(events::ListArgs {
+            count: None,
+            order: None,
+            filter: None,
+        })
+        .unwrap()
+        .events
+        .len()
+}
+
+fn send_in_blocks(harness: &mut Setup) {
+    harness.set_balance(harness.id, 1_000_000, *MFX_SYMBOL);
+    // Blocks are one second apart.
+    for i in 1..=6 {
+        harness.block(|h| h.send_(h.id, identity(i), 10u32));
+    }
+}
+
+#[test]
+fn old_events_are_deleted() {
+    let mut harness = Setup::new_with_state(true, |state| state.events_max_age = Some(3));
+    send_in_blocks(&mut harness);
+
+    // The last block is at 1_000_006, the events before 1_000_003 are pruned.
+    assert_eq!(nb_listed(&harness), 4);
+
+    // The pruned events are still counted.
+    let info = EventsModuleBackend::info(&harness.module_impl, events::InfoArgs {}).unwrap();
+    assert_eq!(info.total, 6);
+}
+
+#[test]
+fn old_events_are_archived() {
+    let cold_path = tempfile::tempdir().unwrap();
+    let mut harness = Setup::new_with_state(true, |state| state.events_max_age = Some(3));
+    harness.module_impl = harness
+        .module_impl
+        .with_cold_store(Some(ColdStore::open(cold_path.path(), 1_000).unwrap()));
+    send_in_blocks(&mut harness);
+
+    assert_eq!(nb_listed(&harness), 6);
+}
@@ -1,5 +1,6 @@
 use many_identity::testing::identity;
 use many_identity::Address;
+use many_ledger::module::events_page::{EventsPageModuleBackend, ListPageArgs};
 use many_ledger::module::LedgerModuleImpl;
 use many_ledger_test_utils::*;
 use many_modules::account::features::multisig::{
@@ -8,9 +9,10 @@ use many_modules::account::features::multisig::{
 use many_modules::events::{
     self, EventFilterAttributeSpecific, EventFilterAttributeSpecificIndex, EventsModuleBackend,
 };
+use many_modules::idstore::{self, IdStoreModuleBackend};
 use many_modules::ledger;
 use many_modules::ledger::LedgerCommandsModuleBackend;
-use many_types::{CborRange, Memo, Timestamp};
+use many_types::{CborRange, Memo, SortOrder, Timestamp};
 use proptest::prelude::*;
 use proptest::test_runner::Config;
 use std::collections::BTreeMap;
@@ -341,3 +343,417 @@ proptest! {
         assert!(result.events.is_empty());
     }
 }
+
+fn list_all_pages(module_impl: &LedgerModuleImpl, order: SortOrder) -> Vec<events::EventLog> {
+    let mut args = ListPageArgs {
+        count: Some(2),
+        order: Some(order),
+        ..Default::default()
+    };
+    let mut all = Vec::new();
+    loop {
+        let page = module_impl.list_page(&identity(1), args.clone()).unwrap();
+        assert_eq!(page.nb_events, 5);
+        assert!(page.events.len() <= 2);
+        all.extend(page.events);
+        match page.cursor {
+            Some(cursor) => args.cursor = Some(cursor),
+            None => return all,
+        }
+    }
+}
+
+#[test]
+fn list_page() {
+    let Setup {
+        mut module_impl,
+        id,
+        ..
+    } = setup();
+    for _ in 0..5 {
+        send(&mut module_impl, id, identity(1));
+    }
+
+    let ids = |events: Vec<events::EventLog>| -> Vec<events::EventId> {
+        events.into_iter().map(|e| e.id).collect()
+    };
+    let list = |order: SortOrder| {
+        module_impl
+            .list(events::ListArgs {
+                count: None,
+                order: Some(order),
+                filter: None,
+            })
+            .unwrap()
+            .events
+    };
+
+    assert_eq!(
+        ids(list_all_pages(&module_impl, SortOrder::Ascending)),
+        ids(list(SortOrder::Ascending))
+    );
+    assert_eq!(
+        ids(list_all_pages(&module_impl, SortOrder::Descending)),
+        ids(list(SortOrder::Descending))
+    );
+}
+
+#[test]
+fn list_page_last_page_has_no_cursor() {
+    let Setup {
+        mut module_impl,
+        id,
+        ..
+    } = setup();
+    send(&mut module_impl, id, identity(1));
+    send(&mut module_impl, id, identity(1));
+
+    let page = module_impl
+        .list_page(
+            &id,
+            ListPageArgs {
+                count: Some(2),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(page.events.len(), 2);
+    assert!(page.cursor.is_none());
+}
+
+#[test]
+fn list_uses_event_index() {
+    use many_ledger::migration::event_index::EVENT_INDEX_MIGRATION;
+
+    let mut harness = Setup::new_with_migrations(true, [(2, &EVENT_INDEX_MIGRATION)], false);
+    harness.set_balance(harness.id, 1_000_000, *MFX_SYMBOL);
+
+    // Events logged before the migration are indexed by it, events logged after
+    // as they come.
+    for i in 1..5 {
+        harness.block(|h| {
+            h.send_(h.id, identity(i), 10u32);
+            h.send_(h.id, identity(i + 1), 10u32);
+        });
+    }
+
+    let list = |filter: events::EventFilter| {
+        harness
+            .module_impl
+            .list(events::ListArgs {
+                count: None,
+                order: None,
+                filter: Some(filter),
+            })
+            .unwrap()
+            .events
+    };
+    let all = list(events::EventFilter::default());
+    assert_eq!(all.len(), 8);
+
+    for i in 1..6 {
+        let expected: Vec<events::EventLog> = all
+            .iter()
+            .filter(|e| e.is_about(identity(i)))
+            .cloned()
+            .collect();
+        let indexed = list(events::EventFilter {
+            account: Some(vec![identity(i)].into()),
+            ..events::EventFilter::default()
+        });
+        assert_eq!(
+            indexed.iter().map(|e| &e.id).collect::<Vec<_>>(),
+            expected.iter().map(|e| &e.id).collect::<Vec<_>>()
+        );
+    }
+
+    let sends = list(events::EventFilter {
+        kind: Some(vec![events::EventKind::Send].into()),
+        ..events::EventFilter::default()
+    });
+    assert_eq!(sends.len(), 8);
+}
+
+#[test]
+fn list_page_account_names() {
+    let Setup {
+        mut module_impl,
+        id,
+        cred_id,
+        public_key,
+        ..
+    } = setup();
+    let recall_phrase = module_impl
+        .store(
+            &id,
+            idstore::StoreArgs {
+                address: id,
+                cred_id,
+                public_key,
+            },
+        )
+        .unwrap()
+        .0;
+    send(&mut module_impl, identity(2), identity(3));
+    send(&mut module_impl, identity(2), id);
+
+    let page = module_impl
+        .list_page(
+            &id,
+            ListPageArgs {
+                account_names: Some(vec![recall_phrase.join(" ")]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(page.events.len(), 1);
+    assert!(page.events[0].is_about(id));
+
+    let result = module_impl.list_page(
+        &id,
+        ListPageArgs {
+            account_names: Some(vec!["unknown name".to_string()]),
+            ..Default::default()
+        },
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn list_page_proofs() {
+    use many_modules::abci_backend::ManyAbciModuleBackend;
+
+    let mut harness = Setup::new(true);
+    let id = harness.id;
+    harness.set_balance(id, 1_000, *MFX_SYMBOL);
+    harness.block(|h| h.send_(id, identity(1), 10u64));
+    harness.block(|h| h.send_(id, identity(2), 10u64));
+
+    let args = ListPageArgs {
+        count: Some(10),
+        ..Default::default()
+    };
+    let page = harness.module_impl.list_page(&id, args.clone()).unwrap();
+    assert!(page.proofs.is_none());
+
+    let page = harness
+        .module_impl
+        .list_page(
+            &id,
+            ListPageArgs {
+                proof: Some(true),
+                ..args
+            },
+        )
+        .unwrap();
+    let info = harness.module_impl.info().unwrap();
+    assert_eq!(page.height, Some(info.height));
+
+    let proofs = page.proofs.unwrap();
+    assert_eq!(proofs.len(), page.events.len());
+    let hash: [u8; 32] = info.hash.as_slice().try_into().unwrap();
+    for proof in proofs {
+        let proof = proof.expect("Events in the store have a proof");
+        assert!(merk::proofs::query::verify(&proof, hash).is_ok());
+    }
+}
+
+#[test]
+fn list_page_symbol_uses_index() {
+    use many_ledger::migration::event_index::SYMBOL_EVENT_INDEX_MIGRATION;
+
+    let mut harness = Setup::new_with_migrations(true, [(2, &SYMBOL_EVENT_INDEX_MIGRATION)], false);
+    harness.set_balance(harness.id, 1_000_000, *MFX_SYMBOL);
+    for i in 1..5 {
+        harness.block(|h| h.send_(h.id, identity(i), 10u32));
+    }
+
+    let list = |symbol, account: Option<Address>| {
+        harness
+            .module_impl
+            .list_page(
+                &identity(1),
+                ListPageArgs {
+                    filter: Some(events::EventFilter {
+                        account: account.map(|a| vec![a].into()),
+                        kind: Some(vec![events::EventKind::Send].into()),
+                        ..events::EventFilter::default()
+                    }),
+                    symbol: Some(symbol),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .events
+    };
+    assert_eq!(list(*MFX_SYMBOL, None).len(), 4);
+    assert_eq!(list(*MFX_SYMBOL, Some(identity(2))).len(), 1);
+    assert!(list(identity(1000), None).is_empty());
+}
+
+#[test]
+fn list_count_matches_filter() {
+    use many_ledger::migration::event_index::EVENT_INDEX_MIGRATION;
+    use many_ledger::module::events_page::ListCountArgs;
+
+    let mut harness = Setup::new_with_migrations(true, [(2, &EVENT_INDEX_MIGRATION)], false);
+    harness.set_balance(harness.id, 1_000_000, *MFX_SYMBOL);
+    for i in 1..5 {
+        harness.block(|h| {
+            h.send_(h.id, identity(i), 10u32);
+            h.send_(h.id, identity(i + 1), 10u32);
+        });
+    }
+
+    let count = |filter: events::EventFilter, symbol| {
+        harness
+            .module_impl
+            .list_count(
+                &identity(1),
+                ListCountArgs {
+                    filter: Some(filter),
+                    symbol,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .count
+    };
+    let account = |i| events::EventFilter {
+        account: Some(vec![identity(i)].into()),
+        ..events::EventFilter::default()
+    };
+
+    assert_eq!(count(events::EventFilter::default(), None), 8);
+    // Served by the index.
+    assert_eq!(count(account(1), None), 1);
+    assert_eq!(count(account(2), None), 2);
+    // Read the events.
+    assert_eq!(count(account(2), Some(*MFX_SYMBOL)), 2);
+    assert_eq!(count(account(2), Some(identity(1000))), 0);
+    assert_eq!(
+        count(
+            events::EventFilter {
+                account: Some(vec![identity(1), identity(5)].into()),
+                ..events::EventFilter::default()
+            },
+            None
+        ),
+        2
+    );
+}
+
+#[test]
+fn list_page_descending_offset() {
+    use many_ledger::migration::event_index::EVENT_INDEX_MIGRATION;
+
+    let mut harness = Setup::new_with_migrations(true, [(2, &EVENT_INDEX_MIGRATION)], false);
+    harness.set_balance(harness.id, 1_000_000, *MFX_SYMBOL);
+    for i in 1..5 {
+        harness.block(|h| {
+            h.send_(h.id, identity(i), 10u32);
+            h.send_(h.id, identity(i + 1), 10u32);
+        });
+    }
+
+    let ids = |events: Vec<events::EventLog>| -> Vec<events::EventId> {
+        events.into_iter().map(|e| e.id).collect()
+    };
+    let page = |symbol, offset, cursor| {
+        harness
+            .module_impl
+            .list_page(
+                &identity(1),
+                ListPageArgs {
+                    count: Some(2),
+                    order: Some(SortOrder::Descending),
+                    filter: Some(events::EventFilter {
+                        account: Some(vec![harness.id].into()),
+                        ..events::EventFilter::default()
+                    }),
+                    cursor,
+                    symbol,
+                    offset,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+    };
+    let all = ids(harness
+        .module_impl
+        .list(events::ListArgs {
+            count: None,
+            order: Some(SortOrder::Descending),
+            filter: None,
+        })
+        .unwrap()
+        .events);
+    assert_eq!(all.len(), 8);
+
+    // Skipped on the index keys, or after reading the events.
+    for symbol in [None, Some(*MFX_SYMBOL)] {
+        let result = page(symbol, Some(3), None);
+        assert_eq!(ids(result.events), all[3..5].to_vec());
+        assert_eq!(result.cursor, Some(all[4].clone()));
+
+        let result = page(symbol, Some(1), Some(all[4].clone()));
+        assert_eq!(ids(result.events), all[6..8].to_vec());
+        assert_eq!(result.cursor, None);
+
+        assert!(page(symbol, Some(8), None).events.is_empty());
+    }
+}
+
+#[test]
+fn event_ids_numbered_from_height() {
+    use many_ledger::migration::event_id::EVENT_ID_MIGRATION;
+    use many_ledger::storage::event::{event_id, event_id_height, event_id_index};
+
+    let mut harness = Setup::new_with_migrations(true, [(2, &EVENT_ID_MIGRATION)], false);
+    harness.set_balance(harness.id, 1_000_000, *MFX_SYMBOL);
+    let mut heights = vec![];
+    for i in 1..5 {
+        let (height, _) = harness.block(|h| {
+            h.send_(h.id, identity(i), 10u32);
+            h.send_(h.id, identity(i + 1), 10u32);
+        });
+        heights.push(height);
+    }
+    assert_eq!(heights, vec![1, 2, 3, 4]);
+
+    let page = harness
+        .module_impl
+        .list_page(
+            &harness.id,
+            ListPageArgs {
+                order: Some(SortOrder::Ascending),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let events: Vec<_> = page
+        .events
+        .iter()
+        .map(|e| (event_id_height(&e.id), event_id_index(&e.id)))
+        .zip(page.heights.unwrap())
+        .collect();
+
+    // Events of the blocks after the activation height are numbered from
+    // their own height, events before it keep the legacy numbering.
+    assert!(events.contains(&((Some(0), Some(1)), Some(2))));
+    for height in [3, 4] {
+        for index in [1, 2] {
+            let id = event_id(height, index);
+            assert_eq!(event_id_height(&id), Some(height));
+            assert_eq!(event_id_index(&id), Some(index));
+            assert!(events.contains(&((Some(height), Some(index)), Some(height))));
+        }
+    }
+    assert_eq!(
+        events
+            .iter()
+            .filter(|(_, height)| *height > Some(2))
+            .count(),
+        4
+    );
+}
@@ -0,0 +1,153 @@
+use many_error::ManyError;
+use many_identity::testing::identity;
+use many_identity::Address;
+use many_ledger::error;
+use many_ledger::json::{FaucetJson, InitialStateJson};
+use many_ledger::module::faucet::{FaucetClaimArgs, FaucetInfoArgs, FaucetModuleBackend};
+use many_ledger::module::LedgerModuleImpl;
+use many_ledger::storage::clock::SimulatedClock;
+use many_ledger_test_utils::*;
+use many_modules::ledger::{BalanceArgs, LedgerModuleBackend};
+use many_types::ledger::TokenAmount;
+use many_types::Timestamp;
+use std::collections::BTreeMap;
+
+const PERIOD: u64 = 3600;
+
+fn faucet_account() -> Address {
+    identity(100)
+}
+
+fn setup(faucet: Option<FaucetJson>) -> (LedgerModuleImpl, SimulatedClock, tempfile::TempDir) {
+    let store_path = tempfile::tempdir().expect("Could not create a temporary dir.");
+    let mut state = InitialStateJson::read("../../staging/ledger_state.json5")
+        .or_else(|_| InitialStateJson::read("staging/ledger_state.json5"))
+        .expect("Could not read initial state.");
+    state.hash = None;
+    state.faucet = faucet;
+
+    let clock = SimulatedClock::new(Timestamp::new(1_000_000).unwrap());
+    let mut module_impl = LedgerModuleImpl::new(state, None, store_path.path(), false)
+        .unwrap()
+        .with_clock(clock.clone());
+    module_impl
+        .set_balance_only_for_testing(faucet_account(), 1000, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+    (module_impl, clock, store_path)
+}
+
+fn setup_with_faucet() -> (LedgerModuleImpl, SimulatedClock, tempfile::TempDir) {
+    setup(Some(FaucetJson {
+        account: faucet_account(),
+        amounts: BTreeMap::from([(*MFX_SYMBOL, TokenAmount::from(100u64))]),
+        period: PERIOD,
+    }))
+}
+
+fn claim(module_impl: &mut LedgerModuleImpl, to: Address) -> Result<TokenAmount, ManyError> {
+    module_impl
+        .claim(
+            &to,
+            FaucetClaimArgs {
+                symbol: *MFX_SYMBOL,
+                to: None,
+            },
+        )
+        .map(|r| r.amount)
+}
+
+fn balance(module_impl: &LedgerModuleImpl, account: Address) -> TokenAmount {
+    module_impl
+        .balance(
+            &account,
+            BalanceArgs {
+                account: Some(account),
+                symbols: Some(vec![*MFX_SYMBOL].into()),
+            },
+        )
+        .unwrap()
+        .balances
+        .remove(&*MFX_SYMBOL)
+        .unwrap_or_else(TokenAmount::zero)
+}
+
+#[test]
+fn claim_once_per_period() {
+    let (mut module_impl, clock, _dir) = setup_with_faucet();
+
+    assert_eq!(
+        claim(&mut module_impl, identity(1)).unwrap(),
+        TokenAmount::from(100u64)
+    );
+    assert_eq!(
+        balance(&module_impl, identity(1)),
+        TokenAmount::from(100u64)
+    );
+    assert_eq!(
+        balance(&module_impl, faucet_account()),
+        TokenAmount::from(900u64)
+    );
+
+    clock.advance(PERIOD - 10);
+    assert_many_err(
+        claim(&mut module_impl, identity(1)),
+        error::faucet_claim_too_soon(10),
+    );
+    let info = module_impl
+        .info(&identity(1), FaucetInfoArgs::default())
+        .unwrap();
+    assert_eq!(info.retry_after, BTreeMap::from([(*MFX_SYMBOL, 10)]));
+
+    // The period is per identity.
+    claim(&mut module_impl, identity(2)).unwrap();
+
+    clock.advance(10);
+    claim(&mut module_impl, identity(1)).unwrap();
+    assert_eq!(
+        balance(&module_impl, identity(1)),
+        TokenAmount::from(200u64)
+    );
+}
+
+#[test]
+fn claim_without_faucet() {
+    let (mut module_impl, _clock, _dir) = setup(None);
+    assert_many_err(
+        claim(&mut module_impl, identity(1)),
+        error::faucet_disabled(),
+    );
+    let info = module_impl
+        .info(&identity(1), FaucetInfoArgs::default())
+        .unwrap();
+    assert!(info.config.is_none());
+}
+
+#[test]
+fn claim_empty_faucet() {
+    let (mut module_impl, _clock, _dir) = setup_with_faucet();
+    for i in 1..=10 {
+        claim(&mut module_impl, identity(i)).unwrap();
+    }
+    assert!(claim(&mut module_impl, identity(11)).is_err());
+
+    // A failed claim does not count.
+    module_impl
+        .set_balance_only_for_testing(faucet_account(), 1000, *MFX_SYMBOL)
+        .unwrap();
+    claim(&mut module_impl, identity(11)).unwrap();
+}
+
+#[test]
+fn unknown_faucet_symbol() {
+    let store_path = tempfile::tempdir().unwrap();
+    let mut state = InitialStateJson::read("../../staging/ledger_state.json5")
+        .or_else(|_| InitialStateJson::read("staging/ledger_state.json5"))
+        .unwrap();
+    state.hash = None;
+    state.faucet = Some(FaucetJson {
+        account: faucet_account(),
+        amounts: BTreeMap::from([(identity(1000), TokenAmount::from(1u64))]),
+        period: PERIOD,
+    });
+    assert!(LedgerModuleImpl::new(state, None, store_path.path(), false).is_err());
+}
@@ -0,0 +1,213 @@
+use many_identity::testing::identity;
+use many_identity::Address;
+use many_ledger::error;
+use many_ledger::json::{InitialStateJson, TransferFeeJson};
+use many_ledger::migration::data::{
+    ACCOUNT_COUNT_DATA_ATTRIBUTE, ACCOUNT_TOTAL_COUNT_INDEX, NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX,
+};
+use many_ledger::module::fees::{FeeInfoArgs, LedgerFeesModuleBackend};
+use many_ledger::module::LedgerModuleImpl;
+use many_ledger::storage::fees::TransferFee;
+use many_ledger_test_utils::*;
+use many_migration::MigrationConfig;
+use many_modules::abci_backend::{AbciBlock, ManyAbciModuleBackend};
+use many_modules::data::{DataModuleBackend, DataQueryArgs};
+use many_modules::ledger;
+use many_modules::ledger::LedgerCommandsModuleBackend;
+use many_types::ledger::TokenAmount;
+use many_types::VecOrSingle;
+use num_bigint::BigInt;
+use std::collections::{BTreeMap, BTreeSet};
+
+fn collector() -> Address {
+    identity(100)
+}
+
+fn setup_with_fee(fee: TransferFeeJson) -> (LedgerModuleImpl, tempfile::TempDir) {
+    let store_path = tempfile::tempdir().expect("Could not create a temporary dir.");
+    let mut state = InitialStateJson::read("../../staging/ledger_state.json5")
+        .or_else(|_| InitialStateJson::read("staging/ledger_state.json5"))
+        .expect("Could not read initial state.");
+    state.hash = None;
+    state.fee_collector = Some(collector());
+    state.fees = Some(BTreeMap::from([(*MFX_SYMBOL, fee)]));
+
+    let module_impl = LedgerModuleImpl::new(state, None, store_path.path(), false).unwrap();
+    (module_impl, store_path)
+}
+
+fn send(
+    module_impl: &mut LedgerModuleImpl,
+    from: Address,
+    to: Address,
+    amount: u64,
+) -> Result<(), many_error::ManyError> {
+    module_impl
+        .send(
+            &from,
+            ledger::SendArgs {
+                from: Some(from),
+                to,
+                amount: amount.into(),
+                symbol: *MFX_SYMBOL,
+                memo: None,
+            },
+        )
+        .map(|_| ())
+}
+
+#[test]
+fn flat_fee() {
+    let (mut module_impl, _store) = setup_with_fee(TransferFeeJson::Flat(10u64.into()));
+    module_impl
+        .set_balance_only_for_testing(identity(1), 1000, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+
+    send(&mut module_impl, identity(1), identity(2), 100).unwrap();
+    verify_balance(&module_impl, identity(1), *MFX_SYMBOL, 890u64.into());
+    verify_balance(&module_impl, identity(2), *MFX_SYMBOL, 100u64.into());
+    verify_balance(&module_impl, collector(), *MFX_SYMBOL, 10u64.into());
+}
+
+#[test]
+fn basis_points_fee() {
+    // 2.5%
+    let (mut module_impl, _store) = setup_with_fee(TransferFeeJson::BasisPoints(250));
+    module_impl
+        .set_balance_only_for_testing(identity(1), 1000, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+
+    send(&mut module_impl, identity(1), identity(2), 400).unwrap();
+    verify_balance(&module_impl, identity(1), *MFX_SYMBOL, 590u64.into());
+    verify_balance(&module_impl, identity(2), *MFX_SYMBOL, 400u64.into());
+    verify_balance(&module_impl, collector(), *MFX_SYMBOL, 10u64.into());
+}
+
+#[test]
+fn fee_counts_toward_insufficient_funds() {
+    let (mut module_impl, _store) = setup_with_fee(TransferFeeJson::Flat(10u64.into()));
+    module_impl
+        .set_balance_only_for_testing(identity(1), 100, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+
+    let result = send(&mut module_impl, identity(1), identity(2), 100);
+    assert_eq!(
+        result.unwrap_err().code(),
+        error::insufficient_funds("", "").code()
+    );
+    verify_balance(&module_impl, identity(1), *MFX_SYMBOL, 100u64.into());
+    verify_balance(&module_impl, collector(), *MFX_SYMBOL, 0u64.into());
+}
+
+#[test]
+fn collector_pays_no_fee() {
+    let (mut module_impl, _store) = setup_with_fee(TransferFeeJson::Flat(10u64.into()));
+    module_impl
+        .set_balance_only_for_testing(collector(), 100, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+
+    send(&mut module_impl, collector(), identity(2), 100).unwrap();
+    verify_balance(&module_impl, collector(), *MFX_SYMBOL, 0u64.into());
+    verify_balance(&module_impl, identity(2), *MFX_SYMBOL, 100u64.into());
+}
+
+#[test]
+fn fee_info() {
+    let (module_impl, _store) = setup_with_fee(TransferFeeJson::BasisPoints(250));
+
+    let info = module_impl
+        .fee_info(&identity(1), FeeInfoArgs::default())
+        .unwrap();
+    assert_eq!(info.collector, Some(collector()));
+    assert_eq!(
+        info.fees,
+        BTreeMap::from([(*MFX_SYMBOL, TransferFee::BasisPoints(250))])
+    );
+    assert_eq!(
+        TransferFee::BasisPoints(250).amount_for(&400u64.into()),
+        TokenAmount::from(10u64)
+    );
+
+    let info = module_impl
+        .fee_info(
+            &identity(1),
+            FeeInfoArgs {
+                symbols: Some(BTreeSet::from([identity(1000)])),
+            },
+        )
+        .unwrap();
+    assert!(info.fees.is_empty());
+}
+
+#[test]
+fn no_fees_by_default() {
+    let Setup {
+        module_impl, id, ..
+    } = setup();
+
+    let info = module_impl.fee_info(&id, FeeInfoArgs::default()).unwrap();
+    assert_eq!(info.collector, None);
+    assert!(info.fees.is_empty());
+}
+
+/// The total and non-zero account counts.
+fn account_counts(module_impl: &LedgerModuleImpl) -> (BigInt, BigInt) {
+    let query = module_impl
+        .query(
+            &identity(1),
+            DataQueryArgs {
+                indices: VecOrSingle(vec![
+                    ACCOUNT_TOTAL_COUNT_INDEX,
+                    NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX,
+                ]),
+            },
+        )
+        .unwrap();
+    (
+        query[&ACCOUNT_TOTAL_COUNT_INDEX]
+            .clone()
+            .try_into()
+            .unwrap(),
+        query[&NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX]
+            .clone()
+            .try_into()
+            .unwrap(),
+    )
+}
+
+#[test]
+fn fee_counts_collector_account() {
+    let store_path = tempfile::tempdir().expect("Could not create a temporary dir.");
+    let mut state = InitialStateJson::read("../../staging/ledger_state.json5")
+        .or_else(|_| InitialStateJson::read("staging/ledger_state.json5"))
+        .expect("Could not read initial state.");
+    state.hash = None;
+    state.fee_collector = Some(collector());
+    state.fees = Some(BTreeMap::from([(
+        *MFX_SYMBOL,
+        TransferFeeJson::Flat(10u64.into()),
+    )]));
+    let migrations: MigrationConfig = serde_json::from_str(&format!(
+        r#"{{ "migrations": [{}] }}"#,
+        MigrationHarness::from((1, &ACCOUNT_COUNT_DATA_ATTRIBUTE)).to_json_str()
+    ))
+    .unwrap();
+    let mut module_impl =
+        LedgerModuleImpl::new(state, Some(migrations), store_path.path(), true).unwrap();
+    module_impl
+        .begin_block(AbciBlock {
+            time: Some(1_000_000),
+        })
+        .unwrap();
+    module_impl.end_block().unwrap();
+    module_impl.commit().unwrap();
+
+    module_impl
+        .set_balance_only_for_testing(identity(1), 1000, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+    let (total, non_zero) = account_counts(&module_impl);
+
+    // The recipient and the collector both get their first balance.
+    send(&mut module_impl, identity(1), identity(2), 100).unwrap();
+    assert_eq!(account_counts(&module_impl), (total + 2u32, non_zero + 2u32));
+}
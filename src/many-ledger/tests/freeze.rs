@@ -0,0 +1,104 @@
+use many_identity::testing::identity;
+use many_identity::Address;
+use many_ledger::error;
+use many_ledger::json::InitialStateJson;
+use many_ledger::module::freeze::{FreezeArgs, LedgerFreezeModuleBackend, UnfreezeArgs};
+use many_ledger::module::LedgerModuleImpl;
+use many_ledger_test_utils::*;
+use many_modules::ledger;
+use many_modules::ledger::LedgerCommandsModuleBackend;
+
+fn compliance() -> Address {
+    identity(100)
+}
+
+fn setup_with_compliance() -> (LedgerModuleImpl, tempfile::TempDir) {
+    let store_path = tempfile::tempdir().expect("Could not create a temporary dir.");
+    let mut state = InitialStateJson::read("../../staging/ledger_state.json5")
+        .or_else(|_| InitialStateJson::read("staging/ledger_state.json5"))
+        .expect("Could not read initial state.");
+    state.hash = None;
+    state.compliance_identity = Some(compliance());
+
+    let mut module_impl = LedgerModuleImpl::new(state, None, store_path.path(), false).unwrap();
+    module_impl
+        .set_balance_only_for_testing(identity(1), 1000, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+    (module_impl, store_path)
+}
+
+fn send(
+    module_impl: &mut LedgerModuleImpl,
+    from: Address,
+    to: Address,
+) -> Result<(), many_error::ManyError> {
+    module_impl
+        .send(
+            &from,
+            ledger::SendArgs {
+                from: Some(from),
+                to,
+                amount: 10u64.into(),
+                symbol: *MFX_SYMBOL,
+                memo: None,
+            },
+        )
+        .map(|_| ())
+}
+
+#[test]
+fn freeze_unauthorized() {
+    let (mut module_impl, _store) = setup_with_compliance();
+    let result = module_impl.freeze(
+        &identity(1),
+        FreezeArgs {
+            account: identity(2),
+        },
+    );
+    assert_eq!(result.unwrap_err().code(), error::unauthorized().code());
+}
+
+#[test]
+fn frozen_cannot_send_or_receive() {
+    let (mut module_impl, _store) = setup_with_compliance();
+    module_impl
+        .freeze(
+            &compliance(),
+            FreezeArgs {
+                account: identity(2),
+            },
+        )
+        .unwrap();
+
+    let result = send(&mut module_impl, identity(1), identity(2));
+    assert_eq!(
+        result.unwrap_err().code(),
+        error::account_frozen(identity(2)).code()
+    );
+    verify_balance(&module_impl, identity(1), *MFX_SYMBOL, 1000u64.into());
+
+    module_impl
+        .freeze(
+            &compliance(),
+            FreezeArgs {
+                account: identity(1),
+            },
+        )
+        .unwrap();
+    let result = send(&mut module_impl, identity(1), identity(3));
+    assert_eq!(
+        result.unwrap_err().code(),
+        error::account_frozen(identity(1)).code()
+    );
+
+    module_impl
+        .unfreeze(
+            &compliance(),
+            UnfreezeArgs {
+                account: identity(1),
+            },
+        )
+        .unwrap();
+    assert!(send(&mut module_impl, identity(1), identity(3)).is_ok());
+    verify_balance(&module_impl, identity(3), *MFX_SYMBOL, 10u64.into());
+}
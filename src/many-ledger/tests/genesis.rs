@@ -0,0 +1,128 @@
+use many_ledger::error;
+use many_ledger::json::InitialStateJson;
+
+const IDENTITY: &str = "mahukzwuwgt3porn6q4vq4xu3mwy5gyskhouryzbscq7wb2iow";
+const HOLDER: &str = "maffbahksdwaqeenayy2gxke32hgb7aq4ao4wt745lsfs6wijp";
+const MFX: &str = "mqbfbahksdwaqeenayy2gxke32hgb7aq4ao4wt745lsfs6wiaaaaqnz";
+const ABC: &str = "mqdukzwuwgt3porn6q4vq4xu3mwy5gyskhouryzbscq7wb2iaaaaac6";
+
+fn parse(initial: &str, symbols: &str, extra: &str) -> Result<InitialStateJson, String> {
+    json5::from_str(&format!(
+        r#"{{ identity: "{IDENTITY}", initial: {initial}, symbols: {symbols}, {extra} }}"#
+    ))
+    .map_err(|e| e.to_string())
+}
+
+fn validate(initial: &str, symbols: &str, extra: &str) -> Result<(), many_error::ManyError> {
+    parse(initial, symbols, extra).unwrap().validate()
+}
+
+#[test]
+fn valid() {
+    let initial = format!(r#"{{ "{HOLDER}": {{ "MFX": 100, "{ABC}": 5 }} }}"#);
+    let symbols = format!(r#"{{ "{MFX}": "MFX", "{ABC}": "ABC" }}"#);
+    assert!(validate(&initial, &symbols, "").is_ok());
+}
+
+#[test]
+fn duplicate_symbol() {
+    let symbols = format!(r#"{{ "{MFX}": "MFX", "{MFX}": "ABC" }}"#);
+    let err = parse("{}", &symbols, "").unwrap_err();
+    assert!(err.contains("duplicate key"), "{err}");
+}
+
+#[test]
+fn duplicate_symbol_name() {
+    let symbols = format!(r#"{{ "{MFX}": "MFX", "{ABC}": "MFX" }}"#);
+    assert_eq!(
+        validate("{}", &symbols, "").unwrap_err().code(),
+        error::invalid_genesis("").code()
+    );
+}
+
+#[test]
+fn undefined_symbol() {
+    let initial = format!(r#"{{ "{HOLDER}": {{ "ABC": 100 }} }}"#);
+    let symbols = format!(r#"{{ "{MFX}": "MFX" }}"#);
+    let err = validate(&initial, &symbols, "").unwrap_err();
+    assert_eq!(err.code(), error::invalid_genesis("").code());
+    assert!(err.to_string().contains("'ABC'"), "{err}");
+}
+
+#[test]
+fn symbol_listed_twice() {
+    let initial = format!(r#"{{ "{HOLDER}": {{ "MFX": 100, "{MFX}": 5 }} }}"#);
+    let symbols = format!(r#"{{ "{MFX}": "MFX" }}"#);
+    assert_eq!(
+        validate(&initial, &symbols, "").unwrap_err().code(),
+        error::invalid_genesis("").code()
+    );
+}
+
+#[test]
+fn over_maximum() {
+    let initial = format!(r#"{{ "{HOLDER}": {{ "MFX": 100 }} }}"#);
+    let symbols = format!(r#"{{ "{MFX}": "MFX" }}"#);
+    let meta =
+        format!(r#"symbols_meta: {{ "{MFX}": {{ name: "Manifest", decimals: 9, maximum: 99 }} }}"#);
+    assert_eq!(
+        validate(&initial, &symbols, &meta).unwrap_err().code(),
+        error::invalid_genesis("").code()
+    );
+}
+
+#[test]
+fn anonymous_identity() {
+    let initial = r#"{ "maa": { "MFX": 100 } }"#;
+    let symbols = format!(r#"{{ "{MFX}": "MFX" }}"#);
+    assert_eq!(
+        validate(initial, &symbols, "").unwrap_err().code(),
+        error::invalid_genesis("").code()
+    );
+}
+
+#[test]
+fn versions() {
+    let symbols = format!(r#"{{ "{MFX}": "MFX" }}"#);
+    assert!(validate("{}", &symbols, "version: 1").is_ok());
+    assert!(validate("{}", &symbols, "version: 2").is_ok());
+    assert_eq!(
+        validate("{}", &symbols, "version: 3").unwrap_err().code(),
+        error::invalid_genesis("").code()
+    );
+}
+
+#[test]
+fn multisig_defaults_need_version_2() {
+    let symbols = format!(r#"{{ "{MFX}": "MFX" }}"#);
+    let defaults = "multisig_defaults: { timeout_in_secs: 60, execute_automatically: true }";
+    assert_eq!(
+        validate("{}", &symbols, defaults).unwrap_err().code(),
+        error::invalid_genesis("").code()
+    );
+    assert!(validate("{}", &symbols, &format!("version: 2, {defaults}")).is_ok());
+}
+
+#[test]
+fn invalid_account_role() {
+    let symbols = format!(r#"{{ "{MFX}": "MFX" }}"#);
+    let accounts = format!(
+        r#"accounts: [{{ subresource_id: 0, roles: {{ "{HOLDER}": ["superuser"] }}, features: [{{ id: 0 }}] }}]"#
+    );
+    // Version 1 files are not checked, as before.
+    assert!(validate("{}", &symbols, &accounts).is_ok());
+    let err = validate("{}", &symbols, &format!("version: 2, {accounts}")).unwrap_err();
+    assert_eq!(err.code(), error::invalid_genesis("").code());
+    assert!(err.to_string().contains("'superuser'"), "{err}");
+}
+
+#[test]
+fn basis_points_over_maximum() {
+    let symbols = format!(r#"{{ "{MFX}": "MFX" }}"#);
+    let fees = |bps: u32| format!(r#"fees: {{ "{MFX}": {{ basis_points: {bps} }} }}"#);
+    assert!(validate("{}", &symbols, &fees(10_000)).is_ok());
+    assert_eq!(
+        validate("{}", &symbols, &fees(10_001)).unwrap_err().code(),
+        error::invalid_transfer_fee("", "", "").code()
+    );
+}
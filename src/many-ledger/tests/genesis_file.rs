@@ -0,0 +1,57 @@
+use many_ledger::error;
+use many_ledger::genesis_file;
+use many_ledger::json::InitialStateJson;
+use many_ledger::module::LedgerModuleImpl;
+
+const SPEC: &str = r#"{
+  // The staging ledger.
+  identity: "mahukzwuwgt3porn6q4vq4xu3mwy5gyskhouryzbscq7wb2iow",
+  symbols: { "mqbfbahksdwaqeenayy2gxke32hgb7aq4ao4wt745lsfs6wiaaaaqnz": "MFX" },
+  balances: [
+    { identity: "maffbahksdwaqeenayy2gxke32hgb7aq4ao4wt745lsfs6wijp", symbol: "MFX", amount: 1000 },
+    {
+      identity: "mqdukzwuwgt3porn6q4vq4xu3mwy5gyskhouryzbscq7wb2iaaaaq25",
+      symbol: "mqbfbahksdwaqeenayy2gxke32hgb7aq4ao4wt745lsfs6wiaaaaqnz",
+      amount: 2000,
+    },
+  ],
+  hash: "stale",
+}"#;
+
+#[test]
+fn builds_state_with_hash() {
+    let state = genesis_file::build(SPEC).unwrap();
+    let state: InitialStateJson = json5::from_str(&state).unwrap();
+    assert_eq!(state.initial.len(), 2);
+    let hash = state.hash.clone().unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let module_impl = LedgerModuleImpl::new(state, None, dir.path(), false).unwrap();
+    assert_eq!(hex::encode(module_impl.hash()), hash);
+}
+
+#[test]
+fn builds_deterministically() {
+    assert_eq!(
+        genesis_file::build(SPEC).unwrap(),
+        genesis_file::build(SPEC).unwrap()
+    );
+}
+
+#[test]
+fn unknown_symbol() {
+    let spec = SPEC.replace(r#"symbol: "MFX""#, r#"symbol: "ABC""#);
+    assert_eq!(
+        genesis_file::build(&spec).unwrap_err(),
+        error::invalid_genesis("unknown symbol 'ABC'".to_string())
+    );
+}
+
+#[test]
+fn duplicate_balance() {
+    let spec = SPEC.replace(
+        "mqdukzwuwgt3porn6q4vq4xu3mwy5gyskhouryzbscq7wb2iaaaaq25",
+        "maffbahksdwaqeenayy2gxke32hgb7aq4ao4wt745lsfs6wijp",
+    );
+    assert!(genesis_file::build(&spec).is_err());
+}
@@ -0,0 +1,34 @@
+use many_ledger::module::genesis::LedgerGenesisModuleBackend;
+use many_ledger_test_utils::*;
+use many_modules::abci_backend::ManyAbciModuleBackend;
+use many_modules::EmptyArg;
+use many_types::ledger::TokenAmount;
+
+#[test]
+fn genesis_report() {
+    let mut harness = Setup::new(true);
+    let genesis_hash = ManyAbciModuleBackend::info(&harness.module_impl)
+        .unwrap()
+        .hash;
+
+    let report = harness
+        .module_impl
+        .genesis_report(&harness.id, EmptyArg)
+        .unwrap();
+    assert_eq!(report.hash.as_ref(), genesis_hash.as_ref());
+    assert_eq!(report.allocations.len(), 2);
+    assert_eq!(
+        report.totals[&*MFX_SYMBOL],
+        TokenAmount::from(2_000_000_000u64)
+    );
+
+    // The report does not change with the state.
+    harness.block(|_| ());
+    assert_eq!(
+        harness
+            .module_impl
+            .genesis_report(&harness.id, EmptyArg)
+            .unwrap(),
+        report
+    );
+}
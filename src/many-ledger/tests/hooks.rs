@@ -0,0 +1,120 @@
+use many_ledger::hooks::{CommitHook, CommitHooks, CommitInfo};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+fn info() -> CommitInfo {
+    CommitInfo {
+        height: 12,
+        hash: vec![0xab, 0xcd],
+        event_count: 34,
+    }
+}
+
+fn notify(hook: CommitHook, timeout: Duration) {
+    for handle in CommitHooks::new(vec![hook], timeout).notify(&info()) {
+        handle.join().unwrap();
+    }
+}
+
+#[cfg(unix)]
+fn script(dir: &Path, content: &str) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = dir.join("hook.sh");
+    std::fs::write(&path, format!("#!/bin/sh\n{content}\n")).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[test]
+fn parse() {
+    assert_eq!(
+        CommitHook::from_str("/usr/bin/hook").unwrap(),
+        CommitHook::Exec("/usr/bin/hook".into())
+    );
+    assert_eq!(
+        CommitHook::from_str("http://localhost:8080/commit").unwrap(),
+        CommitHook::Http {
+            host: "localhost".to_string(),
+            port: 8080,
+            path: "/commit".to_string(),
+        }
+    );
+    assert_eq!(
+        CommitHook::from_str("http://example.com").unwrap(),
+        CommitHook::Http {
+            host: "example.com".to_string(),
+            port: 80,
+            path: "/".to_string(),
+        }
+    );
+    assert!(CommitHook::from_str("https://example.com").is_err());
+    assert!(CommitHook::from_str("http://example.com:port").is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn exec() {
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("out");
+    let hook = script(dir.path(), &format!("echo \"$@\" > {}", out.display()));
+
+    notify(CommitHook::Exec(hook), Duration::from_secs(10));
+    assert_eq!(std::fs::read_to_string(out).unwrap(), "12 abcd 34\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn exec_timeout() {
+    let dir = tempfile::tempdir().unwrap();
+    let hook = script(dir.path(), "sleep 10");
+
+    let start = Instant::now();
+    notify(CommitHook::Exec(hook), Duration::from_millis(100));
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn http() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line == "\r\n" {
+                break;
+            }
+            if let Some(value) = line.strip_prefix("Content-Length: ") {
+                content_length = value.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        stream
+            .write_all(b"HTTP/1.1 204 No Content\r\n\r\n")
+            .unwrap();
+        (request_line, String::from_utf8(body).unwrap())
+    });
+
+    notify(
+        CommitHook::Http {
+            host: "127.0.0.1".to_string(),
+            port,
+            path: "/commit".to_string(),
+        },
+        Duration::from_secs(10),
+    );
+    let (request_line, body) = server.join().unwrap();
+    assert_eq!(request_line, "POST /commit HTTP/1.1\r\n");
+    assert_eq!(body, r#"{"height":12,"hash":"abcd","event_count":34}"#);
+}
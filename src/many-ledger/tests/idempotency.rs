@@ -0,0 +1,85 @@
+use many_error::ManyError;
+use many_identity::testing::identity;
+use many_ledger::error;
+use many_ledger::module::idempotency::{
+    IdempotencyKeyUsedArgs, LedgerIdempotencyModuleBackend, SendOnceArgs,
+};
+use many_ledger::storage::idempotency::MAXIMUM_IDEMPOTENCY_WINDOW;
+use many_ledger_test_utils::*;
+use many_types::ledger::TokenAmount;
+
+fn send_once(h: &mut Setup, key: &[u8], window_in_secs: Option<u64>) -> Result<(), ManyError> {
+    h.module_impl
+        .send_once(
+            &identity(1),
+            SendOnceArgs {
+                from: None,
+                to: identity(2),
+                amount: 100u64.into(),
+                symbol: *MFX_SYMBOL,
+                memo: None,
+                idempotency_key: key.to_vec().into(),
+                window_in_secs,
+            },
+        )
+        .map(|_| ())
+}
+
+fn key_used(h: &Setup, key: &[u8]) -> bool {
+    h.module_impl
+        .idempotency_key_used(
+            &identity(1),
+            IdempotencyKeyUsedArgs {
+                sender: None,
+                idempotency_key: key.to_vec().into(),
+            },
+        )
+        .unwrap()
+        .used
+}
+
+#[test]
+fn duplicate_is_rejected_within_window() {
+    let mut h = Setup::new(true);
+    let (_, r) = h.block(|h| {
+        h.set_balance(identity(1), 1000, *MFX_SYMBOL);
+        send_once(h, b"nonce", Some(10))
+    });
+    assert!(r.is_ok());
+    assert!(key_used(&h, b"nonce"));
+
+    let (_, r) = h.block(|h| send_once(h, b"nonce", Some(10)));
+    assert_eq!(
+        r.unwrap_err().code(),
+        error::duplicate_idempotency_key("").code()
+    );
+    assert_eq!(h.balance_(identity(1)), TokenAmount::from(900u64));
+
+    // Other keys are independent.
+    let (_, r) = h.block(|h| send_once(h, b"other", None));
+    assert!(r.is_ok());
+    assert_eq!(h.balance_(identity(1)), TokenAmount::from(800u64));
+
+    // The key is forgotten after its window.
+    h.inc_time(20);
+    h.block(|_| ());
+    assert!(!key_used(&h, b"nonce"));
+    let (_, r) = h.block(|h| send_once(h, b"nonce", None));
+    assert!(r.is_ok());
+    assert_eq!(h.balance_(identity(1)), TokenAmount::from(700u64));
+}
+
+#[test]
+fn invalid_window() {
+    let mut h = Setup::new(true);
+    let (_, r) = h.block(|h| {
+        h.set_balance(identity(1), 1000, *MFX_SYMBOL);
+        send_once(h, b"nonce", Some(MAXIMUM_IDEMPOTENCY_WINDOW + 1))
+    });
+    assert_eq!(
+        r.unwrap_err().code(),
+        error::invalid_idempotency_window("").code()
+    );
+    assert_eq!(h.balance_(identity(1)), TokenAmount::from(1000u64));
+    assert!(!key_used(&h, b"nonce"));
+}
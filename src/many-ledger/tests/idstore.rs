@@ -1,5 +1,14 @@
 use many_error::ManyError;
+use many_identity::testing::identity;
 use many_identity::Address;
+use many_ledger::error;
+use many_ledger::module::idstore_alias::{
+    AddAliasArgs, GetAliasOwnerArgs, IdStoreAliasModuleBackend, ListAliasesArgs, RemoveAliasArgs,
+};
+use many_ledger::module::idstore_credentials::{
+    IdStoreCredentialsModuleBackend, ListFromAddressArgs, RemoveCredentialArgs,
+};
+use many_ledger::module::idstore_update::{IdStoreUpdateModuleBackend, RevokeArgs, UpdateArgs};
 use many_ledger::module::LedgerModuleImpl;
 use many_ledger_test_utils::*;
 use many_modules::idstore;
@@ -176,3 +185,245 @@ fn get_from_invalid_address() {
         idstore::entry_not_found("".to_string()).code()
     );
 }
+
+#[test]
+fn update() {
+    let SetupWithStore {
+        mut module_impl,
+        id,
+        public_key,
+        recall_phrase,
+        ..
+    } = setup_with_store();
+    let cred_id = CredentialId(vec![2; 16].into());
+
+    let result = module_impl.update(
+        &identity(1),
+        UpdateArgs {
+            address: id,
+            cred_id: cred_id.clone(),
+        },
+    );
+    assert_eq!(result.unwrap_err().code(), error::unauthorized().code());
+
+    module_impl
+        .update(
+            &id,
+            UpdateArgs {
+                address: id,
+                cred_id: cred_id.clone(),
+            },
+        )
+        .unwrap();
+    for result in [
+        module_impl.get_from_address(idstore::GetFromAddressArgs(id)),
+        module_impl.get_from_recall_phrase(idstore::GetFromRecallPhraseArgs(recall_phrase)),
+    ] {
+        let result = result.unwrap();
+        assert_eq!(result.cred_id, cred_id);
+        assert_eq!(result.public_key, public_key);
+    }
+}
+
+#[test]
+fn revoke() {
+    let SetupWithStore {
+        mut module_impl,
+        id,
+        recall_phrase,
+        ..
+    } = setup_with_store();
+
+    let result = module_impl.revoke(
+        &identity(1),
+        RevokeArgs {
+            recall_phrase: recall_phrase.clone(),
+        },
+    );
+    assert_eq!(result.unwrap_err().code(), error::unauthorized().code());
+
+    module_impl
+        .revoke(
+            &id,
+            RevokeArgs {
+                recall_phrase: recall_phrase.clone(),
+            },
+        )
+        .unwrap();
+    assert!(module_impl
+        .get_from_recall_phrase(idstore::GetFromRecallPhraseArgs(recall_phrase.clone()))
+        .is_err());
+    assert!(module_impl
+        .get_from_address(idstore::GetFromAddressArgs(id))
+        .is_err());
+
+    let result = module_impl.revoke(&id, RevokeArgs { recall_phrase });
+    assert_eq!(
+        result.unwrap_err().code(),
+        idstore::entry_not_found("").code()
+    );
+}
+
+#[test]
+fn multiple_credentials() {
+    let SetupWithStore {
+        mut module_impl,
+        id,
+        cred_id,
+        public_key,
+        recall_phrase,
+    } = setup_with_store();
+    let other_cred_id = CredentialId(vec![2; 16].into());
+    let other_recall_phrase = module_impl
+        .store(
+            &id,
+            idstore::StoreArgs {
+                address: id,
+                cred_id: other_cred_id.clone(),
+                public_key: public_key.clone(),
+            },
+        )
+        .unwrap()
+        .0;
+
+    let credentials = module_impl
+        .list_from_address(ListFromAddressArgs { address: id })
+        .unwrap()
+        .credentials;
+    assert_eq!(credentials.len(), 2);
+    assert_eq!(credentials[0].cred_id, cred_id);
+    assert_eq!(credentials[1].cred_id, other_cred_id);
+    assert_eq!(
+        module_impl
+            .get_from_address(idstore::GetFromAddressArgs(id))
+            .unwrap()
+            .cred_id,
+        other_cred_id
+    );
+    assert_eq!(
+        module_impl
+            .get_from_recall_phrase(idstore::GetFromRecallPhraseArgs(recall_phrase.clone()))
+            .unwrap()
+            .cred_id,
+        cred_id
+    );
+
+    let result = module_impl.remove_credential(
+        &identity(1),
+        RemoveCredentialArgs {
+            address: id,
+            cred_id: cred_id.clone(),
+        },
+    );
+    assert_eq!(result.unwrap_err().code(), error::unauthorized().code());
+
+    module_impl
+        .remove_credential(
+            &id,
+            RemoveCredentialArgs {
+                address: id,
+                cred_id: other_cred_id.clone(),
+            },
+        )
+        .unwrap();
+    let credentials = module_impl
+        .list_from_address(ListFromAddressArgs { address: id })
+        .unwrap()
+        .credentials;
+    assert_eq!(credentials.len(), 1);
+    assert_eq!(credentials[0].cred_id, cred_id);
+    assert_eq!(
+        module_impl
+            .get_from_address(idstore::GetFromAddressArgs(id))
+            .unwrap()
+            .cred_id,
+        cred_id
+    );
+    assert!(module_impl
+        .get_from_recall_phrase(idstore::GetFromRecallPhraseArgs(other_recall_phrase))
+        .is_err());
+    assert!(module_impl
+        .get_from_recall_phrase(idstore::GetFromRecallPhraseArgs(recall_phrase))
+        .is_ok());
+
+    let result = module_impl.remove_credential(
+        &id,
+        RemoveCredentialArgs {
+            address: id,
+            cred_id: other_cred_id,
+        },
+    );
+    assert_eq!(
+        result.unwrap_err().code(),
+        idstore::entry_not_found("").code()
+    );
+}
+
+#[test]
+fn aliases() {
+    let Setup {
+        mut module_impl,
+        id,
+        ..
+    } = setup();
+    let alias = id.with_subresource_id(1).unwrap();
+    let other_alias = id.with_subresource_id(2).unwrap();
+
+    module_impl.add_alias(&id, AddAliasArgs { alias }).unwrap();
+    module_impl
+        .add_alias(&id, AddAliasArgs { alias: other_alias })
+        .unwrap();
+    assert_eq!(
+        module_impl
+            .list_aliases(ListAliasesArgs { address: id })
+            .unwrap()
+            .aliases,
+        vec![alias, other_alias]
+    );
+    assert_eq!(
+        module_impl
+            .get_alias_owner(GetAliasOwnerArgs { alias })
+            .unwrap()
+            .address,
+        id
+    );
+
+    // Binding twice, or binding an address not controlled by the sender, fails.
+    let result = module_impl.add_alias(&id, AddAliasArgs { alias });
+    assert_eq!(result.unwrap_err().code(), idstore::existing_entry().code());
+    let result = module_impl.add_alias(
+        &id,
+        AddAliasArgs {
+            alias: identity(1).with_subresource_id(1).unwrap(),
+        },
+    );
+    assert_eq!(
+        result.unwrap_err().code(),
+        idstore::invalid_address("").code()
+    );
+    let result = module_impl.add_alias(&alias, AddAliasArgs { alias: other_alias });
+    assert_eq!(
+        result.unwrap_err().code(),
+        ManyError::invalid_identity().code()
+    );
+
+    let result = module_impl.remove_alias(&identity(1), RemoveAliasArgs { alias });
+    assert_eq!(result.unwrap_err().code(), error::unauthorized().code());
+    module_impl
+        .remove_alias(&id, RemoveAliasArgs { alias })
+        .unwrap();
+    assert_eq!(
+        module_impl
+            .list_aliases(ListAliasesArgs { address: id })
+            .unwrap()
+            .aliases,
+        vec![other_alias]
+    );
+    assert_eq!(
+        module_impl
+            .get_alias_owner(GetAliasOwnerArgs { alias })
+            .unwrap_err()
+            .code(),
+        idstore::entry_not_found("").code()
+    );
+}
@@ -0,0 +1,16 @@
+use many_ledger::error;
+use many_ledger::migration::tokens::TOKEN_MIGRATION;
+use many_ledger_test_utils::*;
+
+#[test]
+fn supply_matches_balances() {
+    let mut harness = Setup::new_with_migrations(false, [(0, &TOKEN_MIGRATION)], true);
+    assert!(harness.module_impl.check_invariants().is_ok());
+
+    // Balances set without minting break the supply invariant.
+    harness.set_balance(harness.id, 1_000, *MFX_SYMBOL);
+    assert_eq!(
+        harness.module_impl.check_invariants().unwrap_err().code(),
+        error::invariant_violated("").code()
+    );
+}
@@ -1,9 +1,14 @@
 use many_identity::testing::identity;
 use many_ledger::error;
+use many_ledger::json::{InitialStateJson, PayloadLimitsJson};
+use many_ledger::module::LedgerModuleImpl;
 use many_ledger_test_utils::*;
+use many_modules::account::{self, AccountModuleBackend, Role};
 use many_modules::ledger;
 use many_modules::ledger::LedgerCommandsModuleBackend;
+use many_types::Memo;
 use proptest::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
 
 proptest! {
     #[test]
@@ -90,3 +95,99 @@ fn send_invalid_account() {
     assert!(result.is_err());
     assert_eq!(result.unwrap_err().code(), error::unauthorized().code());
 }
+
+#[test]
+fn send_memo_too_large() {
+    let store_path = tempfile::tempdir().expect("Could not create a temporary dir.");
+    let mut state = InitialStateJson::read("../../staging/ledger_state.json5")
+        .or_else(|_| InitialStateJson::read("staging/ledger_state.json5"))
+        .expect("Could not read initial state.");
+    state.hash = None;
+    state.payload_limits = Some(PayloadLimitsJson {
+        max_memo_size: Some(10),
+        ..PayloadLimitsJson::default()
+    });
+
+    let mut module_impl = LedgerModuleImpl::new(state, None, store_path.path(), false).unwrap();
+    let id = identity(2);
+    module_impl
+        .set_balance_only_for_testing(id, 1000, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+
+    let send = |module_impl: &mut LedgerModuleImpl, memo: &str| {
+        module_impl.send(
+            &id,
+            ledger::SendArgs {
+                from: Some(id),
+                to: identity(1),
+                amount: 10u16.into(),
+                symbol: *MFX_SYMBOL,
+                memo: Some(Memo::try_from(memo).unwrap()),
+            },
+        )
+    };
+
+    assert!(send(&mut module_impl, "short").is_ok());
+    let result = send(&mut module_impl, "a memo that is way too long");
+    assert_eq!(
+        result.unwrap_err().code(),
+        error::memo_too_large(0, 0).code()
+    );
+    verify_balance(&module_impl, id, *MFX_SYMBOL, 990u16.into());
+}
+
+#[test]
+fn send_account_follows_roles() {
+    let SetupWithAccount {
+        mut module_impl,
+        account_id,
+        id,
+    } = setup_with_account(AccountType::Ledger);
+    module_impl
+        .set_balance_only_for_testing(account_id, 100, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+    let send = |module_impl: &mut LedgerModuleImpl| {
+        module_impl.send(
+            &identity(5),
+            ledger::SendArgs {
+                from: Some(account_id),
+                to: identity(1),
+                amount: 10u16.into(),
+                symbol: *MFX_SYMBOL,
+                memo: None,
+            },
+        )
+    };
+    let roles = BTreeMap::from([(identity(5), BTreeSet::from([Role::CanLedgerTransact]))]);
+
+    assert_eq!(
+        send(&mut module_impl).unwrap_err().code(),
+        account::errors::user_needs_role("").code()
+    );
+
+    module_impl
+        .add_roles(
+            &id,
+            account::AddRolesArgs {
+                account: account_id,
+                roles: roles.clone(),
+            },
+        )
+        .unwrap();
+    send(&mut module_impl).unwrap();
+    verify_balance(&module_impl, identity(1), *MFX_SYMBOL, 10u64.into());
+
+    module_impl
+        .remove_roles(
+            &id,
+            account::RemoveRolesArgs {
+                account: account_id,
+                roles,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        send(&mut module_impl).unwrap_err().code(),
+        account::errors::user_needs_role("").code()
+    );
+}
@@ -0,0 +1,88 @@
+use many_identity::testing::identity;
+use many_ledger::metrics::{GrowthRates, Metrics, StateMetrics};
+use many_ledger::module::growth::{GrowthArgs, LedgerGrowthModuleBackend};
+use many_ledger_test_utils::*;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+#[test]
+fn render() {
+    let metrics = Metrics::default();
+    metrics.record_call("ledger.send");
+    metrics.record_call("ledger.send");
+    metrics.record_call("abci.commit");
+    metrics.record_commit(Duration::from_millis(500));
+
+    let out = metrics.render(&StateMetrics {
+        height: 12,
+        events: 34,
+        storage_bytes: 56,
+        growth: GrowthRates {
+            events: BTreeMap::from([("ledger".to_string(), 1.5)]),
+            bytes: BTreeMap::from([("balances".to_string(), 100.0)]),
+            ..Default::default()
+        },
+    });
+    assert!(out.contains("many_ledger_calls_total{method=\"ledger.send\"} 2\n"));
+    assert!(out.contains("many_ledger_calls_total{method=\"abci.commit\"} 1\n"));
+    assert!(out.contains("many_ledger_commit_seconds_sum 0.5\n"));
+    assert!(out.contains("many_ledger_commit_seconds_count 1\n"));
+    assert!(out.contains("many_ledger_height 12\n"));
+    assert!(out.contains("many_ledger_events 34\n"));
+    assert!(out.contains("many_ledger_storage_bytes 56\n"));
+    assert!(out.contains("many_ledger_events_per_day{module=\"ledger\"} 1.5\n"));
+    assert!(out.contains("many_ledger_storage_bytes_per_day{root=\"balances\"} 100\n"));
+}
+
+#[test]
+fn unknown_methods_are_bounded() {
+    let metrics = Metrics::default();
+    for i in 0..1000 {
+        metrics.record_call(&format!("made.up{i}"));
+    }
+
+    let out = metrics.render(&StateMetrics::default());
+    let lines = out
+        .lines()
+        .filter(|l| l.starts_with("many_ledger_calls_total"))
+        .count();
+    assert_eq!(lines, 257);
+    assert!(out.contains("many_ledger_calls_total{method=\"other\"} 744\n"));
+}
+
+#[test]
+fn growth() {
+    let mut h = Setup::new(true);
+    h.module_impl = h.module_impl.with_growth_metrics(true);
+    let id = h.id;
+    h.set_balance(id, 1_000, *MFX_SYMBOL);
+    h.block(|h| h.send_(id, identity(1), 100u64));
+    h.block(|h| h.send_(id, identity(2), 100u64));
+
+    let days = h
+        .module_impl
+        .growth(&id, GrowthArgs::default())
+        .unwrap()
+        .days;
+    assert_eq!(days.len(), 1);
+    let today = days.values().next().unwrap();
+    assert_eq!(today.events["ledger"], 2);
+    assert_eq!(today.symbol_events[&*MFX_SYMBOL], 2);
+    assert!(today.bytes["balances"] > 0);
+    assert!(today.bytes["events"] > 0);
+    assert!(today.symbol_bytes[&*MFX_SYMBOL] > 0);
+
+    // Rates only cover complete days.
+    assert!(h
+        .module_impl
+        .state_metrics()
+        .unwrap()
+        .growth
+        .events
+        .is_empty());
+    h.inc_time(24 * 60 * 60);
+    h.block(|_| ());
+    let rates = h.module_impl.state_metrics().unwrap().growth;
+    assert_eq!(rates.events["ledger"], 2.0);
+    assert_eq!(rates.symbol_events[&MFX_SYMBOL.to_string()], 2.0);
+}
@@ -4,6 +4,8 @@ use many_identity::testing::identity;
 use many_ledger::migration::data::{
     ACCOUNT_COUNT_DATA_ATTRIBUTE, ACCOUNT_TOTAL_COUNT_INDEX, NON_ZERO_ACCOUNT_TOTAL_COUNT_INDEX,
 };
+use many_ledger::storage::migrations::MIGRATION_LOG;
+use many_ledger::storage::snapshot::read_snapshot_info;
 use many_ledger_test_utils::*;
 use many_modules::{
     data::{DataGetInfoArgs, DataModuleBackend, DataQueryArgs},
@@ -151,3 +153,41 @@ fn migration_stress() {
     assert_eq!(balance3, 0u32);
     assert_metrics(&harness, 5, 3);
 }
+
+#[test]
+fn backup_before_migration() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut harness = Setup::new_with_migrations(true, [(2, &ACCOUNT_COUNT_DATA_ATTRIBUTE)], false);
+    harness.module_impl = harness
+        .module_impl
+        .with_migration_backups(Some(dir.path().to_path_buf()))
+        .unwrap();
+
+    harness.block(|_| ());
+    assert!(!dir.path().join(MIGRATION_LOG).exists());
+
+    harness.block(|_| ());
+    let info = read_snapshot_info(dir.path().join(format!("{:020}.snapshot", 2))).unwrap();
+    assert_eq!(info.height, 2);
+
+    let log = std::fs::read_to_string(dir.path().join(MIGRATION_LOG)).unwrap();
+    assert_eq!(
+        log,
+        format!(
+            "2 {} {}\n",
+            hex::encode(info.hash.as_slice()),
+            ACCOUNT_COUNT_DATA_ATTRIBUTE.name()
+        )
+    );
+
+    // The migration is active.
+    assert_eq!(
+        harness
+            .module_impl
+            .info(&harness.id, EmptyArg)
+            .unwrap()
+            .indices
+            .len(),
+        2
+    );
+}
@@ -0,0 +1,95 @@
+#![feature(used_with_arg)]
+
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_ledger::migration::chunked::{self, Chunk, ChunkedMigration, CHUNKED_MIGRATIONS};
+use many_ledger::migration::MIGRATIONS;
+use many_ledger::module::migrations::LedgerMigrationsModuleBackend;
+use many_ledger::storage::InnerStorage;
+use many_ledger_test_utils::*;
+use many_migration::InnerMigration;
+use many_modules::EmptyArg;
+use merk::Op;
+
+const ITEMS: u64 = 5;
+
+fn key(i: u64) -> Vec<u8> {
+    format!("/test/chunked/{i:08}").into_bytes()
+}
+
+/// Write `ITEMS` keys, `limit` at a time.
+fn write_keys(
+    storage: &mut InnerStorage,
+    cursor: Option<&[u8]>,
+    limit: usize,
+) -> Result<Chunk, ManyError> {
+    let start = cursor.map_or(0, |c| u64::from_be_bytes(c.try_into().unwrap()));
+    let end = ITEMS.min(start + limit as u64);
+    let batch: Vec<_> = (start..end).map(|i| (key(i), Op::Put(vec![]))).collect();
+    storage.apply(&batch).map_err(ManyError::unknown)?;
+    Ok(Chunk {
+        cursor: (end < ITEMS).then(|| end.to_be_bytes().to_vec()),
+        processed: end - start,
+    })
+}
+
+#[distributed_slice(MIGRATIONS)]
+static WRITE_KEYS_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_initialize(chunked::start, "Write Keys", "For testing purpose only.");
+
+#[distributed_slice(CHUNKED_MIGRATIONS)]
+static WRITE_KEYS: ChunkedMigration = ChunkedMigration {
+    migration: &WRITE_KEYS_MIGRATION,
+    chunk_size: 2,
+    run: write_keys,
+};
+
+#[test]
+fn runs_in_chunks() {
+    let mut harness = Setup::new_with_migrations(true, [(2, &WRITE_KEYS_MIGRATION)], false);
+    let progress = |harness: &Setup| {
+        harness
+            .module_impl
+            .migration_progress(&harness.id, EmptyArg)
+            .unwrap()
+            .migrations
+            .remove("Write Keys")
+    };
+
+    harness.block(|_| ());
+    assert_eq!(progress(&harness), None);
+
+    // Two items per block.
+    for (processed, done) in [(2, false), (4, false), (5, true), (5, true)] {
+        let (height, _) = harness.block(|_| ());
+        let p = progress(&harness).unwrap();
+        assert_eq!((p.processed, p.done), (processed, done));
+        if !done {
+            assert_eq!(p.height, height);
+        }
+    }
+}
+
+#[test]
+fn status() {
+    let mut harness = Setup::new_with_migrations(true, [(2, &WRITE_KEYS_MIGRATION)], false);
+    let status = |harness: &Setup| {
+        harness
+            .module_impl
+            .migration_status(&harness.id, EmptyArg)
+            .unwrap()
+            .migrations
+            .into_iter()
+            .find(|m| m.name == "Write Keys")
+            .unwrap()
+    };
+
+    let pending = status(&harness);
+    assert!(pending.enabled);
+    assert_eq!(pending.block_height, Some(2));
+    assert!(!pending.active);
+
+    harness.block(|_| ());
+    harness.block(|_| ());
+    assert!(status(&harness).active);
+}
@@ -0,0 +1,63 @@
+#![feature(used_with_arg)]
+
+use linkme::distributed_slice;
+use many_error::ManyError;
+use many_ledger::migration::chunked;
+use many_ledger::migration::patch::{PatchChange, PatchMigration, PATCH_MIGRATIONS};
+use many_ledger::migration::MIGRATIONS;
+use many_ledger::module::migrations::LedgerMigrationsModuleBackend;
+use many_ledger::storage::InnerStorage;
+use many_ledger_test_utils::*;
+use many_migration::InnerMigration;
+use many_modules::EmptyArg;
+
+const KEY: &[u8] = b"/test/patch";
+
+fn patch_key(_: &InnerStorage) -> Result<Vec<(Vec<u8>, Option<Vec<u8>>)>, ManyError> {
+    Ok(vec![(KEY.to_vec(), Some(b"fixed".to_vec()))])
+}
+
+#[distributed_slice(MIGRATIONS)]
+static PATCH_KEY_MIGRATION: InnerMigration<InnerStorage, ManyError> =
+    InnerMigration::new_initialize(chunked::start, "Patch Key", "For testing purpose only.");
+
+#[distributed_slice(PATCH_MIGRATIONS)]
+static PATCH_KEY: PatchMigration = PatchMigration {
+    migration: &PATCH_KEY_MIGRATION,
+    patch: patch_key,
+};
+
+#[test]
+fn patches_once() {
+    let mut harness = Setup::new_with_migrations(true, [(2, &PATCH_KEY_MIGRATION)], false);
+    let patch = |harness: &Setup| {
+        harness
+            .module_impl
+            .migration_status(&harness.id, EmptyArg)
+            .unwrap()
+            .migrations
+            .into_iter()
+            .find(|m| m.name == "Patch Key")
+            .unwrap()
+            .patch
+    };
+
+    harness.block(|_| ());
+    assert_eq!(patch(&harness), None);
+
+    let (height, _) = harness.block(|_| ());
+    let record = patch(&harness).unwrap();
+    assert_eq!(record.height, height);
+    assert_eq!(
+        record.changes,
+        vec![PatchChange {
+            key: KEY.to_vec().into(),
+            before: None,
+            after: Some(b"fixed".to_vec().into()),
+        }]
+    );
+
+    // Later blocks don't patch again.
+    harness.block(|_| ());
+    assert_eq!(patch(&harness).unwrap().height, height);
+}
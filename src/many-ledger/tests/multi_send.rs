@@ -0,0 +1,190 @@
+use many_identity::testing::identity;
+use many_identity::Address;
+use many_ledger::error;
+use many_ledger::json::InitialStateJson;
+use many_ledger::module::multi_send::{
+    LedgerMultiSendModuleBackend, MultiSendArgs, MultiSendTransfer, SendSymbolsArgs, MAX_TRANSFERS,
+};
+use many_ledger::module::LedgerModuleImpl;
+use many_ledger_test_utils::*;
+use std::collections::BTreeMap;
+
+fn transfer(to: Address, amount: u64) -> MultiSendTransfer {
+    MultiSendTransfer {
+        to,
+        symbol: *MFX_SYMBOL,
+        amount: amount.into(),
+    }
+}
+
+fn args(from: Address, transfers: Vec<MultiSendTransfer>) -> MultiSendArgs {
+    MultiSendArgs {
+        from: Some(from),
+        transfers,
+        memo: None,
+    }
+}
+
+#[test]
+fn multi_send() {
+    let Setup {
+        mut module_impl,
+        id,
+        ..
+    } = setup();
+    module_impl
+        .set_balance_only_for_testing(id, 1000, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+
+    let result = module_impl.multi_send(
+        &id,
+        args(
+            id,
+            vec![
+                transfer(identity(1), 100),
+                transfer(identity(2), 200),
+                transfer(identity(1), 50),
+            ],
+        ),
+    );
+    assert!(result.is_ok());
+    verify_balance(&module_impl, id, *MFX_SYMBOL, 650u64.into());
+    verify_balance(&module_impl, identity(1), *MFX_SYMBOL, 150u64.into());
+    verify_balance(&module_impl, identity(2), *MFX_SYMBOL, 200u64.into());
+}
+
+#[test]
+fn multi_send_is_atomic() {
+    let Setup {
+        mut module_impl,
+        id,
+        ..
+    } = setup();
+    module_impl
+        .set_balance_only_for_testing(id, 1000, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+
+    // The last transfer overdraws the account, so none is applied.
+    let result = module_impl.multi_send(
+        &id,
+        args(
+            id,
+            vec![transfer(identity(1), 600), transfer(identity(2), 600)],
+        ),
+    );
+    assert_eq!(
+        result.unwrap_err().code(),
+        error::insufficient_funds("", "").code()
+    );
+    verify_balance(&module_impl, id, *MFX_SYMBOL, 1000u64.into());
+    verify_balance(&module_impl, identity(1), *MFX_SYMBOL, 0u64.into());
+    verify_balance(&module_impl, identity(2), *MFX_SYMBOL, 0u64.into());
+}
+
+#[test]
+fn multi_send_destination_is_source() {
+    let Setup {
+        mut module_impl,
+        id,
+        ..
+    } = setup();
+    module_impl
+        .set_balance_only_for_testing(id, 1000, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+
+    let result = module_impl.multi_send(
+        &id,
+        args(id, vec![transfer(identity(1), 100), transfer(id, 100)]),
+    );
+    assert_eq!(
+        result.unwrap_err().code(),
+        error::destination_is_source().code()
+    );
+    verify_balance(&module_impl, id, *MFX_SYMBOL, 1000u64.into());
+}
+
+#[test]
+fn multi_send_invalid_transfer_count() {
+    let Setup {
+        mut module_impl,
+        id,
+        ..
+    } = setup();
+    module_impl
+        .set_balance_only_for_testing(id, 1000, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+
+    let result = module_impl.multi_send(&id, args(id, vec![]));
+    assert_eq!(
+        result.unwrap_err().code(),
+        error::invalid_transfer_count(0, MAX_TRANSFERS).code()
+    );
+
+    let transfers = vec![transfer(identity(1), 1); MAX_TRANSFERS + 1];
+    let result = module_impl.multi_send(&id, args(id, transfers));
+    assert_eq!(
+        result.unwrap_err().code(),
+        error::invalid_transfer_count(MAX_TRANSFERS + 1, MAX_TRANSFERS).code()
+    );
+}
+
+fn other_symbol() -> Address {
+    identity(1000)
+}
+
+fn setup_with_two_symbols() -> (LedgerModuleImpl, tempfile::TempDir) {
+    let store_path = tempfile::tempdir().expect("Could not create a temporary dir.");
+    let mut state = InitialStateJson::read("../../staging/ledger_state.json5")
+        .or_else(|_| InitialStateJson::read("staging/ledger_state.json5"))
+        .expect("Could not read initial state.");
+    state.hash = None;
+    state.symbols.insert(other_symbol(), "OTHER".to_string());
+
+    let mut module_impl = LedgerModuleImpl::new(state, None, store_path.path(), false).unwrap();
+    module_impl
+        .set_balance_only_for_testing(identity(1), 1000, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+    module_impl
+        .set_balance_only_for_testing(identity(1), 10, other_symbol())
+        .expect("Unable to set balance for testing.");
+    (module_impl, store_path)
+}
+
+fn send_symbols(
+    module_impl: &mut LedgerModuleImpl,
+    mfx: u64,
+    other: u64,
+) -> Result<(), many_error::ManyError> {
+    module_impl
+        .send_symbols(
+            &identity(1),
+            SendSymbolsArgs {
+                from: None,
+                to: identity(2),
+                amounts: BTreeMap::from([
+                    (*MFX_SYMBOL, mfx.into()),
+                    (other_symbol(), other.into()),
+                ]),
+                memo: None,
+            },
+        )
+        .map(|_| ())
+}
+
+#[test]
+fn send_symbols_atomically() {
+    let (mut module_impl, _store) = setup_with_two_symbols();
+
+    send_symbols(&mut module_impl, 100, 5).unwrap();
+    verify_balance(&module_impl, identity(2), *MFX_SYMBOL, 100u64.into());
+    verify_balance(&module_impl, identity(2), other_symbol(), 5u64.into());
+
+    // The second leg fails, so the first one is not applied either.
+    let result = send_symbols(&mut module_impl, 100, 6);
+    assert_eq!(
+        result.unwrap_err().code(),
+        error::insufficient_funds("", "").code()
+    );
+    verify_balance(&module_impl, identity(1), *MFX_SYMBOL, 900u64.into());
+    verify_balance(&module_impl, identity(1), other_symbol(), 5u64.into());
+}
@@ -108,6 +108,49 @@ fn set_defaults() {
     assert_eq!(arguments.execute_automatically, Some(true));
 }
 
+#[test]
+/// Verify the defaults apply to new submissions which don't specify them
+fn defaults_apply_to_submissions() {
+    let mut setup = Setup::new(false);
+    let account_id = setup.create_account_(AccountType::Multisig);
+    let id = setup.id;
+    setup
+        .module_impl
+        .multisig_set_defaults(
+            &id,
+            multisig::SetDefaultsArgs {
+                account: account_id,
+                threshold: Some(2),
+                timeout_in_secs: Some(12),
+                execute_automatically: Some(true),
+            },
+        )
+        .unwrap();
+
+    let token = setup.multisig_send_(account_id, identity(1234), 10u16);
+    let info = tx_info(&mut setup.module_impl, id, &token);
+    assert_eq!(info.threshold, 2);
+    assert!(info.execute_automatically);
+}
+
+#[test]
+/// Verify defaults cannot be set on an account without the multisig feature
+fn set_defaults_without_multisig() {
+    let mut setup = Setup::new(false);
+    let account_id = setup.create_account_(AccountType::Ledger);
+    let id = setup.id;
+    let result = setup.module_impl.multisig_set_defaults(
+        &id,
+        multisig::SetDefaultsArgs {
+            account: account_id,
+            threshold: Some(2),
+            timeout_in_secs: None,
+            execute_automatically: None,
+        },
+    );
+    assert!(result.is_err());
+}
+
 proptest! {
     #![proptest_config(Config { cases: 200, source_file: Some("tests/multisig"), .. Config::default() })]
 
@@ -497,7 +540,7 @@ fn multiple_multisig() {
         // Insufficient funds.
         assert_many_err(
             setup.multisig_execute_(&tokens[3]).data,
-            ledger::insufficient_funds(),
+            many_ledger::error::legacy::insufficient_funds(),
         );
     });
     assert_eq!(h, 3);
@@ -803,3 +846,67 @@ fn approve_executed_tx() {
     let result = setup.multisig_approve(identity(6), &token);
     assert_many_err(result, multisig::errors::transaction_expired_or_withdrawn());
 }
+
+#[test]
+/// Verify that revocations and withdrawals are logged, and that a withdrawn
+/// transaction cannot be revoked.
+fn withdraw_and_revoke_are_logged() {
+    use many_modules::events::EventsModuleBackend;
+
+    let mut setup = Setup::new(false);
+    let acc1 = setup.create_account_(AccountType::Multisig);
+    let token = setup.multisig_send_(acc1, identity(1234), 10u16);
+    setup.multisig_approve_(identity(2), &token);
+
+    let revoke = |setup: &mut Setup| {
+        setup.module_impl.multisig_revoke(
+            &identity(2),
+            multisig::RevokeArgs {
+                token: token.clone(),
+            },
+        )
+    };
+    revoke(&mut setup).unwrap();
+    let id = setup.id;
+    setup
+        .module_impl
+        .multisig_withdraw(
+            &id,
+            multisig::WithdrawArgs {
+                token: token.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        revoke(&mut setup).unwrap_err().code(),
+        multisig::errors::transaction_expired_or_withdrawn().code()
+    );
+
+    let logged = setup
+        .module_impl
+        .list(events::ListArgs {
+            count: None,
+            order: None,
+            filter: Some(events::EventFilter {
+                kind: Some(
+                    vec![
+                        events::EventKind::AccountMultisigRevoke,
+                        events::EventKind::AccountMultisigWithdraw,
+                    ]
+                    .into(),
+                ),
+                ..events::EventFilter::default()
+            }),
+        })
+        .unwrap()
+        .events;
+    assert_eq!(logged.len(), 2);
+    assert!(logged.iter().any(|e| matches!(
+        &e.content,
+        events::EventInfo::AccountMultisigRevoke { revoker, .. } if *revoker == identity(2)
+    )));
+    assert!(logged.iter().any(|e| matches!(
+        &e.content,
+        events::EventInfo::AccountMultisigWithdraw { withdrawer, .. } if *withdrawer == id
+    )));
+}
@@ -0,0 +1,116 @@
+use many_error::ManyError;
+use many_identity::testing::identity;
+use many_identity::Address;
+use many_ledger::error;
+use many_ledger::module::notice::{ListArgs, NoticeModuleBackend, PublishArgs, RetractArgs};
+use many_ledger::storage::notice::Notice;
+use many_ledger_test_utils::*;
+use many_types::Timestamp;
+use std::str::FromStr;
+
+/// The identity of the staging ledger, which publishes notices by default.
+fn ledger_identity() -> Address {
+    Address::from_str("mahukzwuwgt3porn6q4vq4xu3mwy5gyskhouryzbscq7wb2iow").unwrap()
+}
+
+fn publish(
+    h: &mut Setup,
+    sender: Address,
+    title: &str,
+    expires: Option<u64>,
+) -> Result<u64, ManyError> {
+    h.module_impl
+        .publish(
+            &sender,
+            PublishArgs {
+                title: title.to_string(),
+                body: "The network upgrades at height 1000.".to_string(),
+                starts: None,
+                expires: expires.map(|secs| Timestamp::new(secs).unwrap()),
+            },
+        )
+        .map(|r| r.id)
+}
+
+fn notices(h: &Setup) -> Vec<Notice> {
+    h.module_impl
+        .list(&Address::anonymous(), ListArgs {})
+        .unwrap()
+        .notices
+}
+
+#[test]
+fn publish_and_retract() {
+    let mut h = Setup::new(true);
+    let (_, first) = h.block(|h| publish(h, ledger_identity(), "Upgrade", None));
+    let (_, second) = h.block(|h| publish(h, ledger_identity(), "Maintenance", None));
+    let (first, second) = (first.unwrap(), second.unwrap());
+    assert_ne!(first, second);
+
+    let listed = notices(&h);
+    assert_eq!(listed.len(), 2);
+    assert_eq!(listed[0].title, "Upgrade");
+    assert_eq!(listed[0].author, ledger_identity());
+    assert_eq!(listed[1].title, "Maintenance");
+
+    let (_, r) = h.block(|h| {
+        h.module_impl
+            .retract(&ledger_identity(), RetractArgs { id: first })
+    });
+    assert!(r.is_ok());
+    let listed = notices(&h);
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].id, second);
+
+    let (_, r) = h.block(|h| {
+        h.module_impl
+            .retract(&ledger_identity(), RetractArgs { id: first })
+    });
+    assert_eq!(r.unwrap_err().code(), error::notice_not_found("").code());
+}
+
+#[test]
+fn unauthorized() {
+    let mut h = Setup::new(true);
+    let (_, r) = h.block(|h| publish(h, identity(1), "Upgrade", None));
+    assert_eq!(r.unwrap_err().code(), error::unauthorized().code());
+
+    let (_, id) = h.block(|h| publish(h, ledger_identity(), "Upgrade", None));
+    let (_, r) = h.block(|h| {
+        h.module_impl
+            .retract(&identity(1), RetractArgs { id: id.unwrap() })
+    });
+    assert_eq!(r.unwrap_err().code(), error::unauthorized().code());
+    assert_eq!(notices(&h).len(), 1);
+}
+
+#[test]
+fn invalid_notice() {
+    let mut h = Setup::new(true);
+    let (_, r) = h.block(|h| publish(h, ledger_identity(), "", None));
+    assert_eq!(r.unwrap_err().code(), error::invalid_notice("").code());
+
+    // Block times start at 1_000_000.
+    let (_, r) = h.block(|h| publish(h, ledger_identity(), "Upgrade", Some(1_000)));
+    assert_eq!(r.unwrap_err().code(), error::invalid_notice("").code());
+    assert!(notices(&h).is_empty());
+}
+
+#[test]
+fn expired_notices_are_removed() {
+    let mut h = Setup::new(true);
+    let (_, r) = h.block(|h| publish(h, ledger_identity(), "Maintenance", Some(1_000_100)));
+    let id = r.unwrap();
+    assert_eq!(notices(&h).len(), 1);
+
+    h.inc_time(200);
+    h.block(|_| ());
+    assert!(notices(&h).is_empty());
+
+    // The expired notice is gone, not only hidden.
+    let (_, r) = h.block(|h| {
+        h.module_impl
+            .retract(&ledger_identity(), RetractArgs { id })
+    });
+    assert_eq!(r.unwrap_err().code(), error::notice_not_found("").code());
+}
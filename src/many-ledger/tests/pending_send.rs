@@ -0,0 +1,106 @@
+use many_error::ManyError;
+use many_identity::testing::identity;
+use many_identity::Address;
+use many_ledger::error;
+use many_ledger::module::pending_send::{
+    AcceptPendingArgs, LedgerPendingSendModuleBackend, PendingInfoArgs, SendPendingArgs,
+};
+use many_ledger::storage::pending_send::MAXIMUM_PENDING_SEND_TIMEOUT;
+use many_ledger_test_utils::*;
+use many_types::ledger::TokenAmount;
+
+fn send_pending(h: &mut Setup, timeout_in_secs: Option<u64>) -> Result<u64, ManyError> {
+    h.module_impl
+        .send_pending(
+            &identity(1),
+            SendPendingArgs {
+                from: None,
+                to: identity(2),
+                amount: 100u64.into(),
+                symbol: *MFX_SYMBOL,
+                memo: None,
+                timeout_in_secs,
+            },
+        )
+        .map(|r| r.id)
+}
+
+fn accept_pending(h: &mut Setup, sender: Address, id: u64) -> Result<(), ManyError> {
+    h.module_impl
+        .accept_pending(&sender, AcceptPendingArgs { id, to: None })
+        .map(|_| ())
+}
+
+fn pending_exists(h: &Setup, id: u64) -> bool {
+    h.module_impl
+        .pending_info(&identity(1), PendingInfoArgs { id })
+        .is_ok()
+}
+
+fn setup_pending(timeout_in_secs: Option<u64>) -> (Setup, u64) {
+    let mut h = Setup::new(true);
+    let (_, id) = h.block(|h| {
+        h.set_balance(identity(1), 1000, *MFX_SYMBOL);
+        send_pending(h, timeout_in_secs).unwrap()
+    });
+
+    // The funds are held until the transfer is accepted.
+    assert_eq!(h.balance_(identity(1)), TokenAmount::from(900u64));
+    assert_eq!(h.balance_(identity(2)), TokenAmount::zero());
+    assert!(pending_exists(&h, id));
+    (h, id)
+}
+
+#[test]
+fn accept() {
+    let (mut h, id) = setup_pending(Some(10));
+
+    let (_, r) = h.block(|h| accept_pending(h, identity(3), id));
+    assert_eq!(r.unwrap_err().code(), error::unauthorized().code());
+
+    let (_, r) = h.block(|h| accept_pending(h, identity(2), id));
+    assert!(r.is_ok());
+    assert_eq!(h.balance_(identity(1)), TokenAmount::from(900u64));
+    assert_eq!(h.balance_(identity(2)), TokenAmount::from(100u64));
+    assert!(!pending_exists(&h, id));
+
+    // The refund does not apply once accepted.
+    h.inc_time(20);
+    h.block(|_| ());
+    assert_eq!(h.balance_(identity(1)), TokenAmount::from(900u64));
+    assert_eq!(h.balance_(identity(2)), TokenAmount::from(100u64));
+}
+
+#[test]
+fn refund_after_timeout() {
+    let (mut h, id) = setup_pending(Some(10));
+
+    h.block(|_| ());
+    assert!(pending_exists(&h, id));
+
+    h.inc_time(20);
+    h.block(|_| ());
+    assert_eq!(h.balance_(identity(1)), TokenAmount::from(1000u64));
+    assert_eq!(h.balance_(identity(2)), TokenAmount::zero());
+    assert!(!pending_exists(&h, id));
+
+    let (_, r) = h.block(|h| accept_pending(h, identity(2), id));
+    assert_eq!(
+        r.unwrap_err().code(),
+        error::pending_send_not_found("").code()
+    );
+}
+
+#[test]
+fn invalid_timeout() {
+    let mut h = Setup::new(true);
+    h.set_balance(identity(1), 1000, *MFX_SYMBOL);
+
+    for timeout in [0, MAXIMUM_PENDING_SEND_TIMEOUT + 1] {
+        assert_eq!(
+            send_pending(&mut h, Some(timeout)).unwrap_err().code(),
+            error::invalid_pending_timeout("", "").code()
+        );
+    }
+    assert_eq!(h.balance_(identity(1)), TokenAmount::from(1000u64));
+}
@@ -0,0 +1,92 @@
+use many_identity::testing::identity;
+use many_ledger::error;
+use many_ledger::module::proof::{BalanceProofArgs, LedgerProofModuleBackend};
+use many_ledger_test_utils::*;
+use many_modules::abci_backend::ManyAbciModuleBackend;
+use many_types::ledger::TokenAmount;
+
+#[test]
+fn balance_proof() {
+    let mut harness = Setup::new(true);
+    let id = harness.id;
+    harness.set_balance(id, 1_000, *MFX_SYMBOL);
+    harness.block(|h| h.send_(id, identity(1), 100u64));
+
+    let returns = harness
+        .module_impl
+        .balance_proof(
+            &id,
+            BalanceProofArgs {
+                account: Some(identity(1)),
+                symbols: Some(vec![*MFX_SYMBOL].into()),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        returns.balances.get(&*MFX_SYMBOL),
+        Some(&TokenAmount::from(100u64))
+    );
+
+    let info = ManyAbciModuleBackend::info(&harness.module_impl).unwrap();
+    assert_eq!(returns.height, info.height);
+    let hash: [u8; 32] = info.hash.as_slice().try_into().unwrap();
+    assert!(merk::proofs::query::verify(&returns.proof, hash).is_ok());
+
+    // The proof no longer verifies once the state changes.
+    harness.block(|h| h.send_(id, identity(1), 100u64));
+    let info = ManyAbciModuleBackend::info(&harness.module_impl).unwrap();
+    let hash: [u8; 32] = info.hash.as_slice().try_into().unwrap();
+    assert!(merk::proofs::query::verify(&returns.proof, hash).is_err());
+}
+
+#[test]
+fn balance_proof_of_absent_balances() {
+    let mut harness = Setup::new(true);
+    harness.block(|_| ());
+
+    let returns = harness
+        .module_impl
+        .balance_proof(&identity(5), BalanceProofArgs::default())
+        .unwrap();
+    assert!(returns.balances.is_empty());
+
+    let info = ManyAbciModuleBackend::info(&harness.module_impl).unwrap();
+    let hash: [u8; 32] = info.hash.as_slice().try_into().unwrap();
+    assert!(merk::proofs::query::verify(&returns.proof, hash).is_ok());
+}
+
+#[test]
+fn balance_proof_during_a_block() {
+    let mut harness = Setup::new(true);
+    let id = harness.id;
+    harness.set_balance(id, 1_000, *MFX_SYMBOL);
+
+    let (_, result) = harness.block(|h| {
+        h.send_(id, identity(1), 100u64);
+        h.module_impl
+            .balance_proof(&id, BalanceProofArgs::default())
+    });
+    assert_eq!(
+        result.unwrap_err().code(),
+        error::state_not_committed().code()
+    );
+
+    // Once committed, the proof is of the new balances.
+    assert!(harness
+        .module_impl
+        .balance_proof(&id, BalanceProofArgs::default())
+        .is_ok());
+}
+
+#[test]
+fn commitment_matches_info() {
+    let mut harness = Setup::new(true);
+    let id = harness.id;
+    harness.set_balance(id, 1_000, *MFX_SYMBOL);
+    harness.block(|h| h.send_(id, identity(1), 100u64));
+
+    let commitment = harness.module_impl.commitment().unwrap();
+    let info = ManyAbciModuleBackend::info(&harness.module_impl).unwrap();
+    assert_eq!(commitment.height, info.height);
+    assert_eq!(commitment.hash.as_ref(), info.hash.as_slice());
+}
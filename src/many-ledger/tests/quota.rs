@@ -0,0 +1,72 @@
+use many_identity::testing::identity;
+use many_ledger::error;
+use many_ledger::module::quota::{BandwidthQuota, MAX_ANONYMOUS_BYTES};
+use std::time::{Duration, Instant};
+
+#[test]
+fn quota_per_identity() {
+    let quota = BandwidthQuota::new(100, Duration::from_secs(60));
+    let now = Instant::now();
+
+    quota.record(&identity(1), 80, now);
+    assert!(quota.check(&identity(1), now).is_ok());
+    quota.record(&identity(1), 30, now);
+    assert_eq!(
+        quota.check(&identity(1), now).unwrap_err().code(),
+        error::quota_exceeded(0, 0, 0).code()
+    );
+
+    // Other identities have their own quota.
+    assert!(quota.check(&identity(2), now).is_ok());
+}
+
+#[test]
+fn quota_sliding_window() {
+    let quota = BandwidthQuota::new(100, Duration::from_secs(60));
+    let start = Instant::now();
+
+    quota.record(&identity(1), 60, start);
+    quota.record(&identity(1), 60, start + Duration::from_secs(30));
+    assert!(quota
+        .check(&identity(1), start + Duration::from_secs(59))
+        .is_err());
+
+    // The first download left the window.
+    assert!(quota
+        .check(&identity(1), start + Duration::from_secs(60))
+        .is_ok());
+    assert!(quota
+        .check(&identity(1), start + Duration::from_secs(90))
+        .is_ok());
+}
+
+#[test]
+fn quota_forgets_expired_identities() {
+    let quota = BandwidthQuota::new(100, Duration::from_secs(60));
+    let start = Instant::now();
+    for i in 0..100_000 {
+        quota.record(&identity(i), 1, start);
+    }
+    assert_eq!(quota.identities(), 100_000);
+
+    // A new identity past the window sweeps the others.
+    quota.record(&identity(100_000), 1, start + Duration::from_secs(60));
+    assert_eq!(quota.identities(), 1);
+}
+
+#[test]
+fn quota_anonymous() {
+    let quota = BandwidthQuota::new(1_000_000, Duration::from_secs(60));
+    assert!(quota.check_anonymous(MAX_ANONYMOUS_BYTES).is_ok());
+    assert_eq!(
+        quota
+            .check_anonymous(MAX_ANONYMOUS_BYTES + 1)
+            .unwrap_err()
+            .code(),
+        error::anonymous_download_too_large(0, 0).code()
+    );
+
+    // The quota applies if it is lower.
+    let quota = BandwidthQuota::new(100, Duration::from_secs(60));
+    assert!(quota.check_anonymous(101).is_err());
+}
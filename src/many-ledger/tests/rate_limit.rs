@@ -0,0 +1,46 @@
+use many_identity::testing::identity;
+use many_ledger::error;
+use many_ledger::module::rate_limit::RateLimiter;
+use std::time::{Duration, Instant};
+
+#[test]
+fn rate_limit_burst() {
+    let limiter = RateLimiter::new(3, 3600);
+    let now = Instant::now();
+
+    for _ in 0..3 {
+        assert!(limiter.check(&identity(1), now).is_ok());
+    }
+    assert_eq!(
+        limiter.check(&identity(1), now).unwrap_err().code(),
+        error::rate_limited(0).code()
+    );
+
+    // Other identities have their own bucket.
+    assert!(limiter.check(&identity(2), now).is_ok());
+}
+
+#[test]
+fn rate_limit_refill() {
+    // One lookup per minute.
+    let limiter = RateLimiter::new(2, 60);
+    let start = Instant::now();
+
+    assert!(limiter.check(&identity(1), start).is_ok());
+    assert!(limiter.check(&identity(1), start).is_ok());
+    assert!(limiter
+        .check(&identity(1), start + Duration::from_secs(30))
+        .is_err());
+    assert!(limiter
+        .check(&identity(1), start + Duration::from_secs(60))
+        .is_ok());
+    assert!(limiter
+        .check(&identity(1), start + Duration::from_secs(61))
+        .is_err());
+
+    // The bucket never holds more than the burst.
+    let later = start + Duration::from_secs(3600);
+    assert!(limiter.check(&identity(1), later).is_ok());
+    assert!(limiter.check(&identity(1), later).is_ok());
+    assert!(limiter.check(&identity(1), later).is_err());
+}
@@ -0,0 +1,157 @@
+use many_error::ManyError;
+use many_identity::testing::identity;
+use many_identity::Address;
+use many_ledger::error;
+use many_ledger::module::recurring_send::{
+    LedgerRecurringSendModuleBackend, ListSubscriptionsArgs, SubscribeArgs, UnsubscribeArgs,
+};
+use many_ledger::storage::recurring_send::{RecurringSend, MINIMUM_RECURRING_INTERVAL};
+use many_ledger_test_utils::*;
+use many_types::ledger::TokenAmount;
+use many_types::Timestamp;
+
+const INTERVAL: u64 = MINIMUM_RECURRING_INTERVAL;
+
+fn subscribe(
+    h: &mut Setup,
+    interval_in_secs: u64,
+    start: Option<u64>,
+    end: Option<u64>,
+) -> Result<u64, ManyError> {
+    h.module_impl
+        .subscribe(
+            &identity(1),
+            SubscribeArgs {
+                from: None,
+                to: identity(2),
+                amount: 100u64.into(),
+                symbol: *MFX_SYMBOL,
+                memo: None,
+                interval_in_secs,
+                start: start.map(|secs| Timestamp::new(secs).unwrap()),
+                end: end.map(|secs| Timestamp::new(secs).unwrap()),
+            },
+        )
+        .map(|r| r.id)
+}
+
+fn unsubscribe(h: &mut Setup, sender: Address, id: u64) -> Result<(), ManyError> {
+    h.module_impl
+        .unsubscribe(&sender, UnsubscribeArgs { id })
+        .map(|_| ())
+}
+
+fn subscriptions(h: &Setup) -> Vec<RecurringSend> {
+    h.module_impl
+        .list_subscriptions(&identity(1), ListSubscriptionsArgs::default())
+        .unwrap()
+        .subscriptions
+}
+
+/// Run a block one interval later.
+fn next_interval(h: &mut Setup) {
+    h.inc_time(INTERVAL);
+    h.block(|_| ());
+}
+
+#[test]
+fn executes_every_interval_until_end() {
+    let mut h = Setup::new(true);
+    // Block times start at 1_000_000, this block is at 1_000_001.
+    let end = 1_000_001 + 3 * INTERVAL;
+    let (_, r) = h.block(|h| {
+        h.set_balance(identity(1), 1000, *MFX_SYMBOL);
+        subscribe(h, INTERVAL, None, Some(end))
+    });
+    let id = r.unwrap();
+
+    // The first transfer executes on the commit of the subscription.
+    assert_eq!(h.balance_(identity(2)), TokenAmount::from(100u64));
+    assert_eq!(subscriptions(&h)[0].id, id);
+    assert_eq!(subscriptions(&h)[0].executions, 1);
+
+    // Nothing is due before the next interval.
+    h.block(|_| ());
+    assert_eq!(h.balance_(identity(2)), TokenAmount::from(100u64));
+
+    for expected in [200u64, 300, 400] {
+        next_interval(&mut h);
+        assert_eq!(h.balance_(identity(2)), TokenAmount::from(expected));
+    }
+    assert_eq!(h.balance_(identity(1)), TokenAmount::from(600u64));
+    assert!(subscriptions(&h).is_empty());
+
+    next_interval(&mut h);
+    assert_eq!(h.balance_(identity(2)), TokenAmount::from(400u64));
+}
+
+#[test]
+fn failures_are_recorded() {
+    let mut h = Setup::new(true);
+    let (_, r) = h.block(|h| {
+        h.set_balance(identity(1), 150, *MFX_SYMBOL);
+        subscribe(h, INTERVAL, None, None)
+    });
+    assert!(r.is_ok());
+    assert_eq!(h.balance_(identity(2)), TokenAmount::from(100u64));
+
+    next_interval(&mut h);
+    assert_eq!(h.balance_(identity(1)), TokenAmount::from(50u64));
+    assert_eq!(h.balance_(identity(2)), TokenAmount::from(100u64));
+    let order = &subscriptions(&h)[0];
+    assert_eq!(order.executions, 1);
+    assert_eq!(order.failures, 1);
+    assert!(order.last_failure.is_some());
+
+    // The order keeps running once funded again.
+    h.set_balance(identity(1), 100, *MFX_SYMBOL);
+    next_interval(&mut h);
+    assert_eq!(h.balance_(identity(2)), TokenAmount::from(200u64));
+    assert_eq!(subscriptions(&h)[0].executions, 2);
+}
+
+#[test]
+fn unsubscribe_stops_transfers() {
+    let mut h = Setup::new(true);
+    let (_, r) = h.block(|h| {
+        h.set_balance(identity(1), 1000, *MFX_SYMBOL);
+        subscribe(h, INTERVAL, Some(1_000_001 + INTERVAL), None)
+    });
+    let id = r.unwrap();
+    assert_eq!(h.balance_(identity(2)), TokenAmount::zero());
+
+    let (_, r) = h.block(|h| unsubscribe(h, identity(3), id));
+    assert_eq!(r.unwrap_err().code(), error::unauthorized().code());
+
+    let (_, r) = h.block(|h| unsubscribe(h, identity(1), id));
+    assert!(r.is_ok());
+    assert!(subscriptions(&h).is_empty());
+
+    next_interval(&mut h);
+    assert_eq!(h.balance_(identity(2)), TokenAmount::zero());
+
+    let (_, r) = h.block(|h| unsubscribe(h, identity(1), id));
+    assert_eq!(
+        r.unwrap_err().code(),
+        error::recurring_send_not_found("").code()
+    );
+}
+
+#[test]
+fn invalid_subscription() {
+    let mut h = Setup::new(true);
+    h.set_balance(identity(1), 1000, *MFX_SYMBOL);
+    let invalid = [
+        (INTERVAL - 1, None, None),
+        (INTERVAL, Some(1_000), None),
+        (INTERVAL, Some(1_000_100), Some(1_000_050)),
+    ];
+    for (interval, start, end) in invalid {
+        let (_, r) = h.block(|h| subscribe(h, interval, start, end));
+        assert_eq!(
+            r.unwrap_err().code(),
+            error::invalid_recurring_send("").code()
+        );
+    }
+    assert!(subscriptions(&h).is_empty());
+}
@@ -0,0 +1,98 @@
+use many_ledger_test_utils::replay::{send_envelope, Replay, ReplayBlock};
+use many_ledger_test_utils::MFX_SYMBOL;
+use proptest::prelude::*;
+
+const START_BALANCE: u64 = 1_000_000;
+
+fn corpus() -> Vec<ReplayBlock> {
+    vec![
+        ReplayBlock {
+            time: 1_000_000,
+            txs: vec![
+                send_envelope(0, 1, 1000, *MFX_SYMBOL),
+                send_envelope(1, 2, 500, *MFX_SYMBOL),
+            ],
+        },
+        ReplayBlock {
+            time: 1_000_001,
+            txs: vec![],
+        },
+        ReplayBlock {
+            time: 1_000_005,
+            txs: vec![
+                send_envelope(2, 0, 250, *MFX_SYMBOL),
+                // Insufficient funds, must fail the same way on every run.
+                send_envelope(1, 0, 10 * START_BALANCE, *MFX_SYMBOL),
+            ],
+        },
+    ]
+}
+
+#[test]
+fn replay_is_deterministic() {
+    let corpus = corpus();
+    let first = Replay::run(&corpus, START_BALANCE, *MFX_SYMBOL);
+    let second = Replay::run(&corpus, START_BALANCE, *MFX_SYMBOL);
+    assert_eq!(first.len(), corpus.len());
+    assert_eq!(first, second);
+
+    // The transfer exceeding the balance is refused by the mempool check.
+    assert_eq!(first[0].checked, vec![true, true]);
+    assert_eq!(first[2].checked, vec![true, false]);
+}
+
+#[test]
+fn replay_recorded_corpus() {
+    // A corpus survives a round trip through its recorded form.
+    let corpus = corpus();
+    let recorded = minicbor::to_vec(&corpus).unwrap();
+    let loaded: Vec<ReplayBlock> = minicbor::decode(&recorded).unwrap();
+
+    assert_eq!(
+        Replay::run(&corpus, START_BALANCE, *MFX_SYMBOL),
+        Replay::run(&loaded, START_BALANCE, *MFX_SYMBOL)
+    );
+}
+
+#[test]
+fn replay_changes_hash_on_transfer() {
+    let mut replay = Replay::new(START_BALANCE, *MFX_SYMBOL);
+    let empty = replay.block(&ReplayBlock {
+        time: 1_000_000,
+        txs: vec![],
+    });
+    let transfer = replay.block(&ReplayBlock {
+        time: 1_000_001,
+        txs: vec![send_envelope(0, 1, 1, *MFX_SYMBOL)],
+    });
+    assert_ne!(empty.hash, transfer.hash);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(8))]
+
+    #[test]
+    fn fuzz_replay_is_deterministic(
+        blocks in prop::collection::vec(
+            prop::collection::vec((0usize..3, 0usize..3, 0u64..2 * START_BALANCE), 0..5),
+            1..5,
+        )
+    ) {
+        let corpus: Vec<ReplayBlock> = blocks
+            .into_iter()
+            .enumerate()
+            .map(|(i, txs)| ReplayBlock {
+                time: 1_000_000 + i as u64,
+                txs: txs
+                    .into_iter()
+                    .map(|(from, to, amount)| send_envelope(from, to, amount, *MFX_SYMBOL))
+                    .collect(),
+            })
+            .collect();
+
+        prop_assert_eq!(
+            Replay::run(&corpus, START_BALANCE, *MFX_SYMBOL),
+            Replay::run(&corpus, START_BALANCE, *MFX_SYMBOL)
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use many_ledger::module::events_page::ListPageReturns;
+use many_ledger::response_metadata::{is_query, truncated};
+use many_modules::events::{self, EventId};
+
+#[test]
+fn truncated_page() {
+    let page = |cursor: Option<EventId>| {
+        minicbor::to_vec(ListPageReturns {
+            nb_events: 2,
+            events: vec![],
+            cursor,
+            proofs: None,
+            height: None,
+            heights: None,
+        })
+        .unwrap()
+    };
+    assert_eq!(
+        truncated("events.listPage", &[], &page(Some(EventId::from(1u64)))),
+        Some(true)
+    );
+    assert_eq!(truncated("events.listPage", &[], &page(None)), Some(false));
+}
+
+#[test]
+fn truncated_list() {
+    let args = minicbor::to_vec(events::ListArgs {
+        count: Some(10),
+        order: None,
+        filter: None,
+    })
+    .unwrap();
+    let returns = minicbor::to_vec(events::ListReturns {
+        nb_events: 20,
+        events: vec![],
+    })
+    .unwrap();
+    assert_eq!(truncated("events.list", &args, &returns), Some(false));
+}
+
+#[test]
+fn not_a_listing() {
+    assert_eq!(truncated("ledger.info", &[], &[]), None);
+}
+
+#[test]
+fn commands_get_no_metadata() {
+    assert!(is_query("ledger.balance"));
+    assert!(is_query("events.listPage"));
+    assert!(!is_query("ledger.send"));
+    assert!(!is_query("ledger.multiSend"));
+    assert!(!is_query("unknown.method"));
+}
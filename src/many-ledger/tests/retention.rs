@@ -0,0 +1,58 @@
+use many_identity::testing::identity;
+use many_identity::Address;
+use many_ledger_test_utils::*;
+use many_modules::abci_backend::ManyAbciModuleBackend;
+use many_modules::events::{self, EventsModuleBackend};
+
+fn list_destinations(harness: &Setup) -> Vec<Address> {
+    harness
+        .module_impl
+        .list(events::ListArgs {
+            count: None,
+            order: None,
+            filter: None,
+        })
+        .unwrap()
+        .events
+        .into_iter()
+        .filter_map(|e| match e.content {
+            events::EventInfo::Send { to, .. } => Some(to),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn old_blocks_are_pruned() {
+    let mut harness = Setup::new(true);
+    harness.module_impl = harness.module_impl.with_retain_blocks(Some(3));
+    harness.set_balance(harness.id, 1_000_000, *MFX_SYMBOL);
+
+    let mut height = 0;
+    for i in 1..=6 {
+        height = harness.block(|h| h.send_(h.id, identity(i), 10u32)).0;
+    }
+
+    // The events are not part of the pruned blocks.
+    assert_eq!(
+        list_destinations(&harness),
+        (1..=6).map(identity).collect::<Vec<_>>()
+    );
+
+    // The next commit lets Tendermint prune up to the last 3 blocks.
+    let info = ManyAbciModuleBackend::commit(&mut harness.module_impl).unwrap();
+    assert_eq!(info.retain_height, height + 1 - 3 + 1);
+}
+
+#[test]
+fn every_block_is_retained_by_default() {
+    let mut harness = Setup::new(true);
+    harness.set_balance(harness.id, 1_000_000, *MFX_SYMBOL);
+    for i in 1..=6 {
+        harness.block(|h| h.send_(h.id, identity(i), 10u32));
+    }
+    assert_eq!(list_destinations(&harness).len(), 6);
+
+    let info = ManyAbciModuleBackend::commit(&mut harness.module_impl).unwrap();
+    assert_eq!(info.retain_height, 0);
+}
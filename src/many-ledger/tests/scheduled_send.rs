@@ -0,0 +1,135 @@
+use many_error::ManyError;
+use many_identity::testing::identity;
+use many_identity::Address;
+use many_ledger::error;
+use many_ledger::module::scheduled_send::{
+    CancelScheduledArgs, LedgerScheduledSendModuleBackend, ScheduledInfoArgs, SendScheduledArgs,
+};
+use many_ledger::storage::scheduled_send::{scheduled_send_memo, MAXIMUM_SCHEDULED_SEND_DELAY};
+use many_ledger_test_utils::*;
+use many_modules::events::{self, EventInfo, EventsModuleBackend};
+use many_types::ledger::TokenAmount;
+use many_types::{SortOrder, Timestamp};
+
+// Block times start at 1_000_000.
+const EXECUTE_AT: u64 = 1_000_100;
+
+fn send_scheduled(h: &mut Setup, amount: u64, execute_at: u64) -> Result<u64, ManyError> {
+    h.module_impl
+        .send_scheduled(
+            &identity(1),
+            SendScheduledArgs {
+                from: None,
+                to: identity(2),
+                amount: amount.into(),
+                symbol: *MFX_SYMBOL,
+                memo: None,
+                execute_at: Timestamp::new(execute_at).unwrap(),
+            },
+        )
+        .map(|r| r.id)
+}
+
+fn cancel_scheduled(h: &mut Setup, sender: Address, id: u64) -> Result<(), ManyError> {
+    h.module_impl
+        .cancel_scheduled(&sender, CancelScheduledArgs { id })
+        .map(|_| ())
+}
+
+fn scheduled_exists(h: &Setup, id: u64) -> bool {
+    h.module_impl
+        .scheduled_info(&identity(1), ScheduledInfoArgs { id })
+        .is_ok()
+}
+
+fn setup_scheduled(amount: u64) -> (Setup, u64) {
+    let mut h = Setup::new(true);
+    let (_, id) = h.block(|h| {
+        h.set_balance(identity(1), 1000, *MFX_SYMBOL);
+        send_scheduled(h, amount, EXECUTE_AT).unwrap()
+    });
+
+    // The funds stay with the sender until the transfer executes.
+    assert_eq!(h.balance_(identity(1)), TokenAmount::from(1000u64));
+    assert!(scheduled_exists(&h, id));
+    (h, id)
+}
+
+#[test]
+fn executes_at_time() {
+    let (mut h, id) = setup_scheduled(100);
+
+    h.block(|_| ());
+    assert_eq!(h.balance_(identity(2)), TokenAmount::zero());
+    assert!(scheduled_exists(&h, id));
+
+    h.inc_time(200);
+    h.block(|_| ());
+    assert_eq!(h.balance_(identity(1)), TokenAmount::from(900u64));
+    assert_eq!(h.balance_(identity(2)), TokenAmount::from(100u64));
+    assert!(!scheduled_exists(&h, id));
+
+    // The Send event is marked as a scheduled transfer.
+    let list = h
+        .module_impl
+        .list(events::ListArgs {
+            count: Some(1),
+            order: Some(SortOrder::Descending),
+            filter: None,
+        })
+        .unwrap();
+    match &list.events[0].content {
+        EventInfo::Send { memo, .. } => {
+            assert_eq!(memo.as_ref(), Some(&scheduled_send_memo(None).unwrap()))
+        }
+        content => panic!("Unexpected event {content:?}"),
+    }
+}
+
+#[test]
+fn cancel() {
+    let (mut h, id) = setup_scheduled(100);
+
+    let (_, r) = h.block(|h| cancel_scheduled(h, identity(3), id));
+    assert_eq!(r.unwrap_err().code(), error::unauthorized().code());
+    assert!(scheduled_exists(&h, id));
+
+    let (_, r) = h.block(|h| cancel_scheduled(h, identity(1), id));
+    assert!(r.is_ok());
+    assert!(!scheduled_exists(&h, id));
+
+    h.inc_time(200);
+    h.block(|_| ());
+    assert_eq!(h.balance_(identity(1)), TokenAmount::from(1000u64));
+    assert_eq!(h.balance_(identity(2)), TokenAmount::zero());
+
+    let (_, r) = h.block(|h| cancel_scheduled(h, identity(1), id));
+    assert_eq!(
+        r.unwrap_err().code(),
+        error::scheduled_send_not_found("").code()
+    );
+}
+
+#[test]
+fn insufficient_funds_at_execution() {
+    let (mut h, id) = setup_scheduled(2000);
+
+    h.inc_time(200);
+    h.block(|_| ());
+    assert_eq!(h.balance_(identity(1)), TokenAmount::from(1000u64));
+    assert_eq!(h.balance_(identity(2)), TokenAmount::zero());
+    assert!(!scheduled_exists(&h, id));
+}
+
+#[test]
+fn invalid_time() {
+    let mut h = Setup::new(true);
+    h.set_balance(identity(1), 1000, *MFX_SYMBOL);
+    for execute_at in [1_000, 1_000_001 + MAXIMUM_SCHEDULED_SEND_DELAY + 10] {
+        let (_, r) = h.block(|h| send_scheduled(h, 100, execute_at));
+        assert_eq!(
+            r.unwrap_err().code(),
+            error::invalid_scheduled_time("").code()
+        );
+    }
+}
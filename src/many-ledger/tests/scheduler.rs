@@ -0,0 +1,71 @@
+use many_error::ManyError;
+use many_ledger::module::LedgerModuleImpl;
+use many_ledger::storage::scheduler::Trigger;
+use many_ledger::storage::LedgerStorage;
+use many_ledger_test_utils::*;
+use many_modules::abci_backend::{AbciBlock, ManyAbciModuleBackend};
+use many_types::Timestamp;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static HEIGHT_RUNS: AtomicU64 = AtomicU64::new(0);
+static TIME_RUNS: AtomicU64 = AtomicU64::new(0);
+
+fn count_height(_: &mut LedgerStorage, payload: &[u8]) -> Result<(), ManyError> {
+    HEIGHT_RUNS.fetch_add(payload[0] as u64, Ordering::SeqCst);
+    Ok(())
+}
+
+fn count_time(_: &mut LedgerStorage, payload: &[u8]) -> Result<(), ManyError> {
+    TIME_RUNS.fetch_add(payload[0] as u64, Ordering::SeqCst);
+    Ok(())
+}
+
+fn block(module_impl: &mut LedgerModuleImpl, time: u64) {
+    module_impl
+        .begin_block(AbciBlock { time: Some(time) })
+        .expect("Could not begin block");
+    module_impl.end_block().expect("Could not end block");
+    module_impl.commit().expect("Could not commit block");
+}
+
+#[test]
+fn height_trigger() {
+    let Setup { module_impl, .. } = Setup::new(true);
+    let mut module_impl = module_impl.with_task_handler("count", count_height);
+
+    module_impl
+        .schedule_task(Trigger::Height(3), "count", vec![5])
+        .unwrap();
+    block(&mut module_impl, 1);
+    block(&mut module_impl, 2);
+    assert_eq!(HEIGHT_RUNS.load(Ordering::SeqCst), 0);
+
+    block(&mut module_impl, 3);
+    assert_eq!(HEIGHT_RUNS.load(Ordering::SeqCst), 5);
+
+    // Tasks only run once.
+    block(&mut module_impl, 4);
+    assert_eq!(HEIGHT_RUNS.load(Ordering::SeqCst), 5);
+}
+
+#[test]
+fn time_trigger_and_cancel() {
+    let Setup { module_impl, .. } = Setup::new(true);
+    let mut module_impl = module_impl.with_task_handler("count", count_time);
+
+    let trigger = Trigger::Time(Timestamp::new(1_000).unwrap());
+    module_impl
+        .schedule_task(trigger, "count", vec![1])
+        .unwrap();
+    let cancelled = module_impl
+        .schedule_task(trigger, "count", vec![10])
+        .unwrap();
+    assert!(module_impl.cancel_task(&cancelled).unwrap());
+    assert!(!module_impl.cancel_task(&cancelled).unwrap());
+
+    block(&mut module_impl, 999);
+    assert_eq!(TIME_RUNS.load(Ordering::SeqCst), 0);
+
+    block(&mut module_impl, 1_000);
+    assert_eq!(TIME_RUNS.load(Ordering::SeqCst), 1);
+}
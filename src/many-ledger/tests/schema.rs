@@ -0,0 +1,23 @@
+use many_ledger::module::endpoints;
+use many_ledger::schema;
+
+#[test]
+fn cddl_describes_every_endpoint() {
+    let cddl = schema::cddl();
+    for (method, info) in endpoints() {
+        assert!(cddl.contains(&format!("\n{method}-args = ")), "{method}");
+        assert!(cddl.contains(&format!("\n{method}-returns = ")), "{method}");
+        let kind = if info.is_command { "command" } else { "query" };
+        assert!(cddl.contains(&format!("; {method} ({kind})")), "{method}");
+    }
+    assert!(cddl.contains(
+        "ledger.send-args = { ? 0 => address, 1 => address, 2 => ledger-amount, 3 => symbol, ? 4 => memo }"
+    ));
+}
+
+#[test]
+fn openapi_is_json() {
+    let spec: serde_json::Value = serde_json::from_str(&schema::openapi()).unwrap();
+    assert_eq!(spec["openapi"], "3.0.3");
+    assert!(spec["paths"]["/balances/{identity}"]["get"].is_object());
+}
@@ -0,0 +1,71 @@
+use many_identity::testing::identity;
+use many_identity::Address;
+use many_ledger::migration::sequence::SEQUENCE_MIGRATION;
+use many_ledger::module::sequence::{LedgerSequenceModuleBackend, SequenceArgs};
+use many_ledger_test_utils::*;
+
+/// A setup with the sequence migration active.
+fn setup() -> Setup {
+    let mut harness = Setup::new_with_migrations(true, [(1, &SEQUENCE_MIGRATION)], false);
+    harness.block(|_| {});
+    harness
+}
+
+fn sequence(harness: &Setup, sender: &Address, address: Option<Address>) -> u64 {
+    harness
+        .module_impl
+        .sequence(sender, SequenceArgs { address })
+        .unwrap()
+        .sequence
+}
+
+#[test]
+fn sequence_per_identity() {
+    let mut harness = setup();
+    let id = harness.id;
+    assert_eq!(sequence(&harness, &id, None), 0);
+
+    assert_eq!(harness.module_impl.command_executed(&id).unwrap(), 1);
+    assert_eq!(harness.module_impl.command_executed(&id).unwrap(), 2);
+    assert_eq!(sequence(&harness, &id, None), 2);
+    assert_eq!(sequence(&harness, &identity(1), Some(id)), 2);
+
+    // Other identities have their own sequence.
+    assert_eq!(sequence(&harness, &identity(1), None), 0);
+}
+
+#[test]
+fn sequence_anonymous() {
+    let mut harness = setup();
+    assert_eq!(
+        harness
+            .module_impl
+            .command_executed(&Address::anonymous())
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        sequence(&harness, &harness.id, Some(Address::anonymous())),
+        0
+    );
+}
+
+#[test]
+fn sequence_in_blockchain() {
+    let mut harness = setup();
+    let id = harness.id;
+    harness.block(|h| {
+        h.module_impl.command_executed(&id).unwrap();
+    });
+    assert_eq!(sequence(&harness, &id, None), 1);
+}
+
+#[test]
+fn sequence_before_migration() {
+    let mut harness = Setup::new_with_migrations(true, [(2, &SEQUENCE_MIGRATION)], false);
+    let id = harness.id;
+    harness.block(|h| {
+        assert_eq!(h.module_impl.command_executed(&id).unwrap(), 0);
+    });
+    assert_eq!(sequence(&harness, &id, None), 0);
+}
@@ -0,0 +1,90 @@
+use many_error::ManyError;
+use many_identity::testing::identity;
+use many_ledger::error;
+use many_ledger::module::simulate::{LedgerSimulateModuleBackend, SimulateArgs};
+use many_ledger_test_utils::*;
+use many_modules::events::EventInfo;
+use many_modules::ledger;
+use many_types::ledger::TokenAmount;
+
+fn send_args(amount: u64) -> SimulateArgs {
+    SimulateArgs {
+        method: "ledger.send".to_string(),
+        data: minicbor::to_vec(ledger::SendArgs {
+            from: None,
+            to: identity(1),
+            amount: amount.into(),
+            symbol: *MFX_SYMBOL,
+            memo: None,
+        })
+        .unwrap()
+        .into(),
+    }
+}
+
+#[test]
+fn simulate_send() {
+    let Setup {
+        mut module_impl,
+        id,
+        ..
+    } = setup();
+    module_impl
+        .set_balance_only_for_testing(id, 1000, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+
+    let result = module_impl.simulate(&id, send_args(400)).unwrap();
+    assert_eq!(
+        result.balances[&id][&*MFX_SYMBOL],
+        TokenAmount::from(600u64)
+    );
+    assert_eq!(
+        result.balances[&identity(1)][&*MFX_SYMBOL],
+        TokenAmount::from(400u64)
+    );
+    assert!(matches!(
+        result.events.as_slice(),
+        [EventInfo::Send { from, .. }] if from == &id
+    ));
+
+    // Nothing was applied to the storage.
+    verify_balance(&module_impl, id, *MFX_SYMBOL, 1000u64.into());
+    verify_balance(&module_impl, identity(1), *MFX_SYMBOL, 0u64.into());
+}
+
+#[test]
+fn simulate_send_insufficient_funds() {
+    let Setup {
+        mut module_impl,
+        id,
+        ..
+    } = setup();
+    module_impl
+        .set_balance_only_for_testing(id, 100, *MFX_SYMBOL)
+        .expect("Unable to set balance for testing.");
+
+    let result = module_impl.simulate(&id, send_args(400));
+    assert_eq!(
+        result.unwrap_err().code(),
+        error::insufficient_funds("", "").code()
+    );
+}
+
+#[test]
+fn simulate_unknown_method() {
+    let Setup {
+        module_impl, id, ..
+    } = setup();
+
+    let result = module_impl.simulate(
+        &id,
+        SimulateArgs {
+            method: "ledger.unknown".to_string(),
+            data: vec![].into(),
+        },
+    );
+    assert_eq!(
+        result.unwrap_err().code(),
+        ManyError::invalid_method_name("").code()
+    );
+}
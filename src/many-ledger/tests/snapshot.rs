@@ -0,0 +1,242 @@
+use many_identity::testing::identity;
+use many_ledger::error;
+use many_ledger::module::snapshot::{
+    AbciSnapshotModuleBackend, ApplySnapshotChunkArgs, LedgerSnapshotModuleBackend,
+    LoadSnapshotChunkArgs, OfferSnapshotArgs,
+};
+use many_ledger::storage::snapshot::{
+    read_snapshot_chunk, read_snapshot_info, restore_snapshot, restore_snapshot_archive, Snapshots,
+    SNAPSHOT_FORMAT,
+};
+use many_ledger_test_utils::*;
+use many_modules::abci_backend::ManyAbciModuleBackend;
+use many_modules::EmptyArg;
+use many_types::ledger::TokenAmount;
+
+fn setup_with_snapshots(dir: &tempfile::TempDir) -> Setup {
+    setup_with_retention(dir, 2, 2, None)
+}
+
+fn setup_with_retention(
+    dir: &tempfile::TempDir,
+    interval: u64,
+    keep: usize,
+    max_age: Option<u64>,
+) -> Setup {
+    let mut harness = Setup::new(true);
+    harness.module_impl = harness.module_impl.with_snapshots(Some(
+        Snapshots::new(dir.path(), interval)
+            .unwrap()
+            .with_retention(keep, max_age),
+    ));
+    harness
+}
+
+fn snapshot_heights(harness: &Setup) -> Vec<u64> {
+    harness
+        .module_impl
+        .list_snapshots()
+        .unwrap()
+        .snapshots
+        .into_iter()
+        .map(|s| s.height)
+        .collect()
+}
+
+#[test]
+fn snapshot_info() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut harness = setup_with_snapshots(&dir);
+    let latest = |h: &Setup| h.module_impl.snapshot_info(&h.id, EmptyArg).unwrap().latest;
+    assert_eq!(latest(&harness), None);
+
+    harness.block(|_| ());
+    harness.block(|_| ());
+    let info = latest(&harness).unwrap();
+    assert_eq!(info.height, 2);
+    assert_eq!(
+        info.hash.as_slice(),
+        ManyAbciModuleBackend::info(&harness.module_impl)
+            .unwrap()
+            .hash
+            .as_ref()
+    );
+}
+
+#[test]
+fn retention_by_count() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut harness = setup_with_retention(&dir, 1, 3, None);
+    for _ in 0..5 {
+        harness.block(|_| ());
+    }
+    assert_eq!(snapshot_heights(&harness), vec![3, 4, 5]);
+}
+
+#[test]
+fn retention_by_age() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut harness = setup_with_retention(&dir, 1, 10, Some(100));
+    harness.block(|_| ());
+    harness.block(|_| ());
+    assert_eq!(snapshot_heights(&harness), vec![1, 2]);
+
+    // Older snapshots expire, in block time.
+    harness.inc_time(200);
+    harness.block(|_| ());
+    assert_eq!(snapshot_heights(&harness), vec![3]);
+}
+
+#[test]
+fn restore_snapshot() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut source = setup_with_snapshots(&dir);
+    let id = source.id;
+    source.set_balance(id, 1_000, *MFX_SYMBOL);
+
+    source.block(|h| h.send_(id, identity(1), 100u64));
+    assert!(source
+        .module_impl
+        .list_snapshots()
+        .unwrap()
+        .snapshots
+        .is_empty());
+    source.block(|_| ());
+
+    let snapshots = source.module_impl.list_snapshots().unwrap().snapshots;
+    assert_eq!(snapshots.len(), 1);
+    let snapshot = snapshots[0].clone();
+    let source_info = ManyAbciModuleBackend::info(&source.module_impl).unwrap();
+    assert_eq!(snapshot.height, 2);
+    assert_eq!(snapshot.hash.as_slice(), source_info.hash.as_ref());
+
+    let mut target = Setup::new(true);
+    target
+        .module_impl
+        .offer_snapshot(OfferSnapshotArgs {
+            snapshot: snapshot.clone(),
+            app_hash: snapshot.hash.clone(),
+        })
+        .unwrap();
+    for index in 0..snapshot.chunks {
+        let chunk = source
+            .module_impl
+            .load_snapshot_chunk(LoadSnapshotChunkArgs {
+                height: snapshot.height,
+                format: SNAPSHOT_FORMAT,
+                chunk: index,
+            })
+            .unwrap()
+            .chunk;
+        target
+            .module_impl
+            .apply_snapshot_chunk(ApplySnapshotChunkArgs { index, chunk })
+            .unwrap();
+    }
+
+    let target_info = ManyAbciModuleBackend::info(&target.module_impl).unwrap();
+    assert_eq!(target_info.height, 2);
+    assert_eq!(target_info.hash, source_info.hash);
+    assert_eq!(target.balance_(identity(1)), TokenAmount::from(100u64));
+}
+
+#[test]
+fn offer_snapshot_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut source = setup_with_snapshots(&dir);
+    source.block(|_| ());
+    source.block(|_| ());
+    let snapshot = source.module_impl.list_snapshots().unwrap().snapshots[0].clone();
+
+    // The trusted application hash must match.
+    let mut target = Setup::new(true);
+    assert_eq!(
+        target
+            .module_impl
+            .offer_snapshot(OfferSnapshotArgs {
+                snapshot: snapshot.clone(),
+                app_hash: vec![0; 32].into(),
+            })
+            .unwrap_err()
+            .code(),
+        error::invalid_snapshot("").code()
+    );
+
+    // A store with blocks cannot be restored.
+    assert_eq!(
+        source
+            .module_impl
+            .offer_snapshot(OfferSnapshotArgs {
+                snapshot: snapshot.clone(),
+                app_hash: snapshot.hash.clone(),
+            })
+            .unwrap_err()
+            .code(),
+        error::invalid_snapshot("").code()
+    );
+
+    // Chunks cannot be applied without an offered snapshot.
+    assert_eq!(
+        target
+            .module_impl
+            .apply_snapshot_chunk(ApplySnapshotChunkArgs {
+                index: 0,
+                chunk: vec![].into(),
+            })
+            .unwrap_err()
+            .code(),
+        error::invalid_snapshot("").code()
+    );
+}
+
+#[test]
+fn restore_archive() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut harness = setup_with_snapshots(&dir);
+    harness.block(|_| ());
+    harness.block(|_| ());
+
+    let archive = dir.path().join(format!("{:020}.snapshot", 2));
+    let info = read_snapshot_info(&archive).unwrap();
+    assert_eq!(
+        Some(info.clone()),
+        harness
+            .module_impl
+            .snapshot_info(&harness.id, EmptyArg)
+            .unwrap()
+            .latest
+    );
+
+    let restored_dir = tempfile::tempdir().unwrap();
+    let restored = restore_snapshot(restored_dir.path().join("store"), &info, |chunk| {
+        read_snapshot_chunk(&archive, chunk)
+    })
+    .unwrap();
+    assert_eq!(restored.root_hash().as_slice(), info.hash.as_slice());
+}
+
+#[test]
+fn restore_archive_into_store() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut harness = setup_with_snapshots(&dir);
+    harness.block(|_| ());
+    harness.block(|_| ());
+
+    let archive = dir.path().join(format!("{:020}.snapshot", 2));
+    let restored_dir = tempfile::tempdir().unwrap();
+    let store = restored_dir.path().join("store");
+
+    let result = restore_snapshot_archive(&archive, &store, Some(&[0u8; 32]));
+    assert_eq!(
+        result.unwrap_err().code(),
+        error::invalid_snapshot("").code()
+    );
+    assert!(!store.exists());
+
+    let info = read_snapshot_info(&archive).unwrap();
+    assert_eq!(
+        restore_snapshot_archive(&archive, &store, Some(info.hash.as_slice())).unwrap(),
+        info
+    );
+    assert!(store.exists());
+}
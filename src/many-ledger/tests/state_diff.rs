@@ -0,0 +1,118 @@
+use many_identity::testing::identity;
+use many_ledger::error;
+use many_ledger::module::snapshot::{LedgerSnapshotModuleBackend, StateDiffArgs};
+use many_ledger::storage::diff::StateDiff;
+use many_ledger::storage::snapshot::{
+    read_snapshot_chunk, read_snapshot_info, restore_snapshot, Snapshots,
+};
+use many_ledger::storage::InnerStorage;
+use many_ledger_test_utils::*;
+use many_modules::abci_backend::ManyAbciModuleBackend;
+
+fn setup_with_diffs(dir: &tempfile::TempDir) -> Setup {
+    let mut harness = Setup::new(true);
+    harness.module_impl = harness
+        .module_impl
+        .with_snapshots(Some(Snapshots::new(dir.path(), 2).unwrap()))
+        .with_state_diffs(Some(2));
+    harness
+}
+
+fn state_diff(harness: &Setup, height: u64) -> StateDiff {
+    harness
+        .module_impl
+        .state_diff(&harness.id, StateDiffArgs { height })
+        .unwrap()
+        .diff
+}
+
+fn restore_at_2(dir: &tempfile::TempDir, path: &std::path::Path) -> InnerStorage {
+    let archive = dir.path().join(format!("{:020}.snapshot", 2));
+    let info = read_snapshot_info(&archive).unwrap();
+    restore_snapshot(path, &info, |index| read_snapshot_chunk(&archive, index)).unwrap()
+}
+
+#[test]
+fn catch_up() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut source = setup_with_diffs(&dir);
+    let id = source.id;
+    source.set_balance(id, 1_000, *MFX_SYMBOL);
+    source.block(|h| h.send_(id, identity(1), 100u64));
+    source.block(|_| ());
+    source.block(|h| h.send_(id, identity(2), 10u64));
+    source.block(|h| h.send_(id, identity(1), 5u64));
+
+    let target = tempfile::tempdir().unwrap();
+    let mut store = restore_at_2(&dir, &target.path().join("store"));
+    for height in 3..=4 {
+        let diff = state_diff(&source, height);
+        assert_eq!(diff.height, height);
+        diff.apply(&mut store).unwrap();
+    }
+
+    let source_info = ManyAbciModuleBackend::info(&source.module_impl).unwrap();
+    assert_eq!(source_info.height, 4);
+    assert_eq!(store.root_hash().as_slice(), source_info.hash.as_ref());
+}
+
+#[test]
+fn diffs_are_pruned() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut harness = setup_with_diffs(&dir);
+    for _ in 0..4 {
+        harness.block(|_| ());
+    }
+
+    for height in [2, 5] {
+        assert_eq!(
+            harness
+                .module_impl
+                .state_diff(&harness.id, StateDiffArgs { height })
+                .unwrap_err()
+                .code(),
+            error::state_diff_not_found("").code()
+        );
+    }
+    state_diff(&harness, 3);
+    state_diff(&harness, 4);
+}
+
+#[test]
+fn invalid_diff() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut source = setup_with_diffs(&dir);
+    let id = source.id;
+    source.set_balance(id, 1_000, *MFX_SYMBOL);
+    for _ in 0..2 {
+        source.block(|_| ());
+    }
+    source.block(|h| h.send_(id, identity(2), 10u64));
+    source.block(|h| h.send_(id, identity(1), 5u64));
+
+    // Skipping a height does not reproduce the hash, nothing is committed.
+    let target = tempfile::tempdir().unwrap();
+    let path = target.path().join("store");
+    let mut store = restore_at_2(&dir, &path);
+    let hash = store.root_hash();
+    assert_eq!(
+        state_diff(&source, 4).apply(&mut store).unwrap_err().code(),
+        error::invalid_state_diff("", "").code()
+    );
+    drop(store);
+    assert_eq!(InnerStorage::open(&path).unwrap().root_hash(), hash);
+}
+
+#[test]
+fn disabled() {
+    let mut harness = Setup::new(true);
+    harness.block(|_| ());
+    assert_eq!(
+        harness
+            .module_impl
+            .state_diff(&harness.id, StateDiffArgs { height: 1 })
+            .unwrap_err()
+            .code(),
+        error::state_diff_not_found("").code()
+    );
+}
@@ -1,5 +1,6 @@
 use many_identity::testing::identity;
 use many_identity::Address;
+use many_ledger::migration::migration_heights::MIGRATION_HEIGHTS_MIGRATION;
 use many_ledger::migration::tokens::TOKEN_MIGRATION;
 use many_ledger::storage::ledger_tokens::SymbolMeta;
 use many_ledger::{module::LedgerModuleImpl, storage::LedgerStorage};
@@ -187,3 +188,78 @@ fn load_symbol_meta() {
     assert_eq!(info.summary.ticker, "MF0".to_string());
     assert_eq!(info.summary.decimals, 9);
 }
+
+/// The activation heights are recorded in the state once the migration doing it
+/// is active, and a node cannot be restarted with another height for a
+/// migration which activated.
+#[test]
+fn load_migration_heights() {
+    let path = tempfile::tempdir().unwrap().into_path();
+    let metadata = |block_height| Metadata {
+        block_height,
+        disabled: false,
+        issue: None,
+        extra: Default::default(),
+    };
+    let config_at = |block_height| {
+        Some(
+            MigrationConfig::default()
+                .with_migration_opts(&MIGRATION_HEIGHTS_MIGRATION, metadata(1))
+                .with_migration_opts(&TOKEN_MIGRATION, metadata(block_height)),
+        )
+    };
+
+    {
+        let symbols = BTreeMap::from([(identity(1000), "MF0".to_string())]);
+        let initial_balance = BTreeMap::from([(
+            identity(5),
+            BTreeMap::from([(identity(1000), 10000000u64.into())]),
+        )]);
+        let mut storage = LedgerStorage::new(&symbols, path.clone(), identity(666), true)
+            .unwrap()
+            .with_migrations(config_at(2))
+            .unwrap()
+            .with_balances(&symbols, &initial_balance)
+            .unwrap()
+            .with_tokens(&symbols, None, None, None, initial_balance)
+            .unwrap()
+            .build()
+            .unwrap();
+        storage.commit();
+        storage.commit();
+        assert_eq!(
+            storage.get_stored_migration_heights().unwrap(),
+            Some(BTreeMap::from([
+                (MIGRATION_HEIGHTS_MIGRATION.name().to_string(), 1),
+                (TOKEN_MIGRATION.name().to_string(), 2)
+            ]))
+        );
+    }
+
+    assert!(LedgerStorage::load(&path, true, config_at(5)).is_err());
+    assert!(LedgerStorage::load(&path, true, config_at(2)).is_ok());
+}
+
+#[test]
+fn verify_state() {
+    let path = tempfile::tempdir().unwrap().into_path();
+    let symbols = BTreeMap::from([(identity(1000), "MF0".to_string())]);
+    let initial_balance = BTreeMap::from([(
+        identity(5),
+        BTreeMap::from([(identity(1000), 10000000u64.into())]),
+    )]);
+    let mut storage = LedgerStorage::new(&symbols, path, identity(666), true)
+        .unwrap()
+        .with_balances(&symbols, &initial_balance)
+        .unwrap()
+        .with_tokens(&symbols, None, None, None, initial_balance)
+        .unwrap()
+        .build()
+        .unwrap();
+    storage.commit();
+
+    let report = storage.verify_state().unwrap();
+    assert!(report.verified(), "{:?}", report.violations);
+    assert_eq!(report.height, 1);
+    assert_eq!(report.app_hash, Some(report.hash.clone()));
+}
@@ -0,0 +1,146 @@
+use many_error::ManyError;
+use many_identity::testing::identity;
+use many_identity::Address;
+use many_ledger::error;
+use many_ledger::module::sub_account::{
+    AccountSubAccountsModuleBackend, CreateSubAccountArgs, DisableSubAccountArgs,
+    ListSubAccountsArgs, SubAccountInfo,
+};
+use many_ledger_test_utils::*;
+use many_modules::events;
+use many_modules::events::EventsModuleBackend;
+use std::collections::BTreeMap;
+
+fn create(h: &mut Setup, parent: Address, name: &str) -> Result<Address, ManyError> {
+    h.module_impl
+        .create_sub_account(
+            &parent,
+            CreateSubAccountArgs {
+                name: name.to_string(),
+                features: None,
+            },
+        )
+        .map(|r| r.id)
+}
+
+fn list(h: &Setup) -> BTreeMap<String, SubAccountInfo> {
+    h.module_impl
+        .list_sub_accounts(&identity(1), ListSubAccountsArgs { parent: Some(h.id) })
+        .unwrap()
+        .accounts
+}
+
+#[test]
+fn lifecycle() {
+    let mut h = Setup::new(false);
+    let parent = h.id;
+
+    let finance = create(&mut h, parent, "finance").unwrap();
+    let legal = create(&mut h, parent, "legal").unwrap();
+    assert_eq!(finance, parent.with_subresource_id(0).unwrap());
+    assert_eq!(legal, parent.with_subresource_id(1).unwrap());
+    assert_eq!(
+        create(&mut h, parent, "finance").unwrap_err().code(),
+        error::sub_account_exists("").code()
+    );
+    assert_eq!(
+        list(&h),
+        BTreeMap::from([
+            (
+                "finance".to_string(),
+                SubAccountInfo {
+                    id: finance,
+                    disabled: false
+                }
+            ),
+            (
+                "legal".to_string(),
+                SubAccountInfo {
+                    id: legal,
+                    disabled: false
+                }
+            ),
+        ])
+    );
+
+    h.module_impl
+        .disable_sub_account(
+            &parent,
+            DisableSubAccountArgs {
+                name: "legal".to_string(),
+            },
+        )
+        .unwrap();
+    assert!(list(&h)["legal"].disabled);
+    assert!(!list(&h)["finance"].disabled);
+
+    let logged = h
+        .module_impl
+        .list(events::ListArgs {
+            count: None,
+            order: None,
+            filter: Some(events::EventFilter {
+                kind: Some(
+                    vec![
+                        events::EventKind::AccountCreate,
+                        events::EventKind::AccountDisable,
+                    ]
+                    .into(),
+                ),
+                ..events::EventFilter::default()
+            }),
+        })
+        .unwrap()
+        .events;
+    assert_eq!(logged.len(), 3);
+    assert!(logged.iter().any(|e| matches!(
+        &e.content,
+        events::EventInfo::AccountDisable { account } if *account == legal
+    )));
+}
+
+#[test]
+fn parent_owns_sub_accounts() {
+    let mut h = Setup::new(false);
+    let parent = h.id;
+    let finance = create(&mut h, parent, "finance").unwrap();
+    h.set_balance(finance, 100, *MFX_SYMBOL);
+
+    assert!(h
+        .send_as(identity(3), finance, identity(2), 10u64, *MFX_SYMBOL)
+        .is_err());
+    h.send_as(parent, finance, identity(2), 10u64, *MFX_SYMBOL)
+        .unwrap();
+    assert_eq!(h.balance_(identity(2)), 10u64.into());
+}
+
+#[test]
+fn invalid() {
+    let mut h = Setup::new(false);
+    let parent = h.id;
+
+    for name in ["", "a/b", "x".repeat(65).as_str()] {
+        assert_eq!(
+            create(&mut h, parent, name).unwrap_err().code(),
+            error::invalid_sub_account_name("").code()
+        );
+    }
+    assert_eq!(
+        create(&mut h, Address::anonymous(), "finance")
+            .unwrap_err()
+            .code(),
+        error::invalid_sub_account_parent("").code()
+    );
+    assert_eq!(
+        h.module_impl
+            .disable_sub_account(
+                &parent,
+                DisableSubAccountArgs {
+                    name: "finance".to_string(),
+                },
+            )
+            .unwrap_err()
+            .code(),
+        error::sub_account_not_found("").code()
+    );
+}
@@ -0,0 +1,60 @@
+use many_identity::testing::identity;
+use many_ledger::subscriptions::{accept_key, SubscribeArgs, Subscriptions};
+use many_ledger_test_utils::*;
+use many_modules::events::{EventInfo, EventKind};
+use std::sync::Arc;
+
+#[test]
+fn accept_key_rfc6455() {
+    // The example of the RFC.
+    assert_eq!(
+        accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+        "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+    );
+}
+
+#[test]
+fn events_of_committed_blocks() {
+    let subscriptions = Arc::new(Subscriptions::default());
+    let mut harness = Setup::new(true);
+    harness.module_impl = harness
+        .module_impl
+        .with_subscriptions(Some(subscriptions.clone()));
+    let id = harness.id;
+    harness.set_balance(id, 1_000, *MFX_SYMBOL);
+
+    let all = subscriptions.subscribe(SubscribeArgs::default());
+    let about_1 = subscriptions.subscribe(SubscribeArgs {
+        account: Some(identity(1)),
+        ..Default::default()
+    });
+    let other_symbol = subscriptions.subscribe(SubscribeArgs {
+        symbol: Some(identity(1000)),
+        ..Default::default()
+    });
+    let sends = subscriptions.subscribe(SubscribeArgs {
+        kind: Some(vec![EventKind::Send].into()),
+        symbol: Some(*MFX_SYMBOL),
+        ..Default::default()
+    });
+
+    harness.block(|h| {
+        h.send_(id, identity(1), 10u64);
+        h.send_(id, identity(2), 10u64);
+    });
+
+    assert_eq!(all.try_iter().count(), 2);
+    let events: Vec<_> = about_1.try_iter().collect();
+    assert_eq!(events.len(), 1);
+    assert!(matches!(
+        events[0].content,
+        EventInfo::Send { to, .. } if to == identity(1)
+    ));
+    assert_eq!(other_symbol.try_iter().count(), 0);
+    assert_eq!(sends.try_iter().count(), 2);
+
+    // Dropped subscribers are removed.
+    drop(all);
+    harness.block(|h| h.send_(id, identity(1), 10u64));
+    assert_eq!(about_1.try_iter().count(), 1);
+}
@@ -0,0 +1,159 @@
+use many_identity::testing::identity;
+use many_identity::Address;
+use many_ledger::migration::supply::{HOLDERS_INDEX_MIGRATION, HOLDER_COUNT_MIGRATION};
+use many_ledger::migration::tokens::TOKEN_MIGRATION;
+use many_ledger::module::freeze::{FreezeArgs, LedgerFreezeModuleBackend};
+use many_ledger::module::supply::{
+    HoldersArgs, HoldersReturns, LedgerSupplyModuleBackend, SupplyArgs,
+};
+use many_ledger::storage::holders::Holder;
+use many_ledger::storage::supply::SymbolSupply;
+use many_ledger_test_utils::*;
+use many_types::ledger::TokenAmount;
+use minicbor::bytes::ByteVec;
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+/// The identity of the staging ledger, which freezes accounts by default.
+fn ledger_identity() -> Address {
+    Address::from_str("mahukzwuwgt3porn6q4vq4xu3mwy5gyskhouryzbscq7wb2iow").unwrap()
+}
+
+fn supply(h: &Setup) -> SymbolSupply {
+    h.module_impl
+        .supply(
+            &Address::anonymous(),
+            SupplyArgs {
+                symbols: Some(BTreeSet::from([*MFX_SYMBOL])),
+            },
+        )
+        .unwrap()
+        .supply
+        .remove(&MFX_SYMBOL)
+        .unwrap()
+}
+
+fn holders(h: &Setup, count: u64, cursor: Option<ByteVec>) -> HoldersReturns {
+    h.module_impl
+        .holders(
+            &Address::anonymous(),
+            HoldersArgs {
+                symbol: *MFX_SYMBOL,
+                count: Some(count),
+                cursor,
+            },
+        )
+        .unwrap()
+}
+
+fn holder(account: Address, amount: u64) -> Holder {
+    Holder {
+        account,
+        amount: TokenAmount::from(amount),
+    }
+}
+
+#[test]
+fn holders_are_counted() {
+    let mut h = Setup::new_with_migrations(
+        false,
+        [(0, &TOKEN_MIGRATION), (0, &HOLDER_COUNT_MIGRATION)],
+        true,
+    );
+    let holders = supply(&h).holders.unwrap();
+    assert!(holders > 0);
+
+    h.set_balance(identity(1), 1_000, *MFX_SYMBOL);
+    assert_eq!(supply(&h).holders, Some(holders + 1));
+
+    // Emptying an account and funding another keeps the count.
+    h.send_(identity(1), identity(2), 1_000u64);
+    assert_eq!(supply(&h).holders, Some(holders + 1));
+
+    h.send_(identity(2), identity(3), 500u64);
+    assert_eq!(supply(&h).holders, Some(holders + 2));
+}
+
+#[test]
+fn frozen_balances_are_not_circulating() {
+    let mut h = Setup::new_with_migrations(false, [(0, &TOKEN_MIGRATION)], true);
+    let before = supply(&h);
+    assert_eq!(before.holders, None);
+
+    h.set_balance(identity(1), 1_000, *MFX_SYMBOL);
+    h.module_impl
+        .freeze(
+            &ledger_identity(),
+            FreezeArgs {
+                account: identity(1),
+            },
+        )
+        .unwrap();
+
+    let after = supply(&h);
+    assert_eq!(after.total, before.total);
+    let mut circulating = after.circulating;
+    circulating += TokenAmount::from(1_000u64);
+    assert_eq!(circulating, before.circulating);
+}
+
+#[test]
+fn holders_are_ordered_by_balance() {
+    let mut h = Setup::new_with_migrations(
+        false,
+        [(0, &TOKEN_MIGRATION), (0, &HOLDERS_INDEX_MIGRATION)],
+        true,
+    );
+    // Larger than any balance of the initial state.
+    h.set_balance(identity(1), 3_000_000_000, *MFX_SYMBOL);
+    h.set_balance(identity(2), 5_000_000_000, *MFX_SYMBOL);
+    h.set_balance(identity(3), 300_000_000_000, *MFX_SYMBOL);
+
+    let page = holders(&h, 2, None);
+    assert_eq!(
+        page.holders,
+        vec![
+            holder(identity(3), 300_000_000_000),
+            holder(identity(2), 5_000_000_000)
+        ]
+    );
+    let page = holders(&h, 1, page.cursor);
+    assert_eq!(page.holders, vec![holder(identity(1), 3_000_000_000)]);
+    assert!(page.cursor.is_some());
+
+    // Balance changes move the accounts in the index, and empty accounts
+    // leave it.
+    h.send_(identity(3), identity(1), 300_000_000_000u64);
+    let page = holders(&h, 2, None);
+    assert_eq!(
+        page.holders,
+        vec![
+            holder(identity(1), 303_000_000_000),
+            holder(identity(2), 5_000_000_000)
+        ]
+    );
+    let all = holders(&h, 1000, None);
+    assert!(all.cursor.is_none());
+    assert!(all.holders.iter().all(|h| h.account != identity(3)));
+    assert!(all.holders.windows(2).all(|w| w[0].amount >= w[1].amount));
+}
+
+#[test]
+fn holders_need_the_index() {
+    let h = Setup::new_with_migrations(false, [(0, &TOKEN_MIGRATION)], true);
+    let err = h
+        .module_impl
+        .holders(
+            &Address::anonymous(),
+            HoldersArgs {
+                symbol: *MFX_SYMBOL,
+                count: None,
+                cursor: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.code(),
+        many_ledger::error::holders_index_inactive().code()
+    );
+}
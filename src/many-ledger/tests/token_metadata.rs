@@ -0,0 +1,59 @@
+use many_identity::testing::identity;
+use many_ledger::json::{InitialStateJson, TokenMetadataJson};
+use many_ledger::module::token_metadata::{LedgerTokenMetadataModuleBackend, TokenMetadataArgs};
+use many_ledger::module::LedgerModuleImpl;
+use many_ledger_test_utils::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+fn read_state() -> InitialStateJson {
+    let mut state = InitialStateJson::read("../../staging/ledger_state.json5")
+        .or_else(|_| InitialStateJson::read("staging/ledger_state.json5"))
+        .expect("Could not read initial state.");
+    state.hash = None;
+    state
+}
+
+fn mfx_metadata() -> TokenMetadataJson {
+    TokenMetadataJson {
+        decimals: 9,
+        ticker: "MFX".to_string(),
+        description: Some("Manifest token".to_string()),
+        logo_hash: Some("00ff".to_string()),
+    }
+}
+
+#[test]
+fn token_info() {
+    let store_path = tempfile::tempdir().expect("Could not create a temporary dir.");
+    let mut state = read_state();
+    state.token_metadata = Some(BTreeMap::from([(*MFX_SYMBOL, mfx_metadata())]));
+    let module_impl = LedgerModuleImpl::new(state, None, store_path.path(), false).unwrap();
+
+    let result = module_impl
+        .token_info(&identity(1), TokenMetadataArgs::default())
+        .unwrap();
+    let meta = &result.metadata[&*MFX_SYMBOL];
+    assert_eq!(meta.decimals, 9);
+    assert_eq!(meta.ticker, "MFX");
+    assert_eq!(meta.description.as_deref(), Some("Manifest token"));
+    assert_eq!(meta.logo_hash.as_deref(), Some(&[0u8, 0xff][..]));
+
+    // Filtering on a symbol without metadata returns nothing.
+    let result = module_impl
+        .token_info(
+            &identity(1),
+            TokenMetadataArgs {
+                symbols: Some(BTreeSet::from([identity(1000)])),
+            },
+        )
+        .unwrap();
+    assert!(result.metadata.is_empty());
+}
+
+#[test]
+fn token_info_unknown_symbol() {
+    let store_path = tempfile::tempdir().expect("Could not create a temporary dir.");
+    let mut state = read_state();
+    state.token_metadata = Some(BTreeMap::from([(identity(1000), mfx_metadata())]));
+    assert!(LedgerModuleImpl::new(state, None, store_path.path(), false).is_err());
+}
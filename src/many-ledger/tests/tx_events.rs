@@ -0,0 +1,58 @@
+use many_identity::testing::identity;
+use many_ledger::module::multi_send::{
+    LedgerMultiSendModuleBackend, MultiSendArgs, MultiSendTransfer,
+};
+use many_ledger::module::tx_events::AbciTxEventsModuleBackend;
+use many_ledger_test_utils::*;
+use many_modules::events::EventInfo;
+
+fn setup() -> Setup {
+    let mut harness = Setup::new(true);
+    harness.module_impl = harness.module_impl.with_tx_events(true);
+    let id = harness.id;
+    harness.set_balance(id, 1_000, *MFX_SYMBOL);
+    harness
+}
+
+#[test]
+fn tx_events_of_multi_send() {
+    let mut harness = setup();
+    let id = harness.id;
+    harness.block(|h| {
+        h.module_impl.tx_events().unwrap();
+        h.module_impl
+            .multi_send(
+                &id,
+                MultiSendArgs {
+                    from: Some(id),
+                    transfers: [1, 2]
+                        .into_iter()
+                        .map(|i| MultiSendTransfer {
+                            to: identity(i),
+                            symbol: *MFX_SYMBOL,
+                            amount: 10u64.into(),
+                        })
+                        .collect(),
+                    memo: None,
+                },
+            )
+            .unwrap();
+
+        let events = h.module_impl.tx_events().unwrap().events;
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| matches!(e, EventInfo::Send { .. })));
+
+        // Taken once.
+        assert!(h.module_impl.tx_events().unwrap().events.is_empty());
+    });
+}
+
+#[test]
+fn tx_events_cleared_at_begin_block() {
+    let mut harness = setup();
+    let id = harness.id;
+    harness.block(|h| h.send_(id, identity(1), 10u64));
+    harness.block(|h| {
+        assert!(h.module_impl.tx_events().unwrap().events.is_empty());
+    });
+}
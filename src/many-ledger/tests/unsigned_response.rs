@@ -0,0 +1,38 @@
+use coset::CoseSign1;
+use many_ledger::unsigned_response::ResponseCache;
+
+fn response(signature: u8) -> CoseSign1 {
+    CoseSign1 {
+        signature: vec![signature],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn cache_is_cleared_every_height() {
+    let mut cache = ResponseCache::new(10);
+    assert!(cache.get(1, "ledger.info", &[]).is_none());
+    cache.insert(1, "ledger.info", &[], response(1));
+    assert_eq!(cache.get(1, "ledger.info", &[]), Some(response(1)));
+    assert!(cache.get(1, "ledger.info", &[0]).is_none());
+
+    assert!(cache.get(2, "ledger.info", &[]).is_none());
+    // A response computed at an older height is not cached.
+    cache.insert(1, "ledger.info", &[], response(1));
+    assert!(cache.get(2, "ledger.info", &[]).is_none());
+}
+
+#[test]
+fn cache_is_bounded() {
+    let mut cache = ResponseCache::new(2);
+    assert!(cache.get(1, "a", &[]).is_none());
+    cache.insert(1, "a", &[], response(1));
+    cache.insert(1, "b", &[], response(2));
+    cache.insert(1, "c", &[], response(3));
+    assert!(cache.get(1, "a", &[]).is_none());
+    assert_eq!(cache.get(1, "c", &[]), Some(response(3)));
+
+    let mut cache = ResponseCache::new(0);
+    cache.insert(0, "a", &[], response(1));
+    assert!(cache.get(0, "a", &[]).is_none());
+}
@@ -0,0 +1,102 @@
+use many_identity::testing::identity;
+use many_identity::Address;
+use many_ledger::error;
+use many_ledger::module::validators::{
+    AbciValidatorsModuleBackend, ListArgs, UpdateArgs, ValidatorsModuleBackend,
+};
+use many_ledger::storage::validators::Validator;
+use many_ledger_test_utils::*;
+use std::str::FromStr;
+
+/// The identity of the staging ledger, which manages validators by default.
+fn ledger_identity() -> Address {
+    Address::from_str("mahukzwuwgt3porn6q4vq4xu3mwy5gyskhouryzbscq7wb2iow").unwrap()
+}
+
+fn update(
+    h: &mut Setup,
+    sender: Address,
+    key: u8,
+    power: u64,
+) -> Result<(), many_error::ManyError> {
+    h.module_impl
+        .update(
+            &sender,
+            UpdateArgs {
+                public_key: vec![key; 32].into(),
+                power,
+            },
+        )
+        .map(|_| ())
+}
+
+fn validator(key: u8, power: u64) -> Validator {
+    Validator {
+        public_key: vec![key; 32].into(),
+        power,
+    }
+}
+
+#[test]
+fn updates_are_returned_once() {
+    let mut h = Setup::new(true);
+    let (_, updates) = h.block(|h| {
+        update(h, ledger_identity(), 1, 10).unwrap();
+        update(h, ledger_identity(), 2, 5).unwrap();
+        update(h, ledger_identity(), 1, 20).unwrap();
+        h.module_impl.validator_updates().unwrap().updates
+    });
+    assert_eq!(updates, vec![validator(2, 5), validator(1, 20)]);
+
+    let (_, updates) = h.block(|h| h.module_impl.validator_updates().unwrap().updates);
+    assert!(updates.is_empty());
+
+    let validators = h
+        .module_impl
+        .list(&Address::anonymous(), ListArgs {})
+        .unwrap()
+        .validators;
+    assert_eq!(validators, vec![validator(1, 20), validator(2, 5)]);
+}
+
+#[test]
+fn remove() {
+    let mut h = Setup::new(true);
+    h.block(|h| update(h, ledger_identity(), 1, 10).unwrap());
+    let (_, updates) = h.block(|h| {
+        update(h, ledger_identity(), 1, 0).unwrap();
+        // Validators of the Tendermint genesis can be removed too.
+        update(h, ledger_identity(), 3, 0).unwrap();
+        h.module_impl.validator_updates().unwrap().updates
+    });
+    assert_eq!(updates, vec![validator(1, 0), validator(3, 0)]);
+
+    let validators = h
+        .module_impl
+        .list(&Address::anonymous(), ListArgs {})
+        .unwrap()
+        .validators;
+    assert!(validators.is_empty());
+}
+
+#[test]
+fn unauthorized() {
+    let mut h = Setup::new(true);
+    let (_, r) = h.block(|h| update(h, identity(1), 1, 10));
+    assert_eq!(r.unwrap_err().code(), error::unauthorized().code());
+}
+
+#[test]
+fn invalid_public_key() {
+    let mut h = Setup::new(true);
+    let (_, r) = h.block(|h| {
+        h.module_impl.update(
+            &ledger_identity(),
+            UpdateArgs {
+                public_key: vec![1; 16].into(),
+                power: 10,
+            },
+        )
+    });
+    assert_eq!(r.unwrap_err().code(), error::invalid_validator("").code());
+}
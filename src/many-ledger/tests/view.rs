@@ -0,0 +1,79 @@
+use many_identity::testing::identity;
+use many_identity::{AnonymousIdentity, AnonymousVerifier};
+use many_ledger::view::ReadViews;
+use many_ledger_test_utils::*;
+use many_modules::ledger::{BalanceArgs, LedgerModuleBackend};
+use many_server::ManyServer;
+use many_types::ledger::TokenAmount;
+
+fn open(harness: &Setup, dir: &tempfile::TempDir, keep: usize) -> ReadViews {
+    ReadViews::open(
+        dir.path().join("view"),
+        None,
+        keep,
+        Box::new(|_| ManyServer::simple("view", AnonymousIdentity, AnonymousVerifier, None)),
+        &harness.module_impl,
+    )
+    .unwrap()
+}
+
+fn view_balance(views: &ReadViews, height: Option<u64>, harness: &Setup) -> TokenAmount {
+    views
+        .module_impl(height)
+        .unwrap()
+        .lock()
+        .unwrap()
+        .balance(
+            &harness.id,
+            BalanceArgs {
+                account: None,
+                symbols: Some(vec![*MFX_SYMBOL].into()),
+            },
+        )
+        .unwrap()
+        .balances
+        .get(&*MFX_SYMBOL)
+        .cloned()
+        .unwrap_or_default()
+}
+
+#[test]
+fn refresh() {
+    let mut harness = Setup::new(true);
+    harness.set_balance(harness.id, 1_000_000, *MFX_SYMBOL);
+    harness.block(|_| ());
+
+    let dir = tempfile::tempdir().unwrap();
+    let views = open(&harness, &dir, 1);
+    assert_eq!(view_balance(&views, None, &harness), 1_000_000u32);
+
+    // The view keeps the committed state until it is refreshed.
+    harness.block(|h| h.send_(h.id, identity(1), 250u32));
+    assert_eq!(view_balance(&views, None, &harness), 1_000_000u32);
+
+    views.refresh(&harness.module_impl);
+    assert!(!views.is_outdated());
+    assert_eq!(views.heights().len(), 1);
+    assert_eq!(view_balance(&views, None, &harness), 999_750u32);
+}
+
+#[test]
+fn past_heights() {
+    let mut harness = Setup::new(true);
+    harness.set_balance(harness.id, 1_000_000, *MFX_SYMBOL);
+    harness.block(|_| ());
+
+    let dir = tempfile::tempdir().unwrap();
+    let views = open(&harness, &dir, 2);
+    let (first, _) = harness.block(|h| h.send_(h.id, identity(1), 250u32));
+    views.refresh(&harness.module_impl);
+    let (second, _) = harness.block(|h| h.send_(h.id, identity(1), 250u32));
+    views.refresh(&harness.module_impl);
+
+    // Only the last two heights are kept.
+    assert_eq!(views.heights(), vec![first, second]);
+    assert_eq!(view_balance(&views, Some(first), &harness), 999_750u32);
+    assert_eq!(view_balance(&views, Some(second), &harness), 999_500u32);
+    assert_eq!(view_balance(&views, None, &harness), 999_500u32);
+    assert!(views.module_impl(Some(first - 1)).is_none());
+}
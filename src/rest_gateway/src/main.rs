@@ -0,0 +1,142 @@
+use clap::Parser;
+use many_client::client::blocking::ManyClient;
+use many_identity::{Address, AnonymousIdentity, Identity};
+use std::io::Read;
+use std::net::SocketAddr;
+use tiny_http::{Header, Method, Request, Response};
+use tracing::{debug, warn};
+use tracing_subscriber::filter::LevelFilter;
+
+mod routes;
+
+use routes::{RouteError, RouteResult};
+
+#[derive(clap::ArgEnum, Clone)]
+enum LogStrategy {
+    Terminal,
+    Syslog,
+}
+
+#[derive(Parser)]
+struct Opts {
+    /// Many server URL to connect to. It must implement the ledger attribute.
+    #[clap(default_value = "http://localhost:8000")]
+    server: String,
+
+    /// Port and address to bind to.
+    #[clap(long)]
+    addr: SocketAddr,
+
+    /// The identity of the server (an identity string), or anonymous if you don't know it.
+    server_id: Option<Address>,
+
+    /// Increase output logging verbosity to DEBUG level.
+    #[clap(short, long, parse(from_occurrences))]
+    verbose: i8,
+
+    /// Suppress all output logging. Can be used multiple times to suppress more.
+    #[clap(short, long, parse(from_occurrences))]
+    quiet: i8,
+
+    /// Use given logging strategy
+    #[clap(long, arg_enum, default_value_t = LogStrategy::Terminal)]
+    logmode: LogStrategy,
+}
+
+/// Split the URL of a request into its path segments and query pairs.
+fn parse_url(url: &str) -> (Vec<&str>, Vec<(String, String)>) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let segments = path.split('/').filter(|s| !s.is_empty()).collect();
+    let query = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    (segments, query)
+}
+
+fn route(server: &str, client: &ManyClient<impl Identity>, request: &mut Request) -> RouteResult {
+    let method = request.method().clone();
+    let url = request.url().to_string();
+    let (segments, query) = parse_url(&url);
+
+    match (&method, segments.as_slice()) {
+        (Method::Get, ["info"]) => routes::get_info(client),
+        (Method::Get, ["balances", identity]) => routes::get_balances(client, identity, &query),
+        (Method::Get, ["async", token]) => routes::get_async(client, token),
+        (Method::Post, ["send"]) => {
+            let mut body = Vec::new();
+            request
+                .as_reader()
+                .read_to_end(&mut body)
+                .map_err(|e| RouteError::BadRequest(e.to_string()))?;
+            routes::post_send(server, &body)
+        }
+        _ => Err(RouteError::NotFound),
+    }
+}
+
+fn main() {
+    let Opts {
+        addr,
+        server,
+        server_id,
+        verbose,
+        quiet,
+        logmode,
+    } = Opts::parse();
+
+    let verbose_level = 2 + verbose - quiet;
+    let log_level = match verbose_level {
+        x if x > 3 => LevelFilter::TRACE,
+        3 => LevelFilter::DEBUG,
+        2 => LevelFilter::INFO,
+        1 => LevelFilter::WARN,
+        0 => LevelFilter::ERROR,
+        x if x < 0 => LevelFilter::OFF,
+        _ => unreachable!(),
+    };
+
+    let subscriber = tracing_subscriber::fmt::Subscriber::builder().with_max_level(log_level);
+
+    match logmode {
+        LogStrategy::Terminal => {
+            let subscriber = subscriber.with_writer(std::io::stderr);
+            subscriber.init();
+        }
+        LogStrategy::Syslog => {
+            let identity = std::ffi::CStr::from_bytes_with_nul(b"rest_gateway\0").unwrap();
+            let (options, facility) = Default::default();
+            let syslog = syslog_tracing::Syslog::new(identity, options, facility).unwrap();
+
+            let subscriber = subscriber.with_writer(syslog);
+            subscriber.init();
+        }
+    };
+
+    let server_id = server_id.unwrap_or_default();
+    // Queries are anonymous, commands are signed by the clients.
+    let client = ManyClient::new(server.clone(), server_id, AnonymousIdentity).unwrap();
+    let http = tiny_http::Server::http(addr).unwrap();
+    let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+
+    // TODO: parallelize this.
+    for mut request in http.incoming_requests() {
+        debug!("{} {}", request.method(), request.url());
+        let (status, body) = match route(&server, &client, &mut request) {
+            Ok(x) => x,
+            Err(e) => {
+                if let RouteError::Many(e) = &e {
+                    warn!("{} {}: {e}", request.method(), request.url());
+                }
+                (e.status(), e.to_json())
+            }
+        };
+
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(content_type.clone());
+        // Ignore errors on return.
+        let _ = request.respond(response);
+    }
+}
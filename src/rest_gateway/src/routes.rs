@@ -0,0 +1,201 @@
+use coset::{CborSerializable, CoseSign1};
+use many_client::client::blocking::{block_on, ManyClient};
+use many_error::ManyError;
+use many_identity::verifiers::AnonymousVerifier;
+use many_identity::{Address, Identity};
+use many_identity_dsa::CoseKeyVerifier;
+use many_modules::ledger;
+use many_modules::r#async::{StatusArgs, StatusReturn};
+use many_protocol::{decode_response_from_cose_sign1, RequestMessage, ResponseMessage};
+use many_types::ledger::Symbol;
+use serde_json::{json, Map, Value};
+use std::str::FromStr;
+
+/// The error of a route, mapped to an HTTP status code.
+#[derive(Debug)]
+pub enum RouteError {
+    BadRequest(String),
+    NotFound,
+    Many(ManyError),
+}
+
+impl From<ManyError> for RouteError {
+    fn from(e: ManyError) -> Self {
+        RouteError::Many(e)
+    }
+}
+
+impl RouteError {
+    pub fn status(&self) -> u16 {
+        match self {
+            RouteError::BadRequest(_) => 400,
+            RouteError::NotFound => 404,
+            RouteError::Many(_) => 422,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        match self {
+            RouteError::BadRequest(message) => json!({ "error": { "message": message } }),
+            RouteError::NotFound => json!({ "error": { "message": "Not found." } }),
+            RouteError::Many(e) => json!({
+                "error": { "code": i64::from(e.code()), "message": e.to_string() }
+            }),
+        }
+    }
+}
+
+pub type RouteResult = Result<(u16, Value), RouteError>;
+
+fn bad_request(message: impl ToString) -> RouteError {
+    RouteError::BadRequest(message.to_string())
+}
+
+fn address(value: &str) -> Result<Address, RouteError> {
+    Address::from_str(value).map_err(|_| bad_request(format!("Invalid address '{value}'.")))
+}
+
+fn info(client: &ManyClient<impl Identity>) -> Result<ledger::InfoReturns, RouteError> {
+    Ok(minicbor::decode(&client.call_("ledger.info", ())?)
+        .map_err(ManyError::deserialization_error)?)
+}
+
+/// Resolve a symbol from its address or its local name, e.g. `MFX`.
+fn resolve_symbol(client: &ManyClient<impl Identity>, symbol: &str) -> Result<Symbol, RouteError> {
+    if let Ok(symbol) = Address::from_str(symbol) {
+        return Ok(symbol);
+    }
+    info(client)?
+        .local_names
+        .into_iter()
+        .find(|(_, name)| name == symbol)
+        .map(|(symbol, _)| symbol)
+        .ok_or_else(|| bad_request(format!("Could not resolve symbol '{symbol}'.")))
+}
+
+/// `GET /info`: the symbols of the ledger.
+pub fn get_info(client: &ManyClient<impl Identity>) -> RouteResult {
+    let info = info(client)?;
+    let symbols: Map<String, Value> = info
+        .symbols
+        .iter()
+        .map(|symbol| {
+            let mut value = json!({ "name": info.local_names.get(symbol) });
+            if let Some(summary) = info.tokens.get(symbol) {
+                value["ticker"] = json!(summary.ticker);
+                value["decimals"] = json!(summary.decimals);
+            }
+            (symbol.to_string(), value)
+        })
+        .collect();
+    Ok((200, json!({ "symbols": symbols })))
+}
+
+/// `GET /balances/{identity}?symbol=...`: the balances of an identity, as
+/// strings of the smallest unit. All symbols are returned without `symbol`.
+pub fn get_balances(
+    client: &ManyClient<impl Identity>,
+    identity: &str,
+    query: &[(String, String)],
+) -> RouteResult {
+    let account = address(identity)?;
+    let symbols = query
+        .iter()
+        .filter(|(key, _)| key == "symbol")
+        .map(|(_, symbol)| resolve_symbol(client, symbol))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let payload = client.call_(
+        "ledger.balance",
+        ledger::BalanceArgs {
+            account: Some(account),
+            symbols: if symbols.is_empty() {
+                None
+            } else {
+                Some(symbols.into())
+            },
+        },
+    )?;
+    let balance: ledger::BalanceReturns =
+        minicbor::decode(&payload).map_err(ManyError::deserialization_error)?;
+    let balances: Map<String, Value> = balance
+        .balances
+        .into_iter()
+        .map(|(symbol, amount)| (symbol.to_string(), json!(amount.to_string())))
+        .collect();
+    Ok((200, json!({ "balances": balances })))
+}
+
+/// `POST /send`: forward a `ledger.send` envelope signed by the client, e.g.
+/// with `ledger send --offline FILE`. The body is the CBOR `COSE_Sign1`, sent
+/// to the server unchanged, so the gateway never signs for anyone.
+///
+/// Ledgers behind a blockchain answer with an async token (`202`), to follow
+/// with `GET /async/{token}`.
+pub fn post_send(server: &str, body: &[u8]) -> RouteResult {
+    let envelope = CoseSign1::from_slice(body)
+        .map_err(|_| bad_request("The body must be a signed COSE_Sign1 envelope."))?;
+    let request = envelope
+        .payload
+        .as_deref()
+        .and_then(|payload| RequestMessage::from_bytes(payload).ok())
+        .ok_or_else(|| bad_request("The envelope does not contain a request."))?;
+    if request.method != "ledger.send" {
+        return Err(bad_request(format!(
+            "Expected a ledger.send request, got '{}'.",
+            request.method
+        )));
+    }
+    if request.from.unwrap_or_default().is_anonymous() {
+        return Err(bad_request("The envelope must be signed."));
+    }
+
+    let response = block_on(many_client::client::send_envelope(server, envelope))?;
+    let response =
+        decode_response_from_cose_sign1(&response, None, &(AnonymousVerifier, CoseKeyVerifier))?;
+    async_or_empty(response)
+}
+
+fn async_or_empty(response: ResponseMessage) -> RouteResult {
+    let ResponseMessage {
+        data, attributes, ..
+    } = response;
+    data?;
+    match attributes.get::<many_modules::r#async::attributes::AsyncAttribute>() {
+        Ok(attr) => Ok((202, json!({ "token": hex::encode(&attr.token) }))),
+        Err(_) => Ok((200, json!({}))),
+    }
+}
+
+/// `GET /async/{token}`: the status of a command accepted with an async token.
+pub fn get_async(client: &ManyClient<impl Identity>, token: &str) -> RouteResult {
+    let token = hex::decode(token).map_err(|_| bad_request("Invalid token."))?;
+    let response = client.call_(
+        "async.status",
+        StatusArgs {
+            token: token.into(),
+        },
+    )?;
+    let status: StatusReturn =
+        minicbor::decode(&response).map_err(ManyError::deserialization_error)?;
+
+    match status {
+        StatusReturn::Done { response } => {
+            let response: ResponseMessage =
+                minicbor::decode(&response.payload.ok_or_else(|| {
+                    ManyError::deserialization_error("Empty payload. Expected ResponseMessage.")
+                })?)
+                .map_err(ManyError::deserialization_error)?;
+            match response.data {
+                Ok(_) => Ok((200, json!({ "status": "done" }))),
+                Err(e) => Ok((
+                    200,
+                    json!({ "status": "done", "error": RouteError::Many(e).to_json()["error"] }),
+                )),
+            }
+        }
+        StatusReturn::Expired => Ok((200, json!({ "status": "expired" }))),
+        StatusReturn::Unknown => Err(RouteError::NotFound),
+        _ => Ok((200, json!({ "status": "pending" }))),
+    }
+}
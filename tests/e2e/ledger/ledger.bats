@@ -40,3 +40,15 @@ function teardown() {
     call_ledger --pem=4 --port=8000 send --account="$account_id" "$(identity 4)" 2000 MFX
     assert_output --partial "Sender needs role 'canLedgerTransact' to perform this operation."
 }
+
+@test "$SUITE: ledger exits with a dedicated code on missing roles" {
+    account_id=$(account_create --pem=1 '{ 1: { "'"$(identity 2)"'": ["canLedgerTransact"] }, 2: [0] }')
+    call_ledger --pem=4 --port=8000 send --account="$account_id" "$(identity 4)" 2000 MFX
+    assert_equal "$status" 5
+}
+
+@test "$SUITE: ledger can generate shell completions" {
+    call_ledger --port=8000 completions bash
+    assert_success
+    assert_output --partial "complete -F _ledger"
+}
@@ -77,7 +77,7 @@ function teardown() {
 }
 
 @test "$SUITE: ledger can return balance with token info summary" {
-    call_ledger --port=8000 balance "$(identity 8)"
+    call_ledger --port=8000 balance --raw "$(identity 8)"
     assert_output --partial "${START_BALANCE} MFX ($(subresource 1 1))"
 }
 
@@ -86,12 +86,18 @@ function teardown() {
     assert_output --partial "$(subresource 1 0)"
     assert_output --regexp "total:.*(.*2000,.*)"
     assert_output --regexp "circulating:.*(.*2000,.*)"
-    call_ledger --port=8000 balance "$(identity 1)"
+    call_ledger --port=8000 balance --raw "$(identity 1)"
     assert_output --partial "1000 FBR"
-    call_ledger --port=8000 balance "$(identity 2)"
+    call_ledger --port=8000 balance --raw "$(identity 2)"
     assert_output --partial "1000 FBR"
 }
 
+@test "$SUITE: ledger displays balance using token decimals" {
+    create_token --pem=1 --port=8000 --initial-distribution ''\''{"'$(identity 1)'": 1000}'\'''
+    call_ledger --port=8000 balance "$(identity 1)"
+    assert_output --partial "0.000001 FBR"
+}
+
 @test "$SUITE: token create doesn't overwrite existing subresource" {
     # Create FBR
     create_token --pem=1 --port=8000
@@ -76,7 +76,7 @@ function check_consistency() {
 
     for port in "$@"; do
         # Named parameters that can be empty need to be located after those who can't
-        call_ledger "--port=$port" "$pem_arg" balance "$id"
+        call_ledger "--port=$port" "$pem_arg" balance --raw "$id"
         assert_output --partial "$expected_balance MFX "
     done
 }